@@ -1,9 +1,9 @@
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration as TimeDuration, Instant};
 
-use borsh::BorshSerialize;
-use chrono::Duration;
+use borsh::{BorshDeserialize, BorshSerialize};
 use itertools::Itertools;
 use near_primitives::time::{Clock, Utc};
 use rand::rngs::StdRng;
@@ -11,7 +11,10 @@ use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use tracing::{debug, error, info, warn};
 
+use near_chain_configs::FailedReceiptsConfig;
 use near_chain_primitives::error::{Error, ErrorKind, LogTransientStorageError};
+use near_crypto::PublicKey;
+use near_primitives::account::{AccessKey, AccessKeyPermission};
 use near_primitives::block::{genesis_chunks, Tip};
 use near_primitives::challenge::{
     BlockDoubleSign, Challenge, ChallengeBody, ChallengesResult, ChunkProofs, ChunkState,
@@ -22,7 +25,7 @@ use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{
     combine_hash, merklize, verify_path, Direction, MerklePath, MerklePathItem,
 };
-use near_primitives::receipt::Receipt;
+use near_primitives::receipt::{FailedReceipt, FailedReceiptKind, Receipt, RefundKind};
 use near_primitives::sharding::{
     ChunkHash, ChunkHashHeight, ReceiptList, ReceiptProof, ShardChunk, ShardChunkHeader, ShardInfo,
     ShardProof, StateSyncInfo,
@@ -31,19 +34,23 @@ use near_primitives::syncing::{
     get_num_state_parts, ReceiptProofResponse, RootProof, ShardStateSyncResponseHeader,
     ShardStateSyncResponseHeaderV1, ShardStateSyncResponseHeaderV2, StateHeaderKey, StatePartKey,
 };
-use near_primitives::transaction::ExecutionOutcomeWithIdAndProof;
+use near_primitives::transaction::{Action, ExecutionOutcomeWithIdAndProof, ExecutionStatus};
+use near_primitives::trie_key::TrieKey;
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{
-    AccountId, Balance, BlockExtra, BlockHeight, BlockHeightDelta, EpochId, Gas, MerkleHash,
-    NumBlocks, NumShards, ShardId, StateChangesForSplitStates, StateRoot,
+    AccessKeyMethodUsage, AccountId, Balance, BlockExtra, BlockHeight, BlockHeightDelta,
+    BlockTransactionStats, ContractDeployment, EpochChainStats, EpochId, Gas, MerkleHash,
+    NumBlocks, NumShards, RawStateChangesWithTrieKey, ShardId, StateChangeCause,
+    StateChangesForSplitStates, StateRoot, ValidatorPerformanceSummary,
 };
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::MaybeValidated;
 use near_primitives::views::{
     ExecutionOutcomeWithIdView, ExecutionStatusView, FinalExecutionOutcomeView,
     FinalExecutionOutcomeWithReceiptView, FinalExecutionStatus, LightClientBlockView,
-    SignedTransactionView,
+    ReceiptInclusionProofView, RefundStatsView, SignedTransactionView,
 };
+use near_primitives::wasm_exports::exported_functions;
 use near_store::{ColState, ColStateHeaders, ColStateParts, ShardTries, StoreUpdate};
 
 use near_primitives::state_record::StateRecord;
@@ -55,7 +62,7 @@ use crate::store::{ChainStore, ChainStoreAccess, ChainStoreUpdate, GCMode, Saved
 use crate::types::{
     AcceptedBlock, ApplySplitStateResult, ApplySplitStateResultOrStateChanges,
     ApplyTransactionResult, Block, BlockEconomicsConfig, BlockHeader, BlockHeaderInfo, BlockStatus,
-    ChainGenesis, Provenance, RuntimeAdapter,
+    ChainGenesis, Provenance, RuntimeAdapter, ValidatorInfoIdentifier,
 };
 use crate::validate::{
     validate_challenge, validate_chunk_proofs, validate_chunk_with_chunk_extra,
@@ -66,7 +73,9 @@ use crate::{metrics, DoomslugThresholdMode};
 use actix::Message;
 #[cfg(feature = "delay_detector")]
 use delay_detector::DelayDetector;
-use near_primitives::shard_layout::{account_id_to_shard_uid, ShardLayout, ShardUId};
+use near_primitives::shard_layout::{
+    account_id_to_shard_id, account_id_to_shard_uid, ShardLayout, ShardUId,
+};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 /// Maximum number of orphans chain can store.
@@ -75,8 +84,8 @@ pub const MAX_ORPHAN_SIZE: usize = 1024;
 /// Maximum age of orhpan to store in the chain.
 const MAX_ORPHAN_AGE_SECS: u64 = 300;
 
-/// Refuse blocks more than this many block intervals in the future (as in bitcoin).
-const ACCEPTABLE_TIME_DIFFERENCE: i64 = 12 * 10;
+/// Refuse blocks more than this many block intervals in the future (as in bitcoin), by default.
+pub const DEFAULT_MAX_BLOCK_FUTURE_DRIFT: TimeDuration = TimeDuration::from_secs(12 * 10);
 
 /// Over this block height delta in advance if we are not chunk producer - route tx to upcoming validators.
 pub const TX_ROUTING_HEIGHT_HORIZON: BlockHeightDelta = 4;
@@ -90,6 +99,10 @@ pub const NUM_EPOCHS_TO_KEEP_STORE_DATA: u64 = 5;
 /// Maximum number of height to go through at each step when cleaning forks during garbage collection.
 const GC_FORK_CLEAN_STEP: u64 = 1000;
 
+/// Maximum number of blocks becoming final at once accounted for in the epoch statistics, the
+/// access key usage and the contract history, e.g. after the state sync moved the final head, the older ones are skipped.
+const MAX_NEW_FINAL_BLOCKS: usize = 1000;
+
 /// apply_chunks may be called in two code paths, through process_block or through catchup_blocks
 /// When it is called through process_block, it is possible that the shard state for the next epoch
 /// has not been caught up yet, thus the two modes IsCaughtUp and NotCaughtUp.
@@ -222,6 +235,24 @@ pub struct Chain {
     pub block_economics_config: BlockEconomicsConfig,
     pub doomslug_threshold_mode: DoomslugThresholdMode,
     pending_states_to_patch: Option<Vec<StateRecord>>,
+    /// Blocks with a timestamp further ahead of the local clock are refused.
+    max_block_future_drift: TimeDuration,
+    /// Whether the calls made with function call access keys are recorded.
+    save_access_key_usage: bool,
+    /// Whether the deployments of contract code are recorded.
+    save_contract_history: bool,
+    /// Whether the transaction statistics of every block are recorded.
+    save_block_transaction_stats: bool,
+    /// Whether the statistics of every epoch are recorded.
+    save_epoch_chain_stats: bool,
+    /// Kinds of the receipts which execution failed which are recorded, and for how long.
+    failed_receipts: FailedReceiptsConfig,
+    /// Block before the epoch preceding the epoch of which all the history is garbage collected
+    /// once it's final.
+    pruning_checkpoint: Option<CryptoHash>,
+    /// Whether only the epoch of the head and the previous one are kept, while free disk space
+    /// is low.
+    reduced_retention: bool,
 }
 
 impl Chain {
@@ -264,6 +295,14 @@ impl Chain {
             block_economics_config: BlockEconomicsConfig::from(chain_genesis),
             doomslug_threshold_mode,
             pending_states_to_patch: None,
+            max_block_future_drift: DEFAULT_MAX_BLOCK_FUTURE_DRIFT,
+            save_access_key_usage: false,
+            save_contract_history: false,
+            save_block_transaction_stats: false,
+            save_epoch_chain_stats: false,
+            failed_receipts: FailedReceiptsConfig::default(),
+            pruning_checkpoint: None,
+            reduced_retention: false,
         })
     }
 
@@ -385,9 +424,67 @@ impl Chain {
             block_economics_config: BlockEconomicsConfig::from(chain_genesis),
             doomslug_threshold_mode,
             pending_states_to_patch: None,
+            max_block_future_drift: DEFAULT_MAX_BLOCK_FUTURE_DRIFT,
+            save_access_key_usage: false,
+            save_contract_history: false,
+            save_block_transaction_stats: false,
+            save_epoch_chain_stats: false,
+            failed_receipts: FailedReceiptsConfig::default(),
+            pruning_checkpoint: None,
+            reduced_retention: false,
         })
     }
 
+    /// Blocks with a timestamp further ahead of the local clock are refused. Must stay well
+    /// above the block production delay, or honest producers with a slightly fast clock get
+    /// their blocks refused.
+    pub fn set_max_block_future_drift(&mut self, max_block_future_drift: TimeDuration) {
+        self.max_block_future_drift = max_block_future_drift;
+    }
+
+    /// Records the calls made with function call access keys as their blocks become final, to be
+    /// queried with `ChainStore::get_account_access_key_usage`.
+    pub fn set_save_access_key_usage(&mut self, save_access_key_usage: bool) {
+        self.save_access_key_usage = save_access_key_usage;
+    }
+
+    /// Records the deployments of contract code as their blocks become final, to be queried with
+    /// `ChainStore::get_contract_history`.
+    pub fn set_save_contract_history(&mut self, save_contract_history: bool) {
+        self.save_contract_history = save_contract_history;
+    }
+
+    /// Records the transaction statistics of every block, to be queried with
+    /// `ChainStore::get_block_transaction_stats`.
+    pub fn set_save_block_transaction_stats(&mut self, save_block_transaction_stats: bool) {
+        self.save_block_transaction_stats = save_block_transaction_stats;
+    }
+
+    /// Records the statistics of every epoch as its blocks become final, to be queried with
+    /// `ChainStore::get_all_epoch_chain_stats`.
+    pub fn set_save_epoch_chain_stats(&mut self, save_epoch_chain_stats: bool) {
+        self.save_epoch_chain_stats = save_epoch_chain_stats;
+    }
+
+    /// Kinds of the receipts which execution failed recorded when applying chunks, to be queried
+    /// with `ChainStore::get_failed_receipts`, and the number of epochs they're kept for.
+    pub fn set_failed_receipts(&mut self, failed_receipts: FailedReceiptsConfig) {
+        self.failed_receipts = failed_receipts;
+    }
+
+    /// Garbage collects all the history before the epoch preceding the epoch of the checkpoint
+    /// block once it's final, instead of only the history before the last
+    /// `NUM_EPOCHS_TO_KEEP_STORE_DATA` epochs.
+    pub fn set_pruning_checkpoint(&mut self, pruning_checkpoint: Option<CryptoHash>) {
+        self.pruning_checkpoint = pruning_checkpoint;
+    }
+
+    /// Garbage collects all the history before the epoch preceding the epoch of the head while
+    /// set, instead of only the history before the last `NUM_EPOCHS_TO_KEEP_STORE_DATA` epochs.
+    pub fn set_reduced_retention(&mut self, reduced_retention: bool) {
+        self.reduced_retention = reduced_retention;
+    }
+
     #[cfg(feature = "test_features")]
     pub fn adv_disable_doomslug(&mut self) {
         self.doomslug_threshold_mode = DoomslugThresholdMode::NoApprovals
@@ -579,7 +676,14 @@ impl Chain {
 
         let head = self.store.head()?;
         let tail = self.store.tail()?;
-        let gc_stop_height = self.runtime_adapter.get_gc_stop_height(&head.last_block_hash);
+        let mut gc_stop_height = std::cmp::max(
+            self.runtime_adapter.get_gc_stop_height(&head.last_block_hash),
+            self.pruning_stop_height().unwrap_or_default(),
+        );
+        if self.reduced_retention {
+            let reduced_stop_height = self.prev_epoch_start_height(&head.last_block_hash);
+            gc_stop_height = std::cmp::max(gc_stop_height, reduced_stop_height.unwrap_or_default());
+        }
 
         if gc_stop_height > head.height {
             return Err(ErrorKind::GCError(
@@ -599,6 +703,9 @@ impl Chain {
             chain_store_update.commit()?;
             fork_tail = gc_stop_height;
         }
+        if epoch_change {
+            self.clear_old_failed_receipts(&head)?;
+        }
         let mut gc_blocks_remaining = gc_blocks_limit;
 
         // Forks Cleaning
@@ -652,6 +759,41 @@ impl Chain {
         Ok(())
     }
 
+    /// Deletes the failed receipts recorded before the epochs they're kept for, which are
+    /// usually fewer than those of the other block data.
+    fn clear_old_failed_receipts(&mut self, head: &Tip) -> Result<(), Error> {
+        let epoch_start_height =
+            self.runtime_adapter.get_epoch_start_height(&head.last_block_hash)?;
+        let stop_height = epoch_start_height
+            .saturating_sub(self.failed_receipts.num_epochs_to_keep * self.epoch_length);
+        let mut chain_store_update = self.store.store_update();
+        chain_store_update.clear_failed_receipts_below(stop_height)?;
+        chain_store_update.commit()
+    }
+
+    /// Start of the epoch before the one of the pruning checkpoint, the first block of which is
+    /// the new tail, once the checkpoint is final. The previous epoch is kept, as processing the
+    /// blocks up to the next epoch needs the data of its last block. `None` until then, or once
+    /// it's garbage collected itself when the usual stop height passes it.
+    fn pruning_stop_height(&mut self) -> Option<BlockHeight> {
+        let checkpoint = self.pruning_checkpoint?;
+        let height = self.get_block_header(&checkpoint).ok()?.height();
+        if height > self.store.final_head().ok()?.height
+            || self.store.get_block_hash_by_height(height).ok()? != checkpoint
+        {
+            return None;
+        }
+        self.prev_epoch_start_height(&checkpoint)
+    }
+
+    /// Start of the epoch preceding the epoch of a block of the canonical chain.
+    fn prev_epoch_start_height(&mut self, block_hash: &CryptoHash) -> Option<BlockHeight> {
+        let epoch_start_height = self.runtime_adapter.get_epoch_start_height(block_hash).ok()?;
+        let epoch_first_block = self.store.get_block_hash_by_height(epoch_start_height).ok()?;
+        let prev_epoch_last_block = *self.get_block_header(&epoch_first_block).ok()?.prev_hash();
+        self.runtime_adapter.get_epoch_start_height(&prev_epoch_last_block).ok()
+    }
+
     pub fn clear_forks_data(
         &mut self,
         tries: ShardTries,
@@ -2235,7 +2377,13 @@ impl Chain {
     }
 
     fn chain_update(&mut self) -> ChainUpdate {
-        ChainUpdate::new(
+        let max_block_future_drift = self.max_block_future_drift;
+        let save_access_key_usage = self.save_access_key_usage;
+        let save_contract_history = self.save_contract_history;
+        let save_block_transaction_stats = self.save_block_transaction_stats;
+        let save_epoch_chain_stats = self.save_epoch_chain_stats;
+        let failed_receipt_kinds = self.failed_receipts.kinds.clone();
+        let mut chain_update = ChainUpdate::new(
             &mut self.store,
             self.runtime_adapter.clone(),
             &self.orphans,
@@ -2246,14 +2394,27 @@ impl Chain {
             &self.genesis,
             self.transaction_validity_period,
             self.pending_states_to_patch.take(),
-        )
+        );
+        chain_update.max_block_future_drift = max_block_future_drift;
+        chain_update.save_access_key_usage = save_access_key_usage;
+        chain_update.save_contract_history = save_contract_history;
+        chain_update.save_block_transaction_stats = save_block_transaction_stats;
+        chain_update.save_epoch_chain_stats = save_epoch_chain_stats;
+        chain_update.failed_receipt_kinds = failed_receipt_kinds;
+        chain_update
     }
 
     fn chain_update_from_save_store_update(
         &mut self,
         saved_store_update: SavedStoreUpdate,
     ) -> ChainUpdate {
-        ChainUpdate::new_from_save_store_update(
+        let max_block_future_drift = self.max_block_future_drift;
+        let save_access_key_usage = self.save_access_key_usage;
+        let save_contract_history = self.save_contract_history;
+        let save_block_transaction_stats = self.save_block_transaction_stats;
+        let save_epoch_chain_stats = self.save_epoch_chain_stats;
+        let failed_receipt_kinds = self.failed_receipts.kinds.clone();
+        let mut chain_update = ChainUpdate::new_from_save_store_update(
             &mut self.store,
             saved_store_update,
             self.runtime_adapter.clone(),
@@ -2265,7 +2426,14 @@ impl Chain {
             &self.genesis,
             self.transaction_validity_period,
             self.pending_states_to_patch.take(),
-        )
+        );
+        chain_update.max_block_future_drift = max_block_future_drift;
+        chain_update.save_access_key_usage = save_access_key_usage;
+        chain_update.save_contract_history = save_contract_history;
+        chain_update.save_block_transaction_stats = save_block_transaction_stats;
+        chain_update.save_epoch_chain_stats = save_epoch_chain_stats;
+        chain_update.failed_receipt_kinds = failed_receipt_kinds;
+        chain_update
     }
 
     /// Get node at given position (index, level). If the node does not exist, return `None`.
@@ -2425,6 +2593,54 @@ impl Chain {
         }
         Ok(path)
     }
+
+    /// Get merkle proof that the receipt with id `receipt_id` was sent by one of the chunks of the
+    /// block with hash `block_hash` to a shard which the node tracks.
+    /// Returns `None` if the block doesn't carry the receipt.
+    pub fn get_receipt_inclusion_proof(
+        &mut self,
+        receipt_id: &CryptoHash,
+        block_hash: &CryptoHash,
+    ) -> Result<Option<ReceiptInclusionProofView>, Error> {
+        let block = self.get_block(block_hash)?.clone();
+        let outgoing_receipts_roots = block
+            .chunks()
+            .iter()
+            .map(|chunk| chunk.outgoing_receipts_root())
+            .collect::<Vec<CryptoHash>>();
+        let (chunk_receipts_root, chunk_receipts_proofs) = merklize(&outgoing_receipts_roots);
+        // Only corrupted local data could make them differ, the block was validated when received.
+        if &chunk_receipts_root != block.header().chunk_receipts_root() {
+            return Err(ErrorKind::InvalidChunkReceiptsRoot.into());
+        }
+
+        for to_shard_id in 0..block.chunks().len() as ShardId {
+            let receipt_proofs = match self.store.get_incoming_receipts(block_hash, to_shard_id) {
+                Ok(receipt_proofs) => receipt_proofs.clone(),
+                Err(err) => match err.kind() {
+                    ErrorKind::DBNotFoundErr(_) => continue,
+                    _ => return Err(err),
+                },
+            };
+            for ReceiptProof(receipts, shard_proof) in receipt_proofs {
+                if !receipts.iter().any(|receipt| &receipt.receipt_id == receipt_id) {
+                    continue;
+                }
+                let from_shard_id = shard_proof.from_shard_id;
+                return Ok(Some(ReceiptInclusionProofView {
+                    receipt_id: *receipt_id,
+                    from_shard_id,
+                    to_shard_id,
+                    receipts: receipts.into_iter().map(Into::into).collect(),
+                    outgoing_receipts_root: outgoing_receipts_roots[from_shard_id as usize],
+                    outgoing_receipts_proof: shard_proof.proof,
+                    chunk_receipts_proof: chunk_receipts_proofs[from_shard_id as usize].clone(),
+                    block_header: block.header().clone().into(),
+                }));
+            }
+        }
+        Ok(None)
+    }
 }
 
 /// Various chain getters.
@@ -2766,6 +2982,18 @@ pub struct ChainUpdate<'a> {
     #[allow(unused)]
     transaction_validity_period: BlockHeightDelta,
     states_to_patch: Option<Vec<StateRecord>>,
+    /// Blocks with a timestamp further ahead of the local clock are refused.
+    max_block_future_drift: TimeDuration,
+    /// Whether the calls made with function call access keys are recorded.
+    save_access_key_usage: bool,
+    /// Whether the deployments of contract code are recorded.
+    save_contract_history: bool,
+    /// Whether the transaction statistics of every block are recorded.
+    save_block_transaction_stats: bool,
+    /// Whether the statistics of every epoch are recorded.
+    save_epoch_chain_stats: bool,
+    /// Kinds of the receipts which execution failed which are recorded.
+    failed_receipt_kinds: Vec<FailedReceiptKind>,
 }
 
 pub struct SameHeightResult {
@@ -2872,6 +3100,12 @@ impl<'a> ChainUpdate<'a> {
             genesis,
             transaction_validity_period,
             states_to_patch,
+            max_block_future_drift: DEFAULT_MAX_BLOCK_FUTURE_DRIFT,
+            save_access_key_usage: false,
+            save_contract_history: false,
+            save_block_transaction_stats: false,
+            save_epoch_chain_stats: false,
+            failed_receipt_kinds: vec![],
         }
     }
 
@@ -3329,6 +3563,7 @@ impl<'a> ChainUpdate<'a> {
                     let states_to_patch = self.states_to_patch.take();
 
                     result.push(Box::new(move || -> Result<ApplyChunkResult, Error> {
+                        let apply_start = Clock::instant();
                         match runtime_adapter.apply_transactions(
                             shard_id,
                             chunk_inner.prev_state_root(),
@@ -3351,6 +3586,10 @@ impl<'a> ChainUpdate<'a> {
                             None,
                         ) {
                             Ok(apply_result) => {
+                                crate::gas_throughput::record_chunk_apply(
+                                    apply_result.total_gas_burnt,
+                                    apply_start.elapsed(),
+                                );
                                 let apply_split_result_or_state_changes =
                                     if will_shard_layout_change {
                                         Some(Self::apply_split_state_changes(
@@ -3626,11 +3865,35 @@ impl<'a> ChainUpdate<'a> {
                     ),
                 );
                 self.chain_store_update.save_trie_changes(apply_result.trie_changes);
+                record_refunds(shard_id, &apply_result.outgoing_receipts);
                 self.chain_store_update.save_outgoing_receipt(
                     &block_hash,
                     shard_id,
                     apply_result.outgoing_receipts,
                 );
+                let failed_receipts = apply_result
+                    .outcomes
+                    .iter()
+                    .filter_map(|outcome_with_id| match &outcome_with_id.outcome.status {
+                        ExecutionStatus::Failure(error)
+                            if self
+                                .failed_receipt_kinds
+                                .contains(&FailedReceiptKind::of(error)) =>
+                        {
+                            Some(FailedReceipt {
+                                receipt_id: outcome_with_id.id,
+                                executor_id: outcome_with_id.outcome.executor_id.clone(),
+                                error: error.clone(),
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                self.chain_store_update.save_failed_receipts(
+                    &block_hash,
+                    shard_id,
+                    failed_receipts,
+                )?;
                 // Save receipt and transaction results.
                 self.chain_store_update.save_outcomes_with_proofs(
                     &block_hash,
@@ -3681,6 +3944,306 @@ impl<'a> ChainUpdate<'a> {
         Ok(())
     }
 
+    /// Records the calls of the transactions signed with a function call access key in the blocks
+    /// which became final, found from the changes of their access key when converted to a
+    /// receipt. Only final blocks are, so that the forks never count.
+    fn save_access_key_usage(&mut self, final_headers: &[BlockHeader]) -> Result<(), Error> {
+        let mut usages: HashMap<(AccountId, PublicKey), Vec<AccessKeyMethodUsage>> = HashMap::new();
+        for header in final_headers {
+            let state_changes = self
+                .chain_store_update
+                .get_chain_store()
+                .get_raw_state_changes_in_block(header.hash())?;
+            self.record_access_key_usage(header.height(), &state_changes, &mut usages)?;
+        }
+        self.chain_store_update.save_access_key_usage(usages)
+    }
+
+    fn record_access_key_usage(
+        &mut self,
+        block_height: BlockHeight,
+        state_changes: &[RawStateChangesWithTrieKey],
+        usages: &mut HashMap<(AccountId, PublicKey), Vec<AccessKeyMethodUsage>>,
+    ) -> Result<(), Error> {
+        for changes_with_key in state_changes {
+            let (account_id, public_key) = match &changes_with_key.trie_key {
+                TrieKey::AccessKey { account_id, public_key } => (account_id, public_key),
+                _ => continue,
+            };
+            for change in &changes_with_key.changes {
+                let tx_hash = match change.cause {
+                    StateChangeCause::TransactionProcessing { tx_hash } => tx_hash,
+                    _ => continue,
+                };
+                let is_function_call_key = change
+                    .data
+                    .as_ref()
+                    .and_then(|data| AccessKey::try_from_slice(data).ok())
+                    .map_or(false, |access_key| {
+                        matches!(access_key.permission, AccessKeyPermission::FunctionCall(_))
+                    });
+                if !is_function_call_key {
+                    continue;
+                }
+                // Function call access keys can only sign a single function call.
+                let (receiver_id, method_name) =
+                    match self.chain_store_update.get_transaction(&tx_hash)? {
+                        Some(tx) => match tx.transaction.actions.as_slice() {
+                            [Action::FunctionCall(action)] => {
+                                (tx.transaction.receiver_id.clone(), action.method_name.clone())
+                            }
+                            _ => continue,
+                        },
+                        None => continue,
+                    };
+                let usage = match usages.entry((account_id.clone(), public_key.clone())) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(
+                        self.chain_store_update.get_access_key_usage(account_id, public_key)?,
+                    ),
+                };
+                AccessKeyMethodUsage::record(
+                    usage,
+                    &receiver_id,
+                    &method_name,
+                    block_height,
+                    tx_hash,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the deployments of contract code in the blocks which became final, found from the
+    /// changes of the code of the accounts while processing receipts. Only final blocks are, so
+    /// that the forks never count.
+    fn save_contract_history(&mut self, final_headers: &[BlockHeader]) -> Result<(), Error> {
+        let mut histories: HashMap<AccountId, Vec<ContractDeployment>> = HashMap::new();
+        for header in final_headers {
+            let state_changes = self
+                .chain_store_update
+                .get_chain_store()
+                .get_raw_state_changes_in_block(header.hash())?;
+            self.record_contract_history(header.height(), &state_changes, &mut histories)?;
+        }
+        self.chain_store_update.save_contract_history(histories)
+    }
+
+    fn record_contract_history(
+        &mut self,
+        block_height: BlockHeight,
+        state_changes: &[RawStateChangesWithTrieKey],
+        histories: &mut HashMap<AccountId, Vec<ContractDeployment>>,
+    ) -> Result<(), Error> {
+        for changes_with_key in state_changes {
+            let account_id = match &changes_with_key.trie_key {
+                TrieKey::ContractCode { account_id } => account_id,
+                _ => continue,
+            };
+            let history = match histories.entry(account_id.clone()) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    entry.insert(self.chain_store_update.get_contract_history(account_id)?)
+                }
+            };
+            for change in &changes_with_key.changes {
+                let receipt_id = match change.cause {
+                    StateChangeCause::ReceiptProcessing { receipt_hash } => receipt_hash,
+                    _ => continue,
+                };
+                let deployment = match &change.data {
+                    Some(code) => ContractDeployment {
+                        block_height,
+                        receipt_id,
+                        code_hash: hash(code),
+                        code_size: code.len() as u64,
+                        exported_functions: exported_functions(code),
+                    },
+                    None => ContractDeployment {
+                        block_height,
+                        receipt_id,
+                        code_hash: CryptoHash::default(),
+                        code_size: 0,
+                        exported_functions: None,
+                    },
+                };
+                ContractDeployment::record(history, deployment);
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves statistics of the transactions included in the new chunks of the block.
+    /// Only chunks available locally, i.e. of the shards we track, are accounted for.
+    fn save_block_transaction_stats(&mut self, block: &Block) -> Result<(), Error> {
+        let shard_layout = self.runtime_adapter.get_shard_layout(block.header().epoch_id())?;
+        let mut stats = BlockTransactionStats::default();
+        for chunk_header in block.chunks().iter() {
+            if chunk_header.height_included() != block.header().height() {
+                continue;
+            }
+            let chunk = match self.chain_store_update.get_chunk_clone_from_header(chunk_header) {
+                Ok(chunk) => chunk,
+                Err(_) => continue,
+            };
+            let shard_id = chunk_header.shard_id();
+            stats.shards.push(shard_id);
+            for tx in chunk.transactions() {
+                stats.add_transaction(tx.get_size(), &tx.transaction.actions);
+                let receiver_id = &tx.transaction.receiver_id;
+                if &tx.transaction.signer_id == receiver_id {
+                    stats.local_receipts += 1;
+                } else if account_id_to_shard_id(receiver_id, &shard_layout) == shard_id {
+                    stats.same_shard_receipts += 1;
+                } else {
+                    stats.cross_shard_receipts += 1;
+                }
+            }
+        }
+        self.chain_store_update.save_block_transaction_stats(block.hash(), &stats)
+    }
+
+    /// Headers of the blocks which became final since `prev_final_head`, oldest first.
+    fn new_final_headers(
+        &mut self,
+        prev_final_head: &Tip,
+        final_header: &BlockHeader,
+    ) -> Vec<BlockHeader> {
+        let mut headers = vec![];
+        let mut header = final_header.clone();
+        while header.height() > prev_final_head.height && headers.len() < MAX_NEW_FINAL_BLOCKS {
+            let prev_hash = *header.prev_hash();
+            headers.push(header);
+            header = match self.chain_store_update.get_block_header(&prev_hash) {
+                Ok(prev_header) => prev_header.clone(),
+                Err(_) => break,
+            };
+        }
+        headers.reverse();
+        headers
+    }
+
+    /// Accounts for the blocks which became final in the statistics of their epochs. Only final
+    /// blocks are, so that the forks never count.
+    fn save_epoch_chain_stats(&mut self, final_headers: &[BlockHeader]) -> Result<(), Error> {
+        // The statistics updated, and the signers seen for the first time in their epoch.
+        let mut epochs: HashMap<EpochId, (EpochChainStats, HashSet<AccountId>)> = HashMap::new();
+        for header in final_headers {
+            let block = match self.chain_store_update.get_block(header.hash()) {
+                Ok(block) => block.clone(),
+                Err(_) => continue,
+            };
+            let epoch_id = header.epoch_id().clone();
+            if let Ok(prev_header) = self.chain_store_update.get_block_header(header.prev_hash()) {
+                let prev_epoch_id = prev_header.epoch_id().clone();
+                if prev_epoch_id != epoch_id {
+                    self.complete_epoch_chain_stats(&prev_epoch_id, &mut epochs)?;
+                }
+            }
+            if !epochs.contains_key(&epoch_id) {
+                let stats = self
+                    .chain_store_update
+                    .get_epoch_chain_stats(&epoch_id)?
+                    .unwrap_or_else(|| EpochChainStats {
+                        first_block_height: header.height(),
+                        ..Default::default()
+                    });
+                epochs.insert(epoch_id.clone(), (stats, HashSet::new()));
+            }
+            let (stats, new_signers) = epochs.get_mut(&epoch_id).unwrap();
+            stats.last_block_height = header.height();
+            stats.num_blocks += 1;
+            for chunk_header in block.chunks().iter() {
+                if chunk_header.height_included() != header.height() {
+                    continue;
+                }
+                let shard_id = chunk_header.shard_id();
+                *stats.gas_used.entry(shard_id).or_default() += chunk_header.gas_used();
+                let chunk = match self.chain_store_update.get_chunk_clone_from_header(chunk_header)
+                {
+                    Ok(chunk) => chunk,
+                    Err(_) => continue,
+                };
+                if !stats.shards.contains(&shard_id) {
+                    stats.shards.push(shard_id);
+                    stats.shards.sort();
+                }
+                for tx in chunk.transactions() {
+                    stats.num_transactions += 1;
+                    for action in &tx.transaction.actions {
+                        match action {
+                            Action::CreateAccount(_) => stats.new_accounts += 1,
+                            Action::DeployContract(_) => stats.contract_deploys += 1,
+                            _ => {}
+                        }
+                    }
+                    let signer_id = &tx.transaction.signer_id;
+                    if !new_signers.contains(signer_id)
+                        && !self.chain_store_update.is_epoch_signer(&epoch_id, signer_id)?
+                    {
+                        stats.unique_signers += 1;
+                        new_signers.insert(signer_id.clone());
+                    }
+                }
+            }
+        }
+
+        for (epoch_id, (stats, new_signers)) in epochs {
+            self.chain_store_update.save_epoch_chain_stats(&epoch_id, &stats)?;
+            if !stats.complete {
+                self.chain_store_update.save_epoch_signers(&epoch_id, new_signers.iter());
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the statistics of the epoch complete, once its last block is final, with the
+    /// performance of its validators. The signers, only kept to count them, are deleted.
+    fn complete_epoch_chain_stats(
+        &mut self,
+        epoch_id: &EpochId,
+        epochs: &mut HashMap<EpochId, (EpochChainStats, HashSet<AccountId>)>,
+    ) -> Result<(), Error> {
+        let mut stats = match epochs.remove(epoch_id) {
+            Some((stats, _)) => stats,
+            None => match self.chain_store_update.get_epoch_chain_stats(epoch_id)? {
+                Some(stats) => stats,
+                // The node started syncing in a later epoch.
+                None => return Ok(()),
+            },
+        };
+        stats.complete = true;
+        match self
+            .runtime_adapter
+            .get_validator_info(ValidatorInfoIdentifier::EpochId(epoch_id.clone()))
+        {
+            Ok(info) => {
+                let validators = &info.current_validators;
+                stats.validators = Some(ValidatorPerformanceSummary {
+                    epoch_height: info.epoch_height,
+                    num_validators: validators.len() as u64,
+                    total_stake: validators.iter().map(|validator| validator.stake).sum(),
+                    num_produced_blocks: validators
+                        .iter()
+                        .map(|validator| validator.num_produced_blocks)
+                        .sum(),
+                    num_expected_blocks: validators
+                        .iter()
+                        .map(|validator| validator.num_expected_blocks)
+                        .sum(),
+                    num_slashed: validators.iter().filter(|validator| validator.is_slashed).count()
+                        as u64,
+                });
+            }
+            Err(err) => {
+                warn!(target: "chain", "Failed to get the validators of epoch {:?}: {}", epoch_id, err)
+            }
+        }
+        self.chain_store_update.delete_epoch_signers(epoch_id);
+        epochs.insert(epoch_id.clone(), (stats, HashSet::new()));
+        Ok(())
+    }
+
     /// Runs the block processing, including validation and finding a place for the new block in the chain.
     /// Returns new head if chain head updated, as well as a boolean indicating if we need to start
     ///    fetching state for the next epoch.
@@ -3884,6 +4447,9 @@ impl<'a> ChainUpdate<'a> {
 
         // Add validated block to the db, even if it's not the canonical fork.
         self.chain_store_update.save_block(block.clone().into_inner());
+        if self.save_block_transaction_stats {
+            self.save_block_transaction_stats(block)?;
+        }
         self.chain_store_update.inc_block_refcount(block.header().prev_hash())?;
 
         // Update the chain head if it's the new tip
@@ -3967,7 +4533,8 @@ impl<'a> ChainUpdate<'a> {
         F: FnMut(ChallengeBody) -> (),
     {
         // Refuse blocks from the too distant future.
-        if header.timestamp() > Utc::now() + Duration::seconds(ACCEPTABLE_TIME_DIFFERENCE) {
+        let future_drift = (header.timestamp() - Utc::now()).to_std();
+        if future_drift.map_or(false, |drift| drift > self.max_block_future_drift) {
             return Err(ErrorKind::InvalidBlockFutureTime(header.timestamp()).into());
         }
 
@@ -4168,8 +4735,28 @@ impl<'a> ChainUpdate<'a> {
                 },
             };
         if last_final_block_header.height() > final_head.height {
-            let tip = Tip::from_header(last_final_block_header);
+            let last_final_block_header = last_final_block_header.clone();
+            let tip = Tip::from_header(&last_final_block_header);
             self.chain_store_update.save_final_head(&tip)?;
+            let final_headers = self.new_final_headers(&final_head, &last_final_block_header);
+            if self.save_epoch_chain_stats {
+                // The statistics are informational, they must not stop the chain.
+                if let Err(err) = self.save_epoch_chain_stats(&final_headers) {
+                    error!(target: "chain", "Failed to save the epoch chain statistics: {}", err);
+                }
+            }
+            if self.save_access_key_usage {
+                // The usage is only recorded for the RPC, it must not stop the chain.
+                if let Err(err) = self.save_access_key_usage(&final_headers) {
+                    warn!(target: "chain", "Failed to save the access key usage: {}", err);
+                }
+            }
+            if self.save_contract_history {
+                // The history is only recorded for the RPC, it must not stop the chain.
+                if let Err(err) = self.save_contract_history(&final_headers) {
+                    warn!(target: "chain", "Failed to save the contract history: {}", err);
+                }
+            }
             Ok(Some(tip))
         } else {
             Ok(None)
@@ -4542,6 +5129,25 @@ pub fn collect_receipts_from_response(
     )
 }
 
+/// Exports the refunds among the outgoing receipts of an applied chunk to the metrics.
+fn record_refunds(shard_id: ShardId, outgoing_receipts: &[Receipt]) {
+    let stats = RefundStatsView::new(shard_id, outgoing_receipts);
+    let shard_id = shard_id.to_string();
+    for (kind, receipts, amount) in [
+        (RefundKind::Gas, stats.gas_refund_receipts, stats.gas_refund_amount),
+        (RefundKind::Deposit, stats.deposit_refund_receipts, stats.deposit_refund_amount),
+    ] {
+        if receipts == 0 {
+            continue;
+        }
+        let labels = [shard_id.as_str(), kind.as_str()];
+        metrics::REFUND_RECEIPTS_TOTAL.with_label_values(&labels).inc_by(receipts);
+        metrics::REFUND_AMOUNT_MICRONEAR_TOTAL
+            .with_label_values(&labels)
+            .inc_by((amount / 10u128.pow(18)) as u64);
+    }
+}
+
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct ApplyStatePartsRequest {
@@ -0,0 +1,112 @@
+//! Estimation of the gas throughput this node's hardware can sustain.
+//!
+//! Every applied chunk contributes a sample of the gas it burnt and the wall-clock time its
+//! application took. The estimate is a low percentile of the observed gas per second, so that
+//! it reflects the slow chunks rather than the average ones. It is advisory only and is meant
+//! as input for recalibrating the costs of the protocol.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+
+use near_primitives::types::Gas;
+use near_primitives::views::GasThroughputView;
+
+use crate::metrics;
+
+/// Number of most recent chunks the estimate is computed from.
+const MAX_SAMPLES: usize = 1000;
+/// Chunks burning less gas are dominated by fixed overheads and are not sampled.
+const MIN_SAMPLE_GAS: Gas = 10u64.pow(12);
+/// Percentile of the observed gas per second used as the safe estimate.
+const SAFE_PERCENTILE: f64 = 0.1;
+
+/// Estimate shared by all the chains of the process, the client records the chunks it applies
+/// and the view client serves the result.
+pub static GAS_THROUGHPUT: Lazy<Mutex<GasThroughputEstimator>> =
+    Lazy::new(|| Mutex::new(GasThroughputEstimator::default()));
+
+/// Records the application of a chunk in the process-wide estimate.
+pub(crate) fn record_chunk_apply(gas_burnt: Gas, apply_time: Duration) {
+    let mut estimator = GAS_THROUGHPUT.lock().expect("gas throughput lock poisoned");
+    if estimator.record(gas_burnt, apply_time) {
+        let view = estimator.view();
+        metrics::GAS_THROUGHPUT_SAFE.set(view.safe_gas_per_second.unwrap_or(0) as i64);
+        metrics::GAS_THROUGHPUT_AVG.set(view.avg_gas_per_second.unwrap_or(0) as i64);
+    }
+}
+
+#[derive(Default)]
+pub struct GasThroughputEstimator {
+    /// Gas per second of the most recent sampled chunks.
+    samples: VecDeque<f64>,
+}
+
+impl GasThroughputEstimator {
+    /// Adds a sample, returns whether it was kept.
+    pub fn record(&mut self, gas_burnt: Gas, apply_time: Duration) -> bool {
+        let seconds = apply_time.as_secs_f64();
+        if gas_burnt < MIN_SAMPLE_GAS || seconds <= 0.0 {
+            return false;
+        }
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(gas_burnt as f64 / seconds);
+        true
+    }
+
+    pub fn view(&self) -> GasThroughputView {
+        let num_samples = self.samples.len() as u64;
+        if self.samples.is_empty() {
+            return GasThroughputView {
+                num_samples,
+                avg_gas_per_second: None,
+                safe_gas_per_second: None,
+            };
+        }
+        let mut sorted: Vec<f64> = self.samples.iter().cloned().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let index = ((sorted.len() - 1) as f64 * SAFE_PERCENTILE) as usize;
+        let avg = sorted.iter().sum::<f64>() / sorted.len() as f64;
+        GasThroughputView {
+            num_samples,
+            avg_gas_per_second: Some(avg as Gas),
+            safe_gas_per_second: Some(sorted[index] as Gas),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_throughput_estimate() {
+        let mut estimator = GasThroughputEstimator::default();
+        assert_eq!(estimator.view().safe_gas_per_second, None);
+        assert!(!estimator.record(MIN_SAMPLE_GAS - 1, Duration::from_millis(1)));
+        // 10 slow chunks at 1 Tgas/s and 90 fast ones at 10 Tgas/s.
+        for _ in 0..10 {
+            assert!(estimator.record(10u64.pow(12), Duration::from_secs(1)));
+        }
+        for _ in 0..90 {
+            assert!(estimator.record(10u64.pow(13), Duration::from_secs(1)));
+        }
+        let view = estimator.view();
+        assert_eq!(view.num_samples, 100);
+        assert_eq!(view.safe_gas_per_second, Some(10u64.pow(12)));
+        assert_eq!(view.avg_gas_per_second, Some(91 * 10u64.pow(11)));
+    }
+
+    #[test]
+    fn test_gas_throughput_window() {
+        let mut estimator = GasThroughputEstimator::default();
+        for _ in 0..MAX_SAMPLES + 10 {
+            estimator.record(10u64.pow(12), Duration::from_secs(1));
+        }
+        assert_eq!(estimator.view().num_samples, MAX_SAMPLES as u64);
+    }
+}
@@ -9,6 +9,7 @@ pub use types::{Block, BlockHeader, BlockStatus, ChainGenesis, Provenance, Runti
 
 pub mod chain;
 mod doomslug;
+pub mod gas_throughput;
 mod lightclient;
 mod metrics;
 pub mod migrations;
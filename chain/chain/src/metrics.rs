@@ -1,6 +1,6 @@
 use near_metrics::{
-    try_create_histogram, try_create_int_counter, try_create_int_gauge, Histogram, IntCounter,
-    IntGauge,
+    try_create_histogram, try_create_int_counter, try_create_int_counter_vec, try_create_int_gauge,
+    Histogram, IntCounter, IntCounterVec, IntGauge,
 };
 use once_cell::sync::Lazy;
 
@@ -38,3 +38,33 @@ pub static VALIDATOR_ACTIVE_TOTAL: Lazy<IntGauge> = Lazy::new(|| {
 });
 pub static NUM_ORPHANS: Lazy<IntGauge> =
     Lazy::new(|| try_create_int_gauge("near_num_orphans", "Number of orphan blocks.").unwrap());
+pub static GAS_THROUGHPUT_SAFE: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_gas_throughput_safe",
+        "Gas per second this node applies chunks at, in the slowest 10% of recent chunks",
+    )
+    .unwrap()
+});
+pub static GAS_THROUGHPUT_AVG: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_gas_throughput_avg",
+        "Average gas per second this node applies recent chunks at",
+    )
+    .unwrap()
+});
+pub static REFUND_RECEIPTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_refund_receipts_total",
+        "Number of refund receipts sent by the chunks applied, per shard and kind of refund",
+        &["shard_id", "kind"],
+    )
+    .unwrap()
+});
+pub static REFUND_AMOUNT_MICRONEAR_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_refund_amount_micronear_total",
+        "Amount refunded by the chunks applied in microNEAR, per shard and kind of refund",
+        &["shard_id", "kind"],
+    )
+    .unwrap()
+});
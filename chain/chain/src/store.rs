@@ -8,11 +8,12 @@ use cached::{Cached, SizedCache};
 use near_primitives::time::Utc;
 
 use near_chain_primitives::error::{Error, ErrorKind};
+use near_crypto::PublicKey;
 use near_primitives::block::{Approval, Tip};
 use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
-use near_primitives::receipt::Receipt;
+use near_primitives::receipt::{FailedReceipt, Receipt};
 use near_primitives::shard_layout::{account_id_to_shard_id, get_block_shard_uid, ShardUId};
 use near_primitives::sharding::{
     ChunkHash, EncodedShardChunk, PartialEncodedChunk, ReceiptProof, ShardChunk, ShardChunkHeader,
@@ -28,11 +29,14 @@ use near_primitives::transaction::{
 use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::chunk_extra::ChunkExtra;
 use near_primitives::types::{
-    AccountId, BlockExtra, BlockHeight, EpochId, GCCount, NumBlocks, ShardId, StateChanges,
-    StateChangesExt, StateChangesForSplitStates, StateChangesKinds, StateChangesKindsExt,
-    StateChangesRequest,
+    AccessKeyMethodUsage, AccountId, BlockExtra, BlockHeight, BlockTransactionStats,
+    ContractDeployment, EpochChainStats, EpochId, GCCount, NumBlocks, RawStateChangesWithTrieKey,
+    ShardId, StateChanges, StateChangesExt, StateChangesForSplitStates, StateChangesKinds,
+    StateChangesKindsExt, StateChangesRequest,
+};
+use near_primitives::utils::{
+    get_block_shard_id, get_block_shard_id_rev, index_to_bytes, to_timestamp,
 };
-use near_primitives::utils::{get_block_shard_id, index_to_bytes, to_timestamp};
 use near_primitives::views::LightClientBlockView;
 use near_store::{
     read_with_cache, ColBlock, ColBlockExtra, ColBlockHeader, ColBlockHeight, ColBlockInfo,
@@ -50,7 +54,10 @@ use near_store::{
 
 use crate::types::{Block, BlockHeader, LatestKnown};
 use crate::{byzantine_assert, RuntimeAdapter};
-use near_store::db::DBCol::ColStateChangesForSplitStates;
+use near_store::db::DBCol::{
+    ColAccessKeyUsage, ColBlockTransactionStats, ColContractHistory, ColEpochChainStats,
+    ColEpochSigners, ColFailedReceipts, ColStateChangesForSplitStates,
+};
 
 /// lru cache size
 #[cfg(not(feature = "no_cache"))]
@@ -70,6 +77,22 @@ pub enum GCMode {
     StateSync { clear_block_info: bool },
 }
 
+/// Key of a signer of the transactions of the epoch, epoch ids have a fixed size.
+fn get_epoch_signer_key(epoch_id: &EpochId, account_id: &AccountId) -> Vec<u8> {
+    [epoch_id.as_ref(), account_id.as_ref().as_bytes()].concat()
+}
+
+/// Key of the usage of an access key, or without public key the prefix of the ones of the
+/// account. Account ids can't contain the separator, so the prefix doesn't match other accounts.
+fn get_access_key_usage_key(account_id: &AccountId, public_key: Option<&PublicKey>) -> Vec<u8> {
+    let mut key = account_id.as_ref().as_bytes().to_vec();
+    key.push(b',');
+    if let Some(public_key) = public_key {
+        key.extend(public_key.try_to_vec().expect("Failed to serialize"));
+    }
+    key
+}
+
 fn get_height_shard_id(height: BlockHeight, shard_id: ShardId) -> Vec<u8> {
     let mut res = Vec::with_capacity(40);
     res.extend_from_slice(&height.to_le_bytes());
@@ -422,6 +445,72 @@ impl ChainStore {
         )
     }
 
+    /// Returns statistics of the transactions included in the block with the given hash.
+    pub fn get_block_transaction_stats(
+        &self,
+        block_hash: &CryptoHash,
+    ) -> Result<BlockTransactionStats, Error> {
+        option_to_not_found(
+            self.store
+                .get_ser::<BlockTransactionStats>(ColBlockTransactionStats, block_hash.as_ref()),
+            &format!("BLOCK TRANSACTION STATS: {}", block_hash),
+        )
+    }
+
+    /// Returns the receipts which execution failed in the chunk of shard `shard_id` applied in
+    /// the block with the given hash. Empty if none failed or the shard isn't tracked.
+    pub fn get_failed_receipts(
+        &self,
+        block_hash: &CryptoHash,
+        shard_id: ShardId,
+    ) -> Result<Vec<FailedReceipt>, Error> {
+        Ok(self
+            .store
+            .get_ser::<Vec<FailedReceipt>>(
+                ColFailedReceipts,
+                &get_block_shard_id(block_hash, shard_id),
+            )?
+            .unwrap_or_default())
+    }
+
+    /// Returns the calls made with each function call access key of the account which were
+    /// recorded, if the node saves the usage of access keys.
+    pub fn get_account_access_key_usage(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Vec<(PublicKey, Vec<AccessKeyMethodUsage>)>, Error> {
+        let prefix = get_access_key_usage_key(account_id, None);
+        self.store
+            .iter_prefix(ColAccessKeyUsage, &prefix)
+            .map(|(key, value)| -> Result<_, Error> {
+                let public_key = PublicKey::try_from_slice(&key[prefix.len()..])?;
+                Ok((public_key, Vec::<AccessKeyMethodUsage>::try_from_slice(&value)?))
+            })
+            .collect()
+    }
+
+    /// Returns the deployments of contract code on the account, oldest first, if the node saves
+    /// the history of contracts.
+    pub fn get_contract_history(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Vec<ContractDeployment>, Error> {
+        Ok(self
+            .store
+            .get_ser(ColContractHistory, account_id.as_ref().as_bytes())?
+            .unwrap_or_default())
+    }
+
+    /// Returns the statistics of every epoch, in no particular order.
+    pub fn get_all_epoch_chain_stats(&self) -> Result<Vec<(EpochId, EpochChainStats)>, Error> {
+        self.store
+            .iter(ColEpochChainStats)
+            .map(|(key, value)| -> Result<_, Error> {
+                Ok((EpochId::try_from_slice(&key)?, EpochChainStats::try_from_slice(&value)?))
+            })
+            .collect()
+    }
+
     /// Get outgoing receipts that will be *sent* from shard `shard_id` from block whose prev block
     /// is `prev_block_hash`
     /// Note that the meaning of outgoing receipts here are slightly different from
@@ -669,6 +758,16 @@ impl ChainStore {
         Ok(StateChanges::from_changes(&mut block_changes)?)
     }
 
+    /// Retrieve the changes of the accounts, access keys and contracts in a given block, with
+    /// their causes, as they were saved when applying its chunks.
+    pub fn get_raw_state_changes_in_block(
+        &self,
+        block_hash: &CryptoHash,
+    ) -> Result<Vec<RawStateChangesWithTrieKey>, Error> {
+        let storage_key = KeyForStateChanges::get_prefix(&block_hash);
+        Ok(storage_key.find_iter(&self.store).collect::<Result<Vec<_>, _>>()?)
+    }
+
     /// Retrieve the key-value changes from the store and decode them appropriately.
     ///
     /// We store different types of data, so we need to take care of all the types. That is, the
@@ -1138,6 +1237,35 @@ struct ChainStoreCacheUpdate {
     processed_block_heights: HashSet<BlockHeight>,
 }
 
+impl ChainStoreCacheUpdate {
+    /// Lower bound of the number of store operations needed to persist the update, so that
+    /// the store update of a block is allocated once.
+    fn num_store_ops(&self) -> usize {
+        let chunk_ops: usize = self
+            .chunks
+            .values()
+            .map(|chunk| 2 + chunk.transactions().len() + chunk.receipts().len())
+            .sum();
+        2 * self.blocks.len()
+            + 2 * self.headers.len()
+            + chunk_ops
+            + self.block_extras.len()
+            + self.chunk_extras.len()
+            + self.partial_chunks.len()
+            + self.chunk_hash_per_height_shard.len()
+            + self.height_to_hashes.len()
+            + self.next_block_hashes.len()
+            + self.outgoing_receipts.len()
+            + self.incoming_receipts.len()
+            + self.outcomes.len()
+            + self.outcome_ids.len()
+            + self.receipt_id_to_shard_id.len()
+            + self.block_refcounts.len()
+            + self.block_merkle_tree.len()
+            + self.block_ordinal_to_hash.len()
+    }
+}
+
 pub struct ChainStoreUpdateImpl<T> {
     chain_store: T,
     store_updates: Vec<StoreUpdate>,
@@ -1714,6 +1842,157 @@ impl<'a> ChainStoreUpdate<'a> {
         self.chain_store_cache_update.block_extras.insert(*block_hash, block_extra);
     }
 
+    /// Save statistics of the transactions included in the block.
+    pub fn save_block_transaction_stats(
+        &mut self,
+        block_hash: &CryptoHash,
+        stats: &BlockTransactionStats,
+    ) -> Result<(), Error> {
+        let mut store_update = self.store().store_update();
+        store_update.set_ser(ColBlockTransactionStats, block_hash.as_ref(), stats)?;
+        self.merge(store_update);
+        Ok(())
+    }
+
+    /// Save receipts which execution failed when applying the chunk of shard `shard_id`.
+    pub fn save_failed_receipts(
+        &mut self,
+        block_hash: &CryptoHash,
+        shard_id: ShardId,
+        failed_receipts: Vec<FailedReceipt>,
+    ) -> Result<(), Error> {
+        if failed_receipts.is_empty() {
+            return Ok(());
+        }
+        let mut store_update = self.store().store_update();
+        store_update.set_ser(
+            ColFailedReceipts,
+            &get_block_shard_id(block_hash, shard_id),
+            &failed_receipts,
+        )?;
+        self.merge(store_update);
+        Ok(())
+    }
+
+    /// Deletes the receipts which execution failed in the blocks below `height`, those of blocks
+    /// which are already gone included.
+    pub fn clear_failed_receipts_below(&mut self, height: BlockHeight) -> Result<(), Error> {
+        let keys = self.store().iter(ColFailedReceipts).map(|(key, _)| key).collect::<Vec<_>>();
+        for key in keys {
+            let (block_hash, _) =
+                get_block_shard_id_rev(&key).map_err(|err| ErrorKind::Other(err.to_string()))?;
+            let below = match self.get_block_header(&block_hash) {
+                Ok(header) => header.height() < height,
+                Err(_) => true,
+            };
+            if below {
+                self.gc_col(ColFailedReceipts, &key.to_vec());
+            }
+        }
+        Ok(())
+    }
+
+    /// Usage of a function call access key, empty if none was recorded yet.
+    pub fn get_access_key_usage(
+        &self,
+        account_id: &AccountId,
+        public_key: &PublicKey,
+    ) -> Result<Vec<AccessKeyMethodUsage>, Error> {
+        Ok(self
+            .store()
+            .get_ser(ColAccessKeyUsage, &get_access_key_usage_key(account_id, Some(public_key)))?
+            .unwrap_or_default())
+    }
+
+    /// Saves the usage of several keys at once, none of them if any fails.
+    pub fn save_access_key_usage(
+        &mut self,
+        usages: HashMap<(AccountId, PublicKey), Vec<AccessKeyMethodUsage>>,
+    ) -> Result<(), Error> {
+        let mut store_update = self.store().store_update();
+        for ((account_id, public_key), usage) in usages {
+            store_update.set_ser(
+                ColAccessKeyUsage,
+                &get_access_key_usage_key(&account_id, Some(&public_key)),
+                &usage,
+            )?;
+        }
+        self.merge(store_update);
+        Ok(())
+    }
+
+    /// History of contracts of the account, empty if nothing was recorded yet.
+    pub fn get_contract_history(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Vec<ContractDeployment>, Error> {
+        Ok(self
+            .store()
+            .get_ser(ColContractHistory, account_id.as_ref().as_bytes())?
+            .unwrap_or_default())
+    }
+
+    /// Saves the history of several accounts at once, none of them if any fails.
+    pub fn save_contract_history(
+        &mut self,
+        histories: HashMap<AccountId, Vec<ContractDeployment>>,
+    ) -> Result<(), Error> {
+        let mut store_update = self.store().store_update();
+        for (account_id, history) in histories {
+            store_update.set_ser(ColContractHistory, account_id.as_ref().as_bytes(), &history)?;
+        }
+        self.merge(store_update);
+        Ok(())
+    }
+
+    pub fn get_epoch_chain_stats(
+        &self,
+        epoch_id: &EpochId,
+    ) -> Result<Option<EpochChainStats>, Error> {
+        Ok(self.store().get_ser(ColEpochChainStats, epoch_id.as_ref())?)
+    }
+
+    pub fn save_epoch_chain_stats(
+        &mut self,
+        epoch_id: &EpochId,
+        stats: &EpochChainStats,
+    ) -> Result<(), Error> {
+        let mut store_update = self.store().store_update();
+        store_update.set_ser(ColEpochChainStats, epoch_id.as_ref(), stats)?;
+        self.merge(store_update);
+        Ok(())
+    }
+
+    /// Whether the account signed a transaction of a final block of the epoch already saved.
+    pub fn is_epoch_signer(
+        &self,
+        epoch_id: &EpochId,
+        account_id: &AccountId,
+    ) -> Result<bool, Error> {
+        Ok(self.store().exists(ColEpochSigners, &get_epoch_signer_key(epoch_id, account_id))?)
+    }
+
+    pub fn save_epoch_signers<'b>(
+        &mut self,
+        epoch_id: &EpochId,
+        signers: impl Iterator<Item = &'b AccountId>,
+    ) {
+        let mut store_update = self.store().store_update();
+        for account_id in signers {
+            store_update.set(ColEpochSigners, &get_epoch_signer_key(epoch_id, account_id), &[]);
+        }
+        self.merge(store_update);
+    }
+
+    /// Deletes the signers of the epoch once its statistics are complete.
+    pub fn delete_epoch_signers(&mut self, epoch_id: &EpochId) {
+        let mut store_update = self.store().store_update();
+        for (key, _) in self.store().iter_prefix(ColEpochSigners, epoch_id.as_ref()) {
+            store_update.delete(ColEpochSigners, &key);
+        }
+        self.merge(store_update);
+    }
+
     /// Save post applying chunk extra info.
     pub fn save_chunk_extra(
         &mut self,
@@ -2122,6 +2401,7 @@ impl<'a> ChainStoreUpdate<'a> {
             self.gc_outgoing_receipts(&block_hash, shard_id);
             self.gc_col(ColIncomingReceipts, &block_shard_id);
             self.gc_col(ColChunkPerHeightShard, &block_shard_id);
+            self.gc_col(ColFailedReceipts, &block_shard_id);
 
             // For incoming State Parts it's done in chain.clear_downloaded_parts()
             // The following code is mostly for outgoing State Parts.
@@ -2147,9 +2427,13 @@ impl<'a> ChainStoreUpdate<'a> {
         let block_hash_vec: Vec<u8> = block_hash.as_ref().into();
         self.gc_col(ColBlock, &block_hash_vec);
         self.gc_col(ColBlockExtra, &block_hash_vec);
+        self.gc_col(ColBlockTransactionStats, &block_hash_vec);
         self.gc_col(ColNextBlockHashes, &block_hash_vec);
         self.gc_col(ColChallengedBlocks, &block_hash_vec);
         self.gc_col(ColBlocksToCatchup, &block_hash_vec);
+        if !matches!(gc_mode, GCMode::Fork(_)) {
+            self.gc_access_key_usage(&block_hash, height)?;
+        }
         let storage_key = KeyForStateChanges::get_prefix(&block_hash);
         let stored_state_changes: Vec<Vec<u8>> = self
             .chain_store
@@ -2237,6 +2521,42 @@ impl<'a> ChainStoreUpdate<'a> {
         Ok(())
     }
 
+    /// Deletes the usage of the access keys deleted in a block of the canonical chain, unless the
+    /// key was added again and used since.
+    fn gc_access_key_usage(
+        &mut self,
+        block_hash: &CryptoHash,
+        height: BlockHeight,
+    ) -> Result<(), Error> {
+        let deleted_access_keys = self
+            .chain_store
+            .get_raw_state_changes_in_block(block_hash)?
+            .into_iter()
+            .filter_map(|changes_with_key| match changes_with_key.trie_key {
+                TrieKey::AccessKey { account_id, public_key }
+                    if changes_with_key
+                        .changes
+                        .last()
+                        .map_or(false, |change| change.data.is_none()) =>
+                {
+                    Some((account_id, public_key))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (account_id, public_key) in deleted_access_keys {
+            let usage = self.get_access_key_usage(&account_id, &public_key)?;
+            if !usage.is_empty() && usage.iter().all(|usage| usage.last_used_block_height <= height)
+            {
+                self.gc_col(
+                    ColAccessKeyUsage,
+                    &get_access_key_usage_key(&account_id, Some(&public_key)),
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub fn gc_col_state_parts(
         &mut self,
         sync_hash: CryptoHash,
@@ -2421,6 +2741,15 @@ impl<'a> ChainStoreUpdate<'a> {
             DBCol::ColHeaderHashesByHeight => {
                 store_update.delete(col, key);
             }
+            DBCol::ColBlockTransactionStats => {
+                store_update.delete(col, key);
+            }
+            DBCol::ColFailedReceipts => {
+                store_update.delete(col, key);
+            }
+            DBCol::ColAccessKeyUsage => {
+                store_update.delete(col, key);
+            }
             DBCol::ColDbVersion
             | DBCol::ColBlockMisc
             | DBCol::ColGCCount
@@ -2440,7 +2769,10 @@ impl<'a> ChainStoreUpdate<'a> {
             | DBCol::_ColLastBlockWithNewChunk
             | DBCol::_ColTransactionRefCount
             | DBCol::ColStateChangesForSplitStates
-            | DBCol::ColCachedContractCode => {
+            | DBCol::ColCachedContractCode
+            | DBCol::ColContractHistory
+            | DBCol::ColEpochChainStats
+            | DBCol::ColEpochSigners => {
                 unreachable!();
             }
         }
@@ -2464,8 +2796,11 @@ impl<'a> ChainStoreUpdate<'a> {
         Ok(())
     }
 
+    /// Writes the cached changes and merges the other store updates into a single store update,
+    /// preallocated for the operations of the cache update.
     fn finalize(&mut self) -> Result<StoreUpdate, Error> {
         let mut store_update = self.store().store_update();
+        store_update.reserve(self.chain_store_cache_update.num_store_ops());
         Self::write_col_misc(&mut store_update, HEAD_KEY, &mut self.head)?;
         Self::write_col_misc(&mut store_update, TAIL_KEY, &mut self.tail)?;
         Self::write_col_misc(&mut store_update, CHUNK_TAIL_KEY, &mut self.chunk_tail)?;
@@ -2567,14 +2902,17 @@ impl<'a> ChainStoreUpdate<'a> {
 
             // Increase transaction refcounts for all included txs
             for tx in chunk.transactions().iter() {
-                let bytes = tx.try_to_vec().expect("Borsh cannot fail");
-                store_update.update_refcount(ColTransactions, tx.get_hash().as_ref(), &bytes, 1);
+                store_update.update_refcount_ser(ColTransactions, tx.get_hash().as_ref(), tx, 1)?;
             }
 
             // Increase receipt refcounts for all included receipts
             for receipt in chunk.receipts().iter() {
-                let bytes = receipt.try_to_vec().expect("Borsh cannot fail");
-                store_update.update_refcount(ColReceipts, receipt.get_hash().as_ref(), &bytes, 1);
+                store_update.update_refcount_ser(
+                    ColReceipts,
+                    receipt.get_hash().as_ref(),
+                    receipt,
+                    1,
+                )?;
             }
 
             store_update.set_ser(ColChunks, chunk_hash.as_ref(), chunk)?;
@@ -2635,8 +2973,12 @@ impl<'a> ChainStoreUpdate<'a> {
             )?;
         }
         for (receipt_id, shard_id) in self.chain_store_cache_update.receipt_id_to_shard_id.iter() {
-            let data = shard_id.try_to_vec()?;
-            store_update.update_refcount(ColReceiptIdToShardId, receipt_id.as_ref(), &data, 1);
+            store_update.update_refcount_ser(
+                ColReceiptIdToShardId,
+                receipt_id.as_ref(),
+                shard_id,
+                1,
+            )?;
         }
         for (block_hash, refcount) in self.chain_store_cache_update.block_refcounts.iter() {
             store_update.set_ser(ColBlockRefCount, block_hash.as_ref(), refcount)?;
@@ -2746,18 +3088,13 @@ impl<'a> ChainStoreUpdate<'a> {
         for block_height in self.chain_store_cache_update.processed_block_heights.iter() {
             store_update.set_ser(ColProcessedBlockHeights, &index_to_bytes(*block_height), &())?;
         }
-        for (col, mut gc_count) in self.chain_store_cache_update.gc_count.clone().drain() {
-            if let Ok(Some(value)) = self.store().get_ser::<GCCount>(
-                ColGCCount,
-                &col.try_to_vec().expect("Failed to serialize DBCol"),
-            ) {
+        for (col, gc_count) in self.chain_store_cache_update.gc_count.iter() {
+            let key = col.try_to_vec().expect("Failed to serialize DBCol");
+            let mut gc_count = *gc_count;
+            if let Ok(Some(value)) = self.store().get_ser::<GCCount>(ColGCCount, &key) {
                 gc_count += value;
             }
-            store_update.set_ser(
-                ColGCCount,
-                &col.try_to_vec().expect("Failed to serialize DBCol"),
-                &gc_count,
-            )?;
+            store_update.set_ser(ColGCCount, &key, &gc_count)?;
         }
         for other in self.store_updates.drain(..) {
             store_update.merge(other);
@@ -2955,8 +3292,9 @@ mod tests {
     use near_primitives::block::{Block, Tip};
     #[cfg(feature = "expensive_tests")]
     use near_primitives::epoch_manager::block_info::BlockInfo;
-    use near_primitives::errors::InvalidTxError;
+    use near_primitives::errors::{InvalidTxError, TxExecutionError};
     use near_primitives::hash::hash;
+    use near_primitives::receipt::FailedReceipt;
     use near_primitives::types::{BlockHeight, EpochId, GCCount, NumBlocks};
     use near_primitives::utils::index_to_bytes;
     use near_primitives::validator_signer::InMemoryValidatorSigner;
@@ -3261,9 +3599,11 @@ mod tests {
             DBCol::ColChallengedBlocks,
             DBCol::ColStateDlInfos,
             DBCol::ColBlockExtra,
+            DBCol::ColBlockTransactionStats,
             DBCol::ColBlockPerHeight,
             DBCol::ColNextBlockHashes,
             DBCol::ColChunkPerHeightShard,
+            DBCol::ColFailedReceipts,
             DBCol::ColBlockRefCount,
             DBCol::ColOutcomeIds,
             DBCol::ColChunkExtra,
@@ -3300,6 +3640,106 @@ mod tests {
         }
     }
 
+    /// Test that the history before the epoch preceding the epoch of the pruning checkpoint is
+    /// garbage collected once the checkpoint is final, more than the usual epochs.
+    #[test]
+    fn test_clear_old_data_to_pruning_checkpoint() {
+        let mut chain = get_chain_with_epoch_length(1);
+        let runtime_adapter = chain.runtime_adapter.clone();
+        let genesis = chain.get_block_by_height(0).unwrap().clone();
+        let signer = Arc::new(InMemoryValidatorSigner::from_seed(
+            "test1".parse().unwrap(),
+            KeyType::ED25519,
+            "test1",
+        ));
+        let mut prev_block = genesis.clone();
+        let mut blocks = vec![prev_block.clone()];
+        for i in 1..15 {
+            runtime_adapter
+                .get_next_epoch_id_from_prev_block(prev_block.hash())
+                .expect("block must exist");
+            let block = Block::empty_with_height(&prev_block, i, &*signer.clone());
+            blocks.push(block.clone());
+            let mut store_update = chain.mut_store().store_update();
+            store_update.save_block(block.clone());
+            store_update.inc_block_refcount(block.header().prev_hash()).unwrap();
+            store_update.save_head(&Tip::from_header(block.header())).unwrap();
+            store_update.save_block_header(block.header().clone()).unwrap();
+            store_update
+                .chain_store_cache_update
+                .height_to_hashes
+                .insert(i, Some(*block.header().hash()));
+            store_update.save_next_block_hash(&prev_block.hash(), *block.hash());
+            store_update.commit().unwrap();
+
+            prev_block = block.clone();
+        }
+        chain.epoch_length = 1;
+        chain.set_pruning_checkpoint(Some(*blocks[14].hash()));
+        let save_final_head = |chain: &mut Chain, height: usize| {
+            let mut store_update = chain.mut_store().store_update();
+            store_update.save_final_head(&Tip::from_header(blocks[height].header())).unwrap();
+            store_update.commit().unwrap();
+        };
+
+        // Until the checkpoint is final, only the usual epochs are garbage collected.
+        save_final_head(&mut chain, 11);
+        let trie = chain.runtime_adapter.get_tries();
+        assert!(chain.clear_data(trie.clone(), 100).is_ok());
+        assert!(chain.get_block(&blocks[7].hash()).is_err());
+        assert!(chain.get_block(&blocks[8].hash()).is_ok());
+
+        // The epoch of the checkpoint starts at 13, the previous one at 11.
+        save_final_head(&mut chain, 14);
+        assert!(chain.clear_data(trie, 100).is_ok());
+        assert!(chain.get_block(&blocks[9].hash()).is_err());
+        for block in &blocks[10..] {
+            assert!(chain.get_block(&block.hash()).is_ok());
+        }
+    }
+
+    /// Test that the failed receipts of the blocks below a height are deleted, and only those.
+    #[test]
+    fn test_clear_failed_receipts_below() {
+        let mut chain = get_chain();
+        let genesis = chain.get_block_by_height(0).unwrap().clone();
+        let signer = Arc::new(InMemoryValidatorSigner::from_seed(
+            "test1".parse().unwrap(),
+            KeyType::ED25519,
+            "test1",
+        ));
+        let failed_receipt = FailedReceipt {
+            receipt_id: hash(&[1]),
+            executor_id: "test1".parse().unwrap(),
+            error: TxExecutionError::InvalidTxError(InvalidTxError::Expired),
+        };
+        let mut prev_block = genesis;
+        let mut blocks = vec![];
+        for i in 1..6 {
+            let block = Block::empty_with_height(&prev_block, i, &*signer.clone());
+            let mut store_update = chain.mut_store().store_update();
+            store_update.save_block_header(block.header().clone()).unwrap();
+            store_update
+                .save_failed_receipts(block.hash(), 0, vec![failed_receipt.clone()])
+                .unwrap();
+            store_update.commit().unwrap();
+            blocks.push(block.clone());
+            prev_block = block;
+        }
+
+        let mut store_update = chain.mut_store().store_update();
+        store_update.clear_failed_receipts_below(3).unwrap();
+        store_update.commit().unwrap();
+        for block in blocks.iter() {
+            let failed_receipts = chain.store().get_failed_receipts(block.hash(), 0).unwrap();
+            if block.header().height() < 3 {
+                assert!(failed_receipts.is_empty());
+            } else {
+                assert_eq!(failed_receipts, vec![failed_receipt.clone()]);
+            }
+        }
+    }
+
     #[test]
     fn test_clear_old_data_fixed_height() {
         let mut chain = get_chain();
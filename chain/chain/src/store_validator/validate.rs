@@ -19,7 +19,7 @@ use near_store::{
     ColBlock, ColBlockHeader, ColBlockHeight, ColBlockInfo, ColBlockMisc, ColBlockPerHeight,
     ColChunkExtra, ColChunkHashesByHeight, ColChunks, ColHeaderHashesByHeight, ColOutcomeIds,
     ColStateHeaders, ColTransactionResult, DBCol, TrieChanges, TrieIterator, CHUNK_TAIL_KEY,
-    FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY, NUM_COLS, SHOULD_COL_GC, TAIL_KEY,
+    FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY, NUM_COLS, SHOULD_COL_GC, SKIP_COL_GC, TAIL_KEY,
 };
 
 use crate::StoreValidator;
@@ -901,14 +901,16 @@ pub(crate) fn block_height_cmp_tail_final(
 }
 
 pub(crate) fn gc_col_count_final(sv: &mut StoreValidator) -> Result<(), StoreValidatorError> {
+    // Columns which may not be garbage collected at all are left out
     let mut zeroes = 0;
-    for count in sv.inner.gc_col.iter() {
-        if *count == 0 {
+    for (col, count) in sv.inner.gc_col.iter().enumerate() {
+        if *count == 0 && !SKIP_COL_GC[col] {
             zeroes += 1;
         }
     }
+    let skipped_col_count = SKIP_COL_GC.iter().filter(|skip| **skip).count();
     // 1. All zeroes case is acceptable
-    if zeroes == NUM_COLS {
+    if zeroes == NUM_COLS - skipped_col_count {
         return Ok(());
     }
     let mut gc_col_count = 0;
@@ -26,7 +26,7 @@ use near_primitives::sharding::ChunkHash;
 use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::{
     Action, ExecutionMetadata, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus,
-    SignedTransaction, TransferAction,
+    SignedTransaction, Transaction, TransferAction,
 };
 use near_primitives::types::validator_stake::{ValidatorStake, ValidatorStakeIter};
 use near_primitives::types::{
@@ -49,7 +49,7 @@ use crate::chain::{Chain, NUM_EPOCHS_TO_KEEP_STORE_DATA};
 use crate::store::ChainStoreAccess;
 use crate::types::{
     ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo, ChainGenesis,
-    ValidatorInfoIdentifier,
+    SimulateTransactionResult, TransactionFees, ValidatorInfoIdentifier,
 };
 use crate::Doomslug;
 use crate::{BlockHeader, DoomslugThresholdMode, RuntimeAdapter};
@@ -906,14 +906,17 @@ impl RuntimeAdapter for KeyValueRuntime {
                 block_height,
                 block_hash: *block_hash,
             }),
-            QueryRequest::ViewState { .. } => Ok(QueryResponse {
-                kind: QueryResponseKind::ViewState(ViewStateResult {
-                    values: Default::default(),
-                    proof: vec![],
-                }),
-                block_height,
-                block_hash: *block_hash,
-            }),
+            QueryRequest::ViewState { .. } | QueryRequest::ViewStatePage { .. } => {
+                Ok(QueryResponse {
+                    kind: QueryResponseKind::ViewState(ViewStateResult {
+                        values: Default::default(),
+                        proof: vec![],
+                        continuation_token: None,
+                    }),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
             QueryRequest::CallFunction { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::CallResult(CallResult {
                     result: Default::default(),
@@ -925,6 +928,26 @@ impl RuntimeAdapter for KeyValueRuntime {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        _block_header: &BlockHeader,
+        _state_roots: HashMap<ShardId, StateRoot>,
+        _transaction: &SignedTransaction,
+        _verify_signature: bool,
+    ) -> Result<Result<SimulateTransactionResult, InvalidTxError>, Error> {
+        Err(ErrorKind::Other("Transactions can't be simulated by KeyValueRuntime".to_string())
+            .into())
+    }
+
+    fn get_transaction_fees(
+        &self,
+        _epoch_id: &EpochId,
+        _transaction: &Transaction,
+        _gas_price: Balance,
+    ) -> Result<TransactionFees, Error> {
+        Err(ErrorKind::Other("KeyValueRuntime has no transaction fees".to_string()).into())
+    }
+
     fn obtain_state_part(
         &self,
         _shard_id: ShardId,
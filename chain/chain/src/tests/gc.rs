@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::chain::Chain;
-use crate::test_utils::KeyValueRuntime;
+use crate::test_utils::{setup_with_validators, KeyValueRuntime};
 use crate::types::{ChainGenesis, Tip};
 use crate::DoomslugThresholdMode;
 use near_crypto::KeyType;
@@ -9,7 +9,7 @@ use near_primitives::block::Block;
 use near_primitives::merkle::PartialMerkleTree;
 use near_primitives::shard_layout::ShardUId;
 use near_primitives::types::{NumBlocks, NumShards, StateRoot};
-use near_primitives::validator_signer::InMemoryValidatorSigner;
+use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
 use near_store::test_utils::{create_test_store, gen_changes};
 use near_store::{ShardTries, StoreUpdate, Trie, WrappedTrieChanges};
 use rand::Rng;
@@ -547,3 +547,80 @@ fn test_gc_star_small() {
 fn test_gc_star_large() {
     test_gc_star_common(20)
 }
+
+// Produces a block on top of the head, in the epoch given by the runtime
+fn produce_block(
+    chain: &mut Chain,
+    signer: &dyn ValidatorSigner,
+    block_merkle_tree: &mut PartialMerkleTree,
+) -> Block {
+    let prev_hash = chain.head().unwrap().last_block_hash;
+    let prev_block = chain.get_block(&prev_hash).unwrap().clone();
+    let runtime_adapter = chain.runtime_adapter.clone();
+    let epoch_id = runtime_adapter.get_epoch_id_from_prev_block(prev_block.hash()).unwrap();
+    let next_epoch_id =
+        runtime_adapter.get_next_epoch_id_from_prev_block(prev_block.hash()).unwrap();
+    let next_bp_hash = if &epoch_id == prev_block.header().epoch_id() {
+        *prev_block.header().next_bp_hash()
+    } else {
+        Chain::compute_bp_hash(
+            &*runtime_adapter,
+            next_epoch_id.clone(),
+            epoch_id.clone(),
+            prev_block.hash(),
+        )
+        .unwrap()
+    };
+    let block = Block::empty_with_epoch(
+        &prev_block,
+        prev_block.header().height() + 1,
+        epoch_id,
+        next_epoch_id,
+        next_bp_hash,
+        signer,
+        block_merkle_tree,
+    );
+    chain.process_block_test(&None, block.clone()).unwrap();
+    block
+}
+
+// Prunes the history up to the epoch before the one of the checkpoint, then keeps producing
+// blocks into the next epochs
+#[test]
+fn test_gc_pruning_checkpoint() {
+    let (mut chain, _, signers) =
+        setup_with_validators(vec!["test1".parse().unwrap()], 1, 1, 5, 100);
+    let signer = signers[0].clone();
+    let tries = chain.runtime_adapter.get_tries();
+    let mut block_merkle_tree = PartialMerkleTree::default();
+    let mut blocks = vec![chain.get_block_by_height(0).unwrap().clone()];
+    for _ in 0..40 {
+        blocks.push(produce_block(&mut chain, &*signer, &mut block_merkle_tree));
+    }
+
+    let checkpoint = *blocks[37].hash();
+    chain.set_pruning_checkpoint(Some(checkpoint));
+    assert!(chain.clear_data(tries.clone(), 100).is_ok());
+    let epoch_start_height = chain.runtime_adapter.get_epoch_start_height(&checkpoint).unwrap();
+    let prev_epoch_start_height = chain
+        .runtime_adapter
+        .get_epoch_start_height(blocks[epoch_start_height as usize].header().prev_hash())
+        .unwrap();
+    assert!(prev_epoch_start_height < epoch_start_height);
+    // Only the last block before the epoch preceding the one of the checkpoint is left of the
+    // older history, more than the usual epochs are garbage collected.
+    assert!(chain.runtime_adapter.get_gc_stop_height(blocks[40].hash()) < prev_epoch_start_height);
+    for block in &blocks[..prev_epoch_start_height as usize - 1] {
+        assert!(chain.get_block(block.hash()).is_err());
+    }
+    for block in &blocks[prev_epoch_start_height as usize - 1..] {
+        assert!(chain.get_block(block.hash()).is_ok());
+    }
+
+    let epoch_id = chain.head().unwrap().epoch_id;
+    for _ in 0..10 {
+        produce_block(&mut chain, &*signer, &mut block_merkle_tree);
+        assert!(chain.clear_data(tries.clone(), 100).is_ok());
+    }
+    assert_ne!(chain.head().unwrap().epoch_id, epoch_id);
+}
@@ -5,6 +5,7 @@ use chrono::TimeZone;
 use near_logger_utils::init_test_logger;
 use near_primitives::hash::CryptoHash;
 use near_primitives::time::{Clock, MockClockGuard};
+use near_primitives::utils::to_timestamp;
 use near_primitives::version::PROTOCOL_VERSION;
 use num_rational::Rational;
 use std::str::FromStr;
@@ -87,6 +88,7 @@ fn build_chain_with_orhpans() {
     }
     let last_block = &blocks[blocks.len() - 1];
     let block = Block::produce(
+        PROTOCOL_VERSION,
         PROTOCOL_VERSION,
         PROTOCOL_VERSION,
         &last_block.header(),
@@ -245,3 +247,22 @@ fn next_blocks() {
     assert_eq!(chain.mut_store().get_next_block_hash(&b1_hash).unwrap(), &b3_hash);
     assert_eq!(chain.mut_store().get_next_block_hash(&b3_hash).unwrap(), &b4_hash);
 }
+
+#[test]
+fn block_from_the_future() {
+    init_test_logger();
+    let (mut chain, _, signer) = setup();
+    chain.set_max_block_future_drift(std::time::Duration::from_secs(5));
+    let genesis = chain.get_block(&chain.genesis().hash().clone()).unwrap();
+    let mut b1 = Block::empty(&genesis, &*signer);
+    b1.mut_header().get_mut().inner_lite.timestamp =
+        to_timestamp(chrono::Utc::now() + chrono::Duration::seconds(10));
+    b1.mut_header().resign(&*signer);
+    assert!(matches!(
+        chain.process_block_test(&None, b1.clone()).unwrap_err().kind(),
+        ErrorKind::InvalidBlockFutureTime(_)
+    ));
+
+    chain.set_max_block_future_drift(std::time::Duration::from_secs(60));
+    assert!(chain.process_block_test(&None, b1).is_ok());
+}
@@ -22,11 +22,12 @@ use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{merklize, MerklePath};
 use near_primitives::receipt::Receipt;
 use near_primitives::sharding::{ChunkHash, ReceiptList, ShardChunkHeader};
-use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction, Transaction};
 use near_primitives::types::validator_stake::{ValidatorStake, ValidatorStakeIter};
 use near_primitives::types::{
     AccountId, ApprovalStake, Balance, BlockHeight, BlockHeightDelta, EpochId, Gas, MerkleHash,
-    NumBlocks, ShardId, StateChangesForSplitStates, StateRoot, StateRootNode,
+    NumBlocks, RawStateChangesWithTrieKey, ShardId, StateChangesForSplitStates, StateRoot,
+    StateRootNode, StorageUsage,
 };
 use near_primitives::version::{
     ProtocolVersion, MIN_GAS_PRICE_NEP_92, MIN_GAS_PRICE_NEP_92_FIX, MIN_PROTOCOL_VERSION_NEP_92,
@@ -121,6 +122,36 @@ impl ApplyTransactionResult {
     }
 }
 
+/// Outcome of the dry run of a transaction, see `RuntimeAdapter::simulate_transaction`.
+pub struct SimulateTransactionResult {
+    /// Outcomes of the transaction and then of the receipts, in execution order.
+    pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// Receipts produced by the transaction and the receipts, in execution order.
+    pub receipts: Vec<Receipt>,
+    /// Receipts left unexecuted, to shards without a state or past the limit of receipts.
+    pub unexecuted_receipts: Vec<Receipt>,
+    /// Changes of the states of all the shards.
+    pub state_changes: Vec<RawStateChangesWithTrieKey>,
+}
+
+/// Protocol fees of a transaction, see `RuntimeAdapter::get_transaction_fees`.
+pub struct TransactionFees {
+    /// Gas burnt to convert the transaction into a receipt.
+    pub send_gas: Gas,
+    /// Gas of the execution fees of the receipt, prepaid with the transaction.
+    pub exec_gas: Gas,
+    /// Gas attached to the function calls.
+    pub prepaid_gas: Gas,
+    /// Price of the prepaid gas, inflated for the blocks the receipts may take.
+    pub receipt_gas_price: Balance,
+    /// Tokens the transaction costs the signer, with the deposits.
+    pub total_cost: Balance,
+    /// Storage the actions add to the receiver.
+    pub storage_usage_increase: StorageUsage,
+    /// Tokens the receiver must have to pay for the storage added by the actions.
+    pub storage_cost: Balance,
+}
+
 /// Compressed information about block.
 /// Useful for epoch manager.
 #[derive(Default, Clone, Debug)]
@@ -670,6 +701,28 @@ pub trait RuntimeAdapter: Send + Sync {
         request: &QueryRequest,
     ) -> Result<QueryResponse, near_chain_primitives::error::QueryError>;
 
+    /// Dry run of the transaction, and of the receipts it produces, on top of the states of the
+    /// shards after the block, by shard, as if by the next blocks but without storing anything.
+    /// The state of the shard of the signer must be given, the receipts to the other shards
+    /// without a state are left unexecuted.
+    /// Returns the `InvalidTxError` of an invalid transaction.
+    fn simulate_transaction(
+        &self,
+        block_header: &BlockHeader,
+        state_roots: HashMap<ShardId, StateRoot>,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<Result<SimulateTransactionResult, InvalidTxError>, Error>;
+
+    /// Fees of the transaction at the gas price by the protocol of the epoch, and the storage its
+    /// actions add to the receiver.
+    fn get_transaction_fees(
+        &self,
+        epoch_id: &EpochId,
+        transaction: &Transaction,
+        gas_price: Balance,
+    ) -> Result<TransactionFees, Error>;
+
     fn get_validator_info(
         &self,
         epoch_id: ValidatorInfoIdentifier,
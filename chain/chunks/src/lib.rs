@@ -14,11 +14,15 @@ use near_chain::validate::validate_chunk_proofs;
 use near_chain::{
     byzantine_assert, ChainStore, ChainStoreAccess, ChainStoreUpdate, ErrorKind, RuntimeAdapter,
 };
+use near_chain_configs::CHUNK_REQUEST_RETRY_BUDGET;
 use near_network::types::{NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest};
+use near_pool::types::{InsertTransactionResult, TransactionPoolLimits};
 use near_pool::{PoolIteratorWrapper, TransactionPool};
+use near_primitives::backoff::{Backoff, BackoffConfig};
 use near_primitives::block::{BlockHeader, Tip};
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{merklize, verify_path, MerklePath};
+use near_primitives::network::PeerId;
 use near_primitives::receipt::Receipt;
 use near_primitives::sharding::{
     ChunkHash, EncodedShardChunk, PartialEncodedChunk, PartialEncodedChunkPart,
@@ -34,9 +38,12 @@ use near_primitives::types::{
 use near_primitives::utils::MaybeValidated;
 use near_primitives::validator_signer::ValidatorSigner;
 use near_primitives::version::ProtocolVersion;
+use near_primitives::views::ChunkPartsAvailabilityView;
 use near_primitives::{checked_feature, unwrap_or_return};
 
 use crate::chunk_cache::{EncodedChunksCache, EncodedChunksCacheEntry};
+use crate::part_availability::PartAvailabilityTracker;
+use crate::peer_stats::ChunkRequestPeerStats;
 pub use near_chunks_primitives::Error;
 use near_network_primitives::types::{
     AccountIdOrPeerTrackingShard, PartialEncodedChunkForwardMsg, PartialEncodedChunkRequestMsg,
@@ -47,6 +54,8 @@ use near_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
 use rand::Rng;
 
 mod chunk_cache;
+mod part_availability;
+mod peer_stats;
 pub mod test_utils;
 
 const CHUNK_PRODUCER_BLACKLIST_SIZE: usize = 100;
@@ -91,15 +100,40 @@ struct ChunkRequestInfo {
     parent_hash: CryptoHash,
     shard_id: ShardId,
     added: Instant,
-    last_requested: Instant,
+    /// Schedule of the resends of the request.
+    backoff: Backoff,
+    /// Peers the chunk has already been requested from.
+    requested_from: HashSet<AccountId>,
+}
+
+impl ChunkRequestInfo {
+    fn new(
+        height: BlockHeight,
+        parent_hash: CryptoHash,
+        shard_id: ShardId,
+        backoff: &BackoffConfig,
+    ) -> Self {
+        let now = Clock::instant();
+        Self {
+            height,
+            parent_hash,
+            shard_id,
+            added: now,
+            backoff: Backoff::new(backoff.clone(), now),
+            requested_from: HashSet::new(),
+        }
+    }
 }
 
 struct RequestPool {
-    retry_duration: Duration,
+    /// Resends of the requests, the full chunk is requested from archival peers once their
+    /// budget is exhausted.
+    backoff: BackoffConfig,
     switch_to_others_duration: Duration,
     switch_to_full_fetch_duration: Duration,
     max_duration: Duration,
     requests: HashMap<ChunkHash, ChunkRequestInfo>,
+    peer_stats: ChunkRequestPeerStats,
 }
 
 impl RequestPool {
@@ -110,11 +144,14 @@ impl RequestPool {
         max_duration: Duration,
     ) -> Self {
         Self {
-            retry_duration,
+            // Jitter the delays so that retries of many chunks don't hit peers all at once.
+            backoff: BackoffConfig::fixed(retry_duration, 0.5)
+                .with_budget(CHUNK_REQUEST_RETRY_BUDGET),
             switch_to_others_duration,
             switch_to_full_fetch_duration,
             max_duration,
             requests: HashMap::default(),
+            peer_stats: ChunkRequestPeerStats::new(switch_to_others_duration),
         }
     }
     pub fn contains_key(&self, chunk_hash: &ChunkHash) -> bool {
@@ -127,24 +164,27 @@ impl RequestPool {
 
     pub fn remove(&mut self, chunk_hash: &ChunkHash) {
         self.requests.remove(chunk_hash);
+        self.peer_stats.forget_chunk(chunk_hash);
     }
 
     pub fn fetch(&mut self) -> Vec<(ChunkHash, ChunkRequestInfo)> {
+        self.peer_stats.expire();
         let mut removed_requests = HashSet::<ChunkHash>::default();
         let mut requests = Vec::new();
+        let now = Clock::instant();
         for (chunk_hash, mut chunk_request) in self.requests.iter_mut() {
             if chunk_request.added.elapsed() > self.max_duration {
                 debug!(target: "chunks", "Evicted chunk requested that was never fetched {} (shard_id: {})", chunk_hash.0, chunk_request.shard_id);
                 removed_requests.insert(chunk_hash.clone());
                 continue;
             }
-            if chunk_request.last_requested.elapsed() > self.retry_duration {
-                chunk_request.last_requested = Clock::instant();
+            if chunk_request.backoff.is_due(now) {
+                chunk_request.backoff.record_retry(now);
                 requests.push((chunk_hash.clone(), chunk_request.clone()));
             }
         }
         for chunk_hash in removed_requests {
-            self.requests.remove(&chunk_hash);
+            self.remove(&chunk_hash);
         }
         requests
     }
@@ -382,6 +422,8 @@ pub struct ShardsManager {
     me: Option<AccountId>,
 
     tx_pools: HashMap<ShardId, TransactionPool>,
+    /// Limits of the pool of every shard, independent of the pools of the other shards.
+    tx_pool_limits: TransactionPoolLimits,
 
     runtime_adapter: Arc<dyn RuntimeAdapter>,
     peer_manager_adapter: Arc<dyn PeerManagerAdapter>,
@@ -394,6 +436,7 @@ pub struct ShardsManager {
     stored_partial_encoded_chunks:
         HashMap<BlockHeight, HashMap<ShardId, Vec<PartialEncodedChunkV2>>>,
     chunk_forwards_cache: SizedCache<ChunkHash, HashMap<u64, PartialEncodedChunkPart>>,
+    part_availability: PartAvailabilityTracker,
 
     seals_mgr: SealsManager,
     /// Useful to make tests deterministic and reproducible,
@@ -411,6 +454,7 @@ impl ShardsManager {
         Self {
             me: me.clone(),
             tx_pools: HashMap::new(),
+            tx_pool_limits: TransactionPoolLimits::default(),
             runtime_adapter: runtime_adapter.clone(),
             peer_manager_adapter: network_adapter,
             encoded_chunks: EncodedChunksCache::new(),
@@ -422,11 +466,25 @@ impl ShardsManager {
             ),
             stored_partial_encoded_chunks: HashMap::new(),
             chunk_forwards_cache: SizedCache::with_size(CHUNK_FORWARD_CACHE_SIZE),
+            part_availability: PartAvailabilityTracker::new(),
             seals_mgr: SealsManager::new(me, runtime_adapter),
             rng_seed,
         }
     }
 
+    /// Sets the number of retries after which the full chunk is requested from archival peers.
+    pub fn set_chunk_request_retry_budget(&mut self, retry_budget: u32) {
+        self.requested_partial_encoded_chunks.backoff.budget = Some(retry_budget);
+    }
+
+    /// Sets the limits of the transaction pool of every shard.
+    pub fn set_transaction_pool_limits(&mut self, limits: TransactionPoolLimits) {
+        self.tx_pool_limits = limits;
+        for pool in self.tx_pools.values_mut() {
+            pool.set_limits(limits);
+        }
+    }
+
     pub fn update_largest_seen_height(&mut self, new_height: BlockHeight) {
         self.encoded_chunks.update_largest_seen_height(
             new_height,
@@ -438,6 +496,35 @@ impl ShardsManager {
         self.tx_pools.get_mut(&shard_id).map(|pool| pool.pool_iterator())
     }
 
+    pub fn tx_pools(&self) -> &HashMap<ShardId, TransactionPool> {
+        &self.tx_pools
+    }
+
+    /// Records parts of a chunk received from `peer_id`, whether in a partial encoded chunk,
+    /// a response to a request or a forward.
+    pub fn record_chunk_parts_received<I>(
+        &mut self,
+        chunk_hash: &ChunkHash,
+        peer_id: &PeerId,
+        part_ords: I,
+    ) where
+        I: IntoIterator<Item = u64>,
+    {
+        self.part_availability.record_parts(chunk_hash, peer_id, part_ords);
+    }
+
+    /// How the parts of up to `limit` of the most recent chunks were delivered, newest first.
+    pub fn chunk_parts_availability(&self, limit: usize) -> Vec<ChunkPartsAvailabilityView> {
+        let runtime_adapter = &self.runtime_adapter;
+        self.part_availability.recent(
+            limit,
+            runtime_adapter.num_total_parts() as u64,
+            |prev_block_hash, part_ord| {
+                runtime_adapter.get_part_owner(prev_block_hash, part_ord).ok()
+            },
+        )
+    }
+
     pub fn cares_about_shard_this_or_next_epoch(
         &self,
         account_id: Option<&AccountId>,
@@ -477,6 +564,13 @@ impl ShardsManager {
             shard_id,
         )?;
 
+        let requested_from = self
+            .requested_partial_encoded_chunks
+            .requests
+            .get(chunk_hash)
+            .map(|chunk_request| chunk_request.requested_from.clone())
+            .unwrap_or_default();
+
         let me = self.me.as_ref();
         let shard_representative_target = if !request_own_parts_from_others
             && !request_from_archival
@@ -484,7 +578,12 @@ impl ShardsManager {
         {
             AccountIdOrPeerTrackingShard::from_account(shard_id, chunk_producer_account_id.clone())
         } else {
-            self.get_random_target_tracking_shard(&parent_hash, shard_id, request_from_archival)?
+            self.get_random_target_tracking_shard(
+                &parent_hash,
+                shard_id,
+                request_from_archival,
+                &requested_from,
+            )?
         };
 
         let seal = self.seals_mgr.get_seal(chunk_hash, parent_hash, height, shard_id)?;
@@ -511,8 +610,15 @@ impl ShardsManager {
                 } else {
                     let part_owner = self.runtime_adapter.get_part_owner(&parent_hash, part_ord)?;
 
-                    if Some(&part_owner) == me {
-                        // If missing own part, request it from the chunk producer / node tracking shard
+                    if Some(&part_owner) == me
+                        || (request_own_parts_from_others
+                            && self
+                                .requested_partial_encoded_chunks
+                                .peer_stats
+                                .is_unreliable(&part_owner))
+                    {
+                        // If missing own part, or the part owner has been failing to respond,
+                        // request it from the chunk producer / node tracking shard
                         shard_representative_target.clone()
                     } else {
                         AccountIdOrPeerTrackingShard::from_account(shard_id, part_owner)
@@ -536,9 +642,16 @@ impl ShardsManager {
         }
 
         let no_account_id = me.is_none();
+        let mut requested_targets = vec![];
         for (target, part_ords) in bp_to_parts {
             // extra check that we are not sending request to ourselves.
             if no_account_id || me != target.account_id.as_ref() {
+                if let Some(account_id) = &target.account_id {
+                    self.requested_partial_encoded_chunks
+                        .peer_stats
+                        .record_request(account_id, chunk_hash, &part_ords);
+                    requested_targets.push(account_id.clone());
+                }
                 let request = PartialEncodedChunkRequestMsg {
                     chunk_hash: chunk_hash.clone(),
                     part_ords,
@@ -559,15 +672,24 @@ impl ShardsManager {
             }
         }
 
+        if let Some(chunk_request) =
+            self.requested_partial_encoded_chunks.requests.get_mut(chunk_hash)
+        {
+            chunk_request.requested_from.extend(requested_targets);
+        }
+
         Ok(())
     }
 
     /// Get a random shard block producer that is not me.
+    /// Producers which haven't been asked for the chunk yet and which have been responsive
+    /// are preferred.
     fn get_random_target_tracking_shard(
         &self,
         parent_hash: &CryptoHash,
         shard_id: ShardId,
         request_from_archival: bool,
+        excluded: &HashSet<AccountId>,
     ) -> Result<AccountIdOrPeerTrackingShard, near_chain::Error> {
         let mut block_producers = vec![];
         let epoch_id = self.runtime_adapter.get_epoch_id_from_prev_block(parent_hash).unwrap();
@@ -588,7 +710,8 @@ impl ShardsManager {
             }
         }
 
-        let maybe_account_id = block_producers.choose(&mut rand::thread_rng()).cloned();
+        let maybe_account_id =
+            self.requested_partial_encoded_chunks.peer_stats.choose_peer(block_producers, excluded);
 
         Ok(AccountIdOrPeerTrackingShard {
             shard_id,
@@ -658,16 +781,13 @@ impl ShardsManager {
 
         self.encoded_chunks.get_or_insert_from_header(chunk_hash.clone(), chunk_header);
 
-        self.requested_partial_encoded_chunks.insert(
-            chunk_hash.clone(),
-            ChunkRequestInfo {
-                height,
-                parent_hash,
-                shard_id,
-                last_requested: Clock::instant(),
-                added: Clock::instant(),
-            },
+        let chunk_request = ChunkRequestInfo::new(
+            height,
+            parent_hash,
+            shard_id,
+            &self.requested_partial_encoded_chunks.backoff,
         );
+        self.requested_partial_encoded_chunks.insert(chunk_hash.clone(), chunk_request);
 
         if let Some(header_head) = header_head {
             let fetch_from_archival = self.runtime_adapter
@@ -738,18 +858,25 @@ impl ShardsManager {
             });
             let old_block = header_head.last_block_hash != chunk_request.parent_hash
                 && header_head.prev_block_hash != chunk_request.parent_hash;
+            let full_fetch = chunk_request.added.elapsed()
+                > self.requested_partial_encoded_chunks.switch_to_full_fetch_duration;
+            if chunk_request.backoff.has_just_exhausted_budget() {
+                debug!(target: "chunks", "Retry budget exhausted for chunk {:?} (shard_id: {}), the full chunk will be requested from archival peers", chunk_hash.0, chunk_request.shard_id);
+            }
+            // Archival peers are only asked for the full chunk, once the other peers failed to
+            // provide it as well.
+            let archival_fallback = full_fetch && chunk_request.backoff.is_budget_exhausted();
 
             match self.request_partial_encoded_chunk(
                 chunk_request.height,
                 &chunk_request.parent_hash,
                 chunk_request.shard_id,
                 &chunk_hash,
-                chunk_request.added.elapsed()
-                    > self.requested_partial_encoded_chunks.switch_to_full_fetch_duration,
+                full_fetch,
                 old_block
                     || chunk_request.added.elapsed()
                         > self.requested_partial_encoded_chunks.switch_to_others_duration,
-                fetch_from_archival,
+                fetch_from_archival || archival_fallback,
             ) {
                 Ok(()) => {}
                 Err(err) => {
@@ -817,8 +944,11 @@ impl ShardsManager {
         self.encoded_chunks.get_chunk_headers_for_block(&prev_block_hash)
     }
 
-    /// Returns true if transaction is not in the pool before call
-    pub fn insert_transaction(&mut self, shard_id: ShardId, tx: SignedTransaction) -> bool {
+    pub fn insert_transaction(
+        &mut self,
+        shard_id: ShardId,
+        tx: SignedTransaction,
+    ) -> InsertTransactionResult {
         self.pool_for_shard(shard_id).insert_transaction(tx)
     }
 
@@ -844,8 +974,12 @@ impl ShardsManager {
     }
 
     fn pool_for_shard(&mut self, shard_id: ShardId) -> &mut TransactionPool {
+        let rng_seed = &self.rng_seed;
+        let limits = self.tx_pool_limits;
         self.tx_pools.entry(shard_id).or_insert_with(|| {
-            TransactionPool::new(ShardsManager::random_seed(&self.rng_seed, shard_id))
+            let mut pool = TransactionPool::new(ShardsManager::random_seed(rng_seed, shard_id));
+            pool.set_limits(limits);
+            pool
         })
     }
 
@@ -1248,6 +1382,11 @@ impl ShardsManager {
             // TODO: only validate parts we care about
             self.validate_part(header.encoded_merkle_root(), part_info, num_total_parts)?;
         }
+        self.requested_partial_encoded_chunks.peer_stats.record_response(
+            &chunk_hash,
+            partial_encoded_chunk.parts.iter().map(|part_info| part_info.part_ord),
+        );
+        self.part_availability.record_header(header);
 
         // 6. Checking receipts validity
         for proof in partial_encoded_chunk.receipts.iter() {
@@ -1363,6 +1502,7 @@ impl ShardsManager {
                 self.decode_and_persist_encoded_chunk_if_complete(encoded_chunk, chain_store, rs)?;
 
             assert!(successfully_decoded);
+            self.part_availability.record_reconstructed(&chunk_hash);
 
             self.seals_mgr.approve_chunk(height, &chunk_hash);
 
@@ -1819,16 +1959,10 @@ mod test {
             network_adapter.clone(),
             TEST_SEED,
         );
-        let added = Clock::instant();
+        let backoff = shards_manager.requested_partial_encoded_chunks.backoff.clone();
         shards_manager.requested_partial_encoded_chunks.insert(
             ChunkHash(hash(&[1])),
-            ChunkRequestInfo {
-                height: 0,
-                parent_hash: Default::default(),
-                shard_id: 0,
-                added: added,
-                last_requested: added,
-            },
+            ChunkRequestInfo::new(0, Default::default(), 0, &backoff),
         );
         std::thread::sleep(Duration::from_millis(2 * CHUNK_REQUEST_RETRY_MS));
         shards_manager.resend_chunk_requests(&Tip {
@@ -1851,6 +1985,61 @@ mod test {
         };
     }
 
+    /// the full chunk should be requested from archival peers once the retry budget is exhausted,
+    /// but not before the switch to the full fetch
+    #[test]
+    fn test_request_from_archival_after_retry_budget() {
+        let runtime_adapter = Arc::new(KeyValueRuntime::new(create_test_store()));
+        let network_adapter = Arc::new(MockPeerManagerAdapter::default());
+        let mut shards_manager = ShardsManager::new(
+            Some("test".parse().unwrap()),
+            runtime_adapter,
+            network_adapter.clone(),
+            TEST_SEED,
+        );
+        shards_manager.set_chunk_request_retry_budget(1);
+        shards_manager.requested_partial_encoded_chunks.switch_to_full_fetch_duration =
+            Duration::from_millis(10 * CHUNK_REQUEST_RETRY_MS);
+        let chunk_hash = ChunkHash(hash(&[1]));
+        let backoff = shards_manager.requested_partial_encoded_chunks.backoff.clone();
+        shards_manager
+            .requested_partial_encoded_chunks
+            .insert(chunk_hash.clone(), ChunkRequestInfo::new(0, Default::default(), 0, &backoff));
+        let header_head = Tip {
+            height: 0,
+            last_block_hash: CryptoHash::default(),
+            prev_block_hash: CryptoHash::default(),
+            epoch_id: EpochId::default(),
+            next_epoch_id: EpochId::default(),
+        };
+
+        let mut only_archival = vec![];
+        // The budget is exhausted by the second retry, before the switch to the full fetch,
+        // which the last one comes after.
+        for sleep in [2, 2, 2, 5] {
+            std::thread::sleep(Duration::from_millis(sleep * CHUNK_REQUEST_RETRY_MS));
+            shards_manager.resend_chunk_requests(&header_head);
+            let requests = network_adapter.requests.read().unwrap();
+            let msg = requests.last().unwrap().as_network_requests_ref().clone();
+            if let NetworkRequests::PartialEncodedChunkRequest { target, .. } = &msg {
+                only_archival.push(target.only_archival);
+            } else {
+                panic!("Expected PartialEncodedChunkRequest, got {:?}", msg);
+            }
+        }
+        assert_eq!(only_archival, vec![false, false, false, true]);
+        assert_eq!(
+            shards_manager
+                .requested_partial_encoded_chunks
+                .requests
+                .get(&chunk_hash)
+                .unwrap()
+                .backoff
+                .retries(),
+            4
+        );
+    }
+
     #[cfg(feature = "expensive_tests")]
     #[test]
     fn test_seal_removal() {
@@ -1900,15 +2089,15 @@ mod test {
             )
             .unwrap();
         let header = encoded_chunk.cloned_header();
+        let backoff = shards_manager.requested_partial_encoded_chunks.backoff.clone();
         shards_manager.requested_partial_encoded_chunks.insert(
             header.chunk_hash(),
-            ChunkRequestInfo {
-                height: header.height_created(),
-                parent_hash: header.prev_block_hash(),
-                shard_id: header.shard_id(),
-                last_requested: Clock::instant(),
-                added: Clock::instant(),
-            },
+            ChunkRequestInfo::new(
+                header.height_created(),
+                header.prev_block_hash(),
+                header.shard_id(),
+                &backoff,
+            ),
         );
         shards_manager
             .request_partial_encoded_chunk(
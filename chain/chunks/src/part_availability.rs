@@ -0,0 +1,206 @@
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use near_primitives::sharding::{ChunkHash, ShardChunkHeader};
+use near_primitives::time::Clock;
+use near_primitives::types::{AccountId, BlockHeight, ShardId};
+use near_primitives::views::ChunkPartsAvailabilityView;
+
+/// Number of most recent chunks which part deliveries are kept.
+const PART_AVAILABILITY_CHUNKS: usize = 256;
+
+struct ChunkHeaderInfo {
+    height_created: BlockHeight,
+    shard_id: ShardId,
+    prev_block_hash: CryptoHash,
+}
+
+/// How the parts of a single chunk were delivered to this node.
+struct ChunkPartsAvailability {
+    first_part_received: Instant,
+    /// Peer which first delivered each part, by part ordinal.
+    part_providers: HashMap<u64, PeerId>,
+    /// Not known yet if parts were forwarded before the chunk header arrived.
+    header: Option<ChunkHeaderInfo>,
+    reconstructed: Option<Instant>,
+}
+
+/// Records which peers delivered the parts of the recent chunks and how long it took to get
+/// enough of them to reconstruct the chunk, for data availability research.
+///
+/// Parts are recorded as they are received, before they are validated, so a peer sending
+/// invalid parts is still counted as a provider.
+pub(crate) struct PartAvailabilityTracker {
+    chunks: HashMap<ChunkHash, ChunkPartsAvailability>,
+    /// Chunk hashes from the oldest to the most recently seen.
+    order: VecDeque<ChunkHash>,
+}
+
+impl PartAvailabilityTracker {
+    pub fn new() -> Self {
+        Self { chunks: HashMap::default(), order: VecDeque::default() }
+    }
+
+    pub fn record_parts<I>(&mut self, chunk_hash: &ChunkHash, peer_id: &PeerId, part_ords: I)
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        if !self.chunks.contains_key(chunk_hash) {
+            if self.order.len() >= PART_AVAILABILITY_CHUNKS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.chunks.remove(&oldest);
+                }
+            }
+            self.order.push_back(chunk_hash.clone());
+            self.chunks.insert(
+                chunk_hash.clone(),
+                ChunkPartsAvailability {
+                    first_part_received: Clock::instant(),
+                    part_providers: HashMap::default(),
+                    header: None,
+                    reconstructed: None,
+                },
+            );
+        }
+        let chunk = self.chunks.get_mut(chunk_hash).unwrap();
+        for part_ord in part_ords {
+            chunk.part_providers.entry(part_ord).or_insert_with(|| peer_id.clone());
+        }
+    }
+
+    /// Only chunks which parts were received from peers are tracked.
+    pub fn record_header(&mut self, header: &ShardChunkHeader) {
+        if let Some(chunk) = self.chunks.get_mut(&header.chunk_hash()) {
+            if chunk.header.is_none() {
+                chunk.header = Some(ChunkHeaderInfo {
+                    height_created: header.height_created(),
+                    shard_id: header.shard_id(),
+                    prev_block_hash: header.prev_block_hash(),
+                });
+            }
+        }
+    }
+
+    pub fn record_reconstructed(&mut self, chunk_hash: &ChunkHash) {
+        if let Some(chunk) = self.chunks.get_mut(chunk_hash) {
+            chunk.reconstructed.get_or_insert_with(Clock::instant);
+        }
+    }
+
+    /// Up to `limit` of the most recently seen chunks, newest first. `part_owner` gives the
+    /// validator owning a part of a chunk, from the previous block hash and the part ordinal.
+    pub fn recent<F>(
+        &self,
+        limit: usize,
+        num_total_parts: u64,
+        part_owner: F,
+    ) -> Vec<ChunkPartsAvailabilityView>
+    where
+        F: Fn(&CryptoHash, u64) -> Option<AccountId>,
+    {
+        self.order
+            .iter()
+            .rev()
+            .take(limit)
+            .filter_map(|chunk_hash| {
+                let chunk = self.chunks.get(chunk_hash)?;
+                let num_peers = chunk.part_providers.values().collect::<HashSet<_>>().len();
+                let missing_part_owners = match &chunk.header {
+                    Some(header) => {
+                        missing_part_owners(&chunk.part_providers, num_total_parts, |part_ord| {
+                            part_owner(&header.prev_block_hash, part_ord)
+                        })
+                    }
+                    None => vec![],
+                };
+                Some(ChunkPartsAvailabilityView {
+                    chunk_hash: chunk_hash.0,
+                    height_created: chunk.header.as_ref().map(|header| header.height_created),
+                    shard_id: chunk.header.as_ref().map(|header| header.shard_id),
+                    num_total_parts,
+                    num_parts_received: chunk.part_providers.len() as u64,
+                    num_peers: num_peers as u64,
+                    time_to_reconstruction_ms: chunk.reconstructed.map(|reconstructed| {
+                        reconstructed
+                            .saturating_duration_since(chunk.first_part_received)
+                            .as_millis() as u64
+                    }),
+                    missing_part_owners,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Validators none of which parts were received, sorted.
+fn missing_part_owners<F>(
+    part_providers: &HashMap<u64, PeerId>,
+    num_total_parts: u64,
+    part_owner: F,
+) -> Vec<AccountId>
+where
+    F: Fn(u64) -> Option<AccountId>,
+{
+    let mut seen = HashSet::new();
+    let mut missing = BTreeSet::new();
+    for part_ord in 0..num_total_parts {
+        if let Some(owner) = part_owner(part_ord) {
+            if part_providers.contains_key(&part_ord) {
+                seen.insert(owner);
+            } else {
+                missing.insert(owner);
+            }
+        }
+    }
+    missing.into_iter().filter(|owner| !seen.contains(owner)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::hash::hash;
+
+    fn owner(part_ord: u64) -> Option<AccountId> {
+        Some(["alice", "bob", "carol"][part_ord as usize % 3].parse().unwrap())
+    }
+
+    #[test]
+    fn test_missing_part_owners() {
+        let peer = PeerId::random();
+        // Parts 0 and 3 belong to alice, 1 and 4 to bob, 2 and 5 to carol.
+        let part_providers = vec![(0, peer.clone()), (4, peer)].into_iter().collect();
+        let missing = missing_part_owners(&part_providers, 6, owner);
+        assert_eq!(missing, vec!["carol".parse::<AccountId>().unwrap()]);
+    }
+
+    #[test]
+    fn test_recent_chunks() {
+        let mut tracker = PartAvailabilityTracker::new();
+        let (alice, bob) = (PeerId::random(), PeerId::random());
+        let first = ChunkHash(hash(&[1]));
+        let second = ChunkHash(hash(&[2]));
+        tracker.record_parts(&first, &alice, vec![0, 1]);
+        tracker.record_parts(&first, &bob, vec![1, 2]);
+        tracker.record_reconstructed(&first);
+        tracker.record_parts(&second, &alice, vec![0]);
+
+        let recent = tracker.recent(10, 6, |_, part_ord| owner(part_ord));
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].chunk_hash, second.0);
+        assert_eq!(recent[0].time_to_reconstruction_ms, None);
+        assert_eq!(recent[1].num_parts_received, 3);
+        assert_eq!(recent[1].num_peers, 2);
+        assert!(recent[1].time_to_reconstruction_ms.is_some());
+        // Owners are only known once the header is.
+        assert!(recent[1].missing_part_owners.is_empty());
+        assert_eq!(tracker.recent(1, 6, |_, part_ord| owner(part_ord)).len(), 1);
+
+        for i in 0..PART_AVAILABILITY_CHUNKS {
+            tracker.record_parts(&ChunkHash(hash(&i.to_le_bytes())), &alice, vec![0]);
+        }
+        assert_eq!(tracker.chunks.len(), PART_AVAILABILITY_CHUNKS);
+        assert!(!tracker.chunks.contains_key(&first));
+    }
+}
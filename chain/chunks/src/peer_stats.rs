@@ -0,0 +1,233 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use near_primitives::sharding::ChunkHash;
+use near_primitives::time::Clock;
+use near_primitives::types::AccountId;
+
+/// Weight of the newest sample in the moving average of response latencies.
+const LATENCY_SMOOTHING: f64 = 0.2;
+/// Lower bound of a peer score, so that every candidate keeps a chance to be picked.
+const MIN_PEER_SCORE: f64 = 0.01;
+/// Minimal number of resolved part requests before a peer can be considered unreliable.
+const MIN_RESOLVED_FOR_UNRELIABLE: u64 = 3;
+/// Peers answering less than this share of part requests are considered unreliable.
+const UNRELIABLE_SUCCESS_RATE: f64 = 0.5;
+
+/// How well a single peer has been serving the parts we requested from it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PeerRequestStats {
+    /// Number of parts requested from the peer.
+    pub requested: u64,
+    /// Number of requested parts which arrived before the timeout.
+    pub succeeded: u64,
+    /// Number of requested parts which did not arrive before the timeout.
+    pub failed: u64,
+    /// Moving average of the time it took the peer to deliver a part.
+    pub avg_latency: Option<Duration>,
+}
+
+impl PeerRequestStats {
+    /// Share of resolved part requests which succeeded.
+    /// Peers we know nothing about yet are given the benefit of the doubt.
+    pub fn success_rate(&self) -> f64 {
+        let resolved = self.succeeded + self.failed;
+        if resolved == 0 {
+            1.0
+        } else {
+            self.succeeded as f64 / resolved as f64
+        }
+    }
+
+    /// Score favouring peers which answer reliably and fast, relative to `timeout`.
+    pub fn score(&self, timeout: Duration) -> f64 {
+        let latency = self.avg_latency.map_or(0.0, |latency| latency.as_secs_f64());
+        let timeout = timeout.as_secs_f64().max(f64::EPSILON);
+        (self.success_rate() / (1.0 + latency / timeout)).max(MIN_PEER_SCORE)
+    }
+
+    pub fn is_unreliable(&self) -> bool {
+        self.succeeded + self.failed >= MIN_RESOLVED_FOR_UNRELIABLE
+            && self.success_rate() < UNRELIABLE_SUCCESS_RATE
+    }
+
+    fn record_success(&mut self, latency: Duration) {
+        self.succeeded += 1;
+        self.avg_latency = Some(match self.avg_latency {
+            None => latency,
+            Some(avg) => Duration::from_secs_f64(
+                avg.as_secs_f64() * (1.0 - LATENCY_SMOOTHING)
+                    + latency.as_secs_f64() * LATENCY_SMOOTHING,
+            ),
+        });
+    }
+}
+
+/// Tracks outstanding part requests per chunk and how each peer has been answering them.
+///
+/// Responses do not tell which peer sent them, so a received part is credited to the peer we
+/// asked for it first. Requests for parts which are still missing after `timeout` are counted
+/// as failures of the peer they were sent to.
+pub(crate) struct ChunkRequestPeerStats {
+    timeout: Duration,
+    peers: HashMap<AccountId, PeerRequestStats>,
+    in_flight: HashMap<ChunkHash, HashMap<u64, Vec<(AccountId, Instant)>>>,
+}
+
+impl ChunkRequestPeerStats {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout, peers: HashMap::default(), in_flight: HashMap::default() }
+    }
+
+    pub fn get(&self, account_id: &AccountId) -> Option<&PeerRequestStats> {
+        self.peers.get(account_id)
+    }
+
+    pub fn is_unreliable(&self, account_id: &AccountId) -> bool {
+        self.peers.get(account_id).map_or(false, PeerRequestStats::is_unreliable)
+    }
+
+    pub fn record_request(
+        &mut self,
+        account_id: &AccountId,
+        chunk_hash: &ChunkHash,
+        part_ords: &[u64],
+    ) {
+        let now = Clock::instant();
+        self.peers.entry(account_id.clone()).or_default().requested += part_ords.len() as u64;
+        let chunk_in_flight = self.in_flight.entry(chunk_hash.clone()).or_default();
+        for part_ord in part_ords {
+            chunk_in_flight.entry(*part_ord).or_default().push((account_id.clone(), now));
+        }
+    }
+
+    pub fn record_response<I>(&mut self, chunk_hash: &ChunkHash, part_ords: I)
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let chunk_in_flight = match self.in_flight.get_mut(chunk_hash) {
+            Some(chunk_in_flight) => chunk_in_flight,
+            None => return,
+        };
+        for part_ord in part_ords {
+            // Other peers asked for the same part are neither credited nor blamed.
+            if let Some(requests) = chunk_in_flight.remove(&part_ord) {
+                if let Some((account_id, requested_at)) = requests.into_iter().next() {
+                    self.peers
+                        .entry(account_id)
+                        .or_default()
+                        .record_success(requested_at.elapsed());
+                }
+            }
+        }
+        if chunk_in_flight.is_empty() {
+            self.in_flight.remove(chunk_hash);
+        }
+    }
+
+    /// Drops the outstanding requests for a chunk which is no longer needed.
+    pub fn forget_chunk(&mut self, chunk_hash: &ChunkHash) {
+        self.in_flight.remove(chunk_hash);
+    }
+
+    /// Counts the requests which were not answered within `timeout` as failures.
+    pub fn expire(&mut self) {
+        let timeout = self.timeout;
+        let peers = &mut self.peers;
+        for chunk_in_flight in self.in_flight.values_mut() {
+            for requests in chunk_in_flight.values_mut() {
+                requests.retain(|(account_id, requested_at)| {
+                    if requested_at.elapsed() > timeout {
+                        peers.entry(account_id.clone()).or_default().failed += 1;
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+            chunk_in_flight.retain(|_, requests| !requests.is_empty());
+        }
+        self.in_flight.retain(|_, chunk_in_flight| !chunk_in_flight.is_empty());
+    }
+
+    /// Picks a peer among `candidates`, avoiding the `excluded` ones if possible.
+    /// The choice is random, weighted by the peer scores, so that retries are spread
+    /// across peers while still favouring the responsive ones.
+    pub fn choose_peer(
+        &self,
+        candidates: Vec<AccountId>,
+        excluded: &HashSet<AccountId>,
+    ) -> Option<AccountId> {
+        let (fresh, used): (Vec<_>, Vec<_>) =
+            candidates.into_iter().partition(|account_id| !excluded.contains(account_id));
+        let candidates = if fresh.is_empty() { used } else { fresh };
+        let scores: Vec<f64> = candidates
+            .iter()
+            .map(|account_id| {
+                self.peers.get(account_id).map_or(1.0, |stats| stats.score(self.timeout))
+            })
+            .collect();
+        let mut pick = rand::thread_rng().gen::<f64>() * scores.iter().sum::<f64>();
+        for (account_id, score) in candidates.iter().zip(scores.iter()) {
+            if pick < *score {
+                return Some(account_id.clone());
+            }
+            pick -= score;
+        }
+        candidates.last().cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::hash::hash;
+
+    fn account(id: &str) -> AccountId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_response_credits_first_requested_peer() {
+        let mut stats = ChunkRequestPeerStats::new(Duration::from_secs(60));
+        let chunk_hash = ChunkHash(hash(&[1]));
+        stats.record_request(&account("alice"), &chunk_hash, &[0, 1]);
+        stats.record_request(&account("bob"), &chunk_hash, &[1]);
+        stats.record_response(&chunk_hash, vec![0, 1]);
+
+        let alice = stats.get(&account("alice")).unwrap();
+        assert_eq!((alice.requested, alice.succeeded, alice.failed), (2, 2, 0));
+        assert!(alice.avg_latency.is_some());
+        let bob = stats.get(&account("bob")).unwrap();
+        assert_eq!((bob.requested, bob.succeeded, bob.failed), (1, 0, 0));
+        assert!(stats.in_flight.is_empty());
+    }
+
+    #[test]
+    fn test_expired_requests_mark_peer_unreliable() {
+        let mut stats = ChunkRequestPeerStats::new(Duration::from_millis(0));
+        let chunk_hash = ChunkHash(hash(&[1]));
+        stats.record_request(&account("alice"), &chunk_hash, &[0, 1, 2]);
+        std::thread::sleep(Duration::from_millis(1));
+        stats.expire();
+
+        assert!(stats.is_unreliable(&account("alice")));
+        assert!(!stats.is_unreliable(&account("bob")));
+        assert!(stats.in_flight.is_empty());
+    }
+
+    #[test]
+    fn test_choose_peer_avoids_excluded() {
+        let stats = ChunkRequestPeerStats::new(Duration::from_secs(1));
+        let candidates = vec![account("alice"), account("bob")];
+        let excluded = vec![account("alice")].into_iter().collect();
+        for _ in 0..10 {
+            assert_eq!(stats.choose_peer(candidates.clone(), &excluded), Some(account("bob")));
+        }
+        let excluded = candidates.iter().cloned().collect();
+        assert!(stats.choose_peer(candidates.clone(), &excluded).is_some());
+        assert_eq!(stats.choose_peer(vec![], &HashSet::new()), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Typed async API over the client and view client actors.
+//!
+//! Programs embedding a node in-process get the addresses of the actors from `start_with_config`
+//! and use [`ClientApi`] to read blocks, chunks and state and to submit transactions, instead of
+//! constructing the actor messages and unwrapping the mailbox results themselves.
+
+use std::fmt;
+
+use actix::{Addr, MailboxError};
+
+use near_client::{
+    ClientActor, GetBlock, GetChunk, Query, QueryError, Status, StatusResponse, TxStatus,
+    TxStatusError, ViewClientActor,
+};
+use near_client_primitives::types::{GetBlockError, GetChunkError, StatusError};
+use near_network::types::{NetworkClientMessages, NetworkClientResponses};
+use near_primitives::errors::InvalidTxError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::types::{AccountId, BlockReference};
+use near_primitives::views::{
+    BlockView, ChunkView, FinalExecutionOutcomeViewEnum, QueryRequest, QueryResponse,
+};
+
+/// Error of a [`ClientApi`] call.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// The actor is stopped or its mailbox is full.
+    Mailbox(MailboxError),
+    /// The actor handled the request and returned an error.
+    Handler(E),
+}
+
+impl<E: fmt::Debug> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Mailbox(err) => write!(f, "Client actor is unavailable: {}", err),
+            Error::Handler(err) => write!(f, "{:?}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug> std::error::Error for Error<E> {}
+
+impl<E> From<MailboxError> for Error<E> {
+    fn from(err: MailboxError) -> Self {
+        Error::Mailbox(err)
+    }
+}
+
+/// Reasons for a transaction to be rejected on submission.
+#[derive(Debug)]
+pub enum SubmitTransactionError {
+    InvalidTransaction(InvalidTxError),
+    /// The node does not track the shard of the signer and can't route the transaction.
+    DoesNotTrackShard,
+    Unexpected(String),
+}
+
+/// Handle to the actors of a node running in the same process. Cheap to clone.
+#[derive(Clone)]
+pub struct ClientApi {
+    client_addr: Addr<ClientActor>,
+    view_client_addr: Addr<ViewClientActor>,
+}
+
+impl ClientApi {
+    pub fn new(client_addr: Addr<ClientActor>, view_client_addr: Addr<ViewClientActor>) -> Self {
+        Self { client_addr, view_client_addr }
+    }
+
+    pub async fn status(&self) -> Result<StatusResponse, Error<StatusError>> {
+        self.client_addr.send(Status { is_health_check: false }).await?.map_err(Error::Handler)
+    }
+
+    pub async fn block(
+        &self,
+        block_reference: BlockReference,
+    ) -> Result<BlockView, Error<GetBlockError>> {
+        self.view_client_addr.send(GetBlock(block_reference)).await?.map_err(Error::Handler)
+    }
+
+    pub async fn chunk(&self, chunk: GetChunk) -> Result<ChunkView, Error<GetChunkError>> {
+        self.view_client_addr.send(chunk).await?.map_err(Error::Handler)
+    }
+
+    pub async fn query(
+        &self,
+        block_reference: BlockReference,
+        request: QueryRequest,
+    ) -> Result<QueryResponse, Error<QueryError>> {
+        self.view_client_addr
+            .send(Query::new(block_reference, request))
+            .await?
+            .map_err(Error::Handler)
+    }
+
+    /// Adds the transaction to the pool, or forwards it to the validators tracking its shard.
+    pub async fn submit_transaction(
+        &self,
+        transaction: SignedTransaction,
+    ) -> Result<(), Error<SubmitTransactionError>> {
+        let response = self
+            .client_addr
+            .send(NetworkClientMessages::Transaction {
+                transaction,
+                is_forwarded: false,
+                check_only: false,
+            })
+            .await?;
+        match response {
+            NetworkClientResponses::ValidTx | NetworkClientResponses::RequestRouted => Ok(()),
+            NetworkClientResponses::InvalidTx(err) => {
+                Err(Error::Handler(SubmitTransactionError::InvalidTransaction(err)))
+            }
+            NetworkClientResponses::DoesNotTrackShard => {
+                Err(Error::Handler(SubmitTransactionError::DoesNotTrackShard))
+            }
+            response => {
+                Err(Error::Handler(SubmitTransactionError::Unexpected(format!("{:?}", response))))
+            }
+        }
+    }
+
+    /// Outcome of the transaction, `None` if it is not executed yet.
+    pub async fn tx_status(
+        &self,
+        tx_hash: CryptoHash,
+        signer_account_id: AccountId,
+    ) -> Result<Option<FinalExecutionOutcomeViewEnum>, Error<TxStatusError>> {
+        self.view_client_addr
+            .send(TxStatus { tx_hash, signer_account_id, fetch_receipt: false })
+            .await?
+            .map_err(Error::Handler)
+    }
+}
@@ -5,24 +5,36 @@ use std::sync::Arc;
 use actix::Message;
 use chrono::DateTime;
 use near_primitives::time::Utc;
+use serde::Serialize;
 
 use near_chain_configs::ProtocolConfigView;
-use near_network_primitives::types::{AccountOrPeerIdOrHash, KnownProducer, PeerInfo};
+use near_crypto::PublicKey;
+use near_network_primitives::types::{
+    AccountOrPeerIdOrHash, KnownProducer, PeerInfo, PeerScore, PeerTransferStats,
+};
+use near_primitives::backoff::Backoff;
+use near_primitives::cancellation::CancellationToken;
 use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
 use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::{Action, SignedTransaction};
 use near_primitives::types::{
-    AccountId, BlockHeight, BlockReference, EpochReference, MaybeBlockId, ShardId,
-    TransactionOrReceiptId,
+    AccountId, BlockHeight, BlockId, BlockReference, EpochReference, MaybeBlockId, ProtocolVersion,
+    ShardId, TransactionOrReceiptId,
 };
 use near_primitives::utils::generate_random_string;
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
-    QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView, StateChangesRequestView,
-    StateChangesView,
+    AccessKeyUsageView, AccountSummaryView, BlockRefundStatsView, BlockTransactionStatsView,
+    BlockView, ChunkPartsAvailabilityView, ChunkView, ClockSkewReportView, ContractDeploymentView,
+    DebugChainProcessingStatusView, DebugNetworkInfoView, DebugSyncStatusView,
+    DebugValidatorStatusView, EpochChainStatsView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    FailedReceiptView, FinalExecutionOutcomeViewEnum, GasPriceView, GasThroughputView,
+    LightClientBlockLiteView, LightClientBlockView, PoolStatusView, PoolTransactionView,
+    ProtocolUpgradeStatusView, QueryRequest, QueryResponse, ReceiptInclusionProofView, ReceiptView,
+    ShardSyncDownloadView, SimulateTransactionView, StateChangesKindsView, StateChangesRequestView,
+    StateChangesView, SyncStatusView, TransactionFeesView,
 };
 pub use near_primitives::views::{StatusResponse, StatusSyncInfo};
 
@@ -84,6 +96,8 @@ pub struct DownloadStatus {
     pub done: bool,
     pub state_requests_count: u64,
     pub last_target: Option<AccountOrPeerIdOrHash>,
+    /// Resends of the request while it isn't answered.
+    pub backoff: Backoff,
 }
 
 impl Clone for DownloadStatus {
@@ -96,6 +110,7 @@ impl Clone for DownloadStatus {
             done: self.done,
             state_requests_count: self.state_requests_count,
             last_target: self.last_target.clone(),
+            backoff: self.backoff.clone(),
         }
     }
 }
@@ -155,6 +170,44 @@ impl SyncStatus {
     }
 }
 
+impl From<&ShardSyncDownload> for ShardSyncDownloadView {
+    fn from(download: &ShardSyncDownload) -> Self {
+        Self {
+            status: format!("{:?}", download.status),
+            num_downloads: download.downloads.len() as u64,
+            num_downloads_done: download.downloads.iter().filter(|d| d.done).count() as u64,
+            num_downloads_failed: download.downloads.iter().filter(|d| d.error).count() as u64,
+        }
+    }
+}
+
+impl From<&SyncStatus> for SyncStatusView {
+    fn from(status: &SyncStatus) -> Self {
+        match status {
+            SyncStatus::AwaitingPeers => SyncStatusView::AwaitingPeers,
+            SyncStatus::NoSync => SyncStatusView::NoSync,
+            SyncStatus::EpochSync { epoch_ord } => {
+                SyncStatusView::EpochSync { epoch_ord: *epoch_ord }
+            }
+            SyncStatus::HeaderSync { current_height, highest_height } => {
+                SyncStatusView::HeaderSync {
+                    current_height: *current_height,
+                    highest_height: *highest_height,
+                }
+            }
+            SyncStatus::StateSync(sync_hash, shards) => SyncStatusView::StateSync(
+                *sync_hash,
+                shards.iter().map(|(shard_id, download)| (*shard_id, download.into())).collect(),
+            ),
+            SyncStatus::StateSyncDone => SyncStatusView::StateSyncDone,
+            SyncStatus::BodySync { current_height, highest_height } => SyncStatusView::BodySync {
+                current_height: *current_height,
+                highest_height: *highest_height,
+            },
+        }
+    }
+}
+
 /// Actor message requesting block by id or hash.
 pub struct GetBlock(pub BlockReference);
 
@@ -166,6 +219,10 @@ pub enum GetBlockError {
     UnknownBlock { error_message: String },
     #[error("There are no fully synchronized blocks yet")]
     NotSyncedYet,
+    #[error("Block #{block_height} has been garbage collected, the node keeps the blocks from #{tail_height}")]
+    GarbageCollected { block_height: BlockHeight, tail_height: BlockHeight },
+    #[error("Block #{block_height} has not been processed yet, the head of the node is at #{head_height}")]
+    NotProcessedYet { block_height: BlockHeight, head_height: BlockHeight },
     // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
     // expected cases, we cannot statically guarantee that no other errors will be returned
     // in the future.
@@ -174,6 +231,19 @@ pub enum GetBlockError {
     Unreachable { error_message: String },
 }
 
+impl GetBlockError {
+    /// Whether the same request may succeed later on this node, e.g. once it's synced or has
+    /// processed the block, unlike a request for a block it garbage collected or never saw.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::IOError { .. } | Self::NotSyncedYet | Self::NotProcessedYet { .. } => true,
+            Self::UnknownBlock { .. }
+            | Self::GarbageCollected { .. }
+            | Self::Unreachable { .. } => false,
+        }
+    }
+}
+
 impl From<near_chain_primitives::Error> for GetBlockError {
     fn from(error: near_chain_primitives::Error) -> Self {
         match error.kind() {
@@ -232,6 +302,12 @@ pub enum GetChunkError {
     InvalidShardId { shard_id: u64 },
     #[error("Chunk with hash {chunk_hash:?} has never been observed on this node")]
     UnknownChunk { chunk_hash: ChunkHash },
+    #[error("The node does not track the shard ID {shard_id}")]
+    UnavailableShard { shard_id: ShardId },
+    #[error("Block #{block_height} has been garbage collected, the node keeps the blocks from #{tail_height}")]
+    GarbageCollected { block_height: BlockHeight, tail_height: BlockHeight },
+    #[error("Block #{block_height} has not been processed yet, the head of the node is at #{head_height}")]
+    NotProcessedYet { block_height: BlockHeight, head_height: BlockHeight },
     // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
     // expected cases, we cannot statically guarantee that no other errors will be returned
     // in the future.
@@ -240,6 +316,39 @@ pub enum GetChunkError {
     Unreachable { error_message: String },
 }
 
+impl GetChunkError {
+    /// Whether the same request may succeed later on this node, see `GetBlockError::is_retryable`.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::IOError { .. } | Self::NotProcessedYet { .. } => true,
+            Self::UnknownBlock { .. }
+            | Self::InvalidShardId { .. }
+            | Self::UnknownChunk { .. }
+            | Self::UnavailableShard { .. }
+            | Self::GarbageCollected { .. }
+            | Self::Unreachable { .. } => false,
+        }
+    }
+}
+
+impl From<GetBlockError> for GetChunkError {
+    fn from(error: GetBlockError) -> Self {
+        match error {
+            GetBlockError::IOError { error_message } => Self::IOError { error_message },
+            GetBlockError::UnknownBlock { error_message } => Self::UnknownBlock { error_message },
+            GetBlockError::GarbageCollected { block_height, tail_height } => {
+                Self::GarbageCollected { block_height, tail_height }
+            }
+            GetBlockError::NotProcessedYet { block_height, head_height } => {
+                Self::NotProcessedYet { block_height, head_height }
+            }
+            GetBlockError::NotSyncedYet | GetBlockError::Unreachable { .. } => {
+                Self::Unreachable { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
 impl From<near_chain_primitives::Error> for GetChunkError {
     fn from(error: near_chain_primitives::Error) -> Self {
         match error.kind() {
@@ -266,11 +375,18 @@ pub struct Query {
     pub query_id: String,
     pub block_reference: BlockReference,
     pub request: QueryRequest,
+    /// Cancelled when the client of the query went away, to stop working on it.
+    pub cancellation: CancellationToken,
 }
 
 impl Query {
     pub fn new(block_reference: BlockReference, request: QueryRequest) -> Self {
-        Query { query_id: generate_random_string(10), block_reference, request }
+        Query {
+            query_id: generate_random_string(10),
+            block_reference,
+            request,
+            cancellation: CancellationToken::new(),
+        }
     }
 }
 
@@ -378,6 +494,26 @@ impl Message for Status {
     type Result = Result<StatusResponse, StatusError>;
 }
 
+/// State of the node the readiness criteria of `/readyz` are checked against.
+pub struct Readiness {
+    /// Whether to write a probe key to the store, to check that it accepts writes.
+    pub check_store_writable: bool,
+}
+
+#[derive(Debug)]
+pub struct ReadinessResponse {
+    pub head_height: near_primitives::types::BlockHeight,
+    /// Highest height of the peers, `None` without peers.
+    pub highest_peer_height: Option<near_primitives::types::BlockHeight>,
+    pub num_peers: usize,
+    /// `None` unless checked.
+    pub store_writable: Option<bool>,
+}
+
+impl Message for Readiness {
+    type Result = Result<ReadinessResponse, StatusError>;
+}
+
 pub struct GetNextLightClientBlock {
     pub last_block_hash: CryptoHash,
 }
@@ -425,6 +561,122 @@ impl Message for GetNetworkInfo {
     type Result = Result<NetworkInfoResponse, String>;
 }
 
+/// Estimate of the gas throughput this node sustains when applying chunks.
+pub struct GetGasThroughput {}
+
+impl Message for GetGasThroughput {
+    type Result = Result<GasThroughputView, String>;
+}
+
+/// Size and age of the transactions in the transaction pools of this node.
+pub struct GetPoolStatus {}
+
+impl Message for GetPoolStatus {
+    type Result = Result<PoolStatusView, String>;
+}
+
+/// Transactions in the transaction pools of this node, oldest first.
+pub struct GetPoolTransactions {
+    /// All shards if not set.
+    pub shard_id: Option<ShardId>,
+    pub limit: usize,
+}
+
+impl Message for GetPoolTransactions {
+    type Result = Result<Vec<PoolTransactionView>, String>;
+}
+
+/// How the parts of the most recent chunks were delivered to this node, newest first.
+pub struct GetChunkPartsAvailability {
+    pub limit: usize,
+}
+
+impl Message for GetChunkPartsAvailability {
+    type Result = Result<Vec<ChunkPartsAvailabilityView>, String>;
+}
+
+/// Block producers ranked by the clock skew observed from their recent blocks.
+pub struct GetClockSkewReport {}
+
+impl Message for GetClockSkewReport {
+    type Result = Result<ClockSkewReportView, String>;
+}
+
+/// Debug information of the client, a page of the debug pages each.
+pub enum DebugStatus {
+    SyncStatus,
+    ChainProcessingStatus,
+    NetworkInfo,
+    ValidatorStatus,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum DebugStatusResponse {
+    SyncStatus(DebugSyncStatusView),
+    ChainProcessingStatus(DebugChainProcessingStatusView),
+    NetworkInfo(DebugNetworkInfoView),
+    ValidatorStatus(DebugValidatorStatusView),
+}
+
+impl Message for DebugStatus {
+    type Result = Result<DebugStatusResponse, StatusError>;
+}
+
+/// Which protocol version the blocks produced by this node vote for.
+pub struct GetProtocolUpgradeStatus {}
+
+impl Message for GetProtocolUpgradeStatus {
+    type Result = Result<ProtocolUpgradeStatusView, ProtocolUpgradeError>;
+}
+
+/// Lets the blocks produced by this node vote for the protocol version of the binary, which
+/// must be the one given.
+pub struct AcknowledgeProtocolUpgrade {
+    pub protocol_version: ProtocolVersion,
+}
+
+impl Message for AcknowledgeProtocolUpgrade {
+    type Result = Result<ProtocolUpgradeStatusView, ProtocolUpgradeError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ProtocolUpgradeError {
+    #[error(
+        "The binary supports protocol version {binary_protocol_version}, not {protocol_version}"
+    )]
+    UnsupportedProtocolVersion {
+        binary_protocol_version: ProtocolVersion,
+        protocol_version: ProtocolVersion,
+    },
+    #[error("IO Error: {error_message}")]
+    IOError { error_message: String },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for ProtocolUpgradeError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::IOError { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+/// Has the network read the ranges of IPs of the peers again from the config file.
+pub struct ReloadPeerRanges {}
+
+impl Message for ReloadPeerRanges {
+    type Result = Result<(), String>;
+}
+
 pub struct GetGasPrice {
     pub block_id: MaybeBlockId,
 }
@@ -461,6 +713,317 @@ impl From<near_chain_primitives::Error> for GetGasPriceError {
     }
 }
 
+/// Maximum number of blocks which statistics can be requested at once.
+pub const MAX_BLOCK_TRANSACTION_STATS_RANGE: BlockHeight = 100;
+
+/// Transaction statistics of the canonical blocks with heights in `[from_height, to_height]`.
+pub struct GetBlockTransactionStats {
+    pub from_height: BlockHeight,
+    pub to_height: BlockHeight,
+}
+
+impl Message for GetBlockTransactionStats {
+    type Result = Result<Vec<BlockTransactionStatsView>, GetBlockTransactionStatsError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetBlockTransactionStatsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Invalid range of heights from {from_height} to {to_height}, at most {max_range} blocks can be requested")]
+    InvalidRange { from_height: BlockHeight, to_height: BlockHeight, max_range: BlockHeight },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetBlockTransactionStatsError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+/// Maximum number of epochs which statistics can be requested at once.
+pub const MAX_EPOCH_CHAIN_STATS_LIMIT: u64 = 100;
+
+/// Statistics of the last `limit` epochs recorded, oldest first.
+pub struct GetEpochChainStats {
+    pub limit: u64,
+}
+
+impl Message for GetEpochChainStats {
+    type Result = Result<Vec<EpochChainStatsView>, GetEpochChainStatsError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetEpochChainStatsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Invalid limit {limit}, at most {max_limit} epochs can be requested")]
+    InvalidLimit { limit: u64, max_limit: u64 },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetEpochChainStatsError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+/// Dry runs the transaction on the state at the block, see `RuntimeAdapter::simulate_transaction`.
+pub struct SimulateTransaction {
+    pub block_reference: BlockReference,
+    pub transaction: SignedTransaction,
+    /// Whether the signature of the transaction is checked, e.g. not for unsigned transactions.
+    pub verify_signature: bool,
+}
+
+impl Message for SimulateTransaction {
+    type Result = Result<SimulateTransactionView, SimulateTransactionError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SimulateTransactionError {
+    #[error("IO Error: {error_message}")]
+    IOError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock { error_message: String },
+    #[error("The node does not track the shard ID {shard_id} of the signer")]
+    UnavailableShard { shard_id: ShardId },
+    #[error("Invalid transaction: {error:?}")]
+    InvalidTransaction { error: InvalidTxError },
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<GetBlockError> for SimulateTransactionError {
+    fn from(error: GetBlockError) -> Self {
+        match error {
+            GetBlockError::IOError { error_message } => Self::IOError { error_message },
+            GetBlockError::UnknownBlock { error_message } => Self::UnknownBlock { error_message },
+            GetBlockError::GarbageCollected { .. } | GetBlockError::NotProcessedYet { .. } => {
+                Self::UnknownBlock { error_message: error.to_string() }
+            }
+            GetBlockError::NotSyncedYet | GetBlockError::Unreachable { .. } => {
+                Self::Unreachable { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<near_chain_primitives::Error> for SimulateTransactionError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::IOError { error_message }
+            }
+            near_chain_primitives::ErrorKind::DBNotFoundErr(error_message) => {
+                Self::UnknownBlock { error_message }
+            }
+            near_chain_primitives::ErrorKind::StorageError(error) => {
+                Self::InternalError { error_message: error.to_string() }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+/// Protocol fees of the actions sent by the signer to the receiver, and the gas they burn when run
+/// on the state at the block, see `TransactionFeesView`.
+pub struct EstimateTransactionFees {
+    pub block_reference: BlockReference,
+    pub signer_id: AccountId,
+    /// Access key of the transaction, the first full access key of the signer if not given.
+    pub public_key: Option<PublicKey>,
+    pub receiver_id: AccountId,
+    pub actions: Vec<Action>,
+}
+
+impl Message for EstimateTransactionFees {
+    type Result = Result<TransactionFeesView, EstimateTransactionFeesError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EstimateTransactionFeesError {
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock { error_message: String },
+    #[error("The node does not track the shard ID {shard_id} of the signer")]
+    UnavailableShard { shard_id: ShardId },
+    #[error("Account {account_id} does not exist while viewing at block #{block_height}")]
+    UnknownAccount { account_id: AccountId, block_height: BlockHeight },
+    #[error("Access key for public key {public_key} does not exist while viewing at block #{block_height}")]
+    UnknownAccessKey { public_key: PublicKey, block_height: BlockHeight },
+    #[error("Account {account_id} has no full access key")]
+    NoFullAccessKey { account_id: AccountId },
+    #[error("Invalid transaction: {error:?}")]
+    InvalidTransaction { error: InvalidTxError },
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<SimulateTransactionError> for EstimateTransactionFeesError {
+    fn from(error: SimulateTransactionError) -> Self {
+        match error {
+            SimulateTransactionError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            SimulateTransactionError::UnavailableShard { shard_id } => {
+                Self::UnavailableShard { shard_id }
+            }
+            SimulateTransactionError::InvalidTransaction { error } => {
+                Self::InvalidTransaction { error }
+            }
+            SimulateTransactionError::IOError { error_message }
+            | SimulateTransactionError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            SimulateTransactionError::Unreachable { error_message } => {
+                Self::Unreachable { error_message }
+            }
+        }
+    }
+}
+
+impl From<QueryError> for EstimateTransactionFeesError {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { shard_id: requested_shard_id }
+            }
+            QueryError::UnknownAccount { requested_account_id, block_height, .. } => {
+                Self::UnknownAccount { account_id: requested_account_id, block_height }
+            }
+            QueryError::UnknownAccessKey { public_key, block_height, .. } => {
+                Self::UnknownAccessKey { public_key, block_height }
+            }
+            QueryError::UnknownBlock { .. } => {
+                Self::UnknownBlock { error_message: error.to_string() }
+            }
+            QueryError::InternalError { error_message } => Self::InternalError { error_message },
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+impl From<near_chain_primitives::Error> for EstimateTransactionFeesError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+/// Balances, access keys and lockup of an account at the block, see `AccountSummaryView`.
+pub struct GetAccountSummary {
+    pub block_reference: BlockReference,
+    pub account_id: AccountId,
+    /// Factory of the lockups, `lockup.<top-level account>` if not given, e.g. `lockup.near` for
+    /// `alice.near`.
+    pub lockup_factory_id: Option<AccountId>,
+}
+
+impl Message for GetAccountSummary {
+    type Result = Result<AccountSummaryView, GetAccountSummaryError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetAccountSummaryError {
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock { error_message: String },
+    #[error("The node does not track the shard ID {shard_id}")]
+    UnavailableShard { shard_id: ShardId },
+    #[error("Account {account_id} does not exist while viewing at block #{block_height}")]
+    UnknownAccount { account_id: AccountId, block_height: BlockHeight },
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<GetBlockError> for GetAccountSummaryError {
+    fn from(error: GetBlockError) -> Self {
+        match error {
+            GetBlockError::IOError { error_message } => Self::InternalError { error_message },
+            GetBlockError::UnknownBlock { error_message } => Self::UnknownBlock { error_message },
+            GetBlockError::GarbageCollected { .. } | GetBlockError::NotProcessedYet { .. } => {
+                Self::UnknownBlock { error_message: error.to_string() }
+            }
+            GetBlockError::NotSyncedYet | GetBlockError::Unreachable { .. } => {
+                Self::Unreachable { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<QueryError> for GetAccountSummaryError {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::UnavailableShard { requested_shard_id } => {
+                Self::UnavailableShard { shard_id: requested_shard_id }
+            }
+            QueryError::UnknownAccount { requested_account_id, block_height, .. } => {
+                Self::UnknownAccount { account_id: requested_account_id, block_height }
+            }
+            QueryError::UnknownBlock { .. } => {
+                Self::UnknownBlock { error_message: error.to_string() }
+            }
+            QueryError::InternalError { error_message } => Self::InternalError { error_message },
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+impl From<near_chain_primitives::Error> for GetAccountSummaryError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            near_chain_primitives::ErrorKind::DBNotFoundErr(error_message) => {
+                Self::UnknownBlock { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct NetworkInfoResponse {
     pub active_peers: Vec<PeerInfo>,
@@ -468,6 +1031,10 @@ pub struct NetworkInfoResponse {
     pub peer_max_count: u32,
     pub sent_bytes_per_sec: u64,
     pub received_bytes_per_sec: u64,
+    /// Transfers to and from every active peer over the last minute, 5 minutes and hour.
+    pub peer_transfer_stats: Vec<PeerTransferStats>,
+    /// Scores of the peers which misbehaved recently, highest first.
+    pub peer_scores: Vec<PeerScore>,
     /// Accounts of known block and chunk producers from routing table.
     pub known_producers: Vec<KnownProducer>,
 }
@@ -753,6 +1320,191 @@ impl Message for GetReceipt {
     type Result = Result<Option<ReceiptView>, GetReceiptError>;
 }
 
+/// Merkle proof that a receipt was sent by one of the chunks of the given block.
+pub struct GetReceiptInclusionProof {
+    pub receipt_id: CryptoHash,
+    pub block_id: BlockId,
+}
+
+impl Message for GetReceiptInclusionProof {
+    type Result = Result<ReceiptInclusionProofView, GetReceiptInclusionProofError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetReceiptInclusionProofError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock { error_message: String },
+    #[error("Receipt {receipt_id} was not sent to a shard tracked by the node by the chunks of block {block_hash}")]
+    ReceiptNotInBlock { receipt_id: CryptoHash, block_hash: CryptoHash },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetReceiptInclusionProofError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            near_chain_primitives::ErrorKind::DBNotFoundErr(error_message) => {
+                Self::UnknownBlock { error_message }
+            }
+            near_chain_primitives::ErrorKind::InvalidChunkReceiptsRoot => {
+                Self::InternalError { error_message: error.to_string() }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+/// Receipts which execution failed in the chunks of the given block.
+pub struct GetFailedReceipts {
+    pub block_id: BlockId,
+}
+
+impl Message for GetFailedReceipts {
+    type Result = Result<Vec<FailedReceiptView>, GetFailedReceiptsError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetFailedReceiptsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock { error_message: String },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetFailedReceiptsError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            near_chain_primitives::ErrorKind::DBNotFoundErr(error_message) => {
+                Self::UnknownBlock { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+/// Refund receipts produced by the chunks included in the given block.
+pub struct GetRefundStats {
+    pub block_id: BlockId,
+}
+
+impl Message for GetRefundStats {
+    type Result = Result<BlockRefundStatsView, GetRefundStatsError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetRefundStatsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock { error_message: String },
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetRefundStatsError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            near_chain_primitives::ErrorKind::DBNotFoundErr(error_message) => {
+                Self::UnknownBlock { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+pub struct GetAccessKeyUsage {
+    pub account_id: AccountId,
+}
+
+impl Message for GetAccessKeyUsage {
+    type Result = Result<Vec<AccessKeyUsageView>, GetAccessKeyUsageError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetAccessKeyUsageError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("The node doesn't save the usage of access keys")]
+    NotSaved,
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetAccessKeyUsageError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
+pub struct GetContractHistory {
+    pub account_id: AccountId,
+    /// Whether to compare every deployment with the previous one.
+    pub with_diff: bool,
+}
+
+impl Message for GetContractHistory {
+    type Result = Result<Vec<ContractDeploymentView>, GetContractHistoryError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GetContractHistoryError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("The node doesn't save the history of contracts")]
+    NotSaved,
+    // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
+    // expected cases, we cannot statically guarantee that no other errors will be returned
+    // in the future.
+    // TODO #3851: Remove this variant once we can exhaustively match all the underlying errors
+    #[error("It is a bug if you receive this error type, please, report this incident: https://github.com/near/nearcore/issues/new/choose. Details: {error_message}")]
+    Unreachable { error_message: String },
+}
+
+impl From<near_chain_primitives::Error> for GetContractHistoryError {
+    fn from(error: near_chain_primitives::Error) -> Self {
+        match error.kind() {
+            near_chain_primitives::ErrorKind::IOErr(error_message) => {
+                Self::InternalError { error_message }
+            }
+            _ => Self::Unreachable { error_message: error.to_string() },
+        }
+    }
+}
+
 pub struct GetProtocolConfig(pub BlockReference);
 
 impl Message for GetProtocolConfig {
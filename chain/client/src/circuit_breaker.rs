@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use log::{error, info};
+
+use near_chain_configs::CircuitBreakerConfig;
+use near_network::types::FullPeerInfo;
+use near_primitives::block::Block;
+use near_primitives::sharding::ChunkHash;
+use near_primitives::types::{BlockHeight, ShardId};
+
+use crate::metrics;
+
+/// Anomaly which made the circuit breaker stop signing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitBreakerTrip {
+    /// Most of the peers are far ahead of our head.
+    MinorityFork { head_height: BlockHeight, peers_ahead: usize, num_peers: usize },
+    /// Timestamp of a block received from the network is too far from our clock.
+    ClockSkew { skew: Duration },
+    /// Our chunks were repeatedly left out of the blocks at their height.
+    ChunksRejected { consecutive: u64 },
+}
+
+impl fmt::Display for CircuitBreakerTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitBreakerTrip::MinorityFork { head_height, peers_ahead, num_peers } => write!(
+                f,
+                "{} out of {} peers are ahead of head at height {}, likely on a minority fork",
+                peers_ahead, num_peers, head_height
+            ),
+            CircuitBreakerTrip::ClockSkew { skew } => {
+                write!(f, "clock skew of {:?} with the network", skew)
+            }
+            CircuitBreakerTrip::ChunksRejected { consecutive } => {
+                write!(f, "{} consecutive own chunks were not included in blocks", consecutive)
+            }
+        }
+    }
+}
+
+/// Pauses block, chunk and approval signing when the node detects that what it produces is
+/// likely to be useless or slashable. Signing resumes once all the anomalies are gone.
+#[derive(Default)]
+pub(crate) struct CircuitBreaker {
+    config: Option<CircuitBreakerConfig>,
+    minority_fork: Option<CircuitBreakerTrip>,
+    clock_skew: Option<CircuitBreakerTrip>,
+    chunks_rejected: Option<CircuitBreakerTrip>,
+    consecutive_rejected_chunks: u64,
+    /// Height of the last block which left out one of our chunks.
+    last_rejected_chunk_height: Option<BlockHeight>,
+    /// Chunks we produced, which weren't yet checked for inclusion.
+    produced_chunks: BTreeMap<BlockHeight, Vec<(ShardId, ChunkHash)>>,
+    tripped: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: Option<CircuitBreakerConfig>) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    /// Whether signing is currently allowed.
+    pub fn allows_signing(&self) -> bool {
+        !self.tripped
+    }
+
+    /// Anomalies currently detected.
+    pub fn trips(&self) -> Vec<CircuitBreakerTrip> {
+        self.minority_fork
+            .iter()
+            .chain(self.clock_skew.iter())
+            .chain(self.chunks_rejected.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Compares our head with the heights reported by the peers.
+    pub fn check_peers(&mut self, head_height: BlockHeight, peers: &[FullPeerInfo]) {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return,
+        };
+        let num_peers = peers.len();
+        let peers_ahead = peers
+            .iter()
+            .filter(|peer| peer.chain_info.height > head_height + config.max_height_lag)
+            .count();
+        self.minority_fork = if num_peers >= config.min_peers && peers_ahead * 2 > num_peers {
+            Some(CircuitBreakerTrip::MinorityFork { head_height, peers_ahead, num_peers })
+        } else {
+            None
+        };
+        self.update();
+    }
+
+    /// Compares the timestamp of a new head received from the network with our clock.
+    pub fn check_block_timestamp(&mut self, block_timestamp: DateTime<Utc>, now: DateTime<Utc>) {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return,
+        };
+        let skew = (now - block_timestamp)
+            .to_std()
+            .unwrap_or_else(|_| (block_timestamp - now).to_std().unwrap_or(Duration::from_secs(0)));
+        self.clock_skew = if skew > config.max_clock_skew {
+            Some(CircuitBreakerTrip::ClockSkew { skew })
+        } else {
+            None
+        };
+        self.update();
+    }
+
+    /// Remembers a chunk we produced to check later whether it was included.
+    pub fn record_produced_chunk(
+        &mut self,
+        height: BlockHeight,
+        shard_id: ShardId,
+        chunk_hash: ChunkHash,
+    ) {
+        if self.config.is_none() {
+            return;
+        }
+        self.produced_chunks.entry(height).or_default().push((shard_id, chunk_hash));
+    }
+
+    /// Checks whether the chunks we produced for the height of a new head were included.
+    /// Chunks for heights which were skipped are forgotten without being counted.
+    pub fn check_block_chunks(&mut self, block: &Block) {
+        let height = block.header().height();
+        self.check_chunks(height, |shard_id, chunk_hash| {
+            block.chunks().get(shard_id as usize).map_or(false, |chunk| {
+                chunk.height_included() == height && &chunk.chunk_hash() == chunk_hash
+            })
+        });
+    }
+
+    fn check_chunks(
+        &mut self,
+        height: BlockHeight,
+        is_included: impl Fn(ShardId, &ChunkHash) -> bool,
+    ) {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return,
+        };
+        let remaining = self.produced_chunks.split_off(&(height + 1));
+        let produced = std::mem::replace(&mut self.produced_chunks, remaining);
+        for (shard_id, chunk_hash) in produced.get(&height).into_iter().flatten() {
+            if is_included(*shard_id, chunk_hash) {
+                self.consecutive_rejected_chunks = 0;
+            } else {
+                self.consecutive_rejected_chunks += 1;
+                self.last_rejected_chunk_height = Some(height);
+            }
+        }
+        // While tripped, no chunks are produced which could reset the count: it is cleared once
+        // no chunk has been rejected for a while.
+        if self.last_rejected_chunk_height.map_or(false, |last_rejected_height| {
+            height >= last_rejected_height + config.rejected_chunks_reset_blocks
+        }) {
+            self.consecutive_rejected_chunks = 0;
+            self.last_rejected_chunk_height = None;
+        }
+        self.chunks_rejected = if self.consecutive_rejected_chunks >= config.max_rejected_chunks {
+            Some(CircuitBreakerTrip::ChunksRejected {
+                consecutive: self.consecutive_rejected_chunks,
+            })
+        } else {
+            None
+        };
+        self.update();
+    }
+
+    fn update(&mut self) {
+        let trips = self.trips();
+        let tripped = !trips.is_empty();
+        if tripped && !self.tripped {
+            for trip in trips.iter() {
+                error!(target: "client", "Circuit breaker tripped, pausing signing: {}", trip);
+            }
+        } else if !tripped && self.tripped {
+            info!(target: "client", "Circuit breaker reset, resuming signing");
+        }
+        self.tripped = tripped;
+        metrics::CIRCUIT_BREAKER_TRIPPED.set(tripped as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_network::routing::PartialEdgeInfo;
+    use near_network_primitives::types::{PeerChainInfoV2, PeerInfo};
+    use near_primitives::block::GenesisId;
+    use near_primitives::hash::hash;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            max_clock_skew: Duration::from_secs(5),
+            max_rejected_chunks: 2,
+            rejected_chunks_reset_blocks: 5,
+            max_height_lag: 10,
+            min_peers: 2,
+        }
+    }
+
+    #[test]
+    fn test_disabled_never_trips() {
+        let mut breaker = CircuitBreaker::new(None);
+        let now = Utc::now();
+        breaker.check_block_timestamp(now - chrono::Duration::hours(1), now);
+        assert!(breaker.allows_signing());
+    }
+
+    #[test]
+    fn test_clock_skew() {
+        let mut breaker = CircuitBreaker::new(Some(config()));
+        let now = Utc::now();
+        breaker.check_block_timestamp(now + chrono::Duration::seconds(1), now);
+        assert!(breaker.allows_signing());
+        breaker.check_block_timestamp(now + chrono::Duration::seconds(6), now);
+        assert!(!breaker.allows_signing());
+        assert_eq!(
+            breaker.trips(),
+            vec![CircuitBreakerTrip::ClockSkew { skew: Duration::from_secs(6) }]
+        );
+        breaker.check_block_timestamp(now - chrono::Duration::seconds(1), now);
+        assert!(breaker.allows_signing());
+    }
+
+    fn peer(height: BlockHeight) -> FullPeerInfo {
+        FullPeerInfo {
+            peer_info: PeerInfo::random(),
+            chain_info: PeerChainInfoV2 {
+                genesis_id: GenesisId::default(),
+                height,
+                tracked_shards: vec![],
+                archival: false,
+            },
+            partial_edge_info: PartialEdgeInfo::default(),
+        }
+    }
+
+    #[test]
+    fn test_minority_fork() {
+        let mut breaker = CircuitBreaker::new(Some(config()));
+        breaker.check_peers(100, &[peer(200)]);
+        assert!(breaker.allows_signing());
+        breaker.check_peers(100, &[peer(105), peer(200), peer(105)]);
+        assert!(breaker.allows_signing());
+        breaker.check_peers(100, &[peer(111), peer(200), peer(105)]);
+        assert!(!breaker.allows_signing());
+        assert_eq!(
+            breaker.trips(),
+            vec![CircuitBreakerTrip::MinorityFork {
+                head_height: 100,
+                peers_ahead: 2,
+                num_peers: 3
+            }]
+        );
+        breaker.check_peers(195, &[peer(200), peer(200), peer(105)]);
+        assert!(breaker.allows_signing());
+    }
+
+    #[test]
+    fn test_chunks_rejected() {
+        let mut breaker = CircuitBreaker::new(Some(config()));
+        let chunk_hash = |height: BlockHeight| ChunkHash(hash(&height.to_le_bytes()));
+        for height in 1..=3 {
+            breaker.record_produced_chunk(height, 0, chunk_hash(height));
+        }
+        breaker.check_chunks(1, |_, _| false);
+        assert!(breaker.allows_signing());
+        breaker.check_chunks(2, |_, chunk| chunk == &chunk_hash(2));
+        breaker.check_chunks(3, |_, _| false);
+        assert!(breaker.allows_signing());
+
+        for height in 4..=5 {
+            breaker.record_produced_chunk(height, 0, chunk_hash(height));
+            breaker.check_chunks(height, |_, _| false);
+        }
+        assert!(!breaker.allows_signing());
+        assert_eq!(breaker.trips(), vec![CircuitBreakerTrip::ChunksRejected { consecutive: 3 }]);
+
+        // No chunks are produced while tripped, the trip is cleared once none was rejected for
+        // `rejected_chunks_reset_blocks`.
+        for height in 6..10 {
+            breaker.check_chunks(height, |_, _| false);
+            assert!(!breaker.allows_signing());
+        }
+        breaker.check_chunks(10, |_, _| false);
+        assert!(breaker.allows_signing());
+        breaker.record_produced_chunk(11, 0, chunk_hash(11));
+        breaker.check_chunks(11, |_, _| false);
+        assert!(breaker.allows_signing());
+    }
+}
@@ -6,6 +6,7 @@ use std::iter;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+use borsh::BorshDeserialize;
 use cached::{Cached, SizedCache};
 use log::{debug, error, info, warn};
 use near_primitives::time::Clock;
@@ -25,8 +26,9 @@ use near_chunks::{ProcessPartialEncodedChunkResult, ShardsManager};
 use near_network::types::{
     FullPeerInfo, NetworkClientResponses, NetworkRequests, PeerManagerAdapter,
 };
+use near_pool::types::{InsertTransactionResult, TransactionPoolLimits};
 use near_primitives::block::{Approval, ApprovalInner, ApprovalMessage, Block, BlockHeader, Tip};
-use near_primitives::challenge::{Challenge, ChallengeBody};
+use near_primitives::challenge::{Challenge, ChallengeBody, ChunkState};
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, MerklePath};
 use near_primitives::receipt::Receipt;
@@ -42,6 +44,12 @@ use near_primitives::utils::{to_timestamp, MaybeValidated};
 use near_primitives::validator_signer::ValidatorSigner;
 
 use crate::chunks_delay_tracker::ChunksDelayTracker;
+use crate::circuit_breaker::CircuitBreaker;
+use crate::clock_skew::ClockSkewTracker;
+use crate::pool_status::update_pool_metrics;
+use crate::protocol_upgrade::ProtocolUpgradeScheduler;
+use crate::recent_chunks::RecentChunks;
+use crate::state_quarantine::StateQuarantine;
 use crate::sync::{BlockSync, EpochSync, HeaderSync, StateSync, StateSyncResult};
 use crate::{metrics, SyncStatus};
 use near_client_primitives::types::{Error, ShardSyncDownload, ShardSyncStatus};
@@ -104,6 +112,16 @@ pub struct Client {
     last_time_head_progress_made: Instant,
     /// Keeps track of when the latest blocks and chunks were received.
     chunks_delay_tracker: ChunksDelayTracker,
+    /// Pauses signing when an anomaly is detected.
+    pub(crate) circuit_breaker: CircuitBreaker,
+    /// Shards whose local state keeps disagreeing with the chain.
+    state_quarantine: StateQuarantine,
+    /// Which protocol version the produced blocks vote for.
+    pub(crate) protocol_upgrade: ProtocolUpgradeScheduler,
+    /// Clock skew of the block producers, measured from the blocks received.
+    pub(crate) clock_skew: ClockSkewTracker,
+    /// Transactions of the chunks on top of the head, not included in a block yet.
+    recent_chunks: RecentChunks,
 }
 
 impl Client {
@@ -121,13 +139,32 @@ impl Client {
         } else {
             DoomslugThresholdMode::NoApprovals
         };
-        let chain = Chain::new(runtime_adapter.clone(), &chain_genesis, doomslug_threshold_mode)?;
-        let shards_mgr = ShardsManager::new(
+        let mut chain =
+            Chain::new(runtime_adapter.clone(), &chain_genesis, doomslug_threshold_mode)?;
+        chain.set_max_block_future_drift(config.max_block_future_drift);
+        chain.set_save_access_key_usage(config.save_access_key_usage);
+        chain.set_save_contract_history(config.save_contract_history);
+        chain.set_save_block_transaction_stats(config.save_block_transaction_stats);
+        chain.set_save_epoch_chain_stats(config.save_epoch_chain_stats);
+        chain.set_failed_receipts(config.failed_receipts.clone());
+        chain.set_pruning_checkpoint(config.pruning_checkpoint);
+        let mut shards_mgr = ShardsManager::new(
             validator_signer.as_ref().map(|x| x.validator_id().clone()),
             runtime_adapter.clone(),
             network_adapter.clone(),
             rng_seed,
         );
+        shards_mgr.set_chunk_request_retry_budget(config.chunk_request_retry_budget);
+        shards_mgr.set_transaction_pool_limits(TransactionPoolLimits {
+            max_transactions: config.transaction_pool.max_transactions_per_shard,
+            max_size: config.transaction_pool.max_size_per_shard,
+        });
+        let circuit_breaker = CircuitBreaker::new(config.circuit_breaker.clone());
+        let state_quarantine = StateQuarantine::new(config.state_quarantine.clone());
+        let protocol_upgrade = ProtocolUpgradeScheduler::new(
+            config.protocol_upgrade.clone(),
+            chain.store().owned_store(),
+        );
         let sync_status = SyncStatus::AwaitingPeers;
         let genesis_block = chain.genesis_block();
         let epoch_sync = EpochSync::new(
@@ -158,6 +195,8 @@ impl Client {
         let num_block_producer_seats = config.num_block_producer_seats as usize;
         let data_parts = runtime_adapter.num_data_parts();
         let parity_parts = runtime_adapter.num_total_parts() - data_parts;
+        let rs = ReedSolomonWrapper::new(data_parts, parity_parts);
+        info!(target: "client", "Using {} erasure coding backend", rs.backend_name());
 
         let doomslug = Doomslug::new(
             chain.store().largest_target_height()?,
@@ -188,10 +227,15 @@ impl Client {
             block_sync,
             state_sync,
             challenges: Default::default(),
-            rs: ReedSolomonWrapper::new(data_parts, parity_parts),
+            rs,
             rebroadcasted_blocks: SizedCache::with_size(NUM_REBROADCAST_BLOCKS),
             last_time_head_progress_made: Clock::instant(),
             chunks_delay_tracker: Default::default(),
+            circuit_breaker,
+            state_quarantine,
+            protocol_upgrade,
+            clock_skew: ClockSkewTracker::default(),
+            recent_chunks: RecentChunks::default(),
         })
     }
 
@@ -225,6 +269,7 @@ impl Client {
                         // By now the chunk must be in store, otherwise the block would have been orphaned
                         self.chain.get_chunk(&chunk_header.chunk_hash()).unwrap().transactions(),
                     );
+                    update_pool_metrics(self.shards_mgr.tx_pools(), shard_id);
                 }
             }
         }
@@ -248,6 +293,7 @@ impl Client {
                         // By now the chunk must be in store, otherwise the block would have been orphaned
                         self.chain.get_chunk(&chunk_header.chunk_hash()).unwrap().transactions(),
                     );
+                    update_pool_metrics(self.shards_mgr.tx_pools(), shard_id);
                 }
             }
         }
@@ -340,6 +386,10 @@ impl Client {
             .as_ref()
             .ok_or_else(|| Error::BlockProducer("Called without block producer info.".to_string()))?
             .clone();
+        if !self.circuit_breaker.allows_signing() {
+            debug!(target: "client", "Not producing block at height {}: circuit breaker tripped: {:?}", next_height, self.circuit_breaker.trips());
+            return Ok(None);
+        }
         let head = self.chain.head()?;
         assert_eq!(
             head.epoch_id,
@@ -495,9 +545,13 @@ impl Client {
         let next_epoch_protocol_version =
             self.runtime_adapter.get_epoch_protocol_version(&next_epoch_id)?;
 
+        let latest_protocol_version =
+            self.protocol_upgrade.voted_protocol_version(this_epoch_protocol_version, Clock::utc());
+
         let block = Block::produce(
             this_epoch_protocol_version,
             next_epoch_protocol_version,
+            latest_protocol_version,
             &prev_header,
             next_height,
             block_ordinal,
@@ -541,6 +595,14 @@ impl Client {
             .as_ref()
             .ok_or_else(|| Error::ChunkProducer("Called without block producer info.".to_string()))?
             .clone();
+        if !self.circuit_breaker.allows_signing() {
+            debug!(target: "client", "Not producing chunk for shard {} at height {}: circuit breaker tripped: {:?}", shard_id, next_height, self.circuit_breaker.trips());
+            return Ok(None);
+        }
+        if self.state_quarantine.is_quarantined(shard_id) {
+            debug!(target: "client", "Not producing chunk for shard {} at height {}: shard is quarantined", shard_id, next_height);
+            return Ok(None);
+        }
 
         let chunk_proposer =
             self.runtime_adapter.get_chunk_producer(epoch_id, next_height, shard_id).unwrap();
@@ -578,6 +640,7 @@ impl Client {
 
         let prev_block_header = self.chain.get_block_header(&prev_block_hash)?.clone();
         let transactions = self.prepare_transactions(shard_id, &chunk_extra, &prev_block_header)?;
+        self.recent_chunks.record_chunk(shard_id, &prev_block_hash, &transactions);
         let num_filtered_transactions = transactions.len();
         let (tx_root, _) = merklize(&transactions);
         let outgoing_receipts = self.chain.get_outgoing_receipts_for_shard(
@@ -635,6 +698,11 @@ impl Client {
         );
 
         metrics::CHUNK_PRODUCED_TOTAL.inc();
+        self.circuit_breaker.record_produced_chunk(
+            next_height,
+            shard_id,
+            encoded_chunk.chunk_hash(),
+        );
         Ok(Some((encoded_chunk, merkle_paths, outgoing_receipts)))
     }
 
@@ -645,12 +713,14 @@ impl Client {
         chunk_extra: &ChunkExtra,
         prev_block_header: &BlockHeader,
     ) -> Result<Vec<SignedTransaction>, Error> {
-        let Self { chain, shards_mgr, runtime_adapter, .. } = self;
+        let Self { chain, shards_mgr, runtime_adapter, recent_chunks, .. } = self;
 
         let next_epoch_id =
             runtime_adapter.get_epoch_id_from_prev_block(&prev_block_header.hash())?;
         let protocol_version = runtime_adapter.get_epoch_protocol_version(&next_epoch_id)?;
 
+        // Transactions of the chunks which may still be included, to keep in the pool.
+        let mut pending = vec![];
         let transactions = if let Some(mut iter) = shards_mgr.get_pool_iterator(shard_id) {
             let transaction_validity_period = chain.transaction_validity_period;
             runtime_adapter.prepare_transactions(
@@ -665,6 +735,10 @@ impl Client {
                 prev_block_header.height() + 1,
                 &mut iter,
                 &mut |tx: &SignedTransaction| -> bool {
+                    if recent_chunks.contains(shard_id, prev_block_header.hash(), &tx.get_hash()) {
+                        pending.push(tx.clone());
+                        return false;
+                    }
                     chain
                         .mut_store()
                         .check_transaction_validity_period(
@@ -682,6 +756,11 @@ impl Client {
         // Reintroduce valid transactions back to the pool. They will be removed when the chunk is
         // included into the block.
         shards_mgr.reintroduce_transactions(shard_id, &transactions);
+        if !pending.is_empty() {
+            metrics::CHUNK_TRANSACTIONS_PENDING_INCLUSION_TOTAL.inc_by(pending.len() as u64);
+            shards_mgr.reintroduce_transactions(shard_id, &pending);
+        }
+        update_pool_metrics(shards_mgr.tx_pools(), shard_id);
         Ok(transactions)
     }
 
@@ -746,6 +825,13 @@ impl Client {
         // Send out challenges that accumulated via on_challenge.
         self.send_challenges(challenges);
 
+        if let Err(e) = &result {
+            if let near_chain::ErrorKind::InvalidChunkState(chunk_state) = e.kind() {
+                let local_chunk_extra = self.get_local_prev_chunk_extra(&chunk_state);
+                self.state_quarantine.record_mismatch(&chunk_state, local_chunk_extra);
+            }
+        }
+
         // Send out challenge if the block was found to be invalid.
         if let Some(validator_signer) = self.validator_signer.as_ref() {
             match &result {
@@ -758,7 +844,11 @@ impl Client {
                             )),
                         ));
                     }
-                    near_chain::ErrorKind::InvalidChunkState(chunk_state) => {
+                    near_chain::ErrorKind::InvalidChunkState(chunk_state)
+                        if !self
+                            .state_quarantine
+                            .is_quarantined(chunk_state.chunk_header.shard_id()) =>
+                    {
                         self.network_adapter.do_send(PeerManagerMessageRequest::NetworkRequests(
                             NetworkRequests::Challenge(Challenge::produce(
                                 ChallengeBody::ChunkState(*chunk_state),
@@ -783,6 +873,16 @@ impl Client {
         (unwrapped_accepted_blocks, result)
     }
 
+    /// Chunk extra we computed when applying the chunk preceding a chunk which disagreed with it.
+    fn get_local_prev_chunk_extra(&mut self, chunk_state: &ChunkState) -> Option<ChunkExtra> {
+        let block_header = BlockHeader::try_from_slice(&chunk_state.block_header).ok()?;
+        let shard_uid = self
+            .runtime_adapter
+            .shard_id_to_uid(chunk_state.chunk_header.shard_id(), block_header.epoch_id())
+            .ok()?;
+        self.chain.get_chunk_extra(block_header.prev_hash(), &shard_uid).ok().cloned()
+    }
+
     pub fn rebroadcast_block(&mut self, block: &Block) {
         if self.rebroadcasted_blocks.cache_get(&block.hash()).is_none() {
             self.network_adapter.do_send(PeerManagerMessageRequest::NetworkRequests(
@@ -884,6 +984,14 @@ impl Client {
                             pec_v2.header.height_created(),
                             pec_v2.header.shard_id(),
                         );
+                        // Only the chunks of the shards we track are decoded.
+                        if let Ok(chunk) = self.chain.get_chunk(&chunk_hash) {
+                            self.recent_chunks.record_chunk(
+                                pec_v2.header.shard_id(),
+                                &pec_v2.header.prev_block_hash(),
+                                chunk.transactions(),
+                            );
+                        }
                         self.chain.blocks_with_missing_chunks.accept_chunk(&chunk_hash);
                         Ok(self.process_blocks_with_missing_chunks())
                     }
@@ -949,6 +1057,10 @@ impl Client {
         parent_hash: &CryptoHash,
         approval: Approval,
     ) -> Result<(), Error> {
+        if !self.circuit_breaker.allows_signing() {
+            debug!(target: "client", "Not sending an approval for {}: circuit breaker tripped: {:?}", approval.target_height, self.circuit_breaker.trips());
+            return Ok(());
+        }
         let next_epoch_id = self.runtime_adapter.get_epoch_id_from_prev_block(parent_hash)?;
         let next_block_producer =
             self.runtime_adapter.get_block_producer(&next_epoch_id, approval.target_height)?;
@@ -998,6 +1110,7 @@ impl Client {
         };
 
         let _ = self.check_and_update_doomslug_tip();
+        self.state_quarantine.record_accepted_block(&block);
 
         // If we produced the block, then it should have already been broadcasted.
         // If received the block from another node then broadcast "header first" to minimize network traffic.
@@ -1020,6 +1133,18 @@ impl Client {
 
         if status.is_new_head() {
             self.shards_mgr.update_largest_seen_height(block.header().height());
+            self.recent_chunks.update_head(block.hash());
+            self.circuit_breaker.check_block_chunks(&block);
+            if provenance == Provenance::NONE && !self.sync_status.is_syncing() {
+                let now = Clock::utc();
+                self.circuit_breaker.check_block_timestamp(block.header().timestamp(), now);
+                if let Ok(producer) = self
+                    .runtime_adapter
+                    .get_block_producer(block.header().epoch_id(), block.header().height())
+                {
+                    self.clock_skew.record_block(&producer, block.header().timestamp(), now);
+                }
+            }
             let last_final_block = block.header().last_final_block();
             let last_finalized_height = if last_final_block == &CryptoHash::default() {
                 self.chain.genesis().height()
@@ -1561,7 +1686,19 @@ impl Client {
                     shard_id,
                     is_forwarded
                 );
-                self.shards_mgr.insert_transaction(shard_id, tx.clone());
+                match self.shards_mgr.insert_transaction(shard_id, tx.clone()) {
+                    InsertTransactionResult::Success => {
+                        update_pool_metrics(self.shards_mgr.tx_pools(), shard_id)
+                    }
+                    InsertTransactionResult::Duplicate => {}
+                    // Dropped here, it may still be forwarded to other validators below.
+                    InsertTransactionResult::NoSpaceLeft => {
+                        debug!(target: "client", "Transaction pool of shard {} is full, not recording {}", shard_id, tx.get_hash());
+                        metrics::TRANSACTION_POOL_REJECTED
+                            .with_label_values(&[&shard_id.to_string()])
+                            .inc();
+                    }
+                }
 
                 // Active validator:
                 //   possibly forward to next epoch validators
@@ -1,12 +1,14 @@
 //! Client actor orchestrates Client and facilitates network connection.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use actix::dev::ToEnvelope;
-use actix::{Actor, Addr, Arbiter, AsyncContext, Context, Handler, Message};
+use actix::{
+    Actor, Addr, Arbiter, AsyncContext, Context, Handler, Message, ResponseFuture, System,
+};
 use actix_rt::ArbiterHandle;
 use borsh::BorshSerialize;
 use chrono::DateTime;
@@ -17,7 +19,7 @@ use rand::Rng;
 #[cfg(feature = "delay_detector")]
 use delay_detector::DelayDetector;
 use near_chain::test_utils::format_hash;
-use near_chain::types::AcceptedBlock;
+use near_chain::types::{AcceptedBlock, ValidatorInfoIdentifier};
 #[cfg(feature = "test_features")]
 use near_chain::StoreValidator;
 use near_chain::{
@@ -32,7 +34,7 @@ use near_crypto::Signature;
 use near_network::types::SandboxResponse;
 use near_network::types::{
     NetworkClientMessages, NetworkClientResponses, NetworkInfo, NetworkRequests,
-    PeerManagerAdapter, PeerManagerMessageRequest,
+    PeerManagerAdapter, PeerManagerMessageRequest, PeerManagerMessageResponse,
 };
 #[cfg(feature = "test_features")]
 use near_network_primitives::types::NetworkAdversarialMessage;
@@ -43,18 +45,25 @@ use near_performance_metrics_macros::{perf, perf_with_debug};
 use near_primitives::epoch_manager::RngSeed;
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::{AnnounceAccount, PeerId};
-use near_primitives::types::{BlockHeight, EpochId};
+use near_primitives::types::{BlockHeight, EpochId, ProtocolVersion};
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::{from_timestamp, MaybeValidated};
 use near_primitives::validator_signer::ValidatorSigner;
 use near_primitives::version::PROTOCOL_VERSION;
-use near_primitives::views::ValidatorInfo;
+use near_primitives::views::{
+    ChunkPartsAvailabilityView, ClockSkewReportView, DebugChainProcessingStatusView,
+    DebugNetworkInfoView, DebugPeerInfoView, DebugSyncStatusView, DebugValidatorStatusView,
+    PoolStatusView, PoolTransactionView, ProducerScheduleView, ProtocolUpgradeStatusView,
+    ValidatorInfo,
+};
 #[cfg(feature = "test_features")]
-use near_store::ColBlock;
+use near_store::{ColBlock, ColBlockMisc, READINESS_PROBE_KEY};
 use near_telemetry::TelemetryActor;
 
 use crate::client::Client;
+use crate::disk_monitor::{DiskMonitor, DiskPressure};
 use crate::info::{InfoHelper, ValidatorInfoHelper};
+use crate::pool_status::{pool_status, pool_transactions};
 use crate::sync::{highest_height_peer, StateSync, StateSyncResult};
 #[cfg(feature = "test_features")]
 use crate::AdversarialControls;
@@ -65,8 +74,11 @@ use near_chain::chain::{
     BlockCatchUpResponse, StateSplitRequest, StateSplitResponse,
 };
 use near_client_primitives::types::{
-    Error, GetNetworkInfo, NetworkInfoResponse, ShardSyncDownload, ShardSyncStatus, Status,
-    StatusError, StatusSyncInfo, SyncStatus,
+    AcknowledgeProtocolUpgrade, DebugStatus, DebugStatusResponse, Error, GetChunkPartsAvailability,
+    GetClockSkewReport, GetNetworkInfo, GetPoolStatus, GetPoolTransactions,
+    GetProtocolUpgradeStatus, NetworkInfoResponse, ProtocolUpgradeError, Readiness,
+    ReadinessResponse, ReloadPeerRanges, ShardSyncDownload, ShardSyncStatus, Status, StatusError,
+    StatusSyncInfo, SyncStatus,
 };
 use near_network_primitives::types::ReasonForBan;
 use near_primitives::block_header::ApprovalType;
@@ -80,6 +92,8 @@ const BLOCK_HORIZON: u64 = 500;
 /// `max_block_production_time` times this multiplier is how long we wait before rebroadcasting
 /// the current `head`
 const HEAD_STALL_MULTIPLIER: u32 = 4;
+/// Heights following the head whose producers the validator status debug page lists.
+const DEBUG_PRODUCER_SCHEDULE_HEIGHTS: BlockHeight = 10;
 
 pub struct ClientActor {
     /// Adversarial controls
@@ -107,6 +121,7 @@ pub struct ClientActor {
     block_catch_up_scheduler: Box<dyn Fn(BlockCatchUpRequest)>,
     state_split_scheduler: Box<dyn Fn(StateSplitRequest)>,
     state_parts_client_arbiter: Arbiter,
+    disk_monitor: Option<DiskMonitor>,
 }
 
 /// Blocks the program until given genesis time arrives.
@@ -158,6 +173,7 @@ impl ClientActor {
             info!(target: "client", "Starting validator node: {}", vs.validator_id());
         }
         let info_helper = InfoHelper::new(telemetry_actor, &config, validator_signer.clone());
+        let disk_monitor = config.disk_monitor.clone().map(DiskMonitor::new);
         let client = Client::new(
             config,
             chain_genesis,
@@ -182,6 +198,8 @@ impl ClientActor {
                 highest_height_peers: vec![],
                 received_bytes_per_sec: 0,
                 sent_bytes_per_sec: 0,
+                peer_transfer_stats: vec![],
+                peer_scores: vec![],
                 known_producers: vec![],
                 peer_counter: 0,
             },
@@ -202,6 +220,7 @@ impl ClientActor {
                 sync_jobs_actor_addr,
             ),
             state_parts_client_arbiter: state_parts_arbiter,
+            disk_monitor,
         })
     }
 }
@@ -247,6 +266,12 @@ impl Actor for ClientActor {
 
         // Start periodic logging of current state of the client.
         self.log_summary(ctx);
+
+        // Start watching free disk space.
+        self.check_disk_space(ctx);
+
+        // Start posting heartbeats for uptime monitoring.
+        self.send_liveness_beacon(ctx);
     }
 }
 
@@ -563,7 +588,12 @@ impl Handler<NetworkClientMessages> for ClientActor {
                 );
                 NetworkClientResponses::NoResponse
             }
-            NetworkClientMessages::PartialEncodedChunkResponse(response) => {
+            NetworkClientMessages::PartialEncodedChunkResponse(response, peer_id) => {
+                self.client.shards_mgr.record_chunk_parts_received(
+                    &response.chunk_hash,
+                    &peer_id,
+                    response.parts.iter().map(|part| part.part_ord),
+                );
                 if let Ok(accepted_blocks) =
                     self.client.process_partial_encoded_chunk_response(response)
                 {
@@ -571,7 +601,12 @@ impl Handler<NetworkClientMessages> for ClientActor {
                 }
                 NetworkClientResponses::NoResponse
             }
-            NetworkClientMessages::PartialEncodedChunk(partial_encoded_chunk) => {
+            NetworkClientMessages::PartialEncodedChunk(partial_encoded_chunk, peer_id) => {
+                self.client.shards_mgr.record_chunk_parts_received(
+                    &partial_encoded_chunk.chunk_hash(),
+                    &peer_id,
+                    partial_encoded_chunk.parts().iter().map(|part| part.part_ord),
+                );
                 if let Ok(accepted_blocks) = self
                     .client
                     .process_partial_encoded_chunk(MaybeValidated::from(partial_encoded_chunk))
@@ -580,7 +615,12 @@ impl Handler<NetworkClientMessages> for ClientActor {
                 }
                 NetworkClientResponses::NoResponse
             }
-            NetworkClientMessages::PartialEncodedChunkForward(forward) => {
+            NetworkClientMessages::PartialEncodedChunkForward(forward, peer_id) => {
+                self.client.shards_mgr.record_chunk_parts_received(
+                    &forward.chunk_hash,
+                    &peer_id,
+                    forward.parts.iter().map(|part| part.part_ord),
+                );
                 match self.client.process_partial_encoded_chunk_forward(forward) {
                     Ok(accepted_blocks) => self.process_accepted_blocks(accepted_blocks),
                     // Unknown chunk is normal if we get parts before the header
@@ -602,6 +642,13 @@ impl Handler<NetworkClientMessages> for ClientActor {
             }
             NetworkClientMessages::NetworkInfo(network_info) => {
                 self.network_info = network_info;
+                if !self.client.sync_status.is_syncing() {
+                    if let Ok(head) = self.client.chain.head() {
+                        self.client
+                            .circuit_breaker
+                            .check_peers(head.height, &self.network_info.active_peers);
+                    }
+                }
                 NetworkClientResponses::NoResponse
             }
         }
@@ -687,6 +734,45 @@ impl Handler<Status> for ClientActor {
                 earliest_block_time,
             },
             validator_account_id,
+            build_features: near_primitives::features::build_features()
+                .into_iter()
+                .map(|feature| feature.name.to_string())
+                .collect(),
+        })
+    }
+}
+
+impl Handler<Readiness> for ClientActor {
+    type Result = Result<ReadinessResponse, StatusError>;
+
+    #[perf]
+    fn handle(&mut self, msg: Readiness, ctx: &mut Context<Self>) -> Self::Result {
+        #[cfg(feature = "delay_detector")]
+        let _d = DelayDetector::new("client readiness".into());
+        self.check_triggers(ctx);
+
+        let head = self.client.chain.head()?;
+        let store_writable = if msg.check_store_writable {
+            let mut store_update = self.client.chain.store().store().store_update();
+            store_update.set(ColBlockMisc, READINESS_PROBE_KEY, &head.height.to_le_bytes());
+            let result = store_update.commit();
+            if let Err(err) = &result {
+                warn!(target: "client", "Store isn't writable: {}", err);
+            }
+            Some(result.is_ok())
+        } else {
+            None
+        };
+        Ok(ReadinessResponse {
+            head_height: head.height,
+            highest_peer_height: self
+                .network_info
+                .highest_height_peers
+                .iter()
+                .map(|peer| peer.chain_info.height)
+                .max(),
+            num_peers: self.network_info.num_active_peers,
+            store_writable,
         })
     }
 }
@@ -712,12 +798,202 @@ impl Handler<GetNetworkInfo> for ClientActor {
             peer_max_count: self.network_info.peer_max_count,
             sent_bytes_per_sec: self.network_info.sent_bytes_per_sec,
             received_bytes_per_sec: self.network_info.received_bytes_per_sec,
+            peer_transfer_stats: self.network_info.peer_transfer_stats.clone(),
+            peer_scores: self.network_info.peer_scores.clone(),
             known_producers: self.network_info.known_producers.clone(),
         })
     }
 }
 
+impl Handler<GetPoolStatus> for ClientActor {
+    type Result = Result<PoolStatusView, String>;
+
+    #[perf]
+    fn handle(&mut self, _msg: GetPoolStatus, _ctx: &mut Context<Self>) -> Self::Result {
+        Ok(pool_status(self.client.shards_mgr.tx_pools(), Clock::instant()))
+    }
+}
+
+impl Handler<GetChunkPartsAvailability> for ClientActor {
+    type Result = Result<Vec<ChunkPartsAvailabilityView>, String>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetChunkPartsAvailability, _ctx: &mut Context<Self>) -> Self::Result {
+        Ok(self.client.shards_mgr.chunk_parts_availability(msg.limit))
+    }
+}
+
+impl Handler<GetClockSkewReport> for ClientActor {
+    type Result = Result<ClockSkewReportView, String>;
+
+    #[perf]
+    fn handle(&mut self, _msg: GetClockSkewReport, _ctx: &mut Context<Self>) -> Self::Result {
+        Ok(self.client.clock_skew.report())
+    }
+}
+
+impl Handler<DebugStatus> for ClientActor {
+    type Result = Result<DebugStatusResponse, StatusError>;
+
+    #[perf]
+    fn handle(&mut self, msg: DebugStatus, _ctx: &mut Context<Self>) -> Self::Result {
+        match msg {
+            DebugStatus::SyncStatus => Ok(DebugStatusResponse::SyncStatus(DebugSyncStatusView {
+                sync_status: (&self.client.sync_status).into(),
+                head: (&self.client.chain.head()?).into(),
+                header_head: (&self.client.chain.header_head()?).into(),
+                highest_peer_height: self
+                    .network_info
+                    .highest_height_peers
+                    .iter()
+                    .map(|peer| peer.chain_info.height)
+                    .max(),
+            })),
+            DebugStatus::ChainProcessingStatus => {
+                Ok(DebugStatusResponse::ChainProcessingStatus(DebugChainProcessingStatusView {
+                    head: (&self.client.chain.head()?).into(),
+                    final_head: (&self.client.chain.final_head()?).into(),
+                    num_orphans: self.client.chain.orphans_len() as u64,
+                    num_orphans_evicted: self.client.chain.orphans_evicted_len() as u64,
+                    num_blocks_missing_chunks: self.client.chain.blocks_with_missing_chunks_len()
+                        as u64,
+                }))
+            }
+            DebugStatus::NetworkInfo => {
+                let highest_height_peers: HashSet<&PeerId> = self
+                    .network_info
+                    .highest_height_peers
+                    .iter()
+                    .map(|peer| &peer.peer_info.id)
+                    .collect();
+                let peers = self
+                    .network_info
+                    .active_peers
+                    .iter()
+                    .map(|peer| DebugPeerInfoView {
+                        peer_id: peer.peer_info.id.clone(),
+                        addr: peer.peer_info.addr.as_ref().map(|addr| addr.to_string()),
+                        account_id: peer.peer_info.account_id.clone(),
+                        height: peer.chain_info.height,
+                        tracked_shards: peer.chain_info.tracked_shards.clone(),
+                        archival: peer.chain_info.archival,
+                        is_highest_height: highest_height_peers.contains(&peer.peer_info.id),
+                    })
+                    .collect();
+                Ok(DebugStatusResponse::NetworkInfo(DebugNetworkInfoView {
+                    num_active_peers: self.network_info.num_active_peers as u64,
+                    peer_max_count: self.network_info.peer_max_count,
+                    sent_bytes_per_sec: self.network_info.sent_bytes_per_sec,
+                    received_bytes_per_sec: self.network_info.received_bytes_per_sec,
+                    peers,
+                }))
+            }
+            DebugStatus::ValidatorStatus => {
+                Ok(DebugStatusResponse::ValidatorStatus(self.debug_validator_status()?))
+            }
+        }
+    }
+}
+
+impl Handler<GetProtocolUpgradeStatus> for ClientActor {
+    type Result = Result<ProtocolUpgradeStatusView, ProtocolUpgradeError>;
+
+    #[perf]
+    fn handle(&mut self, _msg: GetProtocolUpgradeStatus, _ctx: &mut Context<Self>) -> Self::Result {
+        let epoch_protocol_version = self.head_epoch_protocol_version()?;
+        Ok(self.client.protocol_upgrade.status(epoch_protocol_version, Clock::utc()))
+    }
+}
+
+impl Handler<AcknowledgeProtocolUpgrade> for ClientActor {
+    type Result = Result<ProtocolUpgradeStatusView, ProtocolUpgradeError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: AcknowledgeProtocolUpgrade,
+        _ctx: &mut Context<Self>,
+    ) -> Self::Result {
+        self.client.protocol_upgrade.acknowledge(msg.protocol_version)?;
+        let epoch_protocol_version = self.head_epoch_protocol_version()?;
+        Ok(self.client.protocol_upgrade.status(epoch_protocol_version, Clock::utc()))
+    }
+}
+
+impl Handler<ReloadPeerRanges> for ClientActor {
+    type Result = ResponseFuture<Result<(), String>>;
+
+    #[perf]
+    fn handle(&mut self, _msg: ReloadPeerRanges, _ctx: &mut Context<Self>) -> Self::Result {
+        let response = self.network_adapter.send(PeerManagerMessageRequest::ReloadPeerRanges);
+        Box::pin(async move {
+            match response.await {
+                Ok(PeerManagerMessageResponse::ReloadPeerRanges(result)) => result,
+                Ok(response) => Err(format!("Unexpected response {:?}", response)),
+                Err(err) => Err(err.to_string()),
+            }
+        })
+    }
+}
+
+impl Handler<GetPoolTransactions> for ClientActor {
+    type Result = Result<Vec<PoolTransactionView>, String>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetPoolTransactions, _ctx: &mut Context<Self>) -> Self::Result {
+        Ok(pool_transactions(
+            self.client.shards_mgr.tx_pools(),
+            msg.shard_id,
+            msg.limit,
+            Clock::instant(),
+        ))
+    }
+}
+
 impl ClientActor {
+    fn head_epoch_protocol_version(&self) -> Result<ProtocolVersion, near_chain::Error> {
+        let head = self.client.chain.head()?;
+        self.client.runtime_adapter.get_epoch_protocol_version(&head.epoch_id)
+    }
+
+    fn debug_validator_status(&self) -> Result<DebugValidatorStatusView, near_chain::Error> {
+        let head = self.client.chain.head()?;
+        let runtime_adapter = &self.client.runtime_adapter;
+        let epoch_info = runtime_adapter
+            .get_validator_info(ValidatorInfoIdentifier::BlockHash(head.last_block_hash))?;
+        let validator_account_id =
+            self.client.validator_signer.as_ref().map(|vs| vs.validator_id()).cloned();
+        let validator_info = validator_account_id.as_ref().and_then(|account_id| {
+            epoch_info
+                .current_validators
+                .iter()
+                .find(|validator| &validator.account_id == account_id)
+                .cloned()
+        });
+        let num_shards = runtime_adapter.num_shards(&head.epoch_id)?;
+        let next_producers = (head.height + 1..=head.height + DEBUG_PRODUCER_SCHEDULE_HEIGHTS)
+            .map(|height| {
+                Ok(ProducerScheduleView {
+                    height,
+                    block_producer: runtime_adapter.get_block_producer(&head.epoch_id, height)?,
+                    chunk_producers: (0..num_shards)
+                        .map(|shard_id| {
+                            runtime_adapter.get_chunk_producer(&head.epoch_id, height, shard_id)
+                        })
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<_, near_chain::Error>>()?;
+        Ok(DebugValidatorStatusView {
+            validator_account_id,
+            epoch_id: head.epoch_id,
+            epoch_height: epoch_info.epoch_height,
+            epoch_start_height: epoch_info.epoch_start_height,
+            validator_info,
+            next_producers,
+        })
+    }
+
     fn sign_announce_account(&self, epoch_id: &EpochId) -> Result<Signature, ()> {
         if let Some(validator_signer) = self.client.validator_signer.as_ref() {
             Ok(validator_signer.sign_account_announce(
@@ -1450,6 +1726,49 @@ impl ClientActor {
         });
     }
 
+    /// Periodically check free disk space, and stop the node before the database runs out of it.
+    fn check_disk_space(&mut self, ctx: &mut Context<Self>) {
+        let disk_monitor = match self.disk_monitor.as_mut() {
+            Some(disk_monitor) => disk_monitor,
+            None => return,
+        };
+        let pressure = disk_monitor.check(
+            &mut self.client.chain,
+            self.client.runtime_adapter.get_tries(),
+            self.client.config.archive,
+        );
+        if pressure == DiskPressure::Halt {
+            System::current().stop();
+            return;
+        }
+        near_performance_metrics::actix::run_later(
+            ctx,
+            disk_monitor.config().check_period,
+            move |act, ctx| act.check_disk_space(ctx),
+        );
+    }
+
+    /// Periodically post a signed heartbeat to the endpoints of the liveness beacon.
+    fn send_liveness_beacon(&self, ctx: &mut Context<Self>) {
+        let config = match self.client.config.liveness_beacon.as_ref() {
+            Some(config) => config,
+            None => return,
+        };
+        near_performance_metrics::actix::run_later(ctx, config.interval, move |act, ctx| {
+            if let (Ok(head), Some(config)) =
+                (act.client.chain.head(), act.client.config.liveness_beacon.as_ref())
+            {
+                act.info_helper.liveness_beacon(
+                    &config.endpoints,
+                    &head,
+                    &act.node_id,
+                    act.client.doomslug.get_largest_target_height(),
+                );
+            }
+            act.send_liveness_beacon(ctx);
+        });
+    }
+
     /// Periodically log summary.
     fn log_summary(&self, ctx: &mut Context<Self>) {
         near_performance_metrics::actix::run_later(
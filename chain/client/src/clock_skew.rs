@@ -0,0 +1,115 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+
+use near_primitives::types::AccountId;
+use near_primitives::views::{ClockSkewReportView, ProducerClockSkewView};
+
+use crate::metrics;
+
+/// Number of most recent blocks of each producer the clock skew is measured over.
+const CLOCK_SKEW_WINDOW: usize = 100;
+
+/// Measures the clock skew of the block producers from the timestamps of their blocks.
+///
+/// The difference between a block timestamp and the time the block is accepted also includes
+/// the delays to propagate and process the block, which are about the same for all producers,
+/// so producers are compared to the median of all of them rather than to zero.
+#[derive(Default)]
+pub(crate) struct ClockSkewTracker {
+    /// Skews in milliseconds of the recent blocks of each producer, oldest first.
+    producers: HashMap<AccountId, VecDeque<i64>>,
+}
+
+impl ClockSkewTracker {
+    /// Only blocks received from the network while in sync are to be recorded, older blocks
+    /// would look like skew.
+    pub fn record_block(
+        &mut self,
+        producer: &AccountId,
+        block_timestamp: DateTime<Utc>,
+        accepted_at: DateTime<Utc>,
+    ) {
+        let skews = self.producers.entry(producer.clone()).or_default();
+        if skews.len() >= CLOCK_SKEW_WINDOW {
+            skews.pop_front();
+        }
+        skews.push_back((block_timestamp - accepted_at).num_milliseconds());
+        metrics::BLOCK_PRODUCER_CLOCK_SKEW
+            .with_label_values(&[producer.as_ref()])
+            .set(average(skews));
+    }
+
+    pub fn report(&self) -> ClockSkewReportView {
+        let mut averages: Vec<i64> = self.producers.values().map(average).collect();
+        averages.sort_unstable();
+        let median_skew_ms = averages.get(averages.len() / 2).copied().unwrap_or_default();
+        let mut producers: Vec<ProducerClockSkewView> = self
+            .producers
+            .iter()
+            .map(|(account_id, skews)| {
+                let average_skew_ms = average(skews);
+                ProducerClockSkewView {
+                    account_id: account_id.clone(),
+                    num_blocks: skews.len() as u64,
+                    average_skew_ms,
+                    relative_skew_ms: average_skew_ms - median_skew_ms,
+                    min_skew_ms: skews.iter().copied().min().unwrap_or_default(),
+                    max_skew_ms: skews.iter().copied().max().unwrap_or_default(),
+                }
+            })
+            .collect();
+        producers.sort_by(|a, b| {
+            b.relative_skew_ms
+                .abs()
+                .cmp(&a.relative_skew_ms.abs())
+                .then_with(|| a.account_id.cmp(&b.account_id))
+        });
+        ClockSkewReportView { median_skew_ms, producers }
+    }
+}
+
+fn average(skews: &VecDeque<i64>) -> i64 {
+    if skews.is_empty() {
+        return 0;
+    }
+    skews.iter().sum::<i64>() / skews.len() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_report() {
+        let mut tracker = ClockSkewTracker::default();
+        let now = Utc::now();
+        let (alice, bob, carol): (AccountId, AccountId, AccountId) =
+            ("alice".parse().unwrap(), "bob".parse().unwrap(), "carol".parse().unwrap());
+        // Blocks of alice and bob take 300ms to be accepted, carol's clock is 2s ahead.
+        for i in 0..(CLOCK_SKEW_WINDOW + 10) as i64 {
+            let accepted_at = now + Duration::seconds(i);
+            let timestamp = accepted_at - Duration::milliseconds(300);
+            tracker.record_block(&alice, timestamp, accepted_at);
+            tracker.record_block(
+                &bob,
+                timestamp - Duration::milliseconds(i % 2 * 100),
+                accepted_at,
+            );
+            tracker.record_block(&carol, timestamp + Duration::seconds(2), accepted_at);
+        }
+
+        let report = tracker.report();
+        assert_eq!(report.median_skew_ms, -300);
+        let ranking: Vec<_> = report.producers.iter().map(|p| p.account_id.clone()).collect();
+        assert_eq!(ranking, vec![carol, bob, alice]);
+        assert_eq!(report.producers[0].relative_skew_ms, 2000);
+        assert_eq!(report.producers[0].num_blocks, CLOCK_SKEW_WINDOW as u64);
+        assert_eq!(report.producers[1].relative_skew_ms, -50);
+        assert_eq!(
+            (report.producers[1].min_skew_ms, report.producers[1].max_skew_ms),
+            (-400, -300)
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use log::{error, info, warn};
+
+use near_chain::Chain;
+use near_chain_configs::DiskMonitorConfig;
+use near_store::ShardTries;
+
+use crate::metrics;
+
+/// How close the database is to running out of space, in order of severity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum DiskPressure {
+    None,
+    /// Only the epoch of the head and the previous one are kept, and extra garbage collection
+    /// passes are run to catch up with that.
+    ExtraGc,
+    /// The database is also compacted.
+    Compaction,
+    /// The node must stop.
+    Halt,
+}
+
+impl DiskPressure {
+    pub fn from_available_space(available: u64, config: &DiskMonitorConfig) -> Self {
+        if available < config.halt_threshold {
+            DiskPressure::Halt
+        } else if available < config.compaction_threshold {
+            DiskPressure::Compaction
+        } else if available < config.extra_gc_threshold {
+            DiskPressure::ExtraGc
+        } else {
+            DiskPressure::None
+        }
+    }
+}
+
+/// Keeps the database from running out of space on its volume, which RocksDB doesn't always
+/// recover from. As free space goes below the thresholds, more and more aggressive actions are
+/// taken: garbage collection of all the epochs but the last two, in extra passes, then compaction
+/// of the database to actually reclaim the space of what was collected, and at last a clean stop
+/// of the node. The usual retention is restored once free space is back above the thresholds.
+pub(crate) struct DiskMonitor {
+    config: DiskMonitorConfig,
+    pressure: DiskPressure,
+    /// Whether a compaction is running in the background.
+    compacting: Arc<AtomicBool>,
+}
+
+impl DiskMonitor {
+    pub fn new(config: DiskMonitorConfig) -> Self {
+        Self { config, pressure: DiskPressure::None, compacting: Arc::new(AtomicBool::new(false)) }
+    }
+
+    pub fn config(&self) -> &DiskMonitorConfig {
+        &self.config
+    }
+
+    /// Checks free space and takes the actions due. Returns the current pressure, the caller
+    /// stops the node on `DiskPressure::Halt`.
+    pub fn check(&mut self, chain: &mut Chain, tries: ShardTries, archive: bool) -> DiskPressure {
+        let store = chain.store().owned_store();
+        let rocksdb = match store.get_rocksdb() {
+            Some(rocksdb) => rocksdb,
+            None => return DiskPressure::None,
+        };
+        let available = match rocksdb.available_space() {
+            Ok(available) => available.as_u64(),
+            Err(err) => {
+                warn!(target: "client", "Failed to check free disk space: {}", err);
+                return self.pressure;
+            }
+        };
+        metrics::DISK_AVAILABLE_BYTES.set(available as i64);
+        let pressure = DiskPressure::from_available_space(available, &self.config);
+        metrics::DISK_PRESSURE.set(pressure as i64);
+        if pressure != self.pressure {
+            if pressure > self.pressure {
+                warn!(target: "client", "Free disk space is down to {} bytes, disk pressure {:?}", available, pressure);
+            } else {
+                info!(target: "client", "Free disk space is back to {} bytes, disk pressure {:?}", available, pressure);
+            }
+            self.pressure = pressure;
+        }
+
+        if !archive {
+            chain.set_reduced_retention(pressure >= DiskPressure::ExtraGc);
+        }
+        if pressure >= DiskPressure::ExtraGc && !archive {
+            if let Err(err) = chain.clear_data(tries, self.config.extra_gc_blocks_limit) {
+                error!(target: "client", "Extra garbage collection failed: {:?}", err);
+            }
+        }
+        if pressure >= DiskPressure::Compaction && !self.compacting.swap(true, Ordering::SeqCst) {
+            info!(target: "client", "Compacting the database to reclaim disk space");
+            let compacting = self.compacting.clone();
+            std::thread::spawn(move || {
+                if let Some(rocksdb) = store.get_rocksdb() {
+                    rocksdb.compact_all();
+                }
+                info!(target: "client", "Database compaction finished");
+                compacting.store(false, Ordering::SeqCst);
+            });
+        }
+        if pressure == DiskPressure::Halt {
+            error!(target: "client", "Free disk space is down to {} bytes, below the halt threshold of {} bytes. Stopping the node before the database runs out of space", available, self.config.halt_threshold);
+        }
+        pressure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_pressure() {
+        let config = DiskMonitorConfig {
+            extra_gc_threshold: 300,
+            compaction_threshold: 200,
+            halt_threshold: 100,
+            ..Default::default()
+        };
+        assert_eq!(DiskPressure::from_available_space(1000, &config), DiskPressure::None);
+        assert_eq!(DiskPressure::from_available_space(300, &config), DiskPressure::None);
+        assert_eq!(DiskPressure::from_available_space(299, &config), DiskPressure::ExtraGc);
+        assert_eq!(DiskPressure::from_available_space(150, &config), DiskPressure::Compaction);
+        assert_eq!(DiskPressure::from_available_space(99, &config), DiskPressure::Halt);
+        assert!(DiskPressure::Halt > DiskPressure::Compaction);
+    }
+}
@@ -13,12 +13,13 @@ use near_primitives::block::Tip;
 use near_primitives::network::PeerId;
 use near_primitives::serialize::to_base;
 use near_primitives::telemetry::{
-    TelemetryAgentInfo, TelemetryChainInfo, TelemetryInfo, TelemetrySystemInfo,
+    LivenessBeacon, TelemetryAgentInfo, TelemetryChainInfo, TelemetryInfo, TelemetrySystemInfo,
 };
 use near_primitives::types::{BlockHeight, Gas};
+use near_primitives::utils::to_timestamp;
 use near_primitives::validator_signer::ValidatorSigner;
 use near_primitives::version::Version;
-use near_telemetry::{telemetry, TelemetryActor};
+use near_telemetry::{liveness_beacon, telemetry, TelemetryActor};
 
 use crate::{metrics, SyncStatus};
 use near_client_primitives::types::ShardSyncStatus;
@@ -193,6 +194,32 @@ impl InfoHelper {
         };
         telemetry(&self.telemetry_actor, content);
     }
+
+    /// Posts a heartbeat signed with the validator key to the `endpoints`. Nothing is sent
+    /// without a validator key: the heartbeat couldn't be told apart from a forged one.
+    pub fn liveness_beacon(
+        &self,
+        endpoints: &[String],
+        head: &Tip,
+        node_id: &PeerId,
+        last_endorsement_height: BlockHeight,
+    ) {
+        let validator_signer = match self.validator_signer.as_ref() {
+            Some(validator_signer) => validator_signer,
+            None => return,
+        };
+        let beacon = LivenessBeacon {
+            account_id: validator_signer.validator_id().clone(),
+            public_key: validator_signer.public_key(),
+            node_id: node_id.to_string(),
+            timestamp: to_timestamp(Clock::utc()),
+            latest_block_hash: to_base(&head.last_block_hash),
+            latest_block_height: head.height,
+            last_endorsement_height,
+        };
+        let content = validator_signer.sign_liveness_beacon(&beacon);
+        liveness_beacon(&self.telemetry_actor, endpoints.to_vec(), content);
+    }
 }
 
 fn display_sync_status(
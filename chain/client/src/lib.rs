@@ -1,11 +1,18 @@
 pub use near_client_primitives::types::{
-    Error, GetBlock, GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree, GetChunk,
-    GetExecutionOutcome, GetExecutionOutcomeResponse, GetExecutionOutcomesForBlock, GetGasPrice,
-    GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
-    GetStateChangesInBlock, GetStateChangesWithCauseInBlock, GetValidatorInfo, GetValidatorOrdered,
-    Query, QueryError, Status, StatusResponse, SyncStatus, TxStatus, TxStatusError,
+    AcknowledgeProtocolUpgrade, DebugStatus, DebugStatusResponse, Error, EstimateTransactionFees,
+    GetAccessKeyUsage, GetAccountSummary, GetBlock, GetBlockProof, GetBlockProofResponse,
+    GetBlockTransactionStats, GetBlockWithMerkleTree, GetChunk, GetChunkPartsAvailability,
+    GetClockSkewReport, GetContractHistory, GetEpochChainStats, GetExecutionOutcome,
+    GetExecutionOutcomeResponse, GetExecutionOutcomesForBlock, GetFailedReceipts, GetGasPrice,
+    GetGasThroughput, GetNetworkInfo, GetNextLightClientBlock, GetPoolStatus, GetPoolTransactions,
+    GetProtocolConfig, GetProtocolUpgradeStatus, GetReceipt, GetReceiptInclusionProof,
+    GetRefundStats, GetStateChanges, GetStateChangesInBlock, GetStateChangesWithCauseInBlock,
+    GetValidatorInfo, GetValidatorOrdered, Query, QueryError, Readiness, ReadinessResponse,
+    ReloadPeerRanges, SimulateTransaction, Status, StatusResponse, SyncStatus, TxStatus,
+    TxStatusError,
 };
 
+pub use crate::circuit_breaker::CircuitBreakerTrip;
 pub use crate::client::Client;
 pub use crate::client_actor::{start_client, ClientActor};
 #[cfg(feature = "test_features")]
@@ -13,10 +20,17 @@ pub use crate::view_client::AdversarialControls;
 pub use crate::view_client::{start_view_client, ViewClientActor};
 
 mod chunks_delay_tracker;
+mod circuit_breaker;
 mod client;
 mod client_actor;
+mod clock_skew;
+mod disk_monitor;
 mod info;
 mod metrics;
+mod pool_status;
+mod protocol_upgrade;
+mod recent_chunks;
+mod state_quarantine;
 pub mod sync;
 pub mod test_utils;
 #[cfg(test)]
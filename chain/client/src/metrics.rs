@@ -1,6 +1,6 @@
 use near_metrics::{
-    try_create_histogram, try_create_int_counter, try_create_int_gauge, Histogram, IntCounter,
-    IntGauge,
+    try_create_histogram, try_create_int_counter, try_create_int_counter_vec, try_create_int_gauge,
+    try_create_int_gauge_vec, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -18,6 +18,13 @@ pub static CHUNK_PRODUCED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static CHUNK_TRANSACTIONS_PENDING_INCLUSION_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_chunk_transactions_pending_inclusion_total",
+        "Number of transactions left out of the chunks produced because they are in a chunk not included in a block yet",
+    )
+    .unwrap()
+});
 pub static IS_VALIDATOR: Lazy<IntGauge> = Lazy::new(|| {
     try_create_int_gauge("near_is_validator", "Bool to denote if it is currently validating")
         .unwrap()
@@ -68,3 +75,79 @@ pub static BLOCKS_AHEAD_OF_HEAD: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static CIRCUIT_BREAKER_TRIPPED: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_circuit_breaker_tripped",
+        "Bool to denote if signing is paused because of a detected anomaly",
+    )
+    .unwrap()
+});
+pub static QUARANTINED_SHARDS: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_quarantined_shards",
+        "Number of shards whose local state keeps disagreeing with the chain",
+    )
+    .unwrap()
+});
+pub static DISK_AVAILABLE_BYTES: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_disk_available_bytes",
+        "Free space left on the volume of the database, in bytes",
+    )
+    .unwrap()
+});
+pub static DISK_PRESSURE: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_disk_pressure",
+        "Disk pressure level: 0 none, 1 extra gc, 2 compaction, 3 halt",
+    )
+    .unwrap()
+});
+pub static PROTOCOL_UPGRADE_VOTED_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_protocol_upgrade_voted_version",
+        "Protocol version the blocks produced by this node vote for",
+    )
+    .unwrap()
+});
+pub static BLOCK_PRODUCER_CLOCK_SKEW: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_block_producer_clock_skew_ms",
+        "Average difference between the timestamps of the recent blocks of a producer and their \
+         acceptance by this node, in milliseconds",
+        &["account_id"],
+    )
+    .unwrap()
+});
+pub static TRANSACTION_POOL_TRANSACTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_transaction_pool_transactions",
+        "Number of transactions in the pool of a shard",
+        &["shard_id"],
+    )
+    .unwrap()
+});
+pub static TRANSACTION_POOL_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_transaction_pool_size_bytes",
+        "Sum of the sizes of the transactions in the pool of a shard, in bytes",
+        &["shard_id"],
+    )
+    .unwrap()
+});
+pub static TRANSACTION_POOL_REJECTED: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_transaction_pool_rejected_total",
+        "Number of transactions not inserted into the pool of a shard because it is full",
+        &["shard_id"],
+    )
+    .unwrap()
+});
+pub static VIEW_CLIENT_QUERIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_view_client_queries_total",
+        "Total number of queries handled by the view client, completed or cancelled because their client went away",
+        &["outcome"],
+    )
+    .unwrap()
+});
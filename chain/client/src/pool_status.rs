@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use near_pool::TransactionPool;
+use near_primitives::time::Instant;
+use near_primitives::types::ShardId;
+use near_primitives::views::{
+    PoolAgeBucketView, PoolStatusView, PoolTransactionView, ShardPoolStatusView,
+};
+
+use crate::metrics;
+
+/// Upper bounds of the buckets of the age distribution of the transactions in the pool.
+const AGE_BUCKETS_SECS: [u64; 5] = [1, 10, 60, 600, 3600];
+
+fn age_distribution(ages: &[Duration]) -> Vec<PoolAgeBucketView> {
+    let mut buckets: Vec<PoolAgeBucketView> = AGE_BUCKETS_SECS
+        .iter()
+        .map(|&max_age_secs| Some(max_age_secs))
+        .chain(std::iter::once(None))
+        .map(|max_age_secs| PoolAgeBucketView { max_age_secs, num_transactions: 0 })
+        .collect();
+    for age in ages {
+        let bucket = AGE_BUCKETS_SECS
+            .iter()
+            .position(|&max_age_secs| *age < Duration::from_secs(max_age_secs))
+            .unwrap_or(AGE_BUCKETS_SECS.len());
+        buckets[bucket].num_transactions += 1;
+    }
+    buckets
+}
+
+pub(crate) fn pool_status(
+    tx_pools: &HashMap<ShardId, TransactionPool>,
+    now: Instant,
+) -> PoolStatusView {
+    let mut all_ages = vec![];
+    let mut shards: Vec<ShardPoolStatusView> = tx_pools
+        .iter()
+        .map(|(&shard_id, pool)| {
+            let ages: Vec<Duration> = pool
+                .transactions_with_insertion_time()
+                .map(|(_, inserted)| now.saturating_duration_since(inserted))
+                .collect();
+            all_ages.extend_from_slice(&ages);
+            ShardPoolStatusView {
+                shard_id,
+                num_transactions: ages.len() as u64,
+                num_signers: pool.num_groups() as u64,
+                total_size: pool.total_size(),
+                max_age_secs: ages.iter().max().map(Duration::as_secs),
+                age_distribution: age_distribution(&ages),
+            }
+        })
+        .collect();
+    shards.sort_by_key(|shard| shard.shard_id);
+    PoolStatusView {
+        num_transactions: all_ages.len() as u64,
+        shards,
+        age_distribution: age_distribution(&all_ages),
+    }
+}
+
+/// Updates the metrics of the pool of `shard_id` after its transactions changed.
+pub(crate) fn update_pool_metrics(tx_pools: &HashMap<ShardId, TransactionPool>, shard_id: ShardId) {
+    if let Some(pool) = tx_pools.get(&shard_id) {
+        let shard_id = shard_id.to_string();
+        metrics::TRANSACTION_POOL_TRANSACTIONS
+            .with_label_values(&[&shard_id])
+            .set(pool.len() as i64);
+        metrics::TRANSACTION_POOL_SIZE
+            .with_label_values(&[&shard_id])
+            .set(pool.total_size() as i64);
+    }
+}
+
+/// Up to `limit` transactions of the pool of `shard_id`, or of all pools, oldest first.
+pub(crate) fn pool_transactions(
+    tx_pools: &HashMap<ShardId, TransactionPool>,
+    shard_id: Option<ShardId>,
+    limit: usize,
+    now: Instant,
+) -> Vec<PoolTransactionView> {
+    let mut transactions: Vec<(Instant, PoolTransactionView)> = tx_pools
+        .iter()
+        .filter(|(&pool_shard_id, _)| shard_id.map_or(true, |shard_id| shard_id == pool_shard_id))
+        .flat_map(|(&shard_id, pool)| {
+            pool.transactions_with_insertion_time().map(move |(tx, inserted)| {
+                let view = PoolTransactionView {
+                    hash: tx.get_hash(),
+                    shard_id,
+                    signer_id: tx.transaction.signer_id.clone(),
+                    public_key: tx.transaction.public_key.clone(),
+                    nonce: tx.transaction.nonce,
+                    receiver_id: tx.transaction.receiver_id.clone(),
+                    num_actions: tx.transaction.actions.len() as u64,
+                    age_secs: now.saturating_duration_since(inserted).as_secs(),
+                };
+                (inserted, view)
+            })
+        })
+        .collect();
+    transactions.sort_by_key(|(inserted, _)| *inserted);
+    transactions.into_iter().take(limit).map(|(_, view)| view).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::time::Clock;
+    use near_primitives::transaction::SignedTransaction;
+
+    fn transaction(nonce: u64) -> SignedTransaction {
+        let signer = InMemorySigner::from_seed("alice.near".parse().unwrap(), KeyType::ED25519, "");
+        SignedTransaction::send_money(
+            nonce,
+            "alice.near".parse().unwrap(),
+            "bob.near".parse().unwrap(),
+            &signer,
+            1,
+            CryptoHash::default(),
+        )
+    }
+
+    #[test]
+    fn test_age_distribution() {
+        let ages: Vec<Duration> =
+            [0, 5, 9, 10, 4000].iter().map(|&secs| Duration::from_secs(secs)).collect();
+        let counts: Vec<(Option<u64>, u64)> = age_distribution(&ages)
+            .into_iter()
+            .map(|bucket| (bucket.max_age_secs, bucket.num_transactions))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (Some(1), 1),
+                (Some(10), 2),
+                (Some(60), 1),
+                (Some(600), 0),
+                (Some(3600), 0),
+                (None, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pool_status() {
+        let mut tx_pools = HashMap::new();
+        let mut pool = TransactionPool::new([0; 32]);
+        pool.insert_transaction(transaction(1));
+        pool.insert_transaction(transaction(2));
+        tx_pools.insert(1, pool);
+        tx_pools.insert(0, TransactionPool::new([0; 32]));
+
+        let now = Clock::instant() + Duration::from_secs(30);
+        let status = pool_status(&tx_pools, now);
+        assert_eq!(status.num_transactions, 2);
+        assert_eq!(status.shards.len(), 2);
+        assert_eq!(status.shards[0].num_transactions, 0);
+        assert_eq!(status.shards[0].max_age_secs, None);
+        assert_eq!(status.shards[1].num_transactions, 2);
+        assert_eq!(status.shards[1].num_signers, 1);
+        assert_eq!(status.shards[1].total_size, 2 * transaction(1).get_size());
+        assert_eq!(status.age_distribution[2].num_transactions, 2);
+
+        assert_eq!(pool_transactions(&tx_pools, None, 1, now).len(), 1);
+        assert_eq!(pool_transactions(&tx_pools, Some(1), 10, now).len(), 2);
+        assert!(pool_transactions(&tx_pools, Some(0), 10, now).is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+
+use near_chain_configs::ProtocolUpgradeConfig;
+use near_client_primitives::types::ProtocolUpgradeError;
+use near_primitives::types::ProtocolVersion;
+use near_primitives::version::PROTOCOL_VERSION;
+use near_primitives::views::{ProtocolUpgradeStatusView, ProtocolUpgradeVoteState};
+use near_store::{DBCol, Store};
+
+use crate::metrics;
+
+/// Key in `ColBlockMisc` of the protocol version acknowledged through the admin endpoint.
+const ACKNOWLEDGED_PROTOCOL_VERSION_KEY: &[u8] = b"ACKNOWLEDGED_PROTOCOL_VERSION";
+
+/// Protocol version to vote for, given the one of the binary and the one of the current epoch.
+fn vote(
+    binary_protocol_version: ProtocolVersion,
+    epoch_protocol_version: ProtocolVersion,
+    acknowledged: bool,
+    vote_after: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> (ProtocolVersion, ProtocolUpgradeVoteState) {
+    if binary_protocol_version <= epoch_protocol_version {
+        (binary_protocol_version, ProtocolUpgradeVoteState::UpToDate)
+    } else if acknowledged || vote_after.map_or(false, |vote_after| now >= vote_after) {
+        (binary_protocol_version, ProtocolUpgradeVoteState::Voting)
+    } else {
+        (epoch_protocol_version, ProtocolUpgradeVoteState::AwaitingAcknowledgement)
+    }
+}
+
+/// Holds back the vote of the produced blocks for the protocol version of the binary until the
+/// operator acknowledges the upgrade, in the config or through the admin endpoint of the JSON
+/// RPC, or until the time set in the config. Without config the blocks vote for it right away.
+pub(crate) struct ProtocolUpgradeScheduler {
+    config: Option<ProtocolUpgradeConfig>,
+    store: Arc<Store>,
+    /// Acknowledged through the admin endpoint, persisted across restarts.
+    acknowledged_protocol_version: Option<ProtocolVersion>,
+}
+
+impl ProtocolUpgradeScheduler {
+    pub fn new(config: Option<ProtocolUpgradeConfig>, store: Arc<Store>) -> Self {
+        let acknowledged_protocol_version = store
+            .get_ser(DBCol::ColBlockMisc, ACKNOWLEDGED_PROTOCOL_VERSION_KEY)
+            .unwrap_or_else(|err| {
+                warn!(target: "client", "Failed to read the acknowledged protocol version: {}", err);
+                None
+            });
+        Self { config, store, acknowledged_protocol_version }
+    }
+
+    fn acknowledged_protocol_version(&self) -> Option<ProtocolVersion> {
+        let from_config =
+            self.config.as_ref().and_then(|config| config.acknowledged_protocol_version);
+        from_config.max(self.acknowledged_protocol_version)
+    }
+
+    pub fn status(
+        &self,
+        epoch_protocol_version: ProtocolVersion,
+        now: DateTime<Utc>,
+    ) -> ProtocolUpgradeStatusView {
+        let acknowledged_protocol_version = self.acknowledged_protocol_version();
+        let vote_after = self.config.as_ref().and_then(|config| config.vote_after);
+        let (voted_protocol_version, state) = vote(
+            PROTOCOL_VERSION,
+            epoch_protocol_version,
+            self.config.is_none()
+                || acknowledged_protocol_version
+                    .map_or(false, |version| version >= PROTOCOL_VERSION),
+            vote_after,
+            now,
+        );
+        ProtocolUpgradeStatusView {
+            binary_protocol_version: PROTOCOL_VERSION,
+            epoch_protocol_version,
+            voted_protocol_version,
+            state,
+            acknowledged_protocol_version,
+            vote_after,
+        }
+    }
+
+    /// Protocol version a block produced now in an epoch of `epoch_protocol_version` votes for.
+    pub fn voted_protocol_version(
+        &self,
+        epoch_protocol_version: ProtocolVersion,
+        now: DateTime<Utc>,
+    ) -> ProtocolVersion {
+        let status = self.status(epoch_protocol_version, now);
+        metrics::PROTOCOL_UPGRADE_VOTED_VERSION.set(status.voted_protocol_version as i64);
+        status.voted_protocol_version
+    }
+
+    /// Acknowledges the upgrade to the protocol version of the binary, which must be the one
+    /// given so that an operator doesn't acknowledge a version they didn't review.
+    pub fn acknowledge(
+        &mut self,
+        protocol_version: ProtocolVersion,
+    ) -> Result<(), ProtocolUpgradeError> {
+        if protocol_version != PROTOCOL_VERSION {
+            return Err(ProtocolUpgradeError::UnsupportedProtocolVersion {
+                binary_protocol_version: PROTOCOL_VERSION,
+                protocol_version,
+            });
+        }
+        let mut store_update = self.store.store_update();
+        store_update
+            .set_ser(DBCol::ColBlockMisc, ACKNOWLEDGED_PROTOCOL_VERSION_KEY, &protocol_version)
+            .and_then(|()| store_update.commit())
+            .map_err(|err| ProtocolUpgradeError::IOError { error_message: err.to_string() })?;
+        self.acknowledged_protocol_version = Some(protocol_version);
+        info!(target: "client", "Upgrade to protocol version {} acknowledged, produced blocks now vote for it", protocol_version);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_vote() {
+        let now = Utc::now();
+        assert_eq!(vote(50, 50, false, None, now), (50, ProtocolUpgradeVoteState::UpToDate));
+        assert_eq!(
+            vote(51, 50, false, None, now),
+            (50, ProtocolUpgradeVoteState::AwaitingAcknowledgement)
+        );
+        assert_eq!(vote(51, 50, true, None, now), (51, ProtocolUpgradeVoteState::Voting));
+        assert_eq!(
+            vote(51, 50, false, Some(now + Duration::hours(1)), now),
+            (50, ProtocolUpgradeVoteState::AwaitingAcknowledgement)
+        );
+        assert_eq!(vote(51, 50, false, Some(now), now), (51, ProtocolUpgradeVoteState::Voting));
+    }
+
+    #[test]
+    fn test_acknowledge() {
+        let store = near_store::test_utils::create_test_store();
+        let config = ProtocolUpgradeConfig::default();
+        let mut scheduler = ProtocolUpgradeScheduler::new(Some(config.clone()), store.clone());
+        let now = Utc::now();
+        assert_eq!(
+            scheduler.voted_protocol_version(PROTOCOL_VERSION - 1, now),
+            PROTOCOL_VERSION - 1
+        );
+        assert!(scheduler.acknowledge(PROTOCOL_VERSION + 1).is_err());
+        scheduler.acknowledge(PROTOCOL_VERSION).unwrap();
+        assert_eq!(scheduler.voted_protocol_version(PROTOCOL_VERSION - 1, now), PROTOCOL_VERSION);
+
+        // The acknowledgement is persisted.
+        let scheduler = ProtocolUpgradeScheduler::new(Some(config), store.clone());
+        assert_eq!(scheduler.voted_protocol_version(PROTOCOL_VERSION - 1, now), PROTOCOL_VERSION);
+        // Without config, blocks vote for the protocol version of the binary right away.
+        let scheduler =
+            ProtocolUpgradeScheduler::new(None, near_store::test_utils::create_test_store());
+        assert_eq!(scheduler.voted_protocol_version(PROTOCOL_VERSION - 1, now), PROTOCOL_VERSION);
+    }
+}
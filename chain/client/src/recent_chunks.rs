@@ -0,0 +1,100 @@
+use std::collections::{HashMap, HashSet};
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::types::ShardId;
+
+/// Transactions of the chunks produced or received on top of the head.
+///
+/// The transactions of a chunk are only removed from the pool once the block including it is
+/// accepted, so that a chunk produced in between on the same previous block, for a height whose
+/// block was skipped, picks the same transactions again and half of them are rejected as
+/// duplicates. The transactions of the chunks on the same previous block are excluded instead,
+/// as a block on top of it may still include them. Once the head moves, the chunks on the former
+/// head are either included and their transactions gone from the pool, or they never will be and
+/// their transactions are fair game.
+#[derive(Default)]
+pub(crate) struct RecentChunks {
+    /// Hashes of the transactions of the recent chunks, per previous block and shard.
+    transactions: HashMap<CryptoHash, HashMap<ShardId, HashSet<CryptoHash>>>,
+}
+
+impl RecentChunks {
+    pub fn record_chunk(
+        &mut self,
+        shard_id: ShardId,
+        prev_block_hash: &CryptoHash,
+        transactions: &[SignedTransaction],
+    ) {
+        if transactions.is_empty() {
+            return;
+        }
+        self.transactions
+            .entry(*prev_block_hash)
+            .or_default()
+            .entry(shard_id)
+            .or_default()
+            .extend(transactions.iter().map(|tx| tx.get_hash()));
+    }
+
+    /// Whether the transaction is in a chunk of the shard on top of `prev_block_hash`, which a
+    /// block on top of it may still include.
+    pub fn contains(
+        &self,
+        shard_id: ShardId,
+        prev_block_hash: &CryptoHash,
+        tx_hash: &CryptoHash,
+    ) -> bool {
+        self.transactions
+            .get(prev_block_hash)
+            .and_then(|shards| shards.get(&shard_id))
+            .map_or(false, |hashes| hashes.contains(tx_hash))
+    }
+
+    /// Forgets the chunks on top of other blocks than the new head.
+    pub fn update_head(&mut self, head_hash: &CryptoHash) {
+        self.transactions.retain(|prev_block_hash, _| prev_block_hash == head_hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::hash::hash;
+
+    use super::*;
+
+    #[test]
+    fn test_recent_chunks() {
+        let signer = InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test");
+        let transactions: Vec<_> = (1..=3)
+            .map(|nonce| {
+                SignedTransaction::send_money(
+                    nonce,
+                    "test".parse().unwrap(),
+                    "other".parse().unwrap(),
+                    &signer,
+                    1,
+                    CryptoHash::default(),
+                )
+            })
+            .collect();
+        let [tx1, tx2, tx3] = [0, 1, 2].map(|i| transactions[i].get_hash());
+        let [block1, block2] = [hash(b"block1"), hash(b"block2")];
+
+        let mut recent = RecentChunks::default();
+        recent.record_chunk(0, &block1, &transactions[..2]);
+        recent.record_chunk(0, &block2, &transactions[2..]);
+        assert!(recent.contains(0, &block1, &tx1) && recent.contains(0, &block1, &tx2));
+        assert!(!recent.contains(1, &block1, &tx1));
+        // The chunk on top of the first block can't be included on top of the second one.
+        assert!(!recent.contains(0, &block2, &tx1));
+        assert!(recent.contains(0, &block2, &tx3));
+
+        recent.update_head(&block2);
+        assert!(!recent.contains(0, &block1, &tx1));
+        assert!(recent.contains(0, &block2, &tx3));
+        recent.update_head(&hash(b"block3"));
+        assert!(recent.transactions.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use log::{error, info, warn};
+
+use near_chain_configs::StateQuarantineConfig;
+use near_primitives::block::{Block, BlockHeader};
+use near_primitives::challenge::ChunkState;
+use near_primitives::types::chunk_extra::ChunkExtra;
+use near_primitives::types::ShardId;
+
+use crate::metrics;
+
+/// Everything needed to replay offline the application of a chunk whose result disagreed
+/// with the chain.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct StateMismatchDiagnostics {
+    /// Previous chunk with its transactions, the block headers, inclusion proofs and the
+    /// partial state the previous chunk touched.
+    pub chunk_state: ChunkState,
+    /// What we computed when applying the previous chunk.
+    pub local_chunk_extra: Option<ChunkExtra>,
+}
+
+/// Tracks the shards for which the state roots we compute disagree with the chunks of the
+/// blocks we receive.
+///
+/// A single mismatch is expected to be a malicious chunk producer, and is challenged. When the
+/// chunks of several consecutive blocks disagree with us, it is more likely that our own state
+/// is corrupted: the shard is quarantined, we stop producing chunks and sending challenges for
+/// it until a block with a new chunk for the shard is accepted again.
+#[derive(Default)]
+pub(crate) struct StateQuarantine {
+    config: Option<StateQuarantineConfig>,
+    /// Number of consecutive blocks with a mismatching chunk, per shard.
+    mismatches: HashMap<ShardId, u64>,
+    quarantined: HashSet<ShardId>,
+}
+
+impl StateQuarantine {
+    pub fn new(config: Option<StateQuarantineConfig>) -> Self {
+        Self { config, ..Default::default() }
+    }
+
+    pub fn is_quarantined(&self, shard_id: ShardId) -> bool {
+        self.quarantined.contains(&shard_id)
+    }
+
+    /// Records a chunk whose previous state root disagrees with what we computed, writes its
+    /// diagnostics and quarantines the shard once there were too many consecutive mismatches.
+    pub fn record_mismatch(
+        &mut self,
+        chunk_state: &ChunkState,
+        local_chunk_extra: Option<ChunkExtra>,
+    ) {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return,
+        };
+        let shard_id = chunk_state.chunk_header.shard_id();
+        match write_diagnostics(&config.diagnostics_dir, chunk_state, local_chunk_extra) {
+            Ok(path) => {
+                info!(target: "client", "Wrote state mismatch diagnostics for shard {} to {}", shard_id, path.display())
+            }
+            Err(err) => {
+                warn!(target: "client", "Failed to write state mismatch diagnostics for shard {}: {}", shard_id, err)
+            }
+        }
+        self.count_mismatch(shard_id);
+    }
+
+    fn count_mismatch(&mut self, shard_id: ShardId) {
+        let max_mismatches = match &self.config {
+            Some(config) => config.max_mismatches,
+            None => return,
+        };
+        let mismatches = self.mismatches.entry(shard_id).or_default();
+        *mismatches += 1;
+        if *mismatches >= max_mismatches && self.quarantined.insert(shard_id) {
+            error!(target: "client", "State of shard {} disagreed with {} consecutive blocks, quarantining it. The local state is likely corrupted, re-sync the node.", shard_id, mismatches);
+            metrics::QUARANTINED_SHARDS.set(self.quarantined.len() as i64);
+        }
+    }
+
+    /// Clears the mismatches of the shards with a new chunk in an accepted block, since our
+    /// state agreed with them.
+    pub fn record_accepted_block(&mut self, block: &Block) {
+        if self.mismatches.is_empty() {
+            return;
+        }
+        let height = block.header().height();
+        for chunk in block.chunks().iter() {
+            if chunk.height_included() != height {
+                continue;
+            }
+            self.clear(chunk.shard_id());
+        }
+    }
+
+    fn clear(&mut self, shard_id: ShardId) {
+        self.mismatches.remove(&shard_id);
+        if self.quarantined.remove(&shard_id) {
+            info!(target: "client", "State of shard {} agrees with the chain again, lifting quarantine", shard_id);
+            metrics::QUARANTINED_SHARDS.set(self.quarantined.len() as i64);
+        }
+    }
+}
+
+/// Writes the diagnostics as a borsh encoded bundle, along with a JSON summary of the
+/// mismatch. Returns the path of the bundle.
+fn write_diagnostics(
+    dir: &Path,
+    chunk_state: &ChunkState,
+    local_chunk_extra: Option<ChunkExtra>,
+) -> io::Result<PathBuf> {
+    let chunk_header = &chunk_state.chunk_header;
+    let block_hash = BlockHeader::try_from_slice(&chunk_state.block_header)?.hash().to_string();
+    let name = format!(
+        "{}-{}-shard{}",
+        chunk_header.height_included(),
+        block_hash,
+        chunk_header.shard_id()
+    );
+    let summary = serde_json::json!({
+        "block_hash": block_hash,
+        "height": chunk_header.height_included(),
+        "shard_id": chunk_header.shard_id(),
+        "chunk_hash": chunk_header.chunk_hash().0.to_string(),
+        "chunk_prev_state_root": chunk_header.prev_state_root().to_string(),
+        "local_state_root": local_chunk_extra.as_ref().map(|extra| extra.state_root().to_string()),
+        "chunk_prev_outcome_root": chunk_header.outcome_root().to_string(),
+        "local_outcome_root": local_chunk_extra.as_ref().map(|extra| extra.outcome_root().to_string()),
+    });
+    let diagnostics =
+        StateMismatchDiagnostics { chunk_state: chunk_state.clone(), local_chunk_extra };
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.borsh", name));
+    fs::write(&path, diagnostics.try_to_vec()?)?;
+    fs::write(dir.join(format!("{}.json", name)), serde_json::to_vec_pretty(&summary)?)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantine_after_consecutive_mismatches() {
+        let mut quarantine = StateQuarantine::new(Some(StateQuarantineConfig::default()));
+        quarantine.count_mismatch(0);
+        quarantine.count_mismatch(0);
+        quarantine.count_mismatch(1);
+        assert!(!quarantine.is_quarantined(0));
+        quarantine.count_mismatch(0);
+        assert!(quarantine.is_quarantined(0));
+        assert!(!quarantine.is_quarantined(1));
+
+        quarantine.clear(0);
+        assert!(!quarantine.is_quarantined(0));
+        quarantine.count_mismatch(0);
+        assert!(!quarantine.is_quarantined(0));
+    }
+
+    #[test]
+    fn test_disabled_never_quarantines() {
+        let mut quarantine = StateQuarantine::new(None);
+        for _ in 0..10 {
+            quarantine.count_mismatch(0);
+        }
+        assert!(!quarantine.is_quarantined(0));
+    }
+}
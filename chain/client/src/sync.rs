@@ -15,6 +15,7 @@ use rand::{thread_rng, Rng};
 
 use near_chain::{Chain, RuntimeAdapter};
 use near_network::types::{FullPeerInfo, NetworkRequests, NetworkResponses, PeerManagerAdapter};
+use near_primitives::backoff::{Backoff, BackoffConfig};
 use near_primitives::block::Tip;
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
@@ -610,7 +611,8 @@ pub struct StateSync {
     network_adapter: Arc<dyn PeerManagerAdapter>,
 
     state_sync_time: HashMap<ShardId, DateTime<Utc>>,
-    last_time_block_requested: Option<DateTime<Utc>>,
+    /// Resends of the request of the block before the sync hash, while it's missing.
+    block_request_backoff: Option<Backoff>,
 
     last_part_id_requested: HashMap<(AccountOrPeerIdOrHash, ShardId), PendingRequestStatus>,
     /// Map from which part we requested to whom.
@@ -630,7 +632,7 @@ impl StateSync {
         StateSync {
             network_adapter,
             state_sync_time: Default::default(),
-            last_time_block_requested: None,
+            block_request_backoff: None,
             last_part_id_requested: Default::default(),
             requested_target: SizedCache::with_size(MAX_PENDING_PART as usize),
             timeout: Duration::from_std(timeout).unwrap(),
@@ -643,28 +645,29 @@ impl StateSync {
         &mut self,
         prev_hash: &CryptoHash,
         chain: &mut Chain,
-        now: DateTime<Utc>,
     ) -> Result<(bool, bool), near_chain::Error> {
-        let (request_block, have_block) = if !chain.block_exists(prev_hash)? {
-            match self.last_time_block_requested {
-                None => (true, false),
-                Some(last_time) => {
-                    if now - last_time >= self.timeout {
-                        error!(target: "sync", "State sync: block request for {} timed out in {} seconds", prev_hash, self.timeout.num_seconds());
-                        (true, false)
-                    } else {
-                        (false, false)
-                    }
-                }
+        if chain.block_exists(prev_hash)? {
+            self.block_request_backoff = None;
+            return Ok((false, true));
+        }
+        let now = Clock::instant();
+        let request_block = match &mut self.block_request_backoff {
+            None => {
+                // Peers which didn't send the block in time are likely busy, so the request is
+                // resent less and less often.
+                let timeout = self.timeout.to_std().unwrap();
+                let config = BackoffConfig::exponential(timeout, timeout * 4);
+                self.block_request_backoff = Some(Backoff::new(config, now));
+                true
             }
-        } else {
-            self.last_time_block_requested = None;
-            (false, true)
-        };
-        if request_block {
-            self.last_time_block_requested = Some(now);
+            Some(backoff) if backoff.is_due(now) => {
+                error!(target: "sync", "State sync: block request for {} timed out in {} seconds", prev_hash, backoff.delay().as_secs());
+                backoff.record_retry(now);
+                true
+            }
+            Some(_) => false,
         };
-        Ok((request_block, have_block))
+        Ok((request_block, false))
     }
 
     pub fn sync_shards_status(
@@ -682,19 +685,23 @@ impl StateSync {
     ) -> Result<(bool, bool), near_chain::Error> {
         let mut all_done = true;
         let mut update_sync_status = false;
+        // Like the block request, the state header and parts are resent less and less often to
+        // the peers which don't answer them.
+        let timeout = self.timeout.to_std().unwrap();
+        let backoff_config = BackoffConfig::exponential(timeout, timeout * 4);
+        let now_instant = Clock::instant();
+        let new_download = || DownloadStatus {
+            start_time: now,
+            prev_update_time: now,
+            run_me: Arc::new(AtomicBool::new(true)),
+            error: false,
+            done: false,
+            state_requests_count: 0,
+            last_target: None,
+            backoff: Backoff::new(backoff_config.clone(), now_instant),
+        };
         let init_sync_download = ShardSyncDownload {
-            downloads: vec![
-                DownloadStatus {
-                    start_time: now,
-                    prev_update_time: now,
-                    run_me: Arc::new(AtomicBool::new(true)),
-                    error: false,
-                    done: false,
-                    state_requests_count: 0,
-                    last_target: None,
-                };
-                1
-            ],
+            downloads: vec![new_download()],
             status: ShardSyncStatus::StateDownloadHeader,
         };
 
@@ -724,31 +731,21 @@ impl StateSync {
                         let state_num_parts =
                             get_num_state_parts(shard_state_header.state_root_node().memory_usage);
                         *shard_sync_download = ShardSyncDownload {
-                            downloads: vec![
-                                DownloadStatus {
-                                    start_time: now,
-                                    prev_update_time: now,
-                                    run_me: Arc::new(AtomicBool::new(true)),
-                                    error: false,
-                                    done: false,
-                                    state_requests_count: 0,
-                                    last_target: None,
-                                };
-                                state_num_parts as usize
-                            ],
+                            // Every part gets its own jitter, so that their resends spread out.
+                            downloads: (0..state_num_parts).map(|_| new_download()).collect(),
                             status: ShardSyncStatus::StateDownloadParts,
                         };
                         need_shard = true;
                     } else {
-                        let prev = shard_sync_download.downloads[0].prev_update_time;
-                        let error = shard_sync_download.downloads[0].error;
-                        download_timeout = now - prev > self.timeout;
-                        if download_timeout || error {
-                            shard_sync_download.downloads[0].run_me.store(true, Ordering::SeqCst);
-                            shard_sync_download.downloads[0].error = false;
-                            shard_sync_download.downloads[0].prev_update_time = now;
+                        let download = &mut shard_sync_download.downloads[0];
+                        download_timeout = download.backoff.is_due(now_instant);
+                        if download_timeout || download.error {
+                            download.run_me.store(true, Ordering::SeqCst);
+                            download.error = false;
+                            download.prev_update_time = now;
+                            download.backoff.record_retry(now_instant);
                         }
-                        if shard_sync_download.downloads[0].run_me.load(Ordering::SeqCst) {
+                        if download.run_me.load(Ordering::SeqCst) {
                             need_shard = true;
                         }
                     }
@@ -758,14 +755,13 @@ impl StateSync {
                     for part_download in shard_sync_download.downloads.iter_mut() {
                         if !part_download.done {
                             parts_done = false;
-                            let prev = part_download.prev_update_time;
-                            let error = part_download.error;
-                            let part_timeout = now - prev > self.timeout;
-                            if part_timeout || error {
+                            let part_timeout = part_download.backoff.is_due(now_instant);
+                            if part_timeout || part_download.error {
                                 download_timeout |= part_timeout;
                                 part_download.run_me.store(true, Ordering::SeqCst);
                                 part_download.error = false;
                                 part_download.prev_update_time = now;
+                                part_download.backoff.record_retry(now_instant);
                             }
                             if part_download.run_me.load(Ordering::SeqCst) {
                                 need_shard = true;
@@ -889,7 +885,7 @@ impl StateSync {
             all_done &= this_done;
 
             if download_timeout {
-                warn!(target: "sync", "State sync didn't download the state for shard {} in {} seconds, sending StateRequest again", shard_id, self.timeout.num_seconds());
+                warn!(target: "sync", "State sync didn't download the state for shard {} in time, sending StateRequest again", shard_id);
                 info!(target: "sync", "State sync status: me {:?}, sync_hash {}, phase {}",
                       me,
                       sync_hash,
@@ -1175,7 +1171,7 @@ impl StateSync {
         let prev_hash = chain.get_block_header(&sync_hash)?.prev_hash().clone();
         let now = Clock::utc();
 
-        let (request_block, have_block) = self.sync_block_status(&prev_hash, chain, now)?;
+        let (request_block, have_block) = self.sync_block_status(&prev_hash, chain)?;
 
         if tracking_shards.is_empty() {
             // This case is possible if a validator cares about the same shards in the new epoch as
@@ -1460,6 +1456,7 @@ mod test {
                     )
                 };
             let block = Block::produce(
+                PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 &last_block.header(),
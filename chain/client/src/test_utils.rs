@@ -586,6 +586,8 @@ pub fn setup_mock_all_validators(
                             highest_height_peers: peers2,
                             sent_bytes_per_sec: 0,
                             received_bytes_per_sec: 0,
+                            peer_transfer_stats: vec![],
+                            peer_scores: vec![],
                             known_producers: vec![],
                             peer_counter: 0,
                         };
@@ -636,7 +638,10 @@ pub fn setup_mock_all_validators(
                         }
                         NetworkRequests::PartialEncodedChunkResponse { route_back, response } => {
                             let create_msg = || {
-                                NetworkClientMessages::PartialEncodedChunkResponse(response.clone())
+                                NetworkClientMessages::PartialEncodedChunkResponse(
+                                    response.clone(),
+                                    my_key_pair.id.clone(),
+                                )
                             };
                             send_chunks(
                                 Arc::clone(&connectors1),
@@ -653,6 +658,7 @@ pub fn setup_mock_all_validators(
                             let create_msg = || {
                                 NetworkClientMessages::PartialEncodedChunk(
                                     partial_encoded_chunk.clone().into(),
+                                    my_key_pair.id.clone(),
                                 )
                             };
                             send_chunks(
@@ -665,7 +671,10 @@ pub fn setup_mock_all_validators(
                         }
                         NetworkRequests::PartialEncodedChunkForward { account_id, forward } => {
                             let create_msg = || {
-                                NetworkClientMessages::PartialEncodedChunkForward(forward.clone())
+                                NetworkClientMessages::PartialEncodedChunkForward(
+                                    forward.clone(),
+                                    my_key_pair.id.clone(),
+                                )
                             };
                             send_chunks(
                                 Arc::clone(&connectors1),
@@ -1581,6 +1590,7 @@ pub fn create_chunk(
         client.chain.mut_store().get_block_merkle_tree(&last_block.hash()).unwrap().clone();
     block_merkle_tree.insert(*last_block.hash());
     let block = Block::produce(
+        PROTOCOL_VERSION,
         PROTOCOL_VERSION,
         PROTOCOL_VERSION,
         &last_block.header(),
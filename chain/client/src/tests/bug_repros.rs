@@ -90,6 +90,7 @@ fn repro_1183() {
                                         connectors1.write().unwrap()[i].0.do_send(
                                             NetworkClientMessages::PartialEncodedChunk(
                                                 partial_encoded_chunk.clone().into(),
+                                                PeerInfo::random().id,
                                             ),
                                         );
                                     }
@@ -65,6 +65,7 @@ fn query_status_not_crash() {
             let header: BlockHeader = block.header.clone().into();
             block_merkle_tree.insert(*header.hash());
             let mut next_block = Block::produce(
+                PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 &header,
@@ -19,13 +19,20 @@ use near_chain::{
 };
 use near_chain_configs::{ClientConfig, ProtocolConfigView};
 use near_client_primitives::types::{
-    Error, GetBlock, GetBlockError, GetBlockProof, GetBlockProofError, GetBlockProofResponse,
-    GetBlockWithMerkleTree, GetChunkError, GetExecutionOutcome, GetExecutionOutcomeError,
-    GetExecutionOutcomesForBlock, GetGasPrice, GetGasPriceError, GetNextLightClientBlockError,
-    GetProtocolConfig, GetProtocolConfigError, GetReceipt, GetReceiptError, GetStateChangesError,
-    GetStateChangesWithCauseInBlock, GetValidatorInfoError, Query, QueryError, TxStatus,
-    TxStatusError,
+    Error, EstimateTransactionFees, EstimateTransactionFeesError, GetAccessKeyUsage,
+    GetAccessKeyUsageError, GetAccountSummary, GetAccountSummaryError, GetBlock, GetBlockError,
+    GetBlockProof, GetBlockProofError, GetBlockProofResponse, GetBlockTransactionStats,
+    GetBlockTransactionStatsError, GetBlockWithMerkleTree, GetChunkError, GetContractHistory,
+    GetContractHistoryError, GetEpochChainStats, GetEpochChainStatsError, GetExecutionOutcome,
+    GetExecutionOutcomeError, GetExecutionOutcomesForBlock, GetFailedReceipts,
+    GetFailedReceiptsError, GetGasPrice, GetGasPriceError, GetGasThroughput,
+    GetNextLightClientBlockError, GetProtocolConfig, GetProtocolConfigError, GetReceipt,
+    GetReceiptError, GetReceiptInclusionProof, GetReceiptInclusionProofError, GetRefundStats,
+    GetRefundStatsError, GetStateChangesError, GetStateChangesWithCauseInBlock,
+    GetValidatorInfoError, Query, QueryError, SimulateTransaction, SimulateTransactionError,
+    TxStatus, TxStatusError, MAX_BLOCK_TRANSACTION_STATS_RANGE, MAX_EPOCH_CHAIN_STATS_LIMIT,
 };
+use near_crypto::Signature;
 use near_network::types::{NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest};
 #[cfg(feature = "test_features")]
 use near_network_primitives::types::NetworkAdversarialMessage;
@@ -35,6 +42,7 @@ use near_network_primitives::types::{
 };
 use near_performance_metrics_macros::{perf, perf_with_debug};
 use near_primitives::block::{Block, BlockHeader, GenesisId, Tip};
+use near_primitives::cancellation::CancellationToken;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{merklize, PartialMerkleTree};
 use near_primitives::network::AnnounceAccount;
@@ -43,20 +51,26 @@ use near_primitives::syncing::{
     ShardStateSyncResponse, ShardStateSyncResponseHeader, ShardStateSyncResponseV1,
     ShardStateSyncResponseV2,
 };
+use near_primitives::transaction::{Action, SignedTransaction, Transaction};
 use near_primitives::types::{
-    AccountId, BlockHeight, BlockId, BlockReference, EpochId, EpochReference, Finality,
-    MaybeBlockId, ShardId, TransactionOrReceiptId,
+    AccountId, Balance, BlockHeight, BlockId, BlockReference, EpochId, EpochReference, Finality,
+    Gas, MaybeBlockId, ShardId, TransactionOrReceiptId,
 };
+use near_primitives::utils::lockup_account_id;
 use near_primitives::views::validator_stake_view::ValidatorStakeView;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
-    FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus, GasPriceView,
-    LightClientBlockView, QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView,
-    StateChangesView,
+    AccessKeyInfoView, AccessKeyPermissionView, AccessKeySummaryView, AccessKeyUsageView,
+    AccountBalanceView, AccountSummaryView, BlockRefundStatsView, BlockTransactionStatsView,
+    BlockView, ChunkView, ContractDeploymentView, EpochChainStatsView, EpochValidatorInfo,
+    ExecutionOutcomeWithIdView, FailedReceiptView, FinalExecutionOutcomeView,
+    FinalExecutionOutcomeViewEnum, FinalExecutionStatus, GasPriceView, GasThroughputView,
+    LightClientBlockView, LockupSummaryView, QueryRequest, QueryResponse, QueryResponseKind,
+    ReceiptInclusionProofView, ReceiptView, RefundStatsView, SimulateTransactionView,
+    StateChangesKindsView, StateChangesView, TransactionFeesView,
 };
 
 use crate::{
-    sync, GetChunk, GetExecutionOutcomeResponse, GetNextLightClientBlock, GetStateChanges,
+    metrics, sync, GetChunk, GetExecutionOutcomeResponse, GetNextLightClientBlock, GetStateChanges,
     GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
 };
 
@@ -195,6 +209,207 @@ impl ViewClientActor {
         }
     }
 
+    /// The block with the hash, or whether the node garbage collected it or hasn't processed it
+    /// yet when its header is known.
+    fn get_block_by_hash(&mut self, block_hash: &CryptoHash) -> Result<Block, GetBlockError> {
+        match self.chain.get_block(block_hash) {
+            Ok(block) => Ok(block.clone()),
+            Err(err) if matches!(err.kind(), ErrorKind::DBNotFoundErr(_)) => {
+                match self.chain.get_block_header(block_hash) {
+                    Ok(header) => {
+                        let block_height = header.height();
+                        Err(self.missing_block_error(block_height, err))
+                    }
+                    Err(_) => Err(err.into()),
+                }
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The canonical block at the height, or whether the node garbage collected it or hasn't
+    /// processed it yet.
+    fn get_block_by_height(&mut self, block_height: BlockHeight) -> Result<Block, GetBlockError> {
+        match self.chain.get_block_by_height(block_height) {
+            Ok(block) => Ok(block.clone()),
+            Err(err) if matches!(err.kind(), ErrorKind::DBNotFoundErr(_)) => {
+                Err(self.missing_block_error(block_height, err))
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn get_block_by_reference(
+        &mut self,
+        block_reference: &BlockReference,
+    ) -> Result<Block, GetBlockError> {
+        match block_reference {
+            BlockReference::Finality(finality) => {
+                let block_hash = self.get_block_hash_by_finality(finality)?;
+                self.get_block_by_hash(&block_hash)
+            }
+            BlockReference::BlockId(BlockId::Height(height)) => self.get_block_by_height(*height),
+            BlockReference::BlockId(BlockId::Hash(hash)) => self.get_block_by_hash(hash),
+            BlockReference::SyncCheckpoint(sync_checkpoint) => {
+                match self.get_block_hash_by_sync_checkpoint(sync_checkpoint)? {
+                    Some(block_hash) => self.get_block_by_hash(&block_hash),
+                    None => Err(GetBlockError::NotSyncedYet),
+                }
+            }
+        }
+    }
+
+    /// Query of the state at the block, e.g. to make several at the same block.
+    fn query_at_block(
+        &mut self,
+        block_hash: CryptoHash,
+        request: QueryRequest,
+    ) -> Result<QueryResponseKind, QueryError> {
+        let query = Query {
+            query_id: String::new(),
+            block_reference: BlockReference::BlockId(BlockId::Hash(block_hash)),
+            request,
+            cancellation: CancellationToken::new(),
+        };
+        Ok(self.handle_query(query)?.kind)
+    }
+
+    /// The lockup of the account under the factory, if it exists.
+    fn get_lockup_summary(
+        &mut self,
+        block_hash: CryptoHash,
+        lockup_account_id: AccountId,
+        storage_amount_per_byte: Balance,
+    ) -> Result<Option<LockupSummaryView>, QueryError> {
+        let request = QueryRequest::ViewAccount { account_id: lockup_account_id.clone() };
+        let account = match self.query_at_block(block_hash, request) {
+            Ok(QueryResponseKind::ViewAccount(account)) => account,
+            Err(QueryError::UnknownAccount { .. }) => return Ok(None),
+            Ok(_) => {
+                return Err(QueryError::Unreachable {
+                    error_message: "Unexpected response to ViewAccount".to_string(),
+                })
+            }
+            Err(err) => return Err(err),
+        };
+        // Any account could be under the factory, it's not a lockup if the call fails.
+        let request = QueryRequest::CallFunction {
+            account_id: lockup_account_id.clone(),
+            method_name: "get_known_deposited_balance".to_string(),
+            args: b"{}".to_vec().into(),
+        };
+        let staked_balance = match self.query_at_block(block_hash, request) {
+            Ok(QueryResponseKind::CallResult(call_result)) => {
+                serde_json::from_slice::<String>(&call_result.result)
+                    .ok()
+                    .and_then(|balance| balance.parse().ok())
+            }
+            _ => None,
+        };
+        Ok(Some(LockupSummaryView {
+            account_id: lockup_account_id,
+            balance: AccountBalanceView::new(&account, storage_amount_per_byte),
+            staked_balance,
+        }))
+    }
+
+    /// Dry run of the transaction on the states after the block of the shards the node tracks.
+    fn simulate_transaction(
+        &mut self,
+        header: &BlockHeader,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<SimulateTransactionView, SimulateTransactionError> {
+        let epoch_id = header.epoch_id();
+        // The shards the node doesn't track have no chunk extra, their receipts aren't executed.
+        let mut state_roots = HashMap::new();
+        for shard_uid in self.runtime_adapter.get_shard_layout(epoch_id)?.get_shard_uids() {
+            match self.chain.get_chunk_extra(header.hash(), &shard_uid) {
+                Ok(chunk_extra) => {
+                    state_roots.insert(shard_uid.shard_id as ShardId, *chunk_extra.state_root());
+                }
+                Err(err) if matches!(err.kind(), ErrorKind::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let shard_id = self
+            .runtime_adapter
+            .account_id_to_shard_id(&transaction.transaction.signer_id, epoch_id)?;
+        if !state_roots.contains_key(&shard_id) {
+            return Err(SimulateTransactionError::UnavailableShard { shard_id });
+        }
+        let result = self
+            .runtime_adapter
+            .simulate_transaction(header, state_roots, transaction, verify_signature)?
+            .map_err(|error| SimulateTransactionError::InvalidTransaction { error })?;
+        SimulateTransactionView::new(
+            *header.hash(),
+            result.outcomes,
+            result.receipts,
+            result.unexecuted_receipts,
+            result.state_changes,
+        )
+        .map_err(|err| SimulateTransactionError::InternalError { error_message: err.to_string() })
+    }
+
+    /// The chunk of the shard in the block, or whether the node doesn't track the shard when it
+    /// doesn't have the chunk.
+    fn get_chunk_from_block(
+        &mut self,
+        block: Block,
+        shard_id: ShardId,
+    ) -> Result<ShardChunk, GetChunkError> {
+        let chunk_header = block
+            .chunks()
+            .get(shard_id as usize)
+            .ok_or(GetChunkError::InvalidShardId { shard_id })?
+            .clone();
+        let chunk_hash = chunk_header.chunk_hash();
+        let chunk = match self.chain.get_chunk(&chunk_hash) {
+            Ok(chunk) => chunk.clone(),
+            Err(err) if matches!(err.kind(), ErrorKind::ChunkMissing(_)) => {
+                return if self.runtime_adapter.cares_about_shard(
+                    self.validator_account_id.as_ref(),
+                    block.header().prev_hash(),
+                    shard_id,
+                    true,
+                ) {
+                    Err(err.into())
+                } else {
+                    Err(GetChunkError::UnavailableShard { shard_id })
+                };
+            }
+            Err(err) => return Err(err.into()),
+        };
+        ShardChunk::with_header(chunk, chunk_header).ok_or_else(|| GetChunkError::Unreachable {
+            error_message: format!("Mismatched versions for chunk with hash {}", chunk_hash.0),
+        })
+    }
+
+    /// Why the block at the height isn't found: below the tail it's garbage collected, above the
+    /// head it's not processed yet, and otherwise it's unknown, e.g. a skipped height.
+    fn missing_block_error(
+        &self,
+        block_height: BlockHeight,
+        error: near_chain::Error,
+    ) -> GetBlockError {
+        let head_height = match self.chain.head() {
+            Ok(head) => head.height,
+            Err(err) => return err.into(),
+        };
+        let tail_height = match self.chain.tail() {
+            Ok(tail_height) => tail_height,
+            Err(err) => return err.into(),
+        };
+        if block_height < tail_height {
+            GetBlockError::GarbageCollected { block_height, tail_height }
+        } else if block_height > head_height {
+            GetBlockError::NotProcessedYet { block_height, head_height }
+        } else {
+            error.into()
+        }
+    }
+
     fn handle_query(&mut self, msg: Query) -> Result<QueryResponse, QueryError> {
         let header = match msg.block_reference {
             BlockReference::BlockId(BlockId::Height(block_height)) => {
@@ -242,6 +457,7 @@ impl ViewClientActor {
         let account_id = match &msg.request {
             QueryRequest::ViewAccount { account_id, .. } => account_id,
             QueryRequest::ViewState { account_id, .. } => account_id,
+            QueryRequest::ViewStatePage { account_id, .. } => account_id,
             QueryRequest::ViewAccessKey { account_id, .. } => account_id,
             QueryRequest::ViewAccessKeyList { account_id, .. } => account_id,
             QueryRequest::CallFunction { account_id, .. } => account_id,
@@ -526,6 +742,11 @@ impl ViewClientActor {
 
 impl Actor for ViewClientActor {
     type Context = SyncContext<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        // The view client serves RPC and peers, its reads give way to the ones of the client.
+        near_store::set_thread_io_class(near_store::IoClass::Rpc);
+    }
 }
 
 impl Handler<Query> for ViewClientActor {
@@ -533,7 +754,16 @@ impl Handler<Query> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: Query, _: &mut Self::Context) -> Self::Result {
-        self.handle_query(msg)
+        let cancellation = msg.cancellation.clone();
+        let result = if cancellation.is_cancelled() {
+            Err(QueryError::InternalError { error_message: "Query cancelled".to_string() })
+        } else {
+            let _entered = near_primitives::cancellation::enter(cancellation.clone());
+            self.handle_query(msg)
+        };
+        let outcome = if cancellation.is_cancelled() { "cancelled" } else { "completed" };
+        metrics::VIEW_CLIENT_QUERIES.with_label_values(&[outcome]).inc();
+        result
     }
 }
 
@@ -543,27 +773,7 @@ impl Handler<GetBlock> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetBlock, _: &mut Self::Context) -> Self::Result {
-        let block = match msg.0 {
-            BlockReference::Finality(finality) => {
-                let block_hash = self.get_block_hash_by_finality(&finality)?;
-                self.chain.get_block(&block_hash).map(Clone::clone)
-            }
-            BlockReference::BlockId(BlockId::Height(height)) => {
-                self.chain.get_block_by_height(height).map(Clone::clone)
-            }
-            BlockReference::BlockId(BlockId::Hash(hash)) => {
-                self.chain.get_block(&hash).map(Clone::clone)
-            }
-            BlockReference::SyncCheckpoint(sync_checkpoint) => {
-                if let Some(block_hash) =
-                    self.get_block_hash_by_sync_checkpoint(&sync_checkpoint)?
-                {
-                    self.chain.get_block(&block_hash).map(Clone::clone)
-                } else {
-                    return Err(GetBlockError::NotSyncedYet);
-                }
-            }
-        }?;
+        let block = self.get_block_by_reference(&msg.0)?;
 
         let block_author = self
             .runtime_adapter
@@ -592,35 +802,15 @@ impl Handler<GetChunk> for ViewClientActor {
 
     #[perf]
     fn handle(&mut self, msg: GetChunk, _: &mut Self::Context) -> Self::Result {
-        let get_chunk_from_block = |block: Block,
-                                    shard_id: ShardId,
-                                    chain: &mut Chain|
-         -> Result<ShardChunk, near_chain::Error> {
-            let chunk_header = block
-                .chunks()
-                .get(shard_id as usize)
-                .ok_or_else(|| near_chain::Error::from(ErrorKind::InvalidShardId(shard_id)))?
-                .clone();
-            let chunk_hash = chunk_header.chunk_hash();
-            chain.get_chunk(&chunk_hash).and_then(|chunk| {
-                ShardChunk::with_header(chunk.clone(), chunk_header).ok_or(near_chain::Error::from(
-                    ErrorKind::Other(format!(
-                        "Mismatched versions for chunk with hash {}",
-                        chunk_hash.0
-                    )),
-                ))
-            })
-        };
-
         let chunk = match msg {
             GetChunk::ChunkHash(chunk_hash) => self.chain.get_chunk(&chunk_hash)?.clone(),
             GetChunk::BlockHash(block_hash, shard_id) => {
-                let block = self.chain.get_block(&block_hash)?.clone();
-                get_chunk_from_block(block, shard_id, &mut self.chain)?
+                let block = self.get_block_by_hash(&block_hash)?;
+                self.get_chunk_from_block(block, shard_id)?
             }
             GetChunk::Height(height, shard_id) => {
-                let block = self.chain.get_block_by_height(height)?.clone();
-                get_chunk_from_block(block, shard_id, &mut self.chain)?
+                let block = self.get_block_by_height(height)?;
+                self.get_chunk_from_block(block, shard_id)?
             }
         };
 
@@ -1319,6 +1509,367 @@ impl Handler<GetGasPrice> for ViewClientActor {
     }
 }
 
+impl Handler<GetGasThroughput> for ViewClientActor {
+    type Result = Result<GasThroughputView, String>;
+
+    #[perf]
+    fn handle(&mut self, _msg: GetGasThroughput, _ctx: &mut Self::Context) -> Self::Result {
+        let estimator = near_chain::gas_throughput::GAS_THROUGHPUT
+            .lock()
+            .map_err(|_| "gas throughput lock poisoned".to_string())?;
+        Ok(estimator.view())
+    }
+}
+
+impl Handler<GetBlockTransactionStats> for ViewClientActor {
+    type Result = Result<Vec<BlockTransactionStatsView>, GetBlockTransactionStatsError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetBlockTransactionStats, _ctx: &mut Self::Context) -> Self::Result {
+        let GetBlockTransactionStats { from_height, to_height } = msg;
+        if from_height > to_height || to_height - from_height >= MAX_BLOCK_TRANSACTION_STATS_RANGE {
+            return Err(GetBlockTransactionStatsError::InvalidRange {
+                from_height,
+                to_height,
+                max_range: MAX_BLOCK_TRANSACTION_STATS_RANGE,
+            });
+        }
+        let mut result = vec![];
+        for height in from_height..=to_height {
+            // Skipped heights and blocks processed before the statistics were recorded,
+            // or already garbage collected, are left out.
+            let block_hash = match self.chain.mut_store().get_block_hash_by_height(height) {
+                Ok(block_hash) => block_hash,
+                Err(err) if matches!(err.kind(), ErrorKind::DBNotFoundErr(_)) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            match self.chain.store().get_block_transaction_stats(&block_hash) {
+                Ok(stats) => result.push(BlockTransactionStatsView::new(height, block_hash, stats)),
+                Err(err) if matches!(err.kind(), ErrorKind::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Handler<GetEpochChainStats> for ViewClientActor {
+    type Result = Result<Vec<EpochChainStatsView>, GetEpochChainStatsError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetEpochChainStats, _ctx: &mut Self::Context) -> Self::Result {
+        let GetEpochChainStats { limit } = msg;
+        if limit == 0 || limit > MAX_EPOCH_CHAIN_STATS_LIMIT {
+            return Err(GetEpochChainStatsError::InvalidLimit {
+                limit,
+                max_limit: MAX_EPOCH_CHAIN_STATS_LIMIT,
+            });
+        }
+        let mut epochs = self.chain.store().get_all_epoch_chain_stats()?;
+        epochs.sort_by_key(|(_, stats)| stats.first_block_height);
+        let skip = epochs.len().saturating_sub(limit as usize);
+        Ok(epochs
+            .into_iter()
+            .skip(skip)
+            .map(|(epoch_id, stats)| EpochChainStatsView::new(epoch_id, stats))
+            .collect())
+    }
+}
+
+impl Handler<SimulateTransaction> for ViewClientActor {
+    type Result = Result<SimulateTransactionView, SimulateTransactionError>;
+
+    #[perf]
+    fn handle(&mut self, msg: SimulateTransaction, _ctx: &mut Self::Context) -> Self::Result {
+        let SimulateTransaction { block_reference, transaction, verify_signature } = msg;
+        let block = self.get_block_by_reference(&block_reference)?;
+        self.simulate_transaction(block.header(), &transaction, verify_signature)
+    }
+}
+
+impl Handler<EstimateTransactionFees> for ViewClientActor {
+    type Result = Result<TransactionFeesView, EstimateTransactionFeesError>;
+
+    #[perf]
+    fn handle(&mut self, msg: EstimateTransactionFees, _ctx: &mut Self::Context) -> Self::Result {
+        let EstimateTransactionFees {
+            block_reference,
+            signer_id,
+            public_key,
+            receiver_id,
+            actions,
+        } = msg;
+        let block = self
+            .get_block_by_reference(&block_reference)
+            .map_err(SimulateTransactionError::from)?;
+        let header = block.header();
+        let block_hash = *header.hash();
+
+        let (public_key, access_key) = match public_key {
+            Some(public_key) => {
+                let request = QueryRequest::ViewAccessKey {
+                    account_id: signer_id.clone(),
+                    public_key: public_key.clone(),
+                };
+                match self.query_at_block(block_hash, request)? {
+                    QueryResponseKind::AccessKey(access_key) => (public_key, access_key),
+                    _ => {
+                        return Err(EstimateTransactionFeesError::Unreachable {
+                            error_message: "Unexpected response to ViewAccessKey".to_string(),
+                        })
+                    }
+                }
+            }
+            None => {
+                let request = QueryRequest::ViewAccessKeyList { account_id: signer_id.clone() };
+                let keys = match self.query_at_block(block_hash, request)? {
+                    QueryResponseKind::AccessKeyList(access_key_list) => access_key_list.keys,
+                    _ => {
+                        return Err(EstimateTransactionFeesError::Unreachable {
+                            error_message: "Unexpected response to ViewAccessKeyList".to_string(),
+                        })
+                    }
+                };
+                let key = keys
+                    .into_iter()
+                    .find(|key| key.access_key.permission == AccessKeyPermissionView::FullAccess)
+                    .ok_or_else(|| EstimateTransactionFeesError::NoFullAccessKey {
+                        account_id: signer_id.clone(),
+                    })?;
+                (key.public_key, key.access_key)
+            }
+        };
+        let transaction = Transaction {
+            signer_id,
+            public_key,
+            nonce: access_key.nonce + 1,
+            receiver_id,
+            block_hash,
+            actions,
+        };
+        let fees = self.runtime_adapter.get_transaction_fees(
+            header.epoch_id(),
+            &transaction,
+            header.gas_price(),
+        )?;
+        let creates_account =
+            transaction.actions.iter().any(|action| matches!(action, Action::CreateAccount(_)));
+
+        // Run as if signed, the signature can't be checked before the user signs.
+        let key_type = transaction.public_key.key_type();
+        let signed_transaction = SignedTransaction::new(Signature::empty(key_type), transaction);
+        let simulation = self.simulate_transaction(header, &signed_transaction, false)?;
+        let receipts_gas_burnt: Gas =
+            simulation.receipts_outcome.iter().map(|outcome| outcome.outcome.gas_burnt).sum();
+        Ok(TransactionFeesView {
+            block_hash,
+            gas_price: header.gas_price(),
+            send_gas: fees.send_gas,
+            exec_gas: fees.exec_gas,
+            prepaid_gas: fees.prepaid_gas,
+            receipt_gas_price: fees.receipt_gas_price,
+            total_cost: fees.total_cost,
+            storage_usage_increase: fees.storage_usage_increase,
+            storage_cost: fees.storage_cost,
+            min_deposit: if creates_account { fees.storage_cost } else { 0 },
+            status: simulation.status,
+            receipts_gas_burnt,
+            estimated_prepaid_gas: receipts_gas_burnt.saturating_sub(fees.exec_gas),
+        })
+    }
+}
+
+impl Handler<GetAccountSummary> for ViewClientActor {
+    type Result = Result<AccountSummaryView, GetAccountSummaryError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetAccountSummary, _ctx: &mut Self::Context) -> Self::Result {
+        let GetAccountSummary { block_reference, account_id, lockup_factory_id } = msg;
+        let block = self.get_block_by_reference(&block_reference)?;
+        let header = block.header();
+        let block_hash = *header.hash();
+        let storage_amount_per_byte = self
+            .runtime_adapter
+            .get_protocol_config(header.epoch_id())?
+            .runtime_config
+            .storage_amount_per_byte;
+
+        let account = match self.query_at_block(
+            block_hash,
+            QueryRequest::ViewAccount { account_id: account_id.clone() },
+        )? {
+            QueryResponseKind::ViewAccount(account) => account,
+            _ => {
+                return Err(GetAccountSummaryError::Unreachable {
+                    error_message: "Unexpected response to ViewAccount".to_string(),
+                })
+            }
+        };
+        let request = QueryRequest::ViewAccessKeyList { account_id: account_id.clone() };
+        let keys = match self.query_at_block(block_hash, request)? {
+            QueryResponseKind::AccessKeyList(access_key_list) => access_key_list.keys,
+            _ => {
+                return Err(GetAccountSummaryError::Unreachable {
+                    error_message: "Unexpected response to ViewAccessKeyList".to_string(),
+                })
+            }
+        };
+        let mut last_used_block_heights = HashMap::new();
+        if self.config.save_access_key_usage {
+            for (public_key, usages) in
+                self.chain.store().get_account_access_key_usage(&account_id)?
+            {
+                if let Some(height) = usages.iter().map(|usage| usage.last_used_block_height).max()
+                {
+                    last_used_block_heights.insert(public_key, height);
+                }
+            }
+        }
+        let max_nonce = keys.iter().map(|key| key.access_key.nonce).max().unwrap_or_default();
+        let access_keys = keys
+            .into_iter()
+            .map(|AccessKeyInfoView { public_key, access_key }| AccessKeySummaryView {
+                last_used_block_height: last_used_block_heights.get(&public_key).copied(),
+                public_key,
+                access_key,
+            })
+            .collect();
+
+        let lockup_factory_id = lockup_factory_id.or_else(|| {
+            let (_, top_level_account_id) = account_id.as_ref().rsplit_once('.')?;
+            format!("lockup.{}", top_level_account_id).parse().ok()
+        });
+        let lockup = match lockup_factory_id
+            .and_then(|lockup_factory_id| lockup_account_id(&account_id, &lockup_factory_id))
+        {
+            Some(lockup_account_id) => {
+                self.get_lockup_summary(block_hash, lockup_account_id, storage_amount_per_byte)?
+            }
+            None => None,
+        };
+
+        Ok(AccountSummaryView {
+            balance: AccountBalanceView::new(&account, storage_amount_per_byte),
+            account_id,
+            block_hash,
+            block_height: header.height(),
+            access_keys,
+            max_nonce,
+            lockup,
+        })
+    }
+}
+
+impl Handler<GetAccessKeyUsage> for ViewClientActor {
+    type Result = Result<Vec<AccessKeyUsageView>, GetAccessKeyUsageError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetAccessKeyUsage, _ctx: &mut Self::Context) -> Self::Result {
+        if !self.config.save_access_key_usage {
+            return Err(GetAccessKeyUsageError::NotSaved);
+        }
+        let mut result = vec![];
+        for (public_key, usages) in
+            self.chain.store().get_account_access_key_usage(&msg.account_id)?
+        {
+            result.extend(usages.into_iter().map(|usage| AccessKeyUsageView {
+                public_key: public_key.clone(),
+                receiver_id: usage.receiver_id,
+                method_name: usage.method_name,
+                count: usage.count,
+                last_used_block_height: usage.last_used_block_height,
+                last_used_tx_hash: usage.last_used_tx_hash,
+            }));
+        }
+        Ok(result)
+    }
+}
+
+impl Handler<GetContractHistory> for ViewClientActor {
+    type Result = Result<Vec<ContractDeploymentView>, GetContractHistoryError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetContractHistory, _ctx: &mut Self::Context) -> Self::Result {
+        if !self.config.save_contract_history {
+            return Err(GetContractHistoryError::NotSaved);
+        }
+        let history = self.chain.store().get_contract_history(&msg.account_id)?;
+        Ok(ContractDeploymentView::from_history(history, msg.with_diff))
+    }
+}
+
+impl Handler<GetReceiptInclusionProof> for ViewClientActor {
+    type Result = Result<ReceiptInclusionProofView, GetReceiptInclusionProofError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetReceiptInclusionProof, _ctx: &mut Self::Context) -> Self::Result {
+        let block_hash = self.maybe_block_id_to_block_hash(Some(msg.block_id))?;
+        self.chain.get_receipt_inclusion_proof(&msg.receipt_id, &block_hash)?.ok_or(
+            GetReceiptInclusionProofError::ReceiptNotInBlock {
+                receipt_id: msg.receipt_id,
+                block_hash,
+            },
+        )
+    }
+}
+
+impl Handler<GetFailedReceipts> for ViewClientActor {
+    type Result = Result<Vec<FailedReceiptView>, GetFailedReceiptsError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetFailedReceipts, _ctx: &mut Self::Context) -> Self::Result {
+        let block_hash = self.maybe_block_id_to_block_hash(Some(msg.block_id))?;
+        let block = self.chain.get_block(&block_hash)?.clone();
+        let block_height = block.header().height();
+        let mut result = vec![];
+        for shard_id in 0..block.chunks().len() as ShardId {
+            for failed_receipt in self.chain.store().get_failed_receipts(&block_hash, shard_id)? {
+                let receipt = self
+                    .chain
+                    .mut_store()
+                    .get_receipt(&failed_receipt.receipt_id)?
+                    .map(|receipt| receipt.clone().into());
+                result.push(FailedReceiptView {
+                    receipt_id: failed_receipt.receipt_id,
+                    block_hash,
+                    block_height,
+                    shard_id,
+                    executor_id: failed_receipt.executor_id,
+                    error: failed_receipt.error,
+                    receipt,
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Handler<GetRefundStats> for ViewClientActor {
+    type Result = Result<BlockRefundStatsView, GetRefundStatsError>;
+
+    #[perf]
+    fn handle(&mut self, msg: GetRefundStats, _ctx: &mut Self::Context) -> Self::Result {
+        let block_hash = self.maybe_block_id_to_block_hash(Some(msg.block_id))?;
+        let block = self.chain.get_block(&block_hash)?.clone();
+        let block_height = block.header().height();
+        let mut shards = vec![];
+        for (shard_id, chunk_header) in block.chunks().iter().enumerate() {
+            // The receipts of an old chunk are the ones of the block it was included in.
+            if chunk_header.height_included() != block_height {
+                continue;
+            }
+            let shard_id = shard_id as ShardId;
+            match self.chain.mut_store().get_outgoing_receipts(&block_hash, shard_id) {
+                Ok(receipts) => shards.push(RefundStatsView::new(shard_id, receipts)),
+                // The node doesn't track the shard.
+                Err(err) if matches!(err.kind(), ErrorKind::DBNotFoundErr(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(BlockRefundStatsView { block_hash, block_height, shards })
+    }
+}
+
 /// Starts the View Client in a new arbiter (thread).
 pub fn start_view_client(
     validator_account_id: Option<AccountId>,
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use serde::{Deserialize, Serialize};
+
+use crate::{Publisher, SinkError};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KafkaConfig {
+    pub brokers: Vec<String>,
+    /// How long a message may wait in the producer queue before being reported as failed.
+    #[serde(default = "default_queue_timeout")]
+    pub queue_timeout: Duration,
+    /// Additional librdkafka producer properties.
+    #[serde(default)]
+    pub properties: HashMap<String, String>,
+}
+
+fn default_queue_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+pub struct KafkaPublisher {
+    producer: FutureProducer,
+    queue_timeout: Duration,
+}
+
+impl KafkaPublisher {
+    pub fn new(config: &KafkaConfig) -> Result<Self, SinkError> {
+        let mut client_config = ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", config.brokers.join(","))
+            // Messages are acknowledged once replicated to all in sync replicas, and the producer
+            // retries don't duplicate them.
+            .set("acks", "all")
+            .set("enable.idempotence", "true");
+        for (key, value) in config.properties.iter() {
+            client_config.set(key, value);
+        }
+        let producer = client_config.create().map_err(|err| SinkError::Broker(err.to_string()))?;
+        Ok(Self { producer, queue_timeout: config.queue_timeout })
+    }
+}
+
+impl Publisher for KafkaPublisher {
+    fn publish<'a>(
+        &'a self,
+        topic: &'a str,
+        key: &'a str,
+        payload: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), SinkError>> {
+        Box::pin(async move {
+            let record = FutureRecord::to(topic).key(key).payload(payload);
+            self.producer
+                .send(record, Timeout::After(self.queue_timeout))
+                .await
+                .map(|_| ())
+                .map_err(|(err, _)| SinkError::Broker(err.to_string()))
+        })
+    }
+}
@@ -0,0 +1,327 @@
+//! Push-based streaming of the state changes and execution outcomes of every block to a message
+//! broker, for consumers which can't keep up with pulling from the indexer framework.
+//!
+//! Every block is turned into one message per configured topic and payload kind. The height of
+//! the last block whose messages were all acknowledged by the broker is checkpointed to a file,
+//! and streaming resumes from the next block after a restart: the delivery is at least once, a
+//! block may be published again if the node stopped before its checkpoint was written.
+//! Consumers deduplicate messages by block hash and kind.
+//!
+//! ```ignore
+//! let sink_config = SinkConfig::from_file(&path)?;
+//! let indexer = Indexer::new(IndexerConfig { sync_mode: sink_config.sync_mode()?, .. });
+//! let publisher = KafkaPublisher::new(&kafka_config)?;
+//! actix::spawn(near_indexer_sinks::run(sink_config, chain_id, publisher, indexer.streamer()));
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{fmt, fs, io};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockHeight};
+use near_primitives::views::{
+    ExecutionOutcomeWithIdView, StateChangeValueView, StateChangeWithCauseView,
+};
+
+use near_indexer::{StreamerMessage, SyncModeEnum, INDEXER};
+
+#[cfg(feature = "kafka")]
+pub mod kafka;
+#[cfg(feature = "nats")]
+pub mod nats;
+
+/// Version of the layout of the published messages, increased on every incompatible change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum SinkError {
+    Broker(String),
+    Serialization(serde_json::Error),
+    Checkpoint(io::Error),
+}
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SinkError::Broker(err) => write!(f, "broker error: {}", err),
+            SinkError::Serialization(err) => write!(f, "serialization error: {}", err),
+            SinkError::Checkpoint(err) => write!(f, "checkpoint error: {}", err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SinkError {
+    fn from(error: serde_json::Error) -> Self {
+        SinkError::Serialization(error)
+    }
+}
+
+impl From<io::Error> for SinkError {
+    fn from(error: io::Error) -> Self {
+        SinkError::Checkpoint(error)
+    }
+}
+
+/// Client of a message broker.
+pub trait Publisher: Send + Sync {
+    /// Resolves once the broker acknowledged the message.
+    fn publish<'a>(
+        &'a self,
+        topic: &'a str,
+        key: &'a str,
+        payload: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), SinkError>>;
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadKind {
+    StateChanges,
+    ExecutionOutcomes,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TopicConfig {
+    /// Kafka topic or NATS subject.
+    pub name: String,
+    /// Payloads published to the topic, all of them if not set.
+    #[serde(default)]
+    pub kinds: Option<Vec<PayloadKind>>,
+    /// Only the state changes of these accounts and the outcomes they executed, all of them if
+    /// not set.
+    #[serde(default)]
+    pub accounts: Option<HashSet<AccountId>>,
+}
+
+impl TopicConfig {
+    fn publishes(&self, kind: PayloadKind) -> bool {
+        self.kinds.as_ref().map_or(true, |kinds| kinds.contains(&kind))
+    }
+
+    fn matches(&self, account_id: &AccountId) -> bool {
+        self.accounts.as_ref().map_or(true, |accounts| accounts.contains(account_id))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SinkConfig {
+    pub topics: Vec<TopicConfig>,
+    /// File keeping the height of the last block published to every topic.
+    pub checkpoint_file: PathBuf,
+}
+
+impl SinkConfig {
+    pub fn from_file(path: &Path) -> Result<Self, SinkError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Resumes after the last published block, or starts from the latest one.
+    pub fn sync_mode(&self) -> Result<SyncModeEnum, SinkError> {
+        Ok(match read_checkpoint(&self.checkpoint_file)? {
+            Some(height) => SyncModeEnum::BlockHeight(height + 1),
+            None => SyncModeEnum::LatestSynced,
+        })
+    }
+}
+
+/// Layout of the published messages.
+#[derive(Serialize, Debug)]
+pub struct Envelope<'a, T> {
+    pub schema_version: u32,
+    pub chain_id: &'a str,
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+    pub kind: PayloadKind,
+    pub payload: Vec<&'a T>,
+}
+
+pub(crate) struct SinkMessage {
+    pub topic: String,
+    pub key: String,
+    pub payload: Vec<u8>,
+}
+
+fn state_change_account_id(state_change: &StateChangeWithCauseView) -> &AccountId {
+    match &state_change.value {
+        StateChangeValueView::AccountUpdate { account_id, .. }
+        | StateChangeValueView::AccountDeletion { account_id }
+        | StateChangeValueView::AccessKeyUpdate { account_id, .. }
+        | StateChangeValueView::AccessKeyDeletion { account_id, .. }
+        | StateChangeValueView::DataUpdate { account_id, .. }
+        | StateChangeValueView::DataDeletion { account_id, .. }
+        | StateChangeValueView::ContractCodeUpdate { account_id, .. }
+        | StateChangeValueView::ContractCodeDeletion { account_id } => account_id,
+    }
+}
+
+fn execution_outcomes(message: &StreamerMessage) -> Vec<&ExecutionOutcomeWithIdView> {
+    let mut outcomes = vec![];
+    for shard in message.shards.iter() {
+        if let Some(chunk) = &shard.chunk {
+            outcomes.extend(chunk.transactions.iter().map(|tx| &tx.outcome.execution_outcome));
+        }
+        outcomes.extend(shard.receipt_execution_outcomes.iter().map(|o| &o.execution_outcome));
+    }
+    outcomes
+}
+
+fn envelope<'a, T: Serialize>(
+    message: &StreamerMessage,
+    chain_id: &'a str,
+    kind: PayloadKind,
+    payload: Vec<&'a T>,
+) -> Result<Vec<u8>, SinkError> {
+    Ok(serde_json::to_vec(&Envelope {
+        schema_version: SCHEMA_VERSION,
+        chain_id,
+        block_height: message.block.header.height,
+        block_hash: message.block.header.hash,
+        kind,
+        payload,
+    })?)
+}
+
+/// Messages of a block for every topic. Topics with nothing to publish for the block get no
+/// message.
+pub(crate) fn build_messages(
+    topics: &[TopicConfig],
+    chain_id: &str,
+    message: &StreamerMessage,
+) -> Result<Vec<SinkMessage>, SinkError> {
+    let key = message.block.header.height.to_string();
+    let outcomes = execution_outcomes(message);
+    let mut messages = vec![];
+    for topic in topics {
+        if topic.publishes(PayloadKind::StateChanges) {
+            let state_changes: Vec<&StateChangeWithCauseView> = message
+                .state_changes
+                .iter()
+                .filter(|state_change| topic.matches(state_change_account_id(state_change)))
+                .collect();
+            if !state_changes.is_empty() {
+                let payload =
+                    envelope(message, chain_id, PayloadKind::StateChanges, state_changes)?;
+                messages.push(SinkMessage { topic: topic.name.clone(), key: key.clone(), payload });
+            }
+        }
+        if topic.publishes(PayloadKind::ExecutionOutcomes) {
+            let outcomes: Vec<&ExecutionOutcomeWithIdView> = outcomes
+                .iter()
+                .copied()
+                .filter(|outcome| topic.matches(&outcome.outcome.executor_id))
+                .collect();
+            if !outcomes.is_empty() {
+                let payload =
+                    envelope(message, chain_id, PayloadKind::ExecutionOutcomes, outcomes)?;
+                messages.push(SinkMessage { topic: topic.name.clone(), key: key.clone(), payload });
+            }
+        }
+    }
+    Ok(messages)
+}
+
+fn read_checkpoint(path: &Path) -> Result<Option<BlockHeight>, SinkError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content.trim().parse().map_err(|err| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("invalid checkpoint: {}", err))
+        })?)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Replaces the checkpoint atomically, so that it is never left half written.
+fn write_checkpoint(path: &Path, height: BlockHeight) -> Result<(), SinkError> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, height.to_string())?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Publishes the messages, retrying the ones the broker didn't acknowledge until it does.
+async fn publish_all<P: Publisher>(publisher: &P, messages: Vec<SinkMessage>) {
+    let mut delay = MIN_RETRY_DELAY;
+    for message in messages {
+        while let Err(err) = publisher.publish(&message.topic, &message.key, &message.payload).await
+        {
+            warn!(
+                target: INDEXER,
+                "Failed to publish block {} to {}: {}, retrying in {:?}",
+                message.key,
+                message.topic,
+                err,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        }
+        delay = MIN_RETRY_DELAY;
+    }
+}
+
+/// Publishes the blocks of the stream until it ends.
+pub async fn run<P: Publisher>(
+    config: SinkConfig,
+    chain_id: String,
+    publisher: P,
+    mut stream: mpsc::Receiver<StreamerMessage>,
+) -> Result<(), SinkError> {
+    info!(target: INDEXER, "Streaming blocks to {} topics", config.topics.len());
+    while let Some(message) = stream.recv().await {
+        let messages = build_messages(&config.topics, &chain_id, &message)?;
+        publish_all(&publisher, messages).await;
+        write_checkpoint(&config.checkpoint_file, message.block.header.height)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::views::StateChangeCauseView;
+
+    fn account_deletion(account_id: &str) -> StateChangeWithCauseView {
+        StateChangeWithCauseView {
+            cause: StateChangeCauseView::InitialState,
+            value: StateChangeValueView::AccountDeletion {
+                account_id: account_id.parse().unwrap(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_topic_filters() {
+        let topic = TopicConfig {
+            name: "alice".to_string(),
+            kinds: Some(vec![PayloadKind::StateChanges]),
+            accounts: Some(vec!["alice.near".parse().unwrap()].into_iter().collect()),
+        };
+        assert!(topic.publishes(PayloadKind::StateChanges));
+        assert!(!topic.publishes(PayloadKind::ExecutionOutcomes));
+        assert!(topic.matches(state_change_account_id(&account_deletion("alice.near"))));
+        assert!(!topic.matches(state_change_account_id(&account_deletion("bob.near"))));
+
+        let topic: TopicConfig = serde_json::from_str(r#"{"name": "all"}"#).unwrap();
+        assert!(topic.publishes(PayloadKind::ExecutionOutcomes));
+        assert!(topic.matches(&"bob.near".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SinkConfig { topics: vec![], checkpoint_file: dir.path().join("checkpoint") };
+        assert!(matches!(config.sync_mode().unwrap(), SyncModeEnum::LatestSynced));
+        write_checkpoint(&config.checkpoint_file, 42).unwrap();
+        assert!(matches!(config.sync_mode().unwrap(), SyncModeEnum::BlockHeight(43)));
+    }
+}
@@ -0,0 +1,44 @@
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::{Publisher, SinkError};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NatsConfig {
+    pub url: String,
+}
+
+/// Publishes to JetStream, which acknowledges messages once they are stored by the stream of
+/// the subject.
+pub struct NatsPublisher {
+    jetstream: nats_client::jetstream::JetStream,
+}
+
+impl NatsPublisher {
+    pub fn new(config: &NatsConfig) -> Result<Self, SinkError> {
+        let connection =
+            nats_client::connect(&config.url).map_err(|err| SinkError::Broker(err.to_string()))?;
+        Ok(Self { jetstream: nats_client::jetstream::new(connection) })
+    }
+}
+
+impl Publisher for NatsPublisher {
+    fn publish<'a>(
+        &'a self,
+        topic: &'a str,
+        _key: &'a str,
+        payload: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), SinkError>> {
+        let jetstream = self.jetstream.clone();
+        let subject = topic.to_string();
+        let payload = payload.to_vec();
+        Box::pin(async move {
+            // The client is blocking.
+            tokio::task::spawn_blocking(move || jetstream.publish(&subject, payload))
+                .await
+                .map_err(|err| SinkError::Broker(err.to_string()))?
+                .map(|_| ())
+                .map_err(|err| SinkError::Broker(err.to_string()))
+        })
+    }
+}
@@ -75,6 +75,9 @@ pub struct IndexerConfig {
     pub sync_mode: SyncModeEnum,
     /// Whether await for node to be synced or not
     pub await_for_node_synced: AwaitForNodeSyncedEnum,
+    /// Number of blocks whose data is missing or which wait for a missing block, e.g. while the
+    /// node catches up with a shard, above which the lowest missing block is skipped
+    pub max_buffered_blocks: usize,
 }
 
 /// This is the core component, which handles `nearcore` and internal `streamer`.
@@ -110,6 +113,10 @@ impl Indexer {
     }
 
     /// Boots up `near_indexer::streamer`, so it monitors the new blocks with chunks, transactions, receipts, and execution outcomes inside. The returned stream handler should be drained and handled on the user side.
+    ///
+    /// The messages are the ones of the final blocks, in strictly increasing order of height. The
+    /// blocks whose data is missing are retried before any later block is emitted, until more
+    /// than `max_buffered_blocks` are waiting: they are then skipped and never emitted.
     pub fn streamer(&self) -> mpsc::Receiver<streamer::StreamerMessage> {
         let (sender, receiver) = mpsc::channel(16);
         actix::spawn(streamer::start(
@@ -5,7 +5,7 @@ use async_recursion::async_recursion;
 use rocksdb::DB;
 use tokio::sync::mpsc;
 use tokio::time;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use near_primitives::hash::CryptoHash;
 pub use near_primitives::views;
@@ -17,6 +17,7 @@ use self::fetchers::{
     fetch_block_by_hash, fetch_block_by_height, fetch_chunks, fetch_latest_block, fetch_outcomes,
     fetch_state_changes, fetch_status,
 };
+use self::reorder::ReorderBuffer;
 pub use self::types::{
     IndexerChunkView, IndexerExecutionOutcomeWithOptionalReceipt,
     IndexerExecutionOutcomeWithReceipt, IndexerShard, IndexerTransactionWithOutcome,
@@ -28,6 +29,7 @@ use crate::INDEXER;
 
 mod errors;
 mod fetchers;
+mod reorder;
 mod types;
 mod utils;
 
@@ -289,6 +291,7 @@ pub(crate) async fn start(
     // TODO: implement proper error handling
     let db = DB::open_default(indexer_db_path).unwrap();
     let mut last_synced_block_height: Option<near_primitives::types::BlockHeight> = None;
+    let mut reorder_buffer = ReorderBuffer::new(indexer_config.max_buffered_blocks);
 
     'main: loop {
         time::sleep(INTERVAL).await;
@@ -334,32 +337,52 @@ pub(crate) async fn start(
             start_syncing_block_height,
             latest_block_height
         );
-        for block_height in start_syncing_block_height..=latest_block_height {
+        let heights = reorder_buffer
+            .missing_heights()
+            .into_iter()
+            .chain(start_syncing_block_height..=latest_block_height);
+        for block_height in heights {
             if let Ok(block) = fetch_block_by_height(&view_client, block_height).await {
                 let response = build_streamer_message(&view_client, block).await;
 
                 match response {
-                    Ok(streamer_message) => {
-                        debug!(target: INDEXER, "{:#?}", &streamer_message);
-                        if blocks_sink.send(streamer_message).await.is_err() {
-                            info!(
-                                target: INDEXER,
-                                "Unable to send StreamerMessage to listener, listener doesn't listen. terminating..."
-                            );
-                            break 'main;
-                        }
-                    }
+                    Ok(streamer_message) => reorder_buffer.push(block_height, streamer_message),
                     Err(err) => {
                         debug!(
                             target: INDEXER,
-                            "Missing data, skipping block #{}...", block_height
+                            "Missing data, retrying block #{} later...", block_height
                         );
                         debug!(target: INDEXER, "{:#?}", err);
+                        reorder_buffer.push_missing(block_height);
                     }
                 }
             }
-            db.put(b"last_synced_block_height", &block_height.to_string()).unwrap();
-            last_synced_block_height = Some(block_height);
+
+            let (streamer_messages, skipped_heights) = reorder_buffer.drain();
+            for skipped_height in skipped_heights {
+                warn!(
+                    target: INDEXER,
+                    "Missing data, skipping block #{} after buffering {} blocks",
+                    skipped_height,
+                    indexer_config.max_buffered_blocks
+                );
+            }
+            for streamer_message in streamer_messages {
+                debug!(target: INDEXER, "{:#?}", &streamer_message);
+                if blocks_sink.send(streamer_message).await.is_err() {
+                    info!(
+                        target: INDEXER,
+                        "Unable to send StreamerMessage to listener, listener doesn't listen. terminating..."
+                    );
+                    break 'main;
+                }
+            }
+
+            let last_fetched_block_height =
+                last_synced_block_height.map_or(block_height, |height| height.max(block_height));
+            let settled_block_height = reorder_buffer.settled_up_to(last_fetched_block_height);
+            db.put(b"last_synced_block_height", &settled_block_height.to_string()).unwrap();
+            last_synced_block_height = Some(last_fetched_block_height);
         }
     }
 }
@@ -0,0 +1,102 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use near_primitives::types::BlockHeight;
+
+/// Keeps the messages in the order of the heights of their blocks.
+///
+/// The data of a block may be missing for a while, e.g. the chunks of a shard the node is
+/// still catching up with. The messages of the next blocks are buffered until it's found, so
+/// that they aren't emitted before it, as long as there are at most `max_buffered` blocks
+/// missing or buffered: the lowest missing block is then skipped rather than holding the stream
+/// back any longer.
+pub(crate) struct ReorderBuffer<T> {
+    /// Heights of the blocks which data is missing, retried on every iteration.
+    missing: BTreeSet<BlockHeight>,
+    /// Messages waiting for the lowest missing block.
+    ready: BTreeMap<BlockHeight, T>,
+    max_buffered: usize,
+}
+
+impl<T> ReorderBuffer<T> {
+    pub fn new(max_buffered: usize) -> Self {
+        Self { missing: BTreeSet::new(), ready: BTreeMap::new(), max_buffered }
+    }
+
+    pub fn push(&mut self, height: BlockHeight, message: T) {
+        self.missing.remove(&height);
+        self.ready.insert(height, message);
+    }
+
+    pub fn push_missing(&mut self, height: BlockHeight) {
+        self.missing.insert(height);
+    }
+
+    pub fn missing_heights(&self) -> Vec<BlockHeight> {
+        self.missing.iter().copied().collect()
+    }
+
+    /// The messages which may be emitted, in order, and the heights of the missing blocks given
+    /// up on to emit them.
+    pub fn drain(&mut self) -> (Vec<T>, Vec<BlockHeight>) {
+        let mut skipped = vec![];
+        while self.missing.len() + self.ready.len() > self.max_buffered {
+            match self.missing.iter().next().copied() {
+                Some(height) => {
+                    self.missing.remove(&height);
+                    skipped.push(height);
+                }
+                None => break,
+            }
+        }
+        let ready = match self.missing.iter().next() {
+            Some(lowest_missing) => {
+                let waiting = self.ready.split_off(lowest_missing);
+                std::mem::replace(&mut self.ready, waiting)
+            }
+            None => std::mem::take(&mut self.ready),
+        };
+        (ready.into_iter().map(|(_, message)| message).collect(), skipped)
+    }
+
+    /// Height up to which every block was emitted or skipped, given the last height fetched.
+    pub fn settled_up_to(&self, last_fetched: BlockHeight) -> BlockHeight {
+        match self.missing.iter().next() {
+            Some(lowest_missing) => lowest_missing.saturating_sub(1),
+            None => last_fetched,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder_buffer() {
+        let mut buffer = ReorderBuffer::new(3);
+        buffer.push(10, "10");
+        buffer.push_missing(11);
+        buffer.push(12, "12");
+        assert_eq!(buffer.drain(), (vec!["10"], vec![]));
+        assert_eq!(buffer.settled_up_to(12), 10);
+
+        // The missing block is found, the buffered one follows it.
+        buffer.push(11, "11");
+        buffer.push(13, "13");
+        assert_eq!(buffer.drain(), (vec!["11", "12", "13"], vec![]));
+        assert_eq!(buffer.settled_up_to(13), 13);
+
+        // Once the buffer is full, the lowest missing blocks are skipped.
+        buffer.push_missing(14);
+        buffer.push_missing(15);
+        buffer.push(16, "16");
+        assert_eq!(buffer.drain(), (vec![], vec![]));
+        buffer.push(17, "17");
+        assert_eq!(buffer.drain(), (vec![], vec![14]));
+        assert_eq!(buffer.missing_heights(), vec![15]);
+        assert_eq!(buffer.settled_up_to(17), 14);
+        buffer.push(18, "18");
+        assert_eq!(buffer.drain(), (vec!["16", "17", "18"], vec![15]));
+        assert!(buffer.missing_heights().is_empty());
+    }
+}
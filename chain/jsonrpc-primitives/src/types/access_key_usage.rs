@@ -0,0 +1,73 @@
+use near_client_primitives::types::GetAccessKeyUsageError;
+use near_primitives::types::AccountId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcAccessKeyUsageRequest {
+    pub account_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcAccessKeyUsageResponse {
+    pub usage: Vec<near_primitives::views::AccessKeyUsageView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcAccessKeyUsageError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("The node doesn't save the usage of access keys")]
+    NotSaved,
+}
+
+impl From<GetAccessKeyUsageError> for RpcAccessKeyUsageError {
+    fn from(error: GetAccessKeyUsageError) -> Self {
+        match error {
+            GetAccessKeyUsageError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetAccessKeyUsageError::NotSaved => Self::NotSaved,
+            GetAccessKeyUsageError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcAccessKeyUsageError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcAccessKeyUsageError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcAccessKeyUsageError> for crate::errors::RpcError {
+    fn from(error: RpcAccessKeyUsageError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcAccessKeyUsageError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcAccessKeyUsageRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcAccessKeyUsageRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcAccessKeyUsageRequest>(value)?)
+    }
+}
@@ -0,0 +1,93 @@
+use near_client_primitives::types::GetAccountSummaryError;
+use near_primitives::types::{AccountId, BlockHeight, BlockReference, ShardId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcAccountSummaryRequest {
+    #[serde(flatten)]
+    pub block_reference: BlockReference,
+    pub account_id: AccountId,
+    #[serde(default)]
+    pub lockup_factory_id: Option<AccountId>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcAccountSummaryResponse {
+    #[serde(flatten)]
+    pub account_summary: near_primitives::views::AccountSummaryView,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcAccountSummaryError {
+    #[error("Block not found: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+    #[error("The node does not track the shard ID {shard_id}")]
+    UnavailableShard { shard_id: ShardId },
+    #[error("Account {account_id} does not exist while viewing at block #{block_height}")]
+    UnknownAccount { account_id: AccountId, block_height: BlockHeight },
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<GetAccountSummaryError> for RpcAccountSummaryError {
+    fn from(error: GetAccountSummaryError) -> Self {
+        match error {
+            GetAccountSummaryError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            GetAccountSummaryError::UnavailableShard { shard_id } => {
+                Self::UnavailableShard { shard_id }
+            }
+            GetAccountSummaryError::UnknownAccount { account_id, block_height } => {
+                Self::UnknownAccount { account_id, block_height }
+            }
+            GetAccountSummaryError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetAccountSummaryError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcAccountSummaryError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcAccountSummaryError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcAccountSummaryError> for crate::errors::RpcError {
+    fn from(error: RpcAccountSummaryError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcAccountSummaryError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcAccountSummaryRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcAccountSummaryRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcAccountSummaryRequest>(value)?)
+    }
+}
@@ -14,6 +14,16 @@ pub enum RpcBlockError {
     },
     #[error("There are no fully synchronized blocks yet")]
     NotSyncedYet,
+    #[error("Block #{block_height} has been garbage collected, the node keeps the blocks from #{tail_height}")]
+    GarbageCollectedBlock {
+        block_height: near_primitives::types::BlockHeight,
+        tail_height: near_primitives::types::BlockHeight,
+    },
+    #[error("Block #{block_height} has not been processed yet, the head of the node is at #{head_height}")]
+    NotProcessedYet {
+        block_height: near_primitives::types::BlockHeight,
+        head_height: near_primitives::types::BlockHeight,
+    },
     #[error("The node reached its limits. Try again later. More details: {error_message}")]
     InternalError { error_message: String },
 }
@@ -37,6 +47,14 @@ impl From<near_client_primitives::types::GetBlockError> for RpcBlockError {
                 Self::UnknownBlock { error_message }
             }
             near_client_primitives::types::GetBlockError::NotSyncedYet => Self::NotSyncedYet,
+            near_client_primitives::types::GetBlockError::GarbageCollected {
+                block_height,
+                tail_height,
+            } => Self::GarbageCollectedBlock { block_height, tail_height },
+            near_client_primitives::types::GetBlockError::NotProcessedYet {
+                block_height,
+                head_height,
+            } => Self::NotProcessedYet { block_height, head_height },
             near_client_primitives::types::GetBlockError::IOError { error_message } => {
                 Self::InternalError { error_message }
             }
@@ -64,9 +82,10 @@ impl From<RpcBlockError> for crate::errors::RpcError {
                 "DB Not Found Error: {} \n Cause: Unknown",
                 error_message
             ))),
-            RpcBlockError::NotSyncedYet | RpcBlockError::InternalError { .. } => {
-                Some(Value::String(error.to_string()))
-            }
+            RpcBlockError::NotSyncedYet
+            | RpcBlockError::GarbageCollectedBlock { .. }
+            | RpcBlockError::NotProcessedYet { .. }
+            | RpcBlockError::InternalError { .. } => Some(Value::String(error.to_string())),
         };
 
         let error_data_value = match serde_json::to_value(error) {
@@ -59,6 +59,10 @@ impl From<near_client_primitives::types::GetBlockError> for RpcStateChangesError
             near_client_primitives::types::GetBlockError::UnknownBlock { error_message } => {
                 Self::UnknownBlock { error_message }
             }
+            near_client_primitives::types::GetBlockError::GarbageCollected { .. }
+            | near_client_primitives::types::GetBlockError::NotProcessedYet { .. } => {
+                Self::UnknownBlock { error_message: error.to_string() }
+            }
             near_client_primitives::types::GetBlockError::NotSyncedYet => Self::NotSyncedYet,
             near_client_primitives::types::GetBlockError::IOError { error_message } => {
                 Self::InternalError { error_message }
@@ -39,6 +39,18 @@ pub enum RpcChunkError {
     InvalidShardId { shard_id: u64 },
     #[error("Chunk with hash {chunk_hash:?} has never been observed on this node")]
     UnknownChunk { chunk_hash: near_primitives::sharding::ChunkHash },
+    #[error("The node does not track the shard ID {shard_id}")]
+    UnavailableShard { shard_id: near_primitives::types::ShardId },
+    #[error("Block #{block_height} has been garbage collected, the node keeps the blocks from #{tail_height}")]
+    GarbageCollectedBlock {
+        block_height: near_primitives::types::BlockHeight,
+        tail_height: near_primitives::types::BlockHeight,
+    },
+    #[error("Block #{block_height} has not been processed yet, the head of the node is at #{head_height}")]
+    NotProcessedYet {
+        block_height: near_primitives::types::BlockHeight,
+        head_height: near_primitives::types::BlockHeight,
+    },
 }
 
 impl From<ChunkReference> for near_client_primitives::types::GetChunk {
@@ -90,6 +102,17 @@ impl From<near_client_primitives::types::GetChunkError> for RpcChunkError {
             near_client_primitives::types::GetChunkError::UnknownChunk { chunk_hash } => {
                 Self::UnknownChunk { chunk_hash }
             }
+            near_client_primitives::types::GetChunkError::UnavailableShard { shard_id } => {
+                Self::UnavailableShard { shard_id }
+            }
+            near_client_primitives::types::GetChunkError::GarbageCollected {
+                block_height,
+                tail_height,
+            } => Self::GarbageCollectedBlock { block_height, tail_height },
+            near_client_primitives::types::GetChunkError::NotProcessedYet {
+                block_height,
+                head_height,
+            } => Self::NotProcessedYet { block_height, head_height },
             near_client_primitives::types::GetChunkError::Unreachable { ref error_message } => {
                 tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
                 crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
@@ -115,7 +138,10 @@ impl From<RpcChunkError> for crate::errors::RpcError {
                 "DB Not Found Error: {} \n Cause: Unknown",
                 error_message
             ))),
-            RpcChunkError::InvalidShardId { .. } => Some(Value::String(error.to_string())),
+            RpcChunkError::InvalidShardId { .. }
+            | RpcChunkError::UnavailableShard { .. }
+            | RpcChunkError::GarbageCollectedBlock { .. }
+            | RpcChunkError::NotProcessedYet { .. } => Some(Value::String(error.to_string())),
             RpcChunkError::UnknownChunk { chunk_hash } => Some(Value::String(format!(
                 "Chunk Missing (unavailable on the node): ChunkHash(`{}`) \n Cause: Unknown",
                 chunk_hash.0.to_string()
@@ -0,0 +1,76 @@
+use near_client_primitives::types::GetContractHistoryError;
+use near_primitives::types::AccountId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcContractHistoryRequest {
+    pub account_id: AccountId,
+    /// Whether to compare every deployment with the previous one.
+    #[serde(default)]
+    pub with_diff: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcContractHistoryResponse {
+    pub history: Vec<near_primitives::views::ContractDeploymentView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcContractHistoryError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("The node doesn't save the history of contracts")]
+    NotSaved,
+}
+
+impl From<GetContractHistoryError> for RpcContractHistoryError {
+    fn from(error: GetContractHistoryError) -> Self {
+        match error {
+            GetContractHistoryError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetContractHistoryError::NotSaved => Self::NotSaved,
+            GetContractHistoryError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcContractHistoryError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcContractHistoryError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcContractHistoryError> for crate::errors::RpcError {
+    fn from(error: RpcContractHistoryError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcContractHistoryError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcContractHistoryRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcContractHistoryRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcContractHistoryRequest>(value)?)
+    }
+}
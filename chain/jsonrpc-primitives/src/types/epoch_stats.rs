@@ -0,0 +1,79 @@
+use near_client_primitives::types::GetEpochChainStatsError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+fn default_limit() -> u64 {
+    10
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcEpochChainStatsRequest {
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcEpochChainStatsResponse {
+    pub epochs: Vec<near_primitives::views::EpochChainStatsView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcEpochChainStatsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Invalid limit {limit}, at most {max_limit} epochs can be requested")]
+    InvalidLimit { limit: u64, max_limit: u64 },
+}
+
+impl From<GetEpochChainStatsError> for RpcEpochChainStatsError {
+    fn from(error: GetEpochChainStatsError) -> Self {
+        match error {
+            GetEpochChainStatsError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetEpochChainStatsError::InvalidLimit { limit, max_limit } => {
+                Self::InvalidLimit { limit, max_limit }
+            }
+            GetEpochChainStatsError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcEpochChainStatsError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcEpochChainStatsError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcEpochChainStatsError> for crate::errors::RpcError {
+    fn from(error: RpcEpochChainStatsError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcEpochChainStatsError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcEpochChainStatsRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcEpochChainStatsRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcEpochChainStatsRequest>(value)?)
+    }
+}
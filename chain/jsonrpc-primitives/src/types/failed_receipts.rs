@@ -0,0 +1,78 @@
+use near_client_primitives::types::GetFailedReceiptsError;
+use near_primitives::types::BlockId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcFailedReceiptsRequest {
+    pub block_id: BlockId,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcFailedReceiptsResponse {
+    pub failed_receipts: Vec<near_primitives::views::FailedReceiptView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcFailedReceiptsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+}
+
+impl From<GetFailedReceiptsError> for RpcFailedReceiptsError {
+    fn from(error: GetFailedReceiptsError) -> Self {
+        match error {
+            GetFailedReceiptsError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetFailedReceiptsError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            GetFailedReceiptsError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcFailedReceiptsError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcFailedReceiptsError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcFailedReceiptsError> for crate::errors::RpcError {
+    fn from(error: RpcFailedReceiptsError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcFailedReceiptsError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcFailedReceiptsRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcFailedReceiptsRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcFailedReceiptsRequest>(value)?)
+    }
+}
@@ -0,0 +1,135 @@
+use std::convert::TryFrom;
+
+use near_client_primitives::types::EstimateTransactionFeesError;
+use near_primitives::transaction::Action;
+use near_primitives::types::{AccountId, BlockHeight, BlockReference, ShardId};
+use near_primitives::views::ActionView;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug)]
+pub struct RpcEstimateFeesRequest {
+    pub block_reference: BlockReference,
+    pub signer_id: AccountId,
+    pub public_key: Option<near_crypto::PublicKey>,
+    pub receiver_id: AccountId,
+    pub actions: Vec<Action>,
+}
+
+/// The actions as in the views of the transactions, the code of a deployment in base64.
+#[derive(Deserialize)]
+struct RpcEstimateFeesParams {
+    #[serde(flatten)]
+    block_reference: BlockReference,
+    signer_id: AccountId,
+    #[serde(default)]
+    public_key: Option<near_crypto::PublicKey>,
+    receiver_id: AccountId,
+    actions: Vec<ActionView>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcEstimateFeesResponse {
+    #[serde(flatten)]
+    pub fees: near_primitives::views::TransactionFeesView,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcEstimateFeesError {
+    #[error("Block not found: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+    #[error("The node does not track the shard ID {shard_id} of the signer")]
+    UnavailableShard { shard_id: ShardId },
+    #[error("Account {account_id} does not exist while viewing at block #{block_height}")]
+    UnknownAccount { account_id: AccountId, block_height: BlockHeight },
+    #[error("Access key for public key {public_key} does not exist while viewing at block #{block_height}")]
+    UnknownAccessKey { public_key: near_crypto::PublicKey, block_height: BlockHeight },
+    #[error("Account {account_id} has no full access key")]
+    NoFullAccessKey { account_id: AccountId },
+    #[error("An error happened during transaction validation: {context:?}")]
+    InvalidTransaction { context: near_primitives::errors::InvalidTxError },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<EstimateTransactionFeesError> for RpcEstimateFeesError {
+    fn from(error: EstimateTransactionFeesError) -> Self {
+        match error {
+            EstimateTransactionFeesError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            EstimateTransactionFeesError::UnavailableShard { shard_id } => {
+                Self::UnavailableShard { shard_id }
+            }
+            EstimateTransactionFeesError::UnknownAccount { account_id, block_height } => {
+                Self::UnknownAccount { account_id, block_height }
+            }
+            EstimateTransactionFeesError::UnknownAccessKey { public_key, block_height } => {
+                Self::UnknownAccessKey { public_key, block_height }
+            }
+            EstimateTransactionFeesError::NoFullAccessKey { account_id } => {
+                Self::NoFullAccessKey { account_id }
+            }
+            EstimateTransactionFeesError::InvalidTransaction { error } => {
+                Self::InvalidTransaction { context: error }
+            }
+            EstimateTransactionFeesError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            EstimateTransactionFeesError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcEstimateFeesError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcEstimateFeesError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcEstimateFeesError> for crate::errors::RpcError {
+    fn from(error: RpcEstimateFeesError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcEstimateFeesError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcEstimateFeesRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        let params = crate::utils::parse_params::<RpcEstimateFeesParams>(value)?;
+        let actions = params
+            .actions
+            .into_iter()
+            .map(Action::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|err| crate::errors::RpcParseError(format!("Invalid action: {}", err)))?;
+        Ok(Self {
+            block_reference: params.block_reference,
+            signer_id: params.signer_id,
+            public_key: params.public_key,
+            receiver_id: params.receiver_id,
+            actions,
+        })
+    }
+}
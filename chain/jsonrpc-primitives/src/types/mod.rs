@@ -1,13 +1,29 @@
+pub mod access_key_usage;
+pub mod account_summary;
 pub mod blocks;
 pub mod changes;
 pub mod chunks;
+pub mod clock_skew;
 pub mod config;
+pub mod contract_history;
+pub mod epoch_stats;
+pub mod failed_receipts;
+pub mod fees;
 pub mod gas_price;
+pub mod gas_throughput;
 pub mod light_client;
 pub mod network_info;
+pub mod parts_availability;
+pub mod peer_ranges;
+pub mod pool;
+pub mod protocol_upgrade;
 pub mod query;
+pub mod receipt_proof;
 pub mod receipts;
+pub mod refund_stats;
 pub mod sandbox;
+pub mod simulate;
 pub mod status;
+pub mod transaction_stats;
 pub mod transactions;
 pub mod validator;
@@ -1,4 +1,4 @@
-use near_network_primitives::types::{KnownProducer, PeerInfo};
+use near_network_primitives::types::{KnownProducer, PeerInfo, PeerScore, PeerTransferStats};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -8,6 +8,12 @@ pub struct RpcNetworkInfoResponse {
     pub peer_max_count: u32,
     pub sent_bytes_per_sec: u64,
     pub received_bytes_per_sec: u64,
+    /// Transfers to and from every active peer over the last minute, 5 minutes and hour.
+    #[serde(default)]
+    pub peer_transfer_stats: Vec<PeerTransferStats>,
+    /// Scores of the peers which misbehaved recently, highest first.
+    #[serde(default)]
+    pub peer_scores: Vec<PeerScore>,
     /// Accounts of known block and chunk producers from routing table.
     pub known_producers: Vec<KnownProducer>,
 }
@@ -27,6 +33,8 @@ impl From<near_client_primitives::types::NetworkInfoResponse> for RpcNetworkInfo
             peer_max_count: network_info_response.peer_max_count,
             sent_bytes_per_sec: network_info_response.sent_bytes_per_sec,
             received_bytes_per_sec: network_info_response.received_bytes_per_sec,
+            peer_transfer_stats: network_info_response.peer_transfer_stats,
+            peer_scores: network_info_response.peer_scores,
             known_producers: network_info_response.known_producers,
         }
     }
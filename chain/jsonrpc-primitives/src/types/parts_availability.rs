@@ -0,0 +1,74 @@
+use near_primitives::views::ChunkPartsAvailabilityView;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Default number of chunks returned by `EXPERIMENTAL_chunk_parts_availability`.
+pub const DEFAULT_CHUNK_PARTS_AVAILABILITY_LIMIT: usize = 50;
+/// Maximum number of chunks returned by `EXPERIMENTAL_chunk_parts_availability`.
+pub const MAX_CHUNK_PARTS_AVAILABILITY_LIMIT: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcChunkPartsAvailabilityRequest {
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcChunkPartsAvailabilityResponse {
+    /// Most recently seen chunks first.
+    pub chunks: Vec<ChunkPartsAvailabilityView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcChunkPartsAvailabilityError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<actix::MailboxError> for RpcChunkPartsAvailabilityError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<String> for RpcChunkPartsAvailabilityError {
+    fn from(error_message: String) -> Self {
+        Self::InternalError { error_message }
+    }
+}
+
+impl From<RpcChunkPartsAvailabilityError> for crate::errors::RpcError {
+    fn from(error: RpcChunkPartsAvailabilityError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcChunkPartsAvailabilityError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcChunkPartsAvailabilityRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcChunkPartsAvailabilityRequest, crate::errors::RpcParseError> {
+        match value {
+            None => Ok(RpcChunkPartsAvailabilityRequest { limit: None }),
+            value => Ok(crate::utils::parse_params::<RpcChunkPartsAvailabilityRequest>(value)?),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_CHUNK_PARTS_AVAILABILITY_LIMIT)
+            .min(MAX_CHUNK_PARTS_AVAILABILITY_LIMIT)
+    }
+}
@@ -0,0 +1,190 @@
+use near_crypto::PublicKey;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, Nonce, ShardId};
+use near_primitives::views::{PoolStatusView, PoolTransactionView};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Default number of transactions returned by `EXPERIMENTAL_pool_transactions`.
+pub const DEFAULT_POOL_TRANSACTIONS_LIMIT: usize = 100;
+/// Maximum number of transactions returned by `EXPERIMENTAL_pool_transactions`.
+pub const MAX_POOL_TRANSACTIONS_LIMIT: usize = 1000;
+
+/// What `EXPERIMENTAL_pool_transactions` reveals of the transactions in the pool of the node.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcPoolTransactionsExposure {
+    /// The method is disabled.
+    Disabled,
+    /// Only the hash, shard and age of the transactions.
+    Redacted,
+    /// Signer, receiver, nonce and number of actions of the transactions as well.
+    Full,
+}
+
+impl Default for RpcPoolTransactionsExposure {
+    fn default() -> Self {
+        Self::Disabled
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcPoolStatusResponse {
+    #[serde(flatten)]
+    pub pool_status: PoolStatusView,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcPoolTransactionsRequest {
+    /// Transactions of all shards if not set.
+    #[serde(default)]
+    pub shard_id: Option<ShardId>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcPoolTransactionsResponse {
+    /// Oldest transactions first.
+    pub transactions: Vec<RpcPoolTransactionSummary>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcPoolTransactionSummary {
+    pub hash: CryptoHash,
+    pub shard_id: ShardId,
+    pub age_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer_id: Option<AccountId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<PublicKey>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<Nonce>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receiver_id: Option<AccountId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_actions: Option<u64>,
+}
+
+impl RpcPoolTransactionSummary {
+    /// Returns `None` if transactions are not exposed at all.
+    pub fn new(
+        transaction: PoolTransactionView,
+        exposure: RpcPoolTransactionsExposure,
+    ) -> Option<Self> {
+        let redacted = Self {
+            hash: transaction.hash,
+            shard_id: transaction.shard_id,
+            age_secs: transaction.age_secs,
+            signer_id: None,
+            public_key: None,
+            nonce: None,
+            receiver_id: None,
+            num_actions: None,
+        };
+        match exposure {
+            RpcPoolTransactionsExposure::Disabled => None,
+            RpcPoolTransactionsExposure::Redacted => Some(redacted),
+            RpcPoolTransactionsExposure::Full => Some(Self {
+                signer_id: Some(transaction.signer_id),
+                public_key: Some(transaction.public_key),
+                nonce: Some(transaction.nonce),
+                receiver_id: Some(transaction.receiver_id),
+                num_actions: Some(transaction.num_actions),
+                ..redacted
+            }),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcPoolError {
+    #[error("The transactions in the pool are not exposed by this node")]
+    TransactionsNotExposed,
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<actix::MailboxError> for RpcPoolError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<String> for RpcPoolError {
+    fn from(error_message: String) -> Self {
+        Self::InternalError { error_message }
+    }
+}
+
+impl From<RpcPoolError> for crate::errors::RpcError {
+    fn from(error: RpcPoolError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcPoolError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcPoolTransactionsRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcPoolTransactionsRequest, crate::errors::RpcParseError> {
+        match value {
+            None => Ok(RpcPoolTransactionsRequest { shard_id: None, limit: None }),
+            value => Ok(crate::utils::parse_params::<RpcPoolTransactionsRequest>(value)?),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_POOL_TRANSACTIONS_LIMIT).min(MAX_POOL_TRANSACTIONS_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::KeyType;
+
+    #[test]
+    fn test_redaction() {
+        let transaction = PoolTransactionView {
+            hash: CryptoHash::default(),
+            shard_id: 1,
+            signer_id: "alice.near".parse().unwrap(),
+            public_key: PublicKey::empty(KeyType::ED25519),
+            nonce: 5,
+            receiver_id: "bob.near".parse().unwrap(),
+            num_actions: 1,
+            age_secs: 30,
+        };
+        assert!(RpcPoolTransactionSummary::new(
+            transaction.clone(),
+            RpcPoolTransactionsExposure::Disabled
+        )
+        .is_none());
+        let redacted = serde_json::to_value(
+            RpcPoolTransactionSummary::new(
+                transaction.clone(),
+                RpcPoolTransactionsExposure::Redacted,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(redacted.as_object().unwrap().len(), 3);
+        assert_eq!(redacted["age_secs"], 30);
+        let full =
+            RpcPoolTransactionSummary::new(transaction, RpcPoolTransactionsExposure::Full).unwrap();
+        assert_eq!(full.nonce, Some(5));
+    }
+}
@@ -0,0 +1,63 @@
+use near_client_primitives::types::ProtocolUpgradeError;
+use near_primitives::types::ProtocolVersion;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcProtocolUpgradeError {
+    #[error(
+        "The binary supports protocol version {binary_protocol_version}, not {protocol_version}"
+    )]
+    UnsupportedProtocolVersion {
+        binary_protocol_version: ProtocolVersion,
+        protocol_version: ProtocolVersion,
+    },
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<ProtocolUpgradeError> for RpcProtocolUpgradeError {
+    fn from(error: ProtocolUpgradeError) -> Self {
+        match error {
+            ProtocolUpgradeError::UnsupportedProtocolVersion {
+                binary_protocol_version,
+                protocol_version,
+            } => Self::UnsupportedProtocolVersion { binary_protocol_version, protocol_version },
+            ProtocolUpgradeError::IOError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            ProtocolUpgradeError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcProtocolUpgradeError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcProtocolUpgradeError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcProtocolUpgradeError> for crate::errors::RpcError {
+    fn from(error: RpcProtocolUpgradeError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcProtocolUpgradeError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
@@ -0,0 +1,87 @@
+use near_client_primitives::types::GetReceiptInclusionProofError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::BlockId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcReceiptProofRequest {
+    pub receipt_id: CryptoHash,
+    /// Block which chunks sent the receipt, the one in which it was received.
+    pub block_id: BlockId,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcReceiptProofResponse {
+    #[serde(flatten)]
+    pub proof: near_primitives::views::ReceiptInclusionProofView,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcReceiptProofError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+    #[error("Receipt {receipt_id} was not sent to a shard tracked by the node by the chunks of block {block_hash}")]
+    ReceiptNotInBlock { receipt_id: CryptoHash, block_hash: CryptoHash },
+}
+
+impl From<GetReceiptInclusionProofError> for RpcReceiptProofError {
+    fn from(error: GetReceiptInclusionProofError) -> Self {
+        match error {
+            GetReceiptInclusionProofError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetReceiptInclusionProofError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            GetReceiptInclusionProofError::ReceiptNotInBlock { receipt_id, block_hash } => {
+                Self::ReceiptNotInBlock { receipt_id, block_hash }
+            }
+            GetReceiptInclusionProofError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcReceiptProofError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcReceiptProofError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcReceiptProofError> for crate::errors::RpcError {
+    fn from(error: RpcReceiptProofError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcReceiptProofError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcReceiptProofRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcReceiptProofRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcReceiptProofRequest>(value)?)
+    }
+}
@@ -0,0 +1,79 @@
+use near_client_primitives::types::GetRefundStatsError;
+use near_primitives::types::BlockId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcRefundStatsRequest {
+    pub block_id: BlockId,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcRefundStatsResponse {
+    #[serde(flatten)]
+    pub refund_stats: near_primitives::views::BlockRefundStatsView,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcRefundStatsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Block either has never been observed on the node or has been garbage collected: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+}
+
+impl From<GetRefundStatsError> for RpcRefundStatsError {
+    fn from(error: GetRefundStatsError) -> Self {
+        match error {
+            GetRefundStatsError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetRefundStatsError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            GetRefundStatsError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcRefundStatsError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcRefundStatsError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcRefundStatsError> for crate::errors::RpcError {
+    fn from(error: RpcRefundStatsError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcRefundStatsError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcRefundStatsRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcRefundStatsRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcRefundStatsRequest>(value)?)
+    }
+}
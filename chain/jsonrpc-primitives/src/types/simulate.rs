@@ -0,0 +1,123 @@
+use near_client_primitives::types::SimulateTransactionError;
+use near_primitives::borsh::BorshDeserialize;
+use near_primitives::transaction::{SignedTransaction, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug)]
+pub struct RpcSimulateTransactionRequest {
+    pub block_reference: near_primitives::types::BlockReference,
+    pub transaction: SignedTransaction,
+    /// False for the unsigned transactions, which are run as if signed by their signer.
+    pub verify_signature: bool,
+}
+
+/// Either `signed_tx_base64` or `tx_base64`, the borsh of a signed or an unsigned transaction.
+#[derive(Deserialize)]
+struct RpcSimulateTransactionParams {
+    #[serde(flatten)]
+    block_reference: near_primitives::types::BlockReference,
+    signed_tx_base64: Option<String>,
+    tx_base64: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RpcSimulateTransactionResponse {
+    #[serde(flatten)]
+    pub simulation: near_primitives::views::SimulateTransactionView,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcSimulateTransactionError {
+    #[error("Block not found: {error_message}")]
+    UnknownBlock {
+        #[serde(skip_serializing)]
+        error_message: String,
+    },
+    #[error("The node does not track the shard ID {shard_id} of the signer")]
+    UnavailableShard { shard_id: near_primitives::types::ShardId },
+    #[error("An error happened during transaction validation: {context:?}")]
+    InvalidTransaction { context: near_primitives::errors::InvalidTxError },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<SimulateTransactionError> for RpcSimulateTransactionError {
+    fn from(error: SimulateTransactionError) -> Self {
+        match error {
+            SimulateTransactionError::UnknownBlock { error_message } => {
+                Self::UnknownBlock { error_message }
+            }
+            SimulateTransactionError::UnavailableShard { shard_id } => {
+                Self::UnavailableShard { shard_id }
+            }
+            SimulateTransactionError::InvalidTransaction { error } => {
+                Self::InvalidTransaction { context: error }
+            }
+            SimulateTransactionError::IOError { error_message }
+            | SimulateTransactionError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            SimulateTransactionError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcSimulateTransactionError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcSimulateTransactionError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcSimulateTransactionError> for crate::errors::RpcError {
+    fn from(error: RpcSimulateTransactionError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcSimulateTransactionError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+fn decode_base64<T: BorshDeserialize>(encoded: &str) -> Result<T, crate::errors::RpcParseError> {
+    let bytes = near_primitives_core::serialize::from_base64(encoded)
+        .map_err(|err| crate::errors::RpcParseError(err.to_string()))?;
+    T::try_from_slice(&bytes).map_err(|err| {
+        crate::errors::RpcParseError(format!("Failed to decode transaction: {}", err))
+    })
+}
+
+impl RpcSimulateTransactionRequest {
+    pub fn parse(value: Option<Value>) -> Result<Self, crate::errors::RpcParseError> {
+        let params = crate::utils::parse_params::<RpcSimulateTransactionParams>(value)?;
+        let (transaction, verify_signature) = match (params.signed_tx_base64, params.tx_base64) {
+            (Some(signed_tx_base64), None) => (decode_base64(&signed_tx_base64)?, true),
+            (None, Some(tx_base64)) => {
+                let transaction: Transaction = decode_base64(&tx_base64)?;
+                let signature = near_crypto::Signature::empty(transaction.public_key.key_type());
+                (SignedTransaction::new(signature, transaction), false)
+            }
+            _ => {
+                return Err(crate::errors::RpcParseError(
+                    "Exactly one of `signed_tx_base64` and `tx_base64` must be given".to_string(),
+                ))
+            }
+        };
+        Ok(Self { block_reference: params.block_reference, transaction, verify_signature })
+    }
+}
@@ -0,0 +1,76 @@
+use near_client_primitives::types::GetBlockTransactionStatsError;
+use near_primitives::types::BlockHeight;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcBlockTransactionStatsRequest {
+    pub from_height: BlockHeight,
+    pub to_height: BlockHeight,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcBlockTransactionStatsResponse {
+    pub blocks: Vec<near_primitives::views::BlockTransactionStatsView>,
+}
+
+#[derive(thiserror::Error, Debug, Serialize, Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcBlockTransactionStatsError {
+    #[error("Internal error: {error_message}")]
+    InternalError { error_message: String },
+    #[error("Invalid range of heights from {from_height} to {to_height}, at most {max_range} blocks can be requested")]
+    InvalidRange { from_height: BlockHeight, to_height: BlockHeight, max_range: BlockHeight },
+}
+
+impl From<GetBlockTransactionStatsError> for RpcBlockTransactionStatsError {
+    fn from(error: GetBlockTransactionStatsError) -> Self {
+        match error {
+            GetBlockTransactionStatsError::InternalError { error_message } => {
+                Self::InternalError { error_message }
+            }
+            GetBlockTransactionStatsError::InvalidRange { from_height, to_height, max_range } => {
+                Self::InvalidRange { from_height, to_height, max_range }
+            }
+            GetBlockTransactionStatsError::Unreachable { ref error_message } => {
+                tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", &error_message);
+                crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
+                    .with_label_values(&["RpcBlockTransactionStatsError"])
+                    .inc();
+                Self::InternalError { error_message: error.to_string() }
+            }
+        }
+    }
+}
+
+impl From<actix::MailboxError> for RpcBlockTransactionStatsError {
+    fn from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl From<RpcBlockTransactionStatsError> for crate::errors::RpcError {
+    fn from(error: RpcBlockTransactionStatsError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+
+        let error_data_value = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcBlockTransactionStatsError: {:?}", err),
+                )
+            }
+        };
+
+        Self::new_internal_or_handler_error(error_data, error_data_value)
+    }
+}
+
+impl RpcBlockTransactionStatsRequest {
+    pub fn parse(
+        value: Option<Value>,
+    ) -> Result<RpcBlockTransactionStatsRequest, crate::errors::RpcParseError> {
+        Ok(crate::utils::parse_params::<RpcBlockTransactionStatsRequest>(value)?)
+    }
+}
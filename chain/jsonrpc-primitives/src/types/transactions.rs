@@ -40,6 +40,8 @@ pub enum RpcTransactionError {
     InternalError { debug_info: String },
     #[error("Timeout")]
     TimeoutError,
+    #[error("Transaction was rejected by the policy of the node: {reason}")]
+    RejectedByPolicy { reason: String },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
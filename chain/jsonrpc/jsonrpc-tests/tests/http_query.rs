@@ -4,6 +4,7 @@ use futures::{future, FutureExt};
 use near_actix_test_utils::run_actix;
 use near_jsonrpc::client::new_http_client;
 use near_logger_utils::init_test_logger;
+use near_primitives::views::{DebugSyncStatusView, DebugValidatorStatusView};
 
 use near_jsonrpc_tests as test_utils;
 
@@ -26,3 +27,92 @@ fn test_status() {
         }));
     });
 }
+
+/// Retrieve the sync status via the JSON debug API.
+#[test]
+fn test_debug_api_sync_status() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::NonValidator);
+
+        let client = awc::Client::new();
+        actix::spawn(async move {
+            let mut response =
+                client.get(format!("http://{}/debug/api/sync_status", addr)).send().await.unwrap();
+            let body = response.body().await.unwrap();
+            let res: DebugSyncStatusView = serde_json::from_slice(&body).unwrap();
+            assert_eq!(res.head.height, 0);
+            assert_eq!(res.header_head.height, 0);
+            assert_eq!(res.highest_peer_height, None);
+            System::current().stop();
+        });
+    });
+}
+
+/// Retrieve the producers of the next heights via the JSON debug API.
+#[test]
+fn test_debug_api_validator_status() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::Validator);
+
+        let client = awc::Client::new();
+        actix::spawn(async move {
+            let mut response = client
+                .get(format!("http://{}/debug/api/validator_status", addr))
+                .send()
+                .await
+                .unwrap();
+            let body = response.body().await.unwrap();
+            let res: DebugValidatorStatusView = serde_json::from_slice(&body).unwrap();
+            assert_eq!(res.validator_account_id, Some("test1".parse().unwrap()));
+            assert_eq!(res.next_producers.len(), 10);
+            assert_eq!(res.next_producers[0].height, 1);
+            for producers in res.next_producers {
+                assert!(["test1", "test2"].contains(&producers.block_producer.as_ref()));
+                assert_eq!(producers.chunk_producers.len(), 1);
+            }
+            System::current().stop();
+        });
+    });
+}
+
+/// Process a batch of requests, answered in their order, without answering the notifications.
+#[test]
+fn test_batch() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::NonValidator);
+
+        let client = awc::Client::new();
+        actix::spawn(async move {
+            let batch = serde_json::json!([
+                {"jsonrpc": "2.0", "id": "first", "method": "status", "params": []},
+                {"jsonrpc": "2.0", "method": "status", "params": []},
+                {"jsonrpc": "2.0", "id": "second", "method": "no_such_method", "params": []},
+            ]);
+            let mut response =
+                client.post(format!("http://{}", addr)).send_json(&batch).await.unwrap();
+            let body = response.body().await.unwrap();
+            let responses: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+            assert_eq!(responses.len(), 2);
+            assert_eq!(responses[0]["id"], "first");
+            assert_eq!(responses[0]["result"]["chain_id"], "unittest");
+            assert_eq!(responses[1]["id"], "second");
+            assert_eq!(responses[1]["error"]["code"], -32_601);
+
+            let mut response = client
+                .post(format!("http://{}", addr))
+                .send_json(&serde_json::json!([]))
+                .await
+                .unwrap();
+            let body = response.body().await.unwrap();
+            let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(response["error"].is_object());
+            System::current().stop();
+        });
+    });
+}
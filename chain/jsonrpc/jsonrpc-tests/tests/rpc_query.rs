@@ -482,6 +482,21 @@ fn test_genesis_config() {
     });
 }
 
+#[test]
+fn test_node_features() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let node_features = client.EXPERIMENTAL_node_features().await.unwrap();
+        assert_eq!(
+            node_features.protocol_features.len(),
+            near_primitives::version::ProtocolFeature::ALL.len()
+        );
+        let status = client.status().await.unwrap();
+        let build_features: Vec<_> =
+            node_features.build_features.into_iter().map(|feature| feature.name).collect();
+        assert_eq!(status.build_features, build_features);
+    });
+}
+
 /// Retrieve gas price
 #[test]
 fn test_gas_price_by_height() {
@@ -77,6 +77,61 @@ fn test_send_tx_async() {
     });
 }
 
+/// Test sending a borsh serialized transaction to the binary endpoint.
+#[test]
+fn test_send_binary_tx() {
+    init_test_logger();
+
+    run_actix(async {
+        let (_, addr) = test_utils::start_all(test_utils::NodeType::Validator);
+
+        let client = new_client(&format!("http://{}", addr));
+        let http_client = awc::Client::new();
+        actix::spawn(async move {
+            let block_hash = client.block(BlockReference::latest()).await.unwrap().header.hash;
+            let signer =
+                InMemorySigner::from_seed("test1".parse().unwrap(), KeyType::ED25519, "test1");
+            let tx = SignedTransaction::send_money(
+                1,
+                "test1".parse().unwrap(),
+                "test2".parse().unwrap(),
+                &signer,
+                100,
+                block_hash,
+            );
+            let url = format!("http://{}/tx", addr);
+
+            let mut response = http_client
+                .post(&url)
+                .insert_header(("Content-Type", "application/octet-stream"))
+                .send_body(tx.try_to_vec().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), awc::http::StatusCode::OK);
+            let tx_hash: CryptoHash =
+                serde_json::from_slice(&response.body().await.unwrap()).unwrap();
+            assert_eq!(tx_hash, tx.get_hash());
+
+            let response = http_client
+                .post(&url)
+                .insert_header(("Content-Type", "application/octet-stream"))
+                .send_body(vec![1, 2, 3])
+                .await
+                .unwrap();
+            assert_eq!(response.status(), awc::http::StatusCode::BAD_REQUEST);
+
+            let response = http_client
+                .post(&url)
+                .insert_header(("Content-Type", "application/json"))
+                .send_body(tx.try_to_vec().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), awc::http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            System::current().stop();
+        });
+    });
+}
+
 /// Test sending transaction and waiting for it to be committed to a block.
 #[test]
 fn test_send_tx_commit() {
@@ -0,0 +1,237 @@
+//! Admission control of the JSON RPC requests, in front of the view client.
+//!
+//! The view client serves requests one at a time on each of its threads, in the order they
+//! arrive in its mailbox, so a burst of expensive requests used to delay all the others until
+//! they timed out. Requests now first take a slot of their method: the methods configured
+//! apart, `query` by default, have their own slots and queue, all the others share the default
+//! ones. Requests wait in the queue of their method for at most `queue_timeout`, and once the
+//! queue is full further ones are shed right away with a `429 Too Many Requests`, which clients
+//! may retry later. Cheap methods keep being served while expensive ones are shed.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::http;
+use near_jsonrpc_primitives::errors::RpcError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::metrics;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcMethodAdmissionConfig {
+    /// Requests served at a time.
+    pub max_concurrent_requests: usize,
+    /// Requests waiting for a slot, further ones are shed.
+    pub max_queued_requests: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RpcAdmissionConfig {
+    /// Limits shared by all the methods not listed in `methods`.
+    pub default_limits: RpcMethodAdmissionConfig,
+    /// Methods with their own limits, independent of the other methods.
+    pub methods: HashMap<String, RpcMethodAdmissionConfig>,
+    /// How long a request may wait for a slot before it's shed.
+    pub queue_timeout: Duration,
+}
+
+impl Default for RpcAdmissionConfig {
+    fn default() -> Self {
+        let mut methods = HashMap::new();
+        methods.insert(
+            "query".to_string(),
+            RpcMethodAdmissionConfig { max_concurrent_requests: 16, max_queued_requests: 64 },
+        );
+        methods.insert(
+            "broadcast_tx_commit".to_string(),
+            RpcMethodAdmissionConfig { max_concurrent_requests: 256, max_queued_requests: 256 },
+        );
+        Self {
+            default_limits: RpcMethodAdmissionConfig {
+                max_concurrent_requests: 64,
+                max_queued_requests: 256,
+            },
+            methods,
+            queue_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Shedding of a request, returned as the handler error of the JSON RPC response.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AdmissionError {
+    QueueFull,
+    Timeout,
+}
+
+impl fmt::Display for AdmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdmissionError::QueueFull => write!(f, "The node is overloaded, retry later"),
+            AdmissionError::Timeout => {
+                write!(f, "The node is overloaded, the request waited too long to be served")
+            }
+        }
+    }
+}
+
+impl AdmissionError {
+    /// HTTP status of the responses to shed requests.
+    pub fn status_code(&self) -> http::StatusCode {
+        http::StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn reason(&self) -> &'static str {
+        match self {
+            AdmissionError::QueueFull => "queue_full",
+            AdmissionError::Timeout => "timeout",
+        }
+    }
+}
+
+impl From<AdmissionError> for RpcError {
+    fn from(error: AdmissionError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+        match serde_json::to_value(error) {
+            Ok(error_struct) => RpcError::new_internal_or_handler_error(error_data, error_struct),
+            Err(err) => RpcError::new_internal_error(
+                None,
+                format!("Failed to serialize AdmissionError: {:?}", err),
+            ),
+        }
+    }
+}
+
+struct MethodQueue {
+    slots: Arc<Semaphore>,
+    queued: AtomicUsize,
+    max_queued: usize,
+}
+
+impl MethodQueue {
+    fn new(config: &RpcMethodAdmissionConfig) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(config.max_concurrent_requests.max(1))),
+            queued: AtomicUsize::new(0),
+            max_queued: config.max_queued_requests,
+        }
+    }
+}
+
+/// Decrements the number of queued requests when the request leaves the queue, admitted or not.
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub(crate) struct Admission {
+    default_queue: MethodQueue,
+    method_queues: HashMap<String, MethodQueue>,
+    queue_timeout: Duration,
+}
+
+impl Admission {
+    pub fn new(config: RpcAdmissionConfig) -> Self {
+        Self {
+            default_queue: MethodQueue::new(&config.default_limits),
+            method_queues: config
+                .methods
+                .iter()
+                .map(|(method, limits)| (method.clone(), MethodQueue::new(limits)))
+                .collect(),
+            queue_timeout: config.queue_timeout,
+        }
+    }
+
+    /// Waits for a slot of the method, held until the returned permit is dropped.
+    pub async fn admit(&self, method: &str) -> Result<OwnedSemaphorePermit, AdmissionError> {
+        let queue = self.method_queues.get(method).unwrap_or(&self.default_queue);
+        let result = self.wait_for_slot(queue).await;
+        if let Err(err) = &result {
+            metrics::RPC_REQUESTS_SHED_COUNT.with_label_values(&[method, err.reason()]).inc();
+        }
+        result
+    }
+
+    async fn wait_for_slot(
+        &self,
+        queue: &MethodQueue,
+    ) -> Result<OwnedSemaphorePermit, AdmissionError> {
+        if let Ok(permit) = queue.slots.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        if queue.queued.fetch_add(1, Ordering::SeqCst) >= queue.max_queued {
+            queue.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(AdmissionError::QueueFull);
+        }
+        let _queued = QueuedGuard(&queue.queued);
+        match tokio::time::timeout(self.queue_timeout, queue.slots.clone().acquire_owned()).await {
+            Ok(Ok(permit)) => Ok(permit),
+            // The semaphore is never closed.
+            Ok(Err(_)) => unreachable!(),
+            Err(_) => Err(AdmissionError::Timeout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_admission() {
+        let mut methods = HashMap::new();
+        methods.insert(
+            "query".to_string(),
+            RpcMethodAdmissionConfig { max_concurrent_requests: 1, max_queued_requests: 1 },
+        );
+        let admission = Arc::new(Admission::new(RpcAdmissionConfig {
+            default_limits: RpcMethodAdmissionConfig {
+                max_concurrent_requests: 1,
+                max_queued_requests: 0,
+            },
+            methods,
+            queue_timeout: Duration::from_secs(10),
+        }));
+
+        let permit = admission.admit("query").await.unwrap();
+        let queued = {
+            let admission = admission.clone();
+            tokio::spawn(async move { admission.admit("query").await.map(|_| ()) })
+        };
+        while admission.method_queues["query"].queued.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        // The queue of `query` is full, other methods are still served.
+        assert_eq!(admission.admit("query").await.unwrap_err(), AdmissionError::QueueFull);
+        let block_permit = admission.admit("block").await.unwrap();
+        assert_eq!(admission.admit("chunk").await.unwrap_err(), AdmissionError::QueueFull);
+        drop(block_permit);
+        drop(permit);
+        assert_eq!(queued.await.unwrap(), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_queue_timeout() {
+        let admission = Admission::new(RpcAdmissionConfig {
+            queue_timeout: Duration::from_millis(10),
+            ..Default::default()
+        });
+        let _permits: Vec<_> = futures::future::join_all((0..16).map(|_| admission.admit("query")))
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(admission.admit("query").await.unwrap_err(), AdmissionError::Timeout);
+    }
+}
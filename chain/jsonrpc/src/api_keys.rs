@@ -0,0 +1,358 @@
+//! API keys identifying the consumers of the JSON RPC.
+//!
+//! Keys are defined in the RPC config and optionally in a JSON file which is read again
+//! whenever it changes. Each key may restrict the methods it can call and the rate of its
+//! requests. Requests are counted per key and method, both in metrics and in the usage served
+//! by the admin endpoint.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use std::{fmt, fs, io};
+
+use actix_web::http;
+use near_jsonrpc_primitives::errors::RpcError;
+use near_primitives::time::Clock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::metrics;
+
+/// How often the keys file is checked for modifications.
+const KEYS_FILE_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct RpcApiKeysConfig {
+    /// Whether JSON RPC requests without a key are rejected. If false, they are served without
+    /// any restriction and accounted under an empty key name.
+    pub required: bool,
+    pub keys: Vec<RpcApiKeyConfig>,
+    /// JSON file with a list of additional keys, read again whenever it changes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keys_file: Option<PathBuf>,
+    /// Key granting access to the `/admin` endpoints, which are disabled if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RpcApiKeyConfig {
+    /// Name of the consumer, used in metrics and usage reports.
+    pub name: String,
+    /// Secret passed by the consumer in the `X-Api-Key` header.
+    pub key: String,
+    /// Methods the key may call, all of them if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_methods: Option<Vec<String>>,
+    /// Sustained number of requests per second, unlimited if not set. Bursts of up to one
+    /// second worth of requests are allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_requests_per_second: Option<u32>,
+}
+
+/// Rejection of a request by its API key, returned as the handler error of the JSON RPC response.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApiKeyError {
+    Missing,
+    Unknown,
+    MethodNotAllowed { method_name: String },
+    RateLimited,
+}
+
+impl fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiKeyError::Missing => write!(f, "API key is required"),
+            ApiKeyError::Unknown => write!(f, "Unknown API key"),
+            ApiKeyError::MethodNotAllowed { method_name } => {
+                write!(f, "Method {} is not allowed for this API key", method_name)
+            }
+            ApiKeyError::RateLimited => write!(f, "Rate limit of the API key exceeded"),
+        }
+    }
+}
+
+impl ApiKeyError {
+    /// HTTP status of the responses to rejected requests.
+    pub fn status_code(&self) -> http::StatusCode {
+        match self {
+            ApiKeyError::Missing | ApiKeyError::Unknown => http::StatusCode::UNAUTHORIZED,
+            ApiKeyError::MethodNotAllowed { .. } => http::StatusCode::FORBIDDEN,
+            ApiKeyError::RateLimited => http::StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self {
+            ApiKeyError::Missing => "missing",
+            ApiKeyError::Unknown => "unknown",
+            ApiKeyError::MethodNotAllowed { .. } => "method_not_allowed",
+            ApiKeyError::RateLimited => "rate_limited",
+        }
+    }
+}
+
+impl From<ApiKeyError> for RpcError {
+    fn from(error: ApiKeyError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+        match serde_json::to_value(error) {
+            Ok(error_struct) => RpcError::new_internal_or_handler_error(error_data, error_struct),
+            Err(err) => RpcError::new_internal_error(
+                None,
+                format!("Failed to serialize ApiKeyError: {:?}", err),
+            ),
+        }
+    }
+}
+
+/// Requests made with a key.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ApiKeyUsage {
+    pub total: u64,
+    pub rejected: u64,
+    pub methods: BTreeMap<String, u64>,
+}
+
+struct ApiKey {
+    config: RpcApiKeyConfig,
+    /// Requests which can be made right away, refilled at `max_requests_per_second`.
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl ApiKey {
+    fn new(config: RpcApiKeyConfig) -> Self {
+        let tokens = config.max_requests_per_second.unwrap_or(0) as f64;
+        Self { config, tokens, last_refill: Clock::instant() }
+    }
+
+    fn check(&mut self, method: &str) -> Result<(), ApiKeyError> {
+        if let Some(allowed_methods) = &self.config.allowed_methods {
+            if !allowed_methods.iter().any(|allowed| allowed == method) {
+                return Err(ApiKeyError::MethodNotAllowed { method_name: method.to_string() });
+            }
+        }
+        if let Some(rate) = self.config.max_requests_per_second {
+            let now = Clock::instant();
+            let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+            self.last_refill = now;
+            if self.tokens < 1.0 {
+                return Err(ApiKeyError::RateLimited);
+            }
+            self.tokens -= 1.0;
+        }
+        Ok(())
+    }
+}
+
+struct ApiKeysState {
+    /// Keys by secret.
+    keys: HashMap<String, ApiKey>,
+    /// Usage by key name.
+    usage: BTreeMap<String, ApiKeyUsage>,
+    keys_file_modified: Option<SystemTime>,
+    keys_file_checked: Instant,
+}
+
+pub(crate) struct ApiKeys {
+    config: RpcApiKeysConfig,
+    state: Mutex<ApiKeysState>,
+}
+
+impl ApiKeys {
+    pub fn new(config: RpcApiKeysConfig) -> Self {
+        let state = ApiKeysState {
+            keys: HashMap::new(),
+            usage: BTreeMap::new(),
+            keys_file_modified: None,
+            keys_file_checked: Clock::instant(),
+        };
+        let api_keys = Self { config, state: Mutex::new(state) };
+        {
+            let mut state = api_keys.state.lock().unwrap();
+            api_keys.reload(&mut state);
+        }
+        api_keys
+    }
+
+    /// Checks whether a request to `method` may be made with `key`, and accounts for it.
+    /// Returns the name of the key.
+    pub fn check(&self, key: Option<&str>, method: &str) -> Result<String, ApiKeyError> {
+        let mut state = self.state.lock().unwrap();
+        if state.keys_file_checked.elapsed() >= KEYS_FILE_CHECK_PERIOD {
+            self.reload_if_modified(&mut state);
+        }
+        let (name, result) = match key {
+            None if self.config.required => (String::new(), Err(ApiKeyError::Missing)),
+            None => (String::new(), Ok(())),
+            Some(key) => match state.keys.get_mut(key) {
+                None => (String::new(), Err(ApiKeyError::Unknown)),
+                Some(api_key) => (api_key.config.name.clone(), api_key.check(method)),
+            },
+        };
+        let usage = state.usage.entry(name.clone()).or_default();
+        usage.total += 1;
+        match result {
+            Ok(()) => {
+                *usage.methods.entry(method.to_string()).or_default() += 1;
+                metrics::RPC_API_KEY_REQUEST_COUNT.with_label_values(&[&name, method]).inc();
+                Ok(name)
+            }
+            Err(err) => {
+                usage.rejected += 1;
+                metrics::RPC_API_KEY_REJECTED_COUNT.with_label_values(&[&name, err.reason()]).inc();
+                Err(err)
+            }
+        }
+    }
+
+    /// Checks whether a connection may be opened with `key`, without accounting for a request.
+    /// The requests made over the connection are checked one by one.
+    pub fn authenticate(&self, key: Option<&str>) -> Result<(), ApiKeyError> {
+        let mut state = self.state.lock().unwrap();
+        if state.keys_file_checked.elapsed() >= KEYS_FILE_CHECK_PERIOD {
+            self.reload_if_modified(&mut state);
+        }
+        match key {
+            None if self.config.required => Err(ApiKeyError::Missing),
+            Some(key) if !state.keys.contains_key(key) => Err(ApiKeyError::Unknown),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `admin_key` is the configured admin key.
+    pub fn is_admin(&self, admin_key: Option<&str>) -> bool {
+        match (&self.config.admin_key, admin_key) {
+            (Some(expected), Some(admin_key)) => expected == admin_key,
+            _ => false,
+        }
+    }
+
+    /// Usage by key name, if `admin_key` is the configured admin key.
+    pub fn usage(&self, admin_key: Option<&str>) -> Option<BTreeMap<String, ApiKeyUsage>> {
+        if self.is_admin(admin_key) {
+            Some(self.state.lock().unwrap().usage.clone())
+        } else {
+            None
+        }
+    }
+
+    fn reload_if_modified(&self, state: &mut ApiKeysState) {
+        state.keys_file_checked = Clock::instant();
+        let path = match &self.config.keys_file {
+            Some(path) => path,
+            None => return,
+        };
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        if modified.is_none() || modified != state.keys_file_modified {
+            self.reload(state);
+        }
+    }
+
+    /// Rebuilds the keys from the config and the keys file. Rate limits of the keys which are
+    /// still present are carried over.
+    fn reload(&self, state: &mut ApiKeysState) {
+        let mut configs = self.config.keys.clone();
+        if let Some(path) = &self.config.keys_file {
+            state.keys_file_modified =
+                fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+            match read_keys_file(path) {
+                Ok(keys) => configs.extend(keys),
+                Err(err) => {
+                    warn!(target: "jsonrpc", "Failed to read API keys from {}: {}", path.display(), err);
+                    return;
+                }
+            }
+        }
+        let mut old_keys = std::mem::take(&mut state.keys);
+        for config in configs {
+            let api_key = match old_keys.remove(&config.key) {
+                Some(mut api_key)
+                    if api_key.config.max_requests_per_second == config.max_requests_per_second =>
+                {
+                    api_key.config = config;
+                    api_key
+                }
+                _ => ApiKey::new(config),
+            };
+            state.keys.insert(api_key.config.key.clone(), api_key);
+        }
+    }
+}
+
+fn read_keys_file(path: &Path) -> io::Result<Vec<RpcApiKeyConfig>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str, allowed_methods: Option<&[&str]>, rate: Option<u32>) -> RpcApiKeyConfig {
+        RpcApiKeyConfig {
+            name: name.to_string(),
+            key: format!("{}-secret", name),
+            allowed_methods: allowed_methods
+                .map(|methods| methods.iter().map(|method| method.to_string()).collect()),
+            max_requests_per_second: rate,
+        }
+    }
+
+    #[test]
+    fn test_api_keys() {
+        let api_keys = ApiKeys::new(RpcApiKeysConfig {
+            required: true,
+            keys: vec![key("alice", Some(&["block"]), None), key("bob", None, Some(2))],
+            keys_file: None,
+            admin_key: Some("admin".to_string()),
+        });
+        assert_eq!(api_keys.check(None, "block"), Err(ApiKeyError::Missing));
+        assert_eq!(api_keys.check(Some("carol-secret"), "block"), Err(ApiKeyError::Unknown));
+        assert_eq!(api_keys.check(Some("alice-secret"), "block"), Ok("alice".to_string()));
+        assert_eq!(
+            api_keys.check(Some("alice-secret"), "query"),
+            Err(ApiKeyError::MethodNotAllowed { method_name: "query".to_string() })
+        );
+        assert!(api_keys.check(Some("bob-secret"), "query").is_ok());
+        assert!(api_keys.check(Some("bob-secret"), "query").is_ok());
+        assert_eq!(api_keys.check(Some("bob-secret"), "query"), Err(ApiKeyError::RateLimited));
+        assert_eq!(api_keys.authenticate(None), Err(ApiKeyError::Missing));
+        assert_eq!(api_keys.authenticate(Some("carol-secret")), Err(ApiKeyError::Unknown));
+        assert_eq!(api_keys.authenticate(Some("bob-secret")), Ok(()));
+
+        assert_eq!(api_keys.usage(None), None);
+        assert_eq!(api_keys.usage(Some("alice-secret")), None);
+        let usage = api_keys.usage(Some("admin")).unwrap();
+        assert_eq!(usage["alice"].total, 2);
+        assert_eq!(usage["alice"].rejected, 1);
+        assert_eq!(usage["alice"].methods["block"], 1);
+        assert_eq!(usage["bob"].methods["query"], 2);
+        assert_eq!(usage[""].rejected, 2);
+    }
+
+    #[test]
+    fn test_keys_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api_keys.json");
+        fs::write(&path, serde_json::to_string(&vec![key("alice", None, None)]).unwrap()).unwrap();
+        let api_keys = ApiKeys::new(RpcApiKeysConfig {
+            required: true,
+            keys_file: Some(path.clone()),
+            ..Default::default()
+        });
+        assert!(api_keys.check(Some("alice-secret"), "block").is_ok());
+
+        fs::write(&path, serde_json::to_string(&vec![key("bob", None, None)]).unwrap()).unwrap();
+        {
+            let mut state = api_keys.state.lock().unwrap();
+            api_keys.reload(&mut state);
+        }
+        assert_eq!(api_keys.check(Some("alice-secret"), "block"), Err(ApiKeyError::Unknown));
+        assert!(api_keys.check(Some("bob-secret"), "block").is_ok());
+    }
+}
@@ -1,24 +1,34 @@
 #![doc = include_str!("../README.md")]
 
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
 use std::time::Duration;
 
 use actix::Addr;
 use actix_cors::Cors;
-use actix_web::{http, middleware, web, App, Error as HttpError, HttpResponse, HttpServer};
+use actix_web::{
+    http, middleware, web, App, Error as HttpError, HttpRequest, HttpResponse, HttpServer,
+};
 use futures::Future;
 use futures::FutureExt;
 use prometheus;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tokio::time::{sleep, timeout};
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::time::{sleep, timeout, timeout_at, Instant};
 use tracing::info;
 
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    ClientActor, GetBlock, GetBlockProof, GetChunk, GetExecutionOutcome, GetGasPrice,
-    GetNetworkInfo, GetNextLightClientBlock, GetProtocolConfig, GetReceipt, GetStateChanges,
-    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, Query, Status, TxStatus,
-    TxStatusError, ViewClientActor,
+    AcknowledgeProtocolUpgrade, ClientActor, DebugStatus, DebugStatusResponse,
+    EstimateTransactionFees, GetAccessKeyUsage, GetAccountSummary, GetBlock, GetBlockProof,
+    GetBlockTransactionStats, GetChunk, GetChunkPartsAvailability, GetClockSkewReport,
+    GetContractHistory, GetEpochChainStats, GetExecutionOutcome, GetFailedReceipts, GetGasPrice,
+    GetGasThroughput, GetNetworkInfo, GetNextLightClientBlock, GetPoolStatus, GetPoolTransactions,
+    GetProtocolConfig, GetProtocolUpgradeStatus, GetReceipt, GetReceiptInclusionProof,
+    GetRefundStats, GetStateChanges, GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
+    Query, Readiness, ReloadPeerRanges, SimulateTransaction, Status, TxStatus, TxStatusError,
+    ViewClientActor,
 };
 #[cfg(feature = "test_features")]
 use near_jsonrpc_adversarial_primitives::SetAdvOptionsRequest;
@@ -33,9 +43,10 @@ use near_jsonrpc_adversarial_primitives::SetRoutingTableRequest;
 ))]
 use near_jsonrpc_adversarial_primitives::StartRoutingTableSyncRequest;
 pub use near_jsonrpc_client as client;
-use near_jsonrpc_primitives::errors::RpcError;
+use near_jsonrpc_primitives::errors::{RpcError, ServerError};
 use near_jsonrpc_primitives::message::{Message, Request};
 use near_jsonrpc_primitives::types::config::RpcProtocolConfigResponse;
+use near_jsonrpc_primitives::types::protocol_upgrade::RpcProtocolUpgradeError;
 use near_metrics::{Encoder, TextEncoder};
 #[cfg(feature = "test_features")]
 use near_network::routing::GetRoutingTableResult;
@@ -59,13 +70,37 @@ use near_network_primitives::types::NetworkAdversarialMessage;
 use near_network_primitives::types::NetworkSandboxMessage;
 #[cfg(feature = "test_features")]
 use near_network_primitives::types::NetworkViewClientMessages;
+use near_primitives::borsh::BorshDeserialize;
 use near_primitives::hash::CryptoHash;
 use near_primitives::serialize::BaseEncode;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::AccountId;
 use near_primitives::views::FinalExecutionOutcomeViewEnum;
 
+pub use near_jsonrpc_primitives::types::pool::RpcPoolTransactionsExposure;
+
+use crate::admission::Admission;
+pub use crate::admission::{RpcAdmissionConfig, RpcMethodAdmissionConfig};
+use crate::api_keys::ApiKeys;
+pub use crate::api_keys::{RpcApiKeyConfig, RpcApiKeysConfig};
+pub use crate::readiness::RpcReadinessConfig;
+pub use crate::tx_policy::RpcTxPolicyConfig;
+use crate::tx_policy::TxPolicy;
+pub use crate::websocket::RpcWebSocketConfig;
+
+mod admission;
+mod api_keys;
 mod metrics;
+mod readiness;
+mod tx_policy;
+mod websocket;
+
+/// Header carrying the API key of JSON RPC requests and admin requests.
+const API_KEY_HEADER: &str = "x-api-key";
+/// Content type of the borsh serialized transactions accepted by the `/tx` endpoint.
+const BORSH_CONTENT_TYPE: &str = "application/octet-stream";
+/// Method the `/tx` endpoint is restricted, admitted and accounted as, since it does the same.
+const BINARY_TX_METHOD: &str = "broadcast_tx_async";
 
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub struct RpcPollingConfig {
@@ -94,6 +129,22 @@ impl Default for RpcLimitsConfig {
     }
 }
 
+/// Limits of the batches of requests, which are processed concurrently.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct RpcBatchConfig {
+    /// Most requests in a batch, larger batches are refused as a whole.
+    pub max_batch_size: usize,
+    /// Time after which the requests of a batch not processed yet answer a timeout error.
+    pub timeout: Duration,
+}
+
+impl Default for RpcBatchConfig {
+    fn default() -> Self {
+        Self { max_batch_size: 100, timeout: Duration::from_secs(10) }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct RpcConfig {
     pub addr: String,
@@ -103,6 +154,29 @@ pub struct RpcConfig {
     pub polling_config: RpcPollingConfig,
     #[serde(default)]
     pub limits_config: RpcLimitsConfig,
+    #[serde(default)]
+    pub batch: RpcBatchConfig,
+    /// If provided, JSON RPC requests are identified by API keys, restricted and accounted
+    /// per key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_keys: Option<RpcApiKeysConfig>,
+    /// What `EXPERIMENTAL_pool_transactions` reveals of the transactions in the pool.
+    #[serde(default)]
+    pub pool_transactions: RpcPoolTransactionsExposure,
+    /// If provided, transactions submitted by users are checked against this policy before
+    /// being forwarded to the network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_policy: Option<RpcTxPolicyConfig>,
+    /// If provided, requests wait for a slot of their method and are shed when too many are
+    /// waiting, instead of all queueing up in the view client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admission: Option<RpcAdmissionConfig>,
+    /// Criteria of the readiness of the node checked by `/readyz`.
+    #[serde(default)]
+    pub readiness: RpcReadinessConfig,
+    /// Limits of the subscriptions at `/ws`.
+    #[serde(default)]
+    pub websocket: RpcWebSocketConfig,
 }
 
 impl Default for RpcConfig {
@@ -113,6 +187,13 @@ impl Default for RpcConfig {
             cors_allowed_origins: vec!["*".to_owned()],
             polling_config: Default::default(),
             limits_config: Default::default(),
+            batch: Default::default(),
+            api_keys: None,
+            pool_transactions: RpcPoolTransactionsExposure::Disabled,
+            tx_policy: None,
+            admission: None,
+            readiness: Default::default(),
+            websocket: Default::default(),
         }
     }
 }
@@ -231,7 +312,16 @@ struct JsonRpcHandler {
     client_addr: Addr<ClientActor>,
     view_client_addr: Addr<ViewClientActor>,
     polling_config: RpcPollingConfig,
+    batch: RpcBatchConfig,
     genesis_config: GenesisConfig,
+    api_keys: Option<Arc<ApiKeys>>,
+    pool_transactions_exposure: RpcPoolTransactionsExposure,
+    tx_policy: Option<Arc<TxPolicy>>,
+    admission: Option<Arc<Admission>>,
+    readiness: RpcReadinessConfig,
+    websocket: RpcWebSocketConfig,
+    /// Open connections at `/ws`, shared by the workers.
+    websocket_connections: Arc<AtomicUsize>,
     #[cfg(feature = "test_features")]
     peer_manager_addr: Addr<PeerManagerActor>,
     #[cfg(feature = "test_features")]
@@ -251,6 +341,65 @@ impl JsonRpcHandler {
         }
     }
 
+    /// Checks the API key of a request and waits for a slot of its method, see `Admission`.
+    async fn admit(
+        &self,
+        api_key: Option<&str>,
+        request: &Request,
+    ) -> Result<Option<OwnedSemaphorePermit>, (http::StatusCode, RpcError)> {
+        if let Some(api_keys) = &self.api_keys {
+            if let Err(err) = api_keys.check(api_key, &request.method) {
+                return Err((err.status_code(), err.into()));
+            }
+        }
+        match &self.admission {
+            Some(admission) => admission
+                .admit(&request.method)
+                .await
+                .map(Some)
+                .map_err(|err| (err.status_code(), err.into())),
+            None => Ok(None),
+        }
+    }
+
+    /// Processes the requests of a batch concurrently, each admitted like a single request. The
+    /// responses are in the order of the requests, and the notifications have none.
+    async fn process_batch(&self, api_key: Option<&str>, messages: Vec<Message>) -> Message {
+        let max_batch_size = self.batch.max_batch_size;
+        if messages.is_empty() || messages.len() > max_batch_size {
+            return Message::error(RpcError::parse_error(format!(
+                "A batch must have between 1 and {} requests",
+                max_batch_size
+            )));
+        }
+        let deadline = Instant::now() + self.batch.timeout;
+        let responses = futures::future::join_all(messages.into_iter().map(|message| async move {
+            match message {
+                Message::Request(request) => {
+                    let id = request.id.clone();
+                    let method = request.method.clone();
+                    let response = timeout_at(deadline, async {
+                        match self.admit(api_key, &request).await {
+                            Ok(_permit) => self.process_request(request).await,
+                            Err((_, error)) => Err(error),
+                        }
+                    })
+                    .await
+                    .unwrap_or_else(|_| {
+                        metrics::RPC_TIMEOUT_TOTAL.inc();
+                        tracing::warn!(target: "jsonrpc", "Timeout: {} method in a batch", method);
+                        Err(RpcError::from(ServerError::Timeout))
+                    });
+                    Some(Message::response(id, response))
+                }
+                Message::Notification(_) => None,
+                message => self.process(message).await.ok(),
+            }
+        }))
+        .await;
+        Message::Batch(responses.into_iter().flatten().collect())
+    }
+
     async fn process_request(&self, request: Request) -> Result<Value, RpcError> {
         metrics::HTTP_RPC_REQUEST_COUNT.with_label_values(&[request.method.as_ref()]).inc();
         let _rpc_processing_time = metrics::RPC_PROCESSING_TIME
@@ -373,7 +522,7 @@ impl JsonRpcHandler {
                     near_jsonrpc_primitives::types::transactions::RpcBroadcastTransactionRequest::parse(
                         request.params,
                     )?;
-                let transaction_hash = self.send_tx_async(rpc_transaction_request).await;
+                let transaction_hash = self.send_tx_async(rpc_transaction_request).await?;
                 serde_json::to_value((&transaction_hash).to_base())
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
@@ -455,6 +604,34 @@ impl JsonRpcHandler {
                 serde_json::to_value(validator_info)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            "EXPERIMENTAL_access_key_usage" => {
+                let rpc_access_key_usage_request =
+                    near_jsonrpc_primitives::types::access_key_usage::RpcAccessKeyUsageRequest::parse(
+                        request.params,
+                    )?;
+                let access_key_usage = self.access_key_usage(rpc_access_key_usage_request).await?;
+                serde_json::to_value(access_key_usage)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_account_summary" => {
+                let rpc_account_summary_request =
+                    near_jsonrpc_primitives::types::account_summary::RpcAccountSummaryRequest::parse(
+                        request.params,
+                    )?;
+                let account_summary = self.account_summary(rpc_account_summary_request).await?;
+                serde_json::to_value(account_summary)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_block_transaction_stats" => {
+                let rpc_block_transaction_stats_request =
+                    near_jsonrpc_primitives::types::transaction_stats::RpcBlockTransactionStatsRequest::parse(
+                        request.params,
+                    )?;
+                let block_transaction_stats =
+                    self.block_transaction_stats(rpc_block_transaction_stats_request).await?;
+                serde_json::to_value(block_transaction_stats)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             "EXPERIMENTAL_broadcast_tx_sync" => {
                 let rpc_transaction_request =
                     near_jsonrpc_primitives::types::transactions::RpcBroadcastTransactionRequest::parse(
@@ -492,6 +669,53 @@ impl JsonRpcHandler {
                 serde_json::to_value(broadcast_tx_sync_response)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            "EXPERIMENTAL_chunk_parts_availability" => {
+                let rpc_chunk_parts_availability_request =
+                    near_jsonrpc_primitives::types::parts_availability::RpcChunkPartsAvailabilityRequest::parse(
+                        request.params,
+                    )?;
+                let chunk_parts_availability =
+                    self.chunk_parts_availability(rpc_chunk_parts_availability_request).await?;
+                serde_json::to_value(chunk_parts_availability)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_contract_history" => {
+                let rpc_contract_history_request =
+                    near_jsonrpc_primitives::types::contract_history::RpcContractHistoryRequest::parse(
+                        request.params,
+                    )?;
+                let contract_history = self.contract_history(rpc_contract_history_request).await?;
+                serde_json::to_value(contract_history)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_epoch_stats" => {
+                let rpc_epoch_chain_stats_request =
+                    near_jsonrpc_primitives::types::epoch_stats::RpcEpochChainStatsRequest::parse(
+                        request.params,
+                    )?;
+                let epoch_chain_stats =
+                    self.epoch_chain_stats(rpc_epoch_chain_stats_request).await?;
+                serde_json::to_value(epoch_chain_stats)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_estimate_fees" => {
+                let rpc_estimate_fees_request =
+                    near_jsonrpc_primitives::types::fees::RpcEstimateFeesRequest::parse(
+                        request.params,
+                    )?;
+                let fees = self.estimate_fees(rpc_estimate_fees_request).await?;
+                serde_json::to_value(fees)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_failed_receipts" => {
+                let rpc_failed_receipts_request =
+                    near_jsonrpc_primitives::types::failed_receipts::RpcFailedReceiptsRequest::parse(
+                        request.params,
+                    )?;
+                let failed_receipts = self.failed_receipts(rpc_failed_receipts_request).await?;
+                serde_json::to_value(failed_receipts)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             "EXPERIMENTAL_genesis_config" => {
                 let genesis_config = self.genesis_config().await;
                 serde_json::to_value(genesis_config)
@@ -505,6 +729,26 @@ impl JsonRpcHandler {
                 serde_json::to_value(rpc_light_client_execution_proof_response)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            "EXPERIMENTAL_node_features" => {
+                let node_features = self.node_features().await;
+                serde_json::to_value(node_features)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_pool_status" => {
+                let pool_status = self.pool_status().await?;
+                serde_json::to_value(pool_status)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_pool_transactions" => {
+                let rpc_pool_transactions_request =
+                    near_jsonrpc_primitives::types::pool::RpcPoolTransactionsRequest::parse(
+                        request.params,
+                    )?;
+                let pool_transactions =
+                    self.pool_transactions(rpc_pool_transactions_request).await?;
+                serde_json::to_value(pool_transactions)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             "EXPERIMENTAL_protocol_config" => {
                 let rpc_protocol_config_request =
                     near_jsonrpc_primitives::types::config::RpcProtocolConfigRequest::parse(
@@ -523,6 +767,33 @@ impl JsonRpcHandler {
                 serde_json::to_value(receipt)
                     .map_err(|err| RpcError::serialization_error(err.to_string()))
             }
+            "EXPERIMENTAL_receipt_proof" => {
+                let rpc_receipt_proof_request =
+                    near_jsonrpc_primitives::types::receipt_proof::RpcReceiptProofRequest::parse(
+                        request.params,
+                    )?;
+                let receipt_proof = self.receipt_proof(rpc_receipt_proof_request).await?;
+                serde_json::to_value(receipt_proof)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_refund_stats" => {
+                let rpc_refund_stats_request =
+                    near_jsonrpc_primitives::types::refund_stats::RpcRefundStatsRequest::parse(
+                        request.params,
+                    )?;
+                let refund_stats = self.refund_stats(rpc_refund_stats_request).await?;
+                serde_json::to_value(refund_stats)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
+            "EXPERIMENTAL_simulate_tx" => {
+                let rpc_simulate_transaction_request =
+                    near_jsonrpc_primitives::types::simulate::RpcSimulateTransactionRequest::parse(
+                        request.params,
+                    )?;
+                let simulation = self.simulate_tx(rpc_simulate_transaction_request).await?;
+                serde_json::to_value(simulation)
+                    .map_err(|err| RpcError::serialization_error(err.to_string()))
+            }
             "EXPERIMENTAL_tx_status" => {
                 let rpc_transaction_status_common_request = near_jsonrpc_primitives::types::transactions::RpcTransactionStatusCommonRequest::parse(request.params)?;
                 let rpc_transaction_response =
@@ -562,18 +833,34 @@ impl JsonRpcHandler {
         response
     }
 
+    /// Checks a transaction submitted by a user against the policy of the operator, if any.
+    fn check_tx_policy(
+        &self,
+        tx: &SignedTransaction,
+    ) -> Result<(), near_jsonrpc_primitives::types::transactions::RpcTransactionError> {
+        match &self.tx_policy {
+            Some(tx_policy) => tx_policy.check(tx).map_err(|reason| {
+                near_jsonrpc_primitives::types::transactions::RpcTransactionError::RejectedByPolicy {
+                    reason,
+                }
+            }),
+            None => Ok(()),
+        }
+    }
+
     async fn send_tx_async(
         &self,
         request_data: near_jsonrpc_primitives::types::transactions::RpcBroadcastTransactionRequest,
-    ) -> CryptoHash {
+    ) -> Result<CryptoHash, near_jsonrpc_primitives::types::transactions::RpcTransactionError> {
         let tx = request_data.signed_transaction;
+        self.check_tx_policy(&tx)?;
         let hash = tx.get_hash().clone();
         self.client_addr.do_send(NetworkClientMessages::Transaction {
             transaction: tx,
             is_forwarded: false,
             check_only: false, // if we set true here it will not actually send the transaction
         });
-        hash
+        Ok(hash)
     }
 
     async fn tx_exists(
@@ -757,6 +1044,7 @@ impl JsonRpcHandler {
         near_jsonrpc_primitives::types::transactions::RpcBroadcastTxSyncResponse,
         near_jsonrpc_primitives::types::transactions::RpcTransactionError,
     > {
+        self.check_tx_policy(&request_data.signed_transaction)?;
         match self.send_tx(request_data.clone().signed_transaction, false).await? {
             NetworkClientResponses::ValidTx => {
                 Ok(near_jsonrpc_primitives::types::transactions::RpcBroadcastTxSyncResponse {
@@ -783,6 +1071,7 @@ impl JsonRpcHandler {
         near_jsonrpc_primitives::types::transactions::RpcBroadcastTxSyncResponse,
         near_jsonrpc_primitives::types::transactions::RpcTransactionError,
     > {
+        self.check_tx_policy(&request_data.signed_transaction)?;
         match self.send_tx(request_data.clone().signed_transaction, true).await? {
             NetworkClientResponses::ValidTx => {
                 Ok(near_jsonrpc_primitives::types::transactions::RpcBroadcastTxSyncResponse {
@@ -808,6 +1097,7 @@ impl JsonRpcHandler {
         near_jsonrpc_primitives::types::transactions::RpcTransactionError,
     > {
         let tx = request_data.signed_transaction;
+        self.check_tx_policy(&tx)?;
         match self
             .tx_status_fetch(
                 near_jsonrpc_primitives::types::transactions::TransactionInfo::Transaction(
@@ -852,6 +1142,17 @@ impl JsonRpcHandler {
         Ok(self.client_addr.send(Status { is_health_check: true }).await??.into())
     }
 
+    /// The readiness criteria the node fails, none if it's ready.
+    async fn readiness(&self) -> Result<Vec<String>, String> {
+        let readiness = self
+            .client_addr
+            .send(Readiness { check_store_writable: self.readiness.check_store_writable })
+            .await
+            .map_err(|err| err.to_string())?
+            .map_err(|err| err.to_string())?;
+        Ok(self.readiness.failed_criteria(&readiness))
+    }
+
     pub async fn status(
         &self,
     ) -> Result<
@@ -861,6 +1162,13 @@ impl JsonRpcHandler {
         Ok(self.client_addr.send(Status { is_health_check: false }).await??.into())
     }
 
+    async fn debug_status(
+        &self,
+        status: DebugStatus,
+    ) -> Result<DebugStatusResponse, near_jsonrpc_primitives::types::status::RpcStatusError> {
+        Ok(self.client_addr.send(status).await??)
+    }
+
     /// Expose Genesis Config (with internal Runtime Config) without state records to keep the
     /// output at a reasonable size.
     ///
@@ -869,6 +1177,11 @@ impl JsonRpcHandler {
         &self.genesis_config
     }
 
+    /// Build features of the binary and the protocol features compiled into it.
+    pub async fn node_features(&self) -> near_primitives::views::NodeFeaturesView {
+        near_primitives::features::node_features()
+    }
+
     pub async fn protocol_config(
         &self,
         request_data: near_jsonrpc_primitives::types::config::RpcProtocolConfigRequest,
@@ -891,6 +1204,8 @@ impl JsonRpcHandler {
         near_jsonrpc_primitives::types::query::RpcQueryError,
     > {
         let query = Query::new(request_data.block_reference, request_data.request);
+        // Dropped along with the request when the client disconnects.
+        let _cancel_on_drop = query.cancellation.cancel_on_drop();
         Ok(self.view_client_addr.send(query).await??.into())
     }
 
@@ -1039,6 +1354,61 @@ impl JsonRpcHandler {
         })
     }
 
+    async fn chunk_parts_availability(
+        &self,
+        request_data: near_jsonrpc_primitives::types::parts_availability::RpcChunkPartsAvailabilityRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::parts_availability::RpcChunkPartsAvailabilityResponse,
+        near_jsonrpc_primitives::types::parts_availability::RpcChunkPartsAvailabilityError,
+    > {
+        let chunks = self
+            .client_addr
+            .send(GetChunkPartsAvailability { limit: request_data.limit() })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::parts_availability::RpcChunkPartsAvailabilityResponse {
+            chunks,
+        })
+    }
+
+    async fn pool_status(
+        &self,
+    ) -> Result<
+        near_jsonrpc_primitives::types::pool::RpcPoolStatusResponse,
+        near_jsonrpc_primitives::types::pool::RpcPoolError,
+    > {
+        let pool_status = self.client_addr.send(GetPoolStatus {}).await??;
+        Ok(near_jsonrpc_primitives::types::pool::RpcPoolStatusResponse { pool_status })
+    }
+
+    async fn pool_transactions(
+        &self,
+        request_data: near_jsonrpc_primitives::types::pool::RpcPoolTransactionsRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::pool::RpcPoolTransactionsResponse,
+        near_jsonrpc_primitives::types::pool::RpcPoolError,
+    > {
+        if self.pool_transactions_exposure == RpcPoolTransactionsExposure::Disabled {
+            return Err(near_jsonrpc_primitives::types::pool::RpcPoolError::TransactionsNotExposed);
+        }
+        let transactions = self
+            .client_addr
+            .send(GetPoolTransactions {
+                shard_id: request_data.shard_id,
+                limit: request_data.limit(),
+            })
+            .await??;
+        let transactions = transactions
+            .into_iter()
+            .filter_map(|transaction| {
+                near_jsonrpc_primitives::types::pool::RpcPoolTransactionSummary::new(
+                    transaction,
+                    self.pool_transactions_exposure,
+                )
+            })
+            .collect();
+        Ok(near_jsonrpc_primitives::types::pool::RpcPoolTransactionsResponse { transactions })
+    }
+
     async fn network_info(
         &self,
     ) -> Result<
@@ -1048,6 +1418,49 @@ impl JsonRpcHandler {
         Ok(self.client_addr.send(GetNetworkInfo {}).await??.into())
     }
 
+    async fn gas_throughput(
+        &self,
+    ) -> Result<
+        near_primitives::views::GasThroughputView,
+        near_jsonrpc_primitives::types::gas_throughput::RpcGasThroughputError,
+    > {
+        Ok(self.view_client_addr.send(GetGasThroughput {}).await??)
+    }
+
+    async fn clock_skew_report(
+        &self,
+    ) -> Result<
+        near_primitives::views::ClockSkewReportView,
+        near_jsonrpc_primitives::types::clock_skew::RpcClockSkewError,
+    > {
+        Ok(self.client_addr.send(GetClockSkewReport {}).await??)
+    }
+
+    async fn protocol_upgrade_status(
+        &self,
+    ) -> Result<
+        near_primitives::views::ProtocolUpgradeStatusView,
+        near_jsonrpc_primitives::types::protocol_upgrade::RpcProtocolUpgradeError,
+    > {
+        Ok(self.client_addr.send(GetProtocolUpgradeStatus {}).await??)
+    }
+
+    async fn acknowledge_protocol_upgrade(
+        &self,
+        protocol_version: near_primitives::types::ProtocolVersion,
+    ) -> Result<
+        near_primitives::views::ProtocolUpgradeStatusView,
+        near_jsonrpc_primitives::types::protocol_upgrade::RpcProtocolUpgradeError,
+    > {
+        Ok(self.client_addr.send(AcknowledgeProtocolUpgrade { protocol_version }).await??)
+    }
+
+    async fn reload_peer_ranges(
+        &self,
+    ) -> Result<(), near_jsonrpc_primitives::types::peer_ranges::RpcReloadPeerRangesError> {
+        Ok(self.client_addr.send(ReloadPeerRanges {}).await??)
+    }
+
     async fn gas_price(
         &self,
         request_data: near_jsonrpc_primitives::types::gas_price::RpcGasPriceRequest,
@@ -1060,6 +1473,182 @@ impl JsonRpcHandler {
         Ok(near_jsonrpc_primitives::types::gas_price::RpcGasPriceResponse { gas_price_view })
     }
 
+    async fn block_transaction_stats(
+        &self,
+        request_data: near_jsonrpc_primitives::types::transaction_stats::RpcBlockTransactionStatsRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::transaction_stats::RpcBlockTransactionStatsResponse,
+        near_jsonrpc_primitives::types::transaction_stats::RpcBlockTransactionStatsError,
+    > {
+        let blocks = self
+            .view_client_addr
+            .send(GetBlockTransactionStats {
+                from_height: request_data.from_height,
+                to_height: request_data.to_height,
+            })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::transaction_stats::RpcBlockTransactionStatsResponse {
+            blocks,
+        })
+    }
+
+    async fn account_summary(
+        &self,
+        request_data: near_jsonrpc_primitives::types::account_summary::RpcAccountSummaryRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::account_summary::RpcAccountSummaryResponse,
+        near_jsonrpc_primitives::types::account_summary::RpcAccountSummaryError,
+    > {
+        let near_jsonrpc_primitives::types::account_summary::RpcAccountSummaryRequest {
+            block_reference,
+            account_id,
+            lockup_factory_id,
+        } = request_data;
+        let account_summary = self
+            .view_client_addr
+            .send(GetAccountSummary { block_reference, account_id, lockup_factory_id })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::account_summary::RpcAccountSummaryResponse {
+            account_summary,
+        })
+    }
+
+    async fn simulate_tx(
+        &self,
+        request_data: near_jsonrpc_primitives::types::simulate::RpcSimulateTransactionRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::simulate::RpcSimulateTransactionResponse,
+        near_jsonrpc_primitives::types::simulate::RpcSimulateTransactionError,
+    > {
+        let near_jsonrpc_primitives::types::simulate::RpcSimulateTransactionRequest {
+            block_reference,
+            transaction,
+            verify_signature,
+        } = request_data;
+        let simulation = self
+            .view_client_addr
+            .send(SimulateTransaction { block_reference, transaction, verify_signature })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::simulate::RpcSimulateTransactionResponse { simulation })
+    }
+
+    async fn estimate_fees(
+        &self,
+        request_data: near_jsonrpc_primitives::types::fees::RpcEstimateFeesRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::fees::RpcEstimateFeesResponse,
+        near_jsonrpc_primitives::types::fees::RpcEstimateFeesError,
+    > {
+        let near_jsonrpc_primitives::types::fees::RpcEstimateFeesRequest {
+            block_reference,
+            signer_id,
+            public_key,
+            receiver_id,
+            actions,
+        } = request_data;
+        let fees = self
+            .view_client_addr
+            .send(EstimateTransactionFees {
+                block_reference,
+                signer_id,
+                public_key,
+                receiver_id,
+                actions,
+            })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::fees::RpcEstimateFeesResponse { fees })
+    }
+
+    async fn epoch_chain_stats(
+        &self,
+        request_data: near_jsonrpc_primitives::types::epoch_stats::RpcEpochChainStatsRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::epoch_stats::RpcEpochChainStatsResponse,
+        near_jsonrpc_primitives::types::epoch_stats::RpcEpochChainStatsError,
+    > {
+        let epochs =
+            self.view_client_addr.send(GetEpochChainStats { limit: request_data.limit }).await??;
+        Ok(near_jsonrpc_primitives::types::epoch_stats::RpcEpochChainStatsResponse { epochs })
+    }
+
+    async fn access_key_usage(
+        &self,
+        request_data: near_jsonrpc_primitives::types::access_key_usage::RpcAccessKeyUsageRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::access_key_usage::RpcAccessKeyUsageResponse,
+        near_jsonrpc_primitives::types::access_key_usage::RpcAccessKeyUsageError,
+    > {
+        let usage = self
+            .view_client_addr
+            .send(GetAccessKeyUsage { account_id: request_data.account_id })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::access_key_usage::RpcAccessKeyUsageResponse { usage })
+    }
+
+    async fn contract_history(
+        &self,
+        request_data: near_jsonrpc_primitives::types::contract_history::RpcContractHistoryRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::contract_history::RpcContractHistoryResponse,
+        near_jsonrpc_primitives::types::contract_history::RpcContractHistoryError,
+    > {
+        let history = self
+            .view_client_addr
+            .send(GetContractHistory {
+                account_id: request_data.account_id,
+                with_diff: request_data.with_diff,
+            })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::contract_history::RpcContractHistoryResponse { history })
+    }
+
+    async fn failed_receipts(
+        &self,
+        request_data: near_jsonrpc_primitives::types::failed_receipts::RpcFailedReceiptsRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::failed_receipts::RpcFailedReceiptsResponse,
+        near_jsonrpc_primitives::types::failed_receipts::RpcFailedReceiptsError,
+    > {
+        let failed_receipts = self
+            .view_client_addr
+            .send(GetFailedReceipts { block_id: request_data.block_id })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::failed_receipts::RpcFailedReceiptsResponse {
+            failed_receipts,
+        })
+    }
+
+    async fn receipt_proof(
+        &self,
+        request_data: near_jsonrpc_primitives::types::receipt_proof::RpcReceiptProofRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::receipt_proof::RpcReceiptProofResponse,
+        near_jsonrpc_primitives::types::receipt_proof::RpcReceiptProofError,
+    > {
+        let proof = self
+            .view_client_addr
+            .send(GetReceiptInclusionProof {
+                receipt_id: request_data.receipt_id,
+                block_id: request_data.block_id,
+            })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::receipt_proof::RpcReceiptProofResponse { proof })
+    }
+
+    async fn refund_stats(
+        &self,
+        request_data: near_jsonrpc_primitives::types::refund_stats::RpcRefundStatsRequest,
+    ) -> Result<
+        near_jsonrpc_primitives::types::refund_stats::RpcRefundStatsResponse,
+        near_jsonrpc_primitives::types::refund_stats::RpcRefundStatsError,
+    > {
+        let refund_stats = self
+            .view_client_addr
+            .send(GetRefundStats { block_id: request_data.block_id })
+            .await??;
+        Ok(near_jsonrpc_primitives::types::refund_stats::RpcRefundStatsResponse { refund_stats })
+    }
+
     async fn validators(
         &self,
         request_data: near_jsonrpc_primitives::types::validator::RpcValidatorRequest,
@@ -1241,17 +1830,87 @@ impl JsonRpcHandler {
     }
 }
 
+fn api_key(req: &HttpRequest) -> Option<&str> {
+    req.headers().get(API_KEY_HEADER).and_then(|value| value.to_str().ok())
+}
+
 fn rpc_handler(
+    req: HttpRequest,
     message: web::Json<Message>,
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
     let response = async move {
-        let message = handler.process(message.0).await?;
+        let request = match message.into_inner() {
+            Message::Request(request) => request,
+            Message::Batch(messages) => {
+                return Ok(match handler.process_batch(api_key(&req), messages).await {
+                    // Only notifications, which get no response.
+                    Message::Batch(responses) if responses.is_empty() => {
+                        HttpResponse::Ok().finish()
+                    }
+                    response => HttpResponse::Ok().json(&response),
+                });
+            }
+            message => return Ok(HttpResponse::Ok().json(&handler.process(message).await?)),
+        };
+        let _permit = match handler.admit(api_key(&req), &request).await {
+            Ok(permit) => permit,
+            Err((status_code, error)) => {
+                return Ok(HttpResponse::build(status_code)
+                    .json(&Message::response(request.id.clone(), Err(error))));
+            }
+        };
+        let message = handler.process(Message::Request(request)).await?;
         Ok(HttpResponse::Ok().json(&message))
     };
     response.boxed()
 }
 
+/// Submits a borsh serialized `SignedTransaction`, decoded straight from the request body, and
+/// responds with its hash like `broadcast_tx_async` without waiting for it to be processed.
+fn binary_tx_handler(
+    req: HttpRequest,
+    body: web::Bytes,
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        let content_type = req.headers().get(http::header::CONTENT_TYPE);
+        if content_type.map_or(true, |value| value != BORSH_CONTENT_TYPE) {
+            return Ok(HttpResponse::UnsupportedMediaType()
+                .body(format!("Content type must be {}", BORSH_CONTENT_TYPE)));
+        }
+        if let Some(api_keys) = &handler.api_keys {
+            if let Err(err) = api_keys.check(api_key(&req), BINARY_TX_METHOD) {
+                return Ok(HttpResponse::build(err.status_code()).body(err.to_string()));
+            }
+        }
+        let _permit = match &handler.admission {
+            Some(admission) => match admission.admit(BINARY_TX_METHOD).await {
+                Ok(permit) => Some(permit),
+                Err(err) => return Ok(HttpResponse::build(err.status_code()).body(err.to_string())),
+            },
+            None => None,
+        };
+        metrics::HTTP_BINARY_TX_REQUEST_COUNT.inc();
+        let signed_transaction = match SignedTransaction::try_from_slice(&body) {
+            Ok(signed_transaction) => signed_transaction,
+            Err(err) => {
+                return Ok(HttpResponse::BadRequest()
+                    .body(format!("Failed to decode transaction: {}", err)))
+            }
+        };
+        let request_data =
+            near_jsonrpc_primitives::types::transactions::RpcBroadcastTransactionRequest {
+                signed_transaction,
+            };
+        match handler.send_tx_async(request_data).await {
+            Ok(transaction_hash) => Ok(HttpResponse::Ok().json(&transaction_hash)),
+            Err(err) => Ok(HttpResponse::BadRequest().json(&RpcError::from(err))),
+        }
+    };
+    response.boxed()
+}
+
 fn status_handler(
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
@@ -1278,6 +1937,23 @@ fn health_handler(
     response.boxed()
 }
 
+fn liveness_handler() -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    futures::future::ready(Ok(HttpResponse::Ok().json(&json!({}))))
+}
+
+fn readiness_handler(
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        match handler.readiness().await {
+            Ok(failed) if failed.is_empty() => Ok(HttpResponse::Ok().json(&json!({}))),
+            Ok(failed) => Ok(HttpResponse::ServiceUnavailable().json(&json!({ "failed": failed }))),
+            Err(err) => Ok(HttpResponse::ServiceUnavailable().json(&json!({ "failed": [err] }))),
+        }
+    };
+    response.boxed()
+}
+
 fn network_info_handler(
     handler: web::Data<JsonRpcHandler>,
 ) -> impl Future<Output = Result<HttpResponse, HttpError>> {
@@ -1290,6 +1966,109 @@ fn network_info_handler(
     response.boxed()
 }
 
+fn debug_handler(
+    handler: web::Data<JsonRpcHandler>,
+    status: DebugStatus,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        match handler.debug_status(status).await {
+            Ok(value) => Ok(HttpResponse::Ok().json(&value)),
+            Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
+        }
+    };
+    response.boxed()
+}
+
+fn gas_throughput_handler(
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        match handler.gas_throughput().await {
+            Ok(value) => Ok(HttpResponse::Ok().json(&value)),
+            Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
+        }
+    };
+    response.boxed()
+}
+
+fn clock_skew_handler(
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        match handler.clock_skew_report().await {
+            Ok(value) => Ok(HttpResponse::Ok().json(&value)),
+            Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
+        }
+    };
+    response.boxed()
+}
+
+fn protocol_upgrade_handler(
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        match handler.protocol_upgrade_status().await {
+            Ok(value) => Ok(HttpResponse::Ok().json(&value)),
+            Err(_) => Ok(HttpResponse::ServiceUnavailable().finish()),
+        }
+    };
+    response.boxed()
+}
+
+#[derive(Deserialize)]
+struct AcknowledgeProtocolUpgradeRequest {
+    protocol_version: near_primitives::types::ProtocolVersion,
+}
+
+fn acknowledge_protocol_upgrade_handler(
+    req: HttpRequest,
+    request: web::Json<AcknowledgeProtocolUpgradeRequest>,
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        if !handler.api_keys.as_ref().map_or(false, |api_keys| api_keys.is_admin(api_key(&req))) {
+            return Ok(HttpResponse::Unauthorized().finish());
+        }
+        match handler.acknowledge_protocol_upgrade(request.protocol_version).await {
+            Ok(value) => Ok(HttpResponse::Ok().json(&value)),
+            Err(err @ RpcProtocolUpgradeError::UnsupportedProtocolVersion { .. }) => {
+                Ok(HttpResponse::BadRequest().json(&RpcError::from(err)))
+            }
+            Err(err) => Ok(HttpResponse::InternalServerError().json(&RpcError::from(err))),
+        }
+    };
+    response.boxed()
+}
+
+fn reload_peer_ranges_handler(
+    req: HttpRequest,
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        if !handler.api_keys.as_ref().map_or(false, |api_keys| api_keys.is_admin(api_key(&req))) {
+            return Ok(HttpResponse::Unauthorized().finish());
+        }
+        match handler.reload_peer_ranges().await {
+            Ok(()) => Ok(HttpResponse::Ok().finish()),
+            Err(err) => Ok(HttpResponse::InternalServerError().json(&RpcError::from(err))),
+        }
+    };
+    response.boxed()
+}
+
+fn api_keys_usage_handler(
+    req: HttpRequest,
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        match handler.api_keys.as_ref().and_then(|api_keys| api_keys.usage(api_key(&req))) {
+            Some(usage) => Ok(HttpResponse::Ok().json(&usage)),
+            None => Ok(HttpResponse::Unauthorized().finish()),
+        }
+    };
+    response.boxed()
+}
+
 pub async fn prometheus_handler() -> Result<HttpResponse, HttpError> {
     metrics::PROMETHEUS_REQUEST_COUNT.inc();
 
@@ -1313,6 +2092,7 @@ fn get_cors(cors_allowed_origins: &[String]) -> Cors {
     cors.allowed_methods(vec!["GET", "POST"])
         .allowed_headers(vec![http::header::AUTHORIZATION, http::header::ACCEPT])
         .allowed_header(http::header::CONTENT_TYPE)
+        .allowed_header(API_KEY_HEADER)
         .max_age(3600)
 }
 
@@ -1335,8 +2115,29 @@ pub fn start_http(
     #[cfg(feature = "test_features")] peer_manager_addr: Addr<PeerManagerActor>,
     #[cfg(feature = "test_features")] routing_table_addr: Addr<RoutingTableActor>,
 ) -> Vec<(&'static str, actix_web::dev::Server)> {
-    let RpcConfig { addr, prometheus_addr, cors_allowed_origins, polling_config, limits_config } =
-        config;
+    let RpcConfig {
+        addr,
+        prometheus_addr,
+        cors_allowed_origins,
+        polling_config,
+        limits_config,
+        batch,
+        api_keys,
+        pool_transactions,
+        tx_policy,
+        admission,
+        readiness,
+        websocket,
+    } = config;
+    let api_keys = api_keys.map(|config| Arc::new(ApiKeys::new(config)));
+    let tx_policy = tx_policy.map(|config| {
+        Arc::new(
+            TxPolicy::new(config)
+                .unwrap_or_else(|err| panic!("Failed to load the transaction policy: {}", err)),
+        )
+    });
+    let admission = admission.map(|config| Arc::new(Admission::new(config)));
+    let websocket_connections = Arc::new(AtomicUsize::new(0));
     let prometheus_addr = prometheus_addr.filter(|it| it != &addr);
     let cors_allowed_origins_clone = cors_allowed_origins.clone();
     info!(target:"network", "Starting http server at {}", addr);
@@ -1348,7 +2149,15 @@ pub fn start_http(
                 client_addr: client_addr.clone(),
                 view_client_addr: view_client_addr.clone(),
                 polling_config,
+                batch: batch.clone(),
                 genesis_config: genesis_config.clone(),
+                api_keys: api_keys.clone(),
+                pool_transactions_exposure: pool_transactions,
+                tx_policy: tx_policy.clone(),
+                admission: admission.clone(),
+                readiness: readiness.clone(),
+                websocket: websocket.clone(),
+                websocket_connections: websocket_connections.clone(),
                 #[cfg(feature = "test_features")]
                 peer_manager_addr: peer_manager_addr.clone(),
                 #[cfg(feature = "test_features")]
@@ -1356,7 +2165,9 @@ pub fn start_http(
             })
             .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
             .wrap(middleware::Logger::default())
+            .app_data(web::PayloadConfig::new(limits_config.json_payload_max_size))
             .service(web::resource("/").route(web::post().to(rpc_handler)))
+            .service(web::resource("/tx").route(web::post().to(binary_tx_handler)))
             .service(
                 web::resource("/status")
                     .route(web::get().to(status_handler))
@@ -1367,8 +2178,63 @@ pub fn start_http(
                     .route(web::get().to(health_handler))
                     .route(web::head().to(health_handler)),
             )
+            .service(
+                web::resource("/healthz")
+                    .route(web::get().to(liveness_handler))
+                    .route(web::head().to(liveness_handler)),
+            )
+            .service(
+                web::resource("/readyz")
+                    .route(web::get().to(readiness_handler))
+                    .route(web::head().to(readiness_handler)),
+            )
             .service(web::resource("/network_info").route(web::get().to(network_info_handler)))
+            .service(web::resource("/ws").route(web::get().to(websocket::ws_handler)))
             .service(web::resource("/metrics").route(web::get().to(prometheus_handler)))
+            // Stable JSON counterparts of the node debug pages.
+            .service(
+                web::scope("/debug/api")
+                    .service(web::resource("/sync_status").route(web::get().to(
+                        |handler: web::Data<JsonRpcHandler>| {
+                            debug_handler(handler, DebugStatus::SyncStatus)
+                        },
+                    )))
+                    .service(web::resource("/chain_processing_status").route(web::get().to(
+                        |handler: web::Data<JsonRpcHandler>| {
+                            debug_handler(handler, DebugStatus::ChainProcessingStatus)
+                        },
+                    )))
+                    .service(web::resource("/network_info").route(web::get().to(
+                        |handler: web::Data<JsonRpcHandler>| {
+                            debug_handler(handler, DebugStatus::NetworkInfo)
+                        },
+                    )))
+                    .service(web::resource("/validator_status").route(web::get().to(
+                        |handler: web::Data<JsonRpcHandler>| {
+                            debug_handler(handler, DebugStatus::ValidatorStatus)
+                        },
+                    )))
+                    .service(
+                        web::resource("/gas_throughput")
+                            .route(web::get().to(gas_throughput_handler)),
+                    )
+                    .service(web::resource("/clock_skew").route(web::get().to(clock_skew_handler)))
+                    .service(
+                        web::resource("/protocol_upgrade")
+                            .route(web::get().to(protocol_upgrade_handler)),
+                    ),
+            )
+            .service(
+                web::resource("/admin/api_keys/usage").route(web::get().to(api_keys_usage_handler)),
+            )
+            .service(
+                web::resource("/admin/protocol_upgrade/acknowledge")
+                    .route(web::post().to(acknowledge_protocol_upgrade_handler)),
+            )
+            .service(
+                web::resource("/admin/network/reload_peer_ranges")
+                    .route(web::post().to(reload_peer_ranges_handler)),
+            )
     })
     .bind(addr)
     .unwrap()
@@ -1,4 +1,4 @@
-use near_metrics::{HistogramVec, IntCounter, IntCounterVec};
+use near_metrics::{HistogramVec, IntCounter, IntCounterVec, IntGauge};
 use once_cell::sync::Lazy;
 
 pub static RPC_PROCESSING_TIME: Lazy<HistogramVec> = Lazy::new(|| {
@@ -32,6 +32,13 @@ pub static HTTP_RPC_REQUEST_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static HTTP_BINARY_TX_REQUEST_COUNT: Lazy<IntCounter> = Lazy::new(|| {
+    near_metrics::try_create_int_counter(
+        "near_http_binary_tx_requests_total",
+        "Total count of borsh serialized transactions submitted to the /tx endpoint",
+    )
+    .unwrap()
+});
 pub static HTTP_STATUS_REQUEST_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     near_metrics::try_create_int_counter(
         "near_http_status_requests_total",
@@ -47,3 +54,57 @@ pub static RPC_ERROR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static RPC_API_KEY_REQUEST_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    near_metrics::try_create_int_counter_vec(
+        "near_rpc_api_key_requests_total",
+        "Total count of JSON RPC requests served, by API key name and method",
+        &["key", "method"],
+    )
+    .unwrap()
+});
+pub static RPC_API_KEY_REJECTED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    near_metrics::try_create_int_counter_vec(
+        "near_rpc_api_key_rejected_total",
+        "Total count of JSON RPC requests rejected, by API key name and reason",
+        &["key", "reason"],
+    )
+    .unwrap()
+});
+pub static RPC_TX_POLICY_REJECTED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    near_metrics::try_create_int_counter_vec(
+        "near_rpc_tx_policy_rejected_total",
+        "Total count of submitted transactions rejected by the transaction policy, by hook",
+        &["hook"],
+    )
+    .unwrap()
+});
+pub static RPC_TX_POLICY_TAG_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    near_metrics::try_create_int_counter_vec(
+        "near_rpc_tx_policy_tags_total",
+        "Total count of submitted transactions tagged by the transaction policy, by tag",
+        &["tag"],
+    )
+    .unwrap()
+});
+pub static RPC_REQUESTS_SHED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    near_metrics::try_create_int_counter_vec(
+        "near_rpc_requests_shed_total",
+        "Total count of JSON RPC requests shed by the admission control, by method and reason",
+        &["method", "reason"],
+    )
+    .unwrap()
+});
+pub static RPC_WEBSOCKET_CONNECTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    near_metrics::try_create_int_gauge(
+        "near_rpc_websocket_connections",
+        "Number of open WebSocket connections of the JSON RPC",
+    )
+    .unwrap()
+});
+pub static RPC_WEBSOCKET_SUBSCRIPTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    near_metrics::try_create_int_gauge(
+        "near_rpc_websocket_subscriptions",
+        "Number of subscriptions of the WebSocket connections of the JSON RPC",
+    )
+    .unwrap()
+});
@@ -0,0 +1,89 @@
+//! Criteria of the readiness of the node to serve traffic, checked by `/readyz`.
+//!
+//! `/healthz` answers as long as the process serves HTTP requests, which is what a liveness probe
+//! restarting the node needs to know. `/readyz` answers `503 Service Unavailable` with the failed
+//! criteria while the node is too far behind the highest height of its peers, has too few peers
+//! or can't write to its store, so that load balancers stop routing requests to nodes which are
+//! syncing or stalled.
+
+use serde::{Deserialize, Serialize};
+
+use near_client::ReadinessResponse;
+use near_primitives::types::BlockHeightDelta;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RpcReadinessConfig {
+    /// How far the head may be behind the highest height of the peers.
+    pub max_blocks_behind: BlockHeightDelta,
+    /// Fewest peers the node may have.
+    pub min_peers: usize,
+    /// Whether a probe key is written to the store on every check.
+    pub check_store_writable: bool,
+}
+
+impl Default for RpcReadinessConfig {
+    fn default() -> Self {
+        Self { max_blocks_behind: 10, min_peers: 1, check_store_writable: true }
+    }
+}
+
+impl RpcReadinessConfig {
+    /// The criteria the node fails, none if it's ready.
+    pub(crate) fn failed_criteria(&self, readiness: &ReadinessResponse) -> Vec<String> {
+        let mut failed = vec![];
+        let blocks_behind = readiness
+            .highest_peer_height
+            .map_or(0, |height| height.saturating_sub(readiness.head_height));
+        if blocks_behind > self.max_blocks_behind {
+            failed.push(format!(
+                "Head at {} is {} blocks behind the peers, more than {}",
+                readiness.head_height, blocks_behind, self.max_blocks_behind
+            ));
+        }
+        if readiness.num_peers < self.min_peers {
+            failed.push(format!("{} peers, fewer than {}", readiness.num_peers, self.min_peers));
+        }
+        if readiness.store_writable == Some(false) {
+            failed.push("Store isn't writable".to_string());
+        }
+        failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_criteria() {
+        let config = RpcReadinessConfig::default();
+        let ready = ReadinessResponse {
+            head_height: 100,
+            highest_peer_height: Some(110),
+            num_peers: 1,
+            store_writable: Some(true),
+        };
+        assert!(config.failed_criteria(&ready).is_empty());
+        // Not behind peers which are behind.
+        let ahead = ReadinessResponse { highest_peer_height: Some(90), ..ready };
+        assert!(config.failed_criteria(&ahead).is_empty());
+
+        let failing = ReadinessResponse {
+            head_height: 100,
+            highest_peer_height: Some(111),
+            num_peers: 0,
+            store_writable: Some(false),
+        };
+        assert_eq!(
+            config.failed_criteria(&failing),
+            [
+                "Head at 100 is 11 blocks behind the peers, more than 10",
+                "0 peers, fewer than 1",
+                "Store isn't writable",
+            ]
+        );
+        let lenient = RpcReadinessConfig { max_blocks_behind: 20, min_peers: 0, ..config };
+        assert_eq!(lenient.failed_criteria(&failing), ["Store isn't writable"]);
+    }
+}
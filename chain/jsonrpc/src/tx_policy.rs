@@ -0,0 +1,360 @@
+//! Operator policy on the transactions submitted through the JSON RPC.
+//!
+//! The policy is local to the node and has nothing to do with consensus: it only decides
+//! which of the transactions sent to this node by its users get forwarded to the network.
+//! Each hook of the policy may reject a transaction or tag it. Tags are logged and counted in
+//! metrics. Besides a few built-in rules set in the config, the policy may be implemented by
+//! a WASM module, so that providers can enforce their own rules without forking this crate.
+//!
+//! A WASM policy module exports its `memory`, an `alloc(len: i32) -> i32` function returning
+//! where the input of `len` bytes may be written, and a `check_tx(ptr: i32, len: i32) -> i64`
+//! function. The input of `check_tx` is the JSON view of the signed transaction. It returns 0
+//! to accept the transaction as is, or the pointer to its verdict in the upper 32 bits and
+//! its length in the lower 32 bits. The verdict is a JSON object with either a `reject`
+//! reason or a list of `tags`. A module which traps, runs out of fuel or returns a malformed
+//! verdict rejects the transaction.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use wasmtime::{Engine, Instance, Memory, Module, Store};
+
+use near_primitives::serialize::option_u128_dec_format;
+use near_primitives::transaction::{Action, SignedTransaction};
+use near_primitives::types::{AccountId, Balance};
+use near_primitives::views::SignedTransactionView;
+
+use crate::metrics;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RpcTxPolicyConfig {
+    /// Whether transactions are only tagged with the hooks that would reject them, instead of
+    /// being rejected. Allows to try a policy out.
+    pub dry_run: bool,
+    /// Methods which function calls may not call.
+    pub denied_methods: Vec<String>,
+    /// Maximum total deposit attached to the actions of a transaction.
+    #[serde(with = "option_u128_dec_format", skip_serializing_if = "Option::is_none")]
+    pub max_deposit: Option<Balance>,
+    /// Signers allowed to submit transactions, all of them if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_signers: Option<Vec<AccountId>>,
+    /// WASM policy module run on every transaction after the rules above.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wasm_module: Option<PathBuf>,
+    /// Fuel given to each run of the WASM policy module, roughly one unit per instruction.
+    pub wasm_fuel: u64,
+}
+
+impl Default for RpcTxPolicyConfig {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            denied_methods: vec![],
+            max_deposit: None,
+            allowed_signers: None,
+            wasm_module: None,
+            wasm_fuel: 10_000_000,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum TxVerdict {
+    Accept { tags: Vec<String> },
+    Reject { reason: String },
+}
+
+/// Check run on every transaction submitted through the JSON RPC.
+pub(crate) trait TxHook: Send + Sync {
+    /// Name of the hook in logs and metrics.
+    fn name(&self) -> &str;
+
+    fn check(&self, tx: &SignedTransaction) -> TxVerdict;
+}
+
+#[derive(Debug)]
+pub(crate) struct TxPolicyError(anyhow::Error);
+
+impl fmt::Display for TxPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for TxPolicyError {}
+
+pub(crate) struct TxPolicy {
+    dry_run: bool,
+    hooks: Vec<Box<dyn TxHook>>,
+}
+
+impl TxPolicy {
+    pub fn new(config: RpcTxPolicyConfig) -> Result<Self, TxPolicyError> {
+        let mut hooks: Vec<Box<dyn TxHook>> = vec![Box::new(RulesHook::new(&config))];
+        if let Some(path) = &config.wasm_module {
+            hooks.push(Box::new(WasmHook::new(path, config.wasm_fuel).map_err(TxPolicyError)?));
+        }
+        Ok(Self { dry_run: config.dry_run, hooks })
+    }
+
+    /// Runs all hooks on the transaction. Returns the reason of the first rejection, if any.
+    pub fn check(&self, tx: &SignedTransaction) -> Result<(), String> {
+        let tx_hash = tx.get_hash();
+        let mut tags = vec![];
+        for hook in &self.hooks {
+            match hook.check(tx) {
+                TxVerdict::Accept { tags: hook_tags } => tags.extend(hook_tags),
+                TxVerdict::Reject { reason } => {
+                    metrics::RPC_TX_POLICY_REJECTED_COUNT.with_label_values(&[hook.name()]).inc();
+                    if !self.dry_run {
+                        info!(target: "jsonrpc", "Transaction {} rejected by {}: {}", tx_hash, hook.name(), reason);
+                        return Err(reason);
+                    }
+                    info!(target: "jsonrpc", "Transaction {} would be rejected by {}: {}", tx_hash, hook.name(), reason);
+                    tags.push(format!("rejected_by_{}", hook.name()));
+                }
+            }
+        }
+        if !tags.is_empty() {
+            for tag in &tags {
+                metrics::RPC_TX_POLICY_TAG_COUNT.with_label_values(&[tag]).inc();
+            }
+            info!(target: "jsonrpc", "Transaction {} tagged {:?}", tx_hash, tags);
+        }
+        Ok(())
+    }
+}
+
+/// The rules set in the config.
+struct RulesHook {
+    denied_methods: HashSet<String>,
+    max_deposit: Option<Balance>,
+    allowed_signers: Option<HashSet<AccountId>>,
+}
+
+impl RulesHook {
+    fn new(config: &RpcTxPolicyConfig) -> Self {
+        Self {
+            denied_methods: config.denied_methods.iter().cloned().collect(),
+            max_deposit: config.max_deposit,
+            allowed_signers: config
+                .allowed_signers
+                .as_ref()
+                .map(|signers| signers.iter().cloned().collect()),
+        }
+    }
+}
+
+impl TxHook for RulesHook {
+    fn name(&self) -> &str {
+        "rules"
+    }
+
+    fn check(&self, tx: &SignedTransaction) -> TxVerdict {
+        let tx = &tx.transaction;
+        if let Some(allowed_signers) = &self.allowed_signers {
+            if !allowed_signers.contains(&tx.signer_id) {
+                return TxVerdict::Reject {
+                    reason: format!("Signer {} is not allowed", tx.signer_id),
+                };
+            }
+        }
+        for action in &tx.actions {
+            if let Action::FunctionCall(function_call) = action {
+                if self.denied_methods.contains(&function_call.method_name) {
+                    return TxVerdict::Reject {
+                        reason: format!("Method {} is denied", function_call.method_name),
+                    };
+                }
+            }
+        }
+        if let Some(max_deposit) = self.max_deposit {
+            let deposit = tx.actions.iter().fold(0, |total: Balance, action| {
+                total.saturating_add(action.get_deposit_balance())
+            });
+            if deposit > max_deposit {
+                return TxVerdict::Reject {
+                    reason: format!("Deposit {} exceeds the maximum of {}", deposit, max_deposit),
+                };
+            }
+        }
+        TxVerdict::Accept { tags: vec![] }
+    }
+}
+
+#[derive(Deserialize)]
+struct WasmVerdict {
+    #[serde(default)]
+    reject: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Policy implemented by a WASM module. The module is compiled once, and instantiated anew
+/// for each transaction so that no state is kept between transactions.
+struct WasmHook {
+    engine: Engine,
+    module: Module,
+    fuel: u64,
+}
+
+impl WasmHook {
+    fn new(path: &Path, fuel: u64) -> anyhow::Result<Self> {
+        let mut config = wasmtime::Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::from_file(&engine, path)
+            .with_context(|| format!("Failed to load the WASM policy {}", path.display()))?;
+        Ok(Self { engine, module, fuel })
+    }
+
+    fn run(&self, tx: &SignedTransaction) -> anyhow::Result<TxVerdict> {
+        let store = Store::new(&self.engine);
+        store.add_fuel(self.fuel)?;
+        let instance = Instance::new(&store, &self.module, &[])?;
+        let memory = instance.get_memory("memory").ok_or_else(|| anyhow!("memory not exported"))?;
+        let alloc = instance.get_func("alloc").ok_or_else(|| anyhow!("alloc not exported"))?;
+        let alloc = alloc.typed::<i32, i32>()?;
+        let check_tx =
+            instance.get_func("check_tx").ok_or_else(|| anyhow!("check_tx not exported"))?;
+        let check_tx = check_tx.typed::<(i32, i32), i64>()?;
+
+        let input = serde_json::to_vec(&SignedTransactionView::from(tx.clone()))?;
+        let ptr = alloc.call(input.len() as i32)?;
+        write_memory(&memory, ptr as u32 as usize, &input)?;
+        let output = check_tx.call((ptr, input.len() as i32))?;
+        if output == 0 {
+            return Ok(TxVerdict::Accept { tags: vec![] });
+        }
+        let output = read_memory(&memory, (output as u64 >> 32) as usize, output as u32 as usize)?;
+        let verdict: WasmVerdict = serde_json::from_slice(&output)?;
+        Ok(match verdict.reject {
+            Some(reason) => TxVerdict::Reject { reason },
+            None => TxVerdict::Accept { tags: verdict.tags },
+        })
+    }
+}
+
+impl TxHook for WasmHook {
+    fn name(&self) -> &str {
+        "wasm"
+    }
+
+    fn check(&self, tx: &SignedTransaction) -> TxVerdict {
+        self.run(tx).unwrap_or_else(|err| {
+            warn!(target: "jsonrpc", "WASM policy failed on transaction {}: {:#}", tx.get_hash(), err);
+            TxVerdict::Reject { reason: "Transaction policy failed".to_string() }
+        })
+    }
+}
+
+fn write_memory(memory: &Memory, offset: usize, data: &[u8]) -> anyhow::Result<()> {
+    // Safe because the instance doesn't run while its memory is accessed.
+    let memory = unsafe { memory.data_unchecked_mut() };
+    offset
+        .checked_add(data.len())
+        .and_then(|end| memory.get_mut(offset..end))
+        .ok_or_else(|| anyhow!("input written out of memory bounds"))?
+        .copy_from_slice(data);
+    Ok(())
+}
+
+fn read_memory(memory: &Memory, offset: usize, len: usize) -> anyhow::Result<Vec<u8>> {
+    // Safe because the instance doesn't run while its memory is accessed.
+    let memory = unsafe { memory.data_unchecked() };
+    offset
+        .checked_add(len)
+        .and_then(|end| memory.get(offset..end))
+        .map(|data| data.to_vec())
+        .ok_or_else(|| anyhow!("verdict read out of memory bounds"))
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::hash::CryptoHash;
+
+    use super::*;
+
+    fn tx(signer: &str, method: &str, deposit: Balance) -> SignedTransaction {
+        let signer_id: AccountId = signer.parse().unwrap();
+        let signer = InMemorySigner::from_seed(signer_id.clone(), KeyType::ED25519, signer);
+        SignedTransaction::call(
+            1,
+            signer_id,
+            "contract".parse().unwrap(),
+            &signer,
+            deposit,
+            method.to_string(),
+            vec![],
+            100,
+            CryptoHash::default(),
+        )
+    }
+
+    fn wasm_hook(wat: &str) -> WasmHook {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("policy.wasm");
+        std::fs::write(&path, wat::parse_str(wat).unwrap()).unwrap();
+        WasmHook::new(&path, 1000).unwrap()
+    }
+
+    #[test]
+    fn test_rules() {
+        let config = RpcTxPolicyConfig {
+            denied_methods: vec!["drain".to_string()],
+            max_deposit: Some(1000),
+            allowed_signers: Some(vec!["alice".parse().unwrap(), "bob".parse().unwrap()]),
+            ..Default::default()
+        };
+        let policy = TxPolicy::new(config.clone()).unwrap();
+        assert_eq!(policy.check(&tx("alice", "ping", 1000)), Ok(()));
+        assert_eq!(
+            policy.check(&tx("carol", "ping", 0)),
+            Err("Signer carol is not allowed".to_string())
+        );
+        assert_eq!(policy.check(&tx("bob", "drain", 0)), Err("Method drain is denied".to_string()));
+        assert_eq!(
+            policy.check(&tx("bob", "ping", 1001)),
+            Err("Deposit 1001 exceeds the maximum of 1000".to_string())
+        );
+
+        let policy = TxPolicy::new(RpcTxPolicyConfig { dry_run: true, ..config }).unwrap();
+        assert_eq!(policy.check(&tx("bob", "drain", 0)), Ok(()));
+    }
+
+    #[test]
+    fn test_wasm_policy() {
+        let hook = wasm_hook(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 16) "{\"tags\":[\"audited\"]}")
+                (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "check_tx") (param i32 i32) (result i64)
+                    (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 20))))"#,
+        );
+        assert_eq!(
+            hook.check(&tx("alice", "ping", 0)),
+            TxVerdict::Accept { tags: vec!["audited".to_string()] }
+        );
+
+        let hook = wasm_hook(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "check_tx") (param i32 i32) (result i64)
+                    (loop (br 0))
+                    (i64.const 0)))"#,
+        );
+        assert_eq!(
+            hook.check(&tx("alice", "ping", 0)),
+            TxVerdict::Reject { reason: "Transaction policy failed".to_string() }
+        );
+    }
+}
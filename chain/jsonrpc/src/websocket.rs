@@ -0,0 +1,497 @@
+//! Subscriptions over a WebSocket at `/ws`, so that explorers and bots get the new final blocks,
+//! the outcomes of their transactions and the changes of the accounts they follow pushed as they
+//! happen, instead of calling `block` in a tight loop.
+//!
+//! The messages are JSON objects in text frames. A request has an `id`, echoed in its response,
+//! a `method`, `subscribe` or `unsubscribe`, and `params`:
+//!
+//! - `{"type": "final_blocks"}` notifies every new final block, like `block` with the `final`
+//!   finality does.
+//! - `{"type": "tx_status", "tx_hash": ..., "sender_account_id": ...}` notifies the outcome of the
+//!   transaction once it's executed, like `tx` does, and ends.
+//! - `{"type": "changes", "changes_type": ..., ...}`, with the filters of `EXPERIMENTAL_changes`,
+//!   notifies the changes of every new final block which has some, like `EXPERIMENTAL_changes`.
+//!
+//! `subscribe` answers the id of the subscription, which `unsubscribe` takes as `subscription`.
+//! Notifications have the `subscription` instead of an `id`, and a `result` or an `error`. The
+//! subscriptions of a connection are checked every `polling_interval`.
+//!
+//! The API key of the connection is checked at the handshake, and each `subscribe` is checked
+//! and admitted like a request to the method serving the same data: `block`, `tx` or
+//! `EXPERIMENTAL_changes`. A connection whose peer doesn't read its notifications fast enough is
+//! closed once `max_queued_messages` are waiting.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::Ordering;
+
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws;
+use actix_web::http::{header, StatusCode};
+use actix_web::{web, Error as HttpError, HttpRequest, HttpResponse};
+use bytes::{Bytes, BytesMut};
+use futures::channel::mpsc;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use near_client::{GetBlock, GetStateChanges, TxStatus, TxStatusError};
+use near_jsonrpc_primitives::errors::{RpcError, RpcParseError};
+use near_jsonrpc_primitives::types::changes::{
+    RpcStateChangesError, RpcStateChangesInBlockResponse,
+};
+use near_jsonrpc_primitives::types::transactions::RpcTransactionError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockHeight, BlockId, BlockReference, Finality};
+use near_primitives::views::{BlockView, StateChangesRequestView};
+
+use crate::{api_key, metrics, JsonRpcHandler};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct RpcWebSocketConfig {
+    /// Connections served at a time, the others are refused.
+    pub max_connections: usize,
+    /// Subscriptions a connection may have at a time.
+    pub max_subscriptions: usize,
+    /// Messages waiting to be sent on a connection, which is closed once its peer falls further
+    /// behind.
+    pub max_queued_messages: usize,
+}
+
+impl Default for RpcWebSocketConfig {
+    fn default() -> Self {
+        Self { max_connections: 1024, max_subscriptions: 16, max_queued_messages: 1024 }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Subscription {
+    FinalBlocks,
+    TxStatus {
+        tx_hash: CryptoHash,
+        sender_account_id: AccountId,
+    },
+    Changes {
+        #[serde(flatten)]
+        state_changes_request: StateChangesRequestView,
+    },
+}
+
+impl Subscription {
+    fn follows_blocks(&self) -> bool {
+        match self {
+            Subscription::FinalBlocks | Subscription::Changes { .. } => true,
+            Subscription::TxStatus { .. } => false,
+        }
+    }
+
+    /// The method serving the same data, whose API key restrictions and admission apply.
+    fn method(&self) -> &'static str {
+        match self {
+            Subscription::FinalBlocks => "block",
+            Subscription::TxStatus { .. } => "tx",
+            Subscription::Changes { .. } => "EXPERIMENTAL_changes",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Request {
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct Unsubscribe {
+    subscription: u64,
+}
+
+/// Rejection of a `subscribe` or an `unsubscribe`, answered as the handler error.
+#[derive(Serialize, Debug)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+enum SubscriptionError {
+    TooManySubscriptions { max_subscriptions: usize },
+    UnknownSubscription { subscription: u64 },
+}
+
+impl fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubscriptionError::TooManySubscriptions { max_subscriptions } => {
+                write!(f, "At most {} subscriptions per connection", max_subscriptions)
+            }
+            SubscriptionError::UnknownSubscription { subscription } => {
+                write!(f, "No subscription {}", subscription)
+            }
+        }
+    }
+}
+
+impl From<SubscriptionError> for RpcError {
+    fn from(error: SubscriptionError) -> Self {
+        let error_data = Some(Value::String(error.to_string()));
+        match serde_json::to_value(error) {
+            Ok(error_struct) => RpcError::new_internal_or_handler_error(error_data, error_struct),
+            Err(err) => RpcError::new_internal_error(
+                None,
+                format!("Failed to serialize SubscriptionError: {:?}", err),
+            ),
+        }
+    }
+}
+
+fn to_value(value: impl Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|err| RpcError::serialization_error(err.to_string()))
+}
+
+pub(crate) async fn ws_handler(
+    req: HttpRequest,
+    payload: web::Payload,
+    handler: web::Data<JsonRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    ws::verify_handshake(req.head())?;
+    if let Some(api_keys) = &handler.api_keys {
+        if let Err(err) = api_keys.authenticate(api_key(&req)) {
+            return Ok(HttpResponse::build(err.status_code()).body(err.to_string()));
+        }
+    }
+    let max_connections = handler.websocket.max_connections;
+    if handler.websocket_connections.fetch_add(1, Ordering::SeqCst) >= max_connections {
+        handler.websocket_connections.fetch_sub(1, Ordering::SeqCst);
+        return Ok(HttpResponse::TooManyRequests()
+            .body(format!("At most {} WebSocket connections", max_connections)));
+    }
+    let key = req.headers().get(header::SEC_WEBSOCKET_KEY).map_or(&[][..], |key| key.as_bytes());
+    let accept = ws::hash_key(key);
+    let (sender, receiver) = mpsc::channel(handler.websocket.max_queued_messages);
+    let api_key = api_key(&req).map(str::to_string);
+    actix::spawn(Connection::new(handler, api_key, sender).run(payload));
+    Ok(HttpResponse::build(StatusCode::SWITCHING_PROTOCOLS)
+        .upgrade("websocket")
+        .insert_header((header::TRANSFER_ENCODING, "chunked"))
+        .insert_header((header::SEC_WEBSOCKET_ACCEPT, &accept[..]))
+        .streaming(receiver))
+}
+
+struct Connection {
+    handler: web::Data<JsonRpcHandler>,
+    /// API key of the handshake, which the subscriptions are checked against.
+    api_key: Option<String>,
+    /// Encoded frames sent to the peer, closed once the response is dropped.
+    sender: mpsc::Sender<Result<Bytes, HttpError>>,
+    codec: ws::Codec,
+    next_subscription_id: u64,
+    subscriptions: BTreeMap<u64, Subscription>,
+    /// Height of the last final block notified to the subscriptions following the blocks.
+    last_final_height: Option<BlockHeight>,
+}
+
+impl Connection {
+    fn new(
+        handler: web::Data<JsonRpcHandler>,
+        api_key: Option<String>,
+        sender: mpsc::Sender<Result<Bytes, HttpError>>,
+    ) -> Self {
+        Self {
+            handler,
+            api_key,
+            sender,
+            codec: ws::Codec::new(),
+            next_subscription_id: 0,
+            subscriptions: BTreeMap::new(),
+            last_final_height: None,
+        }
+    }
+
+    async fn run(mut self, mut payload: web::Payload) {
+        metrics::RPC_WEBSOCKET_CONNECTIONS.inc();
+        let mut buffer = BytesMut::new();
+        let mut ticks = tokio::time::interval(self.handler.polling_config.polling_interval);
+        loop {
+            let open = tokio::select! {
+                chunk = payload.next() => match chunk {
+                    Some(Ok(chunk)) => {
+                        buffer.extend_from_slice(&chunk);
+                        self.handle_frames(&mut buffer).await
+                    }
+                    _ => false,
+                },
+                _ = ticks.tick() => self.poll().await,
+            };
+            if !open {
+                break;
+            }
+        }
+        metrics::RPC_WEBSOCKET_SUBSCRIPTIONS.sub(self.subscriptions.len() as i64);
+        metrics::RPC_WEBSOCKET_CONNECTIONS.dec();
+        self.handler.websocket_connections.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Sends a message, false once the peer is gone or too far behind.
+    fn send_message(&mut self, message: ws::Message) -> bool {
+        let mut bytes = BytesMut::new();
+        if let Err(err) = self.codec.encode(message, &mut bytes) {
+            debug!(target: "jsonrpc", "Failed to encode WebSocket frame: {}", err);
+            return false;
+        }
+        match self.sender.try_send(Ok(bytes.freeze())) {
+            Ok(()) => true,
+            Err(err) => {
+                if err.is_full() {
+                    debug!(target: "jsonrpc", "Closing a WebSocket connection whose peer fell behind");
+                }
+                false
+            }
+        }
+    }
+
+    fn send(&mut self, value: Value) -> bool {
+        self.send_message(ws::Message::Text(value.to_string().into()))
+    }
+
+    fn notify(&mut self, subscription: u64, result: Result<Value, RpcError>) -> bool {
+        self.send(match result {
+            Ok(result) => {
+                json!({ "jsonrpc": "2.0", "subscription": subscription, "result": result })
+            }
+            Err(error) => json!({ "jsonrpc": "2.0", "subscription": subscription, "error": error }),
+        })
+    }
+
+    /// Handles the complete frames received, false once the connection is closed.
+    async fn handle_frames(&mut self, buffer: &mut BytesMut) -> bool {
+        loop {
+            let frame = match self.codec.decode(buffer) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return true,
+                Err(err) => {
+                    debug!(target: "jsonrpc", "Invalid WebSocket frame: {}", err);
+                    return false;
+                }
+            };
+            let open = match frame {
+                ws::Frame::Text(text) => {
+                    let response = self.handle_request(&text).await;
+                    self.send(response)
+                }
+                ws::Frame::Binary(_) | ws::Frame::Continuation(_) => self.send(json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": RpcError::parse_error("Only text frames are supported".to_string()),
+                })),
+                ws::Frame::Ping(message) => self.send_message(ws::Message::Pong(message)),
+                ws::Frame::Pong(_) => true,
+                ws::Frame::Close(reason) => {
+                    self.send_message(ws::Message::Close(reason));
+                    false
+                }
+            };
+            if !open {
+                return false;
+            }
+        }
+    }
+
+    async fn handle_request(&mut self, text: &[u8]) -> Value {
+        let request: Request = match serde_json::from_slice(text) {
+            Ok(request) => request,
+            Err(err) => {
+                let error = RpcError::parse_error(err.to_string());
+                return json!({ "jsonrpc": "2.0", "id": null, "error": error });
+            }
+        };
+        let result = match request.method.as_str() {
+            "subscribe" => self.subscribe(request.params).await,
+            "unsubscribe" => self.unsubscribe(request.params),
+            method => Err(RpcError::method_not_found(method.to_string())),
+        };
+        match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": request.id, "result": result }),
+            Err(error) => json!({ "jsonrpc": "2.0", "id": request.id, "error": error }),
+        }
+    }
+
+    async fn subscribe(&mut self, params: Value) -> Result<Value, RpcError> {
+        let subscription: Subscription = serde_json::from_value(params)
+            .map_err(|err| RpcParseError(format!("Failed parsing args: {}", err)))?;
+        let max_subscriptions = self.handler.websocket.max_subscriptions;
+        if self.subscriptions.len() >= max_subscriptions {
+            return Err(SubscriptionError::TooManySubscriptions { max_subscriptions }.into());
+        }
+        let method = subscription.method();
+        if let Some(api_keys) = &self.handler.api_keys {
+            api_keys.check(self.api_key.as_deref(), method)?;
+        }
+        if let Some(admission) = &self.handler.admission {
+            // Only the subscription is admitted, its notifications aren't requests.
+            admission.admit(method).await?;
+        }
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.subscriptions.insert(id, subscription);
+        metrics::RPC_WEBSOCKET_SUBSCRIPTIONS.inc();
+        Ok(json!({ "subscription": id }))
+    }
+
+    fn unsubscribe(&mut self, params: Value) -> Result<Value, RpcError> {
+        let Unsubscribe { subscription } = serde_json::from_value(params)
+            .map_err(|err| RpcParseError(format!("Failed parsing args: {}", err)))?;
+        self.remove(subscription)
+            .then(|| json!(true))
+            .ok_or_else(|| SubscriptionError::UnknownSubscription { subscription }.into())
+    }
+
+    fn remove(&mut self, subscription: u64) -> bool {
+        let removed = self.subscriptions.remove(&subscription).is_some();
+        if removed {
+            metrics::RPC_WEBSOCKET_SUBSCRIPTIONS.dec();
+        }
+        removed
+    }
+
+    /// The final blocks since the last one notified, only the latest one at first.
+    async fn new_final_blocks(&mut self) -> Vec<BlockView> {
+        let view_client_addr = &self.handler.view_client_addr;
+        let final_block = match view_client_addr
+            .send(GetBlock(BlockReference::Finality(Finality::Final)))
+            .await
+        {
+            Ok(Ok(block)) => block,
+            _ => return vec![],
+        };
+        let final_height = final_block.header.height;
+        let first_height = self.last_final_height.map_or(final_height, |height| height + 1);
+        let mut blocks = vec![];
+        for height in first_height..final_height {
+            // No block is produced at some heights.
+            let reference = BlockReference::BlockId(BlockId::Height(height));
+            if let Ok(Ok(block)) = view_client_addr.send(GetBlock(reference)).await {
+                blocks.push(block);
+            }
+        }
+        if first_height <= final_height {
+            blocks.push(final_block);
+        }
+        self.last_final_height = Some(final_height);
+        blocks
+    }
+
+    /// Notifies what's new to the subscriptions, false once the peer is gone.
+    async fn poll(&mut self) -> bool {
+        let blocks = if self.subscriptions.values().any(Subscription::follows_blocks) {
+            self.new_final_blocks().await
+        } else {
+            self.last_final_height = None;
+            vec![]
+        };
+        let subscriptions: Vec<_> = self
+            .subscriptions
+            .iter()
+            .map(|(id, subscription)| (*id, subscription.clone()))
+            .collect();
+        for (id, subscription) in subscriptions {
+            let open = match subscription {
+                Subscription::FinalBlocks => {
+                    blocks.iter().all(|block| self.notify(id, to_value(block)))
+                }
+                Subscription::Changes { state_changes_request } => {
+                    self.poll_changes(id, &blocks, state_changes_request).await
+                }
+                Subscription::TxStatus { tx_hash, sender_account_id } => {
+                    self.poll_tx_status(id, tx_hash, sender_account_id).await
+                }
+            };
+            if !open {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn poll_changes(
+        &mut self,
+        id: u64,
+        blocks: &[BlockView],
+        state_changes_request: StateChangesRequestView,
+    ) -> bool {
+        for block in blocks {
+            let block_hash = block.header.hash;
+            let result = match self
+                .handler
+                .view_client_addr
+                .send(GetStateChanges {
+                    block_hash,
+                    state_changes_request: state_changes_request.clone(),
+                })
+                .await
+            {
+                Ok(Ok(changes)) if changes.is_empty() => continue,
+                Ok(Ok(changes)) => to_value(RpcStateChangesInBlockResponse { block_hash, changes }),
+                Ok(Err(err)) => Err(RpcStateChangesError::from(err).into()),
+                Err(err) => Err(RpcError::from(err)),
+            };
+            if !self.notify(id, result) {
+                return false;
+            }
+        }
+        true
+    }
+
+    async fn poll_tx_status(
+        &mut self,
+        id: u64,
+        tx_hash: CryptoHash,
+        signer_account_id: AccountId,
+    ) -> bool {
+        let result = match self
+            .handler
+            .view_client_addr
+            .send(TxStatus { tx_hash, signer_account_id, fetch_receipt: false })
+            .await
+        {
+            Ok(Ok(Some(outcome))) => to_value(outcome),
+            // The transaction may not have reached the node yet.
+            Ok(Ok(None)) | Ok(Err(TxStatusError::MissingTransaction(_))) | Err(_) => return true,
+            Ok(Err(err)) => Err(RpcTransactionError::from(err).into()),
+        };
+        self.remove(id);
+        self.notify(id, result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subscription() {
+        let parse = |params: Value| serde_json::from_value::<Subscription>(params);
+        assert!(matches!(parse(json!({"type": "final_blocks"})), Ok(Subscription::FinalBlocks)));
+        let tx_hash = CryptoHash::default();
+        match parse(json!({"type": "tx_status", "tx_hash": tx_hash, "sender_account_id": "alice"}))
+        {
+            Ok(Subscription::TxStatus { tx_hash: hash, sender_account_id }) => {
+                assert_eq!(hash, tx_hash);
+                assert_eq!(sender_account_id.as_ref(), "alice");
+            }
+            other => panic!("{:?}", other),
+        }
+        match parse(json!({
+            "type": "changes",
+            "changes_type": "account_changes",
+            "account_ids": ["alice", "bob"],
+        })) {
+            Ok(Subscription::Changes {
+                state_changes_request: StateChangesRequestView::AccountChanges { account_ids },
+            }) => assert_eq!(account_ids.len(), 2),
+            other => panic!("{:?}", other),
+        }
+        assert!(parse(json!({"type": "changes"})).is_err());
+        assert!(parse(json!({"type": "blocks"})).is_err());
+    }
+}
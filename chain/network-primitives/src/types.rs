@@ -23,12 +23,14 @@ use near_primitives::types::{AccountId, BlockHeight, BlockReference, EpochId, Sh
 use near_primitives::utils::{from_timestamp, to_timestamp};
 use near_primitives::views::{FinalExecutionOutcomeView, QueryRequest, QueryResponse};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::fmt::{Debug, Error, Formatter};
 use std::hash::Hash;
-use std::net::{AddrParseError, IpAddr, SocketAddr};
+use std::net::{AddrParseError, IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 use strum::AsStaticStr;
 use tokio::net::TcpStream;
@@ -126,6 +128,47 @@ impl TryFrom<&str> for PeerInfo {
     }
 }
 
+/// Boot node given by host name rather than IP, as `peer_id@host:port`. The name is resolved
+/// every time the boot node is dialed, by the proxy if there is one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BootNodeHost {
+    pub id: PeerId,
+    pub host: String,
+    pub port: u16,
+}
+
+impl FromStr for BootNodeHost {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Invalid boot node host format: {:?}", s),
+            )
+        };
+        let (id, addr) = s.split_once('@').ok_or_else(invalid)?;
+        if addr.parse::<SocketAddr>().is_ok() {
+            return Err(invalid().into());
+        }
+        let (host, port) = addr.rsplit_once(':').ok_or_else(invalid)?;
+        if host.is_empty() || host.parse::<IpAddr>().is_ok() {
+            return Err(invalid().into());
+        }
+        Ok(BootNodeHost {
+            id: PeerId::new(id.parse()?),
+            host: host.to_string(),
+            port: port.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl fmt::Display for BootNodeHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}:{}", self.id, self.host, self.port)
+    }
+}
+
 /// Peer chain information.
 /// TODO: Remove in next version
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
@@ -494,7 +537,23 @@ pub struct NetworkConfig {
     pub secret_key: SecretKey,
     pub account_id: Option<AccountId>,
     pub addr: Option<SocketAddr>,
+    /// Address advertised to the peers, `addr` if `None`.
+    /// Peers take the IP of a connection from the connection itself, so on a multi-homed host the
+    /// outbound connections must be made from the advertised IP, see `outbound_source_ip`.
+    pub external_addr: Option<SocketAddr>,
+    /// IP which the outbound connections are made from, chosen by the OS if `None`.
+    pub outbound_source_ip: Option<IpAddr>,
+    /// Source IPs of the outbound connections to the matching peers, by IP:Port or IP.
+    pub outbound_source_ip_overrides: Vec<(PatternAddr, IpAddr)>,
     pub boot_nodes: Vec<PeerInfo>,
+    pub boot_node_hosts: Vec<BootNodeHost>,
+    /// SOCKS5 proxy which every outbound connection goes through, e.g. Tor.
+    pub proxy: Option<SocketAddr>,
+    /// Ask the router to forward the listening port with NAT-PMP, and advertise the port it
+    /// forwards, unless `external_addr` is set.
+    pub port_mapping: bool,
+    /// Router asked for the port mapping, the gateway of the default route if `None`.
+    pub port_mapping_gateway: Option<Ipv4Addr>,
     pub handshake_timeout: Duration,
     pub reconnect_delay: Duration,
     pub bootstrap_peers_period: Duration,
@@ -539,6 +598,40 @@ pub struct NetworkConfig {
     /// Peers on blacklist by IP:Port.
     /// Nodes will not accept or try to establish connection to such peers.
     pub blacklist: HashMap<IpAddr, BlockedPorts>,
+    /// File listing the only peers allowed to connect, for private networks.
+    /// Peers which are not listed are neither connected to nor advertised to other peers.
+    pub peer_allowlist_file: Option<PathBuf>,
+    /// Ranges of IPs the peers are accepted from and dialed at.
+    pub peer_ranges: PeerRanges,
+    /// Reads `peer_ranges` again, from the config file, when the node receives SIGHUP or is asked
+    /// to with `PeerManagerMessageRequest::ReloadPeerRanges`.
+    pub peer_ranges_loader: Option<PeerRangesLoader>,
+    /// CSV database of the AS numbers of the IP ranges, see `max_peers_fraction_per_asn`.
+    pub asn_database_file: Option<PathBuf>,
+    /// Highest fraction of `max_num_peers` which may be peers of the same autonomous system, if
+    /// there is an `asn_database_file`.
+    pub max_peers_fraction_per_asn: f64,
+    /// Fraction of the received messages processed in a span, from 0 (none) to 1 (all).
+    pub message_span_sample_rate: f64,
+    /// Bytes per second which may be sent to all the peers together, unlimited if `None`.
+    pub max_outbound_bytes_per_sec: Option<u64>,
+    /// Bytes per second which may be sent to every peer, unlimited if `None`.
+    pub max_outbound_bytes_per_sec_per_peer: Option<u64>,
+    /// Difficulty, in leading zero bits, of the puzzle the inbound peers solve before their
+    /// handshake is processed. No puzzle if 0.
+    pub handshake_proof_of_work_difficulty: u8,
+    /// Size in bytes from which the messages sent to the peers accepting it are compressed, not
+    /// compressed if `None`.
+    pub compression_threshold_bytes: Option<usize>,
+    /// Time for the score of a misbehaving peer to decay by half.
+    pub peer_score_half_life: Duration,
+    /// Score above which a peer isn't used to sync and is disconnected first.
+    pub peer_score_demote_threshold: f64,
+    /// Score above which a peer is banned for `ban_window`.
+    pub peer_score_ban_threshold: f64,
+    /// Bytes per second above which receiving from a peer counts against its score, not checked
+    /// if `None`.
+    pub peer_max_received_bytes_per_sec: Option<u64>,
     /// Flag to disable outbound connections. When this flag is active, nodes will not try to
     /// establish connection with other nodes, but will accept incoming connection if other requirements
     /// are satisfied.
@@ -558,7 +651,14 @@ impl NetworkConfig {
             secret_key,
             account_id: Some(seed.parse().unwrap()),
             addr: Some(format!("0.0.0.0:{}", port).parse().unwrap()),
+            external_addr: None,
+            outbound_source_ip: None,
+            outbound_source_ip_overrides: vec![],
             boot_nodes: vec![],
+            boot_node_hosts: vec![],
+            proxy: None,
+            port_mapping: false,
+            port_mapping_gateway: None,
             handshake_timeout: Duration::from_secs(60),
             reconnect_delay: Duration::from_secs(60),
             bootstrap_peers_period: Duration::from_millis(100),
@@ -579,11 +679,39 @@ impl NetworkConfig {
             highest_peer_horizon: 5,
             push_info_period: Duration::from_millis(100),
             blacklist: HashMap::new(),
+            peer_allowlist_file: None,
+            peer_ranges: PeerRanges::default(),
+            peer_ranges_loader: None,
+            asn_database_file: None,
+            max_peers_fraction_per_asn: 0.5,
+            message_span_sample_rate: 0.0,
+            max_outbound_bytes_per_sec: None,
+            max_outbound_bytes_per_sec_per_peer: None,
+            handshake_proof_of_work_difficulty: 0,
+            compression_threshold_bytes: None,
+            peer_score_half_life: Duration::from_secs(600),
+            peer_score_demote_threshold: 50.0,
+            peer_score_ban_threshold: 100.0,
+            peer_max_received_bytes_per_sec: None,
             outbound_disabled: false,
             archive: false,
         }
     }
 
+    /// Returns the IP to connect to `peer_addr` from: the override of its IP:Port, else the one of
+    /// its IP, else `outbound_source_ip`. The OS chooses the source IP if the IP found is of another
+    /// version than the peer's.
+    pub fn outbound_source_ip_for(&self, peer_addr: &SocketAddr) -> Option<IpAddr> {
+        let overrides = &self.outbound_source_ip_overrides;
+        overrides
+            .iter()
+            .find(|(pattern, _)| matches!(pattern, PatternAddr::IpPort(addr) if addr == peer_addr))
+            .or_else(|| overrides.iter().find(|(pattern, _)| pattern.contains(peer_addr)))
+            .map(|(_, source_ip)| *source_ip)
+            .or(self.outbound_source_ip)
+            .filter(|source_ip| source_ip.is_ipv4() == peer_addr.is_ipv4())
+    }
+
     pub fn verify(&self) {
         if self.ideal_connections_lo + 1 >= self.ideal_connections_hi {
             error!(target: "network",
@@ -602,6 +730,58 @@ impl NetworkConfig {
             warn!(target: "network", "Outbound connections are disabled.");
         }
 
+        if !(0.0..=1.0).contains(&self.message_span_sample_rate) {
+            error!(target: "network",
+                "message_span_sample_rate({}) must be between 0 and 1.",
+                self.message_span_sample_rate
+            );
+        }
+
+        if self.max_outbound_bytes_per_sec == Some(0)
+            || self.max_outbound_bytes_per_sec_per_peer == Some(0)
+        {
+            error!(target: "network",
+                "max_outbound_bytes_per_sec({:?}) and max_outbound_bytes_per_sec_per_peer({:?}) must be positive.",
+                self.max_outbound_bytes_per_sec, self.max_outbound_bytes_per_sec_per_peer
+            );
+        }
+
+        if self.port_mapping && self.addr.is_none() {
+            warn!(target: "network", "port_mapping is enabled, but the node doesn't listen to any address.");
+        }
+
+        // Nodes give up on the puzzles harder than this, see `MAX_PROOF_OF_WORK_DIFFICULTY`.
+        if self.handshake_proof_of_work_difficulty > 24 {
+            error!(target: "network",
+                "handshake_proof_of_work_difficulty({}) must be at most 24, or no peer can connect.",
+                self.handshake_proof_of_work_difficulty
+            );
+        }
+
+        if !(0.0 < self.max_peers_fraction_per_asn && self.max_peers_fraction_per_asn <= 1.0) {
+            error!(target: "network",
+                "max_peers_fraction_per_asn({}) must be positive and at most 1.",
+                self.max_peers_fraction_per_asn
+            );
+        }
+
+        if self.compression_threshold_bytes == Some(0) {
+            warn!(target: "network", "compression_threshold_bytes is 0, even the smallest messages are compressed.");
+        }
+
+        if !(0.0 < self.peer_score_demote_threshold
+            && self.peer_score_demote_threshold <= self.peer_score_ban_threshold)
+        {
+            error!(target: "network",
+                "peer_score_demote_threshold({}) must be positive and at most peer_score_ban_threshold({}).",
+                self.peer_score_demote_threshold, self.peer_score_ban_threshold
+            );
+        }
+
+        if self.peer_score_half_life == Duration::from_secs(0) {
+            error!(target: "network", "peer_score_half_life must be positive.");
+        }
+
         if self.safe_set_size <= self.minimum_outbound_peers {
             error!(target: "network",
                 "safe_set_size({}) must be larger than minimum_outbound_peers({}).",
@@ -648,6 +828,93 @@ impl FromStr for PatternAddr {
     }
 }
 
+/// Range of IPs in CIDR notation, e.g. `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpNetwork {
+    /// First and last IPs of the range as IPv6 numbers, the IPv4 ones mapped to IPv6, so that
+    /// the ranges of both versions are ordered together.
+    pub fn range(&self) -> (u128, u128) {
+        let (addr, prefix_len) = match self.addr {
+            IpAddr::V4(addr) => (addr.to_ipv6_mapped(), self.prefix_len + 96),
+            IpAddr::V6(addr) => (addr, self.prefix_len),
+        };
+        let host_mask = u128::MAX.checked_shr(prefix_len as u32).unwrap_or(0);
+        let first = u128::from(addr) & !host_mask;
+        (first, first | host_mask)
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let (first, last) = self.range();
+        (first..=last).contains(&ip_to_u128(ip))
+    }
+}
+
+/// The IP as an IPv6 number, see `IpNetwork::range`.
+pub fn ip_to_u128(ip: &IpAddr) -> u128 {
+    match ip {
+        IpAddr::V4(ip) => u128::from(ip.to_ipv6_mapped()),
+        IpAddr::V6(ip) => u128::from(*ip),
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = match s.split_once('/') {
+            Some((addr, prefix_len)) => (addr, Some(prefix_len)),
+            None => (s, None),
+        };
+        let addr = addr.parse::<IpAddr>().map_err(|err| format!("Invalid range {}: {}", s, err))?;
+        let max_prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_prefix_len)
+                .ok_or_else(|| format!("Invalid prefix length in range {}", s))?,
+            None => max_prefix_len,
+        };
+        Ok(IpNetwork { addr, prefix_len })
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+/// Ranges of IPs the peers may be connected at.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerRanges {
+    /// If not empty, the peers must be in one of these ranges.
+    pub allowed: Vec<IpNetwork>,
+    /// The peers must be in none of these ranges, even if allowed.
+    pub denied: Vec<IpNetwork>,
+}
+
+impl PeerRanges {
+    pub fn parse(allowed: &[String], denied: &[String]) -> Result<Self, String> {
+        let parse = |ranges: &[String]| {
+            ranges.iter().map(|range| range.parse()).collect::<Result<Vec<IpNetwork>, _>>()
+        };
+        Ok(PeerRanges { allowed: parse(allowed)?, denied: parse(denied)? })
+    }
+
+    pub fn allows(&self, ip: &IpAddr) -> bool {
+        (self.allowed.is_empty() || self.allowed.iter().any(|range| range.contains(ip)))
+            && !self.denied.iter().any(|range| range.contains(ip))
+    }
+}
+
+pub type PeerRangesLoader = Arc<dyn Fn() -> Result<PeerRanges, String> + Send + Sync>;
+
 /// Status of the known peers.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Eq, PartialEq, Debug, Clone)]
 pub enum KnownPeerStatus {
@@ -980,6 +1247,127 @@ impl Message for NetworkViewClientMessages {
 /// Peer stats query.
 pub struct QueryPeerStats {}
 
+/// Bytes and number of messages transferred over a window.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Transfer {
+    pub bytes: u64,
+    pub count: u64,
+}
+
+/// Transfers over the last minute, 5 minutes and hour. The longer windows smooth out the bursts
+/// of the last minute, to tell the peers which are steadily slow.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransferWindows {
+    pub one_minute: Transfer,
+    pub five_minutes: Transfer,
+    pub one_hour: Transfer,
+}
+
+/// Kind of the messages exchanged with peers, to tell which part of the protocol uses the
+/// bandwidth.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageCategory {
+    /// Blocks, headers, their requests and the approvals.
+    Blocks,
+    /// Partial encoded chunks, their requests, responses and forwards.
+    Chunks,
+    Transactions,
+    /// State and epoch sync.
+    Sync,
+    /// The other routed messages: queries, transaction statuses, pings.
+    Routed,
+    /// Handshakes, routing table and peers exchanges.
+    Network,
+}
+
+impl MessageCategory {
+    pub const ALL: [MessageCategory; 6] = [
+        MessageCategory::Blocks,
+        MessageCategory::Chunks,
+        MessageCategory::Transactions,
+        MessageCategory::Sync,
+        MessageCategory::Routed,
+        MessageCategory::Network,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MessageCategory::Blocks => "blocks",
+            MessageCategory::Chunks => "chunks",
+            MessageCategory::Transactions => "transactions",
+            MessageCategory::Sync => "sync",
+            MessageCategory::Routed => "routed",
+            MessageCategory::Network => "network",
+        }
+    }
+}
+
+/// Transfers of a category of messages.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CategoryTransferStats {
+    pub category: MessageCategory,
+    pub sent: TransferWindows,
+    pub received: TransferWindows,
+}
+
+/// Transfers to and from an active peer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PeerTransferStats {
+    pub peer_id: PeerId,
+    pub sent: TransferWindows,
+    pub received: TransferWindows,
+    /// Transfers of the categories of messages exchanged with the peer. The messages dropped
+    /// before being decoded are only counted in the totals.
+    #[serde(default)]
+    pub categories: Vec<CategoryTransferStats>,
+}
+
+/// Misbehavior of a peer, counting against its score.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Misbehavior {
+    /// A message which can't be decoded, is unexpected, or an invalid transaction.
+    InvalidMessage,
+    /// More bytes received per second than `peer_max_received_bytes_per_sec`.
+    ExcessiveBandwidth,
+    /// No handshake or edge update in time.
+    Timeout,
+    /// A block which the peer already sent, and which wasn't requested again.
+    StaleData,
+}
+
+impl Misbehavior {
+    pub const ALL: [Misbehavior; 4] = [
+        Misbehavior::InvalidMessage,
+        Misbehavior::ExcessiveBandwidth,
+        Misbehavior::Timeout,
+        Misbehavior::StaleData,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Misbehavior::InvalidMessage => "invalid_message",
+            Misbehavior::ExcessiveBandwidth => "excessive_bandwidth",
+            Misbehavior::Timeout => "timeout",
+            Misbehavior::StaleData => "stale_data",
+        }
+    }
+}
+
+/// Reputation of a peer which misbehaved recently.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PeerScore {
+    pub peer_id: PeerId,
+    /// Sum of the penalties of the misbehaviors, halved every `peer_score_half_life`.
+    pub score: f64,
+    /// Whether the score is above `peer_score_demote_threshold`: the peer isn't used to sync
+    /// and is disconnected first to make room for other peers.
+    pub demoted: bool,
+    /// Misbehaviors since the peer was first scored, or last banned.
+    pub misbehaviors: BTreeMap<Misbehavior, u64>,
+}
+
 /// Peer stats result
 #[derive(Debug)]
 pub struct PeerStatsResult {
@@ -993,6 +1381,14 @@ pub struct PeerStatsResult {
     pub is_abusive: bool,
     /// Counts of incoming/outgoing messages from given peer.
     pub message_counts: (u64, u64),
+    /// Transfers to the peer over the last minute, 5 minutes and hour.
+    pub sent: TransferWindows,
+    /// Transfers from the peer over the last minute, 5 minutes and hour.
+    pub received: TransferWindows,
+    /// Transfers to and from the peer per category of messages.
+    pub categories: Vec<CategoryTransferStats>,
+    /// Bytes of the messages queued to be sent to the peer.
+    pub outbound_queued_bytes: u64,
 }
 
 impl<A, M> MessageResponse<A, M> for PeerStatsResult
@@ -1084,6 +1480,84 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_outbound_source_ip() {
+        let mut config = NetworkConfig::from_seed("test", 0);
+        let peer: SocketAddr = "10.0.0.2:24567".parse().unwrap();
+        assert_eq!(config.outbound_source_ip_for(&peer), None);
+
+        config.outbound_source_ip = Some("192.168.0.1".parse().unwrap());
+        config.outbound_source_ip_overrides = vec![
+            ("10.0.0.2".parse().unwrap(), "10.0.0.1".parse().unwrap()),
+            ("10.0.0.2:24567".parse().unwrap(), "10.0.0.3".parse().unwrap()),
+            ("10.0.0.4".parse().unwrap(), "::1".parse().unwrap()),
+        ];
+        let source_ip_for = |peer: &str| {
+            config.outbound_source_ip_for(&peer.parse().unwrap()).map(|ip| ip.to_string())
+        };
+        assert_eq!(source_ip_for("10.0.0.2:24567").as_deref(), Some("10.0.0.3"));
+        assert_eq!(source_ip_for("10.0.0.2:24568").as_deref(), Some("10.0.0.1"));
+        assert_eq!(source_ip_for("10.0.0.5:24567").as_deref(), Some("192.168.0.1"));
+        assert_eq!(source_ip_for("10.0.0.4:24567"), None);
+        assert_eq!(source_ip_for("[::2]:24567"), None);
+    }
+
+    #[test]
+    fn test_ip_network() {
+        let network = |s: &str| s.parse::<IpNetwork>().unwrap();
+        let contains = |s: &str, ip: &str| network(s).contains(&ip.parse().unwrap());
+        assert!(contains("10.0.0.0/8", "10.1.2.3"));
+        assert!(!contains("10.0.0.0/8", "11.0.0.0"));
+        assert!(contains("10.1.2.3/8", "10.255.255.255"));
+        assert!(contains("1.2.3.4", "1.2.3.4"));
+        assert!(!contains("1.2.3.4", "1.2.3.5"));
+        assert!(contains("0.0.0.0/0", "255.255.255.255"));
+        assert!(!contains("0.0.0.0/0", "::1"));
+        assert!(contains("::/0", "::1"));
+        assert!(contains("2001:db8::/32", "2001:db8:ffff::1"));
+        assert!(!contains("2001:db8::/32", "2001:db9::"));
+        assert_eq!(network("10.0.0.0/8").to_string(), "10.0.0.0/8");
+        assert_eq!(network("::1").to_string(), "::1/128");
+        for invalid in ["10.0.0.0/33", "::/129", "10.0.0/8", "10.0.0.0/", "10.0.0.0/a"] {
+            assert!(invalid.parse::<IpNetwork>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_peer_ranges() {
+        let ranges = |allowed: &[&str], denied: &[&str]| {
+            let strings =
+                |ranges: &[&str]| ranges.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            PeerRanges::parse(&strings(allowed), &strings(denied)).unwrap()
+        };
+        let allows = |ranges: &PeerRanges, ip: &str| ranges.allows(&ip.parse().unwrap());
+        assert!(allows(&PeerRanges::default(), "1.2.3.4"));
+        let denied = ranges(&[], &["10.0.0.0/8"]);
+        assert!(!allows(&denied, "10.1.2.3"));
+        assert!(allows(&denied, "11.1.2.3"));
+        let allowed = ranges(&["10.0.0.0/8", "2001:db8::/32"], &["10.0.0.0/16"]);
+        assert!(allows(&allowed, "10.1.2.3"));
+        assert!(!allows(&allowed, "10.0.2.3"));
+        assert!(allows(&allowed, "2001:db8::1"));
+        assert!(!allows(&allowed, "11.1.2.3"));
+        assert!(PeerRanges::parse(&["10.0.0.0/40".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_boot_node_host() {
+        let peer_id = PeerId::new(SecretKey::from_seed(KeyType::ED25519, "test").public_key());
+        let boot_node: BootNodeHost =
+            format!("{}@boot.example.org:24567", peer_id).parse().unwrap();
+        assert_eq!(boot_node.host, "boot.example.org");
+        assert_eq!(boot_node.port, 24567);
+        assert_eq!(boot_node.to_string().parse::<BootNodeHost>().unwrap(), boot_node);
+
+        // The boot nodes given by IP or without address are `PeerInfo`s.
+        for addr in ["", "@1.2.3.4:24567", "@[::1]:24567", "@alice.near", "@boot.example.org"] {
+            assert!(format!("{}{}", peer_id, addr).parse::<BootNodeHost>().is_err());
+        }
+    }
+
     #[test]
     fn test_enum_size() {
         assert_size!(PeerType);
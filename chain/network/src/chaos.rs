@@ -0,0 +1,177 @@
+//! Faults injected in the messages exchanged by in-process nodes, for the integration tests.
+//!
+//! The tests make the messages sent by a node to a peer late, out of order, lost or duplicated,
+//! per edge and per type of message, to reproduce forks and catchups without waiting for the
+//! timeouts of a real network. The random choices are made by an RNG per edge seeded from the seed
+//! of the `NetworkChaos`, so that the same messages sent on an edge get the same faults on every
+//! run. Only the messages sent once the peers are connected are affected, not the handshakes.
+
+use near_primitives::network::PeerId;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Faults injected in the messages matching a `Selector`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Faults {
+    /// Delay of every message.
+    pub latency: Duration,
+    /// Extra delay of a message, between zero and `jitter`, which makes the later messages
+    /// overtake it.
+    pub jitter: Duration,
+    /// Probability of a message being dropped.
+    pub drop_probability: f64,
+    /// Probability of a message being sent twice, the copy with its own delay.
+    pub duplicate_probability: f64,
+}
+
+impl Faults {
+    /// Drops all the messages, e.g. to partition the network.
+    pub fn drop_all() -> Self {
+        Faults { drop_probability: 1.0, ..Default::default() }
+    }
+}
+
+/// Messages sent by the node `from` to the peer `to`, of the type `message_type`, any if `None`.
+/// The types are the variants of the messages, or of the bodies of the routed messages, e.g.
+/// `Block` or `BlockApproval`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Selector {
+    pub from: Option<PeerId>,
+    pub to: Option<PeerId>,
+    pub message_type: Option<String>,
+}
+
+impl Selector {
+    fn matches(&self, from: &PeerId, to: &PeerId, message_type: &str) -> bool {
+        self.from.as_ref().map_or(true, |peer_id| peer_id == from)
+            && self.to.as_ref().map_or(true, |peer_id| peer_id == to)
+            && self.message_type.as_ref().map_or(true, |selected| selected == message_type)
+    }
+}
+
+struct Inner {
+    seed: u64,
+    /// The latest rule matching a message applies.
+    rules: Vec<(Selector, Faults)>,
+    rngs: HashMap<(PeerId, PeerId), StdRng>,
+}
+
+/// Faults injected by the nodes sharing it, see `PeerManagerActor::with_chaos`.
+#[derive(Clone)]
+pub struct NetworkChaos {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl NetworkChaos {
+    pub fn new(seed: u64) -> Self {
+        NetworkChaos {
+            inner: Arc::new(Mutex::new(Inner { seed, rules: vec![], rngs: HashMap::new() })),
+        }
+    }
+
+    /// Injects the faults in the messages matching the selector, replacing those of a previous
+    /// call with the same selector.
+    pub fn set_faults(&self, selector: Selector, faults: Faults) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.rules.retain(|(rule_selector, _)| *rule_selector != selector);
+        inner.rules.push((selector, faults));
+    }
+
+    /// Stops injecting the faults set with the selector.
+    pub fn remove_faults(&self, selector: &Selector) {
+        self.inner.lock().unwrap().rules.retain(|(rule_selector, _)| rule_selector != selector);
+    }
+
+    /// Stops injecting any fault.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().rules.clear();
+    }
+
+    /// Delays after which the copies of a message are sent, none if the message is dropped.
+    pub(crate) fn delays(&self, from: &PeerId, to: &PeerId, message_type: &str) -> Vec<Duration> {
+        let mut inner = self.inner.lock().unwrap();
+        let faults = match inner
+            .rules
+            .iter()
+            .rev()
+            .find(|(selector, _)| selector.matches(from, to, message_type))
+        {
+            Some((_, faults)) => faults.clone(),
+            None => return vec![Duration::ZERO],
+        };
+        let seed = inner.seed;
+        let rng = inner
+            .rngs
+            .entry((from.clone(), to.clone()))
+            .or_insert_with(|| StdRng::seed_from_u64(edge_seed(seed, from, to)));
+        if rng.gen_bool(faults.drop_probability.max(0.0).min(1.0)) {
+            return vec![];
+        }
+        let copies =
+            if rng.gen_bool(faults.duplicate_probability.max(0.0).min(1.0)) { 2 } else { 1 };
+        (0..copies).map(|_| faults.latency + faults.jitter.mul_f64(rng.gen::<f64>())).collect()
+    }
+}
+
+/// Seed of the RNG of an edge, the same on every run.
+fn edge_seed(seed: u64, from: &PeerId, to: &PeerId) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (seed, from, to).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{KeyType, SecretKey};
+
+    fn peer_id(seed: &str) -> PeerId {
+        PeerId::new(SecretKey::from_seed(KeyType::ED25519, seed).public_key())
+    }
+
+    #[test]
+    fn test_network_chaos() {
+        let (alice, bob) = (peer_id("alice"), peer_id("bob"));
+        let chaos = NetworkChaos::new(42);
+        assert_eq!(chaos.delays(&alice, &bob, "Block"), [Duration::ZERO]);
+
+        chaos.set_faults(
+            Selector { from: Some(alice.clone()), ..Default::default() },
+            Faults { latency: Duration::from_millis(100), ..Default::default() },
+        );
+        let blocks = Selector {
+            to: Some(bob.clone()),
+            message_type: Some("Block".to_string()),
+            ..Default::default()
+        };
+        chaos.set_faults(blocks.clone(), Faults::drop_all());
+        assert!(chaos.delays(&alice, &bob, "Block").is_empty());
+        assert_eq!(chaos.delays(&alice, &bob, "BlockApproval"), [Duration::from_millis(100)]);
+        assert_eq!(chaos.delays(&bob, &alice, "Block"), [Duration::ZERO]);
+        chaos.remove_faults(&blocks);
+        assert_eq!(chaos.delays(&alice, &bob, "Block"), [Duration::from_millis(100)]);
+
+        // The same faults are injected with the same seed.
+        let faults = Faults {
+            jitter: Duration::from_millis(100),
+            drop_probability: 0.3,
+            duplicate_probability: 0.3,
+            ..Default::default()
+        };
+        let delays = |chaos: &NetworkChaos| -> Vec<Vec<Duration>> {
+            chaos.set_faults(Selector::default(), faults.clone());
+            (0..50).map(|_| chaos.delays(&bob, &alice, "Block")).collect()
+        };
+        let first = delays(&NetworkChaos::new(7));
+        assert_eq!(first, delays(&NetworkChaos::new(7)));
+        assert!(first.iter().any(|delays| delays.is_empty()));
+        assert!(first.iter().any(|delays| delays.len() == 2));
+        chaos.clear();
+        assert_eq!(chaos.delays(&alice, &bob, "Block"), [Duration::ZERO]);
+    }
+}
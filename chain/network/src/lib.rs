@@ -7,6 +7,8 @@ pub use crate::stats::metrics;
 // TODO(#5307)
 pub use near_network_primitives::types::PeerInfo;
 
+#[cfg(feature = "test_features")]
+pub mod chaos;
 pub(crate) mod common;
 mod peer;
 mod peer_manager;
@@ -5,21 +5,125 @@
 ///
 /// NOTES:
 ///     - Code has an extra logic to ban peers if they sent messages that are too large.
+///     - The largest size allowed depends on the kind of the message, known from its first bytes,
+///       so that a peer can't make the node buffer hundreds of megabytes for a handshake.
 use crate::stats::metrics;
 use bytes::{Buf, BufMut, BytesMut};
 use bytesize::{GIB, MIB};
 use near_network_primitives::types::ReasonForBan;
 use near_performance_metrics::framed_write::EncoderCallBack;
 use near_rust_allocator_proxy::allocator::get_tid;
-use std::io::{Error, ErrorKind};
+use std::io::{self, Error, ErrorKind, Read};
 use tokio_util::codec::{Decoder, Encoder};
 use tracing::error;
 
 /// Maximum size of network message in encoded format.
 /// The size of message is stored as `u32`, so the limit has type `u32`
-const NETWORK_MESSAGE_MAX_SIZE_BYTES: u32 = 512 * MIB as u32;
+pub(crate) const NETWORK_MESSAGE_MAX_SIZE_BYTES: u32 = 512 * MIB as u32;
 /// Maximum capacity of write buffer in bytes.
 const MAX_WRITE_BUFFER_CAPACITY_BYTES: usize = GIB as usize;
+/// Bytes at the start of a message from which its kind is known: the variant of a
+/// `PeerMessage::Routed` body comes after the target, the author, the signature and the TTL, which
+/// are at most 65, 65, 66 and 1 bytes long.
+const MESSAGE_KIND_PREFIX_LEN: usize = 1 + 1 + 65 + 65 + 66 + 1 + 1;
+
+/// Kinds of messages with the same largest size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MessageKind {
+    /// Handshakes, requests, approvals, pings, and the messages which can't be decoded.
+    Small,
+    Transaction,
+    /// Responses to the queries and to the transaction statuses.
+    Query,
+    /// Exchanges of the routing table and of the peers.
+    Routing,
+    /// Blocks and block headers.
+    Block,
+    /// Partial encoded chunks and their responses and forwards.
+    Chunk,
+    /// Challenges, with the state witnesses of the chunks.
+    Challenge,
+    /// State parts and headers, and epoch sync responses.
+    Sync,
+    /// Compressed message, which kind is checked once decompressed.
+    Compressed,
+}
+
+impl MessageKind {
+    /// The kind of the message starting with `prefix`, its first `MESSAGE_KIND_PREFIX_LEN` bytes
+    /// or all of its bytes if it's shorter.
+    pub(crate) fn of(prefix: &[u8]) -> MessageKind {
+        // Indexes of the variants of `PeerMessage`.
+        match prefix.get(0) {
+            Some(3) | Some(7) | Some(25) => MessageKind::Routing,
+            Some(9) | Some(11) => MessageKind::Block,
+            Some(12) => MessageKind::Transaction,
+            Some(13) => match routed_message_body_variant(prefix) {
+                // Indexes of the variants of `RoutedMessageBody`.
+                Some(1) => MessageKind::Transaction,
+                Some(3) | Some(4) | Some(5) => MessageKind::Query,
+                Some(10) | Some(17) => MessageKind::Sync,
+                Some(12) | Some(13) | Some(16) | Some(18) => MessageKind::Chunk,
+                _ => MessageKind::Small,
+            },
+            Some(15) => MessageKind::Challenge,
+            Some(18) | Some(20) => MessageKind::Sync,
+            Some(24) => MessageKind::Compressed,
+            _ => MessageKind::Small,
+        }
+    }
+
+    pub(crate) fn max_size_bytes(self) -> u32 {
+        match self {
+            MessageKind::Small => MIB as u32,
+            MessageKind::Transaction => 16 * MIB as u32,
+            MessageKind::Query | MessageKind::Routing | MessageKind::Block => 64 * MIB as u32,
+            MessageKind::Chunk => 128 * MIB as u32,
+            MessageKind::Challenge => 256 * MIB as u32,
+            MessageKind::Sync | MessageKind::Compressed => NETWORK_MESSAGE_MAX_SIZE_BYTES,
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            MessageKind::Small => "small",
+            MessageKind::Transaction => "transaction",
+            MessageKind::Query => "query",
+            MessageKind::Routing => "routing",
+            MessageKind::Block => "block",
+            MessageKind::Chunk => "chunk",
+            MessageKind::Challenge => "challenge",
+            MessageKind::Sync => "sync",
+            MessageKind::Compressed => "compressed",
+        }
+    }
+
+    /// Whether a message of this kind may be `len` bytes long, counting the rejected ones.
+    fn allows(self, len: usize) -> bool {
+        if len <= self.max_size_bytes() as usize {
+            return true;
+        }
+        metrics::PEER_OVERSIZE_MESSAGES_REJECTED_TOTAL.with_label_values(&[self.as_str()]).inc();
+        false
+    }
+}
+
+/// Reads a message, e.g. being decompressed, failing as soon as it's larger than allowed for its
+/// kind: the size isn't known before, and the message is never buffered past that limit.
+pub(crate) fn read_message(mut reader: impl Read) -> io::Result<Vec<u8>> {
+    let mut message = vec![];
+    (&mut reader).take(MESSAGE_KIND_PREFIX_LEN as u64).read_to_end(&mut message)?;
+    let kind = MessageKind::of(&message);
+    let max_size = kind.max_size_bytes() as usize;
+    reader.take((max_size + 1 - message.len()) as u64).read_to_end(&mut message)?;
+    if !kind.allows(message.len()) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{} message is larger than {} bytes", kind.as_str(), max_size),
+        ));
+    }
+    Ok(message)
+}
 
 #[derive(Default)]
 pub struct Codec {}
@@ -80,18 +184,28 @@ impl Decoder for Codec {
 
         let mut len_bytes: [u8; 4] = [0; 4];
         len_bytes.copy_from_slice(&buf[0..4]);
-        let len = u32::from_le_bytes(len_bytes);
-        if len > NETWORK_MESSAGE_MAX_SIZE_BYTES {
+        let len = u32::from_le_bytes(len_bytes) as usize;
+        if len > NETWORK_MESSAGE_MAX_SIZE_BYTES as usize {
+            metrics::PEER_OVERSIZE_MESSAGES_REJECTED_TOTAL.with_label_values(&["unknown"]).inc();
+            // If this point is reached, abusive peer is banned.
+            return Ok(Some(Err(ReasonForBan::Abusive)));
+        }
+        // The size is checked as soon as the kind of the message is known, before buffering it.
+        let prefix_len = len.min(MESSAGE_KIND_PREFIX_LEN);
+        if buf.len() < 4 + prefix_len {
+            return Ok(None);
+        }
+        if !MessageKind::of(&buf[4..4 + prefix_len]).allows(len) {
             // If this point is reached, abusive peer is banned.
             return Ok(Some(Err(ReasonForBan::Abusive)));
         }
 
-        if buf.len() < 4 + len as usize {
+        if buf.len() < 4 + len {
             // not enough bytes, keep waiting
             Ok(None)
         } else {
-            let res = Some(Ok(buf[4..4 + len as usize].to_vec()));
-            buf.advance(4 + len as usize);
+            let res = Some(Ok(buf[4..4 + len].to_vec()));
+            buf.advance(4 + len);
             Ok(res)
         }
     }
@@ -122,6 +236,13 @@ pub(crate) fn is_forward_transaction(bytes: &[u8]) -> Option<bool> {
         return Some(false);
     }
 
+    // check if type is `RoutedMessageBody::ForwardTx`
+    Some(routed_message_body_variant(bytes)? == 1)
+}
+
+/// Variant of the body of the `PeerMessage::Routed(RoutedMessage)` in `bytes`, read without
+/// deserializing the message.
+fn routed_message_body_variant(bytes: &[u8]) -> Option<u8> {
     // target: PeerIdOrHash
     let author_variant_idx = {
         let target_field_len = {
@@ -167,14 +288,14 @@ pub(crate) fn is_forward_transaction(bytes: &[u8]) -> Option<bool> {
     // pub ttl: u8
     let message_body_idx = ttl_idx + 1;
 
-    // check if type is `RoutedMessageBody::ForwardTx`
-    let message_body_variant = *bytes.get(message_body_idx)?;
-    Some(message_body_variant == 1)
+    bytes.get(message_body_idx).copied()
 }
 
 #[cfg(test)]
 mod test {
-    use crate::peer::codec::{is_forward_transaction, Codec, NETWORK_MESSAGE_MAX_SIZE_BYTES};
+    use crate::peer::codec::{
+        is_forward_transaction, Codec, MessageKind, NETWORK_MESSAGE_MAX_SIZE_BYTES,
+    };
     use crate::routing::edge::PartialEdgeInfo;
     use crate::types::{Handshake, HandshakeFailureReason, HandshakeV2, PeerMessage, SyncData};
     use crate::PeerInfo;
@@ -434,4 +555,31 @@ mod test {
         buffer.put_u32_le(NETWORK_MESSAGE_MAX_SIZE_BYTES);
         assert_ne!(codec.decode(&mut buffer).unwrap(), Some(Err(ReasonForBan::Abusive)));
     }
+
+    #[test]
+    fn test_message_kind_size() {
+        let decode = |len: u32, prefix: &[u8]| {
+            let mut buffer = BytesMut::new();
+            buffer.put_u32_le(len);
+            buffer.put(prefix);
+            Codec::default().decode(&mut buffer).unwrap()
+        };
+        // Not rejected before the kind of the message is known.
+        assert_eq!(decode(2 * 1024 * 1024, &[]), None);
+        // A handshake, a block and a part of the state.
+        assert_eq!(decode(2 * 1024 * 1024, &[0]), Some(Err(ReasonForBan::Abusive)));
+        assert_eq!(decode(2 * 1024 * 1024, &[11]), None);
+        assert_eq!(decode(128 * 1024 * 1024, &[11]), Some(Err(ReasonForBan::Abusive)));
+        assert_eq!(decode(NETWORK_MESSAGE_MAX_SIZE_BYTES, &[18]), None);
+
+        let schema = ForwardTxType {
+            target: ForwardTxTargetType::PublicKey(KeyType::SECP256K1),
+            author: KeyType::SECP256K1,
+            tx: KeyType::SECP256K1,
+        };
+        let bytes = create_tx_forward(schema).try_to_vec().unwrap();
+        assert_eq!(MessageKind::of(&bytes), MessageKind::Transaction);
+        assert_eq!(decode(32 * 1024 * 1024, &bytes), Some(Err(ReasonForBan::Abusive)));
+        assert_eq!(decode(bytes.len() as u32, &bytes), Some(Ok(bytes)));
+    }
 }
@@ -0,0 +1,83 @@
+//! Compression of the large messages sent to a peer, when both ends enable it.
+//!
+//! The node dialing a peer offers the codecs it supports with a `CompressionOffer` sent before its
+//! handshake, and the peer answers with the `CompressionAccept` of the one it picks, if any. From
+//! then on, the messages above the threshold are sent `Compressed` whenever that makes them
+//! smaller. The peers which don't know these messages take the offer for an invalid message, so
+//! compression is only enabled on networks where the peers understand it.
+//!
+//! The state parts, which are the largest messages and aren't latency sensitive, are compressed
+//! at a higher level than the others.
+
+use crate::peer::codec;
+use crate::types::CompressionCodec;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use near_network_primitives::types::MessageCategory;
+use std::io::{self, Write};
+
+/// Codecs the node supports, by order of preference.
+pub(crate) const SUPPORTED_CODECS: [CompressionCodec; 1] = [CompressionCodec::Deflate];
+
+/// The codec picked among the ones offered by a peer.
+pub(crate) fn pick_codec(offered: &[CompressionCodec]) -> Option<CompressionCodec> {
+    SUPPORTED_CODECS.iter().find(|codec| offered.contains(codec)).copied()
+}
+
+pub(crate) fn compress(
+    codec: CompressionCodec,
+    category: MessageCategory,
+    bytes: &[u8],
+) -> io::Result<Vec<u8>> {
+    match codec {
+        CompressionCodec::Deflate => {
+            let mut encoder = DeflateEncoder::new(vec![], deflate_level(category));
+            encoder.write_all(bytes)?;
+            encoder.finish()
+        }
+    }
+}
+
+fn deflate_level(category: MessageCategory) -> Compression {
+    match category {
+        // The state parts and the epoch sync responses are sent once to each syncing node, which
+        // is waiting for all of them anyway.
+        MessageCategory::Sync => Compression::default(),
+        // The fastest level: most of the gain of the higher ones on the borsh serialized
+        // messages, which are sent right away.
+        _ => Compression::fast(),
+    }
+}
+
+/// Decompresses a message, no larger than allowed for its kind, see `codec::read_message`: the
+/// length isn't trusted to allocate the buffer upfront.
+pub(crate) fn decompress(codec: CompressionCodec, bytes: &[u8]) -> io::Result<Vec<u8>> {
+    match codec {
+        CompressionCodec::Deflate => codec::read_message(DeflateDecoder::new(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression() {
+        let bytes: Vec<u8> = (0..10_000u32).map(|i| (i % 7) as u8).collect();
+        for category in MessageCategory::ALL {
+            let compressed = compress(CompressionCodec::Deflate, category, &bytes).unwrap();
+            assert!(compressed.len() < bytes.len() / 10);
+            assert_eq!(decompress(CompressionCodec::Deflate, &compressed).unwrap(), bytes);
+        }
+        assert!(decompress(CompressionCodec::Deflate, &bytes).is_err());
+        // Starting with 0, the variant of a handshake, which is a small message.
+        let large =
+            compress(CompressionCodec::Deflate, MessageCategory::Blocks, &vec![0; 2 * 1024 * 1024])
+                .unwrap();
+        assert!(decompress(CompressionCodec::Deflate, &large).is_err());
+
+        assert_eq!(pick_codec(&[CompressionCodec::Deflate]), Some(CompressionCodec::Deflate));
+        assert_eq!(pick_codec(&[]), None);
+    }
+}
@@ -1,4 +1,7 @@
 pub(crate) mod codec;
+mod compression;
+pub(crate) mod outbound_limiter;
 pub(crate) mod peer_actor;
+mod proof_of_work;
 mod rate_counter;
 mod tracker;
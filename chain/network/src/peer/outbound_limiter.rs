@@ -0,0 +1,229 @@
+//! Scheduling of the messages sent to a peer and shaping of the bandwidth used to send them.
+//!
+//! The messages sent to a peer are queued by class of service, the approvals and chunk parts
+//! first, and handed to the connection in that order as it drains, so that a large state part
+//! being written only delays them by itself rather than by everything queued behind it.
+//!
+//! The messages also go through a token bucket of the peer and one shared by all the peers, if
+//! their bandwidth is limited, each refilled at its configured rate of bytes per second and
+//! holding up to a second of it. A message is written as soon as both buckets have tokens left,
+//! even if it's larger than what is left: the bucket goes into debt and the next messages wait
+//! for it to be paid back, so that the average rate is kept without ever blocking the messages
+//! larger than a second of bandwidth.
+//!
+//! Once the queue of a peer is full, the bulk messages are dropped, the oldest queued ones first,
+//! as their senders retry them anyway. The other messages are always queued, so that a validator
+//! on a metered link keeps up with consensus instead of being kicked by its peers.
+
+use near_network_primitives::types::MessageCategory;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Bytes of messages queued for a peer above which the low priority messages are dropped.
+pub(crate) const MAX_QUEUED_BYTES: usize = 32 * 1024 * 1024;
+
+pub(crate) struct TokenBucket {
+    /// Bytes per second.
+    rate: u64,
+    /// Negative while a message larger than the tokens left is being paid back.
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: u64, now: Instant) -> Self {
+        Self { rate, tokens: rate as f64, last_refill: now }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.rate as f64).min(self.rate as f64);
+        self.last_refill = now;
+    }
+
+    /// Bytes which may be sent at `now`, negative while in debt.
+    pub fn tokens(&mut self, now: Instant) -> f64 {
+        self.refill(now);
+        self.tokens
+    }
+
+    fn has_tokens(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens > 0.0
+    }
+
+    fn take(&mut self, bytes: usize) {
+        self.tokens -= bytes as f64;
+    }
+}
+
+/// Token bucket shared by the peers, limiting the bandwidth of the node.
+pub(crate) type SharedTokenBucket = Arc<Mutex<TokenBucket>>;
+
+/// Class of service of a message. The queued messages of a class are written before the ones of
+/// the next classes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum QosClass {
+    /// Approvals and chunk parts, which validators need in time to produce and endorse blocks.
+    Consensus,
+    /// Blocks, headers and the network messages.
+    Normal,
+    /// State and epoch sync, transactions: large, or retried by their senders, and dropped first.
+    Bulk,
+}
+
+impl QosClass {
+    const ALL: [QosClass; 3] = [QosClass::Consensus, QosClass::Normal, QosClass::Bulk];
+}
+
+/// Messages to send to a peer, waiting for the connection or the bandwidth to be written.
+pub(crate) struct OutboundLimiter {
+    peer: Option<TokenBucket>,
+    global: Option<SharedTokenBucket>,
+    /// Queued messages, per class of service.
+    queues: [VecDeque<(MessageCategory, Vec<u8>)>; 3],
+    queued_bytes: usize,
+    max_queued_bytes: usize,
+}
+
+impl OutboundLimiter {
+    pub fn new(
+        peer_rate: Option<u64>,
+        global: Option<SharedTokenBucket>,
+        max_queued_bytes: usize,
+        now: Instant,
+    ) -> Self {
+        Self {
+            peer: peer_rate.map(|rate| TokenBucket::new(rate, now)),
+            global,
+            queues: Default::default(),
+            queued_bytes: 0,
+            max_queued_bytes,
+        }
+    }
+
+    /// Queues a message, returning the category and size of the messages dropped to make room.
+    pub fn push(
+        &mut self,
+        class: QosClass,
+        category: MessageCategory,
+        bytes: Vec<u8>,
+    ) -> Vec<(MessageCategory, usize)> {
+        let mut dropped = vec![];
+        if class == QosClass::Bulk && self.queued_bytes + bytes.len() > self.max_queued_bytes {
+            dropped.push((category, bytes.len()));
+            return dropped;
+        }
+        self.queued_bytes += bytes.len();
+        self.queues[class as usize].push_back((category, bytes));
+        while self.queued_bytes > self.max_queued_bytes {
+            match self.queues[QosClass::Bulk as usize].pop_front() {
+                Some((category, bytes)) => {
+                    self.queued_bytes -= bytes.len();
+                    dropped.push((category, bytes.len()));
+                }
+                None => break,
+            }
+        }
+        dropped
+    }
+
+    pub fn queued_bytes(&self) -> usize {
+        self.queued_bytes
+    }
+
+    /// The next message to write, if there is one and the bandwidth allows it.
+    pub fn pop(&mut self, now: Instant) -> Option<(MessageCategory, Vec<u8>)> {
+        let class =
+            *QosClass::ALL.iter().find(|class| !self.queues[**class as usize].is_empty())?;
+        if let Some(peer) = &mut self.peer {
+            if !peer.has_tokens(now) {
+                return None;
+            }
+        }
+        let mut global = self.global.as_ref().map(|global| global.lock().unwrap());
+        if let Some(global) = &mut global {
+            if !global.has_tokens(now) {
+                return None;
+            }
+        }
+        let (category, bytes) = self.queues[class as usize].pop_front()?;
+        self.queued_bytes -= bytes.len();
+        if let Some(peer) = &mut self.peer {
+            peer.take(bytes.len());
+        }
+        if let Some(global) = &mut global {
+            global.take(bytes.len());
+        }
+        Some((category, bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_qos_classes() {
+        let now = Instant::now();
+        let mut limiter = OutboundLimiter::new(None, None, 500, now);
+        limiter.push(QosClass::Bulk, MessageCategory::Sync, vec![0; 300]);
+        limiter.push(QosClass::Normal, MessageCategory::Blocks, vec![0; 100]);
+        limiter.push(QosClass::Consensus, MessageCategory::Blocks, vec![0; 1]);
+        limiter.push(QosClass::Consensus, MessageCategory::Chunks, vec![0; 2]);
+        assert_eq!(limiter.queued_bytes(), 403);
+        let written: Vec<_> =
+            std::iter::from_fn(|| limiter.pop(now)).map(|(_, bytes)| bytes.len()).collect();
+        assert_eq!(written, vec![1, 2, 100, 300]);
+        assert_eq!(limiter.queued_bytes(), 0);
+    }
+
+    #[test]
+    fn test_outbound_limiter() {
+        let start = Instant::now();
+        let global = Arc::new(Mutex::new(TokenBucket::new(1_000, start)));
+        let mut limiter = OutboundLimiter::new(Some(100), Some(global.clone()), 500, start);
+        let mut other = OutboundLimiter::new(None, Some(global), 500, start);
+
+        assert!(limiter.push(QosClass::Bulk, MessageCategory::Sync, vec![0; 150]).is_empty());
+        assert!(limiter.push(QosClass::Normal, MessageCategory::Blocks, vec![0; 50]).is_empty());
+        // The block goes first, and the bucket of the peer goes into debt for the state part.
+        assert_eq!(limiter.pop(start).unwrap().1.len(), 50);
+        assert_eq!(limiter.pop(start).unwrap().1.len(), 150);
+        assert!(limiter.push(QosClass::Consensus, MessageCategory::Chunks, vec![0; 10]).is_empty());
+        assert!(limiter.pop(start).is_none());
+        assert!(limiter.pop(start + Duration::from_millis(900)).is_none());
+        assert_eq!(limiter.pop(start + Duration::from_millis(1010)).unwrap().1.len(), 10);
+
+        // The global bucket holds the other peers back once the node used its bandwidth.
+        assert!(other.push(QosClass::Normal, MessageCategory::Routed, vec![0; 1_000]).is_empty());
+        assert!(other.push(QosClass::Normal, MessageCategory::Routed, vec![0; 10]).is_empty());
+        let now = start + Duration::from_millis(1010);
+        assert_eq!(other.pop(now).unwrap().1.len(), 1_000);
+        assert!(other.pop(now).is_none());
+
+        // Once the queue is full the bulk messages make room for the others.
+        let bulk = |limiter: &mut OutboundLimiter, category| {
+            limiter.push(QosClass::Bulk, category, vec![0; 200])
+        };
+        assert!(bulk(&mut limiter, MessageCategory::Transactions).is_empty());
+        assert!(bulk(&mut limiter, MessageCategory::Sync).is_empty());
+        assert_eq!(
+            bulk(&mut limiter, MessageCategory::Transactions),
+            vec![(MessageCategory::Transactions, 200)]
+        );
+        assert_eq!(
+            limiter.push(QosClass::Normal, MessageCategory::Blocks, vec![0; 300]),
+            vec![(MessageCategory::Transactions, 200)]
+        );
+        assert_eq!(
+            limiter.push(QosClass::Consensus, MessageCategory::Blocks, vec![0; 300]),
+            vec![(MessageCategory::Sync, 200)]
+        );
+        // The messages which aren't dropped are queued even above the limit.
+        assert_eq!(limiter.queued_bytes, 600);
+    }
+}
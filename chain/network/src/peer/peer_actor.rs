@@ -1,13 +1,18 @@
+#[cfg(feature = "test_features")]
+use crate::chaos::NetworkChaos;
 use crate::common::message_wrapper::ActixMessageWrapper;
 use crate::peer::codec::{self, Codec};
+use crate::peer::compression;
+use crate::peer::outbound_limiter::OutboundLimiter;
+use crate::peer::proof_of_work::{self, MAX_PROOF_OF_WORK_DIFFICULTY};
 use crate::peer::tracker::Tracker;
 use crate::routing::edge::{Edge, PartialEdgeInfo};
 use crate::stats::metrics::{self, NetworkMetrics};
 use crate::types::{
-    Handshake, HandshakeFailureReason, HandshakeV2, NetworkClientMessages, NetworkClientResponses,
-    NetworkRequests, NetworkResponses, PeerManagerMessageRequest, PeerMessage, PeerRequest,
-    PeerResponse, PeersRequest, PeersResponse, RegisterPeer, RegisterPeerResponse, SendMessage,
-    Unregister,
+    CompressionCodec, Handshake, HandshakeFailureReason, HandshakeV2, NetworkClientMessages,
+    NetworkClientResponses, NetworkRequests, NetworkResponses, PeerManagerMessageRequest,
+    PeerMessage, PeerRequest, PeerResponse, PeersRequest, PeersResponse, RegisterPeer,
+    RegisterPeerResponse, SendMessage, Unregister,
 };
 use crate::{PeerInfo, PeerManagerActor};
 use actix::{
@@ -19,15 +24,16 @@ use borsh::BorshSerialize;
 use cached::{Cached, SizedCache};
 use near_crypto::Signature;
 use near_network_primitives::types::{
-    Ban, NetworkViewClientMessages, NetworkViewClientResponses, PeerChainInfo, PeerChainInfoV2,
-    PeerIdOrHash, PeerManagerRequest, PeerStatsResult, PeerStatus, PeerType, QueryPeerStats,
-    ReasonForBan, RoutedMessage, RoutedMessageBody, RoutedMessageFrom, StateResponseInfo,
-    UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE,
+    Ban, MessageCategory, Misbehavior, NetworkViewClientMessages, NetworkViewClientResponses,
+    PeerChainInfo, PeerChainInfoV2, PeerIdOrHash, PeerManagerRequest, PeerStatsResult, PeerStatus,
+    PeerType, QueryPeerStats, ReasonForBan, RoutedMessage, RoutedMessageBody, RoutedMessageFrom,
+    StateResponseInfo, UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE,
 };
 use near_performance_metrics::framed_write::{FramedWrite, WriteHandler};
 use near_performance_metrics_macros::perf;
 use near_primitives::block::GenesisId;
 use near_primitives::borsh::maybestd::io::Error;
+use near_primitives::hash::CryptoHash;
 use near_primitives::network::PeerId;
 use near_primitives::sharding::PartialEncodedChunk;
 use near_primitives::time::Clock;
@@ -38,6 +44,7 @@ use near_primitives::version::{
 use near_primitives::{logging, unwrap_option_or_return};
 use near_rate_limiter::ThrottleController;
 use near_rust_allocator_proxy::allocator::get_tid;
+use rand::Rng;
 use std::cmp::max;
 use std::fmt::Debug;
 use std::io;
@@ -61,6 +68,10 @@ const MAX_TRANSACTIONS_PER_BLOCK_MESSAGE: usize = 1000;
 pub const ROUTED_MESSAGE_CACHE_SIZE: usize = 1000;
 /// Duplicated messages will be dropped if routed through the same peer multiple times.
 pub const DROP_DUPLICATED_MESSAGES_PERIOD: Duration = Duration::from_millis(50);
+/// How often the queued messages are written, as the connection and the bandwidth allow.
+const OUTBOUND_FLUSH_PERIOD: Duration = Duration::from_millis(10);
+/// Bytes waiting to be written to the connection below which the queued messages are written.
+const MAX_WRITE_BUFFER_BYTES: usize = 16 * 1024;
 
 pub struct PeerActor {
     /// This node's id and address (either listening or socket address).
@@ -107,6 +118,24 @@ pub struct PeerActor {
     /// A helper data structure for limiting reading
     #[allow(unused)]
     throttle_controller: ThrottleController,
+    /// Fraction of the received messages processed in a `peer_message` span.
+    message_span_sample_rate: f64,
+    /// Messages waiting for the outbound bandwidth to be written.
+    outbound_limiter: OutboundLimiter,
+    /// Difficulty of the puzzle an inbound peer solves before its handshake is processed, none
+    /// if 0.
+    proof_of_work_difficulty: u8,
+    /// Seed of the puzzle sent to the inbound peer.
+    proof_of_work_seed: Option<CryptoHash>,
+    /// Whether the inbound peer sent the solution of the puzzle.
+    proof_of_work_solved: bool,
+    /// Size from which the messages are compressed, if the node compresses them.
+    compression_threshold: Option<usize>,
+    /// Codec agreed on with the peer to compress the messages sent to it.
+    compression: Option<CompressionCodec>,
+    /// Faults injected in the messages sent to the peer.
+    #[cfg(feature = "test_features")]
+    chaos: Option<NetworkChaos>,
 }
 
 impl Debug for PeerActor {
@@ -131,6 +160,11 @@ impl PeerActor {
         txns_since_last_block: Arc<AtomicUsize>,
         peer_counter: Arc<AtomicUsize>,
         throttle_controller: ThrottleController,
+        message_span_sample_rate: f64,
+        outbound_limiter: OutboundLimiter,
+        proof_of_work_difficulty: u8,
+        compression_threshold: Option<usize>,
+        #[cfg(feature = "test_features")] chaos: Option<NetworkChaos>,
     ) -> Self {
         PeerActor {
             my_node_info,
@@ -154,9 +188,39 @@ impl PeerActor {
             peer_counter,
             routed_message_cache: SizedCache::with_size(ROUTED_MESSAGE_CACHE_SIZE),
             throttle_controller,
+            message_span_sample_rate: message_span_sample_rate.max(0.0).min(1.0),
+            outbound_limiter,
+            proof_of_work_difficulty,
+            proof_of_work_seed: None,
+            proof_of_work_solved: false,
+            compression_threshold,
+            compression: None,
+            #[cfg(feature = "test_features")]
+            chaos,
         }
     }
 
+    /// Span of the processing of a received message, for the sampled ones only.
+    ///
+    /// The processing of messages forwarded to the client or the view client ends once they
+    /// answer, and `queue_wait_ms` records how long it took, most of it waiting in their
+    /// mailboxes behind other messages.
+    fn message_span(&self, msg_type: &str, size: usize) -> tracing::Span {
+        if self.message_span_sample_rate == 0.0
+            || !rand::thread_rng().gen_bool(self.message_span_sample_rate)
+        {
+            return tracing::Span::none();
+        }
+        tracing::info_span!(
+            target: "network",
+            "peer_message",
+            msg_type,
+            size,
+            peer_id = %DisplayOption(self.other_peer_id().cloned()),
+            queue_wait_ms = tracing::field::Empty,
+        )
+    }
+
     /// Whether the peer is considered abusive due to sending too many messages.
     // I am allowing this for now because I assume `MAX_PEER_MSG_PER_MIN` will
     // some day be less than `u64::MAX`.
@@ -177,21 +241,116 @@ impl PeerActor {
 
         match msg.try_to_vec() {
             Ok(bytes) => {
-                self.tracker.increment_sent(bytes.len() as u64);
-                let bytes_len = bytes.len();
-                if !self.framed.write(bytes) {
-                    error!(
-                        "{} Failed to send message {} of size {}",
-                        get_tid(),
-                        strum::AsStaticRef::as_static(msg),
-                        bytes_len,
-                    )
+                let category = msg.category();
+                let bytes = self.compress(category, bytes);
+                for (category, bytes_len) in
+                    self.outbound_limiter.push(msg.qos_class(), category, bytes)
+                {
+                    debug!(target: "network", "Dropped {} message of size {} to {}: outbound queue full", category.as_str(), bytes_len, self.peer_info);
+                    metrics::PEER_OUTBOUND_DROPPED_MESSAGES_TOTAL
+                        .with_label_values(&[category.as_str()])
+                        .inc();
                 }
+                self.flush_outbound();
             }
             Err(err) => error!(target: "network", "Error converting message to bytes: {}", err),
         };
     }
 
+    /// Sends the message with the faults injected in the messages to the peer, if any: it may be
+    /// late, dropped or sent twice.
+    #[cfg(feature = "test_features")]
+    fn send_message_with_chaos(&mut self, ctx: &mut Context<PeerActor>, msg: &PeerMessage) {
+        let delays = match (&self.chaos, self.other_peer_id()) {
+            (Some(chaos), Some(peer_id)) => {
+                chaos.delays(&self.my_node_info.id, peer_id, msg.msg_variant())
+            }
+            _ => return self.send_message(msg),
+        };
+        for delay in delays {
+            if delay == Duration::ZERO {
+                self.send_message(msg);
+            } else {
+                let msg = msg.clone();
+                near_performance_metrics::actix::run_later(ctx, delay, move |act, _ctx| {
+                    act.send_message(&msg);
+                });
+            }
+        }
+    }
+
+    /// The serialized message compressed, if the peer accepted a codec, the message is large
+    /// enough and compressing it makes it smaller; else the message as is.
+    fn compress(&self, category: MessageCategory, bytes: Vec<u8>) -> Vec<u8> {
+        let (codec, threshold) = match (self.compression, self.compression_threshold) {
+            (Some(codec), Some(threshold)) if self.peer_status == PeerStatus::Ready => {
+                (codec, threshold)
+            }
+            _ => return bytes,
+        };
+        if bytes.len() < threshold {
+            return bytes;
+        }
+        let compressed = match compression::compress(codec, category, &bytes)
+            .and_then(|compressed| PeerMessage::Compressed(codec, compressed).try_to_vec())
+        {
+            Ok(compressed) => compressed,
+            Err(err) => {
+                error!(target: "network", "Failed to compress message: {}", err);
+                return bytes;
+            }
+        };
+        let labels = [category.as_str()];
+        metrics::PEER_COMPRESSION_INPUT_BYTES.with_label_values(&labels).inc_by(bytes.len() as u64);
+        if compressed.len() >= bytes.len() {
+            metrics::PEER_COMPRESSION_OUTPUT_BYTES
+                .with_label_values(&labels)
+                .inc_by(bytes.len() as u64);
+            return bytes;
+        }
+        metrics::PEER_COMPRESSION_OUTPUT_BYTES
+            .with_label_values(&labels)
+            .inc_by(compressed.len() as u64);
+        compressed
+    }
+
+    /// Writes a serialized message to the connection, returning whether it succeeded.
+    fn write_message(&mut self, category: MessageCategory, bytes: Vec<u8>) -> bool {
+        let bytes_len = bytes.len() as u64;
+        self.tracker.increment_sent(category, bytes_len);
+        metrics::PEER_MESSAGE_CATEGORY_BYTES
+            .with_label_values(&["sent", category.as_str()])
+            .inc_by(bytes_len);
+        self.framed.write(bytes)
+    }
+
+    /// Writes the queued messages which the outbound bandwidth allows, while the connection has
+    /// little left to write: the messages of higher classes queued meanwhile still go first.
+    fn flush_outbound(&mut self) {
+        while self.framed.buffer_len() < MAX_WRITE_BUFFER_BYTES {
+            let (category, bytes) = match self.outbound_limiter.pop(Clock::instant()) {
+                Some(message) => message,
+                None => break,
+            };
+            let bytes_len = bytes.len();
+            if !self.write_message(category, bytes) {
+                error!(
+                    "{} Failed to send {} message of size {}",
+                    get_tid(),
+                    category.as_str(),
+                    bytes_len,
+                )
+            }
+        }
+    }
+
+    fn schedule_outbound_flush(&self, ctx: &mut Context<PeerActor>) {
+        near_performance_metrics::actix::run_later(ctx, OUTBOUND_FLUSH_PERIOD, move |act, ctx| {
+            act.flush_outbound();
+            act.schedule_outbound_flush(ctx);
+        });
+    }
+
     fn fetch_client_chain_info(&mut self, ctx: &mut Context<PeerActor>) {
         ctx.wait(
             self.view_client_addr
@@ -269,6 +428,15 @@ impl PeerActor {
         ctx.stop();
     }
 
+    /// Counts a misbehavior against the score of the peer, once its id is known.
+    fn report_misbehavior(&self, misbehavior: Misbehavior) {
+        if let Some(peer_id) = self.other_peer_id() {
+            self.peer_manager_addr.do_send(PeerManagerMessageRequest::PeerRequest(
+                PeerRequest::Misbehaved(peer_id.clone(), misbehavior),
+            ));
+        }
+    }
+
     /// `PeerId` of the current node.
     fn my_node_id(&self) -> &PeerId {
         &self.my_node_info.id
@@ -335,10 +503,15 @@ impl PeerActor {
             }
         };
 
+        let span = tracing::Span::current();
+        let received = Clock::instant();
         self.view_client_addr
             .send(view_client_message)
             .into_actor(self)
             .then(move |res, act, _ctx| {
+                let queue_wait = Clock::instant().saturating_duration_since(received);
+                span.record("queue_wait_ms", &(queue_wait.as_millis() as u64));
+                let _entered = span.enter();
                 // Ban peer if client thinks received data is bad.
                 match res {
                     Ok(NetworkViewClientResponses::TxStatus(tx_result)) => {
@@ -405,6 +578,10 @@ impl PeerActor {
             PeerMessage::Block(block) => {
                 metrics::PEER_BLOCK_RECEIVED_TOTAL.inc();
                 let block_hash = *block.hash();
+                if self.tracker.has_received(&block_hash) && !self.tracker.has_request(&block_hash)
+                {
+                    self.report_misbehavior(Misbehavior::StaleData);
+                }
                 self.tracker.push_received(block_hash);
                 self.chain_info.height = max(self.chain_info.height, block.header().height());
                 NetworkClientMessages::Block(block, peer_id, self.tracker.has_request(&block_hash))
@@ -446,18 +623,25 @@ impl PeerActor {
                         NetworkClientMessages::PartialEncodedChunkRequest(request, msg_hash)
                     }
                     RoutedMessageBody::PartialEncodedChunkResponse(response) => {
-                        NetworkClientMessages::PartialEncodedChunkResponse(response)
+                        NetworkClientMessages::PartialEncodedChunkResponse(
+                            response,
+                            routed_message.author,
+                        )
                     }
                     RoutedMessageBody::PartialEncodedChunk(partial_encoded_chunk) => {
-                        NetworkClientMessages::PartialEncodedChunk(PartialEncodedChunk::V1(
-                            partial_encoded_chunk,
-                        ))
+                        NetworkClientMessages::PartialEncodedChunk(
+                            PartialEncodedChunk::V1(partial_encoded_chunk),
+                            routed_message.author,
+                        )
                     }
                     RoutedMessageBody::VersionedPartialEncodedChunk(chunk) => {
-                        NetworkClientMessages::PartialEncodedChunk(chunk)
+                        NetworkClientMessages::PartialEncodedChunk(chunk, routed_message.author)
                     }
                     RoutedMessageBody::PartialEncodedChunkForward(forward) => {
-                        NetworkClientMessages::PartialEncodedChunkForward(forward)
+                        NetworkClientMessages::PartialEncodedChunkForward(
+                            forward,
+                            routed_message.author,
+                        )
                     }
                     RoutedMessageBody::Ping(_)
                     | RoutedMessageBody::Pong(_)
@@ -494,7 +678,11 @@ impl PeerActor {
             | PeerMessage::BlockRequest(_)
             | PeerMessage::BlockHeadersRequest(_)
             | PeerMessage::EpochSyncRequest(_)
-            | PeerMessage::EpochSyncFinalizationRequest(_) => {
+            | PeerMessage::EpochSyncFinalizationRequest(_)
+            | PeerMessage::HandshakeProofOfWork(_)
+            | PeerMessage::CompressionOffer(_)
+            | PeerMessage::CompressionAccept(_)
+            | PeerMessage::Compressed(..) => {
                 error!(target: "network", "Peer receive_client_message received unexpected type: {:?}", msg);
                 return;
             }
@@ -505,10 +693,15 @@ impl PeerActor {
             }
         };
 
+        let span = tracing::Span::current();
+        let received = Clock::instant();
         self.client_addr
             .send(network_client_msg)
             .into_actor(self)
             .then(move |res, act, ctx| {
+                let queue_wait = Clock::instant().saturating_duration_since(received);
+                span.record("queue_wait_ms", &(queue_wait.as_millis() as u64));
+                let _entered = span.enter();
                 // Ban peer if client thinks received data is bad.
                 match res {
                     Ok(NetworkClientResponses::InvalidTx(err)) => {
@@ -590,6 +783,7 @@ impl PeerActor {
             ));
         } else {
             info!(target: "network", "Received invalid data {:?} from {}: {}", logging::pretty_vec(msg), self.peer_info, err);
+            self.report_misbehavior(Misbehavior::InvalidMessage);
         }
     }
 }
@@ -608,12 +802,20 @@ impl Actor for PeerActor {
         near_performance_metrics::actix::run_later(ctx, self.handshake_timeout, move |act, ctx| {
             if act.peer_status != PeerStatus::Ready {
                 info!(target: "network", "Handshake timeout expired for {}", act.peer_info);
+                act.report_misbehavior(Misbehavior::Timeout);
                 ctx.stop();
             }
         });
 
+        self.schedule_outbound_flush(ctx);
+
         // If outbound peer, initiate handshake.
         if self.peer_type == PeerType::Outbound {
+            if self.compression_threshold.is_some() {
+                self.send_message(&PeerMessage::CompressionOffer(
+                    compression::SUPPORTED_CODECS.to_vec(),
+                ));
+            }
             self.send_handshake(ctx);
         }
     }
@@ -678,6 +880,29 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                 return;
             }
         };
+        if let PeerMessage::Compressed(codec, compressed) = peer_msg {
+            let decompressed = compression::decompress(codec, &compressed)
+                .and_then(|msg| Ok((PeerMessage::try_from_slice(&msg)?, msg)));
+            match decompressed {
+                // Only a message is compressed, not a compressed one again.
+                Ok((PeerMessage::Compressed(..), _)) => {
+                    info!(target: "network", "Received a message compressed twice from {}", self.peer_info);
+                    self.report_misbehavior(Misbehavior::InvalidMessage);
+                    return;
+                }
+                Ok((decompressed_msg, decompressed)) => {
+                    if self.should_we_drop_msg_without_decoding(&decompressed) {
+                        return;
+                    }
+                    peer_msg = decompressed_msg;
+                }
+                Err(err) => {
+                    info!(target: "network", "Received invalid compressed data from {}: {}", self.peer_info, err);
+                    self.report_misbehavior(Misbehavior::InvalidMessage);
+                    return;
+                }
+            }
+        }
 
         // Drop duplicated messages routed within DROP_DUPLICATED_MESSAGES_PERIOD ms
         if let PeerMessage::Routed(msg) = &peer_msg {
@@ -700,6 +925,9 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
             self.txns_since_last_block.store(0, Ordering::Release);
         }
 
+        let span = self.message_span(peer_msg.msg_variant(), msg.len());
+        let _entered = span.enter();
+
         trace!(target: "network", "Received message: {}", peer_msg);
 
         self.on_receive_message();
@@ -711,6 +939,11 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
             NetworkMetrics::peer_message_bytes_rx(peer_msg.msg_variant()).as_ref(),
             msg.len() as u64,
         );
+        let category = peer_msg.category();
+        self.tracker.increment_received_category(category, msg.len() as u64);
+        metrics::PEER_MESSAGE_CATEGORY_BYTES
+            .with_label_values(&["received", category.as_str()])
+            .inc_by(msg.len() as u64);
 
         if let PeerMessage::HandshakeV2(handshake) = peer_msg {
             peer_msg = PeerMessage::Handshake(handshake.into());
@@ -751,6 +984,16 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                             Some(self.throttle_controller.clone()),
                         ));
                     }
+                    HandshakeFailureReason::ProofOfWorkRequired { seed, difficulty } => {
+                        if difficulty <= MAX_PROOF_OF_WORK_DIFFICULTY {
+                            debug!(target: "network", "Solving the puzzle of difficulty {} of {}", difficulty, peer_info);
+                            let nonce = proof_of_work::solve(&seed, difficulty);
+                            self.send_message(&PeerMessage::HandshakeProofOfWork(nonce));
+                            self.send_handshake(ctx);
+                            return;
+                        }
+                        warn!(target: "network", "Unable to connect to a node ({}) requiring a proof of work of difficulty {}, above {}", peer_info, difficulty, MAX_PROOF_OF_WORK_DIFFICULTY);
+                    }
                 }
                 ctx.stop();
             }
@@ -765,6 +1008,25 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                 let target_version = std::cmp::min(handshake.protocol_version, PROTOCOL_VERSION);
                 self.protocol_version = target_version;
 
+                // Before anything else, inbound peers prove they spent some work on connecting.
+                if self.peer_type == PeerType::Inbound
+                    && self.proof_of_work_difficulty > 0
+                    && !self.proof_of_work_solved
+                {
+                    let seed = *self
+                        .proof_of_work_seed
+                        .get_or_insert_with(|| CryptoHash(rand::thread_rng().gen()));
+                    self.send_message(&PeerMessage::HandshakeFailure(
+                        self.my_node_info.clone(),
+                        HandshakeFailureReason::ProofOfWorkRequired {
+                            seed,
+                            difficulty: self.proof_of_work_difficulty,
+                        },
+                    ));
+                    return;
+                    // Connection will be closed by a handshake timeout
+                }
+
                 if handshake.sender_chain_info.genesis_id != self.genesis_id {
                     debug!(target: "network", "Received connection from node with different genesis.");
                     ctx.address().do_send(SendMessage {
@@ -815,10 +1077,15 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                     return;
                 }
 
+                // The boot nodes dialed by host name are known by it only, `peer_addr` is the
+                // address of the proxy if there is one.
+                let dialed_by_host = self.peer_type == PeerType::Outbound
+                    && self.peer_info.as_ref().as_ref().map_or(false, |info| info.addr.is_none());
                 let peer_info = PeerInfo {
                     id: handshake.sender_peer_id.clone(),
                     addr: handshake
                         .sender_listen_port
+                        .filter(|_| !dialed_by_host)
                         .map(|port| SocketAddr::new(self.peer_addr.ip(), port)),
                     account_id: None,
                 };
@@ -860,6 +1127,39 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for PeerActor {
                     })
                     .wait(ctx);
             }
+            (PeerType::Inbound, PeerStatus::Connecting, PeerMessage::CompressionOffer(codecs)) => {
+                if self.compression_threshold.is_none() {
+                    return;
+                }
+                if let Some(codec) = compression::pick_codec(&codecs) {
+                    debug!(target: "network", "Compressing the messages to {} with {:?}", self.peer_addr, codec);
+                    self.compression = Some(codec);
+                    self.send_message(&PeerMessage::CompressionAccept(codec));
+                }
+            }
+            (PeerType::Outbound, PeerStatus::Connecting, PeerMessage::CompressionAccept(codec)) => {
+                if self.compression_threshold.is_some()
+                    && compression::SUPPORTED_CODECS.contains(&codec)
+                {
+                    debug!(target: "network", "Compressing the messages to {} with {:?}", self.peer_addr, codec);
+                    self.compression = Some(codec);
+                }
+            }
+            (
+                PeerType::Inbound,
+                PeerStatus::Connecting,
+                PeerMessage::HandshakeProofOfWork(nonce),
+            ) => match self.proof_of_work_seed {
+                Some(seed)
+                    if proof_of_work::verify(&seed, self.proof_of_work_difficulty, nonce) =>
+                {
+                    self.proof_of_work_solved = true;
+                }
+                _ => {
+                    info!(target: "network", "{:?}: Inbound peer {:?} sent an invalid proof of work. Disconnect.", self.my_node_id(), self.peer_addr);
+                    ctx.stop();
+                }
+            },
             (_, PeerStatus::Connecting, PeerMessage::LastEdge(edge)) => {
                 // This message will be received only if we started the connection.
                 if self.peer_type == PeerType::Inbound {
@@ -1027,9 +1327,12 @@ impl Handler<SendMessage> for PeerActor {
     type Result = ();
 
     #[perf]
-    fn handle(&mut self, msg: SendMessage, _: &mut Self::Context) {
+    fn handle(&mut self, msg: SendMessage, _ctx: &mut Self::Context) {
         #[cfg(feature = "delay_detector")]
         let _d = delay_detector::DelayDetector::new("send message".into());
+        #[cfg(feature = "test_features")]
+        self.send_message_with_chaos(_ctx, &msg.message);
+        #[cfg(not(feature = "test_features"))]
         self.send_message(&msg.message);
     }
 }
@@ -1038,9 +1341,12 @@ impl Handler<Arc<SendMessage>> for PeerActor {
     type Result = ();
 
     #[perf]
-    fn handle(&mut self, msg: Arc<SendMessage>, _: &mut Self::Context) {
+    fn handle(&mut self, msg: Arc<SendMessage>, _ctx: &mut Self::Context) {
         #[cfg(feature = "delay_detector")]
         let _d = delay_detector::DelayDetector::new("send message".into());
+        #[cfg(feature = "test_features")]
+        self.send_message_with_chaos(_ctx, &msg.as_ref().message);
+        #[cfg(not(feature = "test_features"))]
         self.send_message(&msg.as_ref().message);
     }
 }
@@ -1061,6 +1367,10 @@ impl Handler<QueryPeerStats> for PeerActor {
                 self.tracker.sent_bytes.count_per_min(),
                 self.tracker.received_bytes.count_per_min(),
             ),
+            sent: self.tracker.sent_bytes.windows(),
+            received: self.tracker.received_bytes.windows(),
+            categories: self.tracker.categories(),
+            outbound_queued_bytes: self.outbound_limiter.queued_bytes() as u64,
         }
     }
 }
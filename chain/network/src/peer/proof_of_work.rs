@@ -0,0 +1,50 @@
+//! Puzzle the inbound peers solve before their handshake is processed, when enabled.
+//!
+//! The node sends a random seed with the required difficulty, and the peer finds a nonce such that
+//! the hash of the seed followed by the nonce starts with as many zero bits as the difficulty.
+//! Finding it takes `2^difficulty` hashes on average, checking it a single one, which makes
+//! flooding a public node with connections that much more expensive than serving them.
+
+use near_primitives::hash::{hash, CryptoHash};
+
+/// Highest difficulty the node solves, a few seconds of hashing, so that a peer can't keep it
+/// busy with an impossible puzzle.
+pub(crate) const MAX_PROOF_OF_WORK_DIFFICULTY: u8 = 24;
+
+fn leading_zero_bits(bytes: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in bytes {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+pub(crate) fn verify(seed: &CryptoHash, difficulty: u8, nonce: u64) -> bool {
+    let digest = hash(&[&seed.0[..], &nonce.to_le_bytes()].concat());
+    leading_zero_bits(&digest.0) >= difficulty as u32
+}
+
+/// The lowest nonce solving the puzzle.
+pub(crate) fn solve(seed: &CryptoHash, difficulty: u8) -> u64 {
+    (0..).find(|nonce| verify(seed, difficulty, *nonce)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proof_of_work() {
+        assert_eq!(leading_zero_bits(&[0, 0, 0b0001_0000, 0]), 19);
+        assert_eq!(leading_zero_bits(&[0, 0]), 16);
+
+        let seed = hash(b"seed");
+        let nonce = solve(&seed, 12);
+        assert!(verify(&seed, 12, nonce));
+        assert!((0..nonce).all(|nonce| !verify(&seed, 12, nonce)));
+        assert!(verify(&seed, 0, 0));
+    }
+}
@@ -13,74 +13,197 @@
 // limitations under the License.
 
 use std::collections::VecDeque;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant};
+
+use near_network_primitives::types::{Transfer, TransferWindows};
+use near_primitives::time::Clock;
 
 const MINUTE: Duration = Duration::from_secs(60);
+const FIVE_MINUTES: Duration = Duration::from_secs(5 * 60);
+const HOUR: Duration = Duration::from_secs(60 * 60);
 
-/// Stores list of entries for `RateCounter`
+/// Stores list of entries for `Window`
 struct Entry {
     /// bytes since last reset
     bytes: u64,
+    /// Number of transfers grouped in the entry.
+    count: u64,
     /// Time we created the entry.
-    recorded: SystemTime,
+    recorded: Instant,
 }
 
-/// A rate counter tracks number of transfers, the amount of data exchanged and the rate of transfer
-/// over the last minute.
-pub struct RateCounter {
+/// Transfers of the last `span`. The transfers within `resolution` of the first one of an entry
+/// are grouped in it, so that long windows don't keep every transfer.
+struct Window {
+    span: Duration,
+    resolution: Duration,
     entries: VecDeque<Entry>,
     bytes_sum: u64,
+    count_sum: u64,
+}
+
+impl Window {
+    fn new(span: Duration, resolution: Duration) -> Self {
+        Window { span, resolution, entries: VecDeque::new(), bytes_sum: 0, count_sum: 0 }
+    }
+
+    fn increment(&mut self, bytes: u64, now: Instant) {
+        match self.entries.back_mut() {
+            Some(entry) if now.saturating_duration_since(entry.recorded) < self.resolution => {
+                entry.bytes += bytes;
+                entry.count += 1;
+            }
+            _ => self.entries.push_back(Entry { bytes, count: 1, recorded: now }),
+        }
+        self.bytes_sum += bytes;
+        self.count_sum += 1;
+        self.truncate(now);
+    }
+
+    /// Transfers of the window ending at `now`, which may be later than the last increment.
+    fn transfer(&self, now: Instant) -> Transfer {
+        let mut transfer = Transfer { bytes: self.bytes_sum, count: self.count_sum };
+        for entry in self.entries.iter() {
+            if now.saturating_duration_since(entry.recorded) <= self.span {
+                break;
+            }
+            transfer.bytes -= entry.bytes;
+            transfer.count -= entry.count;
+        }
+        transfer
+    }
+
+    fn truncate(&mut self, now: Instant) {
+        // Remove entries older than the span.
+        while let Some(entry) = self.entries.front() {
+            if now.saturating_duration_since(entry.recorded) <= self.span {
+                break;
+            }
+            let entry = self.entries.pop_front().unwrap();
+            self.bytes_sum -= entry.bytes;
+            self.count_sum -= entry.count;
+        }
+    }
+}
+
+/// A rate counter tracks number of transfers, the amount of data exchanged and the rate of transfer
+/// over the last minute, 5 minutes and hour.
+pub struct RateCounter {
+    /// Every transfer of the last minute.
+    minute: Window,
+    five_minutes: Window,
+    hour: Window,
 }
 
 impl RateCounter {
     pub fn new() -> Self {
-        RateCounter { entries: VecDeque::new(), bytes_sum: 0 }
+        RateCounter {
+            minute: Window::new(MINUTE, Duration::from_secs(0)),
+            five_minutes: Window::new(FIVE_MINUTES, Duration::from_secs(1)),
+            hour: Window::new(HOUR, Duration::from_secs(10)),
+        }
     }
 
     /// Increment number of bytes transferred, updating counts and rates.
     pub fn increment(&mut self, bytes: u64) {
-        let now = SystemTime::now();
-        self.entries.push_back(Entry { bytes, recorded: now });
-        self.bytes_sum += bytes;
-        self.truncate(now);
+        let now = Clock::instant();
+        self.minute.increment(bytes, now);
+        self.five_minutes.increment(bytes, now);
+        self.hour.increment(bytes, now);
     }
 
     pub fn bytes_per_min(&self) -> u64 {
-        self.bytes_sum
+        self.minute.bytes_sum
     }
 
     pub fn count_per_min(&self) -> u64 {
-        self.entries.len() as u64
+        self.minute.count_sum
     }
 
-    fn truncate(&mut self, now: SystemTime) {
-        // Remove entries older than 1m.
-        while !self.entries.is_empty() && self.entries.front().unwrap().recorded < now - MINUTE {
-            self.bytes_sum -= self.entries.pop_front().unwrap().bytes;
+    /// Transfers of the last minute, 5 minutes and hour, up to now.
+    pub fn windows(&self) -> TransferWindows {
+        let now = Clock::instant();
+        TransferWindows {
+            one_minute: self.minute.transfer(now),
+            five_minutes: self.five_minutes.transfer(now),
+            one_hour: self.hour.transfer(now),
         }
     }
+
+    #[cfg(test)]
+    fn truncate(&mut self, now: Instant) {
+        self.minute.truncate(now);
+        self.five_minutes.truncate(now);
+        self.hour.truncate(now);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use near_primitives::time::Utc;
 
     #[test]
     fn test_rate_counter() {
+        Clock::set_fake(Utc::now());
         let mut rc = RateCounter::new();
 
         rc.increment(1000);
         assert_eq!(rc.bytes_per_min(), 1000);
         assert_eq!(rc.count_per_min(), 1);
 
+        Clock::advance(Duration::from_secs(30));
         rc.increment(123);
 
         assert_eq!(rc.bytes_per_min(), 1123);
         assert_eq!(rc.count_per_min(), 2);
 
-        rc.truncate(SystemTime::now() + MINUTE + Duration::from_millis(1));
+        // The first entry is a minute old.
+        Clock::advance(MINUTE - Duration::from_secs(30));
+        rc.increment(10);
+        assert_eq!(rc.bytes_per_min(), 1133);
+        assert_eq!(rc.count_per_min(), 3);
+
+        Clock::advance(Duration::from_millis(1));
+        rc.increment(1);
+        assert_eq!(rc.bytes_per_min(), 134);
+        assert_eq!(rc.count_per_min(), 3);
+
+        rc.truncate(Clock::instant() + MINUTE + Duration::from_millis(1));
 
         assert_eq!(rc.bytes_per_min(), 0);
         assert_eq!(rc.count_per_min(), 0);
+        Clock::reset();
+    }
+
+    #[test]
+    fn test_rate_counter_windows() {
+        Clock::set_fake(Utc::now());
+        let mut rc = RateCounter::new();
+
+        // Grouped by second in the 5 minutes window and by 10 seconds in the hour one.
+        for _ in 0..10 {
+            rc.increment(100);
+            Clock::advance(Duration::from_millis(500));
+        }
+        Clock::advance(2 * MINUTE);
+        rc.increment(1);
+        let windows = rc.windows();
+        assert_eq!(windows.one_minute, Transfer { bytes: 1, count: 1 });
+        assert_eq!(windows.five_minutes, Transfer { bytes: 1001, count: 11 });
+        assert_eq!(windows.one_hour, Transfer { bytes: 1001, count: 11 });
+        assert_eq!(rc.five_minutes.entries.len(), 6);
+        assert_eq!(rc.hour.entries.len(), 2);
+
+        // The windows slide without increments.
+        Clock::advance(FIVE_MINUTES - 2 * MINUTE);
+        let windows = rc.windows();
+        assert_eq!(windows.one_minute, Transfer::default());
+        assert_eq!(windows.five_minutes, Transfer { bytes: 1, count: 1 });
+        assert_eq!(windows.one_hour, Transfer { bytes: 1001, count: 11 });
+
+        Clock::advance(HOUR);
+        assert_eq!(rc.windows(), TransferWindows::default());
+        Clock::reset();
     }
 }
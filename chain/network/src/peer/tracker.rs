@@ -1,4 +1,7 @@
+use std::collections::HashMap;
+
 use crate::peer::rate_counter::RateCounter;
+use near_network_primitives::types::{CategoryTransferStats, MessageCategory};
 use near_primitives::hash::CryptoHash;
 
 /// Maximum number of requests and responses to track.
@@ -44,6 +47,10 @@ pub struct Tracker {
     pub(crate) sent_bytes: RateCounter,
     /// Bytes we've received.
     pub(crate) received_bytes: RateCounter,
+    /// Bytes we've sent, per category of message.
+    sent_by_category: HashMap<MessageCategory, RateCounter>,
+    /// Bytes we've received and decoded, per category of message.
+    received_by_category: HashMap<MessageCategory, RateCounter>,
     /// Sent requests.
     pub(crate) requested: CircularUniqueQueue,
     /// Received elements.
@@ -55,6 +62,8 @@ impl Default for Tracker {
         Tracker {
             sent_bytes: RateCounter::new(),
             received_bytes: RateCounter::new(),
+            sent_by_category: HashMap::new(),
+            received_by_category: HashMap::new(),
             requested: CircularUniqueQueue::new(MAX_TRACK_SIZE),
             received: CircularUniqueQueue::new(MAX_TRACK_SIZE),
         }
@@ -66,8 +75,39 @@ impl Tracker {
         self.received_bytes.increment(size);
     }
 
-    pub(crate) fn increment_sent(&mut self, size: u64) {
+    pub(crate) fn increment_sent(&mut self, category: MessageCategory, size: u64) {
         self.sent_bytes.increment(size);
+        self.sent_by_category.entry(category).or_insert_with(RateCounter::new).increment(size);
+    }
+
+    /// Accounts a received message to its category, once decoded. It was already counted in
+    /// `received_bytes` by `increment_received`.
+    pub(crate) fn increment_received_category(&mut self, category: MessageCategory, size: u64) {
+        self.received_by_category.entry(category).or_insert_with(RateCounter::new).increment(size);
+    }
+
+    /// Transfers of the categories of messages exchanged with the peer.
+    pub(crate) fn categories(&self) -> Vec<CategoryTransferStats> {
+        MessageCategory::ALL
+            .iter()
+            .filter(|category| {
+                self.sent_by_category.contains_key(category)
+                    || self.received_by_category.contains_key(category)
+            })
+            .map(|&category| CategoryTransferStats {
+                category,
+                sent: self
+                    .sent_by_category
+                    .get(&category)
+                    .map(RateCounter::windows)
+                    .unwrap_or_default(),
+                received: self
+                    .received_by_category
+                    .get(&category)
+                    .map(RateCounter::windows)
+                    .unwrap_or_default(),
+            })
+            .collect()
     }
 
     pub(crate) fn has_received(&self, hash: &CryptoHash) -> bool {
@@ -93,6 +133,25 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_tracker_categories() {
+        let mut tracker = Tracker::default();
+        tracker.increment_sent(MessageCategory::Chunks, 100);
+        tracker.increment_sent(MessageCategory::Blocks, 10);
+        tracker.increment_received(50);
+        tracker.increment_received_category(MessageCategory::Chunks, 50);
+        assert_eq!(tracker.sent_bytes.bytes_per_min(), 110);
+
+        let categories = tracker.categories();
+        assert_eq!(
+            categories.iter().map(|stats| stats.category).collect::<Vec<_>>(),
+            vec![MessageCategory::Blocks, MessageCategory::Chunks]
+        );
+        assert_eq!(categories[1].sent.one_minute.bytes, 100);
+        assert_eq!(categories[1].received.one_hour.bytes, 50);
+        assert_eq!(categories[0].received.one_minute.count, 0);
+    }
+
     #[test]
     #[should_panic]
     fn test_circular_queue_zero_capacity() {
@@ -0,0 +1,98 @@
+use near_crypto::PublicKey;
+use near_primitives::network::PeerId;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+use std::{fs, io};
+
+/// Set of peers allowed to connect to a node running in a private network.
+///
+/// The list is read from a file with one peer id (the peer public key, e.g. `ed25519:...`) per
+/// line. Empty lines and lines starting with `#` are ignored. The file is read again whenever its
+/// modification time changes.
+pub(crate) struct PeerAllowlist {
+    path: PathBuf,
+    peers: HashSet<PeerId>,
+    modified: Option<SystemTime>,
+}
+
+impl PeerAllowlist {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut allowlist =
+            PeerAllowlist { path: path.to_path_buf(), peers: HashSet::new(), modified: None };
+        allowlist.reload()?;
+        Ok(allowlist)
+    }
+
+    pub fn contains(&self, peer_id: &PeerId) -> bool {
+        self.peers.contains(peer_id)
+    }
+
+    /// Reads the file again if it was modified since it was last read.
+    /// Returns whether the allowlist was reloaded.
+    pub fn reload_if_modified(&mut self) -> io::Result<bool> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+        if modified.is_some() && modified == self.modified {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    fn reload(&mut self) -> io::Result<()> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+        self.peers = parse_allowlist(&fs::read_to_string(&self.path)?)?;
+        self.modified = modified;
+        Ok(())
+    }
+}
+
+fn parse_allowlist(content: &str) -> io::Result<HashSet<PeerId>> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            PublicKey::from_str(line).map(PeerId::new).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid peer id `{}` in allowlist: {}", line, err),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{KeyType, SecretKey};
+    use std::io::Write;
+
+    fn peer_id(seed: &str) -> PeerId {
+        PeerId::new(SecretKey::from_seed(KeyType::ED25519, seed).public_key())
+    }
+
+    #[test]
+    fn test_allowlist_reload() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "# consortium members\n{}\n", peer_id("alice")).unwrap();
+        let mut allowlist = PeerAllowlist::load(file.path()).unwrap();
+        assert!(allowlist.contains(&peer_id("alice")));
+        assert!(!allowlist.contains(&peer_id("bob")));
+        assert!(!allowlist.reload_if_modified().unwrap());
+
+        fs::write(file.path(), format!("{}\n", peer_id("bob"))).unwrap();
+        // Make sure the modification time differs on file systems with coarse timestamps.
+        allowlist.modified = None;
+        assert!(allowlist.reload_if_modified().unwrap());
+        assert!(!allowlist.contains(&peer_id("alice")));
+        assert!(allowlist.contains(&peer_id("bob")));
+    }
+
+    #[test]
+    fn test_invalid_allowlist() {
+        assert!(parse_allowlist("not a peer id").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+//! Autonomous system (AS) numbers of the IPs of the peers, for the diversity of the peer set.
+//!
+//! A node which peers all sit in the networks of the same one or two cloud providers can be
+//! eclipsed by them. With a database of the AS numbers, the node keeps the peers of any single AS
+//! below a fraction of its peers, both when dialing and when accepting peers.
+//!
+//! The database is a CSV file in the format of the MaxMind GeoLite2 ASN CSV files, with one range
+//! of IPs in CIDR notation and its AS number per line, e.g. `1.0.0.0/24,13335,CLOUDFLARENET`. The
+//! first line is the header, the other columns are ignored, and the IPv4 and IPv6 ranges may be
+//! in the same file.
+
+use near_network_primitives::types::{ip_to_u128, IpNetwork};
+use std::net::IpAddr;
+use std::path::Path;
+use std::{fs, io};
+
+pub(crate) type Asn = u32;
+
+pub(crate) struct AsnDatabase {
+    /// First and last IP of the ranges, see `IpNetwork::range`, with their AS numbers, ordered
+    /// and disjoint.
+    ranges: Vec<(u128, u128, Asn)>,
+}
+
+impl AsnDatabase {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        parse_database(&fs::read_to_string(path)?)
+    }
+
+    pub fn lookup(&self, ip: &IpAddr) -> Option<Asn> {
+        let ip = ip_to_u128(ip);
+        let index = self.ranges.partition_point(|(first, _, _)| *first <= ip).checked_sub(1)?;
+        let (_, last, asn) = self.ranges[index];
+        if ip <= last {
+            Some(asn)
+        } else {
+            None
+        }
+    }
+
+    pub fn num_ranges(&self) -> usize {
+        self.ranges.len()
+    }
+}
+
+fn parse_database(content: &str) -> io::Result<AsnDatabase> {
+    let mut ranges = content
+        .lines()
+        .skip(1)
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let mut fields = line.split(',');
+            let network = fields.next().unwrap_or_default().trim().parse::<IpNetwork>();
+            let asn = fields.next().unwrap_or_default().trim().parse::<Asn>();
+            match (network, asn) {
+                (Ok(network), Ok(asn)) => {
+                    let (first, last) = network.range();
+                    Ok((first, last, asn))
+                }
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid line {} of the ASN database: {}", index + 2, line),
+                )),
+            }
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    ranges.sort();
+    if let Some(window) = ranges.windows(2).find(|window| window[1].0 <= window[0].1) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Overlapping ranges of AS {} and AS {}", window[0].2, window[1].2),
+        ));
+    }
+    Ok(AsnDatabase { ranges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asn_database() {
+        let database = parse_database(
+            "network,autonomous_system_number,autonomous_system_organization\n\
+             1.0.0.0/24,13335,CLOUDFLARENET\n\
+             3.0.0.0/9,16509,AMAZON-02\n\
+             \n\
+             2600:1f00::/24,16509,\"Amazon.com, Inc.\"\n\
+             1.0.4.0/22,38803,GTELECOM\n",
+        )
+        .unwrap();
+        assert_eq!(database.num_ranges(), 4);
+        let lookup = |ip: &str| database.lookup(&ip.parse().unwrap());
+        assert_eq!(lookup("1.0.0.0"), Some(13335));
+        assert_eq!(lookup("1.0.0.255"), Some(13335));
+        assert_eq!(lookup("1.0.1.0"), None);
+        assert_eq!(lookup("1.0.7.1"), Some(38803));
+        assert_eq!(lookup("3.127.0.1"), Some(16509));
+        assert_eq!(lookup("3.128.0.1"), None);
+        assert_eq!(lookup("0.0.0.1"), None);
+        assert_eq!(lookup("2600:1f18::1"), Some(16509));
+        assert_eq!(lookup("::ffff:1.0.0.1"), Some(13335));
+        assert_eq!(lookup("2a00::1"), None);
+
+        assert!(parse_database("network,asn\n1.0.0.0/24,x\n").is_err());
+        assert!(parse_database("network,asn\n1.0.0.0/16,1\n1.0.1.0/24,2\n").is_err());
+    }
+}
@@ -1,2 +1,7 @@
+mod allowlist;
+mod asn;
 pub(crate) mod peer_manager_actor;
 pub(crate) mod peer_store;
+mod port_mapping;
+mod reputation;
+mod socks5;
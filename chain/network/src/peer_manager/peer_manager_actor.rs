@@ -1,7 +1,17 @@
+#[cfg(feature = "test_features")]
+use crate::chaos::NetworkChaos;
 use crate::common::message_wrapper::{ActixMessageResponse, ActixMessageWrapper};
 use crate::peer::codec::Codec;
+use crate::peer::outbound_limiter::{
+    OutboundLimiter, SharedTokenBucket, TokenBucket, MAX_QUEUED_BYTES,
+};
 use crate::peer::peer_actor::PeerActor;
+use crate::peer_manager::allowlist::PeerAllowlist;
+use crate::peer_manager::asn::AsnDatabase;
 use crate::peer_manager::peer_store::{PeerStore, TrustLevel};
+use crate::peer_manager::port_mapping::{self, MAPPING_LIFETIME};
+use crate::peer_manager::reputation::{PeerReputation, Verdict};
+use crate::peer_manager::socks5::{self, Target};
 #[cfg(all(
     feature = "test_features",
     feature = "protocol_feature_routing_exchange_algorithm"
@@ -33,11 +43,12 @@ use actix::{
 use futures::task::Poll;
 use futures::{future, Stream, StreamExt};
 use near_network_primitives::types::{
-    AccountOrPeerIdOrHash, Ban, BlockedPorts, InboundTcpConnect, KnownPeerState, KnownPeerStatus,
-    KnownProducer, NetworkConfig, NetworkViewClientMessages, NetworkViewClientResponses,
-    OutboundTcpConnect, PeerIdOrHash, PeerManagerRequest, PeerType, Ping, Pong, QueryPeerStats,
-    RawRoutedMessage, ReasonForBan, RoutedMessage, RoutedMessageBody, RoutedMessageFrom,
-    StateResponseInfo,
+    AccountOrPeerIdOrHash, Ban, BlockedPorts, CategoryTransferStats, InboundTcpConnect,
+    KnownPeerState, KnownPeerStatus, KnownProducer, Misbehavior, NetworkConfig,
+    NetworkViewClientMessages, NetworkViewClientResponses, OutboundTcpConnect, PeerIdOrHash,
+    PeerManagerRequest, PeerTransferStats, PeerType, Ping, Pong, QueryPeerStats, RawRoutedMessage,
+    ReasonForBan, RoutedMessage, RoutedMessageBody, RoutedMessageFrom, StateResponseInfo,
+    TransferWindows,
 };
 use near_performance_metrics::framed_write::FramedWrite;
 use near_performance_metrics_macros::perf;
@@ -53,12 +64,12 @@ use rand::seq::{IteratorRandom, SliceRandom};
 use rand::thread_rng;
 use std::cmp;
 use std::collections::{HashMap, HashSet};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::pin::Pin;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
 use tokio::sync::Semaphore;
 use tokio_util::sync::PollSemaphore;
 use tracing::{debug, error, info, trace, warn};
@@ -94,6 +105,11 @@ const BROAD_CAST_EDGES_MAX_WORK_ALLOWED: Duration = Duration::from_millis(50);
 const WAIT_FOR_SYNC_DELAY: Duration = Duration::from_millis(1_000);
 /// How often should we update the routing table
 const UPDATE_ROUTING_TABLE_INTERVAL: Duration = Duration::from_millis(1_000);
+/// Timeout of the outbound connections through the proxy, which connects to the peer itself,
+/// through several relays in the case of Tor.
+const PROXY_CONNECT_TIMEOUT: Duration = Duration::from_millis(10_000);
+/// Time to wait to map the listening port again after failing to.
+const PORT_MAPPING_RETRY_DELAY: Duration = Duration::from_millis(60_000);
 
 /// Max number of messages we received from peer, and they are in progress, before we start throttling.
 /// Disabled for now (TODO PUT UNDER FEATURE FLAG)
@@ -101,6 +117,9 @@ const MAX_MESSAGES_COUNT: usize = usize::MAX;
 /// Max total size of all messages that are in progress, before we start throttling.
 /// Disabled for now (TODO PUT UNDER FEATURE FLAG)
 const MAX_MESSAGES_TOTAL_SIZE: usize = usize::MAX;
+/// Tiers of the peers in the metrics of their bandwidth: the validators which announced their
+/// account, the archival nodes, and the other peers.
+const PEER_TIERS: [&str; 3] = ["validator", "archival", "regular"];
 
 macro_rules! unwrap_or_error(($obj: expr, $error: expr) => (match $obj {
     Ok(result) => result,
@@ -118,6 +137,14 @@ struct ActivePeer {
     received_bytes_per_sec: u64,
     /// Number of bytes we've sent to the peer.
     sent_bytes_per_sec: u64,
+    /// Transfers to the peer over the last minute, 5 minutes and hour.
+    sent: TransferWindows,
+    /// Transfers from the peer over the last minute, 5 minutes and hour.
+    received: TransferWindows,
+    /// Transfers to and from the peer per category of messages.
+    categories: Vec<CategoryTransferStats>,
+    /// Bytes of the messages queued to be sent to the peer.
+    outbound_queued_bytes: u64,
     /// Last time requested peers.
     last_time_peer_requested: Instant,
     /// Last time we received a message from this peer.
@@ -169,6 +196,16 @@ pub struct PeerManagerActor {
     pending_incoming_connections_counter: Arc<AtomicUsize>,
     /// Number of active peers, used for rate limiting.
     peer_counter: Arc<AtomicUsize>,
+    /// Bandwidth left to send messages to all the peers, if limited.
+    outbound_bandwidth: Option<SharedTokenBucket>,
+    /// Scores of the peers which misbehaved recently.
+    reputation: PeerReputation,
+    /// If set, only these peers may connect to us and be advertised to other peers.
+    peer_allowlist: Option<PeerAllowlist>,
+    /// AS numbers of the IPs, to limit the peers of any single autonomous system.
+    asn_database: Option<AsnDatabase>,
+    /// Public address the router forwards to the listening port, if mapped with NAT-PMP.
+    mapped_addr: Option<SocketAddr>,
     /// Used for testing, for disabling features.
     adv_helper: AdvHelper,
 }
@@ -181,6 +218,9 @@ struct AdvHelper {
     adv_disable_edge_signature_verification: bool,
     #[cfg(feature = "test_features")]
     adv_disable_edge_pruning: bool,
+    /// Faults injected in the messages sent to the peers.
+    #[cfg(feature = "test_features")]
+    chaos: Option<NetworkChaos>,
 }
 
 impl AdvHelper {
@@ -217,14 +257,38 @@ impl PeerManagerActor {
             panic!("Exceeded max peer limit: {}", MAX_NUM_PEERS);
         }
 
-        let peer_store = PeerStore::new(store.clone(), &config.boot_nodes)?;
+        let peer_store = PeerStore::new(
+            store.clone(),
+            &config.boot_nodes,
+            Some(config.peer_expiration_duration),
+        )?;
         debug!(target: "network", "Found known peers: {} (boot nodes={})", peer_store.len(), config.boot_nodes.len());
         debug!(target: "network", "Blacklist: {:?}", config.blacklist);
+        let peer_allowlist = match &config.peer_allowlist_file {
+            Some(path) => Some(PeerAllowlist::load(path)?),
+            None => None,
+        };
+        let asn_database = match &config.asn_database_file {
+            Some(path) => {
+                let asn_database = AsnDatabase::load(path)?;
+                info!(target: "network", "Loaded {} IP ranges from ASN database {}", asn_database.num_ranges(), path.display());
+                Some(asn_database)
+            }
+            None => None,
+        };
 
         let my_peer_id: PeerId = PeerId::new(config.public_key.clone());
         let routing_table = RoutingTableView::new(my_peer_id.clone(), store);
 
         let txns_since_last_block = Arc::new(AtomicUsize::new(0));
+        let outbound_bandwidth = config
+            .max_outbound_bytes_per_sec
+            .map(|rate| Arc::new(Mutex::new(TokenBucket::new(rate, Clock::instant()))));
+        let reputation = PeerReputation::new(
+            config.peer_score_half_life,
+            config.peer_score_demote_threshold,
+            config.peer_score_ban_threshold,
+        );
 
         Ok(PeerManagerActor {
             my_peer_id,
@@ -244,10 +308,22 @@ impl PeerManagerActor {
             txns_since_last_block,
             pending_incoming_connections_counter: Arc::new(AtomicUsize::new(0)),
             peer_counter: Arc::new(AtomicUsize::new(0)),
+            outbound_bandwidth,
+            reputation,
+            peer_allowlist,
+            asn_database,
+            mapped_addr: None,
             adv_helper: AdvHelper::default(),
         })
     }
 
+    /// Injects the faults of `chaos` in the messages sent to the peers.
+    #[cfg(feature = "test_features")]
+    pub fn with_chaos(mut self, chaos: NetworkChaos) -> Self {
+        self.adv_helper.chaos = Some(chaos);
+        self
+    }
+
     fn update_routing_table_and_prune_edges(
         &mut self,
         ctx: &mut Context<Self>,
@@ -435,6 +511,109 @@ impl PeerManagerActor {
         }
     }
 
+    /// Whether the peer is allowed in the network, always true if there is no allowlist.
+    fn is_peer_allowed(&self, peer_id: &PeerId) -> bool {
+        self.peer_allowlist.as_ref().map_or(true, |allowlist| allowlist.contains(peer_id))
+    }
+
+    /// Whether the IP of the peer is in the ranges allowed.
+    fn is_in_peer_ranges(&self, addr: &SocketAddr) -> bool {
+        self.config.peer_ranges.allows(&addr.ip())
+    }
+
+    /// Reads the ranges of IPs of the peers again and disconnects the peers no longer allowed.
+    fn reload_peer_ranges(&mut self) -> Result<(), String> {
+        let loader = self
+            .config
+            .peer_ranges_loader
+            .as_ref()
+            .ok_or_else(|| "The ranges of the peers can't be reloaded".to_string())?;
+        let peer_ranges = loader().map_err(|err| {
+            error!(target: "network", "Failed to reload the ranges of the peers: {}", err);
+            err
+        })?;
+        info!(target: "network", "Reloaded the ranges of the peers: {} allowed, {} denied", peer_ranges.allowed.len(), peer_ranges.denied.len());
+        self.config.peer_ranges = peer_ranges;
+        for (peer_id, active_peer) in self.active_peers.iter() {
+            if active_peer
+                .full_peer_info
+                .peer_info
+                .addr
+                .map_or(false, |addr| !self.is_in_peer_ranges(&addr))
+            {
+                debug!(target: "network", "Disconnecting peer {} out of the allowed ranges", peer_id);
+                active_peer.addr.do_send(PeerManagerRequest::UnregisterPeer);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reloads the ranges of IPs of the peers on every SIGHUP.
+    #[cfg(unix)]
+    fn reload_peer_ranges_on_sighup(&self, ctx: &mut Context<Self>) {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sighup = match signal(SignalKind::hangup()) {
+            Ok(sighup) => sighup,
+            Err(err) => {
+                error!(target: "network", "Failed to listen to SIGHUP: {}", err);
+                return;
+            }
+        };
+        let addr = ctx.address();
+        actix::spawn(async move {
+            while sighup.recv().await.is_some() {
+                addr.do_send(PeerManagerMessageRequest::ReloadPeerRanges);
+            }
+        });
+    }
+
+    /// Whether connecting to the peer at `addr` would exceed the peers allowed of its autonomous
+    /// system, never if there is no ASN database or its AS is unknown.
+    fn is_asn_limit_reached(&self, addr: &SocketAddr) -> bool {
+        let asn_database = match &self.asn_database {
+            Some(asn_database) => asn_database,
+            None => return false,
+        };
+        let asn = match asn_database.lookup(&addr.ip()) {
+            Some(asn) => asn,
+            None => return false,
+        };
+        let max_peers =
+            ((self.config.max_num_peers as f64 * self.config.max_peers_fraction_per_asn) as usize)
+                .max(1);
+        let peers = self
+            .active_peers
+            .values()
+            .filter_map(|active_peer| active_peer.full_peer_info.peer_info.addr)
+            .filter(|peer_addr| asn_database.lookup(&peer_addr.ip()) == Some(asn))
+            .count();
+        peers >= max_peers
+    }
+
+    /// Reloads the allowlist if its file changed and disconnects the peers which were removed.
+    fn reload_peer_allowlist(&mut self) {
+        let allowlist = match self.peer_allowlist.as_mut() {
+            Some(allowlist) => allowlist,
+            None => return,
+        };
+        match allowlist.reload_if_modified() {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(err) => {
+                // Keep the previous allowlist, the file may be in the middle of being rewritten.
+                error!(target: "network", "Failed to reload peer allowlist: {}", err);
+                return;
+            }
+        }
+        info!(target: "network", "Reloaded peer allowlist");
+        for (peer_id, active_peer) in self.active_peers.iter() {
+            if !self.is_peer_allowed(peer_id) {
+                debug!(target: "network", "Disconnecting peer {} removed from allowlist", peer_id);
+                active_peer.addr.do_send(PeerManagerRequest::UnregisterPeer);
+            }
+        }
+    }
+
     #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
     fn initialize_routing_table_exchange(
         &mut self,
@@ -531,6 +710,10 @@ impl PeerManagerActor {
                 full_peer_info,
                 sent_bytes_per_sec: 0,
                 received_bytes_per_sec: 0,
+                sent: TransferWindows::default(),
+                received: TransferWindows::default(),
+                categories: vec![],
+                outbound_queued_bytes: 0,
                 last_time_peer_requested: Clock::instant(),
                 last_time_received_message: Clock::instant(),
                 connection_established_time: Clock::instant(),
@@ -721,6 +904,26 @@ impl PeerManagerActor {
         }
     }
 
+    /// Counts a misbehavior against the score of the peer, banning it if it crossed the
+    /// threshold.
+    fn record_misbehavior(
+        &mut self,
+        ctx: &mut Context<Self>,
+        peer_id: &PeerId,
+        misbehavior: Misbehavior,
+    ) {
+        metrics::PEER_MISBEHAVIOR_TOTAL.with_label_values(&[misbehavior.as_str()]).inc();
+        match self.reputation.record(peer_id, misbehavior, Clock::instant()) {
+            Verdict::Tolerate => {
+                debug!(target: "network", "Peer {:?} misbehaved: {:?}", peer_id, misbehavior)
+            }
+            Verdict::Demote => {
+                info!(target: "network", "Demoting peer {:?} after misbehaving: {:?}", peer_id, misbehavior)
+            }
+            Verdict::Ban => self.try_ban_peer(ctx, peer_id, ReasonForBan::Abusive),
+        }
+    }
+
     /// Connects peer with given TcpStream and optional information if it's outbound.
     /// This might fail if the other peers drop listener at its endpoint while establishing connection.
     fn try_connect_peer(
@@ -733,8 +936,17 @@ impl PeerManagerActor {
     ) {
         let my_peer_id = self.my_peer_id.clone();
         let account_id = self.config.account_id.clone();
-        let server_addr = self.config.addr;
+        let server_addr = self.config.external_addr.or(self.mapped_addr).or(self.config.addr);
         let handshake_timeout = self.config.handshake_timeout;
+        let message_span_sample_rate = self.config.message_span_sample_rate;
+        let proof_of_work_difficulty = self.config.handshake_proof_of_work_difficulty;
+        let compression_threshold = self.config.compression_threshold_bytes;
+        let outbound_limiter = OutboundLimiter::new(
+            self.config.max_outbound_bytes_per_sec_per_peer,
+            self.outbound_bandwidth.clone(),
+            MAX_QUEUED_BYTES,
+            Clock::instant(),
+        );
         let client_addr = self.client_addr.clone();
         let view_client_addr = self.view_client_addr.clone();
 
@@ -749,7 +961,12 @@ impl PeerManagerActor {
             },
         };
 
-        let remote_addr = match stream.peer_addr() {
+        // Through the proxy, the stream is connected to the proxy rather than to the peer.
+        let remote_addr = match peer_info.as_ref().and_then(|peer_info| peer_info.addr) {
+            Some(remote_addr) => Ok(remote_addr),
+            None => stream.peer_addr(),
+        };
+        let remote_addr = match remote_addr {
             Ok(remote_addr) => remote_addr,
             _ => {
                 warn!(target: "network", "Failed establishing connection with {:?}", peer_info);
@@ -760,6 +977,9 @@ impl PeerManagerActor {
         let network_metrics = self.network_metrics.clone();
         let txns_since_last_block = Arc::clone(&self.txns_since_last_block);
 
+        #[cfg(feature = "test_features")]
+        let chaos = self.adv_helper.chaos.clone();
+
         // Start every peer actor on separate thread.
         let arbiter = Arbiter::new();
         let peer_counter = self.peer_counter.clone();
@@ -803,6 +1023,12 @@ impl PeerManagerActor {
                 txns_since_last_block,
                 peer_counter,
                 rate_limiter,
+                message_span_sample_rate,
+                outbound_limiter,
+                proof_of_work_difficulty,
+                compression_threshold,
+                #[cfg(feature = "test_features")]
+                chaos,
             )
         });
     }
@@ -842,10 +1068,20 @@ impl PeerManagerActor {
 
     /// Returns single random peer with close to the highest height
     fn highest_height_peers(&self) -> Vec<FullPeerInfo> {
-        // This finds max height among peers, and returns one peer close to such height.
-        let max_height = match self
+        // Demoted peers are only used to sync if there are no others.
+        let now = Clock::instant();
+        let mut peers: Vec<_> = self
             .active_peers
-            .values()
+            .iter()
+            .filter(|(peer_id, _)| !self.reputation.is_demoted(peer_id, now))
+            .map(|(_, active_peer)| active_peer)
+            .collect();
+        if peers.is_empty() {
+            peers = self.active_peers.values().collect();
+        }
+        // This finds max height among peers, and returns one peer close to such height.
+        let max_height = match peers
+            .iter()
             .map(|active_peers| active_peers.full_peer_info.chain_info.height)
             .max()
         {
@@ -853,8 +1089,8 @@ impl PeerManagerActor {
             None => return vec![],
         };
         // Find all peers whose height is within `highest_peer_horizon` from max height peer(s).
-        self.active_peers
-            .values()
+        peers
+            .into_iter()
             .filter_map(|active_peer| {
                 if active_peer
                     .full_peer_info
@@ -884,6 +1120,54 @@ impl PeerManagerActor {
         unconnected_peers.choose(&mut rand::thread_rng()).cloned()
     }
 
+    /// Returns a random boot node given by host name which isn't connected.
+    fn sample_boot_node_host(&self) -> Option<PeerId> {
+        let boot_nodes: Vec<_> = self
+            .config
+            .boot_node_hosts
+            .iter()
+            .map(|boot_node| &boot_node.id)
+            .filter(|peer_id| {
+                self.my_peer_id != **peer_id
+                    && !self.outgoing_peers.contains(*peer_id)
+                    && !self.active_peers.contains_key(*peer_id)
+                    && !self.peer_store.is_banned(peer_id)
+                    && self.is_peer_allowed(peer_id)
+            })
+            .collect();
+        boot_nodes.choose(&mut rand::thread_rng()).map(|peer_id| (*peer_id).clone())
+    }
+
+    /// Right after a restart, connects at once to the peers we were connected to when the node
+    /// stopped, instead of one at a time, so that a healthy peer set is re-established quickly.
+    fn connect_to_recently_connected_peers(&mut self, ctx: &mut Context<Self>) {
+        let peers: Vec<_> = self
+            .peer_store
+            .take_recently_connected_peers()
+            .into_iter()
+            .filter(|peer_info| {
+                self.my_peer_id != peer_info.id
+                    && self.config.addr != peer_info.addr
+                    && !self.outgoing_peers.contains(&peer_info.id)
+                    && !self.active_peers.contains_key(&peer_info.id)
+                    && self.is_peer_allowed(&peer_info.id)
+            })
+            .collect();
+        if peers.is_empty() {
+            return;
+        }
+        let total_connections = self.active_peers.len() + self.outgoing_peers.len();
+        let max_new_connections =
+            (self.config.ideal_connections_hi as usize).saturating_sub(total_connections);
+        info!(target: "network", "Reconnecting to {} of {} peers connected before the restart", peers.len().min(max_new_connections), peers.len());
+        for peer_info in peers.into_iter().take(max_new_connections) {
+            self.outgoing_peers.insert(peer_info.id.clone());
+            ctx.notify(PeerManagerMessageRequest::OutboundTcpConnect(OutboundTcpConnect {
+                peer_info,
+            }));
+        }
+    }
+
     /// Query current peers for more peers.
     fn query_active_peers_for_more_peers(&mut self, ctx: &mut Context<Self>) {
         let mut requests = futures::stream::FuturesUnordered::new();
@@ -984,7 +1268,7 @@ impl PeerManagerActor {
         near_performance_metrics::actix::run_later(
             ctx,
             WAIT_ON_TRY_UPDATE_NONCE,
-            move |act, _ctx| {
+            move |act, ctx| {
                 if let Some(cur_nonce) = act.local_peer_pending_update_nonce_request.get(&other) {
                     if *cur_nonce == nonce {
                         if let Some(peer) = act.active_peers.get(&other) {
@@ -992,6 +1276,7 @@ impl PeerManagerActor {
                             peer.addr.do_send(PeerManagerRequest::UnregisterPeer);
                         }
                         act.local_peer_pending_update_nonce_request.remove(&other);
+                        act.record_misbehavior(ctx, &other, Misbehavior::Timeout);
                     }
                 }
             },
@@ -1000,6 +1285,8 @@ impl PeerManagerActor {
 
     /// Periodically query peer actors for latest weight and traffic info.
     fn monitor_peer_stats_trigger(&mut self, ctx: &mut Context<Self>, interval: Duration) {
+        self.reputation.prune(Clock::instant());
+        self.update_peer_tier_metrics();
         for (peer_id, active_peer) in self.active_peers.iter() {
             let peer_id1 = peer_id.clone();
             active_peer
@@ -1007,8 +1294,17 @@ impl PeerManagerActor {
                 .send(QueryPeerStats {})
                 .into_actor(self)
                 .map(|result, _, _| result.map_err(|err| error!(target: "network", "Failed sending message(monitor_peer_stats): {}", err)))
-                .map(move |res, act, _| {
+                .map(move |res, act, ctx| {
                     let _ignore = res.map(|res| {
+                        if let Some(max_received) = act.config.peer_max_received_bytes_per_sec {
+                            if res.received_bytes_per_sec > max_received {
+                                act.record_misbehavior(
+                                    ctx,
+                                    &peer_id1,
+                                    Misbehavior::ExcessiveBandwidth,
+                                );
+                            }
+                        }
                         if res.is_abusive {
                             trace!(target: "network", "Banning peer {} for abuse ({} sent, {} recv)", peer_id1, res.message_counts.0, res.message_counts.1);
                             // TODO(MarX, #1586): Ban peer if we found them abusive. Fix issue with heavy
@@ -1021,6 +1317,10 @@ impl PeerManagerActor {
                             active_peer.full_peer_info.chain_info = res.chain_info;
                             active_peer.sent_bytes_per_sec = res.sent_bytes_per_sec;
                             active_peer.received_bytes_per_sec = res.received_bytes_per_sec;
+                            active_peer.sent = res.sent;
+                            active_peer.received = res.received;
+                            active_peer.categories = res.categories;
+                            active_peer.outbound_queued_bytes = res.outbound_queued_bytes;
                         }
                     });
                 })
@@ -1032,6 +1332,44 @@ impl PeerManagerActor {
         });
     }
 
+    /// Sets the gauges of the bandwidth and of the outbound queues of the peers per tier, from
+    /// their stats of the last round of `monitor_peer_stats_trigger`.
+    fn update_peer_tier_metrics(&mut self) {
+        let validators: HashSet<PeerId> = self
+            .routing_table_view
+            .get_announce_accounts()
+            .into_iter()
+            .map(|announce_account| announce_account.peer_id)
+            .collect();
+        // Connections, bytes sent and received over the last minute, and queued bytes per tier.
+        let mut tiers = [(0, 0, 0, 0); PEER_TIERS.len()];
+        for (peer_id, active_peer) in self.active_peers.iter() {
+            let tier = if validators.contains(peer_id) {
+                0
+            } else if active_peer.full_peer_info.chain_info.archival {
+                1
+            } else {
+                2
+            };
+            tiers[tier].0 += 1;
+            tiers[tier].1 += active_peer.sent.one_minute.bytes;
+            tiers[tier].2 += active_peer.received.one_minute.bytes;
+            tiers[tier].3 += active_peer.outbound_queued_bytes;
+        }
+        for (&tier, (connections, sent, received, queued)) in PEER_TIERS.iter().zip(tiers) {
+            metrics::PEER_TIER_CONNECTIONS.with_label_values(&[tier]).set(connections);
+            metrics::PEER_BANDWIDTH_BYTES.with_label_values(&["sent", tier]).set(sent as i64);
+            metrics::PEER_BANDWIDTH_BYTES
+                .with_label_values(&["received", tier])
+                .set(received as i64);
+            metrics::PEER_OUTBOUND_QUEUED_BYTES.with_label_values(&[tier]).set(queued as i64);
+        }
+        if let Some(outbound_bandwidth) = &self.outbound_bandwidth {
+            let tokens = outbound_bandwidth.lock().unwrap().tokens(Clock::instant());
+            metrics::OUTBOUND_BANDWIDTH_TOKENS.set(tokens as i64);
+        }
+    }
+
     /// Select one peer and send signal to stop connection to it gracefully.
     /// Selection process:
     ///     Create a safe set of peers, and among the remaining peers select one at random.
@@ -1095,8 +1433,12 @@ impl PeerManagerActor {
             safe_set.insert(peer_id.clone());
         }
 
+        // Demoted peers are never safe, and disconnected before the others.
+        let now = Clock::instant();
+        safe_set.retain(|peer_id| !self.reputation.is_demoted(peer_id, now));
+
         // Build valid candidate list to choose the peer to be removed. All peers outside the safe set.
-        let candidates = self
+        let mut candidates = self
             .active_peers
             .keys()
             .filter_map(
@@ -1109,6 +1451,9 @@ impl PeerManagerActor {
                 },
             )
             .collect::<Vec<_>>();
+        if candidates.iter().any(|peer_id| self.reputation.is_demoted(peer_id, now)) {
+            candidates.retain(|peer_id| self.reputation.is_demoted(peer_id, now));
+        }
 
         if let Some(peer_id) = candidates.choose(&mut rand::thread_rng()) {
             if let Some(active_peer) = self.active_peers.get(peer_id) {
@@ -1124,6 +1469,8 @@ impl PeerManagerActor {
     ///  - unban peers that have been banned for awhile,
     ///  - remove expired peers,
     fn monitor_peers_trigger(&mut self, ctx: &mut Context<Self>, max_interval: Duration) {
+        self.reload_peer_allowlist();
+
         let mut to_unban = vec![];
         for (peer_id, peer_state) in self.peer_store.iter() {
             if let KnownPeerStatus::Banned(_, last_banned) = peer_state.status {
@@ -1142,6 +1489,10 @@ impl PeerManagerActor {
             unwrap_or_error!(self.peer_store.peer_unban(&peer_id), "Failed to unban a peer");
         }
 
+        if self.is_outbound_bootstrap_needed() {
+            self.connect_to_recently_connected_peers(ctx);
+        }
+
         if self.is_outbound_bootstrap_needed() {
             if let Some(peer_info) = self.sample_random_peer(|peer_state| {
                 // Ignore connecting to ourself
@@ -1149,6 +1500,12 @@ impl PeerManagerActor {
                     || self.config.addr == peer_state.peer_info.addr
                     // Or to peers we are currently trying to connect to
                     || self.outgoing_peers.contains(&peer_state.peer_info.id)
+                    // Or to peers which are not allowed in the network
+                    || !self.is_peer_allowed(&peer_state.peer_info.id)
+                    // Or to peers out of the allowed ranges
+                    || peer_state.peer_info.addr.map_or(false, |addr| !self.is_in_peer_ranges(&addr))
+                    // Or to peers of an autonomous system which has enough peers
+                    || peer_state.peer_info.addr.map_or(false, |addr| self.is_asn_limit_reached(&addr))
             }) {
                 // Start monitor_peers_attempts from start after we discover the first healthy peer
                 if !self.started_connect_attempts {
@@ -1160,6 +1517,12 @@ impl PeerManagerActor {
                 ctx.notify(PeerManagerMessageRequest::OutboundTcpConnect(OutboundTcpConnect {
                     peer_info,
                 }));
+            } else if let Some(peer_id) = self.sample_boot_node_host() {
+                // The boot nodes given by host name are dialed by it, without address.
+                self.outgoing_peers.insert(peer_id.clone());
+                ctx.notify(PeerManagerMessageRequest::OutboundTcpConnect(OutboundTcpConnect {
+                    peer_info: PeerInfo { id: peer_id, addr: None, account_id: None },
+                }));
             } else {
                 self.query_active_peers_for_more_peers(ctx);
             }
@@ -1457,6 +1820,17 @@ impl PeerManagerActor {
             highest_height_peers: self.highest_height_peers(),
             sent_bytes_per_sec,
             received_bytes_per_sec,
+            peer_transfer_stats: self
+                .active_peers
+                .iter()
+                .map(|(peer_id, active_peer)| PeerTransferStats {
+                    peer_id: peer_id.clone(),
+                    sent: active_peer.sent,
+                    received: active_peer.received,
+                    categories: active_peer.categories.clone(),
+                })
+                .collect(),
+            peer_scores: self.reputation.scores(Clock::instant()),
             known_producers: self
                 .routing_table_view
                 .get_announce_accounts()
@@ -1472,6 +1846,53 @@ impl PeerManagerActor {
         }
     }
 
+    /// Asks the router to forward the listening port, and again half way through the lifetime of
+    /// the mapping to renew it. While mapped, the external port is advertised in the handshakes.
+    fn map_port(&mut self, ctx: &mut Context<Self>) {
+        let internal_port = match self.config.addr {
+            Some(addr) => addr.port(),
+            None => return,
+        };
+        let gateway = match self.config.port_mapping_gateway {
+            Some(gateway) => Ok(gateway),
+            None => port_mapping::default_gateway(),
+        };
+        let gateway = match gateway {
+            Ok(gateway) => gateway,
+            Err(err) => {
+                warn!(target: "network", "Failed to find the router to map the listening port: {}", err);
+                metrics::PORT_MAPPING_FAILURES_TOTAL.inc();
+                return;
+            }
+        };
+        port_mapping::map_port(gateway, internal_port, MAPPING_LIFETIME)
+            .into_actor(self)
+            .then(move |result, act, ctx| {
+                let delay = match result {
+                    Ok(mapping) => {
+                        if act.mapped_addr != Some(mapping.external_addr) {
+                            info!(target: "network", "Router {} forwards {} to the listening port", gateway, mapping.external_addr);
+                        }
+                        act.mapped_addr = Some(mapping.external_addr);
+                        metrics::PORT_MAPPING_ACTIVE.set(1);
+                        cmp::max(mapping.lifetime / 2, Duration::from_secs(1))
+                    }
+                    Err(err) => {
+                        warn!(target: "network", "Failed to map the listening port on router {}: {}", gateway, err);
+                        act.mapped_addr = None;
+                        metrics::PORT_MAPPING_ACTIVE.set(0);
+                        metrics::PORT_MAPPING_FAILURES_TOTAL.inc();
+                        PORT_MAPPING_RETRY_DELAY
+                    }
+                };
+                near_performance_metrics::actix::run_later(ctx, delay, move |act, ctx| {
+                    act.map_port(ctx);
+                });
+                actix::fut::ready(())
+            })
+            .spawn(ctx);
+    }
+
     fn push_network_info_trigger(&mut self, ctx: &mut Context<Self>, interval: Duration) {
         let network_info = self.get_network_info();
 
@@ -1499,6 +1920,41 @@ impl Stream for IncomingCrutch {
     }
 }
 
+/// Connects to `target` through `proxy`, which resolves it if it's a host name. Without a proxy,
+/// connects from the source IP given by `source_ip_for` the address of the target.
+async fn dial(
+    target: Target,
+    proxy: Option<SocketAddr>,
+    source_ip_for: impl FnOnce(&SocketAddr) -> Option<IpAddr>,
+) -> std::io::Result<TcpStream> {
+    if let Some(proxy) = proxy {
+        return socks5::connect(proxy, &target).await;
+    }
+    let addr = match target {
+        Target::Addr(addr) => addr,
+        Target::Host(host, port) => {
+            tokio::net::lookup_host((host.as_str(), port)).await?.next().ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("No address found for {}", host),
+                )
+            })?
+        }
+    };
+    connect_from(addr, source_ip_for(&addr)).await
+}
+
+/// Connects to `addr` from `source_ip`, or from the IP chosen by the OS if `None`.
+async fn connect_from(addr: SocketAddr, source_ip: Option<IpAddr>) -> std::io::Result<TcpStream> {
+    let source_ip = match source_ip {
+        Some(source_ip) => source_ip,
+        None => return TcpStream::connect(addr).await,
+    };
+    let socket = if addr.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    socket.bind(SocketAddr::new(source_ip, 0))?;
+    socket.connect(addr).await
+}
+
 impl Actor for PeerManagerActor {
     type Context = Context<Self>;
 
@@ -1541,6 +1997,16 @@ impl Actor for PeerManagerActor {
             ));
         }
 
+        #[cfg(unix)]
+        if self.config.peer_ranges_loader.is_some() {
+            self.reload_peer_ranges_on_sighup(ctx);
+        }
+
+        // Forward the listening port on the router, if asked to.
+        if self.config.port_mapping && self.config.external_addr.is_none() {
+            self.map_port(ctx);
+        }
+
         // Periodically push network information to client.
         self.push_network_info_trigger(ctx, self.config.push_info_period);
 
@@ -2000,7 +2466,9 @@ impl PeerManagerActor {
             let _d = delay_detector::DelayDetector::new("inbound tcp connect".into());
         }
 
-        if self.is_inbound_allowed() {
+        if msg.stream.peer_addr().map_or(false, |addr| !self.is_in_peer_ranges(&addr)) {
+            debug!(target: "network", "Inbound connection dropped (out of the allowed ranges).");
+        } else if self.is_inbound_allowed() {
             self.try_connect_peer(ctx.address(), msg.stream, PeerType::Inbound, None, None);
         } else {
             // TODO(1896): Gracefully drop inbound connection for other peer.
@@ -2027,7 +2495,21 @@ impl PeerManagerActor {
         #[cfg(feature = "delay_detector")]
         let _d = delay_detector::DelayDetector::new("outbound tcp connect".into());
         debug!(target: "network", "Trying to connect to {}", msg.peer_info);
-        if let Some(addr) = msg.peer_info.addr {
+        if msg.peer_info.addr.map_or(false, |addr| !self.is_in_peer_ranges(&addr)) {
+            debug!(target: "network", "Not connecting to {} out of the allowed ranges", msg.peer_info);
+            self.outgoing_peers.remove(&msg.peer_info.id);
+            return;
+        }
+        let target = match msg.peer_info.addr {
+            Some(addr) => Some(Target::Addr(addr)),
+            None => self
+                .config
+                .boot_node_hosts
+                .iter()
+                .find(|boot_node| boot_node.id == msg.peer_info.id)
+                .map(|boot_node| Target::Host(boot_node.host.clone(), boot_node.port)),
+        };
+        if let Some(target) = target {
             // The `connect` may take several minutes. This happens when the
             // `SYN` packet for establishing a TCP connection gets silently
             // dropped, in which case the default TCP timeout is applied. That's
@@ -2036,7 +2518,13 @@ impl PeerManagerActor {
             // Why exactly a second? It was hard-coded in a library we used
             // before, so we keep it to preserve behavior. Removing the timeout
             // completely was observed to break stuff for real on the testnet.
-            tokio::time::timeout(Duration::from_secs(1), TcpStream::connect(addr))
+            // Through the proxy, which connects to the peer itself, it's longer.
+            let proxy = self.config.proxy;
+            let timeout =
+                if proxy.is_some() { PROXY_CONNECT_TIMEOUT } else { Duration::from_secs(1) };
+            let config = self.config.clone();
+            let source_ip_for = move |addr: &SocketAddr| config.outbound_source_ip_for(addr);
+            let connect = tokio::time::timeout(timeout, dial(target.clone(), proxy, source_ip_for))
                 .into_actor(self)
                 .then(move |res, act, ctx| match res {
                     Ok(res) => match res {
@@ -2054,18 +2542,23 @@ impl PeerManagerActor {
                             actix::fut::ready(())
                         }
                         Err(err) => {
-                            info!(target: "network", "Error connecting to {}: {}", addr, err);
+                            info!(target: "network", "Error connecting to {}: {}", target, err);
                             act.outgoing_peers.remove(&msg.peer_info.id);
                             actix::fut::ready(())
                         }
                     },
                     Err(err) => {
-                        info!(target: "network", "Error connecting to {}: {}", addr, err);
+                        info!(target: "network", "Error connecting to {}: {}", target, err);
                         act.outgoing_peers.remove(&msg.peer_info.id);
                         actix::fut::ready(())
                     }
-                })
-                .wait(ctx);
+                });
+            // Connecting through the proxy takes long enough to hold the other messages back.
+            if proxy.is_some() {
+                ctx.spawn(connect);
+            } else {
+                ctx.wait(connect);
+            }
         } else {
             warn!(target: "network", "Trying to connect to peer with no public address: {:?}", msg.peer_info);
         }
@@ -2082,7 +2575,10 @@ impl PeerManagerActor {
         let _d = delay_detector::DelayDetector::new("consolidate".into());
 
         // Check if this is a blacklisted peer.
-        if msg.peer_info.addr.as_ref().map_or(true, |addr| self.is_blacklisted(addr)) {
+        // The boot nodes dialed by host name are known by it only.
+        if msg.peer_info.addr.as_ref().map_or(msg.peer_type == PeerType::Inbound, |addr| {
+            self.is_blacklisted(addr) || !self.is_in_peer_ranges(addr)
+        }) {
             debug!(target: "network", "Dropping connection from blacklisted peer or unknown address: {:?}", msg.peer_info);
             return RegisterPeerResponse::Reject;
         }
@@ -2092,6 +2588,11 @@ impl PeerManagerActor {
             return RegisterPeerResponse::Reject;
         }
 
+        if !self.is_peer_allowed(&msg.peer_info.id) {
+            debug!(target: "network", "Dropping connection from peer not in allowlist: {:?}", msg.peer_info.id);
+            return RegisterPeerResponse::Reject;
+        }
+
         // We already connected to this peer.
         if self.active_peers.contains_key(&msg.peer_info.id) {
             debug!(target: "network", "Dropping handshake (Active Peer). {:?} {:?}", self.my_peer_id, msg.peer_info.id);
@@ -2114,6 +2615,12 @@ impl PeerManagerActor {
             return RegisterPeerResponse::Reject;
         }
 
+        if msg.peer_info.addr.map_or(false, |addr| self.is_asn_limit_reached(&addr)) {
+            debug!(target: "network", "Dropping connection from peer of an autonomous system with the most peers allowed: {:?}", msg.peer_info);
+            metrics::PEER_ASN_LIMIT_REACHED_TOTAL.inc();
+            return RegisterPeerResponse::Reject;
+        }
+
         if msg.other_edge_info.nonce == 0 {
             debug!(target: "network", "Invalid nonce. It must be greater than 0. nonce={}", msg.other_edge_info.nonce);
             return RegisterPeerResponse::Reject;
@@ -2183,7 +2690,9 @@ impl PeerManagerActor {
     ) -> PeerRequestResult {
         #[cfg(feature = "delay_detector")]
         let _d = delay_detector::DelayDetector::new("peers request".into());
-        PeerRequestResult { peers: self.peer_store.healthy_peers(self.config.max_send_peers) }
+        let mut peers = self.peer_store.healthy_peers(self.config.max_send_peers);
+        peers.retain(|peer_info| self.is_peer_allowed(&peer_info.id));
+        PeerRequestResult { peers }
     }
 
     fn handle_msg_peers_response(&mut self, msg: PeersResponse, _ctx: &mut Context<Self>) {
@@ -2191,7 +2700,12 @@ impl PeerManagerActor {
         let _d = delay_detector::DelayDetector::new("peers response".into());
         unwrap_or_error!(
             self.peer_store.add_indirect_peers(
-                msg.peers.into_iter().filter(|peer_info| peer_info.id != self.my_peer_id).collect()
+                msg.peers
+                    .into_iter()
+                    .filter(|peer_info| {
+                        peer_info.id != self.my_peer_id && self.is_peer_allowed(&peer_info.id)
+                    })
+                    .collect()
             ),
             "Fail to update peer store"
         );
@@ -2283,6 +2797,9 @@ impl PeerManagerActor {
                 self.handle_msg_ban(msg, ctx);
                 PeerManagerMessageResponse::Ban(())
             }
+            PeerManagerMessageRequest::ReloadPeerRanges => {
+                PeerManagerMessageResponse::ReloadPeerRanges(self.reload_peer_ranges())
+            }
             #[cfg(feature = "test_features")]
             #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
             PeerManagerMessageRequest::StartRoutingTableSync(msg) => {
@@ -2373,6 +2890,10 @@ impl PeerManagerActor {
                 }
                 PeerResponse::NoResponse
             }
+            PeerRequest::Misbehaved(peer_id, misbehavior) => {
+                self.record_misbehavior(ctx, &peer_id, misbehavior);
+                PeerResponse::NoResponse
+            }
         }
     }
 }
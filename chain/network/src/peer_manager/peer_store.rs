@@ -10,10 +10,11 @@ use near_store::{ColPeers, Store};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::collections::hash_map::{Entry, Iter};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tracing::{debug, error};
+use std::time::Duration;
+use tracing::{debug, error, info};
 
 /// Level of trust we have about a new (PeerId, Addr) pair.
 #[derive(Eq, PartialEq, Debug, Clone)]
@@ -49,15 +50,24 @@ pub struct PeerStore {
     // It can happens that some peers don't have known address, so
     // they will not be present in this list, otherwise they will be present.
     addr_peers: HashMap<SocketAddr, VerifiedPeer>,
+    /// Peers we were connected to when the node last stopped, which we have not tried to
+    /// connect to again yet.
+    recently_connected: HashSet<PeerId>,
 }
 
 impl PeerStore {
+    /// Loads the known peers from the store. Peers which were not seen for more than
+    /// `max_age` are stale and removed from the store, unless they are banned.
     pub fn new(
         store: Arc<Store>,
         boot_nodes: &[PeerInfo],
+        max_age: Option<Duration>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut peer_states = HashMap::default();
         let mut addr_peers = HashMap::default();
+        let mut recently_connected = HashSet::default();
+        let mut stale_peers = vec![];
+        let now = Utc::now();
 
         for peer_info in boot_nodes.iter() {
             if !peer_states.contains_key(&peer_info.id) {
@@ -84,10 +94,19 @@ impl PeerStore {
             let value: Vec<u8> = value.into();
             let peer_id: PeerId = key.try_into()?;
             let mut peer_state: KnownPeerState = value.try_into()?;
-            // Mark loaded node last seen to now, to avoid deleting them as soon as they are loaded.
-            peer_state.last_seen = to_timestamp(Utc::now());
+            if let Some(max_age) = max_age {
+                let age = (now - peer_state.last_seen()).to_std().unwrap_or_default();
+                if !peer_state.status.is_banned() && age > max_age {
+                    stale_peers.push(peer_id);
+                    continue;
+                }
+            }
             match peer_state.status {
                 KnownPeerStatus::Banned(_, _) => {}
+                KnownPeerStatus::Connected => {
+                    recently_connected.insert(peer_id.clone());
+                    peer_state.status = KnownPeerStatus::NotConnected;
+                }
                 _ => peer_state.status = KnownPeerStatus::NotConnected,
             };
 
@@ -106,7 +125,17 @@ impl PeerStore {
                 }
             }
         }
-        Ok(PeerStore { store, peer_states, addr_peers })
+        recently_connected.retain(|peer_id| peer_states.contains_key(peer_id));
+
+        if !stale_peers.is_empty() {
+            info!(target: "network", "Removing {} peers not seen for more than {:?}", stale_peers.len(), max_age.unwrap_or_default());
+            let mut store_update = store.store_update();
+            for peer_id in stale_peers {
+                store_update.delete(ColPeers, &peer_id.try_to_vec()?);
+            }
+            store_update.commit()?;
+        }
+        Ok(PeerStore { store, peer_states, addr_peers, recently_connected })
     }
 
     pub fn len(&self) -> usize {
@@ -124,6 +153,7 @@ impl PeerStore {
         peer_info: &PeerInfo,
     ) -> Result<(), Box<dyn std::error::Error>> {
         self.add_trusted_peer(peer_info.clone(), TrustLevel::Signed)?;
+        self.recently_connected.remove(&peer_info.id);
         let entry = self.peer_states.get_mut(&peer_info.id).unwrap();
         entry.last_seen = to_timestamp(Utc::now());
         entry.status = KnownPeerStatus::Connected;
@@ -203,6 +233,19 @@ impl PeerStore {
         )
     }
 
+    /// Returns the peers we were connected to when the node last stopped and forgets them, so
+    /// that they are tried only once right after a restart. Peers with unknown addresses are
+    /// filtered out.
+    pub fn take_recently_connected_peers(&mut self) -> Vec<PeerInfo> {
+        let peer_states = &self.peer_states;
+        std::mem::take(&mut self.recently_connected)
+            .into_iter()
+            .filter_map(|peer_id| peer_states.get(&peer_id))
+            .filter(|p| p.status == KnownPeerStatus::NotConnected && p.peer_info.addr.is_some())
+            .map(|p| p.peer_info.clone())
+            .collect()
+    }
+
     /// Return healthy known peers up to given amount.
     pub fn healthy_peers(&self, max_count: u32) -> Vec<PeerInfo> {
         self.find_peers(
@@ -369,7 +412,7 @@ pub fn iter_peers_from_store<F>(store: Arc<Store>, f: F)
 where
     F: Fn((&PeerId, &KnownPeerState)),
 {
-    let peer_store = PeerStore::new(store, &[]).unwrap();
+    let peer_store = PeerStore::new(store, &[], None).unwrap();
     peer_store.iter().for_each(|x| f(x));
 }
 
@@ -409,18 +452,45 @@ mod test {
         let boot_nodes = vec![peer_info_a, peer_info_to_ban.clone()];
         {
             let store = create_store(tmp_dir.path());
-            let mut peer_store = PeerStore::new(store, &boot_nodes).unwrap();
+            let mut peer_store = PeerStore::new(store, &boot_nodes, None).unwrap();
             assert_eq!(peer_store.healthy_peers(3).len(), 2);
             peer_store.peer_ban(&peer_info_to_ban.id, ReasonForBan::Abusive).unwrap();
             assert_eq!(peer_store.healthy_peers(3).len(), 1);
         }
         {
             let store_new = create_store(tmp_dir.path());
-            let peer_store_new = PeerStore::new(store_new, &boot_nodes).unwrap();
+            let peer_store_new = PeerStore::new(store_new, &boot_nodes, None).unwrap();
             assert_eq!(peer_store_new.healthy_peers(3).len(), 1);
         }
     }
 
+    #[test]
+    fn warm_restart() {
+        let store = create_test_store();
+        let connected = gen_peer_info(0);
+        let disconnected = gen_peer_info(1);
+        let stale = gen_peer_info(2);
+        {
+            let mut peer_store = PeerStore::new(store.clone(), &[], None).unwrap();
+            peer_store.peer_connected(&connected).unwrap();
+            peer_store.peer_connected(&disconnected).unwrap();
+            peer_store.peer_disconnected(&disconnected.id).unwrap();
+            peer_store.add_indirect_peers(vec![stale.clone()]).unwrap();
+            let mut peer_state = peer_store.peer_states[&stale.id].clone();
+            peer_state.last_seen -= Duration::from_secs(30 * 24 * 60 * 60).as_nanos() as u64;
+            let mut store_update = store.store_update();
+            store_update.set_ser(ColPeers, &stale.id.try_to_vec().unwrap(), &peer_state).unwrap();
+            store_update.commit().unwrap();
+        }
+        let mut peer_store =
+            PeerStore::new(store.clone(), &[], Some(Duration::from_secs(7 * 24 * 60 * 60)))
+                .unwrap();
+        assert_eq!(peer_store.len(), 2);
+        assert!(store.get(ColPeers, &stale.id.try_to_vec().unwrap()).unwrap().is_none());
+        assert_eq!(peer_store.take_recently_connected_peers(), vec![connected]);
+        assert!(peer_store.take_recently_connected_peers().is_empty());
+    }
+
     fn check_exist(
         peer_store: &PeerStore,
         peer_id: &PeerId,
@@ -463,7 +533,7 @@ mod test {
     #[test]
     fn handle_peer_id_change() {
         let store = create_test_store();
-        let mut peer_store = PeerStore::new(store, &[]).unwrap();
+        let mut peer_store = PeerStore::new(store, &[], None).unwrap();
 
         let peers_id = (0..2).map(|ix| get_peer_id(format!("node{}", ix))).collect::<Vec<_>>();
         let addr = get_addr(0);
@@ -486,7 +556,7 @@ mod test {
     #[test]
     fn dont_handle_address_change() {
         let store = create_test_store();
-        let mut peer_store = PeerStore::new(store, &[]).unwrap();
+        let mut peer_store = PeerStore::new(store, &[], None).unwrap();
 
         let peers_id = (0..1).map(|ix| get_peer_id(format!("node{}", ix))).collect::<Vec<_>>();
         let addrs = (0..2).map(get_addr).collect::<Vec<_>>();
@@ -504,7 +574,7 @@ mod test {
     #[test]
     fn check_add_peers_overriding() {
         let store = create_test_store();
-        let mut peer_store = PeerStore::new(store.clone(), &[]).unwrap();
+        let mut peer_store = PeerStore::new(store.clone(), &[], None).unwrap();
 
         // Five peers: A, B, C, D, X, T
         let peers_id = (0..6).map(|ix| get_peer_id(format!("node{}", ix))).collect::<Vec<_>>();
@@ -579,7 +649,7 @@ mod test {
         assert!(check_integrity(&peer_store));
 
         // Check we are able to recover from store previous signed connection
-        let peer_store_2 = PeerStore::new(store, &[]).unwrap();
+        let peer_store_2 = PeerStore::new(store, &[], None).unwrap();
         assert!(check_exist(&peer_store_2, &peers_id[0], Some((addrs[0], TrustLevel::Indirect))));
         assert!(check_integrity(&peer_store_2));
     }
@@ -0,0 +1,161 @@
+//! Mapping of the listening port on the router of a home network with NAT-PMP (RFC 6886).
+//!
+//! Behind a NAT, the peers can't connect to the node unless the router forwards its port. Routers
+//! supporting NAT-PMP, or PCP which answers NAT-PMP requests, forward a port on request for a
+//! lifetime, and the mapping is renewed half way through. The public address of the router and the
+//! external port it chose are then advertised to the peers instead of the listening address.
+
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+/// Port the router listens to NAT-PMP requests on.
+const NAT_PMP_PORT: u16 = 5351;
+const VERSION: u8 = 0;
+const PUBLIC_ADDRESS: u8 = 0;
+const MAP_TCP: u8 = 2;
+/// Opcodes of the responses are those of the requests plus 128.
+const RESPONSE: u8 = 128;
+/// Lifetime requested for the mapping, the one recommended by the RFC.
+pub(crate) const MAPPING_LIFETIME: Duration = Duration::from_secs(7200);
+/// Time waited for the first response, doubled on every retry.
+const INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+const MAX_ATTEMPTS: u32 = 5;
+
+/// A port forwarded by the router.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct PortMapping {
+    pub external_addr: SocketAddr,
+    /// Time after which the router drops the mapping unless renewed.
+    pub lifetime: Duration,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The gateway of the default route, from `/proc/net/route`.
+pub(crate) fn default_gateway() -> io::Result<Ipv4Addr> {
+    parse_default_gateway(&fs::read_to_string("/proc/net/route")?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No default IPv4 gateway"))
+}
+
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        if fields.len() < 3 || fields[1] != "00000000" {
+            return None;
+        }
+        // In hexadecimal, in the byte order of the host.
+        let gateway = u32::from_str_radix(fields[2], 16).ok()?;
+        Some(Ipv4Addr::from(gateway.to_ne_bytes()))
+    })
+}
+
+fn check_response(response: &[u8], opcode: u8, len: usize) -> io::Result<()> {
+    if response.len() < len || response[0] != VERSION || response[1] != opcode + RESPONSE {
+        return Err(invalid_data(format!("Invalid NAT-PMP response {:?}", response)));
+    }
+    let reason = match u16::from_be_bytes([response[2], response[3]]) {
+        0 => return Ok(()),
+        1 => "unsupported version",
+        2 => "not authorized",
+        3 => "network failure",
+        4 => "out of resources",
+        5 => "unsupported opcode",
+        _ => "unknown error",
+    };
+    Err(io::Error::new(io::ErrorKind::Other, format!("NAT-PMP request refused: {}", reason)))
+}
+
+fn map_request(internal_port: u16, lifetime: Duration) -> Vec<u8> {
+    let mut request = vec![VERSION, MAP_TCP, 0, 0];
+    request.extend_from_slice(&internal_port.to_be_bytes());
+    // Suggest the same external port.
+    request.extend_from_slice(&internal_port.to_be_bytes());
+    request.extend_from_slice(&(lifetime.as_secs() as u32).to_be_bytes());
+    request
+}
+
+fn parse_public_address(response: &[u8]) -> io::Result<Ipv4Addr> {
+    check_response(response, PUBLIC_ADDRESS, 12)?;
+    Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11]))
+}
+
+/// The external port and the lifetime granted.
+fn parse_map(response: &[u8]) -> io::Result<(u16, Duration)> {
+    check_response(response, MAP_TCP, 16)?;
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    let lifetime = u32::from_be_bytes([response[12], response[13], response[14], response[15]]);
+    Ok((external_port, Duration::from_secs(lifetime as u64)))
+}
+
+/// Sends the request until the router answers, UDP being unreliable.
+async fn request(socket: &UdpSocket, request: &[u8]) -> io::Result<Vec<u8>> {
+    let mut timeout = INITIAL_TIMEOUT;
+    let mut response = [0; 16];
+    for _ in 0..MAX_ATTEMPTS {
+        socket.send(request).await?;
+        if let Ok(len) = tokio::time::timeout(timeout, socket.recv(&mut response)).await {
+            return Ok(response[..len?].to_vec());
+        }
+        timeout *= 2;
+    }
+    Err(io::Error::new(io::ErrorKind::TimedOut, "No NAT-PMP response from the router"))
+}
+
+/// Asks the router at `gateway` to forward its TCP port to `internal_port`, or renews the mapping.
+pub(crate) async fn map_port(
+    gateway: Ipv4Addr,
+    internal_port: u16,
+    lifetime: Duration,
+) -> io::Result<PortMapping> {
+    let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.connect(SocketAddrV4::new(gateway, NAT_PMP_PORT)).await?;
+    let public_ip = parse_public_address(&request(&socket, &[VERSION, PUBLIC_ADDRESS]).await?)?;
+    let (external_port, lifetime) =
+        parse_map(&request(&socket, &map_request(internal_port, lifetime)).await?)?;
+    Ok(PortMapping { external_addr: SocketAddr::new(public_ip.into(), external_port), lifetime })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_default_gateway() {
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\n\
+            eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\n\
+            eth0\t00000000\t0100A8C0\t0003\t0\t0\t0\t00000000\n";
+        let expected = if cfg!(target_endian = "little") {
+            Ipv4Addr::new(192, 168, 0, 1)
+        } else {
+            Ipv4Addr::new(1, 0, 168, 192)
+        };
+        assert_eq!(parse_default_gateway(routes), Some(expected));
+        assert_eq!(parse_default_gateway("Iface\tDestination\tGateway\n"), None);
+    }
+
+    #[test]
+    fn test_nat_pmp_messages() {
+        assert_eq!(
+            map_request(24567, MAPPING_LIFETIME),
+            [0, 2, 0, 0, 0x5f, 0xf7, 0x5f, 0xf7, 0, 0, 0x1c, 0x20]
+        );
+        assert_eq!(
+            parse_public_address(&[0, 128, 0, 0, 0, 0, 0, 1, 203, 0, 113, 7]).unwrap(),
+            Ipv4Addr::new(203, 0, 113, 7)
+        );
+        assert_eq!(
+            parse_map(&[0, 130, 0, 0, 0, 0, 0, 1, 0x5f, 0xf7, 0x5f, 0xf8, 0, 0, 0x0e, 0x10])
+                .unwrap(),
+            (24568, Duration::from_secs(3600))
+        );
+        let err = parse_map(&[0, 130, 0, 2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap_err();
+        assert_eq!(err.to_string(), "NAT-PMP request refused: not authorized");
+        assert!(parse_map(&[0, 128, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+        assert!(parse_public_address(&[0, 128, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Reputation of the peers, from their misbehaviors.
+//!
+//! Every misbehavior adds its penalty to the score of the peer, which halves every `half_life`, so
+//! that a peer timing out once in a while over a bad link is forgiven, while one which keeps
+//! misbehaving crosses the thresholds: above the demote threshold it isn't used to sync and is
+//! disconnected first to make room for other peers, above the ban threshold it's banned.
+//!
+//! The scores are kept by peer id rather than by connection, so that reconnecting doesn't reset
+//! them, and forgotten once decayed below 1 or the peer banned.
+
+use near_network_primitives::types::{Misbehavior, PeerScore};
+use near_primitives::network::PeerId;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+/// Score below which a peer is forgotten.
+const MIN_SCORE: f64 = 1.0;
+
+fn penalty(misbehavior: Misbehavior) -> f64 {
+    match misbehavior {
+        Misbehavior::InvalidMessage => 20.0,
+        Misbehavior::ExcessiveBandwidth | Misbehavior::Timeout => 10.0,
+        Misbehavior::StaleData => 5.0,
+    }
+}
+
+/// What to do with a peer after a misbehavior.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    Tolerate,
+    /// The score just crossed the demote threshold.
+    Demote,
+    /// The score crossed the ban threshold, and was forgotten.
+    Ban,
+}
+
+struct Score {
+    value: f64,
+    updated: Instant,
+    misbehaviors: BTreeMap<Misbehavior, u64>,
+}
+
+impl Score {
+    fn value_at(&self, half_life: Duration, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated);
+        self.value * 0.5f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+    }
+}
+
+pub(crate) struct PeerReputation {
+    scores: HashMap<PeerId, Score>,
+    half_life: Duration,
+    demote_threshold: f64,
+    ban_threshold: f64,
+}
+
+impl PeerReputation {
+    pub fn new(half_life: Duration, demote_threshold: f64, ban_threshold: f64) -> Self {
+        Self { scores: HashMap::new(), half_life, demote_threshold, ban_threshold }
+    }
+
+    pub fn record(&mut self, peer_id: &PeerId, misbehavior: Misbehavior, now: Instant) -> Verdict {
+        let score = self.scores.entry(peer_id.clone()).or_insert_with(|| Score {
+            value: 0.0,
+            updated: now,
+            misbehaviors: BTreeMap::new(),
+        });
+        let before = score.value_at(self.half_life, now);
+        score.value = before + penalty(misbehavior);
+        score.updated = now;
+        *score.misbehaviors.entry(misbehavior).or_default() += 1;
+
+        if score.value >= self.ban_threshold {
+            self.scores.remove(peer_id);
+            Verdict::Ban
+        } else if score.value >= self.demote_threshold && before < self.demote_threshold {
+            Verdict::Demote
+        } else {
+            Verdict::Tolerate
+        }
+    }
+
+    pub fn is_demoted(&self, peer_id: &PeerId, now: Instant) -> bool {
+        self.scores
+            .get(peer_id)
+            .map_or(false, |score| score.value_at(self.half_life, now) >= self.demote_threshold)
+    }
+
+    /// Forgets the peers which score decayed below `MIN_SCORE`.
+    pub fn prune(&mut self, now: Instant) {
+        let half_life = self.half_life;
+        self.scores.retain(|_, score| score.value_at(half_life, now) >= MIN_SCORE);
+    }
+
+    /// Scores of the peers, highest first.
+    pub fn scores(&self, now: Instant) -> Vec<PeerScore> {
+        let mut scores: Vec<_> = self
+            .scores
+            .iter()
+            .map(|(peer_id, score)| {
+                let value = score.value_at(self.half_life, now);
+                PeerScore {
+                    peer_id: peer_id.clone(),
+                    score: value,
+                    demoted: value >= self.demote_threshold,
+                    misbehaviors: score.misbehaviors.clone(),
+                }
+            })
+            .collect();
+        scores.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_reputation() {
+        let (peer1, peer2) = (PeerId::random(), PeerId::random());
+        let start = Instant::now();
+        let half_life = Duration::from_secs(100);
+        let mut reputation = PeerReputation::new(half_life, 50.0, 100.0);
+
+        assert_eq!(
+            reputation.record(&peer1, Misbehavior::InvalidMessage, start),
+            Verdict::Tolerate
+        );
+        assert_eq!(
+            reputation.record(&peer1, Misbehavior::InvalidMessage, start),
+            Verdict::Tolerate
+        );
+        assert_eq!(reputation.record(&peer1, Misbehavior::Timeout, start), Verdict::Demote);
+        assert_eq!(reputation.record(&peer1, Misbehavior::StaleData, start), Verdict::Tolerate);
+        assert!(reputation.is_demoted(&peer1, start));
+        assert!(!reputation.is_demoted(&peer2, start));
+
+        // The score of 55 halves, and the peer is promoted again.
+        let later = start + half_life;
+        assert!(!reputation.is_demoted(&peer1, later));
+        reputation.record(&peer2, Misbehavior::StaleData, later);
+        let scores = reputation.scores(later);
+        assert_eq!(scores.iter().map(|score| &score.peer_id).collect::<Vec<_>>(), [&peer1, &peer2]);
+        assert!((scores[0].score - 27.5).abs() < 1e-9);
+        assert_eq!(scores[0].misbehaviors[&Misbehavior::InvalidMessage], 2);
+
+        // Misbehaving again right away crosses the ban threshold.
+        for _ in 0..4 {
+            assert_ne!(reputation.record(&peer2, Misbehavior::InvalidMessage, later), Verdict::Ban);
+        }
+        assert_eq!(reputation.record(&peer2, Misbehavior::InvalidMessage, later), Verdict::Ban);
+        assert!(reputation.scores(later).iter().all(|score| score.peer_id != peer2));
+
+        reputation.prune(later + 5 * half_life);
+        assert!(reputation.scores.is_empty());
+    }
+}
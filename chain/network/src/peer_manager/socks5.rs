@@ -0,0 +1,155 @@
+//! Outbound connections through a SOCKS5 proxy (RFC 1928), e.g. Tor.
+//!
+//! Only the CONNECT command without authentication is supported, which is what Tor and `ssh -D`
+//! offer locally. The boot nodes given by host name are sent to the proxy as such, so that it
+//! resolves them: resolving them locally would leak the lookups outside of the proxy.
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+const CONNECT: u8 = 1;
+const IPV4: u8 = 1;
+const DOMAIN_NAME: u8 = 3;
+const IPV6: u8 = 4;
+
+/// Address of a peer to dial.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Target {
+    Addr(SocketAddr),
+    Host(String, u16),
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Addr(addr) => write!(f, "{}", addr),
+            Target::Host(host, port) => write!(f, "{}:{}", host, port),
+        }
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn connect_request(target: &Target) -> io::Result<Vec<u8>> {
+    let mut request = vec![VERSION, CONNECT, 0];
+    let port = match target {
+        Target::Addr(SocketAddr::V4(addr)) => {
+            request.push(IPV4);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        Target::Addr(SocketAddr::V6(addr)) => {
+            request.push(IPV6);
+            request.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        Target::Host(host, port) => {
+            if host.len() > u8::MAX as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Host name {} is too long for the proxy", host),
+                ));
+            }
+            request.push(DOMAIN_NAME);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+            *port
+        }
+    };
+    request.extend_from_slice(&port.to_be_bytes());
+    Ok(request)
+}
+
+/// Bytes of the reply left after its first 5, the header and the first byte of the address bound
+/// by the proxy, from which the length of the address is known.
+fn reply_remaining_len(reply: &[u8; 5]) -> io::Result<usize> {
+    if reply[0] != VERSION {
+        return Err(invalid_data(format!("Proxy replied with SOCKS version {}", reply[0])));
+    }
+    let reason = match reply[1] {
+        0 => None,
+        1 => Some("general failure"),
+        2 => Some("connection not allowed by ruleset"),
+        3 => Some("network unreachable"),
+        4 => Some("host unreachable"),
+        5 => Some("connection refused"),
+        6 => Some("TTL expired"),
+        7 => Some("command not supported"),
+        8 => Some("address type not supported"),
+        _ => Some("unknown error"),
+    };
+    if let Some(reason) = reason {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("Proxy failed to connect: {}", reason),
+        ));
+    }
+    // The port follows the address.
+    match reply[3] {
+        IPV4 => Ok(4 - 1 + 2),
+        IPV6 => Ok(16 - 1 + 2),
+        DOMAIN_NAME => Ok(reply[4] as usize + 2),
+        address_type => {
+            Err(invalid_data(format!("Proxy replied with address type {}", address_type)))
+        }
+    }
+}
+
+/// Connects to `target` through the proxy at `proxy`.
+pub(crate) async fn connect(proxy: SocketAddr, target: &Target) -> io::Result<TcpStream> {
+    let request = connect_request(target)?;
+    let mut stream = TcpStream::connect(proxy).await?;
+    stream.write_all(&[VERSION, 1, NO_AUTHENTICATION]).await?;
+    let mut method = [0; 2];
+    stream.read_exact(&mut method).await?;
+    if method != [VERSION, NO_AUTHENTICATION] {
+        return Err(invalid_data("Proxy requires an authentication".to_string()));
+    }
+    stream.write_all(&request).await?;
+    let mut reply = [0; 5];
+    stream.read_exact(&mut reply).await?;
+    let mut bound_addr = vec![0; reply_remaining_len(&reply)?];
+    stream.read_exact(&mut bound_addr).await?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_request() {
+        let request = |target| connect_request(&target).unwrap();
+        assert_eq!(
+            request(Target::Addr("1.2.3.4:24567".parse().unwrap())),
+            [5, 1, 0, 1, 1, 2, 3, 4, 0x5f, 0xf7]
+        );
+        assert_eq!(
+            request(Target::Addr("[::1]:80".parse().unwrap())),
+            [&[5, 1, 0, 4][..], &[0; 15][..], &[1, 0, 80][..]].concat()
+        );
+        assert_eq!(
+            request(Target::Host("a.io".to_string(), 80)),
+            b"\x05\x01\x00\x03\x04a.io\x00\x50"
+        );
+        assert!(connect_request(&Target::Host("a".repeat(256), 80)).is_err());
+    }
+
+    #[test]
+    fn test_reply() {
+        assert_eq!(reply_remaining_len(&[5, 0, 0, 1, 127]).unwrap(), 5);
+        assert_eq!(reply_remaining_len(&[5, 0, 0, 4, 0]).unwrap(), 17);
+        assert_eq!(reply_remaining_len(&[5, 0, 0, 3, 10]).unwrap(), 12);
+        let err = reply_remaining_len(&[5, 5, 0, 1, 0]).unwrap_err();
+        assert_eq!(err.to_string(), "Proxy failed to connect: connection refused");
+        assert!(reply_remaining_len(&[4, 0, 0, 1, 0]).is_err());
+        assert!(reply_remaining_len(&[5, 0, 0, 2, 0]).is_err());
+    }
+}
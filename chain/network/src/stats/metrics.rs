@@ -1,7 +1,8 @@
 use crate::types::PeerMessage;
 use near_metrics::{
     inc_counter_by_opt, inc_counter_opt, try_create_histogram, try_create_int_counter,
-    try_create_int_gauge, Histogram, IntCounter, IntGauge,
+    try_create_int_counter_vec, try_create_int_gauge, try_create_int_gauge_vec, Histogram,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use near_network_primitives::types::RoutedMessageBody;
 use once_cell::sync::Lazy;
@@ -40,6 +41,106 @@ pub static PEER_TRANSACTION_RECEIVED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static PEER_MESSAGE_CATEGORY_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_message_category_bytes",
+        "Bytes of the messages sent to and received from peers, per category of message",
+        &["direction", "category"],
+    )
+    .unwrap()
+});
+pub static PEER_OUTBOUND_DROPPED_MESSAGES_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_outbound_dropped_messages_total",
+        "Number of messages to peers dropped because the outbound bandwidth was exhausted",
+        &["category"],
+    )
+    .unwrap()
+});
+pub static PEER_MISBEHAVIOR_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_misbehavior_total",
+        "Number of misbehaviors of peers counted against their scores",
+        &["misbehavior"],
+    )
+    .unwrap()
+});
+pub static PEER_ASN_LIMIT_REACHED_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_peer_asn_limit_reached_total",
+        "Number of peers rejected because their autonomous system has the most peers allowed",
+    )
+    .unwrap()
+});
+pub static PEER_BANDWIDTH_BYTES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_peer_bandwidth_bytes",
+        "Bytes sent to and received from the peers over the last minute, per tier of peer",
+        &["direction", "peer_tier"],
+    )
+    .unwrap()
+});
+pub static PEER_TIER_CONNECTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_peer_tier_connections",
+        "Number of connected peers, per tier of peer",
+        &["peer_tier"],
+    )
+    .unwrap()
+});
+pub static PEER_OUTBOUND_QUEUED_BYTES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "near_peer_outbound_queued_bytes",
+        "Bytes of the messages queued to be sent to the peers, per tier of peer",
+        &["peer_tier"],
+    )
+    .unwrap()
+});
+pub static OUTBOUND_BANDWIDTH_TOKENS: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_outbound_bandwidth_tokens",
+        "Bytes the node may send to its peers before its bandwidth limit, negative while in debt",
+    )
+    .unwrap()
+});
+pub static PEER_OVERSIZE_MESSAGES_REJECTED_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_oversize_messages_rejected_total",
+        "Number of messages from peers rejected for being larger than allowed for their kind",
+        &["kind"],
+    )
+    .unwrap()
+});
+pub static PEER_COMPRESSION_INPUT_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_compression_input_bytes",
+        "Bytes of the messages sent to peers which were large enough to be compressed",
+        &["category"],
+    )
+    .unwrap()
+});
+pub static PEER_COMPRESSION_OUTPUT_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "near_peer_compression_output_bytes",
+        "Bytes sent for the messages large enough to be compressed, once compressed if smaller",
+        &["category"],
+    )
+    .unwrap()
+});
+pub static PORT_MAPPING_ACTIVE: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "near_port_mapping_active",
+        "Whether the router forwards the listening port, mapped with NAT-PMP",
+    )
+    .unwrap()
+});
+pub static PORT_MAPPING_FAILURES_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_port_mapping_failures_total",
+        "Number of failed requests to map or renew the mapping of the listening port",
+    )
+    .unwrap()
+});
 
 // Routing table metrics
 pub static ROUTING_TABLE_RECALCULATIONS: Lazy<IntCounter> = Lazy::new(|| {
@@ -1,3 +1,4 @@
+use crate::peer::outbound_limiter::QosClass;
 use crate::peer::peer_actor::PeerActor;
 use crate::routing::edge::{Edge, PartialEdgeInfo, SimpleEdge};
 use crate::routing::routing::{GetRoutingTableResult, PeerRequestResult, RoutingTableInfo};
@@ -12,9 +13,10 @@ use futures::future::BoxFuture;
 use futures::FutureExt;
 use near_network_primitives::types::{
     AccountIdOrPeerTrackingShard, AccountOrPeerIdOrHash, Ban, InboundTcpConnect, KnownProducer,
-    OutboundTcpConnect, PartialEncodedChunkForwardMsg, PartialEncodedChunkRequestMsg,
-    PartialEncodedChunkResponseMsg, PeerChainInfo, PeerChainInfoV2, PeerType, Ping, Pong,
-    ReasonForBan, RoutedMessage, RoutedMessageBody, RoutedMessageFrom, StateResponseInfo,
+    MessageCategory, Misbehavior, OutboundTcpConnect, PartialEncodedChunkForwardMsg,
+    PartialEncodedChunkRequestMsg, PartialEncodedChunkResponseMsg, PeerChainInfo, PeerChainInfoV2,
+    PeerScore, PeerTransferStats, PeerType, Ping, Pong, ReasonForBan, RoutedMessage,
+    RoutedMessageBody, RoutedMessageFrom, StateResponseInfo,
 };
 use near_primitives::block::{Approval, ApprovalMessage, Block, BlockHeader, GenesisId};
 use near_primitives::challenge::Challenge;
@@ -41,9 +43,17 @@ const ERROR_UNEXPECTED_LENGTH_OF_INPUT: &str = "Unexpected length of input";
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
 pub enum HandshakeFailureReason {
-    ProtocolVersionMismatch { version: u32, oldest_supported_version: u32 },
+    ProtocolVersionMismatch {
+        version: u32,
+        oldest_supported_version: u32,
+    },
     GenesisMismatch(GenesisId),
     InvalidTarget,
+    /// The node only accepts the handshake once the proof of work of this puzzle is sent.
+    ProofOfWorkRequired {
+        seed: CryptoHash,
+        difficulty: u8,
+    },
 }
 
 impl fmt::Display for HandshakeFailureReason {
@@ -341,10 +351,28 @@ pub enum PeerMessage {
     EpochSyncFinalizationRequest(EpochId),
     EpochSyncFinalizationResponse(EpochSyncFinalizationResponse),
 
+    /// Nonce solving the puzzle of `HandshakeFailureReason::ProofOfWorkRequired`, sent before
+    /// the handshake again. Before the variant behind a feature, to have the same index with or
+    /// without it.
+    HandshakeProofOfWork(u64),
+    /// Codecs the node dialing the peer supports, sent before the handshake if it compresses.
+    CompressionOffer(Vec<CompressionCodec>),
+    /// Codec picked by the peer among the offered ones, the messages may be compressed with it.
+    CompressionAccept(CompressionCodec),
+    /// Serialized message compressed with the codec.
+    Compressed(CompressionCodec, Vec<u8>),
+
     #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
     RoutingTableSyncV2(RoutingSyncV2),
 }
 
+/// Codec the large messages are compressed with, see `PeerMessage::Compressed`.
+#[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CompressionCodec {
+    Deflate,
+}
+
 #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Clone, Debug)]
@@ -397,6 +425,51 @@ impl PeerMessage {
         }
     }
 
+    pub(crate) fn category(&self) -> MessageCategory {
+        match self {
+            PeerMessage::BlockHeadersRequest(_)
+            | PeerMessage::BlockHeaders(_)
+            | PeerMessage::BlockRequest(_)
+            | PeerMessage::Block(_) => MessageCategory::Blocks,
+            PeerMessage::Transaction(_) => MessageCategory::Transactions,
+            PeerMessage::EpochSyncRequest(_)
+            | PeerMessage::EpochSyncResponse(_)
+            | PeerMessage::EpochSyncFinalizationRequest(_)
+            | PeerMessage::EpochSyncFinalizationResponse(_) => MessageCategory::Sync,
+            PeerMessage::Routed(r) => match r.body {
+                RoutedMessageBody::BlockApproval(_) => MessageCategory::Blocks,
+                RoutedMessageBody::PartialEncodedChunkRequest(_)
+                | RoutedMessageBody::PartialEncodedChunkResponse(_)
+                | RoutedMessageBody::PartialEncodedChunk(_)
+                | RoutedMessageBody::VersionedPartialEncodedChunk(_)
+                | RoutedMessageBody::PartialEncodedChunkForward(_) => MessageCategory::Chunks,
+                RoutedMessageBody::ForwardTx(_) => MessageCategory::Transactions,
+                RoutedMessageBody::StateRequestHeader(..)
+                | RoutedMessageBody::StateRequestPart(..)
+                | RoutedMessageBody::StateResponse(_)
+                | RoutedMessageBody::VersionedStateResponse(_) => MessageCategory::Sync,
+                _ => MessageCategory::Routed,
+            },
+            _ => MessageCategory::Network,
+        }
+    }
+
+    /// Class of service of the message in the outbound queue of the peer.
+    pub(crate) fn qos_class(&self) -> QosClass {
+        match self {
+            PeerMessage::Routed(r) if matches!(r.body, RoutedMessageBody::BlockApproval(_)) => {
+                QosClass::Consensus
+            }
+            _ => match self.category() {
+                MessageCategory::Chunks => QosClass::Consensus,
+                MessageCategory::Transactions | MessageCategory::Sync => QosClass::Bulk,
+                MessageCategory::Blocks | MessageCategory::Routed | MessageCategory::Network => {
+                    QosClass::Normal
+                }
+            },
+        }
+    }
+
     pub(crate) fn is_client_message(&self) -> bool {
         match self {
             PeerMessage::Block(_)
@@ -561,6 +634,8 @@ pub enum PeerRequest {
     RouteBack(Box<RoutedMessageBody>, CryptoHash),
     UpdatePeerInfo(PeerInfo),
     ReceivedMessage(PeerId, Instant),
+    /// Counts a misbehavior against the score of the peer.
+    Misbehaved(PeerId, Misbehavior),
 }
 
 #[cfg(feature = "deepsize_feature")]
@@ -573,6 +648,7 @@ impl deepsize::DeepSizeOf for PeerRequest {
             }
             PeerRequest::UpdatePeerInfo(x) => x.deep_size_of_children(context),
             PeerRequest::ReceivedMessage(x, _) => x.deep_size_of_children(context),
+            PeerRequest::Misbehaved(x, _) => x.deep_size_of_children(context),
         }
     }
 }
@@ -621,6 +697,8 @@ pub enum PeerManagerMessageRequest {
     InboundTcpConnect(InboundTcpConnect),
     Unregister(Unregister),
     Ban(Ban),
+    /// Reads the ranges of IPs of the peers again, see `NetworkConfig::peer_ranges_loader`.
+    ReloadPeerRanges,
     #[cfg(feature = "test_features")]
     #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
     StartRoutingTableSync(StartRoutingTableSync),
@@ -667,6 +745,7 @@ pub enum PeerManagerMessageResponse {
     InboundTcpConnect(()),
     Unregister(()),
     Ban(()),
+    ReloadPeerRanges(Result<(), String>),
     #[cfg(feature = "test_features")]
     #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
     StartRoutingTableSync(()),
@@ -895,6 +974,10 @@ pub struct NetworkInfo {
     pub highest_height_peers: Vec<FullPeerInfo>,
     pub sent_bytes_per_sec: u64,
     pub received_bytes_per_sec: u64,
+    /// Transfers to and from every active peer over the last minute, 5 minutes and hour.
+    pub peer_transfer_stats: Vec<PeerTransferStats>,
+    /// Scores of the peers which misbehaved recently, highest first.
+    pub peer_scores: Vec<PeerScore>,
     /// Accounts of known block and chunk producers from routing table.
     pub known_producers: Vec<KnownProducer>,
     pub peer_counter: usize,
@@ -977,12 +1060,12 @@ pub enum NetworkClientMessages {
 
     /// Request chunk parts and/or receipts.
     PartialEncodedChunkRequest(PartialEncodedChunkRequestMsg, CryptoHash),
-    /// Response to a request for  chunk parts and/or receipts.
-    PartialEncodedChunkResponse(PartialEncodedChunkResponseMsg),
+    /// Response to a request for  chunk parts and/or receipts, with the peer which sent it.
+    PartialEncodedChunkResponse(PartialEncodedChunkResponseMsg, PeerId),
     /// Information about chunk such as its header, some subset of parts and/or incoming receipts
-    PartialEncodedChunk(PartialEncodedChunk),
+    PartialEncodedChunk(PartialEncodedChunk, PeerId),
     /// Forwarding parts to those tracking the shard (so they don't need to send requests)
-    PartialEncodedChunkForward(PartialEncodedChunkForwardMsg),
+    PartialEncodedChunkForward(PartialEncodedChunkForwardMsg, PeerId),
 
     /// A challenge to invalidate the block.
     Challenge(Challenge),
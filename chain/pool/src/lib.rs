@@ -1,10 +1,14 @@
+use std::collections::hash_map::Entry;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
-use crate::types::{PoolIterator, PoolKey, TransactionGroup};
+use crate::types::{
+    InsertTransactionResult, PoolIterator, PoolKey, TransactionGroup, TransactionPoolLimits,
+};
 use borsh::BorshSerialize;
 use near_crypto::PublicKey;
 use near_primitives::epoch_manager::RngSeed;
 use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::time::{Clock, Instant};
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::AccountId;
 use std::ops::Bound;
@@ -17,12 +21,17 @@ pub struct TransactionPool {
     /// NOTE: It's more efficient on average to keep transactions unsorted and with potentially
     /// conflicting nonce than to create a BTreeMap for every transaction.
     pub transactions: BTreeMap<PoolKey, Vec<SignedTransaction>>,
-    /// Set of all hashes to quickly check if the given transaction is in the pool.
-    pub unique_transactions: HashSet<CryptoHash>,
+    /// All hashes to quickly check if the given transaction is in the pool, with the time the
+    /// transaction was inserted.
+    pub unique_transactions: HashMap<CryptoHash, Instant>,
     /// A uniquely generated key seed to randomize PoolKey order.
     key_seed: RngSeed,
     /// The key after which the pool iterator starts. Doesn't have to be present in the pool.
     last_used_key: PoolKey,
+    /// Transactions beyond the limits are rejected.
+    limits: TransactionPoolLimits,
+    /// Sum of the sizes of the transactions in the pool, in bytes.
+    total_size: u64,
 }
 
 impl TransactionPool {
@@ -30,11 +39,18 @@ impl TransactionPool {
         Self {
             key_seed,
             transactions: BTreeMap::new(),
-            unique_transactions: HashSet::new(),
+            unique_transactions: HashMap::new(),
             last_used_key: CryptoHash::default(),
+            limits: TransactionPoolLimits::default(),
+            total_size: 0,
         }
     }
 
+    /// Only applies to the transactions inserted from now on.
+    pub fn set_limits(&mut self, limits: TransactionPoolLimits) {
+        self.limits = limits;
+    }
+
     fn key(&self, account_id: &AccountId, public_key: &PublicKey) -> PoolKey {
         let mut v = public_key.try_to_vec().unwrap();
         v.extend_from_slice(&self.key_seed);
@@ -42,18 +58,33 @@ impl TransactionPool {
         hash(&v)
     }
 
+    fn has_room_for(&self, size: u64) -> bool {
+        self.limits.max_transactions.map_or(true, |max| self.unique_transactions.len() < max)
+            && self.limits.max_size.map_or(true, |max| self.total_size + size <= max)
+    }
+
     /// Insert a signed transaction into the pool that passed validation.
-    pub fn insert_transaction(&mut self, signed_transaction: SignedTransaction) -> bool {
-        if !self.unique_transactions.insert(signed_transaction.get_hash()) {
-            return false;
+    pub fn insert_transaction(
+        &mut self,
+        signed_transaction: SignedTransaction,
+    ) -> InsertTransactionResult {
+        let size = signed_transaction.get_size();
+        let has_room = self.has_room_for(size);
+        match self.unique_transactions.entry(signed_transaction.get_hash()) {
+            Entry::Occupied(_) => return InsertTransactionResult::Duplicate,
+            Entry::Vacant(_) if !has_room => return InsertTransactionResult::NoSpaceLeft,
+            Entry::Vacant(entry) => {
+                entry.insert(Clock::instant());
+            }
         }
+        self.total_size += size;
         let signer_id = &signed_transaction.transaction.signer_id;
         let signer_public_key = &signed_transaction.transaction.public_key;
         self.transactions
             .entry(self.key(signer_id, signer_public_key))
             .or_insert_with(Vec::new)
             .push(signed_transaction);
-        true
+        InsertTransactionResult::Success
     }
 
     /// Returns a pool iterator wrapper that implements an iterator like trait to iterate over
@@ -68,7 +99,7 @@ impl TransactionPool {
     pub fn remove_transactions(&mut self, transactions: &[SignedTransaction]) {
         let mut grouped_transactions = HashMap::new();
         for tx in transactions {
-            if self.unique_transactions.contains(&tx.get_hash()) {
+            if self.unique_transactions.contains_key(&tx.get_hash()) {
                 let signer_id = &tx.transaction.signer_id;
                 let signer_public_key = &tx.transaction.public_key;
                 grouped_transactions
@@ -80,7 +111,14 @@ impl TransactionPool {
         for (key, hashes) in grouped_transactions {
             let mut remove_entry = false;
             if let Some(v) = self.transactions.get_mut(&key) {
-                v.retain(|tx| !hashes.contains(&tx.get_hash()));
+                let total_size = &mut self.total_size;
+                v.retain(|tx| {
+                    let remove = hashes.contains(&tx.get_hash());
+                    if remove {
+                        *total_size -= tx.get_size();
+                    }
+                    !remove
+                });
                 remove_entry = v.is_empty();
             }
             if remove_entry {
@@ -106,6 +144,26 @@ impl TransactionPool {
     pub fn is_empty(&self) -> bool {
         self.unique_transactions.is_empty()
     }
+
+    /// Sum of the sizes of the transactions in the pool, in bytes.
+    pub fn total_size(&self) -> u64 {
+        self.total_size
+    }
+
+    /// Number of distinct pairs of signer account ID and public key.
+    pub fn num_groups(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Transactions in the pool, with the time they were inserted.
+    pub fn transactions_with_insertion_time(
+        &self,
+    ) -> impl Iterator<Item = (&SignedTransaction, Instant)> + '_ {
+        self.transactions.values().flatten().map(move |tx| {
+            let inserted = self.unique_transactions.get(&tx.get_hash()).copied();
+            (tx, inserted.unwrap_or_else(Clock::instant))
+        })
+    }
 }
 
 /// PoolIterator is a structure to pull transactions from the pool.
@@ -166,6 +224,7 @@ impl<'a> PoolIterator for PoolIteratorWrapper<'a> {
                 key,
                 transactions,
                 removed_transaction_hashes: vec![],
+                removed_transactions_size: 0,
             });
             Some(self.sorted_groups.back_mut().expect("just pushed"))
         } else {
@@ -174,6 +233,7 @@ impl<'a> PoolIterator for PoolIteratorWrapper<'a> {
                     for hash in sorted_group.removed_transaction_hashes {
                         self.pool.unique_transactions.remove(&hash);
                     }
+                    self.pool.total_size -= sorted_group.removed_transactions_size;
                 } else {
                     self.sorted_groups.push_back(sorted_group);
                     return Some(self.sorted_groups.back_mut().expect("just pushed"));
@@ -193,6 +253,7 @@ impl<'a> Drop for PoolIteratorWrapper<'a> {
             for hash in group.removed_transaction_hashes {
                 self.pool.unique_transactions.remove(&hash);
             }
+            self.pool.total_size -= group.removed_transactions_size;
             if !group.transactions.is_empty() {
                 self.pool.transactions.insert(group.key, group.transactions);
             }
@@ -454,4 +515,71 @@ mod tests {
         new_nonces.sort();
         assert_ne!(nonces, new_nonces);
     }
+
+    #[test]
+    fn test_insertion_time() {
+        let transactions = generate_transactions("alice.near", "alice.near", 1, 3);
+        let mut pool = TransactionPool::new(TEST_SEED);
+        let before = Clock::instant();
+        for tx in transactions.clone() {
+            pool.insert_transaction(tx);
+        }
+        let inserted: HashMap<CryptoHash, Instant> = pool
+            .transactions_with_insertion_time()
+            .map(|(tx, inserted)| (tx.get_hash(), inserted))
+            .collect();
+        assert_eq!(inserted.len(), 3);
+        assert!(inserted.values().all(|&inserted| inserted >= before));
+        assert_eq!(pool.num_groups(), 1);
+
+        // Inserting a transaction again keeps the time it was first inserted.
+        assert_eq!(
+            pool.insert_transaction(transactions[0].clone()),
+            InsertTransactionResult::Duplicate
+        );
+        assert_eq!(
+            pool.unique_transactions[&transactions[0].get_hash()],
+            inserted[&transactions[0].get_hash()]
+        );
+
+        prepare_transactions(&mut pool, 3);
+        assert_eq!(pool.transactions_with_insertion_time().count(), 0);
+        assert!(pool.unique_transactions.is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        let transactions = generate_transactions("alice.near", "alice.near", 1, 4);
+        let size = transactions[0].get_size();
+        let mut pool = TransactionPool::new(TEST_SEED);
+        pool.set_limits(TransactionPoolLimits { max_transactions: Some(3), max_size: None });
+        for tx in transactions[..3].iter().cloned() {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        assert_eq!(
+            pool.insert_transaction(transactions[3].clone()),
+            InsertTransactionResult::NoSpaceLeft
+        );
+        assert_eq!(
+            pool.insert_transaction(transactions[0].clone()),
+            InsertTransactionResult::Duplicate
+        );
+        assert_eq!(pool.total_size(), 3 * size);
+
+        // Transactions pulled by the iterator and removed make room again.
+        pool.set_limits(TransactionPoolLimits { max_transactions: None, max_size: Some(2 * size) });
+        assert_eq!(prepare_transactions(&mut pool, 2).len(), 2);
+        assert_eq!(pool.total_size(), size);
+        assert_eq!(
+            pool.insert_transaction(transactions[3].clone()),
+            InsertTransactionResult::Success
+        );
+        assert_eq!(
+            pool.insert_transaction(transactions[0].clone()),
+            InsertTransactionResult::NoSpaceLeft
+        );
+        pool.remove_transactions(&transactions[2..]);
+        assert!(pool.is_empty());
+        assert_eq!(pool.total_size(), 0);
+    }
 }
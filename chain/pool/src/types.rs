@@ -21,6 +21,8 @@ pub struct TransactionGroup {
     pub(crate) transactions: Vec<SignedTransaction>,
     /// Hashes of the transactions that were pulled from the group using `.next()`.
     pub(crate) removed_transaction_hashes: Vec<CryptoHash>,
+    /// Sum of the sizes of the transactions that were pulled from the group.
+    pub(crate) removed_transactions_size: u64,
 }
 
 impl TransactionGroup {
@@ -29,9 +31,30 @@ impl TransactionGroup {
     pub fn next(&mut self) -> Option<SignedTransaction> {
         if let Some(tx) = self.transactions.pop() {
             self.removed_transaction_hashes.push(tx.get_hash());
+            self.removed_transactions_size += tx.get_size();
             Some(tx)
         } else {
             None
         }
     }
 }
+
+/// Limits of a transaction pool. Every shard has its own pool, so that the transactions of one
+/// shard can't take the room of the transactions of the others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransactionPoolLimits {
+    /// Maximum number of transactions. None is no limit.
+    pub max_transactions: Option<usize>,
+    /// Maximum sum of the sizes of the transactions, in bytes. None is no limit.
+    pub max_size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertTransactionResult {
+    /// The transaction was inserted.
+    Success,
+    /// The transaction is already in the pool.
+    Duplicate,
+    /// The pool has reached one of its limits.
+    NoSpaceLeft,
+}
@@ -22,6 +22,14 @@ pub struct TelemetryEvent {
     content: serde_json::Value,
 }
 
+/// Signed heartbeat of a validator to send to uptime monitoring services.
+#[derive(Message, Debug)]
+#[rtype(result = "()")]
+pub struct LivenessBeaconEvent {
+    endpoints: Vec<String>,
+    content: serde_json::Value,
+}
+
 pub struct TelemetryActor {
     config: TelemetryConfig,
     client: Client,
@@ -56,24 +64,40 @@ impl Actor for TelemetryActor {
     type Context = Context<Self>;
 }
 
+impl TelemetryActor {
+    fn post(&self, endpoints: &[String], content: &serde_json::Value) {
+        for endpoint in endpoints.iter() {
+            near_performance_metrics::actix::spawn(
+                "telemetry",
+                self.client
+                    .post(endpoint)
+                    .insert_header(("Content-Type", "application/json"))
+                    .send_json(content)
+                    .map(|response| {
+                        if let Err(error) = response {
+                            info!(target: "telemetry", "Telemetry data could not be sent due to: {}", error);
+                        }
+                    }),
+            );
+        }
+    }
+}
+
 impl Handler<TelemetryEvent> for TelemetryActor {
     type Result = ();
 
     #[perf]
     fn handle(&mut self, msg: TelemetryEvent, _ctx: &mut Context<Self>) {
-        for endpoint in self.config.endpoints.iter() {
-            near_performance_metrics::actix::spawn("telemetry",
-                                           self.client
-                        .post(endpoint)
-                        .insert_header(("Content-Type", "application/json"))
-                        .send_json(&msg.content)
-                        .map(|response| {
-                            if let Err(error) = response {
-                                info!(target: "telemetry", "Telemetry data could not be sent due to: {}", error);
-                            }
-                        }),
-                );
-        }
+        self.post(&self.config.endpoints, &msg.content);
+    }
+}
+
+impl Handler<LivenessBeaconEvent> for TelemetryActor {
+    type Result = ();
+
+    #[perf]
+    fn handle(&mut self, msg: LivenessBeaconEvent, _ctx: &mut Context<Self>) {
+        self.post(&msg.endpoints, &msg.content);
     }
 }
 
@@ -81,3 +105,12 @@ impl Handler<TelemetryEvent> for TelemetryActor {
 pub fn telemetry(telemetry: &Addr<TelemetryActor>, content: serde_json::Value) {
     telemetry.do_send(TelemetryEvent { content });
 }
+
+/// Send a liveness beacon to the endpoints of the beacon, rather than those of the telemetry.
+pub fn liveness_beacon(
+    telemetry: &Addr<TelemetryActor>,
+    endpoints: Vec<String>,
+    content: serde_json::Value,
+) {
+    telemetry.do_send(LivenessBeaconEvent { endpoints, content });
+}
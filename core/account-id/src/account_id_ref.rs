@@ -0,0 +1,186 @@
+use alloc::borrow::ToOwned;
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::Deref;
+
+use crate::{AccountId, ParseAccountError, MIN_ACCOUNT_ID_LEN};
+
+/// A borrowed, valid Account ID, like `&str` for a `String`.
+///
+/// [`AccountId`] dereferences to it, so its methods are available on both.
+#[derive(Eq, Ord, Hash, Debug, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct AccountIdRef(str);
+
+impl AccountIdRef {
+    /// Validates `account_id` and borrows it as an Account ID, without allocating unless it is
+    /// invalid.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use near_account_id::AccountIdRef;
+    ///
+    /// let alice = AccountIdRef::new("alice.near").unwrap();
+    /// assert_eq!(alice.parent().unwrap().as_str(), "near");
+    ///
+    /// assert!(AccountIdRef::new("Alice.near").is_err());
+    /// ```
+    pub fn new(account_id: &str) -> Result<&Self, ParseAccountError> {
+        AccountId::validate(account_id)?;
+        Ok(Self::new_unchecked(account_id))
+    }
+
+    /// The caller must ensure that `account_id` is valid.
+    pub(crate) fn new_unchecked(account_id: &str) -> &Self {
+        // SAFETY: `AccountIdRef` is a transparent wrapper of `str`.
+        unsafe { &*(account_id as *const str as *const AccountIdRef) }
+    }
+
+    /// Used by [`account_id!`](crate::account_id): fails the evaluation of the `const` if
+    /// `account_id` is invalid, which doesn't compile, or panics at runtime.
+    #[doc(hidden)]
+    pub const fn __new_checked(account_id: &str) -> &Self {
+        // `panic!` isn't allowed in `const fn` before Rust 1.57, an index out of bounds fails the
+        // evaluation as well.
+        let _ = [(); 1][crate::check(account_id).is_err() as usize];
+        // SAFETY: `AccountIdRef` is a transparent wrapper of `str`, and `account_id` is valid.
+        unsafe { core::mem::transmute::<&str, &AccountIdRef>(account_id) }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the account which the Account ID is a sub-account of, `None` if it's a top-level
+    /// account, implicit accounts included.
+    ///
+    /// An invalid parent, like the one letter `a` of `alice.a`, is also `None`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use near_account_id::AccountId;
+    ///
+    /// let app: AccountId = "app.stage.testnet".parse().unwrap();
+    /// let stage = app.parent().unwrap();
+    /// assert_eq!(stage.as_str(), "stage.testnet");
+    /// assert_eq!(stage.parent().unwrap().as_str(), "testnet");
+    /// assert!(stage.parent().unwrap().parent().is_none());
+    /// ```
+    pub fn parent(&self) -> Option<&AccountIdRef> {
+        let (_, parent) = self.0.split_once('.')?;
+        // The parts of a valid Account ID and their separators are valid, only the length of
+        // the parent needs to be checked.
+        if parent.len() < MIN_ACCOUNT_ID_LEN {
+            return None;
+        }
+        Some(Self::new_unchecked(parent))
+    }
+
+    /// Returns the parts of the Account ID separated by `.`, from the deepest sub-account to the
+    /// top-level account.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use near_account_id::AccountId;
+    ///
+    /// let app: AccountId = "app.stage.testnet".parse().unwrap();
+    /// assert_eq!(app.components().collect::<Vec<_>>(), ["app", "stage", "testnet"]);
+    /// ```
+    pub fn components(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.0.split('.')
+    }
+
+    /// Returns `true` if the Account ID has no parent, like `near` or an implicit account.
+    ///
+    /// Unlike [`AccountId::is_top_level_account_id`], `system` is a top-level account.
+    pub fn is_top_level(&self) -> bool {
+        !self.0.contains('.')
+    }
+}
+
+impl fmt::Display for AccountIdRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl AsRef<str> for AccountIdRef {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> TryFrom<&'a str> for &'a AccountIdRef {
+    type Error = ParseAccountError;
+
+    fn try_from(account_id: &'a str) -> Result<Self, ParseAccountError> {
+        AccountIdRef::new(account_id)
+    }
+}
+
+impl ToOwned for AccountIdRef {
+    type Owned = AccountId;
+
+    fn to_owned(&self) -> AccountId {
+        AccountId(self.0.into())
+    }
+}
+
+impl Deref for AccountId {
+    type Target = AccountIdRef;
+
+    fn deref(&self) -> &AccountIdRef {
+        AccountIdRef::new_unchecked(&self.0)
+    }
+}
+
+impl From<&AccountIdRef> for AccountId {
+    fn from(account_id: &AccountIdRef) -> Self {
+        account_id.to_owned()
+    }
+}
+
+impl Borrow<AccountIdRef> for AccountId {
+    fn borrow(&self) -> &AccountIdRef {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hierarchy() {
+        for (account_id, parent, components) in [
+            ("near", None, vec!["near"]),
+            ("alice.near", Some("near"), vec!["alice", "near"]),
+            ("a.b-c.near", Some("b-c.near"), vec!["a", "b-c", "near"]),
+            ("alice.a", None, vec!["alice", "a"]),
+            (
+                "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+                None,
+                vec!["0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"],
+            ),
+        ] {
+            let account_id: AccountId = account_id.parse().unwrap();
+            assert_eq!(account_id.parent().map(AccountIdRef::as_str), parent, "{}", account_id);
+            assert_eq!(account_id.components().collect::<Vec<_>>(), components);
+            assert_eq!(account_id.is_top_level(), components.len() == 1);
+            if let Some(parent) = account_id.parent() {
+                assert!(account_id.is_sub_account_of(&parent.to_owned()));
+                assert!(AccountId::validate(parent.as_str()).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_account_id_macro() {
+        const ALICE: &AccountIdRef = crate::account_id!("alice.near");
+        assert_eq!(ALICE.to_owned(), "alice.near".parse::<AccountId>().unwrap());
+        assert_eq!(ALICE.parent().map(AccountIdRef::as_str), Some("near"));
+    }
+}
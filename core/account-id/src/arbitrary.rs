@@ -0,0 +1,134 @@
+use super::{AccountId, MAX_ACCOUNT_ID_LEN, MIN_ACCOUNT_ID_LEN};
+
+use alloc::string::String;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+const HEX: &[u8] = b"0123456789abcdef";
+const SEPARATORS: &[u8] = b".-_";
+const INVALID_CHARS: &[char] = &[' ', '@', '#', '!', '/', '\0', 'ƒ', 'é'];
+
+/// Valid Account IDs: named accounts with sub-accounts and separators, and implicit accounts.
+impl<'a> Arbitrary<'a> for AccountId {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let account_id = valid_account_id(u)?;
+        debug_assert!(AccountId::validate(&account_id).is_ok(), "{:?}", account_id);
+        Ok(Self(account_id.into()))
+    }
+}
+
+/// A string to parse as an Account ID, which is valid half of the time and otherwise only one
+/// mistake away from a valid one: too short or too long, with a capital letter, with separators
+/// at its ends or following each other, or with a character which isn't allowed.
+///
+/// Fuzzers of the code parsing Account IDs should use it rather than arbitrary strings, which are
+/// almost never valid and rarely exercise more than the first checks of the validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountIdCandidate(pub String);
+
+impl<'a> Arbitrary<'a> for AccountIdCandidate {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut account_id = valid_account_id(u)?;
+        match u.int_in_range(0..=9u8)? {
+            0 => {
+                account_id.truncate(MIN_ACCOUNT_ID_LEN - 1);
+            }
+            1 => {
+                let len = u.int_in_range(MAX_ACCOUNT_ID_LEN + 1..=MAX_ACCOUNT_ID_LEN * 2)?;
+                while account_id.len() < len {
+                    account_id.push(*u.choose(ALPHANUMERIC)? as char);
+                }
+            }
+            2 => {
+                let letters: alloc::vec::Vec<usize> = account_id
+                    .bytes()
+                    .enumerate()
+                    .filter(|(_, c)| c.is_ascii_lowercase())
+                    .map(|(i, _)| i)
+                    .collect();
+                match u.choose(&letters) {
+                    Ok(&i) => account_id[i..=i].make_ascii_uppercase(),
+                    Err(_) => account_id.insert(0, 'A'),
+                }
+            }
+            3 => {
+                let separator = *u.choose(SEPARATORS)? as char;
+                match u.int_in_range(0..=2u8)? {
+                    0 => account_id.insert(0, separator),
+                    1 => account_id.push(separator),
+                    _ => {
+                        // The separators of valid Account IDs are followed by a character.
+                        let i = u.int_in_range(1..=account_id.len() - 1)?;
+                        account_id.insert(i, separator);
+                        account_id.insert(i, *u.choose(SEPARATORS)? as char);
+                    }
+                }
+            }
+            4 => {
+                // Account IDs are ASCII, so every index is a char boundary.
+                let i = u.int_in_range(0..=account_id.len() - 1)?;
+                account_id.replace_range(i..=i, u.choose(INVALID_CHARS)?.encode_utf8(&mut [0; 4]));
+            }
+            _ => {}
+        }
+        Ok(Self(account_id))
+    }
+}
+
+fn valid_account_id(u: &mut Unstructured) -> Result<String> {
+    let mut account_id = String::new();
+    if u.int_in_range(0..=7u8)? == 0 {
+        for _ in 0..64 {
+            account_id.push(*u.choose(HEX)? as char);
+        }
+        return Ok(account_id);
+    }
+    let len = u.int_in_range(MIN_ACCOUNT_ID_LEN..=MAX_ACCOUNT_ID_LEN)?;
+    while account_id.len() < len {
+        account_id.push(*u.choose(ALPHANUMERIC)? as char);
+        // A separator must be followed by a character.
+        if account_id.len() + 1 < len && u.int_in_range(0..=5u8)? == 0 {
+            account_id.push(*u.choose(SEPARATORS)? as char);
+        }
+    }
+    Ok(account_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unstructured_data(seed: u32) -> alloc::vec::Vec<u8> {
+        (0..1024u32).map(|i| (i.wrapping_mul(2654435761).wrapping_add(seed) >> 13) as u8).collect()
+    }
+
+    #[test]
+    fn test_arbitrary_account_id() {
+        for seed in 0..200 {
+            let data = unstructured_data(seed);
+            let mut u = Unstructured::new(&data);
+            while !u.is_empty() {
+                let account_id = AccountId::arbitrary(&mut u).unwrap();
+                assert!(AccountId::validate(account_id.as_str()).is_ok(), "{:?}", account_id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_account_id_candidate() {
+        let (mut valid, mut invalid) = (0, 0);
+        for seed in 0..200 {
+            let data = unstructured_data(seed);
+            let mut u = Unstructured::new(&data);
+            while !u.is_empty() {
+                let AccountIdCandidate(account_id) = AccountIdCandidate::arbitrary(&mut u).unwrap();
+                match AccountId::validate(&account_id) {
+                    Ok(()) => valid += 1,
+                    Err(_) => invalid += 1,
+                }
+            }
+        }
+        assert!(valid > 0 && invalid > 0, "{} valid, {} invalid", valid, invalid);
+    }
+}
@@ -1,6 +1,8 @@
-use super::AccountId;
+use super::{AccountId, AccountIdRef};
 
-use std::io::{Error, Write};
+use alloc::boxed::Box;
+
+use borsh::maybestd::io::{Error, ErrorKind, Write};
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -10,11 +12,16 @@ impl BorshSerialize for AccountId {
     }
 }
 
+impl BorshSerialize for AccountIdRef {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.0.serialize(writer)
+    }
+}
+
 impl BorshDeserialize for AccountId {
-    fn deserialize(buf: &mut &[u8]) -> Result<Self, std::io::Error> {
+    fn deserialize(buf: &mut &[u8]) -> Result<Self, Error> {
         let account_id = Box::<str>::deserialize(buf)?;
-        Self::validate(&account_id)
-            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        Self::validate(&account_id).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
         Ok(Self(account_id))
     }
 }
@@ -46,6 +53,10 @@ mod tests {
                     panic!("failed to serialize account ID {:?}: {}", account_id, err)
                 });
             assert_eq!(serialized_account_id, str_serialized_account_id);
+            assert_eq!(
+                AccountIdRef::new(account_id).unwrap().try_to_vec().unwrap(),
+                str_serialized_account_id
+            );
         }
 
         for account_id in BAD_ACCOUNT_IDS.iter().cloned() {
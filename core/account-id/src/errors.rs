@@ -1,8 +1,14 @@
-use std::fmt;
+use alloc::string::String;
+use core::fmt;
+use core::ops::Range;
 
 /// An error occurred when parsing an invalid Account ID with [`AccountId::validate`](crate::AccountId::validate).
 #[derive(Eq, Clone, Debug, PartialEq)]
-pub struct ParseAccountError(pub(crate) ParseErrorKind, pub(crate) String);
+pub struct ParseAccountError(
+    pub(crate) ParseErrorKind,
+    pub(crate) String,
+    pub(crate) Option<Range<usize>>,
+);
 
 impl ParseAccountError {
     /// Returns the corresponding [`ParseErrorKind`] for this error.
@@ -14,9 +20,17 @@ impl ParseAccountError {
     pub fn get_account_id(self) -> String {
         self.1
     }
+
+    /// Returns the byte range of the offending character in the Account ID, if the error is
+    /// caused by one rather than by the length.
+    pub fn char_range(&self) -> Option<Range<usize>> {
+        self.2.clone()
+    }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for ParseAccountError {}
+
 impl fmt::Display for ParseAccountError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "[{}]: {}", self.1, self.0)
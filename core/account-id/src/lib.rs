@@ -60,19 +60,72 @@
 //!   )
 //! );
 //! ```
+//!
+//! ## `no_std`
+//!
+//! Without the default `std` feature the crate only depends on `core` and `alloc`. The `borsh`,
+//! `deepsize_feature` and `arbitrary` features still need the standard library and must be
+//! disabled too.
+//!
+//! ## Fuzzing
+//!
+//! The `arbitrary` feature implements [`Arbitrary`] for [`AccountId`], generating valid Account
+//! IDs, and for `AccountIdCandidate`, generating strings which are either valid Account IDs or
+//! invalid in one of the ways the validation checks.
+//!
+//! [`Arbitrary`]: https://docs.rs/arbitrary/1/arbitrary/trait.Arbitrary.html
 
-use std::{fmt, str::FromStr};
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use core::ops::Range;
+use core::{fmt, str::FromStr};
+
+mod account_id_ref;
 mod errors;
+mod normalize;
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
 #[cfg(feature = "borsh")]
 mod borsh;
 #[cfg(feature = "serde")]
 mod serde;
 
+pub use account_id_ref::AccountIdRef;
+#[cfg(feature = "arbitrary")]
+pub use arbitrary::AccountIdCandidate;
 #[cfg(feature = "deepsize_feature")]
 use deepsize::DeepSizeOf;
 pub use errors::{ParseAccountError, ParseErrorKind};
+pub use normalize::{Fix, Normalized};
+
+/// Creates a `&'static` [`AccountIdRef`] from a string literal checked at compile time: an
+/// invalid Account ID doesn't compile, and a valid one costs nothing at runtime.
+///
+/// ## Examples
+///
+/// ```
+/// use near_account_id::{account_id, AccountId, AccountIdRef};
+///
+/// const ALICE: &AccountIdRef = account_id!("alice.near");
+/// let alice: AccountId = ALICE.to_owned();
+/// assert!(alice.is_sub_account_of(&account_id!("near").to_owned()));
+/// ```
+///
+/// ```compile_fail
+/// let alice = near_account_id::account_id!("Alice.near"); // no caps
+/// ```
+#[macro_export]
+macro_rules! account_id {
+    ($account_id:expr) => {{
+        const ACCOUNT_ID: &$crate::AccountIdRef = $crate::AccountIdRef::__new_checked($account_id);
+        ACCOUNT_ID
+    }};
+}
 
 /// Smallest valid length for a NEAR Account ID.
 pub const MIN_ACCOUNT_ID_LEN: usize = 2;
@@ -196,9 +249,11 @@ impl AccountId {
         prefix.find('.') == Some(prefix.len() - 1) && suffix == parent_account.as_ref()
     }
 
-    /// Returns `true` if the `AccountId` is a 64 characters long hexadecimal.
+    /// Returns `true` if the `AccountId` is a NEAR implicit account, see
+    /// [`AccountId::is_near_implicit`].
     ///
-    /// See [Implicit-Accounts](https://docs.near.org/docs/concepts/account#implicit-accounts).
+    /// Ethereum implicit accounts aren't implicit accounts for the protocol, which only creates
+    /// NEAR implicit accounts on transfers.
     ///
     /// ## Examples
     ///
@@ -214,8 +269,44 @@ impl AccountId {
     /// assert!(rando.is_implicit());
     /// ```
     pub fn is_implicit(&self) -> bool {
-        self.len() == 64
-            && self.as_ref().as_bytes().iter().all(|b| matches!(b, b'a'..=b'f' | b'0'..=b'9'))
+        self.is_near_implicit()
+    }
+
+    /// Returns `true` if the `AccountId` is a 64 characters long hexadecimal, the hex encoding of
+    /// an ED25519 public key.
+    ///
+    /// See [Implicit-Accounts](https://docs.near.org/docs/concepts/account#implicit-accounts).
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use near_account_id::AccountId;
+    ///
+    /// let rando = "98793cd91a3f870fb126f66285808c7e094afcfc4eda8a970f6648cdf0dbd6de"
+    ///     .parse::<AccountId>()
+    ///     .unwrap();
+    /// assert!(rando.is_near_implicit());
+    /// assert!(!rando.is_eth_implicit());
+    /// ```
+    pub fn is_near_implicit(&self) -> bool {
+        self.len() == 64 && is_lower_hex(self.as_ref())
+    }
+
+    /// Returns `true` if the `AccountId` is `0x` followed by 40 hexadecimal characters, the
+    /// address of an Ethereum account.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use near_account_id::AccountId;
+    ///
+    /// let eth: AccountId = "0xb794f5ea0ba39494ce839613fffba74279579268".parse().unwrap();
+    /// assert!(eth.is_eth_implicit());
+    /// assert!(!eth.is_near_implicit());
+    /// ```
+    pub fn is_eth_implicit(&self) -> bool {
+        self.len() == 42
+            && self.as_ref().strip_prefix("0x").map_or(false, |address| is_lower_hex(address))
     }
 
     /// Validates a string as a well-structured NEAR Account ID.
@@ -239,46 +330,14 @@ impl AccountId {
     /// assert!(
     ///   matches!(
     ///     AccountId::validate("MelissaCarver.near"), // no caps
-    ///     Err(err) if err.kind().is_invalid()
+    ///     Err(err) if err.kind().is_invalid() && err.char_range() == Some(0..1)
     ///   )
     /// );
     /// ```
     pub fn validate(account_id: &str) -> Result<(), ParseAccountError> {
-        if account_id.len() < MIN_ACCOUNT_ID_LEN {
-            Err(ParseAccountError(ParseErrorKind::TooShort, account_id.to_string()))
-        } else if account_id.len() > MAX_ACCOUNT_ID_LEN {
-            Err(ParseAccountError(ParseErrorKind::TooLong, account_id.to_string()))
-        } else {
-            // Adapted from https://github.com/near/near-sdk-rs/blob/fd7d4f82d0dfd15f824a1cf110e552e940ea9073/near-sdk/src/environment/env.rs#L819
-
-            // NOTE: We don't want to use Regex here, because it requires extra time to compile it.
-            // The valid account ID regex is /^(([a-z\d]+[-_])*[a-z\d]+\.)*([a-z\d]+[-_])*[a-z\d]+$/
-            // Instead the implementation is based on the previous character checks.
-
-            // We can safely assume that last char was a separator.
-            let mut last_char_is_separator = true;
-
-            for c in account_id.bytes() {
-                let current_char_is_separator = match c {
-                    b'a'..=b'z' | b'0'..=b'9' => false,
-                    b'-' | b'_' | b'.' => true,
-                    _ => {
-                        return Err(ParseAccountError(
-                            ParseErrorKind::Invalid,
-                            account_id.to_string(),
-                        ))
-                    }
-                };
-                if current_char_is_separator && last_char_is_separator {
-                    return Err(ParseAccountError(ParseErrorKind::Invalid, account_id.to_string()));
-                }
-                last_char_is_separator = current_char_is_separator;
-            }
-
-            (!last_char_is_separator)
-                .then(|| ())
-                .ok_or_else(|| ParseAccountError(ParseErrorKind::Invalid, account_id.to_string()))
-        }
+        check(account_id).map_err(|(kind, char_range)| {
+            ParseAccountError(kind, account_id.to_string(), char_range)
+        })
     }
 
     /// Creates an `AccountId` without any validation checks.
@@ -309,6 +368,7 @@ impl AccountId {
         Self(account_id.into())
     }
 
+    /// Returns `true` if the `AccountId` is `system`, the predecessor of the refund receipts.
     pub fn is_system(&self) -> bool {
         self.as_ref() == "system"
     }
@@ -322,6 +382,60 @@ impl AccountId {
     }
 }
 
+/// Checks the rules of Account IDs, in `const` contexts too. On error, returns the kind of the
+/// error and the byte range of the offending character, if any.
+const fn check(account_id: &str) -> Result<(), (ParseErrorKind, Option<Range<usize>>)> {
+    let bytes = account_id.as_bytes();
+    if bytes.len() < MIN_ACCOUNT_ID_LEN {
+        return Err((ParseErrorKind::TooShort, None));
+    }
+    if bytes.len() > MAX_ACCOUNT_ID_LEN {
+        return Err((ParseErrorKind::TooLong, None));
+    }
+    // Adapted from https://github.com/near/near-sdk-rs/blob/fd7d4f82d0dfd15f824a1cf110e552e940ea9073/near-sdk/src/environment/env.rs#L819
+
+    // NOTE: We don't want to use Regex here, because it requires extra time to compile it.
+    // The valid account ID regex is /^(([a-z\d]+[-_])*[a-z\d]+\.)*([a-z\d]+[-_])*[a-z\d]+$/
+    // Instead the implementation is based on the previous character checks.
+    // The loop goes over bytes, as iterators aren't available in `const fn`. All the valid
+    // characters are ASCII, so the first byte which isn't starts the offending character.
+
+    // We can safely assume that last char was a separator.
+    let mut last_char_is_separator = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let current_char_is_separator = match bytes[i] {
+            b'a'..=b'z' | b'0'..=b'9' => false,
+            b'-' | b'_' | b'.' => true,
+            byte => return Err((ParseErrorKind::Invalid, Some(i..i + utf8_char_width(byte)))),
+        };
+        if current_char_is_separator && last_char_is_separator {
+            return Err((ParseErrorKind::Invalid, Some(i..i + 1)));
+        }
+        last_char_is_separator = current_char_is_separator;
+        i += 1;
+    }
+
+    if last_char_is_separator {
+        return Err((ParseErrorKind::Invalid, Some(bytes.len() - 1..bytes.len())));
+    }
+    Ok(())
+}
+
+/// Length of the UTF-8 character starting with `first_byte`.
+const fn utf8_char_width(first_byte: u8) -> usize {
+    match first_byte {
+        0xf0..=0xff => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    }
+}
+
+fn is_lower_hex(s: &str) -> bool {
+    s.bytes().all(|b| matches!(b, b'a'..=b'f' | b'0'..=b'9'))
+}
+
 impl<T: ?Sized> AsRef<T> for AccountId
 where
     Box<str>: AsRef<T>,
@@ -331,7 +445,7 @@ where
     }
 }
 
-impl std::borrow::Borrow<str> for AccountId {
+impl core::borrow::Borrow<str> for AccountId {
     fn borrow(&self) -> &str {
         self.as_ref()
     }
@@ -448,6 +562,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_invalid_account_id_char_range() {
+        for (account_id, char_range) in [
+            ("a", None),
+            ("alice near", Some(5..6)),
+            ("ƒelicia.near", Some(0..2)),
+            ("bob.ƒelicia", Some(4..6)),
+            ("alice..near", Some(6..7)),
+            ("_alice", Some(0..1)),
+            ("alice-", Some(5..6)),
+        ] {
+            let err = AccountId::validate(account_id).unwrap_err();
+            assert_eq!(err.char_range(), char_range, "{}", account_id);
+        }
+    }
+
     #[test]
     fn test_is_valid_top_level_account_id() {
         let ok_top_level_account_ids = &[
@@ -643,4 +773,30 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_account_id_classification() {
+        for (account_id, near_implicit, eth_implicit, system) in [
+            (
+                "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+                true,
+                false,
+                false,
+            ),
+            ("0xb794f5ea0ba39494ce839613fffba74279579268", false, true, false),
+            ("0xb794f5ea0ba39494ce839613fffba7427957926", false, false, false),
+            ("0xb794f5ea0ba39494ce839613fffba742795792680", false, false, false),
+            ("0xg794f5ea0ba39494ce839613fffba74279579268", false, false, false),
+            ("00b794f5ea0ba39494ce839613fffba74279579268", false, false, false),
+            ("system", false, false, true),
+            ("system.near", false, false, false),
+            ("alice.near", false, false, false),
+        ] {
+            let account_id: AccountId = account_id.parse().unwrap();
+            assert_eq!(account_id.is_near_implicit(), near_implicit, "{}", account_id);
+            assert_eq!(account_id.is_implicit(), near_implicit, "{}", account_id);
+            assert_eq!(account_id.is_eth_implicit(), eth_implicit, "{}", account_id);
+            assert_eq!(account_id.is_system(), system, "{}", account_id);
+        }
+    }
 }
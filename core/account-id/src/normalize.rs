@@ -0,0 +1,129 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{AccountId, ParseAccountError};
+
+/// A fix applied by [`AccountId::normalize`].
+#[non_exhaustive]
+#[derive(Eq, Clone, Copy, Debug, PartialEq)]
+pub enum Fix {
+    /// Uppercase characters were replaced by their lowercase form.
+    Lowercased,
+    /// Separators immediately following each other were collapsed into one.
+    CollapsedSeparators,
+    /// Separators at the start or the end were removed.
+    TrimmedSeparators,
+}
+
+/// The result of [`AccountId::normalize`]: the valid Account ID and the fixes applied to get it.
+#[derive(Eq, Clone, Debug, PartialEq)]
+pub struct Normalized {
+    pub account_id: AccountId,
+    /// The fixes applied, without duplicates, empty if the Account ID was valid as is.
+    pub fixes: Vec<Fix>,
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '_' | '.')
+}
+
+impl AccountId {
+    /// Suggests the valid form of a mistyped Account ID.
+    ///
+    /// Uppercase ASCII characters are lowercased, runs of separators collapsed into a single one,
+    /// `.` if the run has one and its first separator otherwise, and the separators at the start
+    /// and the end removed. Other mistakes, like invalid characters, can't be fixed and the
+    /// validation error of the fixed Account ID is returned.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use near_account_id::{AccountId, Fix};
+    ///
+    /// let normalized = AccountId::normalize("Alice-.near").unwrap();
+    /// assert_eq!(normalized.account_id.to_string(), "alice.near");
+    /// assert_eq!(normalized.fixes, vec![Fix::Lowercased, Fix::CollapsedSeparators]);
+    ///
+    /// assert!(AccountId::normalize("alice.near").unwrap().fixes.is_empty());
+    ///
+    /// assert!(
+    ///   matches!(
+    ///     AccountId::normalize("ƒelicia.near"), // fancy ƒ!
+    ///     Err(err) if err.kind().is_invalid()
+    ///   )
+    /// );
+    /// ```
+    pub fn normalize(account_id: &str) -> Result<Normalized, ParseAccountError> {
+        let mut fixes = vec![];
+        let mut add_fix = |fix: Fix| {
+            if !fixes.contains(&fix) {
+                fixes.push(fix);
+            }
+        };
+
+        let mut normalized = String::with_capacity(account_id.len());
+        // The run of separators since the last other character.
+        let mut separators = String::new();
+        for c in account_id.chars() {
+            if is_separator(c) {
+                separators.push(c);
+                continue;
+            }
+            if !separators.is_empty() {
+                if normalized.is_empty() {
+                    add_fix(Fix::TrimmedSeparators);
+                } else {
+                    if separators.len() > 1 {
+                        add_fix(Fix::CollapsedSeparators);
+                    }
+                    normalized.push(if separators.contains('.') {
+                        '.'
+                    } else {
+                        separators.chars().next().unwrap()
+                    });
+                }
+                separators.clear();
+            }
+            if c.is_ascii_uppercase() {
+                add_fix(Fix::Lowercased);
+            }
+            normalized.push(c.to_ascii_lowercase());
+        }
+        if !separators.is_empty() {
+            add_fix(Fix::TrimmedSeparators);
+        }
+
+        let account_id = normalized.parse()?;
+        Ok(Normalized { account_id, fixes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        for (account_id, expected, expected_fixes) in [
+            ("alice.near", "alice.near", vec![]),
+            ("ALICE.Near", "alice.near", vec![Fix::Lowercased]),
+            ("bob__carol", "bob_carol", vec![Fix::CollapsedSeparators]),
+            ("app-_.stage..testnet", "app.stage.testnet", vec![Fix::CollapsedSeparators]),
+            ("_alice.", "alice", vec![Fix::TrimmedSeparators]),
+            (
+                "..Not-_Alice.near-",
+                "not-alice.near",
+                vec![Fix::TrimmedSeparators, Fix::Lowercased, Fix::CollapsedSeparators],
+            ),
+        ] {
+            let normalized = AccountId::normalize(account_id).unwrap();
+            assert_eq!(normalized.account_id.to_string(), expected, "{}", account_id);
+            assert_eq!(normalized.fixes, expected_fixes, "{}", account_id);
+        }
+
+        for account_id in ["alice near", "ƒelicia.near", "_a_", "-", ""] {
+            assert!(AccountId::normalize(account_id).is_err(), "{}", account_id);
+        }
+    }
+}
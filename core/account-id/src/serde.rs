@@ -1,4 +1,6 @@
-use super::AccountId;
+use super::{AccountId, AccountIdRef};
+
+use alloc::boxed::Box;
 
 use serde::{de, ser};
 
@@ -22,11 +24,32 @@ impl<'de> de::Deserialize<'de> for AccountId {
     }
 }
 
+impl ser::Serialize for AccountIdRef {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Borrows the Account ID from the input, which fails if it can't be borrowed, like a JSON
+/// string with escapes.
+impl<'de: 'a, 'a> de::Deserialize<'de> for &'a AccountIdRef {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let account_id = <&str>::deserialize(deserializer)?;
+        AccountIdRef::new(account_id).map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
         super::tests::{BAD_ACCOUNT_IDS, OK_ACCOUNT_IDS},
-        AccountId,
+        AccountId, AccountIdRef,
     };
     use serde_json::json;
 
@@ -48,6 +71,14 @@ mod tests {
                     panic!("failed to serialize account ID {:?}: {}", account_id, err)
                 });
             assert_eq!(serialized_account_id, json!(account_id));
+
+            let json = serialized_account_id.to_string();
+            let borrowed_account_id: &AccountIdRef =
+                serde_json::from_str(&json).unwrap_or_else(|err| {
+                    panic!("failed to deserialize account ID {:?}: {}", account_id, err)
+                });
+            assert_eq!(borrowed_account_id, &*parsed_account_id);
+            assert_eq!(serde_json::to_string(borrowed_account_id).unwrap(), json);
         }
 
         for account_id in BAD_ACCOUNT_IDS.iter().cloned() {
@@ -56,6 +87,11 @@ mod tests {
                 "successfully deserialized invalid account ID {:?}",
                 account_id
             );
+            assert!(
+                serde_json::from_str::<&AccountIdRef>(&json!(account_id).to_string()).is_err(),
+                "successfully deserialized invalid account ID {:?}",
+                account_id
+            );
         }
     }
 }
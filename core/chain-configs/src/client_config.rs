@@ -1,13 +1,20 @@
 //! Chain Client Configuration
 use std::cmp::min;
+use std::path::PathBuf;
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use near_primitives::hash::CryptoHash;
+use near_primitives::receipt::FailedReceiptKind;
 use near_primitives::types::{AccountId, BlockHeightDelta, Gas, NumBlocks, NumSeats, ShardId};
-use near_primitives::version::Version;
+use near_primitives::version::{ProtocolVersion, Version};
 
 pub const TEST_STATE_SYNC_TIMEOUT: u64 = 5;
+/// Default number of chunk request retries after which the full chunk is requested from
+/// archival peers.
+pub const CHUNK_REQUEST_RETRY_BUDGET: u32 = 10;
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum LogSummaryStyle {
@@ -17,6 +24,155 @@ pub enum LogSummaryStyle {
     Colored,
 }
 
+/// Conditions under which a validator stops signing blocks, chunks and approvals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Maximum difference between the local clock and timestamps of blocks received from peers.
+    pub max_clock_skew: Duration,
+    /// Number of consecutive own chunks not included in the block at their height.
+    pub max_rejected_chunks: u64,
+    /// Number of blocks after the last rejected chunk after which the rejected chunks trip is
+    /// cleared. No chunks are produced while it is tripped, so none can be included either.
+    pub rejected_chunks_reset_blocks: BlockHeightDelta,
+    /// How far ahead of our head the majority of peers have to be to consider us on a minority fork.
+    pub max_height_lag: BlockHeightDelta,
+    /// Minimum number of peers reporting their height to check for a minority fork.
+    pub min_peers: usize,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            max_clock_skew: Duration::from_secs(10),
+            max_rejected_chunks: 10,
+            rejected_chunks_reset_blocks: 20,
+            max_height_lag: 20,
+            min_peers: 5,
+        }
+    }
+}
+
+/// Handling of state roots which keep disagreeing with the chunks of the blocks we receive,
+/// which most likely means the local state is corrupted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateQuarantineConfig {
+    /// Number of consecutive blocks with a mismatching chunk after which the shard is
+    /// quarantined: no chunks are produced and no challenges are sent for it.
+    pub max_mismatches: u64,
+    /// Directory the diagnostic bundles of the mismatching chunks are written to.
+    pub diagnostics_dir: PathBuf,
+}
+
+impl Default for StateQuarantineConfig {
+    fn default() -> Self {
+        StateQuarantineConfig { max_mismatches: 3, diagnostics_dir: PathBuf::from("quarantine") }
+    }
+}
+
+/// Actions taken as free space on the volume of the database runs low, from the least to the
+/// most disruptive. Thresholds are in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskMonitorConfig {
+    /// How often free space is checked.
+    pub check_period: Duration,
+    /// Below this, only the epoch of the head and the previous one are kept, and extra garbage
+    /// collection passes are run on every check.
+    pub extra_gc_threshold: u64,
+    /// Number of blocks garbage collected by every extra pass.
+    pub extra_gc_blocks_limit: NumBlocks,
+    /// Below this, the database is also compacted to reclaim the space of deleted data.
+    pub compaction_threshold: u64,
+    /// Below this, the node stops before the database runs out of space.
+    pub halt_threshold: u64,
+}
+
+const GIB: u64 = 1 << 30;
+
+impl Default for DiskMonitorConfig {
+    fn default() -> Self {
+        DiskMonitorConfig {
+            check_period: Duration::from_secs(10),
+            extra_gc_threshold: 100 * GIB,
+            extra_gc_blocks_limit: 1000,
+            compaction_threshold: 50 * GIB,
+            halt_threshold: 10 * GIB,
+        }
+    }
+}
+
+/// Signed heartbeats a validator posts to uptime monitoring services, which then need no access to
+/// the RPC of the node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LivenessBeaconConfig {
+    /// URLs the heartbeats are posted to.
+    pub endpoints: Vec<String>,
+    /// Time between heartbeats.
+    pub interval: Duration,
+}
+
+impl Default for LivenessBeaconConfig {
+    fn default() -> Self {
+        LivenessBeaconConfig { endpoints: vec![], interval: Duration::from_secs(60) }
+    }
+}
+
+/// When the produced blocks vote for the protocol version of the binary, instead of as soon as
+/// the binary runs. Until then they vote for the protocol version of the current epoch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProtocolUpgradeConfig {
+    /// Vote for the protocol version of the binary if it is not above this one.
+    pub acknowledged_protocol_version: Option<ProtocolVersion>,
+    /// Vote for the protocol version of the binary from this time on, acknowledged or not.
+    pub vote_after: Option<DateTime<Utc>>,
+}
+
+/// Limits of the transaction pool of every shard. Each shard has its own pool, so that a flood of
+/// transactions for one shard doesn't take the room of the transactions for the others.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionPoolConfig {
+    /// Maximum number of transactions in the pool of a shard. None is no limit.
+    pub max_transactions_per_shard: Option<usize>,
+    /// Maximum sum of the sizes of the transactions in the pool of a shard, in bytes. None is no
+    /// limit.
+    pub max_size_per_shard: Option<u64>,
+}
+
+impl Default for TransactionPoolConfig {
+    fn default() -> Self {
+        TransactionPoolConfig {
+            max_transactions_per_shard: None,
+            max_size_per_shard: Some(100_000_000),
+        }
+    }
+}
+
+/// Receipts which execution failed which are kept, for investigating the failures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailedReceiptsConfig {
+    /// Kinds of failures kept, none are if empty. Failed actions are left out by default, most
+    /// are contracts rejecting their calls.
+    pub kinds: Vec<FailedReceiptKind>,
+    /// Number of epochs before the current one the failed receipts are kept for. They're always
+    /// garbage collected along with their blocks.
+    pub num_epochs_to_keep: u64,
+}
+
+impl Default for FailedReceiptsConfig {
+    fn default() -> Self {
+        FailedReceiptsConfig {
+            kinds: vec![FailedReceiptKind::InvalidTx, FailedReceiptKind::Runtime],
+            num_epochs_to_keep: 1,
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ClientConfig {
     /// Version of the binary.
@@ -77,6 +233,11 @@ pub struct ClientConfig {
     pub catchup_step_period: Duration,
     /// Time between checking to re-request chunks.
     pub chunk_request_retry_period: Duration,
+    /// Number of chunk request retries after which the full chunk is requested from archival peers,
+    /// once the other peers have been asked for it.
+    pub chunk_request_retry_budget: u32,
+    /// Blocks with a timestamp further ahead of the local clock are refused.
+    pub max_block_future_drift: Duration,
     /// Time between running doomslug timer.
     pub doosmslug_step_period: Duration,
     /// Behind this horizon header fetch kicks in.
@@ -101,6 +262,33 @@ pub struct ClientConfig {
     /// genesis file.  The value only affects the RPCs without influencing the
     /// protocol thus changing it per-node doesn’t affect the blockchain.
     pub max_gas_burnt_view: Option<Gas>,
+    /// Pause signing when an anomaly is detected. None disables the circuit breaker.
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Quarantine shards whose state disagrees with the chain. None disables the quarantine.
+    pub state_quarantine: Option<StateQuarantineConfig>,
+    /// Garbage collect more, compact and eventually stop as disk space runs low. None disables
+    /// the monitor.
+    pub disk_monitor: Option<DiskMonitorConfig>,
+    /// Post signed heartbeats of the validator. None disables them.
+    pub liveness_beacon: Option<LivenessBeaconConfig>,
+    /// Only vote for a protocol upgrade once acknowledged by the operator or from a given time.
+    /// None votes for the protocol version of the binary right away.
+    pub protocol_upgrade: Option<ProtocolUpgradeConfig>,
+    /// Limits of the transaction pool of every shard.
+    pub transaction_pool: TransactionPoolConfig,
+    /// Receipts which execution failed which are kept.
+    pub failed_receipts: FailedReceiptsConfig,
+    /// Count the calls made with function call access keys, per contract method.
+    pub save_access_key_usage: bool,
+    /// Record the deployments of contract code on every account.
+    pub save_contract_history: bool,
+    /// Record the transaction statistics of every block.
+    pub save_block_transaction_stats: bool,
+    /// Record the statistics of every epoch.
+    pub save_epoch_chain_stats: bool,
+    /// Garbage collect all the history before the epoch preceding the epoch of this block, once
+    /// final. None keeps the usual number of epochs.
+    pub pruning_checkpoint: Option<CryptoHash>,
 }
 
 impl ClientConfig {
@@ -147,6 +335,8 @@ impl ClientConfig {
                 Duration::from_millis(100),
                 Duration::from_millis(min_block_prod_time / 5),
             ),
+            chunk_request_retry_budget: CHUNK_REQUEST_RETRY_BUDGET,
+            max_block_future_drift: Duration::from_secs(120),
             doosmslug_step_period: Duration::from_millis(100),
             block_header_fetch_horizon: 50,
             gc_blocks_limit: 100,
@@ -159,6 +349,18 @@ impl ClientConfig {
             view_client_throttle_period: Duration::from_secs(1),
             trie_viewer_state_size_limit: None,
             max_gas_burnt_view: None,
+            circuit_breaker: None,
+            state_quarantine: None,
+            disk_monitor: None,
+            liveness_beacon: None,
+            protocol_upgrade: None,
+            transaction_pool: TransactionPoolConfig::default(),
+            failed_receipts: FailedReceiptsConfig::default(),
+            save_access_key_usage: false,
+            save_contract_history: false,
+            save_block_transaction_stats: false,
+            save_epoch_chain_stats: false,
+            pruning_checkpoint: None,
         }
     }
 }
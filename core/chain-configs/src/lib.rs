@@ -2,7 +2,11 @@ mod client_config;
 mod genesis_config;
 pub mod genesis_validate;
 
-pub use client_config::{ClientConfig, LogSummaryStyle, TEST_STATE_SYNC_TIMEOUT};
+pub use client_config::{
+    CircuitBreakerConfig, ClientConfig, DiskMonitorConfig, FailedReceiptsConfig,
+    LivenessBeaconConfig, LogSummaryStyle, ProtocolUpgradeConfig, StateQuarantineConfig,
+    TransactionPoolConfig, CHUNK_REQUEST_RETRY_BUDGET, TEST_STATE_SYNC_TIMEOUT,
+};
 pub use genesis_config::{
     get_initial_supply, Genesis, GenesisConfig, GenesisRecords, ProtocolConfig, ProtocolConfigView,
 };
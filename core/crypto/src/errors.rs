@@ -43,3 +43,11 @@ impl From<ParseKeyTypeError> for ParseSignatureError {
         }
     }
 }
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SigningPolicyError {
+    #[error("invalid signing policy: {threshold} approvals required but only {num_approvers} approvers configured")]
+    InvalidThreshold { threshold: usize, num_approvers: usize },
+    #[error("signing rejected: {approvals} approvals received, {threshold} required")]
+    NotEnoughApprovals { approvals: usize, threshold: usize },
+}
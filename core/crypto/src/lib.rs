@@ -1,10 +1,10 @@
-pub use errors::{ParseKeyError, ParseKeyTypeError, ParseSignatureError};
+pub use errors::{ParseKeyError, ParseKeyTypeError, ParseSignatureError, SigningPolicyError};
 pub use key_file::KeyFile;
 pub use signature::{
     ED25519PublicKey, KeyType, PublicKey, Secp256K1PublicKey, Secp256K1Signature, SecretKey,
     Signature,
 };
-pub use signer::{EmptySigner, InMemorySigner, Signer};
+pub use signer::{CompositeSigner, EmptySigner, InMemorySigner, Signer, SigningApprover};
 
 #[macro_use]
 mod hash;
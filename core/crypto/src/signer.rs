@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::errors::SigningPolicyError;
 use crate::key_conversion::convert_secret_key;
 use crate::key_file::KeyFile;
 use crate::{KeyType, PublicKey, SecretKey, Signature};
@@ -113,3 +114,131 @@ impl From<Arc<InMemorySigner>> for KeyFile {
         }
     }
 }
+
+/// Party whose consent is required before a `CompositeSigner` produces a signature.
+///
+/// Implementations may ask an operator for confirmation, check a second key holder's signature
+/// over the data, consult an external policy file, etc.
+pub trait SigningApprover: Sync + Send {
+    fn approve(&self, data: &[u8]) -> bool;
+}
+
+impl<F: Fn(&[u8]) -> bool + Sync + Send> SigningApprover for F {
+    fn approve(&self, data: &[u8]) -> bool {
+        self(data)
+    }
+}
+
+/// Signer that only signs after at least `threshold` out of its approvers agreed to it
+/// (m-of-n approval policy).  Meant for high-value tooling flows such as validator key
+/// injection and key rotation.
+pub struct CompositeSigner {
+    signer: Arc<dyn Signer>,
+    approvers: Vec<Box<dyn SigningApprover>>,
+    threshold: usize,
+}
+
+impl CompositeSigner {
+    pub fn new(
+        signer: Arc<dyn Signer>,
+        approvers: Vec<Box<dyn SigningApprover>>,
+        threshold: usize,
+    ) -> Result<Self, SigningPolicyError> {
+        if threshold == 0 || threshold > approvers.len() {
+            return Err(SigningPolicyError::InvalidThreshold {
+                threshold,
+                num_approvers: approvers.len(),
+            });
+        }
+        Ok(Self { signer, approvers, threshold })
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.signer.public_key()
+    }
+
+    /// Checks that enough approvers agree to sign the data. Approvers are asked in order and the
+    /// remaining ones are not consulted once the threshold is reached.
+    fn check_approvals(&self, data: &[u8]) -> Result<(), SigningPolicyError> {
+        let mut approvals = 0;
+        for approver in self.approvers.iter() {
+            if approver.approve(data) {
+                approvals += 1;
+                if approvals >= self.threshold {
+                    return Ok(());
+                }
+            }
+        }
+        Err(SigningPolicyError::NotEnoughApprovals { approvals, threshold: self.threshold })
+    }
+
+    /// Signs the data if enough approvers agree. There is no infallible `sign`: the signer isn't
+    /// a `Signer`, so that a rejection can't be missed.
+    pub fn try_sign(&self, data: &[u8]) -> Result<Signature, SigningPolicyError> {
+        self.check_approvals(data)?;
+        Ok(self.signer.sign(data))
+    }
+
+    /// Computes the VRF of the data if enough approvers agree.
+    pub fn try_compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> Result<(crate::vrf::Value, crate::vrf::Proof), SigningPolicyError> {
+        self.check_approvals(data)?;
+        Ok(self.signer.compute_vrf_with_proof(data))
+    }
+
+    /// Verifies a signature of the key, which needs no approval.
+    pub fn verify(&self, data: &[u8], signature: &Signature) -> bool {
+        self.signer.verify(data, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn composite_signer(votes: Vec<bool>, threshold: usize) -> CompositeSigner {
+        let signer =
+            Arc::new(InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test"));
+        let approvers = votes
+            .into_iter()
+            .map(|vote| Box::new(move |_: &[u8]| vote) as Box<dyn SigningApprover>)
+            .collect();
+        CompositeSigner::new(signer, approvers, threshold).unwrap()
+    }
+
+    #[test]
+    fn test_composite_signer_threshold() {
+        let signer = composite_signer(vec![true, false, true], 2);
+        let signature = signer.try_sign(b"data").unwrap();
+        assert!(signer.verify(b"data", &signature));
+
+        let signer = composite_signer(vec![true, false, false], 2);
+        assert!(matches!(
+            signer.try_sign(b"data"),
+            Err(SigningPolicyError::NotEnoughApprovals { approvals: 1, threshold: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_composite_signer_vrf_requires_approvals() {
+        let signer = composite_signer(vec![true, true], 2);
+        assert!(signer.try_compute_vrf_with_proof(b"data").is_ok());
+
+        let signer = composite_signer(vec![false, true], 2);
+        assert!(matches!(
+            signer.try_compute_vrf_with_proof(b"data"),
+            Err(SigningPolicyError::NotEnoughApprovals { approvals: 1, threshold: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_composite_signer_invalid_threshold() {
+        let signer =
+            Arc::new(InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test"));
+        assert!(CompositeSigner::new(signer.clone(), vec![], 1).is_err());
+        let approvers: Vec<Box<dyn SigningApprover>> = vec![Box::new(|_: &[u8]| true)];
+        assert!(CompositeSigner::new(signer, approvers, 0).is_err());
+    }
+}
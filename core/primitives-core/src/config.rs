@@ -329,6 +329,16 @@ pub struct ExtCostsConfig {
     /// Cost for pairing check per byte
     #[cfg(feature = "protocol_feature_alt_bn128")]
     pub alt_bn128_pairing_check_byte: Gas,
+
+    // ##########
+    // # Events #
+    // ##########
+    /// Cost for emitting an event
+    #[cfg(feature = "protocol_feature_emit_event")]
+    pub emit_event_base: Gas,
+    /// Cost per byte of the standard, version and data of an event
+    #[cfg(feature = "protocol_feature_emit_event")]
+    pub emit_event_byte: Gas,
 }
 
 // We multiply the actual computed costs by the fixed factor to ensure we
@@ -405,6 +415,10 @@ impl ExtCostsConfig {
             alt_bn128_g1_sum_base: SAFETY_MULTIPLIER * 1058438125,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             alt_bn128_g1_sum_byte: SAFETY_MULTIPLIER * 25406181,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            emit_event_base: SAFETY_MULTIPLIER * 1181104350,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            emit_event_byte: SAFETY_MULTIPLIER * 4399597,
         }
     }
 
@@ -476,6 +490,10 @@ impl ExtCostsConfig {
             alt_bn128_g1_sum_base: 0,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             alt_bn128_g1_sum_byte: 0,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            emit_event_base: 0,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            emit_event_byte: 0,
         }
     }
 }
@@ -550,6 +568,10 @@ pub enum ExtCosts {
     alt_bn128_g1_sum_base,
     #[cfg(feature = "protocol_feature_alt_bn128")]
     alt_bn128_g1_sum_byte,
+    #[cfg(feature = "protocol_feature_emit_event")]
+    emit_event_base,
+    #[cfg(feature = "protocol_feature_emit_event")]
+    emit_event_byte,
 
     // NOTE: this should be the last element of the enum.
     __count,
@@ -677,6 +699,10 @@ impl ExtCosts {
             alt_bn128_g1_sum_base => config.alt_bn128_g1_sum_base,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             alt_bn128_g1_sum_byte => config.alt_bn128_g1_sum_byte,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            emit_event_base => config.emit_event_base,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            emit_event_byte => config.emit_event_byte,
 
             __count => unreachable!(),
         }
@@ -754,6 +780,10 @@ impl ExtCosts {
             "alt_bn128_g1_sum_base",
             #[cfg(feature = "protocol_feature_alt_bn128")]
             "alt_bn128_g1_sum_byte",
+            #[cfg(feature = "protocol_feature_emit_event")]
+            "emit_event_base",
+            #[cfg(feature = "protocol_feature_emit_event")]
+            "emit_event_byte",
         ][index]
     }
 }
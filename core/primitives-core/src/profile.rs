@@ -241,8 +241,17 @@ impl Cost {
         Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_base },
         #[cfg(feature = "protocol_feature_alt_bn128")]
         Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_byte },
+        #[cfg(feature = "protocol_feature_emit_event")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::emit_event_base },
+        #[cfg(feature = "protocol_feature_emit_event")]
+        Cost::ExtCost { ext_cost_kind: ExtCosts::emit_event_byte },
     ];
 
+    /// Number of the alt_bn128 costs, which come before the event ones when enabled.
+    #[cfg(feature = "protocol_feature_emit_event")]
+    const NUM_ALT_BN128_COSTS: usize =
+        if cfg!(feature = "protocol_feature_alt_bn128") { 7 } else { 0 };
+
     pub fn index(self) -> usize {
         match self {
             Cost::ActionCost { action_cost_kind: ActionCosts::create_account } => 0,
@@ -322,6 +331,14 @@ impl Cost {
             Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_base } => 67,
             #[cfg(feature = "protocol_feature_alt_bn128")]
             Cost::ExtCost { ext_cost_kind: ExtCosts::alt_bn128_g1_sum_byte } => 68,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::emit_event_base } => {
+                62 + Self::NUM_ALT_BN128_COSTS
+            }
+            #[cfg(feature = "protocol_feature_emit_event")]
+            Cost::ExtCost { ext_cost_kind: ExtCosts::emit_event_byte } => {
+                63 + Self::NUM_ALT_BN128_COSTS
+            }
             Cost::ExtCost { ext_cost_kind: ExtCosts::__count } => unreachable!(),
         }
     }
@@ -367,6 +384,13 @@ mod test {
         assert_eq!(res, u64::MAX);
     }
 
+    #[test]
+    fn test_cost_index() {
+        for (index, cost) in Cost::ALL.iter().enumerate() {
+            assert_eq!(cost.index(), index, "{:?}", cost);
+        }
+    }
+
     #[test]
     fn test_merge() {
         let mut profile_data = ProfileData::new();
@@ -0,0 +1,70 @@
+#[macro_use]
+extern crate bencher;
+
+use bencher::Bencher;
+
+use near_primitives::erasure_coding::{ErasureCodingBackend, ReferenceBackend, SimdBackend};
+
+/// Parts of a 1MB chunk with 100 block producers.
+const DATA_PARTS: usize = 33;
+const PARITY_PARTS: usize = 67;
+const PART_LEN: usize = 1024 * 1024 / DATA_PARTS;
+
+fn data_parts() -> Vec<Option<Box<[u8]>>> {
+    (0..DATA_PARTS + PARITY_PARTS)
+        .map(|i| {
+            if i < DATA_PARTS {
+                Some((0..PART_LEN).map(|j| (i * j) as u8).collect::<Vec<u8>>().into_boxed_slice())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn encode(bench: &mut Bencher, backend: &mut dyn ErasureCodingBackend) {
+    let parts = data_parts();
+    bench.bytes = (DATA_PARTS * PART_LEN) as u64;
+    bench.iter(|| {
+        let mut parts = parts.clone();
+        backend.reconstruct(&mut parts).unwrap();
+        parts
+    });
+}
+
+fn decode(bench: &mut Bencher, backend: &mut dyn ErasureCodingBackend) {
+    let mut parts = data_parts();
+    backend.reconstruct(&mut parts).unwrap();
+    for part in parts[..DATA_PARTS].iter_mut() {
+        *part = None;
+    }
+    bench.bytes = (DATA_PARTS * PART_LEN) as u64;
+    bench.iter(|| {
+        let mut parts = parts.clone();
+        backend.reconstruct(&mut parts).unwrap();
+        parts
+    });
+}
+
+fn encode_reference(bench: &mut Bencher) {
+    encode(bench, &mut ReferenceBackend::new(DATA_PARTS, PARITY_PARTS).unwrap());
+}
+
+fn encode_simd(bench: &mut Bencher) {
+    if let Some(mut backend) = SimdBackend::new(DATA_PARTS, PARITY_PARTS).unwrap() {
+        encode(bench, &mut backend);
+    }
+}
+
+fn decode_reference(bench: &mut Bencher) {
+    decode(bench, &mut ReferenceBackend::new(DATA_PARTS, PARITY_PARTS).unwrap());
+}
+
+fn decode_simd(bench: &mut Bencher) {
+    if let Some(mut backend) = SimdBackend::new(DATA_PARTS, PARITY_PARTS).unwrap() {
+        decode(bench, &mut backend);
+    }
+}
+
+benchmark_group!(benches, encode_reference, encode_simd, decode_reference, decode_simd);
+benchmark_main!(benches);
@@ -47,6 +47,7 @@ fn create_block() -> Block {
     );
     let signer = InMemoryValidatorSigner::from_random(AccountId::test_account(), KeyType::ED25519);
     Block::produce(
+        PROTOCOL_VERSION,
         PROTOCOL_VERSION,
         PROTOCOL_VERSION,
         genesis.header(),
@@ -0,0 +1,135 @@
+//! Backoff of the requests resent to peers until they answer.
+//!
+//! The sync components resend their requests on a timer, and when they all use a fixed delay the
+//! resends of many requests, or of many nodes after the same hiccup, hit the peers at the same
+//! time. `Backoff` grows the delay after every attempt up to a maximum, jitters it so that
+//! concurrent requests spread out, and counts the retries against a budget after which the caller
+//! is expected to stop or to fall back to other peers.
+
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackoffConfig {
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+    /// Largest delay between two attempts, before the jitter.
+    pub max_delay: Duration,
+    /// Factor by which the delay grows after every retry, 1 for a fixed delay.
+    pub multiplier: f64,
+    /// Delays are increased by a random fraction of them, up to this one.
+    pub jitter: f64,
+    /// Number of retries after which the budget is exhausted, unlimited if `None`.
+    pub budget: Option<u32>,
+}
+
+impl BackoffConfig {
+    /// Retries after a fixed delay, which is jittered.
+    pub fn fixed(delay: Duration, jitter: f64) -> Self {
+        Self { initial_delay: delay, max_delay: delay, multiplier: 1.0, jitter, budget: None }
+    }
+
+    /// Retries after a delay doubling every time, up to `max_delay`, with a 10% jitter.
+    pub fn exponential(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self { initial_delay, max_delay, multiplier: 2.0, jitter: 0.1, budget: None }
+    }
+
+    pub fn with_budget(self, budget: u32) -> Self {
+        Self { budget: Some(budget), ..self }
+    }
+}
+
+/// Schedule of the attempts of one request.
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    config: BackoffConfig,
+    /// Delay before the next retry, without the jitter.
+    base_delay: Duration,
+    /// Delay before the next retry.
+    delay: Duration,
+    last_attempt: Instant,
+    retries: u32,
+}
+
+impl Backoff {
+    /// Starts the backoff of a request first sent at `now`.
+    pub fn new(config: BackoffConfig, now: Instant) -> Self {
+        let base_delay = config.initial_delay;
+        let delay = jittered(base_delay, config.jitter);
+        Self { config, base_delay, delay, last_attempt: now, retries: 0 }
+    }
+
+    /// Whether the request should be resent at `now`.
+    pub fn is_due(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_attempt) >= self.delay
+    }
+
+    /// Records that the request was resent at `now`, and schedules the next retry.
+    pub fn record_retry(&mut self, now: Instant) {
+        self.retries += 1;
+        self.last_attempt = now;
+        self.base_delay =
+            self.base_delay.mul_f64(self.config.multiplier).min(self.config.max_delay);
+        self.delay = jittered(self.base_delay, self.config.jitter);
+    }
+
+    /// Number of times the request has been resent.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Whether the request has been resent more times than the budget allows.
+    pub fn is_budget_exhausted(&self) -> bool {
+        self.config.budget.map_or(false, |budget| self.retries > budget)
+    }
+
+    /// Whether the last retry is the first one over the budget.
+    pub fn has_just_exhausted_budget(&self) -> bool {
+        self.config.budget.map_or(false, |budget| self.retries == budget + 1)
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+fn jittered(delay: Duration, jitter: f64) -> Duration {
+    if jitter <= 0.0 {
+        return delay;
+    }
+    delay.mul_f64(1.0 + rand::thread_rng().gen::<f64>() * jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let start = Instant::now();
+        let config = BackoffConfig {
+            jitter: 0.0,
+            ..BackoffConfig::exponential(Duration::from_secs(1), Duration::from_secs(3))
+        }
+        .with_budget(2);
+        let mut backoff = Backoff::new(config, start);
+        let mut now = start;
+        let mut delays = vec![];
+        while !backoff.is_budget_exhausted() {
+            assert!(!backoff.is_due(now + backoff.delay() - Duration::from_millis(1)));
+            now += backoff.delay();
+            assert!(backoff.is_due(now));
+            delays.push(backoff.delay().as_secs());
+            backoff.record_retry(now);
+            assert_eq!(backoff.has_just_exhausted_budget(), backoff.retries() == 3);
+        }
+        assert_eq!(delays, vec![1, 2, 3]);
+
+        let backoff =
+            Backoff::new(BackoffConfig::fixed(Duration::from_secs(10), 0.5), Instant::now());
+        assert!(backoff.delay() >= Duration::from_secs(10));
+        assert!(backoff.delay() <= Duration::from_secs(15));
+        assert!(!backoff.is_budget_exhausted());
+    }
+}
@@ -196,9 +196,11 @@ impl Block {
     }
 
     /// Produces new block from header of previous block, current state root and set of transactions.
+    /// `latest_protocol_version` is the protocol version the block votes for.
     pub fn produce(
         this_epoch_protocol_version: ProtocolVersion,
         next_epoch_protocol_version: ProtocolVersion,
+        latest_protocol_version: ProtocolVersion,
         prev: &BlockHeader,
         height: BlockHeight,
         block_ordinal: NumBlocks,
@@ -272,6 +274,7 @@ impl Block {
         let header = BlockHeader::new(
             this_epoch_protocol_version,
             next_epoch_protocol_version,
+            latest_protocol_version,
             height,
             prev.hash().clone(),
             Block::compute_state_root(&chunks),
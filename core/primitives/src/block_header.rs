@@ -15,7 +15,7 @@ use crate::types::validator_stake::{ValidatorStake, ValidatorStakeIter, Validato
 use crate::types::{AccountId, Balance, BlockHeight, EpochId, MerkleHash, NumBlocks};
 use crate::utils::{from_timestamp, to_timestamp};
 use crate::validator_signer::ValidatorSigner;
-use crate::version::{ProtocolVersion, PROTOCOL_VERSION};
+use crate::version::ProtocolVersion;
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Debug, Clone, Eq, PartialEq)]
@@ -346,6 +346,7 @@ impl BlockHeader {
     pub fn new(
         this_epoch_protocol_version: ProtocolVersion,
         next_epoch_protocol_version: ProtocolVersion,
+        latest_protocol_version: ProtocolVersion,
         height: BlockHeight,
         prev_hash: CryptoHash,
         prev_state_root: MerkleHash,
@@ -404,7 +405,7 @@ impl BlockHeader {
                 last_final_block,
                 last_ds_final_block,
                 approvals,
-                latest_protocol_version: PROTOCOL_VERSION,
+                latest_protocol_version,
             };
             let (hash, signature) = signer.sign_block_header_parts(
                 prev_hash,
@@ -433,7 +434,7 @@ impl BlockHeader {
                 last_final_block,
                 last_ds_final_block,
                 approvals,
-                latest_protocol_version: PROTOCOL_VERSION,
+                latest_protocol_version,
             };
             let (hash, signature) = signer.sign_block_header_parts(
                 prev_hash,
@@ -465,7 +466,7 @@ impl BlockHeader {
                 prev_height,
                 epoch_sync_data_hash,
                 approvals,
-                latest_protocol_version: PROTOCOL_VERSION,
+                latest_protocol_version,
             };
             let (hash, signature) = signer.sign_block_header_parts(
                 prev_hash,
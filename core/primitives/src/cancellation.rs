@@ -0,0 +1,88 @@
+//! Cancellation of the work done on behalf of requests whose client went away.
+//!
+//! The JSON RPC server creates a token for the expensive requests and cancels it once the
+//! request is dropped, which is when the client disconnects. The view client runs the request
+//! with the token entered on its thread, so that the reads of the database and the iteration of
+//! the state, which check `is_thread_cancelled`, stop early instead of finishing work nobody
+//! waits for anymore.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Returns a guard cancelling the token when dropped, to be kept along with the request.
+    pub fn cancel_on_drop(&self) -> CancelOnDrop {
+        CancelOnDrop(self.clone())
+    }
+}
+
+pub struct CancelOnDrop(CancellationToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+thread_local! {
+    static THREAD_CANCELLATION: RefCell<Option<CancellationToken>> = RefCell::new(None);
+}
+
+/// Work of the current thread is done on behalf of the token until the guard is dropped.
+pub fn enter(token: CancellationToken) -> EnteredCancellation {
+    let previous = THREAD_CANCELLATION.with(|cell| cell.replace(Some(token)));
+    EnteredCancellation { previous }
+}
+
+/// Whether the token entered on the current thread, if any, is cancelled.
+pub fn is_thread_cancelled() -> bool {
+    THREAD_CANCELLATION
+        .with(|cell| cell.borrow().as_ref().map_or(false, CancellationToken::is_cancelled))
+}
+
+pub struct EnteredCancellation {
+    previous: Option<CancellationToken>,
+}
+
+impl Drop for EnteredCancellation {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        THREAD_CANCELLATION.with(|cell| cell.replace(previous));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_cancellation() {
+        assert!(!is_thread_cancelled());
+        let token = CancellationToken::new();
+        let cancel_on_drop = token.cancel_on_drop();
+        {
+            let _entered = enter(token.clone());
+            assert!(!is_thread_cancelled());
+            drop(cancel_on_drop);
+            assert!(is_thread_cancelled());
+        }
+        assert!(token.is_cancelled());
+        assert!(!is_thread_cancelled());
+    }
+}
@@ -0,0 +1,561 @@
+//! Reed-Solomon erasure coding of chunk parts.
+//!
+//! Chunks are split into data parts, extended with parity parts and any `data_shard_count`
+//! parts are enough to reconstruct the others. The coding is part of the protocol: every
+//! backend must produce exactly the parts of the `reed-solomon-erasure` crate, which is kept as
+//! the reference implementation.
+//!
+//! The SIMD backend implements the same code, over the same field and with the same encoding
+//! matrix, multiplying whole parts by a constant with the split nibble tables technique of
+//! ISA-L. It is used whenever the CPU supports SSSE3 or AVX2.
+
+use reed_solomon_erasure::galois_8::ReedSolomon;
+use reed_solomon_erasure::Error;
+
+/// Maximum number of parts, the size of the field.
+const MAX_SHARDS: usize = 256;
+
+/// The ttl for a reed solomon instance to control memory usage. This number below corresponds to
+/// roughly 60MB of memory usage.
+const RS_TTL: u64 = 2 * 1024;
+
+pub trait ErasureCodingBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn data_shard_count(&self) -> usize;
+
+    fn total_shard_count(&self) -> usize;
+
+    /// Computes the missing parts from the present ones. Parts are the data parts followed by
+    /// the parity parts, encoding is reconstructing all the parity parts.
+    fn reconstruct(&mut self, parts: &mut [Option<Box<[u8]>>]) -> Result<(), Error>;
+}
+
+/// Returns the fastest backend supported by the CPU.
+pub fn default_backend(
+    data_shards: usize,
+    parity_shards: usize,
+) -> Result<Box<dyn ErasureCodingBackend>, Error> {
+    match SimdBackend::new(data_shards, parity_shards)? {
+        Some(backend) => Ok(Box::new(backend)),
+        None => Ok(Box::new(ReferenceBackend::new(data_shards, parity_shards)?)),
+    }
+}
+
+/// The `reed-solomon-erasure` crate.
+///
+/// The underlying reed solomon is reset occasionally to work around its memory leak
+/// <https://github.com/darrenldl/reed-solomon-erasure/issues/74>
+pub struct ReferenceBackend {
+    rs: ReedSolomon,
+    ttl: u64,
+}
+
+impl ReferenceBackend {
+    pub fn new(data_shards: usize, parity_shards: usize) -> Result<Self, Error> {
+        Ok(Self { rs: ReedSolomon::new(data_shards, parity_shards)?, ttl: RS_TTL })
+    }
+}
+
+impl ErasureCodingBackend for ReferenceBackend {
+    fn name(&self) -> &'static str {
+        "reference"
+    }
+
+    fn data_shard_count(&self) -> usize {
+        self.rs.data_shard_count()
+    }
+
+    fn total_shard_count(&self) -> usize {
+        self.rs.total_shard_count()
+    }
+
+    fn reconstruct(&mut self, parts: &mut [Option<Box<[u8]>>]) -> Result<(), Error> {
+        let res = self.rs.reconstruct(parts);
+        self.ttl -= 1;
+        if self.ttl == 0 {
+            self.rs =
+                ReedSolomon::new(self.rs.data_shard_count(), self.rs.parity_shard_count()).unwrap();
+            self.ttl = RS_TTL;
+        }
+        res
+    }
+}
+
+/// Adds the product of `input` by a constant to `output`.
+type MulAddFn = fn(u8, &[u8], &mut [u8]);
+
+pub struct SimdBackend {
+    data_shards: usize,
+    /// Encoding matrix, of `total_shard_count` rows and `data_shard_count` columns. The rows
+    /// of the data parts are the identity.
+    matrix: Vec<Vec<u8>>,
+    name: &'static str,
+    mul_add: MulAddFn,
+}
+
+impl SimdBackend {
+    /// Returns `None` if the CPU supports neither SSSE3 nor AVX2.
+    pub fn new(data_shards: usize, parity_shards: usize) -> Result<Option<Self>, Error> {
+        match detect_kernel() {
+            Some((name, mul_add)) => {
+                Self::with_kernel(data_shards, parity_shards, name, mul_add).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn with_kernel(
+        data_shards: usize,
+        parity_shards: usize,
+        name: &'static str,
+        mul_add: MulAddFn,
+    ) -> Result<Self, Error> {
+        if data_shards == 0 {
+            return Err(Error::TooFewDataShards);
+        }
+        if parity_shards == 0 {
+            return Err(Error::TooFewParityShards);
+        }
+        if data_shards + parity_shards > MAX_SHARDS {
+            return Err(Error::TooManyShards);
+        }
+        let matrix = encoding_matrix(data_shards, data_shards + parity_shards);
+        Ok(Self { data_shards, matrix, name, mul_add })
+    }
+
+    /// Sum of the products of `inputs` by the coefficients.
+    fn combine(&self, coefficients: &[u8], inputs: &[&[u8]], len: usize) -> Box<[u8]> {
+        let mut output = vec![0; len];
+        for (&coefficient, input) in coefficients.iter().zip(inputs) {
+            if coefficient != 0 {
+                (self.mul_add)(coefficient, input, &mut output);
+            }
+        }
+        output.into_boxed_slice()
+    }
+}
+
+impl ErasureCodingBackend for SimdBackend {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn data_shard_count(&self) -> usize {
+        self.data_shards
+    }
+
+    fn total_shard_count(&self) -> usize {
+        self.matrix.len()
+    }
+
+    /// Fails and decodes in the same cases and the same way as the reference implementation.
+    fn reconstruct(&mut self, parts: &mut [Option<Box<[u8]>>]) -> Result<(), Error> {
+        let total_shards = self.total_shard_count();
+        if parts.len() < total_shards {
+            return Err(Error::TooFewShards);
+        }
+        if parts.len() > total_shards {
+            return Err(Error::TooManyShards);
+        }
+        let mut num_present = 0;
+        let mut part_len = None;
+        for part in parts.iter().flatten() {
+            if part.is_empty() {
+                return Err(Error::EmptyShard);
+            }
+            if part_len.map_or(false, |len| len != part.len()) {
+                return Err(Error::IncorrectShardSize);
+            }
+            part_len = Some(part.len());
+            num_present += 1;
+        }
+        if num_present == total_shards {
+            return Ok(());
+        }
+        if num_present < self.data_shards {
+            return Err(Error::TooFewShardsPresent);
+        }
+        let part_len = part_len.unwrap();
+
+        if parts[..self.data_shards].iter().any(Option::is_none) {
+            // Decode from the first present parts, as the reference implementation does.
+            let valid_indices: Vec<usize> =
+                (0..total_shards).filter(|&i| parts[i].is_some()).take(self.data_shards).collect();
+            let decoding_matrix =
+                invert(valid_indices.iter().map(|&i| self.matrix[i].clone()).collect())
+                    .expect("sub-matrices of the encoding matrix are invertible");
+            let inputs: Vec<&[u8]> =
+                valid_indices.iter().map(|&i| parts[i].as_deref().unwrap()).collect();
+            let decoded: Vec<(usize, Box<[u8]>)> = (0..self.data_shards)
+                .filter(|&i| parts[i].is_none())
+                .map(|i| (i, self.combine(&decoding_matrix[i], &inputs, part_len)))
+                .collect();
+            for (i, part) in decoded {
+                parts[i] = Some(part);
+            }
+        }
+
+        let inputs: Vec<&[u8]> =
+            parts[..self.data_shards].iter().map(|part| part.as_deref().unwrap()).collect();
+        let encoded: Vec<(usize, Box<[u8]>)> = (self.data_shards..total_shards)
+            .filter(|&i| parts[i].is_none())
+            .map(|i| (i, self.combine(&self.matrix[i], &inputs, part_len)))
+            .collect();
+        for (i, part) in encoded {
+            parts[i] = Some(part);
+        }
+        Ok(())
+    }
+}
+
+/// Arithmetic of GF(2^8) with the polynomial and generator of the reference implementation.
+mod galois {
+    /// x^8 + x^4 + x^3 + x^2 + 1, without the x^8 term.
+    const GENERATING_POLYNOMIAL: usize = 29;
+
+    const fn tables() -> ([u8; 256], [u8; 510]) {
+        let mut log = [0u8; 256];
+        let mut exp = [0u8; 510];
+        let mut b = 1usize;
+        let mut i = 0;
+        while i < 255 {
+            log[b] = i as u8;
+            exp[i] = b as u8;
+            exp[i + 255] = b as u8;
+            b <<= 1;
+            if b >= 256 {
+                b = (b - 256) ^ GENERATING_POLYNOMIAL;
+            }
+            i += 1;
+        }
+        (log, exp)
+    }
+
+    const LOG_TABLE: [u8; 256] = tables().0;
+    const EXP_TABLE: [u8; 510] = tables().1;
+
+    pub fn mul(a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            EXP_TABLE[LOG_TABLE[a as usize] as usize + LOG_TABLE[b as usize] as usize]
+        }
+    }
+
+    pub fn div(a: u8, b: u8) -> u8 {
+        assert!(b != 0, "division by zero");
+        if a == 0 {
+            0
+        } else {
+            EXP_TABLE[LOG_TABLE[a as usize] as usize + 255 - LOG_TABLE[b as usize] as usize]
+        }
+    }
+
+    /// `a` to the power `n`, with 0^0 = 1.
+    pub fn exp(a: u8, n: usize) -> u8 {
+        if n == 0 {
+            1
+        } else if a == 0 {
+            0
+        } else {
+            EXP_TABLE[LOG_TABLE[a as usize] as usize * n % 255]
+        }
+    }
+}
+
+/// Vandermonde matrix of `total_shards` rows, multiplied by the inverse of its top square so
+/// that the data parts are encoded as themselves.
+fn encoding_matrix(data_shards: usize, total_shards: usize) -> Vec<Vec<u8>> {
+    let vandermonde: Vec<Vec<u8>> = (0..total_shards)
+        .map(|row| (0..data_shards).map(|col| galois::exp(row as u8, col)).collect())
+        .collect();
+    let top_inverse = invert(vandermonde[..data_shards].to_vec())
+        .expect("top of the vandermonde matrix is invertible");
+    vandermonde
+        .iter()
+        .map(|row| {
+            (0..data_shards)
+                .map(|col| {
+                    row.iter()
+                        .zip(&top_inverse)
+                        .fold(0, |acc, (&a, inverse_row)| acc ^ galois::mul(a, inverse_row[col]))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Gauss-Jordan elimination of a square matrix. Returns `None` if it is singular.
+fn invert(mut matrix: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<u8>> =
+        (0..n).map(|row| (0..n).map(|col| (row == col) as u8).collect()).collect();
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| matrix[row][col] != 0)?;
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+        let scale = galois::div(1, matrix[col][col]);
+        for value in matrix[col].iter_mut().chain(inverse[col].iter_mut()) {
+            *value = galois::mul(*value, scale);
+        }
+        let (pivot_row, pivot_inverse) = (matrix[col].clone(), inverse[col].clone());
+        for row in 0..n {
+            let factor = matrix[row][col];
+            if row == col || factor == 0 {
+                continue;
+            }
+            for (value, &pivot) in matrix[row].iter_mut().zip(&pivot_row) {
+                *value ^= galois::mul(factor, pivot);
+            }
+            for (value, &pivot) in inverse[row].iter_mut().zip(&pivot_inverse) {
+                *value ^= galois::mul(factor, pivot);
+            }
+        }
+    }
+    Some(inverse)
+}
+
+fn mul_add_scalar(c: u8, input: &[u8], output: &mut [u8]) {
+    let mut products = [0u8; 256];
+    for (i, product) in products.iter_mut().enumerate() {
+        *product = galois::mul(c, i as u8);
+    }
+    for (output, &input) in output.iter_mut().zip(input) {
+        *output ^= products[input as usize];
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    use super::galois;
+
+    /// Products of `c` by every value of the low and of the high nibble of a byte. The product
+    /// of a byte is the xor of the products of its nibbles.
+    fn nibble_tables(c: u8) -> ([u8; 16], [u8; 16]) {
+        let mut low = [0; 16];
+        let mut high = [0; 16];
+        for (i, (low, high)) in low.iter_mut().zip(high.iter_mut()).enumerate() {
+            *low = galois::mul(c, i as u8);
+            *high = galois::mul(c, (i as u8) << 4);
+        }
+        (low, high)
+    }
+
+    /// Bytes left over after the last full vector.
+    fn mul_add_remainder(c: u8, input: &[u8], output: &mut [u8]) {
+        for (output, &input) in output.iter_mut().zip(input) {
+            *output ^= galois::mul(c, input);
+        }
+    }
+
+    /// Only called once SSSE3 support was detected.
+    pub fn mul_add_ssse3(c: u8, input: &[u8], output: &mut [u8]) {
+        unsafe { mul_add_ssse3_impl(c, input, output) }
+    }
+
+    /// Only called once AVX2 support was detected.
+    pub fn mul_add_avx2(c: u8, input: &[u8], output: &mut [u8]) {
+        unsafe { mul_add_avx2_impl(c, input, output) }
+    }
+
+    #[target_feature(enable = "ssse3")]
+    unsafe fn mul_add_ssse3_impl(c: u8, input: &[u8], output: &mut [u8]) {
+        let len = input.len().min(output.len());
+        let (low, high) = nibble_tables(c);
+        let low = _mm_loadu_si128(low.as_ptr() as *const __m128i);
+        let high = _mm_loadu_si128(high.as_ptr() as *const __m128i);
+        let mask = _mm_set1_epi8(0x0f);
+        let vectorized_len = len - len % 16;
+        for i in (0..vectorized_len).step_by(16) {
+            let x = _mm_loadu_si128(input.as_ptr().add(i) as *const __m128i);
+            let low_product = _mm_shuffle_epi8(low, _mm_and_si128(x, mask));
+            let high_product = _mm_shuffle_epi8(high, _mm_and_si128(_mm_srli_epi64(x, 4), mask));
+            let out = output.as_mut_ptr().add(i) as *mut __m128i;
+            let sum = _mm_xor_si128(_mm_loadu_si128(out), _mm_xor_si128(low_product, high_product));
+            _mm_storeu_si128(out, sum);
+        }
+        mul_add_remainder(c, &input[vectorized_len..len], &mut output[vectorized_len..len]);
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn mul_add_avx2_impl(c: u8, input: &[u8], output: &mut [u8]) {
+        let len = input.len().min(output.len());
+        let (low, high) = nibble_tables(c);
+        // Shuffles look up each 128 bits lane separately, both lanes get the tables.
+        let low = _mm256_broadcastsi128_si256(_mm_loadu_si128(low.as_ptr() as *const __m128i));
+        let high = _mm256_broadcastsi128_si256(_mm_loadu_si128(high.as_ptr() as *const __m128i));
+        let mask = _mm256_set1_epi8(0x0f);
+        let vectorized_len = len - len % 32;
+        for i in (0..vectorized_len).step_by(32) {
+            let x = _mm256_loadu_si256(input.as_ptr().add(i) as *const __m256i);
+            let low_product = _mm256_shuffle_epi8(low, _mm256_and_si256(x, mask));
+            let high_product =
+                _mm256_shuffle_epi8(high, _mm256_and_si256(_mm256_srli_epi64(x, 4), mask));
+            let out = output.as_mut_ptr().add(i) as *mut __m256i;
+            let sum = _mm256_xor_si256(
+                _mm256_loadu_si256(out),
+                _mm256_xor_si256(low_product, high_product),
+            );
+            _mm256_storeu_si256(out, sum);
+        }
+        mul_add_remainder(c, &input[vectorized_len..len], &mut output[vectorized_len..len]);
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn detect_kernel() -> Option<(&'static str, MulAddFn)> {
+    if is_x86_feature_detected!("avx2") {
+        Some(("avx2", x86::mul_add_avx2))
+    } else if is_x86_feature_detected!("ssse3") {
+        Some(("ssse3", x86::mul_add_ssse3))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn detect_kernel() -> Option<(&'static str, MulAddFn)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// The kernels supported by the CPU running the tests, the scalar one included.
+    fn kernels() -> Vec<(&'static str, MulAddFn)> {
+        let mut kernels: Vec<(&'static str, MulAddFn)> = vec![("scalar", mul_add_scalar)];
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("ssse3") {
+                kernels.push(("ssse3", x86::mul_add_ssse3));
+            }
+            if is_x86_feature_detected!("avx2") {
+                kernels.push(("avx2", x86::mul_add_avx2));
+            }
+        }
+        kernels
+    }
+
+    fn random_parts(
+        rng: &mut StdRng,
+        data_shards: usize,
+        total_shards: usize,
+        len: usize,
+    ) -> Vec<Option<Box<[u8]>>> {
+        (0..total_shards)
+            .map(|i| {
+                if i < data_shards {
+                    Some((0..len).map(|_| rng.gen()).collect::<Vec<u8>>().into_boxed_slice())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_kernels_match_field_multiplication() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for (name, mul_add) in kernels() {
+            for len in [0, 1, 15, 16, 17, 31, 32, 33, 100, 1000] {
+                let c: u8 = rng.gen();
+                let input: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+                let mut output: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+                let expected: Vec<u8> =
+                    output.iter().zip(&input).map(|(&o, &i)| o ^ galois::mul(c, i)).collect();
+                mul_add(c, &input, &mut output);
+                assert_eq!(output, expected, "kernel {}, len {}", name, len);
+            }
+        }
+    }
+
+    /// Encodes random chunks and reconstructs them from random subsets of their parts with every
+    /// kernel, and checks that the result is always the one of the reference implementation.
+    #[test]
+    fn test_identical_to_reference() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..200 {
+            let data_shards = rng.gen_range(1, 40);
+            let parity_shards = rng.gen_range(1, 80);
+            let total_shards = data_shards + parity_shards;
+            let len = rng.gen_range(1, 300);
+            let mut reference = ReferenceBackend::new(data_shards, parity_shards).unwrap();
+            let mut encoded = random_parts(&mut rng, data_shards, total_shards, len);
+            reference.reconstruct(&mut encoded).unwrap();
+
+            let mut damaged = encoded.clone();
+            let num_missing = rng.gen_range(0, parity_shards + 1);
+            for _ in 0..num_missing {
+                damaged[rng.gen_range(0, total_shards)] = None;
+            }
+
+            for (name, mul_add) in kernels() {
+                let mut backend =
+                    SimdBackend::with_kernel(data_shards, parity_shards, name, mul_add).unwrap();
+                let mut parts = encoded.clone();
+                for part in parts[data_shards..].iter_mut() {
+                    *part = None;
+                }
+                backend.reconstruct(&mut parts).unwrap();
+                assert_eq!(parts, encoded, "encoding with kernel {}", name);
+
+                let mut parts = damaged.clone();
+                backend.reconstruct(&mut parts).unwrap();
+                assert_eq!(parts, encoded, "reconstruction with kernel {}", name);
+            }
+        }
+    }
+
+    /// Parity of the reference implementation for 5 data parts and 5 parity parts.
+    #[test]
+    fn test_known_parity() {
+        for (name, mul_add) in kernels() {
+            let mut backend = SimdBackend::with_kernel(5, 5, name, mul_add).unwrap();
+            let mut parts: Vec<Option<Box<[u8]>>> = [[0, 1], [4, 5], [2, 3], [6, 7], [8, 9]]
+                .iter()
+                .map(|part| Some(part.to_vec().into_boxed_slice()))
+                .chain(std::iter::repeat(None).take(5))
+                .collect();
+            backend.reconstruct(&mut parts).unwrap();
+            let parity: Vec<&[u8]> =
+                parts[5..].iter().map(|part| part.as_deref().unwrap()).collect();
+            assert_eq!(parity, vec![[12, 13], [10, 11], [14, 15], [90, 91], [94, 95]], "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_errors_identical_to_reference() {
+        let mut reference = ReferenceBackend::new(3, 2).unwrap();
+        let mut backend = SimdBackend::with_kernel(3, 2, "scalar", mul_add_scalar).unwrap();
+        let part = |len: usize| Some(vec![1u8; len].into_boxed_slice());
+        let cases: Vec<Vec<Option<Box<[u8]>>>> = vec![
+            vec![part(4), part(4), part(4), None],
+            vec![part(4), part(4), part(4), None, None, None],
+            vec![part(4), None, None, part(4), None],
+            vec![part(4), part(3), part(4), None, None],
+            vec![part(4), part(0), part(4), None, None],
+        ];
+        for case in cases {
+            let expected = reference.reconstruct(&mut case.clone());
+            assert!(expected.is_err());
+            assert_eq!(backend.reconstruct(&mut case.clone()), expected, "{:?}", case);
+        }
+        assert_eq!(
+            SimdBackend::with_kernel(0, 2, "scalar", mul_add_scalar).err(),
+            ReferenceBackend::new(0, 2).err()
+        );
+        assert_eq!(
+            SimdBackend::with_kernel(200, 100, "scalar", mul_add_scalar).err(),
+            ReferenceBackend::new(200, 100).err()
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! Registry of the capabilities the binary was built with.
+//!
+//! Behaviors gated by a cargo feature are invisible once the binary is built, so every crate
+//! registers the features it was compiled with, by name and with a one line description, and the
+//! node reports them in `/status` and in the `EXPERIMENTAL_node_features` RPC along with the
+//! protocol features of [`ProtocolFeature::ALL`].
+//!
+//! Crates list their features in a `BUILD_FEATURES` constant, with a `#[cfg(feature = "...")]`
+//! on every entry, and register it when the node starts.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::version::ProtocolFeature;
+use crate::views::{BuildFeatureView, NodeFeaturesView, ProtocolFeatureView};
+
+/// A cargo feature enabled in the build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuildFeature {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// The features of this crate.
+pub const BUILD_FEATURES: &[BuildFeature] = &[
+    #[cfg(feature = "nightly_protocol")]
+    BuildFeature { name: "nightly_protocol", description: "Use the nightly protocol version" },
+    #[cfg(feature = "nightly_protocol_features")]
+    BuildFeature {
+        name: "nightly_protocol_features",
+        description: "Enable all the protocol features under development",
+    },
+    #[cfg(feature = "deepsize_feature")]
+    BuildFeature { name: "deepsize_feature", description: "Measure the memory used by structures" },
+];
+
+static REGISTRY: Lazy<Mutex<BTreeMap<&'static str, &'static str>>> = Lazy::new(|| {
+    Mutex::new(BUILD_FEATURES.iter().map(|feature| (feature.name, feature.description)).collect())
+});
+
+/// Registers build features. Features enabled in several crates are only listed once and
+/// registering the same list again is a no-op.
+pub fn register_build_features(features: &[BuildFeature]) {
+    let mut registry = REGISTRY.lock().unwrap();
+    for feature in features {
+        registry.insert(feature.name, feature.description);
+    }
+}
+
+/// The registered build features, sorted by name.
+pub fn build_features() -> Vec<BuildFeature> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(&name, &description)| BuildFeature { name, description })
+        .collect()
+}
+
+/// The registered build features and the protocol features compiled into the binary.
+pub fn node_features() -> NodeFeaturesView {
+    NodeFeaturesView {
+        build_features: build_features()
+            .into_iter()
+            .map(|feature| BuildFeatureView {
+                name: feature.name.to_string(),
+                description: feature.description.to_string(),
+            })
+            .collect(),
+        protocol_features: ProtocolFeature::ALL
+            .iter()
+            .map(|&feature| ProtocolFeatureView {
+                name: format!("{:?}", feature),
+                description: feature.description().to_string(),
+                protocol_version: feature.protocol_version(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_build_features() {
+        const FEATURES: &[BuildFeature] = &[
+            BuildFeature { name: "test_feature_b", description: "b" },
+            BuildFeature { name: "test_feature_a", description: "a" },
+        ];
+        register_build_features(FEATURES);
+        register_build_features(FEATURES);
+        let names: Vec<_> = build_features()
+            .into_iter()
+            .map(|feature| feature.name)
+            .filter(|name| name.starts_with("test_feature_"))
+            .collect();
+        assert_eq!(names, vec!["test_feature_a", "test_feature_b"]);
+
+        let features = node_features();
+        assert_eq!(features.protocol_features.len(), ProtocolFeature::ALL.len());
+        let wasmer2 =
+            features.protocol_features.iter().find(|feature| feature.name == "Wasmer2").unwrap();
+        assert_eq!(wasmer2.protocol_version, ProtocolFeature::Wasmer2.protocol_version());
+    }
+}
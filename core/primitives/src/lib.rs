@@ -2,13 +2,17 @@ pub use near_primitives_core::borsh;
 pub use near_primitives_core::num_rational;
 
 pub use near_primitives_core::account;
+pub mod backoff;
 pub mod block;
 pub mod block_header;
+pub mod cancellation;
 pub mod challenge;
 pub use near_primitives_core::config;
 pub use near_primitives_core::contract;
 pub mod epoch_manager;
+pub mod erasure_coding;
 pub mod errors;
+pub mod features;
 pub use near_primitives_core::hash;
 pub use near_primitives_core::logging;
 pub mod merkle;
@@ -32,3 +36,4 @@ pub mod utils;
 pub mod validator_signer;
 pub mod version;
 pub mod views;
+pub mod wasm_exports;
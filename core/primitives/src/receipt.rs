@@ -7,8 +7,10 @@ use deepsize::DeepSizeOf;
 use serde::{Deserialize, Serialize};
 
 use near_crypto::{KeyType, PublicKey};
+use near_vm_errors::{CompilationError, FunctionCallErrorSer};
 
 use crate::borsh::maybestd::collections::HashMap;
+use crate::errors::{ActionErrorKind, TxExecutionError};
 use crate::hash::CryptoHash;
 use crate::logging;
 use crate::serialize::{option_base64_format, u128_dec_format_compatible};
@@ -89,6 +91,45 @@ impl Receipt {
             }),
         }
     }
+
+    /// The kind and amount of the refund, if this is a refund receipt generated by the runtime.
+    pub fn refund(&self) -> Option<(RefundKind, Balance)> {
+        if !self.predecessor_id.is_system() {
+            return None;
+        }
+        match &self.receipt {
+            ReceiptEnum::Action(action_receipt) => match action_receipt.actions.as_slice() {
+                [Action::Transfer(TransferAction { deposit })] => {
+                    let kind = if action_receipt.signer_id.is_system() {
+                        RefundKind::Deposit
+                    } else {
+                        RefundKind::Gas
+                    };
+                    Some((kind, *deposit))
+                }
+                _ => None,
+            },
+            ReceiptEnum::Data(_) => None,
+        }
+    }
+}
+
+/// What a refund receipt gives back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefundKind {
+    /// The prepaid gas which wasn't used, see [`Receipt::new_gas_refund`].
+    Gas,
+    /// The deposit of actions which failed, see [`Receipt::new_balance_refund`].
+    Deposit,
+}
+
+impl RefundKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RefundKind::Gas => "gas",
+            RefundKind::Deposit => "deposit",
+        }
+    }
 }
 
 /// Receipt could be either ActionReceipt or DataReceipt
@@ -169,6 +210,47 @@ impl fmt::Debug for ReceivedData {
     }
 }
 
+/// Receipt which execution failed, kept around so that failures can be investigated without
+/// going through all the execution outcomes of a block.
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct FailedReceipt {
+    pub receipt_id: CryptoHash,
+    /// Account on which the receipt was executed.
+    pub executor_id: AccountId,
+    pub error: TxExecutionError,
+}
+
+/// Kind of the failure of a receipt, to choose which failed receipts are kept.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FailedReceiptKind {
+    /// The receipt could not be executed at all, e.g. its signer couldn't pay for it.
+    InvalidTx,
+    /// The runtime failed to compile or to run a contract, rather than the contract failing.
+    Runtime,
+    /// An action failed, including function calls whose contract panicked or ran out of gas.
+    Action,
+}
+
+impl FailedReceiptKind {
+    pub fn of(error: &TxExecutionError) -> Self {
+        match error {
+            TxExecutionError::InvalidTxError(_) => FailedReceiptKind::InvalidTx,
+            TxExecutionError::ActionError(error) => match &error.kind {
+                ActionErrorKind::FunctionCallError(
+                    FunctionCallErrorSer::WasmUnknownError
+                    | FunctionCallErrorSer::_EVMError
+                    | FunctionCallErrorSer::CompilationError(
+                        CompilationError::WasmerCompileError { .. }
+                        | CompilationError::UnsupportedCompiler { .. },
+                    ),
+                ) => FailedReceiptKind::Runtime,
+                _ => FailedReceiptKind::Action,
+            },
+        }
+    }
+}
+
 /// Stores indices for a persistent queue for delayed receipts that didn't fit into a block.
 #[derive(Default, BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
 pub struct DelayedReceiptIndices {
@@ -180,3 +262,62 @@ pub struct DelayedReceiptIndices {
 
 /// Map of shard to list of receipts to send to it.
 pub type ReceiptResult = HashMap<ShardId, Vec<Receipt>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{ActionError, InvalidTxError};
+    use crate::views::RefundStatsView;
+
+    #[test]
+    fn test_refund_stats() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let receipts = vec![
+            Receipt::new_gas_refund(&alice, 10, PublicKey::empty(KeyType::ED25519)),
+            Receipt::new_gas_refund(&alice, 5, PublicKey::empty(KeyType::ED25519)),
+            Receipt::new_balance_refund(&alice, 100),
+            Receipt { predecessor_id: alice.clone(), ..Receipt::new_balance_refund(&alice, 1) },
+        ];
+        assert_eq!(receipts[0].refund(), Some((RefundKind::Gas, 10)));
+        assert_eq!(receipts[2].refund(), Some((RefundKind::Deposit, 100)));
+        assert_eq!(receipts[3].refund(), None);
+        assert_eq!(
+            RefundStatsView::new(3, &receipts),
+            RefundStatsView {
+                shard_id: 3,
+                gas_refund_receipts: 2,
+                gas_refund_amount: 15,
+                deposit_refund_receipts: 1,
+                deposit_refund_amount: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_failed_receipt_kind() {
+        let action_error =
+            |kind| TxExecutionError::ActionError(ActionError { index: Some(0), kind });
+        assert_eq!(
+            FailedReceiptKind::of(&TxExecutionError::InvalidTxError(InvalidTxError::Expired)),
+            FailedReceiptKind::InvalidTx
+        );
+        assert_eq!(
+            FailedReceiptKind::of(&action_error(ActionErrorKind::FunctionCallError(
+                FunctionCallErrorSer::WasmUnknownError
+            ))),
+            FailedReceiptKind::Runtime
+        );
+        assert_eq!(
+            FailedReceiptKind::of(&action_error(ActionErrorKind::FunctionCallError(
+                FunctionCallErrorSer::ExecutionError("Smart contract panicked".to_string())
+            ))),
+            FailedReceiptKind::Action
+        );
+        assert_eq!(
+            FailedReceiptKind::of(&action_error(ActionErrorKind::AccountDoesNotExist {
+                account_id: "alice.near".parse().unwrap()
+            })),
+            FailedReceiptKind::Action
+        );
+    }
+}
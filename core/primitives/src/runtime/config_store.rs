@@ -113,10 +113,10 @@ mod tests {
     #[test]
     fn test_runtime_config_data() {
         let expected_hashes = vec![
-            "FF2Qg5qSM6iWQjD5ZyzEhdAV2g5MyQKXGYh4kyt8mMcE",
-            "97UzHtVFBc4235jdur3DgNSUGNfGQfzRDLmAkYdZ19Re",
-            "C6uw6BoeXr3KoKpVP34hBA7TqoywMbwMtJgqbTpPCiSB",
-            "2cuq2HvuHT7Z27LUbgEtMxP2ejqrHK34J2V1GL1joiMn",
+            "4a2izhKRpa6pm9MCW2cQJhisQvycR94ttg9eToXEoB9H",
+            "32cnDT9sxdpKZfrKsVVcQAP5z1P7JYmaV3DHzvFCmp6T",
+            "EU4Fkkgo75Xvb9FrcTYVWvQ2mzEjGSFbqvvrwTSB7cNv",
+            "B5aM41aSqBp4YD9LKQh8MwnRoXjamqc3b9w1x2VFeccc",
         ];
         let actual_hashes = CONFIGS
             .iter()
@@ -1,11 +1,11 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 #[cfg(feature = "deepsize_feature")]
 use deepsize::DeepSizeOf;
-use reed_solomon_erasure::galois_8::{Field, ReedSolomon};
 use serde::{Deserialize, Serialize};
 
 use near_crypto::Signature;
 
+use crate::erasure_coding::{default_backend, ErasureCodingBackend};
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::{combine_hash, merklize, MerklePath};
 use crate::receipt::Receipt;
@@ -16,7 +16,6 @@ use crate::validator_signer::ValidatorSigner;
 use crate::version::{
     ProtocolFeature, ProtocolVersion, ProtocolVersionRange, SHARD_CHUNK_HEADER_UPGRADE_VERSION,
 };
-use reed_solomon_erasure::ReconstructShard;
 use std::sync::Arc;
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
@@ -1151,44 +1150,36 @@ impl EncodedShardChunk {
     }
 }
 
-/// The ttl for a reed solomon instance to control memory usage. This number below corresponds to
-/// roughly 60MB of memory usage.
-const RS_TTL: u64 = 2 * 1024;
-
-/// Wrapper around reed solomon which occasionally resets the underlying
-/// reed solomon instead to work around the memory leak in reed solomon
-/// implementation <https://github.com/darrenldl/reed-solomon-erasure/issues/74>
+/// Reed solomon coding of chunk parts, with the fastest backend supported by the CPU.
 pub struct ReedSolomonWrapper {
-    rs: ReedSolomon,
-    ttl: u64,
+    backend: Box<dyn ErasureCodingBackend>,
 }
 
 impl ReedSolomonWrapper {
     pub fn new(data_shards: usize, parity_shards: usize) -> Self {
-        ReedSolomonWrapper {
-            rs: ReedSolomon::new(data_shards, parity_shards).unwrap(),
-            ttl: RS_TTL,
-        }
+        ReedSolomonWrapper { backend: default_backend(data_shards, parity_shards).unwrap() }
+    }
+
+    pub fn with_backend(backend: Box<dyn ErasureCodingBackend>) -> Self {
+        ReedSolomonWrapper { backend }
     }
 
-    pub fn reconstruct<T: ReconstructShard<Field>>(
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    pub fn reconstruct(
         &mut self,
-        slices: &mut [T],
+        slices: &mut [Option<Box<[u8]>>],
     ) -> Result<(), reed_solomon_erasure::Error> {
-        let res = self.rs.reconstruct(slices);
-        self.ttl -= 1;
-        if self.ttl == 0 {
-            *self =
-                ReedSolomonWrapper::new(self.rs.data_shard_count(), self.rs.parity_shard_count());
-        }
-        res
+        self.backend.reconstruct(slices)
     }
 
     pub fn data_shard_count(&self) -> usize {
-        self.rs.data_shard_count()
+        self.backend.data_shard_count()
     }
 
     pub fn total_shard_count(&self) -> usize {
-        self.rs.total_shard_count()
+        self.backend.total_shard_count()
     }
 }
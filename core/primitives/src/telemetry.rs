@@ -2,6 +2,8 @@
 //! node count and their status across the network.
 use serde::{Deserialize, Serialize};
 
+use near_crypto::PublicKey;
+
 use crate::types::BlockHeight;
 
 use crate::types::AccountId;
@@ -38,3 +40,18 @@ pub struct TelemetryInfo {
     pub system: TelemetrySystemInfo,
     pub chain: TelemetryChainInfo,
 }
+
+/// Heartbeat of a validator, for uptime monitoring. Signed with its validator key, so that the
+/// monitoring service can check it comes from the validator.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LivenessBeacon {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+    pub node_id: String,
+    /// Nanoseconds since the unix epoch.
+    pub timestamp: u64,
+    pub latest_block_hash: String,
+    pub latest_block_height: BlockHeight,
+    /// Target height of the last approval sent.
+    pub last_endorsement_height: BlockHeight,
+}
@@ -16,6 +16,10 @@ struct MockClockPerThread {
     instant_call_count: u64,
     instant: Instant,
     is_mock: bool,
+    /// If set, `utc` and `instant` return the time elapsed since the clock was made fake, added
+    /// to `fake_utc` and `instant` respectively, instead of mock samples or the real time.
+    fake_elapsed: Option<Duration>,
+    fake_utc: DateTime<Utc>,
 }
 
 pub struct Clock {}
@@ -28,6 +32,7 @@ impl MockClockPerThread {
         self.instant_call_count = 0;
         self.instant = Instant::now();
         self.is_mock = false;
+        self.fake_elapsed = None;
     }
 
     fn with<F, T>(f: F) -> T
@@ -63,6 +68,8 @@ impl Default for MockClockPerThread {
             instant_call_count: 0,
             instant: Instant::now(),
             is_mock: false,
+            fake_elapsed: None,
+            fake_utc: chrono::Utc::now(),
         }
     }
 }
@@ -103,6 +110,23 @@ impl Clock {
         });
     }
 
+    /// Makes the clock of the current thread fake: it starts at `utc` and only moves forward with
+    /// `Clock::advance`, so that everything timed by it is reproducible.
+    pub fn set_fake(utc: DateTime<chrono::Utc>) {
+        MockClockPerThread::with(|clock| {
+            clock.fake_elapsed = Some(Duration::default());
+            clock.fake_utc = utc;
+        });
+    }
+
+    /// Moves the fake clock of the current thread forward.
+    pub fn advance(duration: Duration) {
+        MockClockPerThread::with(|clock| match &mut clock.fake_elapsed {
+            Some(elapsed) => *elapsed += duration,
+            None => panic!("Use Clock::set_fake before advancing the clock"),
+        });
+    }
+
     pub fn add_instant(mock_instant: Duration) {
         MockClockPerThread::with(|clock| {
             if clock.is_mock {
@@ -115,7 +139,9 @@ impl Clock {
 
     pub fn utc() -> DateTime<chrono::Utc> {
         MockClockPerThread::with(|clock| {
-            if clock.is_mock {
+            if let Some(elapsed) = clock.fake_elapsed {
+                clock.fake_utc + chrono::Duration::from_std(elapsed).unwrap()
+            } else if clock.is_mock {
                 let x = clock.pop_utc();
                 match x {
                     Some(t) => t,
@@ -131,7 +157,9 @@ impl Clock {
 
     pub fn instant() -> Instant {
         MockClockPerThread::with(|clock| {
-            if clock.is_mock {
+            if let Some(elapsed) = clock.fake_elapsed {
+                clock.instant + elapsed
+            } else if clock.is_mock {
                 let x = clock.pop_instant();
                 match x {
                     Some(t) => t,
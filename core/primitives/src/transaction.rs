@@ -20,6 +20,20 @@ use near_primitives_core::profile::ProfileData;
 
 pub type LogEntry = String;
 
+/// Structured event emitted by a contract with the `emit_event` host function. Kept apart from the
+/// logs, so that the events of a standard can be read without parsing log lines.
+#[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct ContractEvent {
+    /// Name of the standard the event belongs to, e.g. `nep171`.
+    pub standard: String,
+    /// Version of the standard.
+    pub version: String,
+    /// Borsh serialized event, as defined by the standard.
+    #[serde(with = "base64_format")]
+    pub data: Vec<u8>,
+}
+
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone)]
 pub struct Transaction {
@@ -376,6 +390,21 @@ pub enum ExecutionMetadata {
 
     // V2: With ProfileData
     V2(ProfileData),
+
+    // V3: With ProfileData and the events emitted by the contract
+    V3 { profile: ProfileData, events: Vec<ContractEvent> },
+}
+
+impl ExecutionMetadata {
+    /// Outcomes without events keep the V2 layout, so that they are unchanged by
+    /// `ProtocolFeature::EmitEvent`.
+    pub fn new(profile: ProfileData, events: Vec<ContractEvent>) -> Self {
+        if events.is_empty() {
+            ExecutionMetadata::V2(profile)
+        } else {
+            ExecutionMetadata::V3 { profile, events }
+        }
+    }
 }
 
 impl Default for ExecutionMetadata {
@@ -392,8 +421,19 @@ impl ExecutionOutcome {
         for log in self.logs.iter() {
             result.push(hash(log.as_bytes()));
         }
+        for event in self.events() {
+            result.push(hash(&event.try_to_vec().expect("Failed to serialize")));
+        }
         result
     }
+
+    /// Events emitted by the contract, only recorded if its execution succeeded.
+    pub fn events(&self) -> &[ContractEvent] {
+        match &self.metadata {
+            ExecutionMetadata::V1 | ExecutionMetadata::V2(_) => &[],
+            ExecutionMetadata::V3 { events, .. } => events,
+        }
+    }
 }
 
 impl fmt::Debug for ExecutionOutcome {
@@ -549,5 +589,19 @@ mod tests {
         };
         let hashes = outcome.to_hashes();
         assert_eq!(hashes.len(), 3);
+
+        let event = ContractEvent {
+            standard: "nep171".to_string(),
+            version: "1.0.0".to_string(),
+            data: vec![1, 2, 3],
+        };
+        let outcome = ExecutionOutcome {
+            metadata: ExecutionMetadata::new(Default::default(), vec![event.clone()]),
+            ..outcome
+        };
+        assert_eq!(outcome.events(), &[event]);
+        let with_event = outcome.to_hashes();
+        assert_eq!(with_event.len(), 4);
+        assert_eq!(&with_event[..3], &hashes[..]);
     }
 }
@@ -11,6 +11,7 @@ use crate::challenge::ChallengesResult;
 use crate::errors::EpochError;
 use crate::hash::CryptoHash;
 use crate::serialize::u128_dec_format;
+use crate::transaction::Action;
 use crate::trie_key::TrieKey;
 
 use crate::receipt::Receipt;
@@ -38,7 +39,7 @@ impl Default for Finality {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountWithPublicKey {
     pub account_id: AccountId,
     pub public_key: PublicKey,
@@ -211,6 +212,191 @@ impl StateChangesForSplitStates {
     }
 }
 
+/// Upper bounds (inclusive) of transaction size buckets, in bytes, used in
+/// `BlockTransactionStats::tx_size_histogram`. The last histogram entry counts bigger transactions.
+pub const TX_SIZE_BUCKETS: [u64; 6] = [256, 512, 1024, 4096, 16384, 65536];
+
+/// Number of actions of each type.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq,
+)]
+pub struct ActionCounts {
+    pub create_account: u64,
+    pub deploy_contract: u64,
+    pub function_call: u64,
+    pub transfer: u64,
+    pub stake: u64,
+    pub add_key: u64,
+    pub delete_key: u64,
+    pub delete_account: u64,
+}
+
+impl ActionCounts {
+    pub fn add(&mut self, action: &Action) {
+        match action {
+            Action::CreateAccount(_) => self.create_account += 1,
+            Action::DeployContract(_) => self.deploy_contract += 1,
+            Action::FunctionCall(_) => self.function_call += 1,
+            Action::Transfer(_) => self.transfer += 1,
+            Action::Stake(_) => self.stake += 1,
+            #[cfg(feature = "protocol_feature_chunk_only_producers")]
+            Action::StakeChunkOnly(_) => self.stake += 1,
+            Action::AddKey(_) => self.add_key += 1,
+            Action::DeleteKey(_) => self.delete_key += 1,
+            Action::DeleteAccount(_) => self.delete_account += 1,
+        }
+    }
+}
+
+/// Statistics of the transactions included in the chunks of a block, for the shards tracked by
+/// the node.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockTransactionStats {
+    /// Shards whose new chunks were accounted for.
+    pub shards: Vec<ShardId>,
+    pub num_transactions: u64,
+    pub total_tx_size: u64,
+    pub max_tx_size: u64,
+    /// Number of transactions per size bucket, see `TX_SIZE_BUCKETS`.
+    pub tx_size_histogram: Vec<u64>,
+    pub actions: ActionCounts,
+    /// Transactions converted to a receipt executed in the same chunk, i.e. `signer_id == receiver_id`.
+    pub local_receipts: u64,
+    /// Transactions converted to a receipt to another account on the same shard.
+    pub same_shard_receipts: u64,
+    /// Transactions converted to a receipt to an account on another shard.
+    pub cross_shard_receipts: u64,
+}
+
+impl Default for BlockTransactionStats {
+    fn default() -> Self {
+        Self {
+            shards: vec![],
+            num_transactions: 0,
+            total_tx_size: 0,
+            max_tx_size: 0,
+            tx_size_histogram: vec![0; TX_SIZE_BUCKETS.len() + 1],
+            actions: ActionCounts::default(),
+            local_receipts: 0,
+            same_shard_receipts: 0,
+            cross_shard_receipts: 0,
+        }
+    }
+}
+
+impl BlockTransactionStats {
+    /// Accounts for a transaction of the given serialized size and actions.
+    pub fn add_transaction(&mut self, tx_size: u64, actions: &[Action]) {
+        self.num_transactions += 1;
+        self.total_tx_size += tx_size;
+        self.max_tx_size = std::cmp::max(self.max_tx_size, tx_size);
+        let bucket = TX_SIZE_BUCKETS.iter().position(|bound| tx_size <= *bound);
+        self.tx_size_histogram[bucket.unwrap_or(TX_SIZE_BUCKETS.len())] += 1;
+        for action in actions {
+            self.actions.add(action);
+        }
+    }
+}
+
+/// Summary of how the validators of an epoch performed, from its validator information.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidatorPerformanceSummary {
+    pub epoch_height: EpochHeight,
+    pub num_validators: u64,
+    pub total_stake: Balance,
+    pub num_produced_blocks: NumBlocks,
+    pub num_expected_blocks: NumBlocks,
+    pub num_slashed: u64,
+}
+
+/// Statistics of the final blocks of an epoch, accumulated as they become final. The transactions
+/// are those of the shards tracked by the node, the gas used is the one of every shard.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpochChainStats {
+    /// First block accounted for: the first block of the epoch, unless the node started later.
+    pub first_block_height: BlockHeight,
+    pub last_block_height: BlockHeight,
+    pub num_blocks: u64,
+    /// Shards whose new chunks were accounted for.
+    pub shards: Vec<ShardId>,
+    pub num_transactions: u64,
+    pub unique_signers: u64,
+    /// Gas used per shard, as reported by the new chunks.
+    pub gas_used: std::collections::BTreeMap<ShardId, Gas>,
+    /// Accounts created by the `CreateAccount` actions of the transactions.
+    pub new_accounts: u64,
+    pub contract_deploys: u64,
+    /// Set once the last block of the epoch is final.
+    pub complete: bool,
+    pub validators: Option<ValidatorPerformanceSummary>,
+}
+
+/// Calls of a method made with a function call access key, recorded by the nodes saving the
+/// usage of access keys.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccessKeyMethodUsage {
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    pub count: u64,
+    pub last_used_block_height: BlockHeight,
+    /// Transaction which used the key last.
+    pub last_used_tx_hash: CryptoHash,
+}
+
+impl AccessKeyMethodUsage {
+    /// Records a call of `method_name` on `receiver_id` in the usage of a key.
+    pub fn record(
+        usages: &mut Vec<AccessKeyMethodUsage>,
+        receiver_id: &AccountId,
+        method_name: &str,
+        block_height: BlockHeight,
+        tx_hash: CryptoHash,
+    ) {
+        match usages
+            .iter_mut()
+            .find(|usage| &usage.receiver_id == receiver_id && usage.method_name == method_name)
+        {
+            Some(usage) if usage.last_used_tx_hash == tx_hash => {}
+            Some(usage) => {
+                usage.count += 1;
+                usage.last_used_block_height = block_height;
+                usage.last_used_tx_hash = tx_hash;
+            }
+            None => usages.push(AccessKeyMethodUsage {
+                receiver_id: receiver_id.clone(),
+                method_name: method_name.to_string(),
+                count: 1,
+                last_used_block_height: block_height,
+                last_used_tx_hash: tx_hash,
+            }),
+        }
+    }
+}
+
+/// Deployment of contract code on an account, recorded by the nodes saving the history of
+/// contracts.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContractDeployment {
+    pub block_height: BlockHeight,
+    /// Receipt which deployed the code.
+    pub receipt_id: CryptoHash,
+    /// Hash of the code, the default hash if the code was removed with the account.
+    pub code_hash: CryptoHash,
+    pub code_size: u64,
+    /// Functions exported by the code, `None` if it isn't a well-formed WASM module.
+    pub exported_functions: Option<Vec<String>>,
+}
+
+impl ContractDeployment {
+    /// Records a deployment in the history of an account, replacing the one of the same receipt.
+    pub fn record(history: &mut Vec<ContractDeployment>, deployment: ContractDeployment) {
+        match history.iter_mut().find(|recorded| recorded.receipt_id == deployment.receipt_id) {
+            Some(recorded) => *recorded = deployment,
+            None => history.push(deployment),
+        }
+    }
+}
+
 /// key that was updated -> list of updates with the corresponding indexing event.
 pub type RawStateChanges = std::collections::BTreeMap<Vec<u8>, RawStateChangesWithTrieKey>;
 
@@ -492,6 +678,7 @@ pub mod validator_stake {
         pub is_chunk_only: bool,
     }
 
+    #[derive(Clone)]
     pub struct ValidatorStakeIter<'a> {
         collection: ValidatorStakeIterSource<'a>,
         curr_index: usize,
@@ -543,6 +730,7 @@ pub mod validator_stake {
         }
     }
 
+    #[derive(Clone)]
     enum ValidatorStakeIterSource<'a> {
         V1(&'a [ValidatorStakeV1]),
         V2(&'a [ValidatorStake]),
@@ -12,7 +12,7 @@ use serde;
 use crate::hash::{hash, CryptoHash};
 use crate::receipt::Receipt;
 use crate::transaction::SignedTransaction;
-use crate::types::{CompiledContractCache, NumSeats, NumShards, ShardId};
+use crate::types::{AccountId, CompiledContractCache, NumSeats, NumShards, ShardId};
 use crate::version::{
     ProtocolVersion, CORRECT_RANDOM_VALUE_PROTOCOL_VERSION, CREATE_HASH_PROTOCOL_VERSION,
     CREATE_RECEIPT_ID_SWITCH_TO_CURRENT_BLOCK_VERSION,
@@ -483,6 +483,17 @@ where
     Serializable(object)
 }
 
+/// The account of the lockup of the owner deployed by the lockup factory, e.g. `lockup.near`: the
+/// first 20 bytes of the hash of the owner in hex, under the factory. `None` if it's too long to be
+/// an account ID.
+pub fn lockup_account_id(
+    owner_account_id: &AccountId,
+    lockup_factory_id: &AccountId,
+) -> Option<AccountId> {
+    let owner_hash = hash(owner_account_id.as_ref().as_bytes());
+    format!("{}.{}", hex::encode(&owner_hash.as_ref()[..20]), lockup_factory_id).parse().ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -572,4 +583,15 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_lockup_account_id() {
+        let lockup_factory_id: AccountId = "lockup.near".parse().unwrap();
+        assert_eq!(
+            lockup_account_id(&"alice.near".parse().unwrap(), &lockup_factory_id).unwrap().as_ref(),
+            "2dd5dda540767b3a1aa33544bcba38042f4df6de.lockup.near"
+        );
+        let long_factory_id: AccountId = "a".repeat(30).parse().unwrap();
+        assert_eq!(lockup_account_id(&"alice.near".parse().unwrap(), &long_factory_id), None);
+    }
 }
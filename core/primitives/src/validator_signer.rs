@@ -10,7 +10,7 @@ use crate::challenge::ChallengeBody;
 use crate::hash::{hash, CryptoHash};
 use crate::network::{AnnounceAccount, PeerId};
 use crate::sharding::ChunkHash;
-use crate::telemetry::TelemetryInfo;
+use crate::telemetry::{LivenessBeacon, TelemetryInfo};
 use crate::types::{AccountId, BlockHeight, EpochId};
 
 /// Validator signer that is used to sign blocks and approvals.
@@ -24,6 +24,9 @@ pub trait ValidatorSigner: Sync + Send {
     /// Serializes telemetry info to JSON and signs it, returning JSON with "signature" field.
     fn sign_telemetry(&self, info: &TelemetryInfo) -> serde_json::Value;
 
+    /// Serializes the heartbeat to JSON and signs it, returning JSON with "signature" field.
+    fn sign_liveness_beacon(&self, beacon: &LivenessBeacon) -> serde_json::Value;
+
     /// Signs given parts of the header.
     fn sign_block_header_parts(
         &self,
@@ -79,6 +82,10 @@ impl ValidatorSigner for EmptyValidatorSigner {
         serde_json::Value::default()
     }
 
+    fn sign_liveness_beacon(&self, _beacon: &LivenessBeacon) -> serde_json::Value {
+        serde_json::Value::default()
+    }
+
     fn sign_block_header_parts(
         &self,
         prev_hash: CryptoHash,
@@ -167,6 +174,13 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         value
     }
 
+    fn sign_liveness_beacon(&self, beacon: &LivenessBeacon) -> serde_json::Value {
+        let mut value = serde_json::to_value(beacon).expect("Beacon must serialize to JSON");
+        let content = serde_json::to_string(&value).expect("Beacon must serialize to JSON");
+        value["signature"] = format!("{}", self.signer.sign(content.as_bytes())).into();
+        value
+    }
+
     fn sign_block_header_parts(
         &self,
         prev_hash: CryptoHash,
@@ -16,7 +16,7 @@ pub struct Version {
 pub type DbVersion = u32;
 
 /// Current version of the database.
-pub const DB_VERSION: DbVersion = 30;
+pub const DB_VERSION: DbVersion = 35;
 
 /// Protocol version type.
 pub use near_primitives_core::types::ProtocolVersion;
@@ -141,6 +141,9 @@ pub enum ProtocolFeature {
     #[cfg(feature = "protocol_feature_access_key_nonce_for_implicit_accounts")]
     /// Add `AccessKey` nonce range for implicit accounts, as in `AccessKeyNonceRange` feature.
     AccessKeyNonceForImplicitAccounts,
+    /// Add `emit_event` host function, recording structured events apart from the logs.
+    #[cfg(feature = "protocol_feature_emit_event")]
+    EmitEvent,
 }
 
 /// Current latest stable version of the protocol.
@@ -151,7 +154,7 @@ pub const PROTOCOL_VERSION: ProtocolVersion = 49;
 
 /// Current latest nightly version of the protocol.
 #[cfg(feature = "nightly_protocol")]
-pub const PROTOCOL_VERSION: ProtocolVersion = 125;
+pub const PROTOCOL_VERSION: ProtocolVersion = 126;
 
 impl ProtocolFeature {
     pub const fn protocol_version(self) -> ProtocolVersion {
@@ -188,6 +191,98 @@ impl ProtocolFeature {
             ProtocolFeature::RoutingExchangeAlgorithm => 117,
             #[cfg(feature = "protocol_feature_access_key_nonce_for_implicit_accounts")]
             ProtocolFeature::AccessKeyNonceForImplicitAccounts => 125,
+            #[cfg(feature = "protocol_feature_emit_event")]
+            ProtocolFeature::EmitEvent => 126,
+        }
+    }
+
+    /// Every protocol feature compiled into the binary, stable ones first.
+    pub const ALL: &'static [ProtocolFeature] = &[
+        ProtocolFeature::ForwardChunkParts,
+        ProtocolFeature::RectifyInflation,
+        ProtocolFeature::AccessKeyNonceRange,
+        ProtocolFeature::FixApplyChunks,
+        ProtocolFeature::LowerStorageCost,
+        ProtocolFeature::DeleteActionRestriction,
+        ProtocolFeature::AccountVersions,
+        ProtocolFeature::TransactionSizeLimit,
+        ProtocolFeature::FixStorageUsage,
+        ProtocolFeature::CapMaxGasPrice,
+        ProtocolFeature::CountRefundReceiptsInGasLimit,
+        ProtocolFeature::MathExtension,
+        ProtocolFeature::RestoreReceiptsAfterFix,
+        ProtocolFeature::Wasmer2,
+        ProtocolFeature::SimpleNightshade,
+        ProtocolFeature::LowerDataReceiptAndEcrecoverBaseCost,
+        ProtocolFeature::LowerRegularOpCost,
+        ProtocolFeature::LowerRegularOpCost2,
+        ProtocolFeature::LimitContractFunctionsNumber,
+        ProtocolFeature::BlockHeaderV3,
+        ProtocolFeature::AliasValidatorSelectionAlgorithm,
+        #[cfg(feature = "protocol_feature_alt_bn128")]
+        ProtocolFeature::AltBn128,
+        #[cfg(feature = "protocol_feature_chunk_only_producers")]
+        ProtocolFeature::ChunkOnlyProducers,
+        #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
+        ProtocolFeature::RoutingExchangeAlgorithm,
+        #[cfg(feature = "protocol_feature_access_key_nonce_for_implicit_accounts")]
+        ProtocolFeature::AccessKeyNonceForImplicitAccounts,
+        #[cfg(feature = "protocol_feature_emit_event")]
+        ProtocolFeature::EmitEvent,
+    ];
+
+    /// One line summary of the feature, shown by the node features RPC.
+    pub const fn description(self) -> &'static str {
+        match self {
+            ProtocolFeature::ForwardChunkParts => "Forward the chunk parts to the other validators",
+            ProtocolFeature::RectifyInflation => "Fix the computation of the inflation",
+            ProtocolFeature::AccessKeyNonceRange => {
+                "Start the nonce of new access keys at (block_height - 1) * 1e6"
+            }
+            ProtocolFeature::FixApplyChunks => "Fix the application of the missing chunks",
+            ProtocolFeature::LowerStorageCost => "Lower the storage cost to 1 NEAR per 100kb",
+            ProtocolFeature::DeleteActionRestriction => {
+                "Restrict the deletion of accounts with a large state"
+            }
+            ProtocolFeature::AccountVersions => "Add versions to the Account data structure",
+            ProtocolFeature::TransactionSizeLimit => "Limit the size of transactions",
+            ProtocolFeature::FixStorageUsage => "Fix the storage usage of accounts caused by #3824",
+            ProtocolFeature::CapMaxGasPrice => "Cap the gas price to 2,000,000,000 yoctoNEAR",
+            ProtocolFeature::CountRefundReceiptsInGasLimit => {
+                "Count the refund receipts in the gas limit of chunks"
+            }
+            ProtocolFeature::MathExtension => "Add the ripemd160 and ecrecover host functions",
+            ProtocolFeature::RestoreReceiptsAfterFix => {
+                "Restore the receipts stuck because of #4228"
+            }
+            ProtocolFeature::Wasmer2 => "Run contracts with wasmer 2",
+            ProtocolFeature::SimpleNightshade => "Split the state into four shards",
+            ProtocolFeature::LowerDataReceiptAndEcrecoverBaseCost => {
+                "Lower the base costs of data receipts and ecrecover"
+            }
+            ProtocolFeature::LowerRegularOpCost => "Lower the cost of wasm instructions",
+            ProtocolFeature::LowerRegularOpCost2 => {
+                "Lower the cost of wasm instructions again, with the intrinsics gas counter"
+            }
+            ProtocolFeature::LimitContractFunctionsNumber => {
+                "Limit the number of wasm functions of contracts"
+            }
+            ProtocolFeature::BlockHeaderV3 => "Version 3 of the block header",
+            ProtocolFeature::AliasValidatorSelectionAlgorithm => {
+                "Validator selection algorithm of NEP-167"
+            }
+            #[cfg(feature = "protocol_feature_alt_bn128")]
+            ProtocolFeature::AltBn128 => "Add the alt_bn128 host functions",
+            #[cfg(feature = "protocol_feature_chunk_only_producers")]
+            ProtocolFeature::ChunkOnlyProducers => "Validators producing only chunks",
+            #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
+            ProtocolFeature::RoutingExchangeAlgorithm => "New exchange of the routing tables",
+            #[cfg(feature = "protocol_feature_access_key_nonce_for_implicit_accounts")]
+            ProtocolFeature::AccessKeyNonceForImplicitAccounts => {
+                "Start the nonce of the access keys of implicit accounts like AccessKeyNonceRange"
+            }
+            #[cfg(feature = "protocol_feature_emit_event")]
+            ProtocolFeature::EmitEvent => "Add the emit_event host function",
         }
     }
 }
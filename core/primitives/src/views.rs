@@ -3,6 +3,7 @@
 //! These types should only change when we cannot avoid this. Thus, when the counterpart internal
 //! type gets changed, the view should preserve the old shape and only re-map the necessary bits
 //! from the source structure in the relevant `From<SourceStruct>` impl.
+use std::collections::BTreeMap;
 use std::fmt;
 use std::sync::Arc;
 
@@ -15,7 +16,7 @@ use serde::{Deserialize, Serialize};
 use near_crypto::{PublicKey, Signature};
 
 use crate::account::{AccessKey, AccessKeyPermission, Account, FunctionCallPermission};
-use crate::block::{Block, BlockHeader};
+use crate::block::{Block, BlockHeader, Tip};
 use crate::block_header::{
     BlockHeaderInnerLite, BlockHeaderInnerRest, BlockHeaderInnerRestV2, BlockHeaderInnerRestV3,
     BlockHeaderV1, BlockHeaderV2, BlockHeaderV3,
@@ -25,27 +26,30 @@ use crate::contract::ContractCode;
 use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
 use crate::logging;
-use crate::merkle::MerklePath;
+use crate::merkle::{verify_path, MerklePath};
+use crate::network::PeerId;
 use crate::profile::Cost;
-use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptEnum};
+use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptEnum, RefundKind};
 use crate::serialize::{
     base64_format, from_base64, option_base64_format, option_u128_dec_format, to_base64,
     u128_dec_format, u64_dec_format,
 };
 use crate::sharding::{
-    ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner, ShardChunkHeaderInnerV2,
-    ShardChunkHeaderV3,
+    ChunkHash, ReceiptList, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner,
+    ShardChunkHeaderInnerV2, ShardChunkHeaderV3,
 };
 use crate::transaction::{
-    Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
-    DeployContractAction, ExecutionMetadata, ExecutionOutcome, ExecutionOutcomeWithIdAndProof,
-    ExecutionStatus, FunctionCallAction, SignedTransaction, StakeAction, TransferAction,
+    Action, AddKeyAction, ContractEvent, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
+    DeployContractAction, ExecutionMetadata, ExecutionOutcome, ExecutionOutcomeWithId,
+    ExecutionOutcomeWithIdAndProof, ExecutionStatus, FunctionCallAction, SignedTransaction,
+    StakeAction, TransferAction,
 };
 use crate::types::{
-    AccountId, AccountWithPublicKey, Balance, BlockHeight, CompiledContractCache, EpochHeight,
-    EpochId, FunctionArgs, Gas, Nonce, NumBlocks, ShardId, StateChangeCause, StateChangeKind,
-    StateChangeValue, StateChangeWithCause, StateChangesRequest, StateRoot, StorageUsage, StoreKey,
-    StoreValue, ValidatorKickoutReason,
+    AccountId, AccountWithPublicKey, ActionCounts, Balance, BlockHeight, BlockTransactionStats,
+    CompiledContractCache, ContractDeployment, EpochChainStats, EpochHeight, EpochId, FunctionArgs,
+    Gas, Nonce, NumBlocks, RawStateChangesWithTrieKey, ShardId, StateChangeCause, StateChangeKind,
+    StateChangeValue, StateChangeWithCause, StateChanges, StateChangesExt, StateChangesRequest,
+    StateRoot, StorageUsage, StoreKey, StoreValue, ValidatorKickoutReason, TX_SIZE_BUCKETS,
 };
 use crate::version::{ProtocolVersion, Version};
 use validator_stake_view::ValidatorStakeView;
@@ -210,11 +214,23 @@ pub struct StateItem {
     pub proof: TrieProofPath,
 }
 
+/// Contract state of an account, the items with their base64 keys, without the account, and values.
+///
+/// The `proof` of a page of the state, see `QueryRequest::ViewStatePage`, is made of the base64
+/// trie nodes and values, as stored in the state, read while iterating the trie for the page. The
+/// hash of every node and value is its key, starting from the state root, so iterating the same
+/// way over the proof alone only gives back the items of the page if they're exactly those of the
+/// state at the root, which `near_store::verify_state_page` checks. It's empty for the full state.
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct ViewStateResult {
     pub values: Vec<StateItem>,
     pub proof: TrieProofPath,
+    /// The `start_after` of the next page when the page is full, the key of its last item, which
+    /// reads the same next page on every call at the block. `None` once a page isn't full, the
+    /// last one, and for the full state.
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "option_base64_format")]
+    pub continuation_token: Option<Vec<u8>>,
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
@@ -291,6 +307,17 @@ pub enum QueryRequest {
         #[serde(rename = "args_base64", with = "base64_format")]
         args: FunctionArgs,
     },
+    /// At most `limit` items of the contract state with keys starting with `prefix`, following
+    /// `start_after` if given, with the proof of the page. Unlike for the full state, the size of
+    /// the state of the account isn't limited.
+    ViewStatePage {
+        account_id: AccountId,
+        #[serde(rename = "prefix_base64", with = "base64_format")]
+        prefix: StoreKey,
+        #[serde(default, rename = "start_after_base64", with = "option_base64_format")]
+        start_after: Option<Vec<u8>>,
+        limit: u64,
+    },
 }
 
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
@@ -343,6 +370,30 @@ pub struct StatusResponse {
     pub sync_info: StatusSyncInfo,
     /// Validator id of the node
     pub validator_account_id: Option<AccountId>,
+    /// Cargo features the binary was built with, see `EXPERIMENTAL_node_features` for details.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub build_features: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BuildFeatureView {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolFeatureView {
+    pub name: String,
+    pub description: String,
+    /// Protocol version from which the feature is enabled.
+    pub protocol_version: ProtocolVersion,
+}
+
+/// Capabilities of the binary, see [`crate::features`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NodeFeaturesView {
+    pub build_features: Vec<BuildFeatureView>,
+    pub protocol_features: Vec<ProtocolFeatureView>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1011,7 +1062,8 @@ impl From<ExecutionMetadata> for ExecutionMetadataView {
     fn from(metadata: ExecutionMetadata) -> Self {
         let gas_profile = match metadata {
             ExecutionMetadata::V1 => None,
-            ExecutionMetadata::V2(profile_data) => Some(
+            ExecutionMetadata::V2(profile_data)
+            | ExecutionMetadata::V3 { profile: profile_data, .. } => Some(
                 Cost::ALL
                     .iter()
                     .filter(|&cost| profile_data[*cost] > 0)
@@ -1059,11 +1111,15 @@ pub struct ExecutionOutcomeView {
     /// Execution metadata, versioned
     #[serde(default)]
     pub metadata: ExecutionMetadataView,
+    /// Events emitted by the contract, if its execution succeeded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<ContractEvent>,
 }
 
 impl From<ExecutionOutcome> for ExecutionOutcomeView {
     fn from(outcome: ExecutionOutcome) -> Self {
         Self {
+            events: outcome.events().to_vec(),
             logs: outcome.logs,
             receipt_ids: outcome.receipt_ids,
             gas_burnt: outcome.gas_burnt,
@@ -1365,6 +1421,60 @@ impl TryFrom<ReceiptView> for Receipt {
     }
 }
 
+/// Proof that a receipt was sent by a chunk of a block to another shard.
+///
+/// The chunk commits to the receipts it sends to every shard in its `outgoing_receipts_root`, and
+/// the block header to the roots of all its chunks in its `chunk_receipts_root`. The leaf of the
+/// first proof is the hash of the borsh serialization of `(to_shard_id, receipts)`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptInclusionProofView {
+    pub receipt_id: CryptoHash,
+    pub from_shard_id: ShardId,
+    pub to_shard_id: ShardId,
+    /// All the receipts sent by the chunk to `to_shard_id`, in order.
+    pub receipts: Vec<ReceiptView>,
+    pub outgoing_receipts_root: CryptoHash,
+    /// Path from the hash of `receipts` to `outgoing_receipts_root`.
+    pub outgoing_receipts_proof: MerklePath,
+    /// Path from `outgoing_receipts_root` to the `chunk_receipts_root` of `block_header`.
+    pub chunk_receipts_proof: MerklePath,
+    pub block_header: BlockHeaderView,
+}
+
+impl ReceiptInclusionProofView {
+    /// Checks that the receipt is one of `receipts`, that the proofs lead from them to the
+    /// `chunk_receipts_root` of the block header and that the hash of the header is the one
+    /// given. The header itself is to be checked against a trusted one, e.g. with a block proof.
+    pub fn verify(&self) -> bool {
+        if !self.receipts.iter().any(|receipt| receipt.receipt_id == self.receipt_id) {
+            return false;
+        }
+        let receipts = match self
+            .receipts
+            .iter()
+            .cloned()
+            .map(Receipt::try_from)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(receipts) => receipts,
+            Err(_) => return false,
+        };
+        let receipts_hash = hash(&ReceiptList(self.to_shard_id, &receipts).try_to_vec().unwrap());
+        let block_header = BlockHeader::from(self.block_header.clone());
+        block_header.hash() == &self.block_header.hash
+            && verify_path(
+                self.outgoing_receipts_root,
+                &self.outgoing_receipts_proof,
+                &receipts_hash,
+            )
+            && verify_path(
+                *block_header.chunk_receipts_root(),
+                &self.chunk_receipts_proof,
+                &self.outgoing_receipts_root,
+            )
+    }
+}
+
 /// Information about this epoch validators and next epoch validators
 #[cfg_attr(feature = "deepsize_feature", derive(DeepSizeOf))]
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -1451,11 +1561,468 @@ pub struct GasPriceView {
     pub gas_price: Balance,
 }
 
+/// Gas throughput observed while applying recent chunks on this node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GasThroughputView {
+    /// Number of chunks the estimate is computed from.
+    pub num_samples: u64,
+    /// Average gas applied per second.
+    pub avg_gas_per_second: Option<Gas>,
+    /// Gas per second the slowest 10% of the chunks were applied at.
+    pub safe_gas_per_second: Option<Gas>,
+}
+
+/// Transactions in the transaction pools of this node, waiting to be included in a chunk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PoolStatusView {
+    pub num_transactions: u64,
+    pub shards: Vec<ShardPoolStatusView>,
+    /// Number of transactions by time spent in the pool, over all shards.
+    pub age_distribution: Vec<PoolAgeBucketView>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShardPoolStatusView {
+    pub shard_id: ShardId,
+    pub num_transactions: u64,
+    /// Number of distinct pairs of signer account ID and public key.
+    pub num_signers: u64,
+    /// Sum of the sizes of the transactions, in bytes.
+    pub total_size: u64,
+    /// Time the oldest transaction has spent in the pool.
+    pub max_age_secs: Option<u64>,
+    pub age_distribution: Vec<PoolAgeBucketView>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PoolAgeBucketView {
+    /// Transactions which spent less than this time in the pool and at least the time of the
+    /// previous bucket. The last bucket is unbounded.
+    pub max_age_secs: Option<u64>,
+    pub num_transactions: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PoolTransactionView {
+    pub hash: CryptoHash,
+    pub shard_id: ShardId,
+    pub signer_id: AccountId,
+    pub public_key: PublicKey,
+    pub nonce: Nonce,
+    pub receiver_id: AccountId,
+    pub num_actions: u64,
+    pub age_secs: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolUpgradeVoteState {
+    /// The binary supports no protocol version above the one of the current epoch.
+    UpToDate,
+    /// Produced blocks vote for the protocol version of the binary.
+    Voting,
+    /// Produced blocks vote for the protocol version of the current epoch, until the operator
+    /// acknowledges the upgrade or `vote_after` is reached.
+    AwaitingAcknowledgement,
+}
+
+/// Which protocol version the blocks produced by this node vote for, and why.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolUpgradeStatusView {
+    pub binary_protocol_version: ProtocolVersion,
+    pub epoch_protocol_version: ProtocolVersion,
+    pub voted_protocol_version: ProtocolVersion,
+    pub state: ProtocolUpgradeVoteState,
+    pub acknowledged_protocol_version: Option<ProtocolVersion>,
+    pub vote_after: Option<DateTime<chrono::Utc>>,
+}
+
+/// Clock skew of a block producer observed from the timestamps of its recent blocks.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProducerClockSkewView {
+    pub account_id: AccountId,
+    pub num_blocks: u64,
+    /// Average of the block timestamps minus the time this node accepted the blocks. Includes
+    /// the propagation and processing delays, so it is negative even with a perfect clock.
+    pub average_skew_ms: i64,
+    /// Average skew minus the median of the average skews of all producers, which cancels out
+    /// the delays common to all of them. Positive for a clock ahead, negative for one behind.
+    pub relative_skew_ms: i64,
+    pub min_skew_ms: i64,
+    pub max_skew_ms: i64,
+}
+
+/// Block producers ranked from the largest to the smallest relative clock skew.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ClockSkewReportView {
+    pub median_skew_ms: i64,
+    pub producers: Vec<ProducerClockSkewView>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockStatusView {
+    pub height: BlockHeight,
+    pub hash: CryptoHash,
+}
+
+impl From<&Tip> for BlockStatusView {
+    fn from(tip: &Tip) -> Self {
+        Self { height: tip.height, hash: tip.last_block_hash }
+    }
+}
+
+/// Stage of the sync of the node, see `SyncStatus` of the client.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SyncStatusView {
+    AwaitingPeers,
+    NoSync,
+    EpochSync {
+        epoch_ord: u64,
+    },
+    HeaderSync {
+        current_height: BlockHeight,
+        highest_height: BlockHeight,
+    },
+    /// Sync hash and state sync of the shards.
+    StateSync(CryptoHash, BTreeMap<ShardId, ShardSyncDownloadView>),
+    StateSyncDone,
+    BodySync {
+        current_height: BlockHeight,
+        highest_height: BlockHeight,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShardSyncDownloadView {
+    /// Stage of the state sync of the shard, e.g. `StateDownloadParts`.
+    pub status: String,
+    /// Downloads of the header or of the parts of the shard, depending on the stage.
+    pub num_downloads: u64,
+    pub num_downloads_done: u64,
+    pub num_downloads_failed: u64,
+}
+
+/// JSON counterpart of the sync status debug page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugSyncStatusView {
+    pub sync_status: SyncStatusView,
+    pub head: BlockStatusView,
+    pub header_head: BlockStatusView,
+    /// Highest height of the peers, the node syncs up to it.
+    pub highest_peer_height: Option<BlockHeight>,
+}
+
+/// JSON counterpart of the chain processing debug page: the blocks the node received but can't
+/// apply yet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugChainProcessingStatusView {
+    pub head: BlockStatusView,
+    pub final_head: BlockStatusView,
+    /// Blocks whose previous block is unknown.
+    pub num_orphans: u64,
+    /// Orphans dropped since the start of the node, the pool being full.
+    pub num_orphans_evicted: u64,
+    /// Blocks waiting for the chunks the node tracks.
+    pub num_blocks_missing_chunks: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugPeerInfoView {
+    pub peer_id: PeerId,
+    pub addr: Option<String>,
+    pub account_id: Option<AccountId>,
+    pub height: BlockHeight,
+    pub tracked_shards: Vec<ShardId>,
+    pub archival: bool,
+    /// Among the peers at the highest height, which the node syncs from.
+    pub is_highest_height: bool,
+}
+
+/// JSON counterpart of the network info debug page, with the chain info of every peer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugNetworkInfoView {
+    pub num_active_peers: u64,
+    pub peer_max_count: u32,
+    pub sent_bytes_per_sec: u64,
+    pub received_bytes_per_sec: u64,
+    pub peers: Vec<DebugPeerInfoView>,
+}
+
+/// Producers of a height, assuming it's in the epoch of the head.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProducerScheduleView {
+    pub height: BlockHeight,
+    pub block_producer: AccountId,
+    /// Chunk producer of every shard, by shard id.
+    pub chunk_producers: Vec<AccountId>,
+}
+
+/// JSON counterpart of the validator info debug page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugValidatorStatusView {
+    pub validator_account_id: Option<AccountId>,
+    pub epoch_id: EpochId,
+    pub epoch_height: EpochHeight,
+    pub epoch_start_height: BlockHeight,
+    /// The validator of the node among the validators of the epoch, with the blocks it produced
+    /// and was expected to, if it is one of them.
+    pub validator_info: Option<CurrentEpochValidatorInfo>,
+    /// Producers of the heights following the head.
+    pub next_producers: Vec<ProducerScheduleView>,
+}
+
+/// How the Reed-Solomon parts of a recent chunk were delivered to this node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkPartsAvailabilityView {
+    pub chunk_hash: CryptoHash,
+    /// Not known if only forwarded parts were received so far.
+    pub height_created: Option<BlockHeight>,
+    pub shard_id: Option<ShardId>,
+    pub num_total_parts: u64,
+    pub num_parts_received: u64,
+    /// Number of distinct peers which delivered at least one part first.
+    pub num_peers: u64,
+    /// Time from the first part received to the chunk being reconstructed, if it was. Only
+    /// chunks of the shards tracked by the node are reconstructed.
+    pub time_to_reconstruction_ms: Option<u64>,
+    /// Validators owning parts of the chunk none of which was received.
+    pub missing_part_owners: Vec<AccountId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockTransactionStatsView {
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+    /// Shards whose new chunks were accounted for.
+    pub shards: Vec<ShardId>,
+    pub num_transactions: u64,
+    pub total_tx_size: u64,
+    pub max_tx_size: u64,
+    /// Upper bounds of the transaction size buckets, the last histogram entry counts bigger
+    /// transactions.
+    pub tx_size_buckets: Vec<u64>,
+    pub tx_size_histogram: Vec<u64>,
+    pub actions: ActionCounts,
+    pub local_receipts: u64,
+    pub same_shard_receipts: u64,
+    pub cross_shard_receipts: u64,
+}
+
+impl BlockTransactionStatsView {
+    pub fn new(
+        block_height: BlockHeight,
+        block_hash: CryptoHash,
+        stats: BlockTransactionStats,
+    ) -> Self {
+        Self {
+            block_height,
+            block_hash,
+            shards: stats.shards,
+            num_transactions: stats.num_transactions,
+            total_tx_size: stats.total_tx_size,
+            max_tx_size: stats.max_tx_size,
+            tx_size_buckets: TX_SIZE_BUCKETS.to_vec(),
+            tx_size_histogram: stats.tx_size_histogram,
+            actions: stats.actions,
+            local_receipts: stats.local_receipts,
+            same_shard_receipts: stats.same_shard_receipts,
+            cross_shard_receipts: stats.cross_shard_receipts,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorPerformanceSummaryView {
+    pub num_validators: u64,
+    #[serde(with = "u128_dec_format")]
+    pub total_stake: Balance,
+    pub num_produced_blocks: NumBlocks,
+    pub num_expected_blocks: NumBlocks,
+    pub num_slashed: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EpochChainStatsView {
+    pub epoch_id: CryptoHash,
+    /// Known once the epoch is complete.
+    pub epoch_height: Option<EpochHeight>,
+    pub first_block_height: BlockHeight,
+    pub last_block_height: BlockHeight,
+    pub num_blocks: u64,
+    /// Shards whose transactions were accounted for.
+    pub shards: Vec<ShardId>,
+    pub num_transactions: u64,
+    pub unique_signers: u64,
+    pub gas_used: BTreeMap<ShardId, Gas>,
+    pub new_accounts: u64,
+    pub contract_deploys: u64,
+    /// Whether the last block of the epoch is final.
+    pub complete: bool,
+    /// Known once the epoch is complete.
+    pub validators: Option<ValidatorPerformanceSummaryView>,
+}
+
+impl EpochChainStatsView {
+    pub fn new(epoch_id: EpochId, stats: EpochChainStats) -> Self {
+        Self {
+            epoch_id: epoch_id.0,
+            epoch_height: stats.validators.as_ref().map(|validators| validators.epoch_height),
+            first_block_height: stats.first_block_height,
+            last_block_height: stats.last_block_height,
+            num_blocks: stats.num_blocks,
+            shards: stats.shards,
+            num_transactions: stats.num_transactions,
+            unique_signers: stats.unique_signers,
+            gas_used: stats.gas_used,
+            new_accounts: stats.new_accounts,
+            contract_deploys: stats.contract_deploys,
+            complete: stats.complete,
+            validators: stats.validators.map(|validators| ValidatorPerformanceSummaryView {
+                num_validators: validators.num_validators,
+                total_stake: validators.total_stake,
+                num_produced_blocks: validators.num_produced_blocks,
+                num_expected_blocks: validators.num_expected_blocks,
+                num_slashed: validators.num_slashed,
+            }),
+        }
+    }
+}
+
+/// Calls of a method made with a function call access key of an account.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccessKeyUsageView {
+    pub public_key: PublicKey,
+    pub receiver_id: AccountId,
+    pub method_name: String,
+    pub count: u64,
+    pub last_used_block_height: BlockHeight,
+    pub last_used_tx_hash: CryptoHash,
+}
+
+/// Deployment of contract code on an account.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContractDeploymentView {
+    pub block_height: BlockHeight,
+    pub receipt_id: CryptoHash,
+    /// Hash of the code, the default hash if the code was removed with the account.
+    pub code_hash: CryptoHash,
+    pub code_size: u64,
+    pub exported_functions: Option<Vec<String>>,
+    /// Changes from the previous deployment, if requested.
+    pub diff: Option<ContractDiffView>,
+}
+
+/// Changes of a deployment from the previous one of the account, or from no code for the first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContractDiffView {
+    pub size_delta: i64,
+    pub added_functions: Vec<String>,
+    pub removed_functions: Vec<String>,
+}
+
+impl ContractDeploymentView {
+    /// Views the history of an account, with the diff of every deployment if `with_diff`.
+    pub fn from_history(history: Vec<ContractDeployment>, with_diff: bool) -> Vec<Self> {
+        let mut previous: Option<ContractDeployment> = None;
+        let mut views = Vec::with_capacity(history.len());
+        for deployment in history {
+            let diff = if with_diff {
+                Some(ContractDiffView::new(previous.as_ref(), &deployment))
+            } else {
+                None
+            };
+            views.push(Self {
+                block_height: deployment.block_height,
+                receipt_id: deployment.receipt_id,
+                code_hash: deployment.code_hash,
+                code_size: deployment.code_size,
+                exported_functions: deployment.exported_functions.clone(),
+                diff,
+            });
+            previous = Some(deployment);
+        }
+        views
+    }
+}
+
+impl ContractDiffView {
+    fn new(previous: Option<&ContractDeployment>, deployment: &ContractDeployment) -> Self {
+        let previous_functions =
+            previous.and_then(|previous| previous.exported_functions.clone()).unwrap_or_default();
+        let new_functions = deployment.exported_functions.clone().unwrap_or_default();
+        Self {
+            size_delta: deployment.code_size as i64
+                - previous.map_or(0, |previous| previous.code_size as i64),
+            added_functions: new_functions
+                .iter()
+                .filter(|function| !previous_functions.contains(function))
+                .cloned()
+                .collect(),
+            removed_functions: previous_functions
+                .iter()
+                .filter(|function| !new_functions.contains(function))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Receipt which execution failed in a block, along with the receipt itself if it is
+/// still known to the node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FailedReceiptView {
+    pub receipt_id: CryptoHash,
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    pub shard_id: ShardId,
+    pub executor_id: AccountId,
+    pub error: TxExecutionError,
+    pub receipt: Option<ReceiptView>,
+}
+
+/// Refund receipts sent by the chunk of a shard in a block.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefundStatsView {
+    pub shard_id: ShardId,
+    pub gas_refund_receipts: u64,
+    #[serde(with = "u128_dec_format")]
+    pub gas_refund_amount: Balance,
+    pub deposit_refund_receipts: u64,
+    #[serde(with = "u128_dec_format")]
+    pub deposit_refund_amount: Balance,
+}
+
+impl RefundStatsView {
+    /// Aggregates the refunds among the outgoing receipts of a chunk.
+    pub fn new(shard_id: ShardId, outgoing_receipts: &[Receipt]) -> Self {
+        let mut stats = Self { shard_id, ..Default::default() };
+        for (kind, amount) in outgoing_receipts.iter().filter_map(Receipt::refund) {
+            let (receipts, total) = match kind {
+                RefundKind::Gas => (&mut stats.gas_refund_receipts, &mut stats.gas_refund_amount),
+                RefundKind::Deposit => {
+                    (&mut stats.deposit_refund_receipts, &mut stats.deposit_refund_amount)
+                }
+            };
+            *receipts += 1;
+            *total += amount;
+        }
+        stats
+    }
+}
+
+/// Refunds of the chunks of a block, for the shards the node tracks.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BlockRefundStatsView {
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    pub shards: Vec<RefundStatsView>,
+}
+
 /// It is a [serializable view] of [`StateChangesRequest`].
 ///
 /// [serializable view]: ./index.html
 /// [`StateChangesRequest`]: ../types/struct.StateChangesRequest.html
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "changes_type", rename_all = "snake_case")]
 pub enum StateChangesRequestView {
     AccountChanges {
@@ -1662,3 +2229,181 @@ impl From<StateChangeWithCause> for StateChangeWithCauseView {
 }
 
 pub type StateChangesView = Vec<StateChangeWithCauseView>;
+
+/// Outcome of the dry run of a transaction on the state at a block, nothing of which is stored.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulateTransactionView {
+    /// Hash of the block on top of which the transaction was run.
+    pub block_hash: CryptoHash,
+    /// Final status, `Started` if the receipts producing it were left unexecuted.
+    pub status: FinalExecutionStatus,
+    /// The execution outcome of the transaction, without a proof.
+    pub transaction_outcome: ExecutionOutcomeWithIdView,
+    /// The execution outcomes of the receipts, without proofs.
+    pub receipts_outcome: Vec<ExecutionOutcomeWithIdView>,
+    /// Receipts produced by the transaction and the receipts.
+    pub receipts: Vec<ReceiptView>,
+    /// Receipts to shards the node doesn't track or past the limit of receipts, not executed.
+    pub unexecuted_receipts: Vec<ReceiptView>,
+    /// Changes the transaction and the receipts executed would make to the state.
+    pub state_changes: StateChangesView,
+    /// Gas burnt by the transaction and the receipts executed.
+    pub gas_burnt: Gas,
+    /// Tokens burnt by the transaction and the receipts executed.
+    #[serde(with = "u128_dec_format")]
+    pub tokens_burnt: Balance,
+}
+
+impl SimulateTransactionView {
+    /// `outcomes` are those of the transaction and then of the receipts, in execution order.
+    pub fn new(
+        block_hash: CryptoHash,
+        outcomes: Vec<ExecutionOutcomeWithId>,
+        receipts: Vec<Receipt>,
+        unexecuted_receipts: Vec<Receipt>,
+        state_changes: Vec<RawStateChangesWithTrieKey>,
+    ) -> Result<Self, std::io::Error> {
+        let state_changes = StateChanges::from_changes(state_changes.into_iter().map(Ok))?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let gas_burnt = outcomes.iter().map(|outcome| outcome.outcome.gas_burnt).sum();
+        let tokens_burnt = outcomes.iter().map(|outcome| outcome.outcome.tokens_burnt).sum();
+        let mut outcomes = outcomes.into_iter().map(|ExecutionOutcomeWithId { id, outcome }| {
+            ExecutionOutcomeWithIdView { proof: vec![], block_hash, id, outcome: outcome.into() }
+        });
+        let transaction_outcome = outcomes.next().expect("the transaction has an outcome");
+        let receipts_outcome: Vec<_> = outcomes.collect();
+
+        let mut looking_for_id = transaction_outcome.id;
+        let mut status = FinalExecutionStatus::Started;
+        for outcome in std::iter::once(&transaction_outcome).chain(receipts_outcome.iter()) {
+            if outcome.id != looking_for_id {
+                continue;
+            }
+            match &outcome.outcome.status {
+                ExecutionStatusView::SuccessReceiptId(id) => looking_for_id = *id,
+                ExecutionStatusView::Failure(error) => {
+                    status = FinalExecutionStatus::Failure(error.clone());
+                    break;
+                }
+                ExecutionStatusView::SuccessValue(value) => {
+                    status = FinalExecutionStatus::SuccessValue(value.clone());
+                    break;
+                }
+                ExecutionStatusView::Unknown => break,
+            }
+        }
+        Ok(Self {
+            block_hash,
+            status,
+            transaction_outcome,
+            receipts_outcome,
+            receipts: receipts.into_iter().map(Into::into).collect(),
+            unexecuted_receipts: unexecuted_receipts.into_iter().map(Into::into).collect(),
+            state_changes,
+            gas_burnt,
+            tokens_burnt,
+        })
+    }
+}
+
+/// Balance of an account, by what the tokens are locked for.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountBalanceView {
+    /// The tokens which can be transferred, not staked and not paying for the storage.
+    #[serde(with = "u128_dec_format")]
+    pub liquid: Balance,
+    /// The tokens paying for the storage used by the account.
+    #[serde(with = "u128_dec_format")]
+    pub storage_staked: Balance,
+    /// The tokens locked by the validator stake of the account.
+    #[serde(with = "u128_dec_format")]
+    pub staked: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub total: Balance,
+}
+
+impl AccountBalanceView {
+    pub fn new(account: &AccountView, storage_amount_per_byte: Balance) -> Self {
+        let storage_staked =
+            (account.storage_usage as Balance).saturating_mul(storage_amount_per_byte);
+        // The locked tokens pay for the storage before the amount does.
+        let storage_staked_from_amount = storage_staked.saturating_sub(account.locked);
+        Self {
+            liquid: account.amount.saturating_sub(storage_staked_from_amount),
+            storage_staked: storage_staked.min(account.amount.saturating_add(account.locked)),
+            staked: account.locked,
+            total: account.amount.saturating_add(account.locked),
+        }
+    }
+}
+
+/// Access key of an account, and when it was used last.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccessKeySummaryView {
+    pub public_key: PublicKey,
+    pub access_key: AccessKeyView,
+    /// Height of the last function call made with the key, if the node saves the usage of the
+    /// access keys.
+    pub last_used_block_height: Option<BlockHeight>,
+}
+
+/// Lockup of an account, found under the lockup factory.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockupSummaryView {
+    pub account_id: AccountId,
+    pub balance: AccountBalanceView,
+    /// The tokens of the lockup deposited to its staking pool, as known by the lockup contract.
+    #[serde(with = "option_u128_dec_format")]
+    pub staked_balance: Option<Balance>,
+}
+
+/// What a wallet shows about an account, all at the same block.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountSummaryView {
+    pub account_id: AccountId,
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    pub balance: AccountBalanceView,
+    pub access_keys: Vec<AccessKeySummaryView>,
+    /// Highest nonce of the access keys.
+    pub max_nonce: Nonce,
+    pub lockup: Option<LockupSummaryView>,
+}
+
+/// Protocol fees of a transaction, and the gas it burns when run on the state at the block.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TransactionFeesView {
+    pub block_hash: CryptoHash,
+    #[serde(with = "u128_dec_format")]
+    pub gas_price: Balance,
+    /// Gas burnt to convert the transaction into a receipt.
+    pub send_gas: Gas,
+    /// Gas of the execution fees of the receipt, prepaid with the transaction.
+    pub exec_gas: Gas,
+    /// Gas attached to the function calls.
+    pub prepaid_gas: Gas,
+    /// Price of the prepaid gas, inflated for the blocks the receipts may take.
+    #[serde(with = "u128_dec_format")]
+    pub receipt_gas_price: Balance,
+    /// Tokens the signer must have: the fees and the prepaid gas at their prices, and the deposits.
+    #[serde(with = "u128_dec_format")]
+    pub total_cost: Balance,
+    /// Storage the actions add to the receiver.
+    pub storage_usage_increase: StorageUsage,
+    /// Tokens the receiver must have to pay for the storage added by the actions.
+    #[serde(with = "u128_dec_format")]
+    pub storage_cost: Balance,
+    /// Deposit to attach to the account the actions create, for its storage.
+    #[serde(with = "u128_dec_format")]
+    pub min_deposit: Balance,
+    /// Final status of the transaction run, `Started` if receipts to shards the node doesn't track
+    /// were left unexecuted, in which case the gas is underestimated.
+    pub status: FinalExecutionStatus,
+    /// Gas burnt by the receipts of the transaction run.
+    pub receipts_gas_burnt: Gas,
+    /// Gas to attach to the function calls for the receipts of the transaction run to be paid
+    /// for, the gas they burn but the execution fees of the receipt.
+    pub estimated_prepaid_gas: Gas,
+}
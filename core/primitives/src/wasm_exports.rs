@@ -0,0 +1,100 @@
+//! Names of the functions exported by a contract, read from the export section of its code.
+//!
+//! Only the module header and the section headers are parsed, the code isn't validated, so this
+//! is cheap enough to run on every deployment but says nothing about whether the contract can be
+//! executed.
+
+const WASM_MAGIC: &[u8; 4] = b"\0asm";
+const EXPORT_SECTION_ID: u8 = 7;
+const FUNCTION_EXPORT_KIND: u8 = 0;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.bytes.len() {
+            return None;
+        }
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(bytes)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        self.read_bytes(1).map(|bytes| bytes[0])
+    }
+
+    /// Reads an unsigned LEB128 integer of at most 32 bits.
+    fn read_u32(&mut self) -> Option<u32> {
+        let mut result = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_u8()?;
+            result |= ((byte & 0x7f) as u32).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+        }
+        None
+    }
+
+    fn read_name(&mut self) -> Option<String> {
+        let len = self.read_u32()? as usize;
+        String::from_utf8(self.read_bytes(len)?.to_vec()).ok()
+    }
+}
+
+/// Returns the names of the exported functions, in the order of the export section, or `None` if
+/// the code isn't a well-formed WASM module.
+pub fn exported_functions(code: &[u8]) -> Option<Vec<String>> {
+    let mut reader = Reader { bytes: code };
+    if reader.read_bytes(4)? != WASM_MAGIC {
+        return None;
+    }
+    reader.read_bytes(4)?; // version
+    let mut functions = vec![];
+    while !reader.is_empty() {
+        let section_id = reader.read_u8()?;
+        let len = reader.read_u32()? as usize;
+        let mut section = Reader { bytes: reader.read_bytes(len)? };
+        if section_id != EXPORT_SECTION_ID {
+            continue;
+        }
+        for _ in 0..section.read_u32()? {
+            let name = section.read_name()?;
+            let kind = section.read_u8()?;
+            section.read_u32()?; // index
+            if kind == FUNCTION_EXPORT_KIND {
+                functions.push(name);
+            }
+        }
+    }
+    Some(functions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exported_functions() {
+        let mut code = b"\0asm\x01\0\0\0".to_vec();
+        // Type section, skipped.
+        code.extend_from_slice(&[1, 4, 1, 0x60, 0, 0]);
+        // Export section with two functions and a memory.
+        code.extend_from_slice(&[7, 20, 3]);
+        code.extend_from_slice(&[4, b'm', b'a', b'i', b'n', 0, 0]);
+        code.extend_from_slice(&[3, b'm', b'e', b'm', 2, 0]);
+        code.extend_from_slice(&[3, b'g', b'e', b't', 0, 1]);
+        assert_eq!(exported_functions(&code), Some(vec!["main".to_string(), "get".to_string()]));
+        assert_eq!(exported_functions(b"\0asm\x01\0\0\0"), Some(vec![]));
+
+        assert_eq!(exported_functions(&code[..code.len() - 1]), None);
+        assert_eq!(exported_functions(b"not wasm"), None);
+    }
+}
@@ -0,0 +1,85 @@
+#[macro_use]
+extern crate bencher;
+
+use bencher::{black_box, Bencher};
+use borsh::BorshSerialize;
+use near_store::db::DBCol::{ColBlockMerkleTree, ColTransactions};
+use near_store::test_utils::create_test_store;
+
+/// Number of values in a batch, about what a block with full chunks writes.
+const NUM_VALUES: usize = 1000;
+/// Size of the serialized values, about the size of a transaction.
+const VALUE_SIZE: usize = 300;
+
+fn values() -> Vec<([u8; 32], Vec<u8>)> {
+    (0..NUM_VALUES)
+        .map(|i| {
+            let mut key = [0u8; 32];
+            key[..8].copy_from_slice(&(i as u64).to_le_bytes());
+            (key, vec![i as u8; VALUE_SIZE])
+        })
+        .collect()
+}
+
+/// Builds the store update of a block: values written with `set_ser` and refcounted values with
+/// `update_refcount_ser`, gathered from several updates merged into one.
+fn benchmark_build_block_update(bench: &mut Bencher) {
+    let store = create_test_store();
+    let values = values();
+    bench.iter(|| {
+        let mut store_update = store.store_update();
+        store_update.reserve(2 * NUM_VALUES);
+        for (key, value) in values.iter() {
+            store_update.set_ser(ColBlockMerkleTree, key, value).unwrap();
+        }
+        let mut refcount_update = store.store_update();
+        for (key, value) in values.iter() {
+            refcount_update.update_refcount_ser(ColTransactions, key, value, 1).unwrap();
+        }
+        store_update.merge(refcount_update);
+        black_box(store_update);
+    });
+}
+
+/// Same as `benchmark_build_block_update`, serializing the values to intermediate buffers as
+/// callers did before `update_refcount_ser`.
+fn benchmark_build_block_update_serialized(bench: &mut Bencher) {
+    let store = create_test_store();
+    let values = values();
+    bench.iter(|| {
+        let mut store_update = store.store_update();
+        for (key, value) in values.iter() {
+            store_update.set(ColBlockMerkleTree, key, &value.try_to_vec().unwrap());
+        }
+        let mut refcount_update = store.store_update();
+        for (key, value) in values.iter() {
+            refcount_update.update_refcount(ColTransactions, key, &value.try_to_vec().unwrap(), 1);
+        }
+        store_update.merge(refcount_update);
+        black_box(store_update);
+    });
+}
+
+/// Builds and commits the store update of a block.
+fn benchmark_commit_block_update(bench: &mut Bencher) {
+    let store = create_test_store();
+    let values = values();
+    bench.iter(|| {
+        let mut store_update = store.store_update();
+        store_update.reserve(2 * NUM_VALUES);
+        for (key, value) in values.iter() {
+            store_update.set_ser(ColBlockMerkleTree, key, value).unwrap();
+            store_update.update_refcount_ser(ColTransactions, key, value, 1).unwrap();
+        }
+        store_update.commit().unwrap();
+    });
+}
+
+benchmark_group!(
+    benches,
+    benchmark_build_block_update,
+    benchmark_build_block_update_serialized,
+    benchmark_commit_block_update
+);
+
+benchmark_main!(benches);
@@ -10,6 +10,7 @@ use rocksdb::{
     BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, Direction, Env, IteratorMode,
     Options, ReadOptions, WriteBatch, DB,
 };
+use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 use tracing::warn;
 
@@ -121,10 +122,24 @@ pub enum DBCol {
     ColHeaderHashesByHeight = 48,
     /// State changes made by a chunk, used for splitting states
     ColStateChangesForSplitStates = 49,
+    /// Statistics of transactions included in a block, indexed by block hash
+    ColBlockTransactionStats = 50,
+    /// Receipts which execution failed, indexed by block hash and shard id
+    ColFailedReceipts = 51,
+    /// Calls made with the function call access keys, indexed by account id and public key.
+    /// Only saved if enabled in the config, and garbage collected with the block deleting the key.
+    ColAccessKeyUsage = 52,
+    /// Deployments of contract code, indexed by account id. Only saved if enabled in the config.
+    ColContractHistory = 53,
+    /// Statistics of the final blocks of every epoch, indexed by epoch id.
+    ColEpochChainStats = 54,
+    /// Signers of the transactions of the final blocks of the current epochs, indexed by epoch id
+    /// and account id, to count the unique ones.
+    ColEpochSigners = 55,
 }
 
 // Do not move this line from enum DBCol
-pub const NUM_COLS: usize = 50;
+pub const NUM_COLS: usize = 56;
 
 impl std::fmt::Display for DBCol {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
@@ -181,6 +196,12 @@ impl std::fmt::Display for DBCol {
             Self::ColStateChangesForSplitStates => {
                 "state changes indexed by block hash and shard id"
             }
+            Self::ColBlockTransactionStats => "transaction statistics per block",
+            Self::ColFailedReceipts => "failed receipts indexed by block hash and shard id",
+            Self::ColAccessKeyUsage => "usage of access keys indexed by account id and public key",
+            Self::ColContractHistory => "deployments of contract code indexed by account id",
+            Self::ColEpochChainStats => "statistics of the final blocks per epoch",
+            Self::ColEpochSigners => "signers of the transactions of the current epochs",
         };
         write!(formatter, "{}", desc)
     }
@@ -214,6 +235,9 @@ pub static SHOULD_COL_GC: [bool; NUM_COLS] = {
     col_gc[DBCol::ColEpochValidatorInfo as usize] = false; // https://github.com/nearprotocol/nearcore/pull/2952
     col_gc[DBCol::ColEpochStart as usize] = false; // https://github.com/nearprotocol/nearcore/pull/2952
     col_gc[DBCol::ColCachedContractCode as usize] = false;
+    col_gc[DBCol::ColContractHistory as usize] = false; // Accumulated over the whole history
+    col_gc[DBCol::ColEpochChainStats as usize] = false; // Accumulated over the whole history
+    col_gc[DBCol::ColEpochSigners as usize] = false; // Deleted once their epoch is final
     col_gc
 };
 
@@ -225,6 +249,8 @@ pub static SKIP_COL_GC: [bool; NUM_COLS] = {
     col_gc[DBCol::ColStateHeaders as usize] = true;
     // True until #2515
     col_gc[DBCol::ColStateParts as usize] = true;
+    // Only the usage of the deleted access keys is collected
+    col_gc[DBCol::ColAccessKeyUsage as usize] = true;
     col_gc
 };
 
@@ -250,6 +276,8 @@ pub const LARGEST_TARGET_HEIGHT_KEY: &[u8; 21] = b"LARGEST_TARGET_HEIGHT";
 pub const VERSION_KEY: &[u8; 7] = b"VERSION";
 pub const GENESIS_JSON_HASH_KEY: &[u8; 17] = b"GENESIS_JSON_HASH";
 pub const GENESIS_STATE_ROOTS_KEY: &[u8; 19] = b"GENESIS_STATE_ROOTS";
+/// Key written to check that the store accepts writes, see `/readyz`.
+pub const READINESS_PROBE_KEY: &[u8; 15] = b"READINESS_PROBE";
 
 pub struct DBTransaction {
     pub ops: Vec<DBOp>,
@@ -263,6 +291,16 @@ pub enum DBOp {
 }
 
 impl DBTransaction {
+    /// Reserves capacity for at least `additional` more operations.
+    pub fn reserve(&mut self, additional: usize) {
+        self.ops.reserve(additional);
+    }
+
+    /// Same as `put`, taking ownership of the key and the value instead of copying them.
+    pub fn put_vec(&mut self, col: DBCol, key: Vec<u8>, value: Vec<u8>) {
+        self.ops.push(DBOp::Insert { col, key, value });
+    }
+
     pub fn put<K: AsRef<[u8]>, V: AsRef<[u8]>>(&mut self, col: DBCol, key: K, value: V) {
         self.ops.push(DBOp::Insert {
             col,
@@ -284,6 +322,12 @@ impl DBTransaction {
         });
     }
 
+    /// Same as `update_refcount`, taking ownership of the key and the value instead of copying
+    /// them.
+    pub fn update_refcount_vec(&mut self, col: DBCol, key: Vec<u8>, value: Vec<u8>) {
+        self.ops.push(DBOp::UpdateRefcount { col, key, value });
+    }
+
     pub fn delete<K: AsRef<[u8]>>(&mut self, col: DBCol, key: K) {
         self.ops.push(DBOp::Delete { col, key: key.as_ref().to_owned() });
     }
@@ -309,6 +353,51 @@ pub struct RocksDB {
 unsafe impl Send for RocksDB {}
 unsafe impl Sync for RocksDB {}
 
+/// Memory related tuning of the database.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct StoreConfig {
+    /// Maximum number of files RocksDB keeps open.
+    pub max_open_files: i32,
+    /// Size of the block cache of the state column, in bytes.
+    pub col_state_cache_size: u64,
+    /// Size of the block cache of each other column, in bytes.
+    pub col_cache_size: u64,
+    /// Memory budget of the memtables of each column, in bytes. A quarter of it is filled
+    /// before being flushed.
+    pub col_memtable_budget: u64,
+    /// If set, maximum number of reads served at the same time for RPC requests, so that they
+    /// don't starve the application of chunks of disk IO. Unlimited by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_max_concurrent_reads: Option<usize>,
+    /// Maximum number of reads for RPC requests waiting for their turn once
+    /// `rpc_max_concurrent_reads` are served, further ones fail.
+    pub rpc_max_queued_reads: usize,
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        Self {
+            max_open_files: 512,
+            col_state_cache_size: 512 * bytesize::MIB,
+            col_cache_size: 32 * bytesize::MIB,
+            col_memtable_budget: 128 * bytesize::MIB,
+            rpc_max_concurrent_reads: None,
+            rpc_max_queued_reads: 64,
+        }
+    }
+}
+
+impl StoreConfig {
+    /// Rough estimate of the memory the database uses with this config once its caches are
+    /// full: the block caches and one memtable being filled per column.
+    pub fn memory_estimate(&self) -> u64 {
+        self.col_state_cache_size
+            + self.col_cache_size * (NUM_COLS as u64 - 1)
+            + self.col_memtable_budget / 4 * NUM_COLS as u64
+    }
+}
+
 /// Options for configuring [`RocksDB`](RocksDB).
 ///
 /// ```rust
@@ -324,6 +413,7 @@ pub struct RocksDBOptions {
     cf_descriptors: Option<Vec<ColumnFamilyDescriptor>>,
 
     rocksdb_options: Option<Options>,
+    store_config: StoreConfig,
     check_free_space_interval: u16,
     free_space_threshold: bytesize::ByteSize,
     warn_treshold: bytesize::ByteSize,
@@ -338,6 +428,7 @@ impl Default for RocksDBOptions {
             cf_names: None,
             cf_descriptors: None,
             rocksdb_options: None,
+            store_config: StoreConfig::default(),
             check_free_space_interval: 256,
             free_space_threshold: bytesize::ByteSize::mb(16),
             warn_treshold: bytesize::ByteSize::mb(256),
@@ -366,6 +457,12 @@ impl RocksDBOptions {
         self
     }
 
+    /// Tuning of the caches and memtables, used unless the options are set explicitly.
+    pub fn store_config(mut self, store_config: StoreConfig) -> Self {
+        self.store_config = store_config;
+        self
+    }
+
     /// After n writes, the free memory in the database's data directory is checked.
     pub fn check_free_space_interval(mut self, interval: u16) -> Self {
         self.check_free_space_interval = interval;
@@ -399,7 +496,8 @@ impl RocksDBOptions {
     /// Opens the database in read/write mode.
     pub fn read_write<P: AsRef<std::path::Path>>(self, path: P) -> Result<RocksDB, DBError> {
         use strum::IntoEnumIterator;
-        let options = self.rocksdb_options.unwrap_or_else(|| rocksdb_options());
+        let store_config = self.store_config;
+        let options = self.rocksdb_options.unwrap_or_else(|| rocksdb_options(&store_config));
         let cf_names = self
             .cf_names
             .unwrap_or_else(|| DBCol::iter().map(|col| format!("col{}", col as usize)).collect());
@@ -408,7 +506,7 @@ impl RocksDBOptions {
                 .map(|col| {
                     ColumnFamilyDescriptor::new(
                         format!("col{}", col as usize),
-                        rocksdb_column_options(col),
+                        rocksdb_column_options(col, &store_config),
                     )
                 })
                 .collect()
@@ -456,6 +554,12 @@ pub trait Database: Sync + Send {
         col: DBCol,
         key_prefix: &'a [u8],
     ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+    /// Iterates in key order over the entries which keys are greater or equal to `from`.
+    fn iter_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
     fn write(&self, batch: DBTransaction) -> Result<(), DBError>;
     fn as_rocksdb(&self) -> Option<&RocksDB> {
         None
@@ -515,6 +619,23 @@ impl Database for RocksDB {
         }
     }
 
+    fn iter_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        let read_options = rocksdb_read_options();
+        unsafe {
+            let cf_handle = &*self.cfs[col as usize];
+            let iterator = self.db.iterator_cf_opt(
+                cf_handle,
+                read_options,
+                IteratorMode::From(from, Direction::Forward),
+            );
+            RocksDB::iter_with_rc_logic(col, iterator)
+        }
+    }
+
     fn write(&self, transaction: DBTransaction) -> Result<(), DBError> {
         if let Err(check) = self.pre_write_check() {
             if check.is_io() {
@@ -591,6 +712,16 @@ impl Database for TestDB {
         )
     }
 
+    fn iter_from<'a>(
+        &'a self,
+        col: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        let mut entries: Vec<_> = self.iter(col).filter(|(key, _value)| &key[..] >= from).collect();
+        entries.sort();
+        Box::new(entries.into_iter())
+    }
+
     fn write(&self, transaction: DBTransaction) -> Result<(), DBError> {
         let mut db = self.db.write().unwrap();
         for op in transaction.ops {
@@ -618,13 +749,13 @@ impl Database for TestDB {
 }
 
 /// DB level options
-fn rocksdb_options() -> Options {
+fn rocksdb_options(store_config: &StoreConfig) -> Options {
     let mut opts = Options::default();
 
     opts.create_missing_column_families(true);
     opts.create_if_missing(true);
     opts.set_use_fsync(false);
-    opts.set_max_open_files(512);
+    opts.set_max_open_files(store_config.max_open_files);
     opts.set_keep_log_file_num(1);
     opts.set_bytes_per_sync(bytesize::MIB);
     opts.set_write_buffer_size(256 * bytesize::MIB as usize);
@@ -657,7 +788,8 @@ fn rocksdb_read_options() -> ReadOptions {
 fn rocksdb_block_based_options(cache_size: usize) -> BlockBasedOptions {
     let mut block_opts = BlockBasedOptions::default();
     block_opts.set_block_size(16 * bytesize::KIB as usize);
-    // We create block_cache for each of 47 columns, so the total cache size is 32 * 47 = 1504mb
+    // We create block_cache for each column, so with the default config the total cache size is
+    // 512 + 32 * 51 = 2144mb
     block_opts.set_block_cache(&Cache::new_lru_cache(cache_size).unwrap());
     block_opts.set_pin_l0_filter_and_index_blocks_in_cache(true);
     block_opts.set_cache_index_and_filter_blocks(true);
@@ -666,19 +798,19 @@ fn rocksdb_block_based_options(cache_size: usize) -> BlockBasedOptions {
 }
 
 // TODO(#5213) Use ByteSize package to represent sizes.
-fn choose_cache_size(col: DBCol) -> usize {
+fn choose_cache_size(col: DBCol, store_config: &StoreConfig) -> usize {
     match col {
-        DBCol::ColState => 512 * 1024 * 1024,
-        _ => 32 * 1024 * 1024,
+        DBCol::ColState => store_config.col_state_cache_size as usize,
+        _ => store_config.col_cache_size as usize,
     }
 }
 
-fn rocksdb_column_options(col: DBCol) -> Options {
+fn rocksdb_column_options(col: DBCol, store_config: &StoreConfig) -> Options {
     let mut opts = Options::default();
     opts.set_level_compaction_dynamic_level_bytes(true);
-    let cache_size = choose_cache_size(col);
+    let cache_size = choose_cache_size(col, store_config);
     opts.set_block_based_table_factory(&rocksdb_block_based_options(cache_size));
-    opts.optimize_level_style_compaction(128 * bytesize::MIB as usize);
+    opts.optimize_level_style_compaction(store_config.col_memtable_budget as usize);
     opts.set_target_file_size_base(64 * bytesize::MIB);
     opts.set_compression_per_level(&[]);
     if col.is_rc() {
@@ -709,6 +841,29 @@ impl RocksDB {
         RocksDBOptions::default().read_write(path)
     }
 
+    /// Returns the space available on the volume of the database.
+    pub fn available_space(&self) -> std::io::Result<bytesize::ByteSize> {
+        available_space(self.db.path())
+    }
+
+    /// Returns the size of the SST files of the column on disk, `None` if RocksDB can't tell.
+    /// The data still in the memtables and the write-ahead log isn't counted.
+    pub fn column_disk_size(&self, col: DBCol) -> Option<u64> {
+        let cf = unsafe { &*self.cfs[col as usize] };
+        self.db.property_int_value_cf(cf, "rocksdb.total-sst-files-size").ok().flatten()
+    }
+
+    /// Compacts all the columns, reclaiming the space of the deleted data. Blocks until done.
+    pub fn compact_all(&self) {
+        for cf in self.cfs.iter() {
+            self.db.compact_range_cf(
+                unsafe { &**cf },
+                Option::<&[u8]>::None,
+                Option::<&[u8]>::None,
+            );
+        }
+    }
+
     /// Checks if there is enough memory left to perform a write. Not having enough memory left can
     /// lead to difficult to recover from state, thus a PreWriteCheckErr is pretty much
     /// unrecoverable in most cases.
@@ -833,6 +988,28 @@ mod tests {
         assert_eq!(store.get(ColState, &[1]).unwrap(), None);
     }
 
+    #[test]
+    fn test_update_refcount_ser() {
+        use borsh::BorshSerialize;
+
+        use crate::db::refcount::{encode_ser_value_with_rc, encode_value_with_rc};
+
+        let value = (7u64, vec![1u8, 2, 3]);
+        for rc in [-2, 0, 1, 3] {
+            assert_eq!(
+                encode_ser_value_with_rc(&value, rc).unwrap(),
+                encode_value_with_rc(&value.try_to_vec().unwrap(), rc)
+            );
+        }
+
+        let tmp_dir = tempfile::Builder::new().prefix("_test_refcount_ser").tempdir().unwrap();
+        let store = create_store(tmp_dir.path());
+        let mut store_update = store.store_update();
+        store_update.update_refcount_ser(ColState, &[1], &value, 2).unwrap();
+        store_update.commit().unwrap();
+        assert_eq!(store.get(ColState, &[1]).unwrap(), Some(value.try_to_vec().unwrap()));
+    }
+
     #[test]
     fn rocksdb_merge_sanity() {
         let tmp_dir = tempfile::Builder::new().prefix("_test_snapshot_sanity").tempdir().unwrap();
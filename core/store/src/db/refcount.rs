@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
-use std::io::{Cursor, Write};
+use std::io::{self, Cursor, Write};
 
+use borsh::BorshSerialize;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use rocksdb::compaction_filter::Decision;
 use rocksdb::MergeOperands;
@@ -78,6 +79,23 @@ pub(crate) fn encode_value_with_rc(data: &[u8], rc: i64) -> Vec<u8> {
     cursor.into_inner()
 }
 
+/// Same as `encode_value_with_rc` for the serialization of `value`, without serializing it to
+/// an intermediate buffer first.
+pub(crate) fn encode_ser_value_with_rc<T: BorshSerialize>(
+    value: &T,
+    rc: i64,
+) -> io::Result<Vec<u8>> {
+    if rc == 0 {
+        return Ok(vec![]);
+    }
+    let mut data = Vec::new();
+    if rc > 0 {
+        value.serialize(&mut data)?;
+    }
+    data.write_i64::<LittleEndian>(rc)?;
+    Ok(data)
+}
+
 impl RocksDB {
     /// ColState has refcounted values.
     /// Merge adds refcounts, zero refcount becomes empty value.
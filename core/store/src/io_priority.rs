@@ -0,0 +1,149 @@
+//! Priority of the database reads of consensus over the ones serving RPC requests.
+//!
+//! Every thread reads as `IoClass::Consensus` unless tagged otherwise. The threads of the view
+//! client, which serve the RPC and the state and block requests of peers, are tagged as
+//! `IoClass::Rpc`. Their reads share a limited number of slots, the others wait in a bounded
+//! queue and are rejected once it's full, so that heavy RPC traffic leaves the disk to the
+//! application of chunks instead of making the node miss them. The reads of RPC threads working
+//! for a request whose client went away fail right away.
+
+use std::cell::Cell;
+use std::io;
+use std::sync::{Condvar, Mutex};
+
+use near_primitives::cancellation::is_thread_cancelled;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IoClass {
+    /// Processing of blocks and chunks, never throttled.
+    Consensus,
+    /// Serving requests of RPC clients and peers.
+    Rpc,
+}
+
+thread_local! {
+    static THREAD_IO_CLASS: Cell<IoClass> = Cell::new(IoClass::Consensus);
+}
+
+/// Tags all the following reads of the current thread.
+pub fn set_thread_io_class(io_class: IoClass) {
+    THREAD_IO_CLASS.with(|cell| cell.set(io_class));
+}
+
+pub fn thread_io_class() -> IoClass {
+    THREAD_IO_CLASS.with(|cell| cell.get())
+}
+
+#[derive(Default)]
+struct ReadQueue {
+    in_flight: usize,
+    queued: usize,
+}
+
+/// Admits the reads of `IoClass::Rpc` threads, at most `max_concurrent_reads` of them at a time
+/// and `max_queued_reads` waiting. Without limits, all reads are admitted right away.
+#[derive(Default)]
+pub(crate) struct ReadScheduler {
+    limits: Option<(usize, usize)>,
+    queue: Mutex<ReadQueue>,
+    slot_freed: Condvar,
+}
+
+/// Slot of an RPC read, freed on drop.
+pub(crate) struct ReadPermit<'a> {
+    scheduler: Option<&'a ReadScheduler>,
+}
+
+impl ReadScheduler {
+    pub fn new(max_concurrent_reads: usize, max_queued_reads: usize) -> Self {
+        Self { limits: Some((max_concurrent_reads.max(1), max_queued_reads)), ..Self::default() }
+    }
+
+    pub fn admit(&self) -> io::Result<ReadPermit<'_>> {
+        if thread_io_class() == IoClass::Rpc && is_thread_cancelled() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Database read cancelled, the request was abandoned",
+            ));
+        }
+        let (max_concurrent_reads, max_queued_reads) = match self.limits {
+            Some(limits) if thread_io_class() == IoClass::Rpc => limits,
+            _ => return Ok(ReadPermit { scheduler: None }),
+        };
+        let mut queue = self.queue.lock().unwrap();
+        if queue.in_flight >= max_concurrent_reads {
+            if queue.queued >= max_queued_reads {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "Too many database reads queued for RPC requests",
+                ));
+            }
+            queue.queued += 1;
+            while queue.in_flight >= max_concurrent_reads {
+                queue = self.slot_freed.wait(queue).unwrap();
+            }
+            queue.queued -= 1;
+        }
+        queue.in_flight += 1;
+        Ok(ReadPermit { scheduler: Some(self) })
+    }
+
+    #[cfg(test)]
+    fn queued(&self) -> usize {
+        self.queue.lock().unwrap().queued
+    }
+}
+
+impl Drop for ReadPermit<'_> {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler {
+            scheduler.queue.lock().unwrap().in_flight -= 1;
+            scheduler.slot_freed.notify_one();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::cancellation::{self, CancellationToken};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rpc_reads_are_limited() {
+        let scheduler = Arc::new(ReadScheduler::new(1, 1));
+        set_thread_io_class(IoClass::Rpc);
+        let permit = scheduler.admit().unwrap();
+
+        let queued = {
+            let scheduler = scheduler.clone();
+            std::thread::spawn(move || {
+                set_thread_io_class(IoClass::Rpc);
+                scheduler.admit().map(|_| ()).is_ok()
+            })
+        };
+        while scheduler.queued() == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        // The queue is full, the next RPC read is rejected but consensus reads go through.
+        assert!(scheduler.admit().is_err());
+        set_thread_io_class(IoClass::Consensus);
+        let _consensus_permit = scheduler.admit().unwrap();
+        drop(permit);
+        assert!(queued.join().unwrap());
+    }
+
+    #[test]
+    fn test_cancelled_rpc_reads_fail() {
+        let scheduler = ReadScheduler::default();
+        let token = CancellationToken::new();
+        let _entered = cancellation::enter(token.clone());
+        set_thread_io_class(IoClass::Rpc);
+        assert!(scheduler.admit().is_ok());
+        token.cancel();
+        assert!(scheduler.admit().is_err());
+        set_thread_io_class(IoClass::Consensus);
+        assert!(scheduler.admit().is_ok());
+    }
+}
@@ -13,7 +13,8 @@ use cached::{Cached, SizedCache};
 pub use db::DBCol::{self, *};
 pub use db::{
     CHUNK_TAIL_KEY, FINAL_HEAD_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY,
-    LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, NUM_COLS, SHOULD_COL_GC, SKIP_COL_GC, TAIL_KEY,
+    LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, NUM_COLS, READINESS_PROBE_KEY, SHOULD_COL_GC,
+    SKIP_COL_GC, TAIL_KEY,
 };
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
@@ -27,17 +28,23 @@ use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::{AccountId, CompiledContractCache, StateRoot};
 
 pub use crate::db::refcount::decode_value_with_rc;
-use crate::db::refcount::encode_value_with_rc;
+use crate::db::refcount::{encode_ser_value_with_rc, encode_value_with_rc};
+pub use crate::db::StoreConfig;
 use crate::db::{
-    DBOp, DBTransaction, Database, RocksDB, GENESIS_JSON_HASH_KEY, GENESIS_STATE_ROOTS_KEY,
+    DBOp, DBTransaction, Database, RocksDB, RocksDBOptions, GENESIS_JSON_HASH_KEY,
+    GENESIS_STATE_ROOTS_KEY,
 };
+use crate::io_priority::ReadScheduler;
+pub use crate::io_priority::{set_thread_io_class, thread_io_class, IoClass};
 pub use crate::trie::{
-    iterator::TrieIterator, split_state, update::TrieUpdate, update::TrieUpdateIterator,
-    update::TrieUpdateValuePtr, ApplyStatePartResult, KeyForStateChanges, PartialStorage,
-    ShardTries, Trie, TrieChanges, WrappedTrieChanges,
+    iterator::TrieIterator, read_state_page, split_state, update::TrieUpdate,
+    update::TrieUpdateIterator, update::TrieUpdateValuePtr, verify_state_page,
+    ApplyStatePartResult, KeyForStateChanges, PartialStorage, RefcountAudit, RefcountMismatch,
+    RefcountReport, ShardTries, StatePageItem, Trie, TrieChanges, WrappedTrieChanges,
 };
 
 pub mod db;
+mod io_priority;
 pub mod migrations;
 pub mod test_utils;
 mod trie;
@@ -45,14 +52,28 @@ mod trie;
 #[derive(Clone)]
 pub struct Store {
     storage: Pin<Arc<dyn Database>>,
+    read_scheduler: Arc<ReadScheduler>,
 }
 
 impl Store {
     pub fn new(storage: Pin<Arc<dyn Database>>) -> Store {
-        Store { storage }
+        Store { storage, read_scheduler: Arc::default() }
+    }
+
+    /// Limits the reads of the threads tagged as `IoClass::Rpc` as set in the config, if it
+    /// does.
+    pub fn with_read_limits(mut self, store_config: &StoreConfig) -> Store {
+        if let Some(max_concurrent_reads) = store_config.rpc_max_concurrent_reads {
+            self.read_scheduler = Arc::new(ReadScheduler::new(
+                max_concurrent_reads,
+                store_config.rpc_max_queued_reads,
+            ));
+        }
+        self
     }
 
     pub fn get(&self, column: DBCol, key: &[u8]) -> Result<Option<Vec<u8>>, io::Error> {
+        let _permit = self.read_scheduler.admit()?;
         self.storage.get(column, key).map_err(|e| e.into())
     }
 
@@ -61,6 +82,7 @@ impl Store {
         column: DBCol,
         key: &[u8],
     ) -> Result<Option<T>, io::Error> {
+        let _permit = self.read_scheduler.admit()?;
         match self.storage.get(column, key) {
             Ok(Some(bytes)) => match T::try_from_slice(bytes.as_ref()) {
                 Ok(result) => Ok(Some(result)),
@@ -72,6 +94,7 @@ impl Store {
     }
 
     pub fn exists(&self, column: DBCol, key: &[u8]) -> Result<bool, io::Error> {
+        let _permit = self.read_scheduler.admit()?;
         self.storage.get(column, key).map(|value| value.is_some()).map_err(|e| e.into())
     }
 
@@ -101,6 +124,14 @@ impl Store {
         self.storage.iter_prefix(column, key_prefix)
     }
 
+    pub fn iter_from<'a>(
+        &'a self,
+        column: DBCol,
+        from: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        self.storage.iter_from(column, from)
+    }
+
     pub fn iter_prefix_ser<'a, T: BorshDeserialize>(
         &'a self,
         column: DBCol,
@@ -173,10 +204,30 @@ impl StoreUpdate {
         StoreUpdate { storage, transaction, tries: Some(tries.clone()) }
     }
 
+    /// Reserves capacity for at least `additional` more operations, for callers which know how
+    /// large their update is going to be.
+    pub fn reserve(&mut self, additional: usize) {
+        self.transaction.reserve(additional);
+    }
+
     pub fn update_refcount(&mut self, column: DBCol, key: &[u8], value: &[u8], rc_delta: i64) {
         debug_assert!(column.is_rc());
         let value = encode_value_with_rc(value, rc_delta);
-        self.transaction.update_refcount(column, key, value)
+        self.transaction.update_refcount_vec(column, key.to_vec(), value)
+    }
+
+    /// Same as `update_refcount` for the borsh serialization of `value`.
+    pub fn update_refcount_ser<T: BorshSerialize>(
+        &mut self,
+        column: DBCol,
+        key: &[u8],
+        value: &T,
+        rc_delta: i64,
+    ) -> Result<(), io::Error> {
+        debug_assert!(column.is_rc());
+        let value = encode_ser_value_with_rc(value, rc_delta)?;
+        self.transaction.update_refcount_vec(column, key.to_vec(), value);
+        Ok(())
     }
 
     pub fn set(&mut self, column: DBCol, key: &[u8], value: &[u8]) {
@@ -191,7 +242,7 @@ impl StoreUpdate {
     ) -> Result<(), io::Error> {
         debug_assert!(!column.is_rc());
         let data = value.try_to_vec()?;
-        self.set(column, key, &data);
+        self.transaction.put_vec(column, key.to_vec(), data);
         Ok(())
     }
 
@@ -218,16 +269,7 @@ impl StoreUpdate {
 
     /// Merge DB Transaction.
     pub fn merge_transaction(&mut self, transaction: DBTransaction) {
-        for op in transaction.ops {
-            match op {
-                DBOp::Insert { col, key, value } => self.transaction.put(col, &key, &value),
-                DBOp::Delete { col, key } => self.transaction.delete(col, &key),
-                DBOp::UpdateRefcount { col, key, value } => {
-                    self.transaction.update_refcount(col, &key, &value)
-                }
-                DBOp::DeleteAll { col } => self.transaction.delete_all(col),
-            }
-        }
+        self.transaction.ops.extend(transaction.ops);
     }
 
     pub fn commit(self) -> Result<(), io::Error> {
@@ -300,6 +342,16 @@ pub fn create_store(path: &Path) -> Arc<Store> {
     Arc::new(Store::new(db))
 }
 
+pub fn create_store_with_config(path: &Path, store_config: &StoreConfig) -> Arc<Store> {
+    let db = Arc::pin(
+        RocksDBOptions::default()
+            .store_config(store_config.clone())
+            .read_write(path)
+            .expect("Failed to open the database"),
+    );
+    Arc::new(Store::new(db).with_read_limits(store_config))
+}
+
 /// Reads an object from Trie.
 /// # Errors
 /// see StorageError
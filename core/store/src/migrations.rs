@@ -43,6 +43,7 @@ use crate::trie::{TrieCache, TrieCachingStorage};
 use crate::{create_store, Store, StoreUpdate, Trie, TrieUpdate, FINAL_HEAD_KEY, HEAD_KEY};
 use std::path::Path;
 
+pub mod online;
 pub mod v6_to_v7;
 pub mod v8_to_v9;
 
@@ -0,0 +1,383 @@
+//! Online migrations of the format of the values of a column, for format changes which would
+//! take hours to migrate offline on archival nodes.
+//!
+//! The values are converted from a source column into a target column while the node runs,
+//! through phases persisted in `ColBlockMisc` so that the migration resumes after a restart:
+//!
+//! 1. Backfill: writes go to both columns through `OnlineMigration::set` and `delete`, while
+//!    the existing entries of the source column are converted in batches, in key order.
+//! 2. Verify: writes still go to both columns. The target is compared to the conversion of the
+//!    source on a sample of the reads, and on a sample of all the entries in a background pass.
+//!    Mismatches are repaired, and a pass with mismatches is followed by another one.
+//! 3. Done: after a clean verification pass the source column is deleted and reads and writes
+//!    only go to the target column.
+//!
+//! Until the migration is removed from the code, writers keep passing values in the source
+//! format, which are converted as they are written.
+
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use rand::Rng;
+use tracing::{debug, info, warn};
+
+use crate::db::DBCol;
+use crate::{Store, StoreUpdate};
+
+/// Conversion of the values of a column to a new format, stored in another column under the
+/// same keys. Refcounted columns are not supported.
+pub trait ColumnMigration: Send + Sync {
+    /// Unique name of the migration, under which its progress is persisted.
+    fn name(&self) -> &'static str;
+    fn source(&self) -> DBCol;
+    fn target(&self) -> DBCol;
+    /// Value of an entry of the source column in the format of the target column.
+    fn convert(&self, key: &[u8], value: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+#[derive(Clone, Debug)]
+pub struct OnlineMigrationConfig {
+    /// Number of entries converted or verified per batch.
+    pub batch_size: usize,
+    /// Pause between batches in the background, to leave the database to the node.
+    pub batch_pause: Duration,
+    /// Share of the reads, and of the entries of the verification pass, which are verified.
+    pub verify_sample_rate: f64,
+}
+
+impl Default for OnlineMigrationConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 10_000,
+            batch_pause: Duration::from_millis(100),
+            verify_sample_rate: 0.01,
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum MigrationPhase {
+    /// `cursor` is the key after the last converted entry.
+    Backfill {
+        cursor: Option<Vec<u8>>,
+        migrated: u64,
+    },
+    /// `cursor` is the key after the last entry of the verification pass.
+    Verify {
+        cursor: Option<Vec<u8>>,
+        verified: u64,
+        mismatches: u64,
+    },
+    Done,
+}
+
+fn phase_key(name: &str) -> Vec<u8> {
+    format!("ONLINE_MIGRATION:{}", name).into_bytes()
+}
+
+/// Key right after `key` in the key order.
+fn next_key(key: &[u8]) -> Vec<u8> {
+    let mut next = key.to_vec();
+    next.push(0);
+    next
+}
+
+pub struct OnlineMigration {
+    store: Arc<Store>,
+    migration: Box<dyn ColumnMigration>,
+    config: OnlineMigrationConfig,
+    phase: Mutex<MigrationPhase>,
+    /// Mismatches found on reads since the last verification batch.
+    read_mismatches: AtomicU64,
+}
+
+impl OnlineMigration {
+    /// Resumes the migration from its persisted phase, or starts it.
+    pub fn new(
+        store: Arc<Store>,
+        migration: Box<dyn ColumnMigration>,
+        config: OnlineMigrationConfig,
+    ) -> io::Result<Self> {
+        assert!(
+            !migration.source().is_rc() && !migration.target().is_rc(),
+            "Online migrations of refcounted columns are not supported"
+        );
+        let phase = store
+            .get_ser(DBCol::ColBlockMisc, &phase_key(migration.name()))?
+            .unwrap_or(MigrationPhase::Backfill { cursor: None, migrated: 0 });
+        info!(target: "store", "Online migration {}: {:?}", migration.name(), phase);
+        Ok(Self {
+            store,
+            migration,
+            config,
+            phase: Mutex::new(phase),
+            read_mismatches: AtomicU64::new(0),
+        })
+    }
+
+    pub fn phase(&self) -> MigrationPhase {
+        self.phase.lock().unwrap().clone()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.phase() == MigrationPhase::Done
+    }
+
+    /// Writes an entry given in the source format.
+    pub fn set(&self, store_update: &mut StoreUpdate, key: &[u8], value: &[u8]) -> io::Result<()> {
+        store_update.set(self.migration.target(), key, &self.migration.convert(key, value)?);
+        if !self.is_done() {
+            store_update.set(self.migration.source(), key, value);
+        }
+        Ok(())
+    }
+
+    pub fn delete(&self, store_update: &mut StoreUpdate, key: &[u8]) {
+        store_update.delete(self.migration.target(), key);
+        if !self.is_done() {
+            store_update.delete(self.migration.source(), key);
+        }
+    }
+
+    /// Reads an entry in the target format. The source column stays authoritative until the
+    /// cutover.
+    pub fn get(&self, key: &[u8]) -> io::Result<Option<Vec<u8>>> {
+        let phase = self.phase();
+        if phase == MigrationPhase::Done {
+            return self.store.get(self.migration.target(), key);
+        }
+        let value = match self.store.get(self.migration.source(), key)? {
+            Some(value) => self.migration.convert(key, &value)?,
+            None => return Ok(None),
+        };
+        if let MigrationPhase::Verify { .. } = phase {
+            if rand::thread_rng().gen::<f64>() < self.config.verify_sample_rate {
+                if !self.verify_entry(key, &value)? {
+                    self.read_mismatches.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// Checks the target entry against the converted source value, repairing it if needed.
+    /// Returns whether it matched.
+    fn verify_entry(&self, key: &[u8], expected: &[u8]) -> io::Result<bool> {
+        let actual = self.store.get(self.migration.target(), key)?;
+        if actual.as_deref() == Some(expected) {
+            return Ok(true);
+        }
+        warn!(target: "store", "Online migration {}: repairing mismatched entry {:?}", self.migration.name(), key);
+        let mut store_update = self.store.store_update();
+        store_update.set(self.migration.target(), key, expected);
+        store_update.commit()?;
+        Ok(false)
+    }
+
+    /// Runs one batch of the current phase and persists the progress. Returns the new phase.
+    pub fn step(&self) -> io::Result<MigrationPhase> {
+        let phase = self.phase();
+        let next_phase = match phase {
+            MigrationPhase::Backfill { cursor, migrated } => {
+                self.backfill_batch(cursor, migrated)?
+            }
+            MigrationPhase::Verify { cursor, verified, mismatches } => {
+                self.verify_batch(cursor, verified, mismatches)?
+            }
+            MigrationPhase::Done => return Ok(MigrationPhase::Done),
+        };
+        let mut store_update = self.store.store_update();
+        if next_phase == MigrationPhase::Done {
+            info!(target: "store", "Online migration {}: cutting over to {:?}", self.migration.name(), self.migration.target());
+            store_update.delete_all(self.migration.source());
+        }
+        store_update.set_ser(
+            DBCol::ColBlockMisc,
+            &phase_key(self.migration.name()),
+            &next_phase,
+        )?;
+        store_update.commit()?;
+        *self.phase.lock().unwrap() = next_phase.clone();
+        Ok(next_phase)
+    }
+
+    fn backfill_batch(&self, cursor: Option<Vec<u8>>, migrated: u64) -> io::Result<MigrationPhase> {
+        let from = cursor.unwrap_or_default();
+        let mut store_update = self.store.store_update();
+        let mut last_key = None;
+        let mut count = 0;
+        for (key, value) in
+            self.store.iter_from(self.migration.source(), &from).take(self.config.batch_size)
+        {
+            // Entries written since the migration started are already in the target format,
+            // and possibly more recent than the value read here.
+            if !self.store.exists(self.migration.target(), &key)? {
+                store_update.set(
+                    self.migration.target(),
+                    &key,
+                    &self.migration.convert(&key, &value)?,
+                );
+            }
+            last_key = Some(key);
+            count += 1;
+        }
+        store_update.commit()?;
+        let migrated = migrated + count as u64;
+        Ok(match last_key {
+            Some(key) if count == self.config.batch_size => {
+                debug!(target: "store", "Online migration {}: {} entries converted", self.migration.name(), migrated);
+                MigrationPhase::Backfill { cursor: Some(next_key(&key)), migrated }
+            }
+            _ => {
+                info!(target: "store", "Online migration {}: backfill of {} entries done, verifying", self.migration.name(), migrated);
+                MigrationPhase::Verify { cursor: None, verified: 0, mismatches: 0 }
+            }
+        })
+    }
+
+    fn verify_batch(
+        &self,
+        cursor: Option<Vec<u8>>,
+        mut verified: u64,
+        mut mismatches: u64,
+    ) -> io::Result<MigrationPhase> {
+        let from = cursor.unwrap_or_default();
+        // Mismatches found on reads keep the current pass from being clean.
+        mismatches += self.read_mismatches.swap(0, Ordering::Relaxed);
+        let mut rng = rand::thread_rng();
+        let mut last_key = None;
+        let mut count = 0;
+        for (key, value) in
+            self.store.iter_from(self.migration.source(), &from).take(self.config.batch_size)
+        {
+            if rng.gen::<f64>() < self.config.verify_sample_rate {
+                verified += 1;
+                if !self.verify_entry(&key, &self.migration.convert(&key, &value)?)? {
+                    mismatches += 1;
+                }
+            }
+            last_key = Some(key);
+            count += 1;
+        }
+        Ok(match last_key {
+            Some(key) if count == self.config.batch_size => {
+                MigrationPhase::Verify { cursor: Some(next_key(&key)), verified, mismatches }
+            }
+            _ if mismatches > 0 => {
+                warn!(target: "store", "Online migration {}: {} of {} verified entries mismatched and were repaired, verifying again", self.migration.name(), mismatches, verified);
+                MigrationPhase::Verify { cursor: None, verified: 0, mismatches: 0 }
+            }
+            _ => MigrationPhase::Done,
+        })
+    }
+
+    /// Runs the migration to completion in a background thread.
+    pub fn spawn(self: Arc<Self>) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || loop {
+            match self.step() {
+                Ok(MigrationPhase::Done) => {
+                    info!(target: "store", "Online migration {} done", self.migration.name());
+                    break;
+                }
+                Ok(_) => std::thread::sleep(self.config.batch_pause),
+                Err(err) => {
+                    warn!(target: "store", "Online migration {} failed, retrying: {}", self.migration.name(), err);
+                    std::thread::sleep(self.config.batch_pause * 10);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::create_test_store;
+
+    /// Widens little endian `u32` values to `u64`.
+    struct WidenMigration;
+
+    impl ColumnMigration for WidenMigration {
+        fn name(&self) -> &'static str {
+            "widen"
+        }
+
+        fn source(&self) -> DBCol {
+            DBCol::ColBlockInfo
+        }
+
+        fn target(&self) -> DBCol {
+            DBCol::ColEpochStart
+        }
+
+        fn convert(&self, _key: &[u8], value: &[u8]) -> io::Result<Vec<u8>> {
+            let value = u32::try_from_slice(value)?;
+            (value as u64).try_to_vec()
+        }
+    }
+
+    fn config() -> OnlineMigrationConfig {
+        OnlineMigrationConfig { batch_size: 3, verify_sample_rate: 1.0, ..Default::default() }
+    }
+
+    fn get_u64(migration: &OnlineMigration, key: u8) -> Option<u64> {
+        migration.get(&[key]).unwrap().map(|value| u64::try_from_slice(&value).unwrap())
+    }
+
+    #[test]
+    fn test_online_migration() {
+        let store = create_test_store();
+        let mut store_update = store.store_update();
+        for key in 0..10u8 {
+            store_update.set_ser(DBCol::ColBlockInfo, &[key], &(key as u32)).unwrap();
+        }
+        store_update.commit().unwrap();
+
+        let migration =
+            OnlineMigration::new(store.clone(), Box::new(WidenMigration), config()).unwrap();
+        assert_eq!(
+            migration.step().unwrap(),
+            MigrationPhase::Backfill { cursor: Some(vec![2, 0]), migrated: 3 }
+        );
+        // Written in both formats while the backfill runs.
+        let mut store_update = store.store_update();
+        migration.set(&mut store_update, &[5], &100u32.try_to_vec().unwrap()).unwrap();
+        migration.delete(&mut store_update, &[6]);
+        store_update.commit().unwrap();
+        assert_eq!(get_u64(&migration, 5), Some(100));
+
+        // The progress is persisted.
+        let migration =
+            OnlineMigration::new(store.clone(), Box::new(WidenMigration), config()).unwrap();
+        while let MigrationPhase::Backfill { .. } = migration.step().unwrap() {}
+        assert_eq!(store.get_ser::<u64>(DBCol::ColEpochStart, &[5]).unwrap(), Some(100));
+        assert!(!store.exists(DBCol::ColEpochStart, &[6]).unwrap());
+
+        // A corrupted entry is repaired and fails the verification pass.
+        let mut store_update = store.store_update();
+        store_update.set_ser(DBCol::ColEpochStart, &[7], &0u64).unwrap();
+        store_update.commit().unwrap();
+        assert_eq!(get_u64(&migration, 7), Some(7));
+        assert_eq!(store.get_ser::<u64>(DBCol::ColEpochStart, &[7]).unwrap(), Some(7));
+        let mut phase = migration.step().unwrap();
+        assert_eq!(
+            phase,
+            MigrationPhase::Verify { cursor: Some(vec![2, 0]), verified: 3, mismatches: 1 }
+        );
+        while phase != MigrationPhase::Done {
+            phase = migration.step().unwrap();
+        }
+
+        assert_eq!(store.iter(DBCol::ColBlockInfo).count(), 0);
+        assert_eq!(get_u64(&migration, 9), Some(9));
+        assert_eq!(get_u64(&migration, 6), None);
+        let mut store_update = store.store_update();
+        migration.set(&mut store_update, &[1], &42u32.try_to_vec().unwrap()).unwrap();
+        store_update.commit().unwrap();
+        assert_eq!(get_u64(&migration, 1), Some(42));
+        assert_eq!(store.iter(DBCol::ColBlockInfo).count(), 0);
+    }
+}
@@ -17,7 +17,9 @@ use near_primitives::types::{StateRoot, StateRootNode};
 use crate::trie::insert_delete::NodesStorage;
 use crate::trie::iterator::TrieIterator;
 use crate::trie::nibble_slice::NibbleSlice;
+pub use crate::trie::refcount_audit::{RefcountAudit, RefcountMismatch, RefcountReport};
 pub use crate::trie::shard_tries::{KeyForStateChanges, ShardTries, WrappedTrieChanges};
+pub use crate::trie::state_page::{read_state_page, verify_state_page, StatePageItem};
 use crate::trie::trie_storage::{
     TouchedNodesCounter, TrieMemoryPartialStorage, TrieRecordingStorage, TrieStorage,
 };
@@ -27,8 +29,10 @@ use crate::StorageError;
 mod insert_delete;
 pub mod iterator;
 mod nibble_slice;
+mod refcount_audit;
 mod shard_tries;
 pub mod split_state;
+mod state_page;
 mod state_parts;
 mod trie_storage;
 pub mod update;
@@ -0,0 +1,238 @@
+//! Audit of the reference counts of the trie nodes and values stored in `ColState`.
+//!
+//! The reference count of a node or value is the number of times the `TrieChanges` of the blocks
+//! inserted it, minus the number of times the garbage collection deleted it. Once every block but
+//! the head is collected, it's the number of times it appears in the trie of the head. Until then,
+//! it also counts the changes the collection of the retained blocks will undo: the deletions of
+//! the canonical blocks, and the insertions of the forks.
+//!
+//! The nodes stored with a positive count which no trie references are leaked, and take disk space
+//! forever. The nodes referenced but not stored are dangling, and reading the tries fails.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::io;
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::types::StateRoot;
+
+use crate::trie::trie_storage::TrieCachingStorage;
+use crate::trie::{RawTrieNode, RawTrieNodeWithSize, TrieChanges};
+use crate::{decode_value_with_rc, DBCol, StorageError, Store, Trie};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RefcountMismatch {
+    pub hash: CryptoHash,
+    pub stored: i64,
+    pub expected: i64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RefcountReport {
+    pub shard_uid: ShardUId,
+    /// Number of nodes and values stored for the shard.
+    pub stored: u64,
+    /// Stored with a positive count, but referenced by no trie.
+    pub leaked: Vec<RefcountMismatch>,
+    /// Referenced, but stored with another count.
+    pub drifted: Vec<RefcountMismatch>,
+    /// Referenced, but not stored.
+    pub dangling: Vec<CryptoHash>,
+}
+
+impl RefcountReport {
+    pub fn is_consistent(&self) -> bool {
+        self.leaked.is_empty() && self.drifted.is_empty() && self.dangling.is_empty()
+    }
+
+    /// Sets the counts of the leaked and drifted nodes to the expected ones, deleting the leaked
+    /// nodes, and returns how many were changed. Refused if there are dangling nodes: the nodes
+    /// below them, which the audit couldn't reach, would be taken for leaked.
+    pub fn repair(&self, store: &Store) -> io::Result<usize> {
+        if !self.dangling.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!(
+                    "{} dangling nodes in shard {:?}, the state must be synced again",
+                    self.dangling.len(),
+                    self.shard_uid
+                ),
+            ));
+        }
+        let mut store_update = store.store_update();
+        for mismatch in self.leaked.iter().chain(&self.drifted) {
+            let key =
+                TrieCachingStorage::get_key_from_shard_uid_and_hash(self.shard_uid, &mismatch.hash);
+            let value = store.get(DBCol::ColState, &key)?.unwrap_or_default();
+            store_update.update_refcount(
+                DBCol::ColState,
+                &key,
+                &value,
+                mismatch.expected - mismatch.stored,
+            );
+        }
+        store_update.commit()?;
+        Ok(self.leaked.len() + self.drifted.len())
+    }
+}
+
+/// Reference counts of the nodes and values of a shard, expected from its tries.
+pub struct RefcountAudit {
+    shard_uid: ShardUId,
+    expected: HashMap<CryptoHash, i64>,
+}
+
+impl RefcountAudit {
+    /// Counts the references of the trie at `state_root`, the state of the shard at the head.
+    pub fn new(
+        store: &Store,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+    ) -> Result<Self, StorageError> {
+        let mut expected: HashMap<CryptoHash, i64> = HashMap::new();
+        let mut nodes = vec![*state_root];
+        while let Some(hash) = nodes.pop() {
+            if hash == Trie::empty_root() {
+                continue;
+            }
+            *expected.entry(hash).or_default() += 1;
+            let key = TrieCachingStorage::get_key_from_shard_uid_and_hash(shard_uid, &hash);
+            let bytes = match store
+                .get(DBCol::ColState, &key)
+                .map_err(|_| StorageError::StorageInternalError)?
+            {
+                Some(bytes) => bytes,
+                // Reported as dangling by `check`.
+                None => continue,
+            };
+            let node = RawTrieNodeWithSize::decode(&bytes).map_err(|_| {
+                StorageError::StorageInconsistentState(format!("Failed to decode node {}", hash))
+            })?;
+            match node.node {
+                RawTrieNode::Leaf(_, _, value_hash) => {
+                    *expected.entry(value_hash).or_default() += 1;
+                }
+                RawTrieNode::Branch(children, value) => {
+                    if let Some((_, value_hash)) = value {
+                        *expected.entry(value_hash).or_default() += 1;
+                    }
+                    nodes.extend(children.iter().flatten());
+                }
+                RawTrieNode::Extension(_, child) => nodes.push(child),
+            }
+        }
+        Ok(Self { shard_uid, expected })
+    }
+
+    /// Counts the changes of a retained block which its garbage collection will undo.
+    pub fn add_pending_gc(&mut self, trie_changes: &TrieChanges, canonical: bool) {
+        let changes = if canonical { &trie_changes.deletions } else { &trie_changes.insertions };
+        for change in changes {
+            *self.expected.entry(change.trie_node_or_value_hash).or_default() += change.rc as i64;
+        }
+    }
+
+    /// Compares the expected counts to the stored ones.
+    pub fn check(&self, store: &Store) -> RefcountReport {
+        let prefix = self.shard_uid.to_bytes();
+        let mut report = RefcountReport {
+            shard_uid: self.shard_uid,
+            stored: 0,
+            leaked: vec![],
+            drifted: vec![],
+            dangling: vec![],
+        };
+        let mut present = HashSet::new();
+        for (key, value) in store.iter_without_rc_logic(DBCol::ColState) {
+            if key.len() != 40 || key[..8] != prefix {
+                continue;
+            }
+            let (_, stored) = decode_value_with_rc(&value);
+            if stored == 0 {
+                continue;
+            }
+            report.stored += 1;
+            let hash = CryptoHash::try_from(&key[8..]).unwrap();
+            let expected = self.expected.get(&hash).copied().unwrap_or_default();
+            if stored > 0 {
+                present.insert(hash);
+            }
+            let mismatch = RefcountMismatch { hash, stored, expected };
+            if expected == 0 && stored > 0 {
+                report.leaked.push(mismatch);
+            } else if stored != expected && (stored > 0 || expected == 0) {
+                report.drifted.push(mismatch);
+            }
+        }
+        report.dangling = self
+            .expected
+            .iter()
+            .filter(|(hash, expected)| **expected > 0 && !present.contains(hash))
+            .map(|(hash, _)| *hash)
+            .collect();
+        report.leaked.sort_by_key(|mismatch| mismatch.hash);
+        report.drifted.sort_by_key(|mismatch| mismatch.hash);
+        report.dangling.sort();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::hash;
+
+    use super::*;
+    use crate::test_utils::{create_tries, test_populate_trie};
+
+    #[test]
+    fn test_refcount_audit() {
+        let tries = create_tries();
+        let store = tries.get_store();
+        let shard_uid = ShardUId { version: 0, shard_id: 0 };
+        let changes = (0..20u8).map(|i| (vec![i / 4, i], Some(vec![i % 3]))).collect();
+        let root = test_populate_trie(&tries, &Trie::empty_root(), shard_uid, changes);
+
+        // The next block isn't collected yet: its deletions are still pending.
+        let trie_changes = tries
+            .get_trie_for_shard(shard_uid)
+            .update(&root, vec![(vec![1, 5], Some(vec![7])), (vec![2, 8], None)].into_iter())
+            .unwrap();
+        let mut store_update = store.store_update();
+        tries.apply_insertions(&trie_changes, shard_uid, &mut store_update).unwrap();
+        store_update.commit().unwrap();
+        let head_root = trie_changes.new_root;
+
+        let audit = |pending: bool| {
+            let mut audit = RefcountAudit::new(&store, shard_uid, &head_root).unwrap();
+            if pending {
+                audit.add_pending_gc(&trie_changes, true);
+            }
+            audit.check(&store)
+        };
+        let report = audit(true);
+        assert!(report.is_consistent(), "{:?}", report);
+        // Without the pending deletions, the nodes of the previous state look leaked.
+        assert!(!audit(false).leaked.is_empty());
+
+        let mut store_update = store.store_update();
+        let key = |hash| TrieCachingStorage::get_key_from_shard_uid_and_hash(shard_uid, &hash);
+        store_update.update_refcount(DBCol::ColState, &key(head_root), &[], 2);
+        let leaked = hash(b"leaked");
+        store_update.update_refcount(DBCol::ColState, &key(leaked), b"leaked", 1);
+        store_update.commit().unwrap();
+        let report = audit(true);
+        assert_eq!(report.leaked, vec![RefcountMismatch { hash: leaked, stored: 1, expected: 0 }]);
+        assert_eq!(
+            report.drifted,
+            vec![RefcountMismatch { hash: head_root, stored: 3, expected: 1 }]
+        );
+        assert!(report.dangling.is_empty());
+
+        assert_eq!(report.repair(&store).unwrap(), 2);
+        assert!(audit(true).is_consistent());
+        // The trie still reads fine.
+        let trie = tries.get_trie_for_shard(shard_uid);
+        assert_eq!(trie.get(&head_root, &[1, 5]), Ok(Some(vec![7])));
+    }
+}
@@ -0,0 +1,128 @@
+//! Pages of the contract state of an account, with their proofs.
+//!
+//! A page is made of the first `limit` items of the contract data of an account which keys start
+//! with a prefix and come after `start_after`, if given. Its proof is the set of trie nodes and
+//! values read while iterating the trie for the page, see `ViewStateResult`. Iterating the same
+//! way over the proof alone only gives back the same page if it's exactly the one of the state at
+//! the root: an item left out, added or altered, or a node missing from the proof, makes the
+//! iteration fail or differ.
+
+use near_primitives::challenge::PartialState;
+use near_primitives::trie_key::trie_key_parsers;
+use near_primitives::types::{AccountId, StateRoot};
+
+use crate::trie::{PartialStorage, Trie};
+use crate::StorageError;
+
+/// Key of a contract data item, without the account, and its value.
+pub type StatePageItem = (Vec<u8>, Vec<u8>);
+
+/// Reads a page of the contract state of the account. Reading it from a trie recording its
+/// reads, see `Trie::recording_reads`, records the proof of the page.
+pub fn read_state_page(
+    trie: &Trie,
+    state_root: &StateRoot,
+    account_id: &AccountId,
+    prefix: &[u8],
+    start_after: Option<&[u8]>,
+    limit: usize,
+) -> Result<Vec<StatePageItem>, StorageError> {
+    let query = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, prefix);
+    let account_prefix_len = query.len() - prefix.len();
+    let start_after = start_after
+        .map(|start_after| [&query[..account_prefix_len], start_after].concat())
+        .filter(|start_after| start_after.as_slice() >= query.as_slice());
+    let mut iter = trie.iter(state_root)?;
+    iter.seek(start_after.as_ref().unwrap_or(&query))?;
+    let mut items = vec![];
+    while items.len() < limit {
+        let (key, value) = match iter.next() {
+            Some(item) => item?,
+            None => break,
+        };
+        if !key.starts_with(&query) {
+            break;
+        }
+        if Some(&key) == start_after.as_ref() {
+            continue;
+        }
+        items.push((key[account_prefix_len..].to_vec(), value));
+    }
+    Ok(items)
+}
+
+/// Whether `items` is the page read with these parameters from the state at `state_root`, given
+/// the trie nodes and values of its proof.
+pub fn verify_state_page(
+    state_root: &StateRoot,
+    account_id: &AccountId,
+    prefix: &[u8],
+    start_after: Option<&[u8]>,
+    limit: usize,
+    items: &[StatePageItem],
+    proof: Vec<Vec<u8>>,
+) -> bool {
+    let trie = Trie::from_recorded_storage(PartialStorage { nodes: PartialState(proof) });
+    match read_state_page(&trie, state_root, account_id, prefix, start_after, limit) {
+        Ok(page) => page == items,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::trie_key::TrieKey;
+
+    use super::*;
+    use crate::test_utils::{create_tries, test_populate_trie};
+    use crate::ShardUId;
+
+    #[test]
+    fn test_state_page() {
+        let tries = create_tries();
+        let shard_uid = ShardUId { version: 0, shard_id: 0 };
+        let alice: AccountId = "alice".parse().unwrap();
+        let bob: AccountId = "bob".parse().unwrap();
+        let mut changes = vec![];
+        let accounts =
+            [(&alice, vec![&b"a1"[..], b"a2", b"b1", b"b2", b"c"]), (&bob, vec![&b"a0"[..]])];
+        for (account_id, keys) in accounts {
+            for key in keys {
+                let trie_key =
+                    TrieKey::ContractData { account_id: account_id.clone(), key: key.to_vec() };
+                changes.push((trie_key.to_vec(), Some(key.to_vec())));
+            }
+        }
+        let root = test_populate_trie(&tries, &Trie::empty_root(), shard_uid, changes);
+        let trie = tries.get_trie_for_shard(shard_uid);
+        let keys = |start_after: Option<&[u8]>, limit| -> Vec<Vec<u8>> {
+            read_state_page(&trie, &root, &alice, b"", start_after, limit)
+                .unwrap()
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect()
+        };
+        assert_eq!(keys(None, 2), [b"a1".to_vec(), b"a2".to_vec()]);
+        assert_eq!(keys(Some(&b"a2"[..]), 2), [b"b1".to_vec(), b"b2".to_vec()]);
+        assert_eq!(keys(Some(&b"a"[..]), 1), [b"a1".to_vec()]);
+        assert_eq!(keys(Some(&b"b2"[..]), 10), [b"c".to_vec()]);
+        assert!(keys(Some(&b"c"[..]), 10).is_empty());
+        let page = read_state_page(&trie, &root, &alice, b"b", None, 10).unwrap();
+        assert_eq!(page, [(b"b1".to_vec(), b"b1".to_vec()), (b"b2".to_vec(), b"b2".to_vec())]);
+
+        let recording_trie = trie.recording_reads();
+        let page =
+            read_state_page(&recording_trie, &root, &alice, b"", Some(&b"a1"[..]), 2).unwrap();
+        let proof = recording_trie.recorded_storage().unwrap().nodes.0;
+        let verify = |items: &[StatePageItem], proof: Vec<Vec<u8>>| {
+            verify_state_page(&root, &alice, b"", Some(&b"a1"[..]), 2, items, proof)
+        };
+        assert!(verify(&page, proof.clone()));
+        // Leaving out or altering an item, or a node of the proof, is detected.
+        assert!(!verify(&page[..1], proof.clone()));
+        let mut altered = page.clone();
+        altered[1].1 = b"b3".to_vec();
+        assert!(!verify(&altered, proof.clone()));
+        assert!(!verify(&page, proof[1..].to_vec()));
+    }
+}
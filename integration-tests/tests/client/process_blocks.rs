@@ -354,6 +354,7 @@ fn receive_network_block() {
             block_merkle_tree.insert(last_block.header.hash);
             let next_block_ordinal = last_block.header.block_ordinal.unwrap() + 1;
             let block = Block::produce(
+                PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 &last_block.header.clone().into(),
@@ -430,6 +431,7 @@ fn produce_block_with_approvals() {
             block_merkle_tree.insert(last_block.header.hash);
             let next_block_ordinal = last_block.header.block_ordinal.unwrap() + 1;
             let block = Block::produce(
+                PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 &last_block.header.clone().into(),
@@ -619,6 +621,7 @@ fn invalid_blocks_common(is_requested: bool) {
             block_merkle_tree.insert(last_block.header.hash);
             let next_block_ordinal = last_block.header.block_ordinal.unwrap() + 1;
             let valid_block = Block::produce(
+                PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 PROTOCOL_VERSION,
                 &last_block.header.clone().into(),
@@ -973,6 +976,8 @@ fn client_sync_headers() {
             }],
             sent_bytes_per_sec: 0,
             received_bytes_per_sec: 0,
+            peer_transfer_stats: vec![],
+            peer_scores: vec![],
             known_producers: vec![],
             peer_counter: 0,
         }));
@@ -1972,6 +1977,50 @@ fn test_block_merkle_proof_same_hash() {
     assert!(proof.is_empty());
 }
 
+#[test]
+fn test_receipt_inclusion_proof() {
+    let genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+    let mut env = TestEnv::builder(ChainGenesis::test())
+        .runtime_adapters(create_nightshade_runtimes(&genesis, 1))
+        .build();
+    let genesis_hash = *env.clients[0].chain.genesis().hash();
+    let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+    let tx = SignedTransaction::send_money(
+        1,
+        "test0".parse().unwrap(),
+        "test1".parse().unwrap(),
+        &signer,
+        100,
+        genesis_hash,
+    );
+    let tx_hash = tx.get_hash();
+    env.clients[0].process_tx(tx, false, false);
+    for i in 1..5 {
+        env.produce_block(0, i);
+    }
+
+    let outcome = env.clients[0].chain.get_execution_outcome(&tx_hash).unwrap();
+    let receipt_id = outcome.outcome_with_id.outcome.receipt_ids[0];
+    // The receipt is received by the block in which it is executed.
+    let block_hash = env.clients[0].chain.get_execution_outcome(&receipt_id).unwrap().block_hash;
+    let proof = env.clients[0]
+        .chain
+        .get_receipt_inclusion_proof(&receipt_id, &block_hash)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proof.block_header.hash, block_hash);
+    assert!(proof.verify());
+
+    let mut forged_proof = proof.clone();
+    forged_proof.receipts[0].receiver_id = "test0".parse().unwrap();
+    assert!(!forged_proof.verify());
+    assert!(env.clients[0]
+        .chain
+        .get_receipt_inclusion_proof(&receipt_id, &outcome.block_hash)
+        .unwrap()
+        .is_none());
+}
+
 #[test]
 fn test_data_reset_before_state_sync() {
     let mut genesis = Genesis::test(vec!["test0".parse().unwrap()], 1);
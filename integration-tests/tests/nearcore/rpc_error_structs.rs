@@ -17,10 +17,10 @@ use near_primitives::serialize::to_base64;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::BlockId;
 
-// Queries json-rpc block that doesn't exists
+// Queries json-rpc block above the head of the node
 // Checks if the struct is expected and contains the proper data
 #[test]
-fn test_block_unknown_block_error() {
+fn test_block_not_processed_yet_error() {
     init_integration_logger();
 
     let cluster = NodeCluster::new(4, |index| format!("block_unknown{}", index))
@@ -54,7 +54,7 @@ fn test_block_unknown_block_error() {
                                         );
                                         assert_eq!(
                                             error_json["cause"]["name"],
-                                            serde_json::json!("UNKNOWN_BLOCK")
+                                            serde_json::json!("NOT_PROCESSED_YET")
                                         );
                                         System::current().stop();
                                     })
@@ -90,6 +90,9 @@ fn outcome_view_to_hashes(outcome: &ExecutionOutcomeView) -> Vec<CryptoHash> {
     for log in outcome.logs.iter() {
         result.push(hash(log.as_bytes()));
     }
+    for event in outcome.events.iter() {
+        result.push(hash(&event.try_to_vec().expect("Failed to serialize")));
+    }
     result
 }
 
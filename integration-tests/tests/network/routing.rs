@@ -1,4 +1,8 @@
 use crate::runner::*;
+#[cfg(feature = "test_features")]
+use near_network::chaos::{Faults, NetworkChaos, Selector};
+#[cfg(feature = "test_features")]
+use std::sync::atomic::Ordering;
 
 #[test]
 fn simple() {
@@ -321,3 +325,29 @@ fn test_dropping_routing_messages() {
 
     start_test(runner);
 }
+
+/// Spawn 2 nodes and drop the pings between them with the faults injected in the network, then
+/// stop dropping them.
+#[test]
+#[cfg(feature = "test_features")]
+fn test_chaos_drop_pings() {
+    let chaos = NetworkChaos::new(0);
+    let pings = Selector { message_type: Some("Ping".to_string()), ..Default::default() };
+    chaos.set_faults(pings.clone(), Faults::drop_all());
+    let mut runner = Runner::new(2, 2).chaos(chaos.clone());
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+    runner.push(Action::PingTo(0, 0, 1));
+    runner.push(Action::Wait(500));
+    runner.push(Action::CheckPingPong(1, vec![], vec![]));
+    runner.push_action(Box::new(move |_info, flag, _ctx, _runner| {
+        chaos.remove_faults(&pings);
+        flag.store(true, Ordering::Relaxed);
+    }));
+    runner.push(Action::PingTo(0, 1, 1));
+    runner.push(Action::CheckPingPong(1, vec![(1, 0, None)], vec![]));
+    runner.push(Action::CheckPingPong(0, vec![], vec![(1, 1, None)]));
+
+    start_test(runner);
+}
@@ -22,6 +22,8 @@ use near_network::test_utils::{
     GetInfo, StopSignal, WaitOrTimeoutActor,
 };
 
+#[cfg(feature = "test_features")]
+use near_network::chaos::NetworkChaos;
 use near_network::routing::start_routing_table_actor;
 #[cfg(feature = "test_features")]
 use near_network::types::SetAdvOptions;
@@ -45,11 +47,13 @@ pub type ActionFn = Box<
 >;
 
 /// Sets up a node with a valid Client, Peer
+#[cfg_attr(not(feature = "test_features"), allow(clippy::let_and_return))]
 pub fn setup_network_node(
     account_id: AccountId,
     validators: Vec<AccountId>,
     genesis_time: DateTime<Utc>,
     config: NetworkConfig,
+    #[cfg(feature = "test_features")] chaos: Option<NetworkChaos>,
 ) -> Addr<PeerManagerActor> {
     let store = create_test_store();
 
@@ -106,14 +110,20 @@ pub fn setup_network_node(
         let routing_table_addr =
             start_routing_table_actor(PeerId::new(config.public_key.clone()), store.clone());
 
-        PeerManagerActor::new(
+        let peer_manager = PeerManagerActor::new(
             store.clone(),
             config,
             client_actor.recipient(),
             view_client_actor.recipient(),
             routing_table_addr,
         )
-        .unwrap()
+        .unwrap();
+        #[cfg(feature = "test_features")]
+        let peer_manager = match chaos {
+            Some(chaos) => peer_manager.with_chaos(chaos),
+            None => peer_manager,
+        };
+        peer_manager
     });
 
     peer_manager
@@ -498,6 +508,8 @@ pub struct Runner {
     ports: Option<Vec<u16>>,
     validators: Option<Vec<AccountId>>,
     genesis_time: Option<DateTime<Utc>>,
+    #[cfg(feature = "test_features")]
+    chaos: Option<NetworkChaos>,
 }
 
 impl Runner {
@@ -512,6 +524,8 @@ impl Runner {
             ports: None,
             validators: None,
             genesis_time: None,
+            #[cfg(feature = "test_features")]
+            chaos: None,
         }
     }
 
@@ -598,6 +612,13 @@ impl Runner {
         self
     }
 
+    /// Inject the faults of `chaos` in the messages between the nodes.
+    #[cfg(feature = "test_features")]
+    pub fn chaos(mut self, chaos: NetworkChaos) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
     /// Add an action to be executed by the Runner. Actions are executed sequentially.
     /// Each action is executed after the previous action succeed.
     pub fn push(&mut self, action: Action) {
@@ -666,6 +687,8 @@ impl Runner {
             self.validators.clone().unwrap(),
             self.genesis_time.clone().unwrap(),
             network_config,
+            #[cfg(feature = "test_features")]
+            self.chaos.clone(),
         )
     }
 
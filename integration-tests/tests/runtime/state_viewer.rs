@@ -151,6 +151,40 @@ fn test_view_state() {
     );
 }
 
+#[test]
+fn test_view_state_page() {
+    let (_, tries, root) = get_runtime_and_trie();
+    let shard_uid = TEST_SHARD_UID;
+    let mut state_update = tries.new_trie_update(shard_uid, root);
+    for key in [&b"a1"[..], b"a2", b"b1"] {
+        state_update.set(
+            TrieKey::ContractData { account_id: alice_account(), key: key.to_vec() },
+            key.to_vec(),
+        );
+    }
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().0;
+    let (db_changes, new_root) = tries.apply_all(&trie_changes, shard_uid).unwrap();
+    db_changes.commit().unwrap();
+
+    let trie_viewer = TrieViewer::default();
+    let view_state_page = |start_after: Option<&[u8]>| {
+        let state_update = tries.new_trie_update(shard_uid, new_root);
+        trie_viewer.view_state_page(&state_update, &alice_account(), b"", start_after, 2).unwrap()
+    };
+    let first = view_state_page(None);
+    assert_eq!(first.values.len(), 2);
+    assert_eq!(first.continuation_token, Some(b"a2".to_vec()));
+    // The same cursor reads the same page.
+    let second = view_state_page(first.continuation_token.as_deref());
+    assert_eq!(second, view_state_page(Some(&b"a2"[..])));
+    assert_eq!(
+        second.values,
+        [StateItem { key: "YjE=".to_string(), value: "YjE=".to_string(), proof: vec![] }]
+    );
+    assert_eq!(second.continuation_token, None);
+}
+
 #[test]
 fn test_view_state_too_large() {
     let (_, tries, root) = get_runtime_and_trie();
@@ -0,0 +1,85 @@
+//! Cargo features of `nearcore`, registered in [`near_primitives::features`] when the node
+//! starts. They enable the features of the same name of the crates below, whose own lists are
+//! merged into the registry.
+
+use near_primitives::features::BuildFeature;
+
+pub const BUILD_FEATURES: &[BuildFeature] = &[
+    #[cfg(feature = "performance_stats")]
+    BuildFeature {
+        name: "performance_stats",
+        description: "Collect the time spent by the actors handling every message",
+    },
+    #[cfg(feature = "memory_stats")]
+    BuildFeature { name: "memory_stats", description: "Track the memory allocated by threads" },
+    #[cfg(feature = "c_memory_stats")]
+    BuildFeature {
+        name: "c_memory_stats",
+        description: "Report the memory allocated by the C allocator",
+    },
+    #[cfg(feature = "test_features")]
+    BuildFeature {
+        name: "test_features",
+        description: "Adversarial controls of the node, for tests only",
+    },
+    #[cfg(feature = "expensive_tests")]
+    BuildFeature { name: "expensive_tests", description: "Build the expensive tests" },
+    #[cfg(feature = "no_cache")]
+    BuildFeature { name: "no_cache", description: "Disable the caches of the storage and runtime" },
+    #[cfg(feature = "delay_detector")]
+    BuildFeature {
+        name: "delay_detector",
+        description: "Log the processing of blocks and chunks taking too long",
+    },
+    #[cfg(feature = "rosetta_rpc")]
+    BuildFeature { name: "rosetta_rpc", description: "Serve the Rosetta RPC" },
+    #[cfg(feature = "json_rpc")]
+    BuildFeature { name: "json_rpc", description: "Serve the JSON RPC" },
+    #[cfg(feature = "force_wasmer2")]
+    BuildFeature {
+        name: "force_wasmer2",
+        description: "Run contracts with wasmer 2 whatever the protocol version",
+    },
+    #[cfg(feature = "force_wasmer0")]
+    BuildFeature {
+        name: "force_wasmer0",
+        description: "Run contracts with wasmer 0 whatever the protocol version",
+    },
+    #[cfg(feature = "force_wasmtime")]
+    BuildFeature {
+        name: "force_wasmtime",
+        description: "Run contracts with wasmtime whatever the protocol version",
+    },
+    #[cfg(feature = "no_cpu_compatibility_checks")]
+    BuildFeature {
+        name: "no_cpu_compatibility_checks",
+        description: "Skip the check of the CPU features needed by the wasm compilers",
+    },
+    #[cfg(feature = "sandbox")]
+    BuildFeature { name: "sandbox", description: "Sandbox RPC method to patch the state" },
+    #[cfg(feature = "protocol_feature_alt_bn128")]
+    BuildFeature {
+        name: "protocol_feature_alt_bn128",
+        description: "Compile the AltBn128 protocol feature",
+    },
+    #[cfg(feature = "protocol_feature_chunk_only_producers")]
+    BuildFeature {
+        name: "protocol_feature_chunk_only_producers",
+        description: "Compile the ChunkOnlyProducers protocol feature",
+    },
+    #[cfg(feature = "protocol_feature_routing_exchange_algorithm")]
+    BuildFeature {
+        name: "protocol_feature_routing_exchange_algorithm",
+        description: "Compile the RoutingExchangeAlgorithm protocol feature",
+    },
+    #[cfg(feature = "protocol_feature_access_key_nonce_for_implicit_accounts")]
+    BuildFeature {
+        name: "protocol_feature_access_key_nonce_for_implicit_accounts",
+        description: "Compile the AccessKeyNonceForImplicitAccounts protocol feature",
+    },
+    #[cfg(feature = "protocol_feature_emit_event")]
+    BuildFeature {
+        name: "protocol_feature_emit_event",
+        description: "Compile the EmitEvent protocol feature",
+    },
+];
@@ -1,7 +1,8 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -10,16 +11,19 @@ use near_primitives::time::Clock;
 use num_rational::Rational;
 use serde::{Deserialize, Serialize};
 use tokio::io::AsyncWriteExt;
-use tracing::info;
+use tracing::{info, warn};
 
 use near_chain_configs::{
-    get_initial_supply, ClientConfig, Genesis, GenesisConfig, LogSummaryStyle,
+    get_initial_supply, CircuitBreakerConfig, ClientConfig, DiskMonitorConfig,
+    FailedReceiptsConfig, Genesis, GenesisConfig, LivenessBeaconConfig, LogSummaryStyle,
+    ProtocolUpgradeConfig, StateQuarantineConfig, TransactionPoolConfig,
+    CHUNK_REQUEST_RETRY_BUDGET,
 };
 use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
 #[cfg(feature = "json_rpc")]
 use near_jsonrpc::RpcConfig;
 use near_network::test_utils::open_port;
-use near_network_primitives::types::{NetworkConfig, ROUTED_MESSAGE_TTL};
+use near_network_primitives::types::{BootNodeHost, NetworkConfig, PeerRanges, ROUTED_MESSAGE_TTL};
 use near_network_primitives::utils::blacklist_from_iter;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::hash::CryptoHash;
@@ -31,11 +35,19 @@ use near_primitives::types::{
 };
 use near_primitives::utils::{generate_random_string, get_num_seats_per_shard};
 use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
-use near_primitives::version::PROTOCOL_VERSION;
+use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 #[cfg(feature = "rosetta_rpc")]
 use near_rosetta_rpc::RosettaRpcConfig;
+use near_store::StoreConfig;
 use near_telemetry::TelemetryConfig;
 
+use crate::config_schema::{parse_config, ConfigMode, CONFIG_VERSION};
+use crate::epoch_hooks::EpochHooksConfig;
+use crate::head_monitor::HeadMonitorConfig;
+use crate::memory_budget::MemoryPreset;
+use crate::signing_policy::{approve_validator_key, SigningPolicyConfig};
+use crate::webhooks::WebhooksConfig;
+
 /// Initial balance used in tests.
 pub const TESTING_INIT_BALANCE: Balance = 1_000_000_000 * NEAR_BASE;
 
@@ -189,6 +201,19 @@ fn default_ttl_account_id_router() -> Duration {
 fn default_peer_stats_period() -> Duration {
     Duration::from_secs(5)
 }
+/// Time for the score of a misbehaving peer to decay by half.
+fn default_max_peers_fraction_per_asn() -> f64 {
+    0.5
+}
+fn default_peer_score_half_life() -> Duration {
+    Duration::from_secs(600)
+}
+fn default_peer_score_demote_threshold() -> f64 {
+    50.0
+}
+fn default_peer_score_ban_threshold() -> f64 {
+    100.0
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Network {
@@ -197,7 +222,8 @@ pub struct Network {
     /// Address to advertise to peers for them to connect.
     /// If empty, will use the same port as the addr, and will introspect on the listener.
     pub external_address: String,
-    /// Comma separated list of nodes to connect to.
+    /// Comma separated list of nodes to connect to, as `peer_id@ip:port`, or `peer_id@host:port`
+    /// to resolve the host name every time the node is dialed.
     pub boot_nodes: String,
     /// Maximum number of active peers. Hard limit.
     #[serde(default = "default_max_num_peers")]
@@ -234,12 +260,89 @@ pub struct Network {
     /// It can be IP:Port or IP (to blacklist all connections coming from this address).
     #[serde(default)]
     pub blacklist: Vec<String>,
+    /// File with the peer ids which are the only ones allowed to connect, one per line.
+    /// Relative to the home directory. Changes to the file are picked up without a restart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_allowlist_file: Option<String>,
+    /// Ranges of IPs in CIDR notation, e.g. `10.0.0.0/8`. If not empty, only the peers in these
+    /// ranges are accepted and dialed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_ranges: Vec<String>,
+    /// Ranges of IPs in CIDR notation of the peers never accepted nor dialed, even in
+    /// `allowed_ranges`. Both lists are read again from the file on SIGHUP, or on a request to the
+    /// `/admin/network/reload_peer_ranges` endpoint of the RPC, and the peers no longer allowed
+    /// are disconnected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_ranges: Vec<String>,
+    /// CSV file of the AS numbers of the IP ranges, in the format of the MaxMind GeoLite2 ASN CSV
+    /// files, relative to the home directory. If set, at most `max_peers_fraction_per_asn` of
+    /// `max_num_peers` are peers of any single autonomous system, to reduce the risk of being
+    /// eclipsed by a cloud provider hosting most of the peers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asn_database_file: Option<String>,
+    #[serde(default = "default_max_peers_fraction_per_asn")]
+    pub max_peers_fraction_per_asn: f64,
     /// Time to persist Accounts Id in the router without removing them in seconds.
     #[serde(default = "default_ttl_account_id_router")]
     pub ttl_account_id_router: Duration,
     /// Period to check on peer status
     #[serde(default = "default_peer_stats_period")]
     pub peer_stats_period: Duration,
+    /// Fraction of the received messages, from 0 to 1, processed in a `peer_message` span.
+    #[serde(default)]
+    pub message_span_sample_rate: f64,
+    /// Bytes per second which may be sent to all the peers together. Unlimited if not set.
+    /// Once reached, the messages are queued and the transactions and sync messages dropped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_outbound_bytes_per_sec: Option<u64>,
+    /// Bytes per second which may be sent to every peer. Unlimited if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_outbound_bytes_per_sec_per_peer: Option<u64>,
+    /// Difficulty of the proof of work the inbound peers solve before their handshake is
+    /// processed, making flooding the node with connections costly: every additional bit doubles
+    /// the work, which takes a peer a fraction of a second at 20. Disabled if 0.
+    #[serde(default)]
+    pub handshake_proof_of_work_difficulty: u8,
+    /// Size in bytes from which the messages sent to the peers, such as blocks, chunks and state
+    /// parts, are compressed with deflate, if the peer accepts it. Not compressed if not set. Only
+    /// for networks where all the peers support compression: the others count the offer to
+    /// compress as an invalid message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression_threshold_bytes: Option<usize>,
+    /// Peers are scored on their misbehaviors: invalid messages count 20, timeouts and excessive
+    /// bandwidth 10, stale data 5. The score halves every `peer_score_half_life`.
+    #[serde(default = "default_peer_score_half_life")]
+    pub peer_score_half_life: Duration,
+    /// Score above which a peer isn't used to sync and is disconnected first.
+    #[serde(default = "default_peer_score_demote_threshold")]
+    pub peer_score_demote_threshold: f64,
+    /// Score above which a peer is banned for `ban_window`.
+    #[serde(default = "default_peer_score_ban_threshold")]
+    pub peer_score_ban_threshold: f64,
+    /// Bytes per second above which receiving from a peer counts against its score, checked every
+    /// `peer_stats_period`. Not checked if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_max_received_bytes_per_sec: Option<u64>,
+    /// IP which the outbound connections to peers are made from, for hosts with several addresses.
+    /// Peers see this IP as the address of the node. Chosen by the OS if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound_source_ip: Option<String>,
+    /// Source IPs of the outbound connections to given peers, by IP:Port or IP, e.g.
+    /// `{"10.8.0.2": "10.8.0.1"}` to reach a peer through a VPN.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outbound_source_ip_overrides: BTreeMap<String, String>,
+    /// SOCKS5 proxy, e.g. `socks5://127.0.0.1:9050` for Tor, which every outbound connection goes
+    /// through. The host names of the boot nodes are then resolved by the proxy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Whether to ask the router of the local network to forward the port of `addr` with NAT-PMP,
+    /// renewing the mapping periodically, and to advertise the port it forwards to the peers.
+    /// Ignored if `external_address` is set.
+    #[serde(default)]
+    pub port_mapping: bool,
+    /// IPv4 address of the router asked for the port mapping, the default gateway if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port_mapping_gateway: Option<String>,
 }
 
 impl Default for Network {
@@ -260,8 +363,27 @@ impl Default for Network {
             skip_sync_wait: false,
             ban_window: Duration::from_secs(3 * 60 * 60),
             blacklist: vec![],
+            peer_allowlist_file: None,
+            allowed_ranges: vec![],
+            denied_ranges: vec![],
+            asn_database_file: None,
+            max_peers_fraction_per_asn: default_max_peers_fraction_per_asn(),
             ttl_account_id_router: default_ttl_account_id_router(),
             peer_stats_period: default_peer_stats_period(),
+            message_span_sample_rate: 0.0,
+            max_outbound_bytes_per_sec: None,
+            max_outbound_bytes_per_sec_per_peer: None,
+            handshake_proof_of_work_difficulty: 0,
+            compression_threshold_bytes: None,
+            peer_score_half_life: default_peer_score_half_life(),
+            peer_score_demote_threshold: default_peer_score_demote_threshold(),
+            peer_score_ban_threshold: default_peer_score_ban_threshold(),
+            peer_max_received_bytes_per_sec: None,
+            outbound_source_ip: None,
+            outbound_source_ip_overrides: BTreeMap::new(),
+            proxy: None,
+            port_mapping: false,
+            port_mapping_gateway: None,
         }
     }
 }
@@ -271,6 +393,14 @@ fn default_reduce_wait_for_missing_block() -> Duration {
     Duration::from_millis(REDUCE_DELAY_FOR_MISSING_BLOCKS)
 }
 
+fn default_chunk_request_retry_budget() -> u32 {
+    CHUNK_REQUEST_RETRY_BUDGET
+}
+
+fn default_max_block_future_drift() -> Duration {
+    Duration::from_secs(120)
+}
+
 fn default_header_sync_initial_timeout() -> Duration {
     Duration::from_secs(10)
 }
@@ -346,6 +476,14 @@ pub struct Consensus {
     pub catchup_step_period: Duration,
     /// Time between checking to re-request chunks.
     pub chunk_request_retry_period: Duration,
+    /// Number of chunk request retries after which the full chunk is requested from archival peers,
+    /// once the other peers have been asked for it.
+    #[serde(default = "default_chunk_request_retry_budget")]
+    pub chunk_request_retry_budget: u32,
+    /// Blocks with a timestamp further ahead of the local clock, expected to be synchronized
+    /// with NTP, are refused. Must stay well above the block production delay.
+    #[serde(default = "default_max_block_future_drift")]
+    pub max_block_future_drift: Duration,
     /// How much time to wait after initial header sync
     #[serde(default = "default_header_sync_initial_timeout")]
     pub header_sync_initial_timeout: Duration,
@@ -387,6 +525,8 @@ impl Default for Consensus {
             block_header_fetch_horizon: BLOCK_HEADER_FETCH_HORIZON,
             catchup_step_period: Duration::from_millis(CATCHUP_STEP_PERIOD),
             chunk_request_retry_period: Duration::from_millis(CHUNK_REQUEST_RETRY_PERIOD),
+            chunk_request_retry_budget: default_chunk_request_retry_budget(),
+            max_block_future_drift: default_max_block_future_drift(),
             header_sync_initial_timeout: default_header_sync_initial_timeout(),
             header_sync_progress_timeout: default_header_sync_progress_timeout(),
             header_sync_stall_ban_timeout: default_header_sync_stall_ban_timeout(),
@@ -403,6 +543,8 @@ impl Default for Consensus {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(default)]
 pub struct Config {
+    /// Version of the config schema, see `config_schema`.
+    pub config_version: u32,
     pub genesis_file: String,
     pub genesis_records_file: Option<String>,
     pub validator_key_file: String,
@@ -432,11 +574,85 @@ pub struct Config {
     /// If set, overrides value in genesis configuration.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_gas_burnt_view: Option<Gas>,
+    /// Endpoints notified about transactions matching their filters.
+    #[serde(skip_serializing_if = "WebhooksConfig::is_empty")]
+    pub webhooks: WebhooksConfig,
+    /// If set, validator pauses signing when it detects it is on a minority fork, its clock is
+    /// skewed or its chunks keep being rejected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// If set, shards whose state roots keep disagreeing with the chain are quarantined and
+    /// diagnostics about the mismatching chunks are written to `diagnostics_dir`, relative to
+    /// the home directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_quarantine: Option<StateQuarantineConfig>,
+    /// If set, more garbage is collected and the database compacted when free disk space runs
+    /// low, and the node stops before the database runs out of space.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk_monitor: Option<DiskMonitorConfig>,
+    /// If set and the node is a validator, heartbeats with its head and the height of its last
+    /// approval, signed with its validator key, are posted to the `endpoints` every `interval`,
+    /// for uptime monitoring services.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liveness_beacon: Option<LivenessBeaconConfig>,
+    /// If set, produced blocks only vote for the protocol version of the binary once the operator
+    /// acknowledged it, here or through the admin endpoint of the JSON RPC, or from a given time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_upgrade: Option<ProtocolUpgradeConfig>,
+    /// Limits of the transaction pool of every shard, independent of those of the other shards.
+    pub transaction_pool: TransactionPoolConfig,
+    /// Kinds of the receipts which execution failed kept for the `EXPERIMENTAL_failed_receipts`
+    /// method of the JSON RPC, and for how many epochs.
+    pub failed_receipts: FailedReceiptsConfig,
+    /// Whether to count the calls made with every function call access key, per contract method,
+    /// for the `EXPERIMENTAL_access_key_usage` method of the JSON RPC.
+    pub save_access_key_usage: bool,
+    /// Whether to record the heights and hashes of the contract code deployed on every account,
+    /// for the `EXPERIMENTAL_contract_history` method of the JSON RPC.
+    pub save_contract_history: bool,
+    /// Whether to record the transaction size, action type and receipt locality statistics of
+    /// every block, for the `EXPERIMENTAL_block_transaction_stats` method of the JSON RPC.
+    pub save_block_transaction_stats: bool,
+    /// Whether to record the number of blocks, transactions, signers, new accounts and contract
+    /// deploys and the gas used of every epoch, for the `EXPERIMENTAL_epoch_stats` method of the
+    /// JSON RPC.
+    pub save_epoch_chain_stats: bool,
+    /// If set, all the history before the epoch preceding the epoch of this block is garbage
+    /// collected once the block is final, instead of keeping the last epochs, leaving only what's
+    /// needed to validate the next blocks and answer queries on the recent ones. Meant for nodes
+    /// of dapp developers on modest hardware, with `gc_blocks_limit` raised to prune a long
+    /// history faster. Ignored by archival nodes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pruning_checkpoint: Option<CryptoHash>,
+    /// If set, every new chunk is also applied with this protocol version and the differences
+    /// with the actual result are logged and counted in metrics. Meant for canary nodes ahead
+    /// of a protocol upgrade, the version must be supported by the binary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shadow_protocol_version: Option<ProtocolVersion>,
+    /// If set, the head of the node is periodically cross-checked against the light client
+    /// blocks of independent JSON RPC endpoints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_monitor: Option<HeadMonitorConfig>,
+    /// If set, commands are run and webhooks called when a new epoch starts and when the
+    /// validator of the node joins or leaves the validator set or changes shards.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch_hooks: Option<EpochHooksConfig>,
+    /// If set, the node only starts with its validator key once `threshold` of the `approvers`
+    /// commands approved its use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validator_signing_policy: Option<SigningPolicyConfig>,
+    /// Sizes of the database caches, for the memory of the machine. Ignored if `store` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_preset: Option<MemoryPreset>,
+    /// Tuning of the database caches and memtables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<StoreConfig>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
+            config_version: CONFIG_VERSION,
             genesis_file: GENESIS_CONFIG_FILENAME.to_string(),
             genesis_records_file: None,
             validator_key_file: VALIDATOR_KEY_FILE.to_string(),
@@ -458,6 +674,25 @@ impl Default for Config {
             view_client_throttle_period: default_view_client_throttle_period(),
             trie_viewer_state_size_limit: default_trie_viewer_state_size_limit(),
             max_gas_burnt_view: None,
+            webhooks: WebhooksConfig::default(),
+            circuit_breaker: None,
+            state_quarantine: None,
+            disk_monitor: None,
+            liveness_beacon: None,
+            protocol_upgrade: None,
+            transaction_pool: TransactionPoolConfig::default(),
+            failed_receipts: FailedReceiptsConfig::default(),
+            save_access_key_usage: false,
+            save_contract_history: false,
+            save_block_transaction_stats: false,
+            save_epoch_chain_stats: false,
+            pruning_checkpoint: None,
+            shadow_protocol_version: None,
+            head_monitor: None,
+            epoch_hooks: None,
+            validator_signing_policy: None,
+            memory_preset: None,
+            store: None,
         }
     }
 }
@@ -498,7 +733,15 @@ impl Config {
 
 impl From<&str> for Config {
     fn from(content: &str) -> Self {
-        serde_json::from_str(content).expect("Failed to deserialize config")
+        let parsed =
+            parse_config(content, ConfigMode::Lenient).expect("Failed to deserialize config");
+        if parsed.is_migrated() {
+            info!(target: "near", "Migrated config from version {} to {}, run `neard config migrate` to update the file", parsed.version, CONFIG_VERSION);
+        }
+        for field in parsed.unknown_fields.iter() {
+            warn!(target: "near", "Ignoring unknown config field `{}`", field);
+        }
+        parsed.config
     }
 }
 
@@ -611,6 +854,10 @@ pub struct NearConfig {
     #[cfg(feature = "rosetta_rpc")]
     pub rosetta_rpc_config: Option<RosettaRpcConfig>,
     pub telemetry_config: TelemetryConfig,
+    pub webhooks_config: WebhooksConfig,
+    pub head_monitor_config: Option<HeadMonitorConfig>,
+    pub epoch_hooks_config: Option<EpochHooksConfig>,
+    pub store_config: StoreConfig,
     pub genesis: Genesis,
     pub validator_signer: Option<Arc<dyn ValidatorSigner>>,
 }
@@ -657,6 +904,8 @@ impl NearConfig {
                 block_header_fetch_horizon: config.consensus.block_header_fetch_horizon,
                 catchup_step_period: config.consensus.catchup_step_period,
                 chunk_request_retry_period: config.consensus.chunk_request_retry_period,
+                chunk_request_retry_budget: config.consensus.chunk_request_retry_budget,
+                max_block_future_drift: config.consensus.max_block_future_drift,
                 doosmslug_step_period: config.consensus.doomslug_step_period,
                 tracked_accounts: config.tracked_accounts,
                 tracked_shards: config.tracked_shards,
@@ -668,6 +917,18 @@ impl NearConfig {
                 view_client_throttle_period: config.view_client_throttle_period,
                 trie_viewer_state_size_limit: config.trie_viewer_state_size_limit,
                 max_gas_burnt_view: config.max_gas_burnt_view,
+                circuit_breaker: config.circuit_breaker,
+                state_quarantine: config.state_quarantine,
+                disk_monitor: config.disk_monitor,
+                liveness_beacon: config.liveness_beacon,
+                protocol_upgrade: config.protocol_upgrade,
+                transaction_pool: config.transaction_pool,
+                failed_receipts: config.failed_receipts,
+                save_access_key_usage: config.save_access_key_usage,
+                save_contract_history: config.save_contract_history,
+                save_block_transaction_stats: config.save_block_transaction_stats,
+                save_epoch_chain_stats: config.save_epoch_chain_stats,
+                pruning_checkpoint: config.pruning_checkpoint,
             },
             network_config: NetworkConfig {
                 public_key: network_key_pair.public_key,
@@ -678,6 +939,33 @@ impl NearConfig {
                 } else {
                     Some(config.network.addr.parse().unwrap())
                 },
+                external_addr: if config.network.external_address.is_empty() {
+                    None
+                } else {
+                    Some(
+                        config
+                            .network
+                            .external_address
+                            .parse()
+                            .expect("Failed to parse external_address"),
+                    )
+                },
+                outbound_source_ip: config
+                    .network
+                    .outbound_source_ip
+                    .map(|ip| ip.parse().expect("Failed to parse outbound_source_ip")),
+                outbound_source_ip_overrides: config
+                    .network
+                    .outbound_source_ip_overrides
+                    .into_iter()
+                    .map(|(peer, ip)| {
+                        (
+                            peer.parse()
+                                .expect("Failed to parse outbound_source_ip_overrides peer"),
+                            ip.parse().expect("Failed to parse outbound_source_ip_overrides IP"),
+                        )
+                    })
+                    .collect(),
                 boot_nodes: if config.network.boot_nodes.is_empty() {
                     vec![]
                 } else {
@@ -685,9 +973,28 @@ impl NearConfig {
                         .network
                         .boot_nodes
                         .split(',')
+                        .filter(|chunk| chunk.parse::<BootNodeHost>().is_err())
                         .map(|chunk| chunk.try_into().expect("Failed to parse PeerInfo"))
                         .collect()
                 },
+                boot_node_hosts: config
+                    .network
+                    .boot_nodes
+                    .split(',')
+                    .filter_map(|chunk| chunk.parse().ok())
+                    .collect(),
+                proxy: config.network.proxy.as_ref().map(|proxy| {
+                    proxy
+                        .strip_prefix("socks5://")
+                        .unwrap_or(proxy)
+                        .parse()
+                        .expect("Failed to parse proxy")
+                }),
+                port_mapping: config.network.port_mapping,
+                port_mapping_gateway: config
+                    .network
+                    .port_mapping_gateway
+                    .map(|ip| ip.parse().expect("Failed to parse port_mapping_gateway")),
                 handshake_timeout: config.network.handshake_timeout,
                 reconnect_delay: config.network.reconnect_delay,
                 bootstrap_peers_period: Duration::from_secs(60),
@@ -710,10 +1017,38 @@ impl NearConfig {
                 highest_peer_horizon: HIGHEST_PEER_HORIZON,
                 push_info_period: Duration::from_millis(100),
                 blacklist: blacklist_from_iter(config.network.blacklist),
+                peer_allowlist_file: config.network.peer_allowlist_file.map(PathBuf::from),
+                peer_ranges: PeerRanges::parse(
+                    &config.network.allowed_ranges,
+                    &config.network.denied_ranges,
+                )
+                .unwrap_or_else(|err| panic!("Failed to parse the ranges of the peers: {}", err)),
+                peer_ranges_loader: None,
+                asn_database_file: config.network.asn_database_file.map(PathBuf::from),
+                max_peers_fraction_per_asn: config.network.max_peers_fraction_per_asn,
+                message_span_sample_rate: config.network.message_span_sample_rate,
+                max_outbound_bytes_per_sec: config.network.max_outbound_bytes_per_sec,
+                max_outbound_bytes_per_sec_per_peer: config
+                    .network
+                    .max_outbound_bytes_per_sec_per_peer,
+                handshake_proof_of_work_difficulty: config
+                    .network
+                    .handshake_proof_of_work_difficulty,
+                compression_threshold_bytes: config.network.compression_threshold_bytes,
+                peer_score_half_life: config.network.peer_score_half_life,
+                peer_score_demote_threshold: config.network.peer_score_demote_threshold,
+                peer_score_ban_threshold: config.network.peer_score_ban_threshold,
+                peer_max_received_bytes_per_sec: config.network.peer_max_received_bytes_per_sec,
                 outbound_disabled: false,
                 archive: config.archive,
             },
             telemetry_config: config.telemetry,
+            webhooks_config: config.webhooks,
+            head_monitor_config: config.head_monitor,
+            epoch_hooks_config: config.epoch_hooks,
+            store_config: config.store.unwrap_or_else(|| {
+                config.memory_preset.map_or_else(StoreConfig::default, MemoryPreset::store_config)
+            }),
             #[cfg(feature = "json_rpc")]
             rpc_config: config.rpc,
             #[cfg(feature = "rosetta_rpc")]
@@ -1214,6 +1549,12 @@ pub fn load_config_without_genesis_records(dir: &Path) -> NearConfig {
         dir.join(&config.genesis_file)
     };
     let validator_signer = if dir.join(&config.validator_key_file).exists() {
+        if let Some(policy) = &config.validator_signing_policy {
+            let signer = InMemorySigner::from_file(&dir.join(&config.validator_key_file));
+            if let Err(err) = approve_validator_key(policy, signer) {
+                panic!("Validator key rejected by the signing policy: {}", err);
+            }
+        }
         let signer =
             Arc::new(InMemoryValidatorSigner::from_file(&dir.join(&config.validator_key_file)))
                 as Arc<dyn ValidatorSigner>;
@@ -1222,12 +1563,35 @@ pub fn load_config_without_genesis_records(dir: &Path) -> NearConfig {
         None
     };
     let network_signer = NodeKeyFile::from_file(&dir.join(&config.node_key_file));
-    NearConfig::new(
+    let mut near_config = NearConfig::new(
         config,
         Genesis::new_with_path(genesis_config, genesis_records_file),
         network_signer.into(),
         validator_signer,
-    )
+    );
+    near_config.network_config.peer_allowlist_file =
+        near_config.network_config.peer_allowlist_file.map(|path| dir.join(path));
+    near_config.network_config.asn_database_file =
+        near_config.network_config.asn_database_file.map(|path| dir.join(path));
+    let config_file = dir.join(CONFIG_FILENAME);
+    near_config.network_config.peer_ranges_loader = Some(Arc::new(move || {
+        let content = fs::read_to_string(&config_file).map_err(|err| err.to_string())?;
+        let network = parse_config(&content, ConfigMode::Lenient)
+            .map_err(|err| err.to_string())?
+            .config
+            .network;
+        PeerRanges::parse(&network.allowed_ranges, &network.denied_ranges)
+    }));
+    if let Some(state_quarantine) = near_config.client_config.state_quarantine.as_mut() {
+        state_quarantine.diagnostics_dir = dir.join(&state_quarantine.diagnostics_dir);
+    }
+    #[cfg(feature = "json_rpc")]
+    if let Some(api_keys) =
+        near_config.rpc_config.as_mut().and_then(|rpc_config| rpc_config.api_keys.as_mut())
+    {
+        api_keys.keys_file = api_keys.keys_file.take().map(|path| dir.join(path));
+    }
+    near_config
 }
 
 pub fn load_config(dir: &Path) -> NearConfig {
@@ -0,0 +1,222 @@
+//! Versioning of the `config.json` schema.
+//!
+//! Config files carry a `config_version` field. Files written by older releases are migrated
+//! step by step to [`CONFIG_VERSION`] before being deserialized, so fields which were renamed or
+//! moved keep their values. Keys which are not part of the schema are reported: they are ignored
+//! in lenient mode, which is what the node uses, and rejected in strict mode, which catches typos
+//! such as `track_shards` instead of `tracked_shards`.
+
+use serde_json::{Map, Value};
+
+use crate::config::Config;
+
+/// Current version of the config schema.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Name of the field holding the schema version.
+const CONFIG_VERSION_FIELD: &str = "config_version";
+
+/// Migration from version `i` to version `i + 1` is at index `i`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CONFIG_VERSION as usize] = [migrate_0_to_1];
+
+/// Files without a version predate the versioning of the schema, which didn't change the
+/// layout of the config.
+fn migrate_0_to_1(_config: &mut Map<String, Value>) {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigMode {
+    /// Unknown fields are an error.
+    Strict,
+    /// Unknown fields are ignored.
+    Lenient,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to parse config: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Config must be a JSON object")]
+    NotAnObject,
+    #[error("Config version {version} is newer than the latest supported version {supported}")]
+    UnsupportedVersion { version: u32, supported: u32 },
+    #[error("Unknown config fields: {}", .0.join(", "))]
+    UnknownFields(Vec<String>),
+}
+
+/// Config parsed from a file along with what had to be done to load it.
+#[derive(Debug)]
+pub struct ParsedConfig {
+    pub config: Config,
+    /// Schema version of the file, 0 if the file had no version.
+    pub version: u32,
+    /// Paths of the fields which are not part of the schema, e.g. `network.max_peers`.
+    pub unknown_fields: Vec<String>,
+    /// Changes writing the config back would make to the file, including the migration, the
+    /// defaults of missing fields and the removal of unknown fields.
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ParsedConfig {
+    pub fn is_migrated(&self) -> bool {
+        self.version != CONFIG_VERSION
+    }
+}
+
+/// Parses the content of a config file, migrating it to the latest schema version.
+pub fn parse_config(content: &str, mode: ConfigMode) -> Result<ParsedConfig, ConfigError> {
+    let original: Value = serde_json::from_str(content)?;
+    let mut value = original.clone();
+    let object = value.as_object_mut().ok_or(ConfigError::NotAnObject)?;
+    let version = object.get(CONFIG_VERSION_FIELD).and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > CONFIG_VERSION {
+        return Err(ConfigError::UnsupportedVersion { version, supported: CONFIG_VERSION });
+    }
+    for migration in MIGRATIONS[version as usize..].iter() {
+        migration(object);
+    }
+    object.insert(CONFIG_VERSION_FIELD.to_string(), Value::from(CONFIG_VERSION));
+
+    let config: Config = serde_json::from_value(value.clone())?;
+    let serialized = serde_json::to_value(&config)?;
+    let mut unknown_fields = vec![];
+    find_unknown_fields(&value, &serialized, "", &mut unknown_fields);
+    unknown_fields.sort();
+    if mode == ConfigMode::Strict && !unknown_fields.is_empty() {
+        return Err(ConfigError::UnknownFields(unknown_fields));
+    }
+    let changes = diff_configs(&original, &serialized);
+    Ok(ParsedConfig { config, version, unknown_fields, changes })
+}
+
+/// Collects the fields of `input` which didn't make it into `parsed`, i.e. the config
+/// re-serialized after parsing. Empty values are skipped, since the config omits some of its
+/// optional fields when they are empty.
+fn find_unknown_fields(input: &Value, parsed: &Value, prefix: &str, result: &mut Vec<String>) {
+    let (input, parsed) = match (input, parsed) {
+        (Value::Object(input), Value::Object(parsed)) => (input, parsed),
+        _ => return,
+    };
+    for (key, value) in input.iter() {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match parsed.get(key) {
+            Some(parsed_value) => find_unknown_fields(value, parsed_value, &path, result),
+            None if is_empty(value) => {}
+            None => result.push(path),
+        }
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(array) => array.is_empty(),
+        Value::Object(object) => object.is_empty(),
+        _ => false,
+    }
+}
+
+/// Change of a single field between two versions of a config.
+#[derive(Debug, PartialEq)]
+pub enum ConfigChange {
+    Added { path: String, value: Value },
+    Removed { path: String, value: Value },
+    Changed { path: String, old: Value, new: Value },
+}
+
+/// Lists the leaf fields which differ between `old` and `new`.
+pub fn diff_configs(old: &Value, new: &Value) -> Vec<ConfigChange> {
+    let mut changes = vec![];
+    diff_values(old, new, "", &mut changes);
+    changes
+}
+
+fn diff_values(old: &Value, new: &Value, prefix: &str, changes: &mut Vec<ConfigChange>) {
+    let (old_object, new_object) = match (old, new) {
+        (Value::Object(old), Value::Object(new)) => (old, new),
+        _ => {
+            if old != new {
+                changes.push(ConfigChange::Changed {
+                    path: prefix.to_string(),
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+            return;
+        }
+    };
+    let join =
+        |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+    for (key, old_value) in old_object.iter() {
+        match new_object.get(key) {
+            Some(new_value) => diff_values(old_value, new_value, &join(key), changes),
+            None => {
+                changes.push(ConfigChange::Removed { path: join(key), value: old_value.clone() })
+            }
+        }
+    }
+    for (key, new_value) in new_object.iter() {
+        if !old_object.contains_key(key) {
+            changes.push(ConfigChange::Added { path: join(key), value: new_value.clone() });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_unversioned_config() {
+        let parsed = parse_config(r#"{"archive": true}"#, ConfigMode::Strict).unwrap();
+        assert_eq!(parsed.version, 0);
+        assert!(parsed.is_migrated());
+        assert!(parsed.config.archive);
+        assert_eq!(parsed.config.config_version, CONFIG_VERSION);
+        assert!(parsed.changes.contains(&ConfigChange::Added {
+            path: "config_version".to_string(),
+            value: Value::from(CONFIG_VERSION)
+        }));
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let mut config = serde_json::to_value(Config::default()).unwrap();
+        config["track_shards"] = serde_json::json!([0]);
+        config["network"]["max_peers"] = Value::from(10);
+        config["circuit_breaker"] = Value::Null;
+        let content = config.to_string();
+        let parsed = parse_config(&content, ConfigMode::Lenient).unwrap();
+        assert_eq!(parsed.unknown_fields, vec!["network.max_peers", "track_shards"]);
+        assert!(matches!(
+            parse_config(&content, ConfigMode::Strict),
+            Err(ConfigError::UnknownFields(_))
+        ));
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let content = format!(r#"{{"config_version": {}}}"#, CONFIG_VERSION + 1);
+        assert!(matches!(
+            parse_config(&content, ConfigMode::Lenient),
+            Err(ConfigError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_diff_configs() {
+        let old = serde_json::json!({"a": 1, "b": {"c": 2, "d": 3}});
+        let new = serde_json::json!({"a": 1, "b": {"c": 4}, "e": 5});
+        assert_eq!(
+            diff_configs(&old, &new),
+            vec![
+                ConfigChange::Changed {
+                    path: "b.c".to_string(),
+                    old: Value::from(2),
+                    new: Value::from(4)
+                },
+                ConfigChange::Removed { path: "b.d".to_string(), value: Value::from(3) },
+                ConfigChange::Added { path: "e".to_string(), value: Value::from(5) },
+            ]
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Hooks run on epoch boundaries, for operators to automate what they used to script by polling
+//! the JSON RPC.
+//!
+//! The node follows its head and, when a new epoch starts, compares the validators of the new
+//! epoch with the ones of the previous epoch it saw. Each boundary fires `epoch_started`, and
+//! for the account of the validator key of the node `validator_joined`, `validator_left` or
+//! `shards_changed`. Every hook matching the event runs its command and POSTs its payload to its
+//! URL. The payload is the JSON of the event, or the `payload` template of the hook in which
+//! `{{field}}` placeholders are replaced with the fields of the event, which are also passed to
+//! commands through `NEAR_EPOCH_*` environment variables. The epoch the node starts in only
+//! serves as the reference for the next one, no hook runs for it.
+use std::time::Duration;
+
+use actix::Addr;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use near_client::{GetBlock, GetValidatorInfo, ViewClientActor};
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{
+    AccountId, BlockHeight, BlockReference, EpochHeight, EpochId, EpochReference, Finality, ShardId,
+};
+use near_primitives::views::EpochValidatorInfo;
+
+use crate::webhooks::{deliver, http_client};
+
+/// How long a command may run before it's killed.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+fn default_poll_interval() -> Duration {
+    Duration::from_secs(5)
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+fn default_retry_delay() -> Duration {
+    Duration::from_secs(1)
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EpochEventKind {
+    /// A new epoch started.
+    EpochStarted,
+    /// The account of the node is a validator of the new epoch but wasn't of the previous one.
+    ValidatorJoined,
+    /// The account of the node was a validator of the previous epoch but isn't of the new one.
+    ValidatorLeft,
+    /// The account of the node validates other shards in the new epoch.
+    ShardsChanged,
+}
+
+impl EpochEventKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EpochEventKind::EpochStarted => "epoch_started",
+            EpochEventKind::ValidatorJoined => "validator_joined",
+            EpochEventKind::ValidatorLeft => "validator_left",
+            EpochEventKind::ShardsChanged => "shards_changed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EpochHookConfig {
+    /// Events the hook runs on, all of them if empty.
+    #[serde(default)]
+    pub events: Vec<EpochEventKind>,
+    /// Command run on the events, the program followed by its arguments, which may contain
+    /// placeholders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+    /// URL the payload is POSTed to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Secret used to sign the POSTed payloads, like the ones of the webhooks. Unsigned if not
+    /// set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Template of the payload, the JSON of the event if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+}
+
+impl EpochHookConfig {
+    fn runs_on(&self, event: EpochEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EpochHooksConfig {
+    pub hooks: Vec<EpochHookConfig>,
+    /// How often the head is checked for a new epoch.
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval: Duration,
+    /// How many times a failed POST is retried before the payload is dropped.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each following attempt.
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: Duration,
+}
+
+/// State of the node in an epoch, as far as the hooks are concerned.
+#[derive(Clone, Debug, PartialEq)]
+struct EpochState {
+    epoch_id: EpochId,
+    epoch_height: EpochHeight,
+    epoch_start_height: BlockHeight,
+    /// Shards the account of the node validates, `None` if it isn't a validator.
+    shards: Option<Vec<ShardId>>,
+}
+
+impl EpochState {
+    fn new(epoch_id: EpochId, info: &EpochValidatorInfo, account_id: Option<&AccountId>) -> Self {
+        let shards = account_id.and_then(|account_id| {
+            info.current_validators
+                .iter()
+                .find(|validator| &validator.account_id == account_id)
+                .map(|validator| validator.shards.clone())
+        });
+        Self {
+            epoch_id,
+            epoch_height: info.epoch_height,
+            epoch_start_height: info.epoch_start_height,
+            shards,
+        }
+    }
+}
+
+/// Event passed to the hooks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EpochEvent {
+    pub event: EpochEventKind,
+    pub epoch_id: CryptoHash,
+    pub epoch_height: EpochHeight,
+    pub epoch_start_height: BlockHeight,
+    /// Account of the validator key of the node, if any.
+    pub account_id: Option<AccountId>,
+    /// Shards the account of the node validates in the new epoch.
+    pub shards: Vec<ShardId>,
+    /// Shards the account of the node validated in the previous epoch.
+    pub previous_shards: Vec<ShardId>,
+}
+
+impl EpochEvent {
+    /// Fields of the event by placeholder name, as text.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let shards = |shards: &[ShardId]| serde_json::to_string(shards).unwrap();
+        vec![
+            ("event", self.event.as_str().to_string()),
+            ("epoch_id", self.epoch_id.to_string()),
+            ("epoch_height", self.epoch_height.to_string()),
+            ("epoch_start_height", self.epoch_start_height.to_string()),
+            ("account_id", self.account_id.as_ref().map(ToString::to_string).unwrap_or_default()),
+            ("shards", shards(&self.shards)),
+            ("previous_shards", shards(&self.previous_shards)),
+        ]
+    }
+
+    /// Replaces the `{{field}}` placeholders of the template with the fields of the event.
+    fn render(&self, template: &str) -> String {
+        let mut result = template.to_string();
+        for (name, value) in self.fields() {
+            result = result.replace(&format!("{{{{{}}}}}", name), &value);
+        }
+        result
+    }
+
+    fn payload(&self, hook: &EpochHookConfig) -> String {
+        match &hook.payload {
+            Some(template) => self.render(template),
+            None => serde_json::to_string(self).unwrap(),
+        }
+    }
+}
+
+/// Events of the boundary between the previous and the new epoch.
+fn epoch_events(
+    previous: &EpochState,
+    current: &EpochState,
+    account_id: Option<&AccountId>,
+) -> Vec<EpochEvent> {
+    let event = |event| EpochEvent {
+        event,
+        epoch_id: current.epoch_id.0,
+        epoch_height: current.epoch_height,
+        epoch_start_height: current.epoch_start_height,
+        account_id: account_id.cloned(),
+        shards: current.shards.clone().unwrap_or_default(),
+        previous_shards: previous.shards.clone().unwrap_or_default(),
+    };
+    let mut events = vec![event(EpochEventKind::EpochStarted)];
+    match (&previous.shards, &current.shards) {
+        (None, Some(_)) => events.push(event(EpochEventKind::ValidatorJoined)),
+        (Some(_), None) => events.push(event(EpochEventKind::ValidatorLeft)),
+        (Some(previous_shards), Some(shards)) if previous_shards != shards => {
+            events.push(event(EpochEventKind::ShardsChanged))
+        }
+        _ => {}
+    }
+    events
+}
+
+async fn run_command(program: String, args: Vec<String>, event: EpochEvent, payload: String) {
+    let mut command = tokio::process::Command::new(&program);
+    command.args(&args).env("NEAR_EPOCH_PAYLOAD", payload).kill_on_drop(true);
+    for (name, value) in event.fields() {
+        command.env(format!("NEAR_EPOCH_{}", name.to_uppercase()), value);
+    }
+    match tokio::time::timeout(COMMAND_TIMEOUT, command.status()).await {
+        Ok(Ok(status)) if status.success() => {
+            debug!(target: "epoch_hooks", "Command {} succeeded on {}", program, event.event.as_str())
+        }
+        Ok(Ok(status)) => {
+            warn!(target: "epoch_hooks", "Command {} failed on {} with {}", program, event.event.as_str(), status)
+        }
+        Ok(Err(err)) => warn!(target: "epoch_hooks", "Failed to run command {}: {}", program, err),
+        Err(_) => {
+            warn!(target: "epoch_hooks", "Command {} killed after running for {:?}", program, COMMAND_TIMEOUT)
+        }
+    }
+}
+
+async fn fetch_epoch_state(
+    view_client: &Addr<ViewClientActor>,
+    account_id: Option<&AccountId>,
+) -> Option<EpochState> {
+    let block =
+        view_client.send(GetBlock(BlockReference::Finality(Finality::None))).await.ok()?.ok()?;
+    let epoch_id = EpochId(block.header.epoch_id);
+    let info = match view_client
+        .send(GetValidatorInfo { epoch_reference: EpochReference::EpochId(epoch_id.clone()) })
+        .await
+    {
+        Ok(Ok(info)) => info,
+        Ok(Err(err)) => {
+            debug!(target: "epoch_hooks", "Validators of epoch {:?} are not known: {}", epoch_id, err);
+            return None;
+        }
+        Err(err) => {
+            warn!(target: "epoch_hooks", "Failed to fetch validators: {}", err);
+            return None;
+        }
+    };
+    Some(EpochState::new(epoch_id, &info, account_id))
+}
+
+/// Follows the head and runs the hooks on epoch boundaries. Runs until the view client stops.
+pub async fn run_epoch_hooks(
+    config: EpochHooksConfig,
+    view_client: Addr<ViewClientActor>,
+    account_id: Option<AccountId>,
+) {
+    info!(target: "epoch_hooks", "Starting {} epoch hook(s)", config.hooks.len());
+    let client = http_client();
+    let mut previous: Option<EpochState> = None;
+    let mut interval = tokio::time::interval(config.poll_interval);
+    loop {
+        interval.tick().await;
+        if !view_client.connected() {
+            info!(target: "epoch_hooks", "View client stopped, stopping epoch hooks");
+            return;
+        }
+        let current = match fetch_epoch_state(&view_client, account_id.as_ref()).await {
+            Some(current) => current,
+            None => continue,
+        };
+        let previous = match previous.replace(current.clone()) {
+            Some(previous) if previous.epoch_id != current.epoch_id => previous,
+            _ => continue,
+        };
+        for event in epoch_events(&previous, &current, account_id.as_ref()) {
+            info!(target: "epoch_hooks", "Epoch {} at height {}: {}", event.epoch_height, event.epoch_start_height, event.event.as_str());
+            for hook in config.hooks.iter().filter(|hook| hook.runs_on(event.event)) {
+                let payload = event.payload(hook);
+                if let Some(url) = &hook.url {
+                    actix::spawn(deliver(
+                        client.clone(),
+                        url.clone(),
+                        hook.secret.clone(),
+                        payload.clone().into_bytes(),
+                        config.max_retries,
+                        config.retry_delay,
+                    ));
+                }
+                if let Some((program, args)) = hook.command.as_ref().and_then(|c| c.split_first()) {
+                    let args = args.iter().map(|arg| event.render(arg)).collect();
+                    actix::spawn(run_command(program.clone(), args, event.clone(), payload));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::hash::hash;
+
+    fn state(epoch: &[u8], shards: Option<Vec<ShardId>>) -> EpochState {
+        EpochState {
+            epoch_id: EpochId(hash(epoch)),
+            epoch_height: 2,
+            epoch_start_height: 100,
+            shards,
+        }
+    }
+
+    fn kinds(events: &[EpochEvent]) -> Vec<EpochEventKind> {
+        events.iter().map(|event| event.event).collect()
+    }
+
+    #[test]
+    fn test_epoch_events() {
+        let account_id: AccountId = "validator.near".parse().unwrap();
+        let account_id = Some(&account_id);
+        let events = epoch_events(&state(b"1", None), &state(b"2", Some(vec![0])), account_id);
+        assert_eq!(
+            kinds(&events),
+            vec![EpochEventKind::EpochStarted, EpochEventKind::ValidatorJoined]
+        );
+        let events =
+            epoch_events(&state(b"1", Some(vec![0])), &state(b"2", Some(vec![1, 2])), account_id);
+        assert_eq!(
+            kinds(&events),
+            vec![EpochEventKind::EpochStarted, EpochEventKind::ShardsChanged]
+        );
+        assert_eq!(
+            (events[1].previous_shards.clone(), events[1].shards.clone()),
+            (vec![0], vec![1, 2])
+        );
+        let events =
+            epoch_events(&state(b"1", Some(vec![0])), &state(b"2", Some(vec![0])), account_id);
+        assert_eq!(kinds(&events), vec![EpochEventKind::EpochStarted]);
+        let events = epoch_events(&state(b"1", Some(vec![0])), &state(b"2", None), account_id);
+        assert_eq!(
+            kinds(&events),
+            vec![EpochEventKind::EpochStarted, EpochEventKind::ValidatorLeft]
+        );
+    }
+
+    #[test]
+    fn test_payload() {
+        let event = EpochEvent {
+            event: EpochEventKind::ShardsChanged,
+            epoch_id: hash(b"2"),
+            epoch_height: 2,
+            epoch_start_height: 100,
+            account_id: Some("validator.near".parse().unwrap()),
+            shards: vec![1, 2],
+            previous_shards: vec![0],
+        };
+        let mut hook = EpochHookConfig {
+            events: vec![EpochEventKind::ShardsChanged],
+            command: None,
+            url: None,
+            secret: None,
+            payload: Some(
+                r#"{"text": "{{account_id}} moves from {{previous_shards}} to {{shards}}"}"#
+                    .to_string(),
+            ),
+        };
+        assert!(hook.runs_on(EpochEventKind::ShardsChanged));
+        assert!(!hook.runs_on(EpochEventKind::EpochStarted));
+        assert_eq!(event.payload(&hook), r#"{"text": "validator.near moves from [0] to [1,2]"}"#);
+        hook.payload = None;
+        let parsed: EpochEvent = serde_json::from_str(&event.payload(&hook)).unwrap();
+        assert_eq!(parsed, event);
+    }
+}
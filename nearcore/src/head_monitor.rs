@@ -0,0 +1,459 @@
+//! Cross-check of the head of the node against independent nodes of the network.
+//!
+//! The monitor periodically samples some of the configured JSON RPC endpoints and asks each of
+//! them for the light client block following the last final block of this node.  Responses are
+//! verified like a light client would, against the block producers known to this node, so that a
+//! malicious endpoint can't cause a false alarm without the signatures of the block producers.
+//! If most of the sampled endpoints have final blocks that this node's chain doesn't contain, or
+//! don't know this node's final block at all, the node is likely eclipsed or its storage is
+//! corrupted, and an error is logged and exported in metrics.
+//!
+//! Light client blocks can't be requested over the peer-to-peer protocol, so peers are not
+//! sampled: the endpoints should be run by independent operators.
+use std::time::Duration;
+
+use actix::Addr;
+use awc::{Client, Connector};
+use borsh::BorshSerialize;
+use near_metrics::{IntCounterVec, IntGauge};
+use once_cell::sync::Lazy;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, info, warn};
+
+use near_client::{GetBlock, GetNextLightClientBlock, GetValidatorOrdered, ViewClientActor};
+use near_primitives::block_header::{Approval, ApprovalInner, BlockHeaderInnerLite};
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::merkle::combine_hash;
+use near_primitives::types::{AccountId, Balance, BlockHeight, BlockHeightDelta, BlockId};
+use near_primitives::views::validator_stake_view::ValidatorStakeView;
+use near_primitives::views::LightClientBlockView;
+
+/// Timeout for establishing connection and receiving a response.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+static HEAD_MONITOR_CHECKS: Lazy<IntCounterVec> = Lazy::new(|| {
+    near_metrics::try_create_int_counter_vec(
+        "near_head_monitor_checks_total",
+        "Number of light client blocks of the sampled endpoints checked against the head, by outcome",
+        &["outcome"],
+    )
+    .unwrap()
+});
+static HEAD_MONITOR_DIVERGED: Lazy<IntGauge> = Lazy::new(|| {
+    near_metrics::try_create_int_gauge(
+        "near_head_monitor_diverged",
+        "Whether most of the sampled endpoints disagree with the head of the node",
+    )
+    .unwrap()
+});
+
+fn default_check_interval() -> Duration {
+    Duration::from_secs(60)
+}
+
+fn default_sample_size() -> usize {
+    3
+}
+
+fn default_max_lag_blocks() -> BlockHeightDelta {
+    50
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HeadMonitorConfig {
+    /// JSON RPC endpoints of independent nodes of the same chain.
+    pub rpc_urls: Vec<String>,
+    /// How often the head is cross-checked.
+    #[serde(default = "default_check_interval")]
+    pub check_interval: Duration,
+    /// Number of endpoints queried on every check.
+    #[serde(default = "default_sample_size")]
+    pub sample_size: usize,
+    /// How many blocks the final head of the node may be behind the endpoints before warning.
+    #[serde(default = "default_max_lag_blocks")]
+    pub max_lag_blocks: BlockHeightDelta,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum LightClientBlockError {
+    #[error("Light client block is in epoch {0} for which block producers are not known")]
+    UnknownEpoch(CryptoHash),
+    #[error("Invalid approval signature of {0}")]
+    InvalidSignature(AccountId),
+    #[error("Approved stake {approved} is not more than 2/3 of total stake {total}")]
+    NotEnoughApprovals { approved: Balance, total: Balance },
+}
+
+/// Result of comparing the light client block of an endpoint with the chain of the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossCheckOutcome {
+    /// The block of the endpoint is on the chain of the node, or not far ahead of it.
+    Agrees,
+    /// The final head of the node lags more than `max_lag_blocks` behind the endpoint.
+    Behind,
+    /// The endpoint has a final block at a height where the node has a different one or none.
+    Diverged,
+    /// The endpoint doesn't know the last final block of the node.
+    UnknownHead,
+}
+
+impl CrossCheckOutcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CrossCheckOutcome::Agrees => "agrees",
+            CrossCheckOutcome::Behind => "behind",
+            CrossCheckOutcome::Diverged => "diverged",
+            CrossCheckOutcome::UnknownHead => "unknown_head",
+        }
+    }
+}
+
+/// Computes the hash of the block described by the light client block.
+pub fn light_client_block_hash(block: &LightClientBlockView) -> CryptoHash {
+    let inner_lite = BlockHeaderInnerLite::from(block.inner_lite.clone())
+        .try_to_vec()
+        .expect("Failed to serialize");
+    combine_hash(combine_hash(hash(&inner_lite), block.inner_rest_hash), block.prev_block_hash)
+}
+
+/// Checks that the block following the light client block is endorsed by more than 2/3 of the
+/// stake of `block_producers`, the ordered block producers of the epoch of the block.  Returns
+/// the hash of the block.
+pub fn verify_light_client_block(
+    block: &LightClientBlockView,
+    block_producers: &[ValidatorStakeView],
+) -> Result<CryptoHash, LightClientBlockError> {
+    let block_hash = light_client_block_hash(block);
+    let next_block_hash = combine_hash(block.next_block_inner_hash, block_hash);
+    let data = Approval::get_data_for_sig(
+        &ApprovalInner::Endorsement(next_block_hash),
+        block.inner_lite.height + 2,
+    );
+    let mut total: Balance = 0;
+    let mut approved: Balance = 0;
+    for (index, block_producer) in block_producers.iter().enumerate() {
+        let block_producer = block_producer.clone().into_validator_stake();
+        total += block_producer.stake();
+        if let Some(Some(signature)) = block.approvals_after_next.get(index) {
+            if !signature.verify(&data, block_producer.public_key()) {
+                return Err(LightClientBlockError::InvalidSignature(
+                    block_producer.take_account_id(),
+                ));
+            }
+            approved += block_producer.stake();
+        }
+    }
+    if approved * 3 <= total * 2 {
+        return Err(LightClientBlockError::NotEnoughApprovals { approved, total });
+    }
+    Ok(block_hash)
+}
+
+/// Compares the verified final block of an endpoint with the block of the node at its height.
+/// Blocks above the final head of the node are only checked for how far ahead they are.
+pub fn compare_with_head(
+    remote_hash: CryptoHash,
+    remote_height: BlockHeight,
+    local_hash: Option<CryptoHash>,
+    local_final_height: BlockHeight,
+    max_lag_blocks: BlockHeightDelta,
+) -> CrossCheckOutcome {
+    if local_hash == Some(remote_hash) {
+        CrossCheckOutcome::Agrees
+    } else if remote_height <= local_final_height {
+        CrossCheckOutcome::Diverged
+    } else if remote_height > local_final_height + max_lag_blocks {
+        CrossCheckOutcome::Behind
+    } else {
+        CrossCheckOutcome::Agrees
+    }
+}
+
+/// Combines the outcomes of the sampled endpoints, the network agreeing unless most of them say
+/// otherwise.  Returns `None` if none of them responded.
+pub fn assess(outcomes: &[CrossCheckOutcome]) -> Option<CrossCheckOutcome> {
+    if outcomes.is_empty() {
+        return None;
+    }
+    let diverged = outcomes
+        .iter()
+        .filter(|outcome| {
+            matches!(outcome, CrossCheckOutcome::Diverged | CrossCheckOutcome::UnknownHead)
+        })
+        .count();
+    let behind = outcomes.iter().filter(|outcome| **outcome == CrossCheckOutcome::Behind).count();
+    Some(if diverged * 2 > outcomes.len() {
+        CrossCheckOutcome::Diverged
+    } else if behind * 2 > outcomes.len() {
+        CrossCheckOutcome::Behind
+    } else {
+        CrossCheckOutcome::Agrees
+    })
+}
+
+enum FetchError {
+    UnknownBlock,
+    Other(String),
+}
+
+/// Calls `next_light_client_block` of the endpoint.
+async fn fetch_light_client_block(
+    client: &Client,
+    url: &str,
+    last_block_hash: &CryptoHash,
+) -> Result<Option<LightClientBlockView>, FetchError> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": "dontcare",
+        "method": "next_light_client_block",
+        "params": { "last_block_hash": last_block_hash },
+    });
+    let mut response = client
+        .post(url)
+        .send_json(&request)
+        .await
+        .map_err(|err| FetchError::Other(err.to_string()))?;
+    let response: serde_json::Value =
+        response.json().limit(1 << 22).await.map_err(|err| FetchError::Other(err.to_string()))?;
+    if let Some(error) = response.get("error") {
+        return Err(match error["cause"]["name"].as_str() {
+            Some("UNKNOWN_BLOCK") => FetchError::UnknownBlock,
+            _ => FetchError::Other(error.to_string()),
+        });
+    }
+    match response.get("result") {
+        Some(serde_json::Value::Object(result)) if result.is_empty() => Ok(None),
+        Some(result) => serde_json::from_value(result.clone())
+            .map(Some)
+            .map_err(|err| FetchError::Other(err.to_string())),
+        None => Err(FetchError::Other("Response has no result".to_string())),
+    }
+}
+
+/// The last final block of the node and the block producers which can sign the light client
+/// blocks following it.
+struct LocalHead {
+    block: LightClientBlockView,
+    hash: CryptoHash,
+    block_producers: Vec<ValidatorStakeView>,
+}
+
+impl LocalHead {
+    fn block_producers(&self, epoch_id: &CryptoHash) -> Option<&[ValidatorStakeView]> {
+        if *epoch_id == self.block.inner_lite.epoch_id {
+            Some(&self.block_producers)
+        } else if *epoch_id == self.block.inner_lite.next_epoch_id {
+            self.block.next_bps.as_deref()
+        } else {
+            None
+        }
+    }
+}
+
+async fn fetch_local_head(view_client: &Addr<ViewClientActor>) -> Option<LocalHead> {
+    let head = view_client.send(GetBlock::latest()).await.ok()?.ok()?;
+    // The light client block of the head is the one of its last final block.
+    let block = view_client
+        .send(GetNextLightClientBlock { last_block_hash: head.header.prev_hash })
+        .await
+        .ok()?
+        .ok()??;
+    let hash = light_client_block_hash(&block);
+    let block_producers = view_client
+        .send(GetValidatorOrdered { block_id: Some(BlockId::Hash(hash)) })
+        .await
+        .ok()?
+        .ok()?;
+    Some(LocalHead { block, hash, block_producers })
+}
+
+async fn check_endpoint(
+    client: &Client,
+    view_client: &Addr<ViewClientActor>,
+    url: &str,
+    local: &LocalHead,
+    max_lag_blocks: BlockHeightDelta,
+) -> Option<CrossCheckOutcome> {
+    let block = match fetch_light_client_block(client, url, &local.hash).await {
+        // The endpoint isn't ahead of the node but knows its final block.
+        Ok(None) => return Some(CrossCheckOutcome::Agrees),
+        Ok(Some(block)) => block,
+        Err(FetchError::UnknownBlock) => return Some(CrossCheckOutcome::UnknownHead),
+        Err(FetchError::Other(err)) => {
+            debug!(target: "head_monitor", "Failed to query {}: {}", url, err);
+            return None;
+        }
+    };
+    let epoch_id = block.inner_lite.epoch_id;
+    let verified = local
+        .block_producers(&epoch_id)
+        .ok_or(LightClientBlockError::UnknownEpoch(epoch_id))
+        .and_then(|block_producers| verify_light_client_block(&block, block_producers));
+    let remote_hash = match verified {
+        Ok(hash) => hash,
+        Err(err) => {
+            warn!(target: "head_monitor", "Endpoint {} served an invalid light client block: {}", url, err);
+            return None;
+        }
+    };
+    let remote_height = block.inner_lite.height;
+    let local_hash = match view_client.send(GetBlock(BlockId::Height(remote_height).into())).await {
+        Ok(Ok(block)) => Some(block.header.hash),
+        _ => None,
+    };
+    let outcome = compare_with_head(
+        remote_hash,
+        remote_height,
+        local_hash,
+        local.block.inner_lite.height,
+        max_lag_blocks,
+    );
+    if outcome != CrossCheckOutcome::Agrees {
+        info!(target: "head_monitor", "Endpoint {} has final block {} at height {}, the node has {:?} and final height {}", url, remote_hash, remote_height, local_hash, local.block.inner_lite.height);
+    }
+    Some(outcome)
+}
+
+/// Periodically cross-checks the head of the node.  Runs until the view client stops.
+pub async fn run_head_monitor(config: HeadMonitorConfig, view_client: Addr<ViewClientActor>) {
+    info!(target: "head_monitor", "Starting head monitor with {} endpoint(s)", config.rpc_urls.len());
+    let client = Client::builder()
+        .timeout(CONNECT_TIMEOUT)
+        .connector(Connector::new().max_http_version(actix_web::http::Version::HTTP_11))
+        .finish();
+    let mut interval = tokio::time::interval(config.check_interval);
+    loop {
+        interval.tick().await;
+        if !view_client.connected() {
+            info!(target: "head_monitor", "View client stopped, stopping head monitor");
+            return;
+        }
+        let local = match fetch_local_head(&view_client).await {
+            Some(local) => local,
+            None => continue,
+        };
+        let urls: Vec<String> = config
+            .rpc_urls
+            .choose_multiple(&mut rand::thread_rng(), config.sample_size)
+            .cloned()
+            .collect();
+        let mut outcomes = vec![];
+        for url in urls {
+            if let Some(outcome) =
+                check_endpoint(&client, &view_client, &url, &local, config.max_lag_blocks).await
+            {
+                HEAD_MONITOR_CHECKS.with_label_values(&[outcome.as_str()]).inc();
+                outcomes.push(outcome);
+            }
+        }
+        match assess(&outcomes) {
+            Some(CrossCheckOutcome::Diverged) => {
+                HEAD_MONITOR_DIVERGED.set(1);
+                error!(target: "head_monitor", "Final block {} at height {} diverges from {} of {} sampled endpoints, the node may be eclipsed or its storage corrupted", local.hash, local.block.inner_lite.height, outcomes.iter().filter(|outcome| **outcome != CrossCheckOutcome::Agrees).count(), outcomes.len());
+            }
+            Some(CrossCheckOutcome::Behind) => {
+                HEAD_MONITOR_DIVERGED.set(0);
+                warn!(target: "head_monitor", "Final block at height {} is more than {} blocks behind the sampled endpoints", local.block.inner_lite.height, config.max_lag_blocks);
+            }
+            Some(_) => HEAD_MONITOR_DIVERGED.set(0),
+            None => debug!(target: "head_monitor", "None of the sampled endpoints responded"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::KeyType;
+    use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
+    use near_primitives::views::BlockHeaderInnerLiteView;
+
+    use super::*;
+
+    fn block_producer(signer: &InMemoryValidatorSigner, stake: Balance) -> ValidatorStakeView {
+        near_primitives::types::validator_stake::ValidatorStake::new_v1(
+            signer.validator_id().clone(),
+            signer.public_key(),
+            stake,
+        )
+        .into()
+    }
+
+    fn light_client_block(signers: &[&InMemoryValidatorSigner]) -> LightClientBlockView {
+        let mut block = LightClientBlockView {
+            prev_block_hash: hash(b"prev"),
+            next_block_inner_hash: hash(b"next"),
+            inner_lite: BlockHeaderInnerLiteView {
+                height: 10,
+                epoch_id: CryptoHash::default(),
+                next_epoch_id: hash(b"next epoch"),
+                prev_state_root: CryptoHash::default(),
+                outcome_root: CryptoHash::default(),
+                timestamp: 0,
+                timestamp_nanosec: 0,
+                next_bp_hash: CryptoHash::default(),
+                block_merkle_root: CryptoHash::default(),
+            },
+            inner_rest_hash: hash(b"rest"),
+            next_bps: None,
+            approvals_after_next: vec![],
+        };
+        let next_block_hash =
+            combine_hash(block.next_block_inner_hash, light_client_block_hash(&block));
+        let inner = ApprovalInner::Endorsement(next_block_hash);
+        block.approvals_after_next =
+            signers.iter().map(|signer| Some(signer.sign_approval(&inner, 12))).collect();
+        block
+    }
+
+    #[test]
+    fn test_verify_light_client_block() {
+        let signer = |seed: &str| {
+            InMemoryValidatorSigner::from_seed(seed.parse().unwrap(), KeyType::ED25519, seed)
+        };
+        let (alice, bob, carol) = (signer("alice"), signer("bob"), signer("carol"));
+        let block_producers =
+            vec![block_producer(&alice, 10), block_producer(&bob, 10), block_producer(&carol, 10)];
+
+        let block = light_client_block(&[&alice, &bob, &carol]);
+        assert_eq!(
+            verify_light_client_block(&block, &block_producers),
+            Ok(light_client_block_hash(&block))
+        );
+
+        let mut block = light_client_block(&[&alice, &bob, &carol]);
+        block.approvals_after_next[2] = None;
+        assert_eq!(
+            verify_light_client_block(&block, &block_producers),
+            Err(LightClientBlockError::NotEnoughApprovals { approved: 20, total: 30 })
+        );
+
+        // Signatures of the wrong block producers.
+        let block = light_client_block(&[&bob, &alice, &carol]);
+        assert_eq!(
+            verify_light_client_block(&block, &block_producers),
+            Err(LightClientBlockError::InvalidSignature("alice".parse().unwrap()))
+        );
+
+        // Tampering with the block invalidates the approvals.
+        let mut block = light_client_block(&[&alice, &bob, &carol]);
+        block.inner_lite.height += 1;
+        assert!(verify_light_client_block(&block, &block_producers).is_err());
+    }
+
+    #[test]
+    fn test_compare_and_assess() {
+        let (a, b) = (hash(b"a"), hash(b"b"));
+        assert_eq!(compare_with_head(a, 10, Some(a), 20, 5), CrossCheckOutcome::Agrees);
+        assert_eq!(compare_with_head(a, 10, Some(b), 20, 5), CrossCheckOutcome::Diverged);
+        assert_eq!(compare_with_head(a, 10, None, 20, 5), CrossCheckOutcome::Diverged);
+        assert_eq!(compare_with_head(a, 24, None, 20, 5), CrossCheckOutcome::Agrees);
+        assert_eq!(compare_with_head(a, 26, None, 20, 5), CrossCheckOutcome::Behind);
+
+        use CrossCheckOutcome::*;
+        assert_eq!(assess(&[]), None);
+        assert_eq!(assess(&[Agrees, Diverged, Agrees]), Some(Agrees));
+        assert_eq!(assess(&[Diverged, UnknownHead, Agrees]), Some(Diverged));
+        assert_eq!(assess(&[Behind, Behind, Agrees]), Some(Behind));
+    }
+}
@@ -13,6 +13,7 @@ use near_chain::ChainGenesis;
 #[cfg(feature = "test_features")]
 use near_client::AdversarialControls;
 use near_client::{start_client, start_view_client, ClientActor, ViewClientActor};
+use near_client_api::ClientApi;
 
 use near_network::routing::start_routing_table_actor;
 use near_network::types::NetworkRecipient;
@@ -28,7 +29,7 @@ use near_store::migrations::{
     migrate_9_to_10, set_store_version,
 };
 use near_store::migrations::{migrate_20_to_21, migrate_26_to_27};
-use near_store::{create_store, Store};
+use near_store::{create_store, create_store_with_config, Store};
 use near_telemetry::TelemetryActor;
 
 pub use crate::config::{init_configs, load_config, load_test_config, NearConfig, NEAR_BASE};
@@ -40,10 +41,17 @@ pub use crate::runtime::NightshadeRuntime;
 pub use crate::shard_tracker::TrackedConfig;
 
 pub mod append_only_map;
+pub mod build_features;
 pub mod config;
+pub mod config_schema;
+pub mod epoch_hooks;
+pub mod head_monitor;
+pub mod memory_budget;
 pub mod migrations;
 mod runtime;
 mod shard_tracker;
+pub mod signing_policy;
+pub mod webhooks;
 
 const STORE_PATH: &str = "data";
 
@@ -256,6 +264,50 @@ pub fn apply_store_migrations(path: &Path, near_config: &NearConfig) {
         info!(target: "near", "Migrate DB from version 29 to 30");
         migrate_29_to_30(&path);
     }
+    if db_version <= 30 {
+        // version 30 => 31: add ColBlockTransactionStats
+        // Does not need to do anything since open db with option `create_missing_column_families`
+        // Nevertheless need to bump db version, because db_version 1 binary can't open db_version 2 db
+        info!(target: "near", "Migrate DB from version 30 to 31");
+        let store = create_store(&path);
+        set_store_version(&store, 31);
+    }
+
+    if db_version <= 31 {
+        // version 31 => 32: add ColFailedReceipts
+        // Does not need to do anything since open db with option `create_missing_column_families`
+        // Nevertheless need to bump db version, because db_version 1 binary can't open db_version 2 db
+        info!(target: "near", "Migrate DB from version 31 to 32");
+        let store = create_store(&path);
+        set_store_version(&store, 32);
+    }
+
+    if db_version <= 32 {
+        // version 32 => 33: add ColAccessKeyUsage
+        // Does not need to do anything since open db with option `create_missing_column_families`
+        // Nevertheless need to bump db version, because db_version 1 binary can't open db_version 2 db
+        info!(target: "near", "Migrate DB from version 32 to 33");
+        let store = create_store(&path);
+        set_store_version(&store, 33);
+    }
+
+    if db_version <= 33 {
+        // version 33 => 34: add ColContractHistory
+        // Does not need to do anything since open db with option `create_missing_column_families`
+        // Nevertheless need to bump db version, because db_version 1 binary can't open db_version 2 db
+        info!(target: "near", "Migrate DB from version 33 to 34");
+        let store = create_store(&path);
+        set_store_version(&store, 34);
+    }
+
+    if db_version <= 34 {
+        // version 34 => 35: add ColEpochChainStats and ColEpochSigners
+        // Does not need to do anything since open db with option `create_missing_column_families`
+        // Nevertheless need to bump db version, because db_version 1 binary can't open db_version 2 db
+        info!(target: "near", "Migrate DB from version 34 to 35");
+        let store = create_store(&path);
+        set_store_version(&store, 35);
+    }
 
     #[cfg(feature = "nightly_protocol")]
     {
@@ -278,7 +330,7 @@ pub fn init_and_migrate_store(home_dir: &Path, near_config: &NearConfig) -> Arc<
     if store_exists {
         apply_store_migrations(&path, near_config);
     }
-    let store = create_store(&path);
+    let store = create_store_with_config(&path, &near_config.store_config);
     if !store_exists {
         set_store_version(&store, near_primitives::version::DB_VERSION);
     }
@@ -292,7 +344,16 @@ pub struct NearNode {
     pub rpc_servers: Vec<(&'static str, actix_web::dev::Server)>,
 }
 
+impl NearNode {
+    /// Typed API over the client actors, for programs embedding the node.
+    pub fn client_api(&self) -> ClientApi {
+        ClientApi::new(self.client.clone(), self.view_client.clone())
+    }
+}
+
 pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
+    near_primitives::features::register_build_features(build_features::BUILD_FEATURES);
+    memory_budget::check_memory_budget(&config);
     let store = init_and_migrate_store(home_dir, &config);
 
     let runtime = Arc::new(NightshadeRuntime::with_config(
@@ -324,8 +385,10 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
     #[cfg(feature = "test_features")]
     let adv = Arc::new(std::sync::RwLock::new(AdversarialControls::default()));
 
+    let validator_account_id =
+        config.validator_signer.as_ref().map(|signer| signer.validator_id().clone());
     let view_client = start_view_client(
-        config.validator_signer.as_ref().map(|signer| signer.validator_id().clone()),
+        validator_account_id.clone(),
         chain_genesis.clone(),
         view_client_runtime,
         network_adapter.clone(),
@@ -394,6 +457,22 @@ pub fn start_with_config(home_dir: &Path, config: NearConfig) -> NearNode {
         ));
     }
 
+    if !config.webhooks_config.is_empty() {
+        actix::spawn(webhooks::run_webhooks(config.webhooks_config, view_client.clone()));
+    }
+
+    if let Some(head_monitor_config) = config.head_monitor_config {
+        actix::spawn(head_monitor::run_head_monitor(head_monitor_config, view_client.clone()));
+    }
+
+    if let Some(epoch_hooks_config) = config.epoch_hooks_config {
+        actix::spawn(epoch_hooks::run_epoch_hooks(
+            epoch_hooks_config,
+            view_client.clone(),
+            validator_account_id,
+        ));
+    }
+
     network_adapter.set_recipient(network_actor.recipient());
 
     rpc_servers.shrink_to_fit();
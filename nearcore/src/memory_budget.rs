@@ -0,0 +1,142 @@
+//! Rough check at startup that the node fits in the memory of the machine, with recommended
+//! config adjustments when it doesn't. Meant for single-board computers and other small
+//! devices, where running out of memory gets the node killed.
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use near_store::StoreConfig;
+
+use crate::config::NearConfig;
+
+const MIB: u64 = 1 << 20;
+const GIB: u64 = 1 << 30;
+
+/// Allowance for the memory the node uses besides the database: tries, chain caches, compiled
+/// contracts and network buffers.
+const BASELINE_MEMORY: u64 = 4 * GIB;
+/// Memory left to the page cache, which RocksDB relies on for the data not in its own caches.
+const PAGE_CACHE_HEADROOM: u64 = 2 * GIB;
+
+/// Sizes of the database caches, from the largest to the smallest.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryPreset {
+    /// For servers, with 16GB of memory or more.
+    Default,
+    /// For devices with 8 to 16GB of memory.
+    Reduced,
+    /// For devices with 8GB of memory.
+    Minimal,
+}
+
+impl MemoryPreset {
+    const ALL: [MemoryPreset; 3] =
+        [MemoryPreset::Default, MemoryPreset::Reduced, MemoryPreset::Minimal];
+
+    pub fn store_config(self) -> StoreConfig {
+        match self {
+            MemoryPreset::Default => StoreConfig::default(),
+            MemoryPreset::Reduced => StoreConfig {
+                max_open_files: 256,
+                col_state_cache_size: 256 * MIB,
+                col_cache_size: 16 * MIB,
+                col_memtable_budget: 64 * MIB,
+                ..StoreConfig::default()
+            },
+            MemoryPreset::Minimal => StoreConfig {
+                max_open_files: 128,
+                col_state_cache_size: 128 * MIB,
+                col_cache_size: 8 * MIB,
+                col_memtable_budget: 32 * MIB,
+                ..StoreConfig::default()
+            },
+        }
+    }
+
+    /// Largest preset with which the node fits in `total_memory`.
+    pub fn recommended(total_memory: u64) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|preset| required_memory(&preset.store_config()) <= total_memory)
+    }
+}
+
+fn required_memory(store_config: &StoreConfig) -> u64 {
+    store_config.memory_estimate() + BASELINE_MEMORY + PAGE_CACHE_HEADROOM
+}
+
+fn format_gib(bytes: u64) -> String {
+    format!("{:.1}GiB", bytes as f64 / GIB as f64)
+}
+
+/// Total memory of the machine, read from `/proc/meminfo` on Linux.
+fn total_memory() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    meminfo.lines().find_map(|line| {
+        let kib = line.strip_prefix("MemTotal:")?.trim().strip_suffix("kB")?.trim();
+        kib.parse::<u64>().ok().map(|kib| kib * 1024)
+    })
+}
+
+/// Config adjustments to make the node fit in `total_memory`.
+fn recommendations(config: &NearConfig, total_memory: u64) -> Vec<String> {
+    let mut recommendations = vec![];
+    match MemoryPreset::recommended(total_memory) {
+        Some(preset) => {
+            if preset.store_config().memory_estimate() < config.store_config.memory_estimate() {
+                recommendations.push(format!(
+                    "set `memory_preset` to {} and remove the `store` section",
+                    serde_json::to_string(&preset).unwrap()
+                ));
+            }
+        }
+        None => recommendations.push(format!(
+            "run the node on a machine with at least {} of memory",
+            format_gib(required_memory(&MemoryPreset::Minimal.store_config()))
+        )),
+    }
+    if config.client_config.archive {
+        recommendations.push("set `archive` to false, archival nodes need more memory".to_string());
+    }
+    if !config.client_config.tracked_shards.is_empty() {
+        recommendations.push("empty `tracked_shards` to only track the shards needed".to_string());
+    }
+    #[cfg(feature = "json_rpc")]
+    if config.validator_signer.is_some() && config.rpc_config.is_some() {
+        recommendations.push("remove the `rpc` section to not serve the JSON RPC".to_string());
+    }
+    recommendations
+}
+
+/// Logs the memory the node is expected to need, and warns with recommended config adjustments
+/// when the machine doesn't have that much.
+pub fn check_memory_budget(config: &NearConfig) {
+    let total_memory = match total_memory() {
+        Some(total_memory) => total_memory,
+        None => return,
+    };
+    let required = required_memory(&config.store_config);
+    info!(target: "near", "Memory budget: about {} needed, {} available", format_gib(required), format_gib(total_memory));
+    if required <= total_memory {
+        return;
+    }
+    warn!(target: "near", "The node may run out of memory, it needs about {} and the machine only has {}", format_gib(required), format_gib(total_memory));
+    for recommendation in recommendations(config, total_memory) {
+        warn!(target: "near", "To use less memory, {}", recommendation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommended_preset() {
+        assert_eq!(MemoryPreset::recommended(64 * GIB), Some(MemoryPreset::Default));
+        assert_eq!(MemoryPreset::recommended(8 * GIB + GIB / 2), Some(MemoryPreset::Reduced));
+        assert_eq!(MemoryPreset::recommended(7 * GIB + GIB / 2), Some(MemoryPreset::Minimal));
+        assert_eq!(MemoryPreset::recommended(4 * GIB), None);
+    }
+}
@@ -2,6 +2,7 @@ use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
+use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
 use borsh::ser::BorshSerialize;
@@ -10,7 +11,8 @@ use tracing::{debug, error, info, warn};
 
 use near_chain::chain::NUM_EPOCHS_TO_KEEP_STORE_DATA;
 use near_chain::types::{
-    ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo, ValidatorInfoIdentifier,
+    ApplySplitStateResult, ApplyTransactionResult, BlockHeaderInfo, SimulateTransactionResult,
+    TransactionFees, ValidatorInfoIdentifier,
 };
 use near_chain::{BlockHeader, Doomslug, DoomslugThresholdMode, Error, ErrorKind, RuntimeAdapter};
 use near_chain_configs::{Genesis, GenesisConfig, ProtocolConfig};
@@ -29,7 +31,7 @@ use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::receipt::Receipt;
 use near_primitives::sharding::ChunkHash;
 use near_primitives::state_record::{state_record_to_account_id, StateRecord};
-use near_primitives::transaction::SignedTransaction;
+use near_primitives::transaction::{SignedTransaction, Transaction};
 use near_primitives::types::validator_stake::{ValidatorStake, ValidatorStakeIter};
 use near_primitives::types::{
     AccountId, ApprovalStake, Balance, BlockHeight, CompiledContractCache, EpochHeight, EpochId,
@@ -46,9 +48,10 @@ use near_vm_runner::precompile_contract;
 use near_store::{
     get_genesis_hash, get_genesis_state_roots, set_genesis_hash, set_genesis_state_roots,
     ApplyStatePartResult, ColState, PartialStorage, ShardTries, Store, StoreCompiledContractCache,
-    StoreUpdate, Trie, WrappedTrieChanges,
+    StoreUpdate, Trie, TrieUpdate, WrappedTrieChanges,
 };
 use node_runtime::adapter::ViewRuntimeAdapter;
+use node_runtime::config::{total_prepaid_gas, total_storage_usage_increase, tx_cost};
 use node_runtime::state_viewer::TrieViewer;
 use node_runtime::{
     validate_transaction, verify_and_charge_transaction, ApplyState, Runtime,
@@ -71,10 +74,13 @@ use node_runtime::near_primitives::shard_layout::ShardLayoutError;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 pub mod errors;
+mod shadow;
 
 const POISONED_LOCK_ERR: &str = "The lock was poisoned.";
 const STATE_DUMP_FILE: &str = "state_dump";
 const GENESIS_ROOTS_FILE: &str = "genesis_roots";
+/// Most receipts executed by the dry run of a transaction.
+const MAX_SIMULATED_RECEIPTS: usize = 200;
 
 /// Wrapper type for epoch manager to get avoid implementing trait for foreign types.
 pub struct SafeEpochManager(pub Arc<RwLock<EpochManager>>);
@@ -141,6 +147,9 @@ pub struct NightshadeRuntime {
     shard_tracker: ShardTracker,
     genesis_state_roots: Vec<StateRoot>,
     migration_data: Arc<MigrationData>,
+    /// If set, new chunks are applied again with this protocol version and the differences
+    /// with the actual result are reported.
+    shadow_protocol_version: Option<ProtocolVersion>,
 }
 
 impl NightshadeRuntime {
@@ -151,7 +160,7 @@ impl NightshadeRuntime {
         trie_viewer_state_size_limit: Option<u64>,
         max_gas_burnt_view: Option<Gas>,
     ) -> Self {
-        Self::new(
+        let mut runtime = Self::new(
             home_dir,
             store,
             &config.genesis,
@@ -159,7 +168,9 @@ impl NightshadeRuntime {
             trie_viewer_state_size_limit,
             max_gas_burnt_view,
             None,
-        )
+        );
+        runtime.shadow_protocol_version = config.config.shadow_protocol_version;
+        runtime
     }
 
     pub fn new(
@@ -209,6 +220,7 @@ impl NightshadeRuntime {
             shard_tracker,
             genesis_state_roots: state_roots,
             migration_data: Arc::new(load_migration_data(&genesis.config.chain_id)),
+            shadow_protocol_version: None,
         }
     }
 
@@ -233,6 +245,87 @@ impl NightshadeRuntime {
         Self::test_with_runtime_config_store(home_dir, store, genesis, RuntimeConfigStore::test())
     }
 
+    /// Applies a chunk again with `shadow_protocol_version` on top of the same state and reports
+    /// how the result differs from `result`. Nothing is committed. Chunks of epochs already
+    /// running the shadow version or a newer one are skipped.
+    fn shadow_apply_transactions(
+        &self,
+        shadow_protocol_version: ProtocolVersion,
+        shard_id: ShardId,
+        state_root: &StateRoot,
+        height: BlockHeight,
+        block_timestamp: u64,
+        prev_block_hash: &CryptoHash,
+        block_hash: &CryptoHash,
+        receipts: &[Receipt],
+        transactions: &[SignedTransaction],
+        last_validator_proposals: ValidatorStakeIter,
+        gas_price: Balance,
+        gas_limit: Gas,
+        challenges: &ChallengesResult,
+        random_seed: CryptoHash,
+        result: &ApplyTransactionResult,
+    ) {
+        let protocol_version = match self
+            .get_epoch_id_from_prev_block(prev_block_hash)
+            .and_then(|epoch_id| self.get_epoch_protocol_version(&epoch_id))
+        {
+            Ok(protocol_version) => protocol_version,
+            Err(err) => {
+                warn!(target: "runtime", "Failed to get protocol version for shadow apply: {}", err);
+                return;
+            }
+        };
+        if protocol_version >= shadow_protocol_version {
+            return;
+        }
+        let trie = match self.get_trie_for_shard(shard_id, prev_block_hash) {
+            Ok(trie) => trie,
+            Err(err) => {
+                warn!(target: "runtime", "Failed to get trie for shadow apply: {}", err);
+                return;
+            }
+        };
+        // The runtime panics on some errors, which the newer version may trigger.
+        let shadow_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.process_state_update(
+                trie,
+                *state_root,
+                shard_id,
+                height,
+                block_hash,
+                block_timestamp,
+                prev_block_hash,
+                receipts,
+                transactions,
+                last_validator_proposals,
+                gas_price,
+                gas_limit,
+                challenges,
+                random_seed,
+                true,
+                false,
+                None,
+                Some(shadow_protocol_version),
+            )
+        }))
+        .map_err(|panic| {
+            panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default()
+        });
+        shadow::report_shadow_result(
+            shard_id,
+            height,
+            protocol_version,
+            shadow_protocol_version,
+            result,
+            shadow_result,
+        );
+    }
+
     fn get_epoch_height_from_prev_block(
         &self,
         prev_block_hash: &CryptoHash,
@@ -436,6 +529,7 @@ impl NightshadeRuntime {
         is_new_chunk: bool,
         is_first_block_with_chunk_of_version: bool,
         states_to_patch: Option<Vec<StateRecord>>,
+        protocol_version_override: Option<ProtocolVersion>,
     ) -> Result<ApplyTransactionResult, Error> {
         let _span = tracing::debug_span!(target: "runtime", "process_state_update").entered();
         let epoch_id = self.get_epoch_id_from_prev_block(prev_block_hash)?;
@@ -521,6 +615,19 @@ impl NightshadeRuntime {
         let current_protocol_version = self.get_epoch_protocol_version(&epoch_id)?;
         let prev_block_protocol_version = self.get_epoch_protocol_version(&prev_block_epoch_id)?;
         let is_first_block_of_version = current_protocol_version != prev_block_protocol_version;
+        // Migrations are only run by the actual upgrade, not when overriding the version.
+        let (
+            current_protocol_version,
+            is_first_block_of_version,
+            is_first_block_with_chunk_of_version,
+        ) = match protocol_version_override {
+            Some(protocol_version) => (protocol_version, false, false),
+            None => (
+                current_protocol_version,
+                is_first_block_of_version,
+                is_first_block_with_chunk_of_version,
+            ),
+        };
 
         debug!(target: "runtime",
                "epoch height: {:?}, epoch id: {:?}, current_protocol_version: {:?}, is_first_block_of_version: {}",
@@ -1362,6 +1469,7 @@ impl RuntimeAdapter for NightshadeRuntime {
     ) -> Result<ApplyTransactionResult, Error> {
         let trie = self.get_trie_for_shard(shard_id, prev_block_hash)?;
         let trie = if generate_storage_proof { trie.recording_reads() } else { trie };
+        let shadow_validator_proposals = last_validator_proposals.clone();
         match self.process_state_update(
             trie,
             *state_root,
@@ -1380,8 +1488,32 @@ impl RuntimeAdapter for NightshadeRuntime {
             is_new_chunk,
             is_first_block_with_chunk_of_version,
             states_to_patch,
+            None,
         ) {
-            Ok(result) => Ok(result),
+            Ok(result) => {
+                if let Some(shadow_protocol_version) =
+                    self.shadow_protocol_version.filter(|_| is_new_chunk)
+                {
+                    self.shadow_apply_transactions(
+                        shadow_protocol_version,
+                        shard_id,
+                        state_root,
+                        height,
+                        block_timestamp,
+                        prev_block_hash,
+                        block_hash,
+                        receipts,
+                        transactions,
+                        shadow_validator_proposals,
+                        gas_price,
+                        gas_limit,
+                        challenges,
+                        random_seed,
+                        &result,
+                    );
+                }
+                Ok(result)
+            }
             Err(e) => match e.kind() {
                 ErrorKind::StorageError(_) => {
                     panic!("{}", e);
@@ -1429,6 +1561,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             is_new_chunk,
             is_first_block_with_chunk_of_version,
             None,
+            None,
         )
     }
 
@@ -1528,6 +1661,29 @@ impl RuntimeAdapter for NightshadeRuntime {
                     block_hash: *block_hash,
                 })
             }
+            QueryRequest::ViewStatePage { account_id, prefix, start_after, limit } => {
+                let view_state_result = self
+                    .view_state_page(
+                        &shard_uid,
+                        *state_root,
+                        account_id,
+                        prefix.as_ref(),
+                        start_after.as_deref(),
+                        *limit,
+                    )
+                    .map_err(|err| {
+                        near_chain::near_chain_primitives::error::QueryError::from_view_state_error(
+                            err,
+                            block_height,
+                            *block_hash,
+                        )
+                    })?;
+                Ok(QueryResponse {
+                    kind: QueryResponseKind::ViewState(view_state_result),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
             QueryRequest::ViewAccessKeyList { account_id } => {
                 let access_key_list =
                     self.view_access_keys(&shard_uid, *state_root, account_id).map_err(|err| {
@@ -1570,6 +1726,105 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn simulate_transaction(
+        &self,
+        block_header: &BlockHeader,
+        state_roots: HashMap<ShardId, StateRoot>,
+        transaction: &SignedTransaction,
+        verify_signature: bool,
+    ) -> Result<Result<SimulateTransactionResult, InvalidTxError>, Error> {
+        let block_hash = block_header.hash();
+        let epoch_id = self.get_epoch_id_from_prev_block(block_hash)?;
+        let epoch_height = self.get_epoch_height_from_prev_block(block_hash)?;
+        let current_protocol_version = self.get_epoch_protocol_version(&epoch_id)?;
+        // The state roots are those of the shards of the epoch of the block.
+        let shard_layout = self.get_shard_layout(block_header.epoch_id())?;
+        let apply_state = ApplyState {
+            block_index: block_header.height() + 1,
+            prev_block_hash: *block_hash,
+            block_hash: CryptoHash::default(),
+            epoch_id,
+            epoch_height,
+            gas_price: block_header.gas_price(),
+            block_timestamp: block_header.raw_timestamp(),
+            gas_limit: None,
+            random_seed: *block_header.random_value(),
+            current_protocol_version,
+            config: self.runtime_config_store.get_config(current_protocol_version).clone(),
+            cache: Some(Arc::new(StoreCompiledContractCache { store: self.store.clone() })),
+            is_new_chunk: true,
+            migration_data: Arc::clone(&self.migration_data),
+            migration_flags: MigrationFlags::default(),
+        };
+        let state_updates = state_roots
+            .into_iter()
+            .map(|(shard_id, state_root)| {
+                let shard_uid = ShardUId::from_shard_id_and_layout(shard_id, &shard_layout);
+                (shard_id, self.tries.new_trie_update(shard_uid, state_root))
+            })
+            .collect();
+        let shard_id_of =
+            |account_id: &AccountId| account_id_to_shard_id(account_id, &shard_layout);
+        match self.runtime.simulate_transaction(
+            state_updates,
+            &shard_id_of,
+            &apply_state,
+            transaction,
+            verify_signature,
+            &self.epoch_manager,
+            MAX_SIMULATED_RECEIPTS,
+        ) {
+            Ok(result) => Ok(Ok(SimulateTransactionResult {
+                outcomes: result.outcomes,
+                receipts: result.receipts,
+                unexecuted_receipts: result.unexecuted_receipts,
+                state_changes: result.state_changes,
+            })),
+            Err(RuntimeError::InvalidTxError(err)) => Ok(Err(err)),
+            Err(RuntimeError::StorageError(err)) => Err(ErrorKind::StorageError(err).into()),
+            Err(RuntimeError::ValidatorError(err)) => Err(err.into()),
+            Err(err) => {
+                Err(ErrorKind::Other(format!("Failed to simulate the transaction: {:?}", err))
+                    .into())
+            }
+        }
+    }
+
+    fn get_transaction_fees(
+        &self,
+        epoch_id: &EpochId,
+        transaction: &Transaction,
+        gas_price: Balance,
+    ) -> Result<TransactionFees, Error> {
+        let current_protocol_version = self.get_epoch_protocol_version(epoch_id)?;
+        let config = self.runtime_config_store.get_config(current_protocol_version);
+        let fees_config = &config.transaction_costs;
+        let sender_is_receiver = transaction.signer_id == transaction.receiver_id;
+        let overflow = |_| ErrorKind::Other("Integer overflow in the fees".to_string());
+        let cost = tx_cost(
+            fees_config,
+            transaction,
+            gas_price,
+            sender_is_receiver,
+            current_protocol_version,
+        )
+        .map_err(overflow)?;
+        let prepaid_gas = total_prepaid_gas(&transaction.actions).map_err(overflow)?;
+        let storage_usage_increase =
+            total_storage_usage_increase(&fees_config.storage_usage_config, &transaction.actions)
+                .map_err(overflow)?;
+        Ok(TransactionFees {
+            send_gas: cost.gas_burnt,
+            exec_gas: cost.gas_remaining - prepaid_gas,
+            prepaid_gas,
+            receipt_gas_price: cost.receipt_gas_price,
+            total_cost: cost.total_cost,
+            storage_usage_increase,
+            storage_cost: Balance::from(storage_usage_increase)
+                .saturating_mul(config.storage_amount_per_byte),
+        })
+    }
+
     fn get_validator_info(
         &self,
         epoch_id: ValidatorInfoIdentifier,
@@ -1931,6 +2186,20 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         let state_update = self.tries.new_trie_update_view(*shard_uid, state_root);
         self.trie_viewer.view_state(&state_update, account_id, prefix)
     }
+
+    fn view_state_page(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        account_id: &AccountId,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: u64,
+    ) -> Result<ViewStateResult, node_runtime::state_viewer::errors::ViewStateError> {
+        let trie = self.tries.get_view_trie_for_shard(*shard_uid).recording_reads();
+        let state_update = TrieUpdate::new(Rc::new(trie), state_root);
+        self.trie_viewer.view_state_page(&state_update, account_id, prefix, start_after, limit)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,168 @@
+//! Shadow execution of chunks with a newer protocol version.
+//!
+//! The runtime keeps the parameters and the feature checks of every protocol version it
+//! supports, so a single binary can apply a chunk with the version of the current epoch and
+//! again with the next one. Canary nodes do so for every new chunk they apply and report the
+//! chunks for which the two results diverge. The shadow result is never committed.
+
+use near_chain::types::ApplyTransactionResult;
+use near_chain::Error;
+use near_metrics::{try_create_int_counter, IntCounter};
+use near_primitives::transaction::ExecutionOutcomeWithId;
+use near_primitives::types::{BlockHeight, ShardId};
+use near_primitives::version::ProtocolVersion;
+use once_cell::sync::Lazy;
+use tracing::{debug, warn};
+
+static SHADOW_APPLY_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_protocol_shadow_apply_total",
+        "Number of chunks applied again with the shadow protocol version",
+    )
+    .unwrap()
+});
+static SHADOW_DIVERGENCE_TOTAL: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "near_protocol_shadow_divergence_total",
+        "Number of chunks for which the shadow protocol version produced a different result",
+    )
+    .unwrap()
+});
+
+/// Logs and counts the differences between the result of applying a chunk with the protocol
+/// version of the epoch and the result of applying it with the shadow version.
+pub(crate) fn report_shadow_result(
+    shard_id: ShardId,
+    height: BlockHeight,
+    protocol_version: ProtocolVersion,
+    shadow_protocol_version: ProtocolVersion,
+    result: &ApplyTransactionResult,
+    shadow_result: Result<Result<ApplyTransactionResult, Error>, String>,
+) {
+    SHADOW_APPLY_TOTAL.inc();
+    let differences = match shadow_result {
+        Ok(Ok(shadow_result)) => diff_apply_results(result, &shadow_result),
+        Ok(Err(err)) => vec![format!("shadow apply failed: {}", err)],
+        Err(panic) => vec![format!("shadow apply panicked: {}", panic)],
+    };
+    if differences.is_empty() {
+        debug!(target: "runtime", shard_id, height, shadow_protocol_version, "Shadow apply matches");
+        return;
+    }
+    SHADOW_DIVERGENCE_TOTAL.inc();
+    for difference in differences {
+        warn!(
+            target: "runtime",
+            shard_id,
+            height,
+            protocol_version,
+            shadow_protocol_version,
+            "Shadow apply diverged: {}",
+            difference
+        );
+    }
+}
+
+fn diff_apply_results(
+    result: &ApplyTransactionResult,
+    shadow_result: &ApplyTransactionResult,
+) -> Vec<String> {
+    let mut differences = diff_outcomes(&result.outcomes, &shadow_result.outcomes);
+    if result.new_root != shadow_result.new_root {
+        differences.push(format!("state root {} != {}", result.new_root, shadow_result.new_root));
+    }
+    if result.total_gas_burnt != shadow_result.total_gas_burnt {
+        differences.push(format!(
+            "gas burnt {} != {}",
+            result.total_gas_burnt, shadow_result.total_gas_burnt
+        ));
+    }
+    if result.total_balance_burnt != shadow_result.total_balance_burnt {
+        differences.push(format!(
+            "balance burnt {} != {}",
+            result.total_balance_burnt, shadow_result.total_balance_burnt
+        ));
+    }
+    let receipt_ids = |result: &ApplyTransactionResult| -> Vec<_> {
+        result.outgoing_receipts.iter().map(|receipt| receipt.receipt_id).collect()
+    };
+    if receipt_ids(result) != receipt_ids(shadow_result) {
+        differences.push(format!(
+            "{} outgoing receipts != {} outgoing receipts",
+            result.outgoing_receipts.len(),
+            shadow_result.outgoing_receipts.len()
+        ));
+    }
+    if result.validator_proposals != shadow_result.validator_proposals {
+        differences.push("validator proposals differ".to_string());
+    }
+    differences
+}
+
+/// Compares the observable parts of the outcomes. The metadata is left out since its version
+/// follows the protocol version.
+fn diff_outcomes(
+    outcomes: &[ExecutionOutcomeWithId],
+    shadow_outcomes: &[ExecutionOutcomeWithId],
+) -> Vec<String> {
+    let mut differences = vec![];
+    if outcomes.len() != shadow_outcomes.len() {
+        differences.push(format!(
+            "{} outcomes != {} outcomes",
+            outcomes.len(),
+            shadow_outcomes.len()
+        ));
+    }
+    for (outcome, shadow_outcome) in outcomes.iter().zip(shadow_outcomes.iter()) {
+        let (id, outcome, shadow_outcome) = (outcome.id, &outcome.outcome, &shadow_outcome.outcome);
+        if outcome.status != shadow_outcome.status {
+            differences.push(format!(
+                "outcome {} status {:?} != {:?}",
+                id, outcome.status, shadow_outcome.status
+            ));
+        }
+        if outcome.gas_burnt != shadow_outcome.gas_burnt {
+            differences.push(format!(
+                "outcome {} gas burnt {} != {}",
+                id, outcome.gas_burnt, shadow_outcome.gas_burnt
+            ));
+        }
+        if outcome.receipt_ids != shadow_outcome.receipt_ids || outcome.logs != shadow_outcome.logs
+        {
+            differences.push(format!("outcome {} receipts or logs differ", id));
+        }
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_primitives::hash::hash;
+    use near_primitives::transaction::{ExecutionOutcome, ExecutionStatus};
+
+    fn outcome(id: u8, gas_burnt: u64, status: ExecutionStatus) -> ExecutionOutcomeWithId {
+        ExecutionOutcomeWithId {
+            id: hash(&[id]),
+            outcome: ExecutionOutcome { gas_burnt, status, ..Default::default() },
+        }
+    }
+
+    #[test]
+    fn test_diff_outcomes() {
+        let outcomes = vec![
+            outcome(0, 100, ExecutionStatus::SuccessValue(vec![])),
+            outcome(1, 200, ExecutionStatus::SuccessValue(vec![])),
+        ];
+        assert!(diff_outcomes(&outcomes, &outcomes).is_empty());
+
+        let shadow_outcomes = vec![
+            outcome(0, 100, ExecutionStatus::SuccessValue(vec![])),
+            outcome(1, 300, ExecutionStatus::SuccessValue(vec![])),
+            outcome(2, 300, ExecutionStatus::SuccessValue(vec![])),
+        ];
+        let differences = diff_outcomes(&outcomes, &shadow_outcomes);
+        assert_eq!(differences.len(), 2, "{:?}", differences);
+        assert!(differences[1].contains("gas burnt 200 != 300"));
+    }
+}
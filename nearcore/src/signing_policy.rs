@@ -0,0 +1,118 @@
+//! Approval of the validator key by a `CompositeSigner` before the node signs anything with it.
+//!
+//! With `validator_signing_policy` set, loading the validator key asks the approver commands, in
+//! order, to agree to its use, and the node refuses to start unless `threshold` of them do. Each
+//! command gets the message to approve on its stdin, which names the account and the public key
+//! of the validator key, and approves it by exiting successfully: it may ask an operator for
+//! confirmation, check a second key holder's signature or consult a policy file.
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use near_crypto::{CompositeSigner, InMemorySigner, SigningApprover, SigningPolicyError};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SigningPolicyConfig {
+    /// Commands asked to approve the validator key, each the program followed by its arguments.
+    pub approvers: Vec<Vec<String>>,
+    /// Approvals required, at least one and at most the number of approvers.
+    pub threshold: usize,
+}
+
+/// Approver running a command, which gets the data on its stdin and approves by succeeding.
+struct CommandApprover {
+    program: String,
+    args: Vec<String>,
+}
+
+impl SigningApprover for CommandApprover {
+    fn approve(&self, data: &[u8]) -> bool {
+        let result =
+            Command::new(&self.program).args(&self.args).stdin(Stdio::piped()).spawn().and_then(
+                |mut child| {
+                    // The command may exit without reading its stdin.
+                    let _ = child.stdin.take().map(|mut stdin| stdin.write_all(data));
+                    child.wait()
+                },
+            );
+        match result {
+            Ok(status) => {
+                if !status.success() {
+                    warn!(target: "near", "Approver {} rejected the signing with {}", self.program, status);
+                }
+                status.success()
+            }
+            Err(err) => {
+                warn!(target: "near", "Failed to run approver {}: {}", self.program, err);
+                false
+            }
+        }
+    }
+}
+
+/// Message the approvers of the validator key are asked to approve.
+fn validator_key_message(signer: &InMemorySigner) -> String {
+    format!("Use validator key {} of {}", signer.public_key, signer.account_id)
+}
+
+/// Asks the approvers of the policy to agree to the use of the validator key.
+pub(crate) fn approve_validator_key(
+    policy: &SigningPolicyConfig,
+    signer: InMemorySigner,
+) -> Result<(), SigningPolicyError> {
+    let approvers = policy
+        .approvers
+        .iter()
+        .filter_map(|command| command.split_first())
+        .map(|(program, args)| {
+            Box::new(CommandApprover { program: program.clone(), args: args.to_vec() })
+                as Box<dyn SigningApprover>
+        })
+        .collect();
+    let message = validator_key_message(&signer);
+    let composite_signer = CompositeSigner::new(Arc::new(signer), approvers, policy.threshold)?;
+    let signature = composite_signer.try_sign(message.as_bytes())?;
+    info!(target: "near", "{}: approved, signed {}", message, signature);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::KeyType;
+
+    fn signer() -> InMemorySigner {
+        InMemorySigner::from_seed("test".parse().unwrap(), KeyType::ED25519, "test")
+    }
+
+    fn command(program: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), program.to_string()]
+    }
+
+    #[test]
+    fn test_approve_validator_key() {
+        let policy = SigningPolicyConfig {
+            approvers: vec![command("exit 1"), command("grep -q 'of test$'")],
+            threshold: 1,
+        };
+        assert!(approve_validator_key(&policy, signer()).is_ok());
+
+        let policy = SigningPolicyConfig {
+            approvers: vec![command("exit 1"), command("cat > /dev/null")],
+            threshold: 2,
+        };
+        assert!(matches!(
+            approve_validator_key(&policy, signer()),
+            Err(SigningPolicyError::NotEnoughApprovals { approvals: 1, threshold: 2 })
+        ));
+
+        let policy = SigningPolicyConfig { approvers: vec![], threshold: 1 };
+        assert!(matches!(
+            approve_validator_key(&policy, signer()),
+            Err(SigningPolicyError::InvalidThreshold { threshold: 1, num_approvers: 0 })
+        ));
+    }
+}
@@ -0,0 +1,426 @@
+//! Node-local webhook notifications about included transactions.
+//!
+//! Operators register endpoints together with filters on the signer, receiver and called method
+//! names.  The notifier follows the chain at the finality requested for each endpoint and, once
+//! a matching transaction has a final execution outcome, POSTs a short JSON summary of it.
+//! Requests are signed with HMAC-SHA256 when a secret is configured and are retried with an
+//! exponential backoff.
+use std::time::Duration;
+
+use actix::Addr;
+use awc::{Client, Connector};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use near_client::{GetBlock, GetChunk, TxStatus, ViewClientActor};
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::serialize::to_base64;
+use near_primitives::types::{
+    AccountId, BlockHeight, BlockId, BlockReference, Finality, Gas, ShardId,
+};
+use near_primitives::views::{
+    ActionView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus, SignedTransactionView,
+};
+
+/// Timeout for establishing connection and receiving a response.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum number of blocks scanned per endpoint on every poll, so that a notifier falling behind
+/// catches up gradually instead of flooding the view client.
+const MAX_BLOCKS_PER_POLL: BlockHeight = 100;
+/// Name of the header holding the base64 encoded HMAC-SHA256 signature of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Near-Signature";
+
+fn default_poll_interval() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+fn default_retry_delay() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_outcome_wait_blocks() -> BlockHeight {
+    100
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WebhookFilter {
+    /// Only notify about transactions signed by one of these accounts.  Empty means any signer.
+    #[serde(default)]
+    pub signer_ids: Vec<AccountId>,
+    /// Only notify about transactions sent to one of these accounts.  Empty means any receiver.
+    #[serde(default)]
+    pub receiver_ids: Vec<AccountId>,
+    /// Only notify about transactions calling at least one of these methods.  Empty means any
+    /// transaction, including ones without function calls.
+    #[serde(default)]
+    pub method_names: Vec<String>,
+}
+
+impl WebhookFilter {
+    pub fn matches(&self, transaction: &SignedTransactionView) -> bool {
+        if !self.signer_ids.is_empty() && !self.signer_ids.contains(&transaction.signer_id) {
+            return false;
+        }
+        if !self.receiver_ids.is_empty() && !self.receiver_ids.contains(&transaction.receiver_id) {
+            return false;
+        }
+        if !self.method_names.is_empty() {
+            return transaction.actions.iter().any(|action| match action {
+                ActionView::FunctionCall { method_name, .. } => {
+                    self.method_names.contains(method_name)
+                }
+                _ => false,
+            });
+        }
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhookEndpointConfig {
+    /// URL the notifications are POSTed to.
+    pub url: String,
+    /// Finality the block including the transaction must reach before notifying.
+    #[serde(default)]
+    pub finality: Finality,
+    #[serde(default)]
+    pub filter: WebhookFilter,
+    /// Secret used to sign request bodies.  Unsigned if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WebhooksConfig {
+    pub endpoints: Vec<WebhookEndpointConfig>,
+    /// How often the chain is checked for new blocks.
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval: Duration,
+    /// How many times a failed delivery is retried before the notification is dropped.
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each following attempt.
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: Duration,
+    /// For how many blocks to wait for a transaction to have a final execution outcome before
+    /// giving up on it.
+    #[serde(default = "default_outcome_wait_blocks")]
+    pub outcome_wait_blocks: BlockHeight,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![],
+            poll_interval: default_poll_interval(),
+            max_retries: default_max_retries(),
+            retry_delay: default_retry_delay(),
+            outcome_wait_blocks: default_outcome_wait_blocks(),
+        }
+    }
+}
+
+impl WebhooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+}
+
+/// Summary of a transaction outcome sent to the webhook endpoints.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TransactionNotification {
+    pub transaction_hash: CryptoHash,
+    pub signer_id: AccountId,
+    pub receiver_id: AccountId,
+    pub block_hash: CryptoHash,
+    pub block_height: BlockHeight,
+    pub shard_id: ShardId,
+    pub finality: Finality,
+    pub status: FinalExecutionStatus,
+    /// Gas burnt by the transaction and all of its receipts.
+    pub gas_burnt: Gas,
+}
+
+/// Transaction that matched an endpoint filter and is waiting for its final outcome.
+struct PendingTransaction {
+    endpoint: usize,
+    transaction: SignedTransactionView,
+    block_hash: CryptoHash,
+    block_height: BlockHeight,
+    shard_id: ShardId,
+}
+
+pub(crate) fn http_client() -> Client {
+    Client::builder()
+        .timeout(CONNECT_TIMEOUT)
+        .connector(Connector::new().max_http_version(actix_web::http::Version::HTTP_11))
+        .finish()
+}
+
+/// Computes HMAC-SHA256 (RFC 2104) of the `message` with the given `key`.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> CryptoHash {
+    const BLOCK_SIZE: usize = 64;
+    let mut key_block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        key_block[..32].copy_from_slice(hash(key).as_ref());
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+    let inner: Vec<u8> =
+        key_block.iter().map(|byte| byte ^ 0x36).chain(message.iter().cloned()).collect();
+    let inner_hash = hash(&inner);
+    let outer: Vec<u8> = key_block
+        .iter()
+        .map(|byte| byte ^ 0x5c)
+        .chain(inner_hash.as_ref().iter().cloned())
+        .collect();
+    hash(&outer)
+}
+
+/// POSTs the body to the URL, signed with the secret if any, and retries it on failure.
+pub(crate) async fn deliver(
+    client: Client,
+    url: String,
+    secret: Option<String>,
+    body: Vec<u8>,
+    max_retries: u32,
+    retry_delay: Duration,
+) {
+    let signature = secret.as_ref().map(|secret| to_base64(hmac_sha256(secret.as_bytes(), &body)));
+    let mut delay = retry_delay;
+    for attempt in 0..=max_retries {
+        let mut request = client.post(&url).insert_header(("Content-Type", "application/json"));
+        if let Some(signature) = &signature {
+            request = request.insert_header((SIGNATURE_HEADER, signature.as_str()));
+        }
+        match request.send_body(body.clone()).await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => {
+                debug!(target: "webhooks", "Endpoint {} responded with {} (attempt {})", url, response.status(), attempt);
+            }
+            Err(err) => {
+                debug!(target: "webhooks", "Failed to reach endpoint {}: {} (attempt {})", url, err, attempt);
+            }
+        }
+        if attempt < max_retries {
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+    warn!(target: "webhooks", "Dropping notification for {} after {} retries", url, max_retries);
+}
+
+async fn fetch_block_transactions(
+    view_client: &Addr<ViewClientActor>,
+    height: BlockHeight,
+) -> Option<(CryptoHash, Vec<(ShardId, SignedTransactionView)>)> {
+    // Skipped heights don't have blocks, which is reported as an error here.
+    let block = view_client
+        .send(GetBlock(BlockReference::BlockId(BlockId::Height(height))))
+        .await
+        .ok()?
+        .ok()?;
+    let mut transactions = vec![];
+    for chunk_header in block.chunks.iter() {
+        if chunk_header.height_included != block.header.height {
+            continue;
+        }
+        match view_client.send(GetChunk::ChunkHash(chunk_header.chunk_hash.into())).await {
+            Ok(Ok(chunk)) => transactions
+                .extend(chunk.transactions.into_iter().map(|tx| (chunk_header.shard_id, tx))),
+            Ok(Err(err)) => {
+                debug!(target: "webhooks", "Chunk {} is not available: {}", chunk_header.chunk_hash, err)
+            }
+            Err(err) => warn!(target: "webhooks", "Failed to fetch chunk: {}", err),
+        }
+    }
+    Some((block.header.hash, transactions))
+}
+
+/// Fetches the final execution outcome of the pending transaction and returns the notification
+/// to send, or `None` if the outcome is not known yet.
+async fn resolve_pending(
+    view_client: &Addr<ViewClientActor>,
+    endpoint: &WebhookEndpointConfig,
+    pending: &PendingTransaction,
+) -> Option<TransactionNotification> {
+    let outcome = view_client
+        .send(TxStatus {
+            tx_hash: pending.transaction.hash,
+            signer_account_id: pending.transaction.signer_id.clone(),
+            fetch_receipt: false,
+        })
+        .await
+        .ok()?
+        .ok()??;
+    let outcome = match outcome {
+        FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome) => outcome,
+        FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(outcome) => {
+            outcome.final_outcome
+        }
+    };
+    match outcome.status {
+        FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => return None,
+        FinalExecutionStatus::Failure(_) | FinalExecutionStatus::SuccessValue(_) => {}
+    }
+    let gas_burnt = outcome.transaction_outcome.outcome.gas_burnt
+        + outcome.receipts_outcome.iter().map(|receipt| receipt.outcome.gas_burnt).sum::<Gas>();
+    Some(TransactionNotification {
+        transaction_hash: pending.transaction.hash,
+        signer_id: pending.transaction.signer_id.clone(),
+        receiver_id: pending.transaction.receiver_id.clone(),
+        block_hash: pending.block_hash,
+        block_height: pending.block_height,
+        shard_id: pending.shard_id,
+        finality: endpoint.finality.clone(),
+        status: outcome.status,
+        gas_burnt,
+    })
+}
+
+/// Follows the chain and notifies the configured endpoints.  Runs until the view client stops.
+pub async fn run_webhooks(config: WebhooksConfig, view_client: Addr<ViewClientActor>) {
+    info!(target: "webhooks", "Starting webhook notifier for {} endpoint(s)", config.endpoints.len());
+    let client = http_client();
+    // Last scanned height for every endpoint.  Only blocks produced after the start are scanned.
+    let mut last_heights: Vec<Option<BlockHeight>> = vec![None; config.endpoints.len()];
+    let mut pending: Vec<PendingTransaction> = vec![];
+    let mut interval = tokio::time::interval(config.poll_interval);
+    loop {
+        interval.tick().await;
+        if !view_client.connected() {
+            info!(target: "webhooks", "View client stopped, stopping webhook notifier");
+            return;
+        }
+        for (index, endpoint) in config.endpoints.iter().enumerate() {
+            let head = match view_client
+                .send(GetBlock(BlockReference::Finality(endpoint.finality.clone())))
+                .await
+            {
+                Ok(Ok(block)) => block.header.height,
+                _ => continue,
+            };
+            let start = match last_heights[index] {
+                Some(last_height) => last_height + 1,
+                None => head,
+            };
+            let end = std::cmp::min(head, start.saturating_add(MAX_BLOCKS_PER_POLL - 1));
+            for height in start..=end {
+                if let Some((block_hash, transactions)) =
+                    fetch_block_transactions(&view_client, height).await
+                {
+                    for (shard_id, transaction) in transactions {
+                        if endpoint.filter.matches(&transaction) {
+                            pending.push(PendingTransaction {
+                                endpoint: index,
+                                transaction,
+                                block_hash,
+                                block_height: height,
+                                shard_id,
+                            });
+                        }
+                    }
+                }
+            }
+            if start <= end {
+                last_heights[index] = Some(end);
+            }
+        }
+
+        let mut still_pending = vec![];
+        for item in pending.drain(..) {
+            let endpoint = &config.endpoints[item.endpoint];
+            match resolve_pending(&view_client, endpoint, &item).await {
+                Some(notification) => {
+                    let body = serde_json::to_vec(&notification)
+                        .expect("serializing a notification can't fail");
+                    actix::spawn(deliver(
+                        client.clone(),
+                        endpoint.url.clone(),
+                        endpoint.secret.clone(),
+                        body,
+                        config.max_retries,
+                        config.retry_delay,
+                    ));
+                }
+                None => {
+                    let last_height = last_heights[item.endpoint].unwrap_or(item.block_height);
+                    if last_height < item.block_height + config.outcome_wait_blocks {
+                        still_pending.push(item);
+                    } else {
+                        warn!(target: "webhooks", "Outcome of transaction {} is not known after {} blocks, skipping it", item.transaction.hash, config.outcome_wait_blocks);
+                    }
+                }
+            }
+        }
+        pending = still_pending;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::transaction::{Action, FunctionCallAction, SignedTransaction};
+
+    use super::*;
+
+    fn function_call_tx(
+        signer_id: &str,
+        receiver_id: &str,
+        method_name: &str,
+    ) -> SignedTransactionView {
+        let signer =
+            InMemorySigner::from_seed(signer_id.parse().unwrap(), KeyType::ED25519, signer_id);
+        SignedTransaction::from_actions(
+            1,
+            signer_id.parse().unwrap(),
+            receiver_id.parse().unwrap(),
+            &signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: method_name.to_string(),
+                args: vec![],
+                gas: 1,
+                deposit: 0,
+            })],
+            CryptoHash::default(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // Test case 2 from RFC 4231.
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            mac.as_ref(),
+            &[
+                0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+                0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+                0x64, 0xec, 0x38, 0x43
+            ][..]
+        );
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let tx = function_call_tx("alice.near", "token.near", "ft_transfer");
+        assert!(WebhookFilter::default().matches(&tx));
+        let filter = WebhookFilter {
+            signer_ids: vec!["alice.near".parse().unwrap()],
+            receiver_ids: vec!["token.near".parse().unwrap()],
+            method_names: vec!["ft_transfer".to_string()],
+        };
+        assert!(filter.matches(&tx));
+        let filter = WebhookFilter { signer_ids: vec!["bob.near".parse().unwrap()], ..filter };
+        assert!(!filter.matches(&tx));
+        let filter = WebhookFilter {
+            method_names: vec!["storage_deposit".to_string()],
+            ..Default::default()
+        };
+        assert!(!filter.matches(&tx));
+    }
+}
@@ -0,0 +1,262 @@
+//! Benchmark of the application of chunks on the hardware of the node.
+//!
+//! `bench apply` applies again the new chunks of the last blocks of a stopped node, for the
+//! shards it tracks, and splits the time it takes into three stages: the reads of the state from
+//! the database, the execution of the transactions and receipts, and the commit of the new state.
+//! Every chunk is first applied against the database while recording the state it reads, then
+//! against the recorded state in memory, the difference between the two being the time spent
+//! reading. The new state is committed to a scratch database, so the data of the node isn't
+//! modified.
+//!
+//! The score is the gas burnt by the chunks per second of the three stages, in Tgas, and is only
+//! comparable between machines benchmarked on the same blocks.
+
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use near_chain::chain::collect_receipts_from_response;
+use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
+use near_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate, RuntimeAdapter};
+use near_primitives::types::{Gas, ShardId};
+use near_store::{create_store, Store};
+use nearcore::config::load_config_without_genesis_records;
+use nearcore::{get_store_path, NightshadeRuntime};
+
+#[derive(Debug, Default)]
+pub(crate) struct BenchStats {
+    pub blocks: u64,
+    pub chunks: u64,
+    pub transactions: u64,
+    pub receipts: u64,
+    pub gas_burnt: Gas,
+    pub nodes_read: u64,
+    pub bytes_read: u64,
+    pub storage_reads: Duration,
+    pub execution: Duration,
+    pub commit: Duration,
+}
+
+impl BenchStats {
+    pub fn total_time(&self) -> Duration {
+        self.storage_reads + self.execution + self.commit
+    }
+
+    /// Tgas burnt per second of the three stages.
+    pub fn score(&self) -> f64 {
+        let secs = self.total_time().as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.gas_burnt as f64 / 1e12 / secs
+    }
+}
+
+fn per_sec(count: f64, time: Duration) -> f64 {
+    count / time.as_secs_f64().max(f64::MIN_POSITIVE)
+}
+
+impl fmt::Display for BenchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Applied {} chunks of {} blocks, with {} transactions and {} receipts burning {:.1} Tgas",
+            self.chunks,
+            self.blocks,
+            self.transactions,
+            self.receipts,
+            self.gas_burnt as f64 / 1e12
+        )?;
+        writeln!(
+            f,
+            "storage reads {:>9.3}s  {:.0} nodes/s, {:.1} MB/s",
+            self.storage_reads.as_secs_f64(),
+            per_sec(self.nodes_read as f64, self.storage_reads),
+            per_sec(self.bytes_read as f64 / 1e6, self.storage_reads)
+        )?;
+        writeln!(
+            f,
+            "execution     {:>9.3}s  {:.1} Tgas/s",
+            self.execution.as_secs_f64(),
+            per_sec(self.gas_burnt as f64 / 1e12, self.execution)
+        )?;
+        writeln!(
+            f,
+            "commit        {:>9.3}s  {:.1} chunks/s",
+            self.commit.as_secs_f64(),
+            per_sec(self.chunks as f64, self.commit)
+        )
+    }
+}
+
+/// Applies the new chunks of the last `num_blocks` blocks of the canonical chain.
+pub(crate) fn bench_apply(home_dir: &Path, num_blocks: u64) -> Result<BenchStats, String> {
+    let near_config = load_config_without_genesis_records(home_dir);
+    let store = create_store(&get_store_path(home_dir));
+    let runtime = NightshadeRuntime::with_config(
+        home_dir,
+        store.clone(),
+        &near_config,
+        None,
+        near_config.client_config.max_gas_burnt_view,
+    );
+    let chain_store = ChainStore::new(store, near_config.genesis.config.genesis_height);
+
+    let scratch_dir = std::env::temp_dir().join(format!("neard-bench-{}", std::process::id()));
+    let scratch_store = create_store(&scratch_dir);
+    let result = apply_blocks(chain_store, &runtime, &scratch_store, num_blocks);
+    drop(scratch_store);
+    let _ = std::fs::remove_dir_all(&scratch_dir);
+    result
+}
+
+fn apply_blocks(
+    mut chain_store: ChainStore,
+    runtime: &NightshadeRuntime,
+    scratch_store: &Store,
+    num_blocks: u64,
+) -> Result<BenchStats, String> {
+    let head = chain_store.head().map_err(|err| format!("The node has no head: {}", err))?;
+    let genesis_height = chain_store.get_genesis_height();
+    // The genesis block has no chunks to apply.
+    let start_height =
+        head.height.saturating_sub(num_blocks.saturating_sub(1)).max(genesis_height + 1);
+
+    let mut stats = BenchStats::default();
+    for height in start_height..=head.height {
+        let block_hash = match chain_store.get_block_hash_by_height(height) {
+            Ok(block_hash) => block_hash,
+            Err(_) => continue,
+        };
+        let block = chain_store.get_block(&block_hash).map_err(|err| err.to_string())?.clone();
+        let prev_block = chain_store
+            .get_block(block.header().prev_hash())
+            .map_err(|err| format!("The block before height {} is missing: {}", height, err))?
+            .clone();
+        stats.blocks += 1;
+
+        for (shard_id, chunk_header) in block.chunks().iter().enumerate() {
+            let shard_id = shard_id as ShardId;
+            if chunk_header.height_included() != height {
+                continue;
+            }
+            // Only the chunks of the tracked shards are stored in full.
+            let chunk = match chain_store.get_chunk(&chunk_header.chunk_hash()) {
+                Ok(chunk) => chunk.clone(),
+                Err(_) => continue,
+            };
+            let receipts = {
+                let mut chain_store_update = ChainStoreUpdate::new(&mut chain_store);
+                let receipt_proof_response = chain_store_update
+                    .get_incoming_receipts_for_shard(
+                        shard_id,
+                        block_hash,
+                        prev_block.chunks()[shard_id as usize].height_included(),
+                    )
+                    .map_err(|err| err.to_string())?;
+                collect_receipts_from_response(&receipt_proof_response)
+            };
+            let is_first_block_with_chunk_of_version =
+                check_if_block_is_first_with_chunk_of_version(
+                    &mut chain_store,
+                    runtime,
+                    block.header().prev_hash(),
+                    shard_id,
+                )
+                .map_err(|err| err.to_string())?;
+            let chunk_inner = chunk.cloned_header().take_inner();
+            let apply_error = |err: near_chain::Error| {
+                format!(
+                    "Failed to apply the chunk of shard {} at height {}: {}",
+                    shard_id, height, err
+                )
+            };
+
+            let started = Instant::now();
+            let mut result = runtime
+                .apply_transactions_with_optional_storage_proof(
+                    shard_id,
+                    chunk_inner.prev_state_root(),
+                    height,
+                    block.header().raw_timestamp(),
+                    block.header().prev_hash(),
+                    block.hash(),
+                    &receipts,
+                    chunk.transactions(),
+                    chunk_inner.validator_proposals(),
+                    prev_block.header().gas_price(),
+                    chunk_inner.gas_limit(),
+                    &block.header().challenges_result(),
+                    *block.header().random_value(),
+                    true,
+                    true,
+                    is_first_block_with_chunk_of_version,
+                    None,
+                )
+                .map_err(apply_error)?;
+            let applied_from_database = started.elapsed();
+            let partial_storage = result.proof.take().expect("The storage proof was requested");
+            stats.nodes_read += partial_storage.nodes.0.len() as u64;
+            stats.bytes_read +=
+                partial_storage.nodes.0.iter().map(|node| node.len() as u64).sum::<u64>();
+
+            let started = Instant::now();
+            runtime
+                .check_state_transition(
+                    partial_storage,
+                    shard_id,
+                    chunk_inner.prev_state_root(),
+                    height,
+                    block.header().raw_timestamp(),
+                    block.header().prev_hash(),
+                    block.hash(),
+                    &receipts,
+                    chunk.transactions(),
+                    chunk_inner.validator_proposals(),
+                    prev_block.header().gas_price(),
+                    chunk_inner.gas_limit(),
+                    &block.header().challenges_result(),
+                    *block.header().random_value(),
+                    true,
+                    is_first_block_with_chunk_of_version,
+                )
+                .map_err(apply_error)?;
+            let execution = started.elapsed();
+
+            let started = Instant::now();
+            let mut store_update = scratch_store.store_update();
+            result
+                .trie_changes
+                .insertions_into(&mut store_update)
+                .map_err(|err| err.to_string())?;
+            store_update.commit().map_err(|err| err.to_string())?;
+            stats.commit += started.elapsed();
+
+            stats.execution += execution;
+            stats.storage_reads += applied_from_database.saturating_sub(execution);
+            stats.chunks += 1;
+            stats.transactions += chunk.transactions().len() as u64;
+            stats.receipts += receipts.len() as u64;
+            stats.gas_burnt += result.total_gas_burnt;
+        }
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        let stats = BenchStats {
+            gas_burnt: 3_000_000_000_000,
+            storage_reads: Duration::from_millis(500),
+            execution: Duration::from_millis(1000),
+            commit: Duration::from_millis(500),
+            ..BenchStats::default()
+        };
+        assert_eq!(stats.score(), 1.5);
+        assert_eq!(BenchStats::default().score(), 0.0);
+    }
+}
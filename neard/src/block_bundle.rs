@@ -0,0 +1,244 @@
+//! Bundles of blocks, to bootstrap nodes which can't download them from their peers.
+//!
+//! `export-blocks` writes a range of blocks of a stopped node to a file, along with the chunks
+//! they include, in full and as partial chunks. `import-blocks` processes them on a stopped node
+//! of the same chain like blocks received from peers: the headers, the approvals and the proofs
+//! of the chunks are validated and the chunks of the tracked shards applied, so a bundle carried
+//! by hand doesn't need to be trusted. The bundle must continue the chain of the importing node,
+//! the blocks it already has are skipped.
+//!
+//! The file starts with `MAGIC` and a header record, followed by one record per block. Every
+//! record is prefixed with its length and the hash of its content, so that a truncated or
+//! corrupted file is reported as such rather than as invalid blocks.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use near_chain::validate::validate_chunk_proofs;
+use near_chain::{
+    Chain, ChainGenesis, ChainStore, ChainStoreAccess, DoomslugThresholdMode, Provenance,
+};
+use near_primitives::block::Block;
+use near_primitives::borsh::{BorshDeserialize, BorshSerialize};
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::sharding::{PartialEncodedChunk, ShardChunk};
+use near_primitives::types::BlockHeight;
+use near_store::create_store;
+use nearcore::config::load_config_without_genesis_records;
+use nearcore::{get_store_path, init_and_migrate_store, NightshadeRuntime};
+
+const MAGIC: &[u8; 8] = b"NEARBLK1";
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct BundleHeader {
+    chain_id: String,
+    genesis_hash: CryptoHash,
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+struct BundledBlock {
+    block: Block,
+    /// The chunks included in the block, for the shards tracked by the exporting node.
+    chunks: Vec<ShardChunk>,
+    /// The partial chunks included in the block, all a node needs of the shards it doesn't track.
+    partial_chunks: Vec<PartialEncodedChunk>,
+}
+
+fn write_record(out: &mut impl Write, record: &impl BorshSerialize) -> io::Result<()> {
+    let bytes = record.try_to_vec()?;
+    out.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    out.write_all(hash(&bytes).as_ref())?;
+    out.write_all(&bytes)
+}
+
+/// Reads the next record, `None` at the end of the file.
+fn read_record<T: BorshDeserialize>(input: &mut impl Read) -> Result<Option<T>, String> {
+    let mut len = [0u8; 8];
+    match input.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(format!("Failed to read the bundle: {}", err)),
+    }
+    let mut checksum = [0u8; 32];
+    let mut bytes = vec![0u8; u64::from_le_bytes(len) as usize];
+    input
+        .read_exact(&mut checksum)
+        .and_then(|()| input.read_exact(&mut bytes))
+        .map_err(|err| format!("The bundle is truncated: {}", err))?;
+    if hash(&bytes) != CryptoHash(checksum) {
+        return Err("The bundle is corrupted, a record doesn't match its checksum".to_string());
+    }
+    T::try_from_slice(&bytes).map(Some).map_err(|err| format!("Invalid record: {}", err))
+}
+
+/// Exports the blocks of the canonical chain between the heights, both included, returning how
+/// many there are.
+pub(crate) fn export_blocks(
+    home_dir: &Path,
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+    output: &Path,
+) -> Result<u64, String> {
+    let near_config = load_config_without_genesis_records(home_dir);
+    let genesis_height = near_config.genesis.config.genesis_height;
+    let mut chain_store = ChainStore::new(create_store(&get_store_path(home_dir)), genesis_height);
+    let genesis_hash = chain_store
+        .get_block_hash_by_height(genesis_height)
+        .map_err(|err| format!("The node has no genesis block: {}", err))?;
+
+    let file = File::create(output)
+        .map_err(|err| format!("Failed to create {}: {}", output.display(), err))?;
+    let mut out = BufWriter::new(file);
+    let header = BundleHeader {
+        chain_id: near_config.genesis.config.chain_id.clone(),
+        genesis_hash,
+        start_height,
+        end_height,
+    };
+    out.write_all(MAGIC)
+        .and_then(|()| write_record(&mut out, &header))
+        .map_err(|err| format!("Failed to write the bundle: {}", err))?;
+
+    let mut exported = 0;
+    for height in start_height..=end_height {
+        // Heights without blocks are skipped.
+        let block_hash = match chain_store.get_block_hash_by_height(height) {
+            Ok(block_hash) => block_hash,
+            Err(_) => continue,
+        };
+        let block = chain_store.get_block(&block_hash).map_err(|err| err.to_string())?.clone();
+        let mut chunks = vec![];
+        let mut partial_chunks = vec![];
+        for chunk_header in block.chunks().iter() {
+            if chunk_header.height_included() != height {
+                continue;
+            }
+            let chunk_hash = chunk_header.chunk_hash();
+            let partial_chunk = chain_store.get_partial_chunk(&chunk_hash).map_err(|err| {
+                format!(
+                    "Partial chunk {:?} of block {} is missing: {}",
+                    chunk_hash, block_hash, err
+                )
+            })?;
+            partial_chunks.push(partial_chunk.clone());
+            if let Ok(chunk) = chain_store.get_chunk(&chunk_hash) {
+                chunks.push(chunk.clone());
+            }
+        }
+        write_record(&mut out, &BundledBlock { block, chunks, partial_chunks })
+            .map_err(|err| format!("Failed to write the bundle: {}", err))?;
+        exported += 1;
+    }
+    out.flush().map_err(|err| format!("Failed to write the bundle: {}", err))?;
+    Ok(exported)
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ImportStats {
+    pub imported: u64,
+    pub skipped: u64,
+}
+
+/// Imports the blocks of a bundle, stopping at the first invalid one.
+pub(crate) fn import_blocks(home_dir: &Path, input: &Path) -> Result<ImportStats, String> {
+    let near_config = load_config_without_genesis_records(home_dir);
+    let store = init_and_migrate_store(home_dir, &near_config);
+    let runtime = Arc::new(NightshadeRuntime::with_config(
+        home_dir,
+        store,
+        &near_config,
+        near_config.client_config.trie_viewer_state_size_limit,
+        near_config.client_config.max_gas_burnt_view,
+    ));
+    let chain_genesis = ChainGenesis::from(&near_config.genesis);
+    let mut chain = Chain::new(runtime.clone(), &chain_genesis, DoomslugThresholdMode::TwoThirds)
+        .map_err(|err| format!("Failed to open the chain: {}", err))?;
+    let me = near_config.validator_signer.as_ref().map(|signer| signer.validator_id().clone());
+
+    let file =
+        File::open(input).map_err(|err| format!("Failed to open {}: {}", input.display(), err))?;
+    let mut input = BufReader::new(file);
+    let mut magic = [0u8; 8];
+    if input.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Err("Not a block bundle".to_string());
+    }
+    let header: BundleHeader =
+        read_record(&mut input)?.ok_or_else(|| "The bundle has no header".to_string())?;
+    if header.chain_id != near_config.genesis.config.chain_id
+        || header.genesis_hash != *chain.genesis().hash()
+    {
+        return Err(format!(
+            "The bundle is of chain {} with genesis {}, not of the chain of the node",
+            header.chain_id, header.genesis_hash
+        ));
+    }
+
+    let mut stats = ImportStats::default();
+    while let Some(BundledBlock { block, chunks, partial_chunks }) = read_record(&mut input)? {
+        let block_hash = *block.hash();
+        let height = block.header().height();
+        if chain.block_exists(&block_hash).map_err(|err| err.to_string())? {
+            stats.skipped += 1;
+            continue;
+        }
+        let is_new_chunk = |chunk_hash| {
+            block.chunks().iter().any(|header| {
+                header.height_included() == height && header.chunk_hash() == chunk_hash
+            })
+        };
+        let mut chain_store_update = chain.mut_store().store_update();
+        for chunk in chunks {
+            let valid = is_new_chunk(chunk.chunk_hash())
+                && validate_chunk_proofs(&chunk, &*runtime).map_err(|err| err.to_string())?;
+            if !valid {
+                return Err(format!("Invalid chunk {:?} in block {}", chunk.chunk_hash(), height));
+            }
+            chain_store_update.save_chunk(chunk);
+        }
+        for partial_chunk in partial_chunks {
+            if !is_new_chunk(partial_chunk.chunk_hash()) {
+                return Err(format!(
+                    "Partial chunk {:?} is not included in block {}",
+                    partial_chunk.chunk_hash(),
+                    height
+                ));
+            }
+            chain_store_update.save_partial_chunk(partial_chunk);
+        }
+        chain_store_update.commit().map_err(|err| err.to_string())?;
+        chain.process_block(&me, block.into(), Provenance::NONE, |_| {}, |_| {}, |_| {}).map_err(
+            |err| format!("Block {} at height {} is rejected: {}", block_hash, height, err),
+        )?;
+        stats.imported += 1;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let header = BundleHeader {
+            chain_id: "localnet".to_string(),
+            genesis_hash: hash(b"genesis"),
+            start_height: 10,
+            end_height: 20,
+        };
+        let mut bytes = vec![];
+        write_record(&mut bytes, &header).unwrap();
+
+        let read: BundleHeader = read_record(&mut bytes.as_slice()).unwrap().unwrap();
+        assert_eq!((read.chain_id.as_str(), read.end_height), ("localnet", 20));
+        assert!(read_record::<BundleHeader>(&mut &bytes[..0]).unwrap().is_none());
+        assert!(read_record::<BundleHeader>(&mut &bytes[..bytes.len() - 1]).is_err());
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(read_record::<BundleHeader>(&mut bytes.as_slice()).is_err());
+    }
+}
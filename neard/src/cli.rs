@@ -1,14 +1,23 @@
-use super::{DEFAULT_HOME, NEARD_VERSION, NEARD_VERSION_STRING, PROTOCOL_VERSION};
+use super::{
+    DEFAULT_HOME, NEARD_VERSION, NEARD_VERSION_STRING, PROTOCOL_VERSION, PROTOCOL_VERSION_STRING,
+};
+use ansi_term::Color::{Green, Red, Yellow};
 use clap::{AppSettings, Clap};
 use futures::future::FutureExt;
-use near_primitives::types::{Gas, NumSeats, NumShards};
+use near_primitives::runtime::config_store::RuntimeConfigStore;
+use near_primitives::types::{
+    AccountId, Balance, BlockHeight, Gas, NumSeats, NumShards, ProtocolVersion, ShardId,
+};
+#[cfg(feature = "state_viewer")]
 use near_state_viewer::StateViewerSubCommand;
+use nearcore::config::CONFIG_FILENAME;
+use nearcore::config_schema::{parse_config, ConfigChange, ConfigMode, CONFIG_VERSION};
 use nearcore::get_store_path;
+use std::io::Read;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::{env, fs, io};
 use tracing::debug;
-#[cfg(feature = "test_features")]
 use tracing::error;
 use tracing::info;
 use tracing::metadata::LevelFilter;
@@ -58,9 +67,19 @@ impl NeardCmd {
                 info!(target: "neard", "Removing all data and config from {}", home_dir.to_string_lossy());
                 fs::remove_dir_all(home_dir).expect("Removing data and config failed.");
             }
+            #[cfg(feature = "state_viewer")]
             NeardSubCommand::StateViewer(cmd) => {
                 cmd.run(&home_dir);
             }
+            NeardSubCommand::Config(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::Localnet(cmd) => cmd.run(),
+            NeardSubCommand::Report(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::Downtime(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::InspectTx(cmd) => cmd.run(),
+            NeardSubCommand::ExportBlocks(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::ImportBlocks(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::Bench(cmd) => cmd.run(&home_dir),
+            NeardSubCommand::Database(cmd) => cmd.run(&home_dir),
         }
     }
 }
@@ -103,8 +122,43 @@ pub(super) enum NeardSubCommand {
     #[clap(name = "unsafe_reset_data")]
     UnsafeResetData,
     /// View DB state.
+    #[cfg(feature = "state_viewer")]
     #[clap(name = "view_state")]
     StateViewer(StateViewerSubCommand),
+    /// Checks or upgrades the config file
+    #[clap(name = "config")]
+    Config(ConfigCmd),
+    /// Runs a deterministic in-process localnet driven by a scenario script, for reproducing
+    /// bugs
+    #[clap(name = "localnet")]
+    Localnet(LocalnetCmd),
+    /// Bundles the version, sync status, block production, peers, latency histograms and
+    /// redacted config of the running node, to attach to support requests
+    #[clap(name = "report")]
+    Report(ReportCmd),
+    /// Projects whether the validator of the running node would be kicked out, and how much of
+    /// its reward it would lose, if it was down during the given windows
+    #[clap(name = "downtime")]
+    Downtime(DowntimeCmd),
+    /// Decodes a signed transaction, checks its signature and limits and estimates its fees,
+    /// without sending it
+    #[clap(name = "inspect-tx")]
+    InspectTx(InspectTxCmd),
+    /// Writes a range of blocks with their chunks to a bundle file, for nodes which can't
+    /// download them from peers. The node must be stopped
+    #[clap(name = "export-blocks")]
+    ExportBlocks(ExportBlocksCmd),
+    /// Validates and processes the blocks of a bundle written by `export-blocks`, as if received
+    /// from peers. The node must be stopped
+    #[clap(name = "import-blocks")]
+    ImportBlocks(ImportBlocksCmd),
+    /// Measures the performance of this machine on the data of the node. The node must be
+    /// stopped
+    #[clap(name = "bench")]
+    Bench(BenchCmd),
+    /// Inspects the database of the node. The node must be stopped
+    #[clap(name = "database")]
+    Database(DatabaseCmd),
 }
 
 #[derive(Clap)]
@@ -177,6 +231,104 @@ impl InitCmd {
     }
 }
 
+#[derive(Clap)]
+pub(super) struct ConfigCmd {
+    #[clap(subcommand)]
+    subcmd: ConfigSubCommand,
+}
+
+#[derive(Clap)]
+enum ConfigSubCommand {
+    /// Checks that the config can be loaded and has no unknown fields
+    #[clap(name = "validate")]
+    Validate(ConfigValidateCmd),
+    /// Rewrites the config with the latest schema version, keeping a backup of the old file
+    #[clap(name = "migrate")]
+    Migrate(ConfigMigrateCmd),
+}
+
+#[derive(Clap)]
+struct ConfigValidateCmd {
+    /// Only warn about unknown fields instead of rejecting them, like the node does.
+    #[clap(long)]
+    lenient: bool,
+}
+
+#[derive(Clap)]
+struct ConfigMigrateCmd {
+    /// Only print the changes without writing the file.
+    #[clap(long)]
+    dry_run: bool,
+}
+
+impl ConfigCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        let path = home_dir.join(CONFIG_FILENAME);
+        let content = fs::read_to_string(&path).unwrap_or_else(|err| {
+            eprintln!("{}", Red.paint(format!("Failed to read {}: {}", path.display(), err)));
+            std::process::exit(1);
+        });
+        let mode = match &self.subcmd {
+            ConfigSubCommand::Validate(cmd) if !cmd.lenient => ConfigMode::Strict,
+            _ => ConfigMode::Lenient,
+        };
+        let parsed = parse_config(&content, mode).unwrap_or_else(|err| {
+            eprintln!("{}", Red.paint(format!("Invalid config {}: {}", path.display(), err)));
+            std::process::exit(1);
+        });
+
+        match self.subcmd {
+            ConfigSubCommand::Validate(_) => {
+                for field in parsed.unknown_fields.iter() {
+                    println!("{}", Yellow.paint(format!("Unknown field `{}` is ignored", field)));
+                }
+                if parsed.is_migrated() {
+                    println!(
+                        "{}",
+                        Yellow.paint(format!(
+                            "Config has version {}, run `neard config migrate` to upgrade it to {}",
+                            parsed.version, CONFIG_VERSION
+                        ))
+                    );
+                }
+                println!("{}", Green.paint(format!("Config {} is valid", path.display())));
+            }
+            ConfigSubCommand::Migrate(cmd) => {
+                if parsed.changes.is_empty() {
+                    println!("Config {} is up to date", path.display());
+                    return;
+                }
+                for change in parsed.changes.iter() {
+                    match change {
+                        ConfigChange::Added { path, value } => {
+                            println!("{}", Green.paint(format!("+ {}: {}", path, value)))
+                        }
+                        ConfigChange::Removed { path, value } => {
+                            println!("{}", Red.paint(format!("- {}: {}", path, value)))
+                        }
+                        ConfigChange::Changed { path, old, new } => {
+                            println!("{}", Red.paint(format!("- {}: {}", path, old)));
+                            println!("{}", Green.paint(format!("+ {}: {}", path, new)));
+                        }
+                    }
+                }
+                if cmd.dry_run {
+                    return;
+                }
+                let backup_path = path.with_extension("json.bak");
+                fs::copy(&path, &backup_path).expect("Failed to back up the config file");
+                parsed.config.write_to_file(&path);
+                println!(
+                    "Migrated config {} to version {}, old config saved to {}",
+                    path.display(),
+                    CONFIG_VERSION,
+                    backup_path.display()
+                );
+            }
+        }
+    }
+}
+
 #[derive(Clap)]
 pub(super) struct RunCmd {
     /// Keep old blocks in the storage (default false).
@@ -339,6 +491,384 @@ impl TestnetCmd {
     }
 }
 
+#[derive(Clap)]
+pub(super) struct LocalnetCmd {
+    /// Seed all the randomness of the localnet derives from.
+    #[clap(long, default_value = "0")]
+    seed: u64,
+    /// YAML scenario with the validators, the number of heights and the transactions and faults
+    /// to inject at given heights.
+    #[clap(long)]
+    script: PathBuf,
+}
+
+impl LocalnetCmd {
+    pub(super) fn run(self) {
+        let scenario = crate::localnet::Scenario::from_file(&self.script).unwrap_or_else(|err| {
+            panic!("Failed to read scenario {}: {}", self.script.display(), err)
+        });
+        match crate::localnet::run(self.seed, &scenario) {
+            Ok(reports) => {
+                for report in reports {
+                    println!("{}", report);
+                }
+            }
+            Err(err) => {
+                error!(target: "neard", "Localnet failed: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[derive(Clap)]
+pub(super) struct ReportCmd {
+    /// Number of epochs, the current one included, to report the block production of.
+    #[clap(long, default_value = "3")]
+    last_epochs: u64,
+    /// JSON RPC address of the node, by default the one in its config.
+    #[clap(long)]
+    rpc_addr: Option<String>,
+    /// Where to write the bundle, by default `report-<time>.json.gz` in the current directory.
+    #[clap(long)]
+    output: Option<PathBuf>,
+}
+
+impl ReportCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        let config = read_config_json(home_dir);
+        let rpc_addr = self.rpc_addr.unwrap_or_else(|| local_rpc_addr(&config));
+        let output = self.output.unwrap_or_else(|| {
+            PathBuf::from(format!("report-{}.json.gz", chrono::Utc::now().format("%Y%m%d%H%M%S")))
+        });
+        let last_epochs = self.last_epochs;
+        let sys = actix::System::new();
+        let report = sys.block_on(crate::report::Report::collect(
+            &rpc_addr,
+            config,
+            NEARD_VERSION.clone(),
+            last_epochs,
+        ));
+        match report.and_then(|report| report.write(&output)) {
+            Ok(()) => info!(target: "neard", "Report written to {}", output.display()),
+            Err(err) => {
+                error!(target: "neard", "Failed to generate the report: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn read_config_json(home_dir: &Path) -> serde_json::Value {
+    let config_path = home_dir.join(CONFIG_FILENAME);
+    fs::read(&config_path)
+        .map_err(|err| err.to_string())
+        .and_then(|config| serde_json::from_slice(&config).map_err(|err| err.to_string()))
+        .unwrap_or_else(|err| panic!("Failed to read {}: {}", config_path.display(), err))
+}
+
+/// The JSON RPC address of the node in its config, reached from this machine.
+fn local_rpc_addr(config: &serde_json::Value) -> String {
+    let addr = config["rpc"]["addr"].as_str().unwrap_or("127.0.0.1:3030");
+    format!("http://{}", addr.replace("0.0.0.0", "127.0.0.1"))
+}
+
+#[derive(Clap)]
+pub(super) struct DowntimeCmd {
+    /// Planned downtime, as `<start height>:<duration>` with the duration in heights or like
+    /// `30m` or `2h`. Can be repeated.
+    #[clap(long = "window", required = true)]
+    windows: Vec<String>,
+    /// Validator to simulate the downtime of, by default the one of the node.
+    #[clap(long)]
+    account_id: Option<AccountId>,
+    /// JSON RPC address of the node, by default the one in its config.
+    #[clap(long)]
+    rpc_addr: Option<String>,
+}
+
+impl DowntimeCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        let rpc_addr = self.rpc_addr.unwrap_or_else(|| local_rpc_addr(&read_config_json(home_dir)));
+        let sys = actix::System::new();
+        let (account_id, thresholds, progress, block_time) = sys
+            .block_on(crate::downtime::query(&rpc_addr, self.account_id))
+            .unwrap_or_else(|err| {
+                error!(target: "neard", "{}", err);
+                std::process::exit(1);
+            });
+        let windows = self
+            .windows
+            .iter()
+            .map(|window| crate::downtime::parse_window(window, block_time))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|err| {
+                error!(target: "neard", "{}", err);
+                std::process::exit(1);
+            });
+        println!(
+            "{} at height {}, {}/{} blocks produced since the start of the epoch at height {}, \
+             {:.2}s per block",
+            account_id,
+            progress.current_height,
+            progress.num_produced_blocks,
+            progress.num_expected_blocks,
+            progress.epoch_start_height,
+            block_time
+        );
+        println!(
+            "Can be down for {} more heights in this epoch without being kicked out",
+            crate::downtime::max_downtime(&thresholds, &progress)
+        );
+        for outcome in crate::downtime::simulate(&thresholds, &progress, &windows) {
+            println!("{}", outcome);
+        }
+    }
+}
+
+#[derive(Clap)]
+pub(super) struct InspectTxCmd {
+    /// Borsh serialized signed transaction, in base64 or hex. Read from stdin if not given.
+    blob: Option<String>,
+    /// Gas price to estimate the cost at, in yoctoNEAR, by default the minimum one of mainnet.
+    #[clap(long, default_value = "100000000")]
+    gas_price: Balance,
+    /// Protocol version of the runtime config the transaction is checked against.
+    #[clap(long, default_value = PROTOCOL_VERSION_STRING.as_str())]
+    protocol_version: ProtocolVersion,
+}
+
+impl InspectTxCmd {
+    pub(super) fn run(self) {
+        let blob = self.blob.unwrap_or_else(|| {
+            let mut blob = String::new();
+            io::stdin().read_to_string(&mut blob).expect("Failed to read the transaction");
+            blob
+        });
+        let config_store = RuntimeConfigStore::new(None);
+        let config = config_store.get_config(self.protocol_version);
+        let inspection = crate::inspect_tx::decode_blob(&blob).and_then(|bytes| {
+            crate::inspect_tx::Inspection::new(
+                &bytes,
+                config,
+                self.gas_price,
+                self.protocol_version,
+            )
+        });
+        match inspection {
+            Ok(inspection) => {
+                print!("{}", inspection);
+                if !inspection.is_valid() {
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => {
+                error!(target: "neard", "{}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[derive(Clap)]
+pub(super) struct ExportBlocksCmd {
+    /// Heights of the blocks to export, both included, as `START..END`.
+    #[clap(long, parse(try_from_str = parse_height_range))]
+    range: (BlockHeight, BlockHeight),
+    /// Where to write the bundle.
+    #[clap(long, parse(from_os_str))]
+    output: PathBuf,
+}
+
+fn parse_height_range(range: &str) -> Result<(BlockHeight, BlockHeight), String> {
+    let (start, end) = range.split_once("..").ok_or("Expected a range as START..END")?;
+    let start = start.parse().map_err(|err| format!("Invalid start height: {}", err))?;
+    let end = end.parse().map_err(|err| format!("Invalid end height: {}", err))?;
+    if start > end {
+        return Err("The start height is above the end one".to_string());
+    }
+    Ok((start, end))
+}
+
+impl ExportBlocksCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        let (start_height, end_height) = self.range;
+        match crate::block_bundle::export_blocks(home_dir, start_height, end_height, &self.output) {
+            Ok(exported) => {
+                info!(target: "neard", "Exported {} blocks to {}", exported, self.output.display())
+            }
+            Err(err) => {
+                error!(target: "neard", "Export failed: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[derive(Clap)]
+pub(super) struct ImportBlocksCmd {
+    /// Bundle written by `export-blocks`.
+    #[clap(parse(from_os_str))]
+    bundle: PathBuf,
+}
+
+impl ImportBlocksCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        match crate::block_bundle::import_blocks(home_dir, &self.bundle) {
+            Ok(stats) => info!(
+                target: "neard",
+                "Imported {} blocks, skipped {} already known",
+                stats.imported, stats.skipped
+            ),
+            Err(err) => {
+                error!(target: "neard", "Import failed: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[derive(Clap)]
+pub(super) struct BenchCmd {
+    #[clap(subcommand)]
+    subcmd: BenchSubCommand,
+}
+
+#[derive(Clap)]
+enum BenchSubCommand {
+    /// Applies again the chunks of the last blocks and reports the throughput of the storage
+    /// reads, the execution and the commit, without modifying the data of the node
+    #[clap(name = "apply")]
+    Apply(BenchApplyCmd),
+}
+
+#[derive(Clap)]
+struct BenchApplyCmd {
+    /// Number of blocks to apply, ending at the head of the node.
+    #[clap(long, default_value = "1000")]
+    blocks: u64,
+}
+
+impl BenchCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        match self.subcmd {
+            BenchSubCommand::Apply(cmd) => {
+                let stats = crate::bench::bench_apply(home_dir, cmd.blocks).unwrap_or_else(|err| {
+                    error!(target: "neard", "Benchmark failed: {}", err);
+                    std::process::exit(1);
+                });
+                if stats.chunks == 0 {
+                    error!(target: "neard", "No chunk of the tracked shards found in the last {} blocks", cmd.blocks);
+                    std::process::exit(1);
+                }
+                print!("{}", stats);
+                println!("{}", Green.paint(format!("Score: {:.1} Tgas/s", stats.score())));
+            }
+        }
+    }
+}
+
+#[derive(Clap)]
+pub(super) struct DatabaseCmd {
+    #[clap(subcommand)]
+    subcmd: DatabaseSubCommand,
+}
+
+#[derive(Clap)]
+enum DatabaseSubCommand {
+    /// Reports the disk usage per column, per shard and per type of data, and how it changed
+    /// since a previous report
+    #[clap(name = "keyspace-report")]
+    KeyspaceReport(KeyspaceReportCmd),
+    /// Checks the reference counts of the trie nodes against the nodes reachable from the state
+    /// of the shards, reporting the leaked and dangling ones. The node must be stopped
+    #[clap(name = "refcount-audit")]
+    RefcountAudit(RefcountAuditCmd),
+}
+
+#[derive(Clap)]
+struct KeyspaceReportCmd {
+    /// File to save the report to, as JSON.
+    #[clap(long)]
+    output: Option<PathBuf>,
+    /// Report saved by a previous run, to show the change of every line since.
+    #[clap(long)]
+    previous: Option<PathBuf>,
+}
+
+#[derive(Clap)]
+struct RefcountAuditCmd {
+    /// Shard to audit, may be repeated. Audits every shard tracked at the head by default.
+    #[clap(long)]
+    shard_id: Vec<ShardId>,
+    /// Sets the stored reference counts to the expected ones.
+    #[clap(long)]
+    repair: bool,
+}
+
+impl DatabaseCmd {
+    pub(super) fn run(self, home_dir: &Path) {
+        match self.subcmd {
+            DatabaseSubCommand::KeyspaceReport(cmd) => {
+                let previous = cmd.previous.map(|path| {
+                    crate::keyspace::KeyspaceReport::read(&path).unwrap_or_else(|err| {
+                        error!(target: "neard", "Failed to read {}: {}", path.display(), err);
+                        std::process::exit(1);
+                    })
+                });
+                let store = near_store::create_store(&get_store_path(home_dir));
+                let report = crate::keyspace::KeyspaceReport::collect(&store);
+                print!("{}", report.compared_to(previous.as_ref()));
+                if let Some(path) = cmd.output {
+                    if let Err(err) = report.write(&path) {
+                        error!(target: "neard", "Failed to write {}: {}", path.display(), err);
+                        std::process::exit(1);
+                    }
+                    info!(target: "neard", "Report saved to {}", path.display());
+                }
+            }
+            DatabaseSubCommand::RefcountAudit(cmd) => {
+                let store = near_store::create_store(&get_store_path(home_dir));
+                let (head, shard_uids) =
+                    crate::refcount_audit::head_shard_uids(&store, &cmd.shard_id).unwrap_or_else(
+                        |err| {
+                            error!(target: "neard", "{}", err);
+                            std::process::exit(1);
+                        },
+                    );
+                let mut consistent = true;
+                for shard_uid in shard_uids {
+                    let report = crate::refcount_audit::audit(&store, &head, shard_uid)
+                        .unwrap_or_else(|err| {
+                            error!(target: "neard", "Failed to audit shard {:?}: {}", shard_uid, err);
+                            std::process::exit(1);
+                        });
+                    print!("{}", crate::refcount_audit::Summary(&report));
+                    if report.is_consistent() {
+                        continue;
+                    }
+                    if !cmd.repair {
+                        consistent = false;
+                        continue;
+                    }
+                    match report.repair(&store) {
+                        Ok(repaired) => {
+                            info!(target: "neard", "Repaired {} reference counts", repaired)
+                        }
+                        Err(err) => {
+                            error!(target: "neard", "Failed to repair: {}", err);
+                            consistent = false;
+                        }
+                    }
+                }
+                if !consistent {
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
 fn init_logging(verbose: Option<&str>) {
     let mut env_filter = EnvFilter::new(
         "tokio_reactor=info,near=info,stats=info,telemetry=info,delay_detector=info,\
@@ -0,0 +1,311 @@
+//! Simulation of the consequences of planned downtime of a validator.
+//!
+//! A validator is kicked out at the end of an epoch if it produced less than
+//! `block_producer_kickout_threshold` percent of the blocks it was expected to, and its reward
+//! shrinks linearly from the full reward at `online_max_threshold` uptime to none at
+//! `online_min_threshold`. `downtime` projects the production of the validator to the end of the
+//! epochs which the planned windows overlap, assuming it produces every block it's expected to
+//! outside of them, at the rate it was expected to produce blocks so far in the current epoch.
+//!
+//! Only block production is known over RPC, the uptime of chunk production is assumed to be the
+//! same, which it is when the whole node is down.
+
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+use near_chain_configs::GenesisConfig;
+use near_jsonrpc_client::new_client;
+use near_primitives::num_rational::Rational;
+use near_primitives::types::{AccountId, BlockHeight, BlockHeightDelta, BlockId};
+
+/// Kickout and reward thresholds of the network.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Thresholds {
+    pub epoch_length: BlockHeightDelta,
+    /// Percentage of the expected blocks under which the validator is kicked out.
+    pub block_producer_kickout_threshold: u8,
+    pub online_min_threshold: f64,
+    pub online_max_threshold: f64,
+}
+
+impl From<&GenesisConfig> for Thresholds {
+    fn from(config: &GenesisConfig) -> Self {
+        let ratio = |r: &Rational| *r.numer() as f64 / *r.denom() as f64;
+        Self {
+            epoch_length: config.epoch_length,
+            block_producer_kickout_threshold: config.block_producer_kickout_threshold,
+            online_min_threshold: ratio(&config.online_min_threshold),
+            online_max_threshold: ratio(&config.online_max_threshold),
+        }
+    }
+}
+
+/// Block production of the validator in the current epoch, up to `current_height` included.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EpochProgress {
+    pub epoch_start_height: BlockHeight,
+    pub current_height: BlockHeight,
+    pub num_produced_blocks: u64,
+    pub num_expected_blocks: u64,
+}
+
+/// Projected production of the validator at the end of an epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EpochOutcome {
+    pub heights: Range<BlockHeight>,
+    /// Heights of the epoch the validator is planned to be down at.
+    pub downtime: BlockHeightDelta,
+    pub produced_blocks: f64,
+    pub expected_blocks: f64,
+    pub kicked_out: bool,
+    /// Fraction of the full reward.
+    pub reward: f64,
+}
+
+impl EpochOutcome {
+    pub fn uptime(&self) -> f64 {
+        if self.expected_blocks == 0.0 {
+            return 1.0;
+        }
+        self.produced_blocks / self.expected_blocks
+    }
+}
+
+impl fmt::Display for EpochOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "heights {}..{}: down {} heights, {:.0}/{:.0} blocks ({:.2}% uptime), {}, {:.1}% of the reward",
+            self.heights.start,
+            self.heights.end,
+            self.downtime,
+            self.produced_blocks,
+            self.expected_blocks,
+            self.uptime() * 100.0,
+            if self.kicked_out { "KICKED OUT" } else { "not kicked out" },
+            self.reward * 100.0
+        )
+    }
+}
+
+/// Projects the outcome of every epoch from the current one to the last one a window overlaps.
+///
+/// A kicked out validator loses its seat two epochs later, the outcomes of the following epochs
+/// are those it would have if it kept it.
+pub(crate) fn simulate(
+    thresholds: &Thresholds,
+    progress: &EpochProgress,
+    windows: &[Range<BlockHeight>],
+) -> Vec<EpochOutcome> {
+    let elapsed = (progress.current_height + 1 - progress.epoch_start_height) as f64;
+    // Expected blocks of `heights` heights, at the rate of the current epoch so far.
+    let expected =
+        |heights: BlockHeightDelta| progress.num_expected_blocks as f64 * heights as f64 / elapsed;
+    let last_height = windows.iter().map(|window| window.end).max().unwrap_or(0);
+
+    let mut outcomes = vec![];
+    let mut start = progress.epoch_start_height;
+    let (mut produced_blocks, mut expected_blocks) =
+        (progress.num_produced_blocks as f64, progress.num_expected_blocks as f64);
+    loop {
+        let heights = start..start + thresholds.epoch_length;
+        // The heights of the current epoch up to the current one are already accounted for.
+        let remaining = heights.start.max(progress.current_height + 1)..heights.end;
+        let downtime: BlockHeightDelta = windows
+            .iter()
+            .map(|window| {
+                window.end.min(remaining.end).saturating_sub(window.start.max(remaining.start))
+            })
+            .sum();
+        let remaining_len = remaining.end.saturating_sub(remaining.start);
+        expected_blocks += expected(remaining_len);
+        produced_blocks += expected(remaining_len.saturating_sub(downtime));
+        let mut outcome = EpochOutcome {
+            heights,
+            downtime,
+            produced_blocks,
+            expected_blocks,
+            kicked_out: false,
+            reward: 0.0,
+        };
+        // As in the epoch manager, the threshold is a percentage.
+        outcome.kicked_out = outcome.produced_blocks * 100.0
+            < f64::from(thresholds.block_producer_kickout_threshold) * outcome.expected_blocks;
+        outcome.reward = ((outcome.uptime() - thresholds.online_min_threshold)
+            / (thresholds.online_max_threshold - thresholds.online_min_threshold))
+            .max(0.0)
+            .min(1.0);
+        let end = outcome.heights.end;
+        outcomes.push(outcome);
+        if end >= last_height {
+            return outcomes;
+        }
+        start = end;
+        produced_blocks = 0.0;
+        expected_blocks = 0.0;
+    }
+}
+
+/// Largest number of heights the validator can be down for in the rest of the current epoch
+/// without being kicked out.
+pub(crate) fn max_downtime(thresholds: &Thresholds, progress: &EpochProgress) -> BlockHeightDelta {
+    let epoch_end = progress.epoch_start_height + thresholds.epoch_length;
+    let remaining = epoch_end.saturating_sub(progress.current_height + 1);
+    let window = progress.current_height + 1..epoch_end;
+    let mut allowed = 0..remaining + 1;
+    // The projected outcome only gets worse as the downtime grows, bisect the first kickout.
+    while allowed.end - allowed.start > 1 {
+        let middle = (allowed.start + allowed.end) / 2;
+        let outcome =
+            simulate(thresholds, progress, &[window.start..window.start + middle]).remove(0);
+        if outcome.kicked_out {
+            allowed.end = middle;
+        } else {
+            allowed.start = middle;
+        }
+    }
+    let kicked_out_without_downtime =
+        simulate(thresholds, progress, &[window.start..window.start]).remove(0).kicked_out;
+    if kicked_out_without_downtime {
+        0
+    } else {
+        allowed.start
+    }
+}
+
+/// Parses a window given as `<start height>:<duration>`, the duration being a number of heights
+/// or a number of hours, minutes or seconds like `2h`, converted with the time between blocks.
+pub(crate) fn parse_window(window: &str, block_time: f64) -> Result<Range<BlockHeight>, String> {
+    let invalid = || format!("Invalid window {:?}, expected <start height>:<duration>", window);
+    let (start, duration) = window.split_once(':').ok_or_else(invalid)?;
+    let start: BlockHeight = start.parse().map_err(|_| invalid())?;
+    let (value, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => duration.split_at(i),
+        None => (duration, ""),
+    };
+    let value: u64 = value.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "" => return Ok(start..start + value),
+        "s" => value,
+        "m" => value * 60,
+        "h" => value * 3600,
+        _ => return Err(invalid()),
+    };
+    Ok(start..start + (seconds as f64 / block_time).ceil() as BlockHeightDelta)
+}
+
+fn rpc_error(err: impl fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("Failed to query the node: {}", err))
+}
+
+/// Queries the node for the thresholds, the progress of the current epoch and the average time
+/// between blocks in it, in seconds.
+pub(crate) async fn query(
+    rpc_addr: &str,
+    account_id: Option<AccountId>,
+) -> io::Result<(AccountId, Thresholds, EpochProgress, f64)> {
+    let client = new_client(rpc_addr);
+    let status = client.status().await.map_err(rpc_error)?;
+    let account_id = account_id.or(status.validator_account_id).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "The node has no validator, pass --account-id")
+    })?;
+    let genesis_config: GenesisConfig =
+        serde_json::from_value(client.EXPERIMENTAL_genesis_config().await.map_err(rpc_error)?)
+            .map_err(rpc_error)?;
+    let validators = client.validators(None).await.map_err(rpc_error)?;
+    let validator =
+        validators.current_validators.iter().find(|v| v.account_id == account_id).ok_or_else(
+            || {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} isn't a validator of the current epoch", account_id),
+                )
+            },
+        )?;
+    let progress = EpochProgress {
+        epoch_start_height: validators.epoch_start_height,
+        current_height: status.sync_info.latest_block_height,
+        num_produced_blocks: validator.num_produced_blocks,
+        num_expected_blocks: validator.num_expected_blocks,
+    };
+    let first_block = client
+        .block_by_id(BlockId::Height(validators.epoch_start_height))
+        .await
+        .map_err(rpc_error)?;
+    let elapsed_ns = (status.sync_info.latest_block_time.timestamp_nanos() as u64)
+        .saturating_sub(first_block.header.timestamp_nanosec);
+    let blocks = progress.current_height.saturating_sub(progress.epoch_start_height).max(1);
+    let block_time = elapsed_ns as f64 / 1e9 / blocks as f64;
+    Ok((account_id, Thresholds::from(&genesis_config), progress, block_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thresholds() -> Thresholds {
+        Thresholds {
+            epoch_length: 1000,
+            block_producer_kickout_threshold: 90,
+            online_min_threshold: 0.9,
+            online_max_threshold: 0.99,
+        }
+    }
+
+    /// Half way through the epoch, expected to produce one block out of ten and produced all.
+    fn progress() -> EpochProgress {
+        EpochProgress {
+            epoch_start_height: 1000,
+            current_height: 1499,
+            num_produced_blocks: 50,
+            num_expected_blocks: 50,
+        }
+    }
+
+    #[test]
+    fn test_simulate() {
+        let outcomes = simulate(&thresholds(), &progress(), &[]);
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].heights, 1000..2000);
+        assert_eq!(outcomes[0].expected_blocks, 100.0);
+        assert_eq!(outcomes[0].reward, 1.0);
+
+        // 50 heights down out of 1000: 95% uptime.
+        let outcomes = simulate(&thresholds(), &progress(), &[1600..1650]);
+        assert_eq!(outcomes[0].downtime, 50);
+        assert_eq!(outcomes[0].produced_blocks, 95.0);
+        assert!(!outcomes[0].kicked_out);
+        assert!((outcomes[0].reward - 5.0 / 9.0).abs() < 1e-9);
+
+        // Over the end of the epoch, the next one being a fresh start.
+        let outcomes = simulate(&thresholds(), &progress(), &[1900..2150]);
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!((outcomes[0].downtime, outcomes[1].downtime), (100, 150));
+        assert!(!outcomes[0].kicked_out);
+        assert!(outcomes[1].kicked_out);
+        assert_eq!(outcomes[1].reward, 0.0);
+
+        // The past heights are already accounted for.
+        let outcomes = simulate(&thresholds(), &progress(), &[1000..1500]);
+        assert_eq!(outcomes[0].downtime, 0);
+    }
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("1000:50", 1.2), Ok(1000..1050));
+        assert_eq!(parse_window("1000:2h", 1.2), Ok(1000..7000));
+        assert_eq!(parse_window("1000:1m", 0.7), Ok(1000..1086));
+        assert!(parse_window("1000", 1.0).is_err());
+        assert!(parse_window("1000:2d", 1.0).is_err());
+        assert!(parse_window("x:2h", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_max_downtime() {
+        // 100 expected blocks, at most 10 can be missed.
+        assert_eq!(max_downtime(&thresholds(), &progress()), 100);
+        let late = EpochProgress { num_produced_blocks: 30, ..progress() };
+        assert_eq!(max_downtime(&thresholds(), &late), 0);
+    }
+}
@@ -0,0 +1,283 @@
+//! Offline decoder of signed transactions, for debugging their serialization.
+//!
+//! The blob is the borsh serialization of a `SignedTransaction`, as sent to the
+//! `broadcast_tx_*` methods of the JSON RPC, in base64 or hex. It's decoded without any access
+//! to a node: the signature is checked against the public key of the transaction, the actions
+//! are validated against the limits of the runtime config and their fees estimated. Whether the
+//! access key exists and has a lower nonce can only be checked on chain.
+
+use std::fmt::{self, Write};
+
+use near_primitives::account::AccessKeyPermission;
+use near_primitives::borsh::BorshDeserialize;
+use near_primitives::errors::RuntimeError;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::runtime::config::RuntimeConfig;
+use near_primitives::serialize::{from_base64, to_base64};
+use near_primitives::transaction::{Action, SignedTransaction};
+use near_primitives::types::{Balance, ProtocolVersion};
+use node_runtime::config::{exec_fee, total_send_fees, tx_cost};
+use node_runtime::validate_transaction;
+
+/// Decodes a hex blob, with or without `0x` prefix, or else a base64 one.
+pub(crate) fn decode_blob(blob: &str) -> Result<Vec<u8>, String> {
+    let blob = blob.trim();
+    let hex_digits = blob.strip_prefix("0x").unwrap_or(blob);
+    if hex_digits.len() % 2 == 0 && hex_digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return hex::decode(hex_digits).map_err(|err| format!("Invalid hex: {}", err));
+    }
+    from_base64(blob).map_err(|err| format!("Neither hex nor base64: {}", err))
+}
+
+pub(crate) struct ActionInspection {
+    description: String,
+    send_fee: u64,
+    exec_fee: u64,
+}
+
+/// What can be told of a signed transaction without the state of the chain.
+pub(crate) struct Inspection {
+    transaction: SignedTransaction,
+    signature_valid: bool,
+    actions: Vec<ActionInspection>,
+    gas_price: Balance,
+    gas_burnt: Option<u64>,
+    gas_remaining: Option<u64>,
+    total_cost: Option<Balance>,
+    /// Fields the transaction would be rejected for, or which are most likely a mistake.
+    problems: Vec<String>,
+}
+
+impl Inspection {
+    pub fn new(
+        bytes: &[u8],
+        config: &RuntimeConfig,
+        gas_price: Balance,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Self, String> {
+        let transaction = SignedTransaction::try_from_slice(bytes)
+            .map_err(|err| format!("Not a signed transaction: {}", err))?;
+        let tx = &transaction.transaction;
+        let mut problems = vec![];
+
+        if transaction.signature.key_type() != tx.public_key.key_type() {
+            problems.push(format!(
+                "The signature is of type {} but the public key of type {}",
+                transaction.signature.key_type(),
+                tx.public_key.key_type()
+            ));
+        }
+        let signature_valid =
+            transaction.signature.verify(transaction.get_hash().as_ref(), &tx.public_key);
+        if !signature_valid {
+            problems
+                .push("The signature doesn't match the transaction and its public key".to_string());
+        }
+        if tx.nonce == 0 {
+            problems.push("The nonce is 0, access keys start above it".to_string());
+        }
+        if tx.block_hash == CryptoHash::default() {
+            problems.push("The block hash is all zeros, not the one of a recent block".to_string());
+        }
+        if tx.actions.is_empty() {
+            problems.push("The transaction has no actions".to_string());
+        }
+        if let Err(err) =
+            validate_transaction(config, gas_price, &transaction, false, protocol_version)
+        {
+            problems.push(match err {
+                RuntimeError::InvalidTxError(err) => err.to_string(),
+                err => format!("{:?}", err),
+            });
+        }
+
+        let fees = &config.transaction_costs;
+        let sender_is_receiver = tx.signer_id == tx.receiver_id;
+        let actions = tx
+            .actions
+            .iter()
+            .map(|action| ActionInspection {
+                description: describe_action(action),
+                send_fee: total_send_fees(
+                    fees,
+                    sender_is_receiver,
+                    std::slice::from_ref(action),
+                    &tx.receiver_id,
+                    protocol_version,
+                )
+                .unwrap_or(u64::MAX),
+                exec_fee: exec_fee(fees, action, &tx.receiver_id, protocol_version),
+            })
+            .collect();
+        let cost = tx_cost(fees, tx, gas_price, sender_is_receiver, protocol_version).ok();
+
+        Ok(Self {
+            signature_valid,
+            actions,
+            gas_price,
+            gas_burnt: cost.as_ref().map(|cost| cost.gas_burnt),
+            gas_remaining: cost.as_ref().map(|cost| cost.gas_remaining),
+            total_cost: cost.as_ref().map(|cost| cost.total_cost),
+            problems,
+            transaction,
+        })
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn describe_action(action: &Action) -> String {
+    match action {
+        Action::CreateAccount(_) => "CreateAccount".to_string(),
+        Action::DeployContract(action) => format!(
+            "DeployContract of {} bytes with hash {}",
+            action.code.len(),
+            hash(&action.code)
+        ),
+        Action::FunctionCall(action) => {
+            // Arguments are printed as is when they are text, JSON most of the time.
+            let args = match std::str::from_utf8(&action.args) {
+                Ok(args) => args.to_string(),
+                Err(_) => format!("base64:{}", to_base64(&action.args)),
+            };
+            format!(
+                "FunctionCall {}({}) with {} gas attached and a deposit of {} yoctoNEAR",
+                action.method_name, args, action.gas, action.deposit
+            )
+        }
+        Action::Transfer(action) => format!("Transfer of {} yoctoNEAR", action.deposit),
+        Action::Stake(action) => {
+            format!("Stake of {} yoctoNEAR with key {}", action.stake, action.public_key)
+        }
+        #[cfg(feature = "protocol_feature_chunk_only_producers")]
+        Action::StakeChunkOnly(action) => {
+            format!("StakeChunkOnly of {} yoctoNEAR with key {}", action.stake, action.public_key)
+        }
+        Action::AddKey(action) => match &action.access_key.permission {
+            AccessKeyPermission::FullAccess => {
+                format!("AddKey {} with full access", action.public_key)
+            }
+            AccessKeyPermission::FunctionCall(permission) => {
+                let mut description = format!(
+                    "AddKey {} calling {} on {}",
+                    action.public_key,
+                    if permission.method_names.is_empty() {
+                        "any method".to_string()
+                    } else {
+                        permission.method_names.join(", ")
+                    },
+                    permission.receiver_id
+                );
+                match permission.allowance {
+                    Some(allowance) => {
+                        write!(description, " with an allowance of {} yoctoNEAR", allowance)
+                    }
+                    None => write!(description, " with an unlimited allowance"),
+                }
+                .unwrap();
+                description
+            }
+        },
+        Action::DeleteKey(action) => format!("DeleteKey {}", action.public_key),
+        Action::DeleteAccount(action) => {
+            format!("DeleteAccount with beneficiary {}", action.beneficiary_id)
+        }
+    }
+}
+
+fn or_overflow(value: Option<impl fmt::Display>) -> String {
+    value.map_or_else(|| "overflow".to_string(), |value| value.to_string())
+}
+
+impl fmt::Display for Inspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tx = &self.transaction.transaction;
+        writeln!(f, "Transaction {}", self.transaction.get_hash())?;
+        writeln!(f, "  signer:     {}", tx.signer_id)?;
+        writeln!(f, "  public key: {}", tx.public_key)?;
+        writeln!(f, "  nonce:      {}", tx.nonce)?;
+        writeln!(f, "  receiver:   {}", tx.receiver_id)?;
+        writeln!(f, "  block hash: {}", tx.block_hash)?;
+        writeln!(
+            f,
+            "  signature:  {} ({})",
+            self.transaction.signature,
+            if self.signature_valid { "valid" } else { "INVALID" }
+        )?;
+        writeln!(f, "  size:       {} bytes", self.transaction.get_size())?;
+        writeln!(f, "Actions:")?;
+        for (index, action) in self.actions.iter().enumerate() {
+            writeln!(f, "  {}. {}", index + 1, action.description)?;
+            writeln!(
+                f,
+                "     send fee {} gas, execution fee {} gas",
+                action.send_fee, action.exec_fee
+            )?;
+        }
+        writeln!(f, "Estimated cost at a gas price of {} yoctoNEAR:", self.gas_price)?;
+        writeln!(f, "  gas burnt on conversion to a receipt: {}", or_overflow(self.gas_burnt))?;
+        writeln!(f, "  gas prepaid for the receipts:         {}", or_overflow(self.gas_remaining))?;
+        writeln!(
+            f,
+            "  total cost with deposits, at most:    {} yoctoNEAR",
+            or_overflow(self.total_cost)
+        )?;
+        if self.problems.is_empty() {
+            writeln!(f, "No problems found, the state of the chain is left to check")?;
+        } else {
+            writeln!(f, "Problems:")?;
+            for problem in &self.problems {
+                writeln!(f, "  - {}", problem)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::borsh::BorshSerialize;
+    use near_primitives::runtime::config_store::RuntimeConfigStore;
+    use near_primitives::version::PROTOCOL_VERSION;
+
+    fn inspect(transaction: &SignedTransaction) -> Inspection {
+        let blob = to_base64(&transaction.try_to_vec().unwrap());
+        let config = RuntimeConfigStore::new(None).get_config(PROTOCOL_VERSION).clone();
+        Inspection::new(&decode_blob(&blob).unwrap(), &config, 100_000_000, PROTOCOL_VERSION)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_inspect() {
+        let signer = InMemorySigner::from_seed("alice.near".parse().unwrap(), KeyType::ED25519, "");
+        let mut transaction = SignedTransaction::send_money(
+            1,
+            "alice.near".parse().unwrap(),
+            "bob.near".parse().unwrap(),
+            &signer,
+            10,
+            hash(b"block"),
+        );
+        let inspection = inspect(&transaction);
+        assert!(inspection.is_valid(), "{}", inspection);
+        assert!(inspection.gas_burnt.unwrap() > 0);
+
+        transaction.transaction.nonce = 0;
+        transaction.transaction.actions.clear();
+        let inspection = inspect(&transaction);
+        assert_eq!(inspection.problems.len(), 3, "{}", inspection);
+        assert!(!inspection.signature_valid);
+    }
+
+    #[test]
+    fn test_decode_blob() {
+        assert_eq!(decode_blob("0x0aff").unwrap(), vec![0x0a, 0xff]);
+        assert_eq!(decode_blob("0aff").unwrap(), vec![0x0a, 0xff]);
+        assert_eq!(decode_blob("Cv8=").unwrap(), vec![0x0a, 0xff]);
+        assert!(decode_blob("not a blob").is_err());
+    }
+}
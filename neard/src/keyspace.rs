@@ -0,0 +1,291 @@
+//! Breakdown of the disk usage of the database.
+//!
+//! `database keyspace-report` scans every key of every column of a stopped node and sums the size
+//! of the keys and values per column, per shard for the columns keyed by shard UID, and per type of
+//! data: trie nodes, receipts, outcomes and so on. The state of the shards is only stored as trie
+//! nodes in this version of the database, there is no flat state to report separately.
+//!
+//! The report is saved as JSON so that the next one can show how each part grew in between.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use near_primitives::shard_layout::ShardUId;
+use near_store::{DBCol, Store};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Usage {
+    pub keys: u64,
+    pub key_bytes: u64,
+    pub value_bytes: u64,
+}
+
+impl Usage {
+    fn add(&mut self, key: &[u8], value: &[u8]) {
+        self.keys += 1;
+        self.key_bytes += key.len() as u64;
+        self.value_bytes += value.len() as u64;
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.key_bytes + self.value_bytes
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub(crate) struct KeyspaceReport {
+    pub generated_at: String,
+    pub columns: BTreeMap<String, Usage>,
+    /// Size of the SST files of every column, compressed, as reported by RocksDB.
+    pub column_disk_bytes: BTreeMap<String, u64>,
+    /// Usage of the columns keyed by shard UID, per shard UID.
+    pub shards: BTreeMap<String, Usage>,
+    pub key_types: BTreeMap<String, Usage>,
+}
+
+impl KeyspaceReport {
+    pub fn collect(store: &Store) -> Self {
+        let mut report =
+            Self { generated_at: chrono::Utc::now().to_rfc3339(), ..Default::default() };
+        for col in DBCol::iter() {
+            for (key, value) in store.iter_without_rc_logic(col) {
+                report.record(col, &key, &value);
+            }
+            if let Some(size) = store.get_rocksdb().and_then(|db| db.column_disk_size(col)) {
+                report.column_disk_bytes.insert(format!("{:?}", col), size);
+            }
+        }
+        report
+    }
+
+    fn record(&mut self, col: DBCol, key: &[u8], value: &[u8]) {
+        self.columns.entry(format!("{:?}", col)).or_default().add(key, value);
+        self.key_types.entry(key_type(col).to_string()).or_default().add(key, value);
+        if let Some(shard_uid) = shard_uid(col, key) {
+            let shard = format!("s{}.v{}", shard_uid.shard_id, shard_uid.version);
+            self.shards.entry(shard).or_default().add(key, value);
+        }
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        serde_json::from_reader(File::open(path)?).map_err(io::Error::from)
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        serde_json::to_writer_pretty(File::create(path)?, self).map_err(io::Error::from)
+    }
+
+    /// The report, with the change of every line since `previous` if given.
+    pub fn compared_to<'a>(&'a self, previous: Option<&'a Self>) -> Comparison<'a> {
+        Comparison { report: self, previous }
+    }
+}
+
+/// Shard UID in the key of the columns keyed by shard UID.
+fn shard_uid(col: DBCol, key: &[u8]) -> Option<ShardUId> {
+    let bytes = match col {
+        // The shard UID followed by the hash of the node.
+        DBCol::ColState => key.get(..8)?,
+        // The block hash followed by the shard UID, see `get_block_shard_uid`.
+        DBCol::ColChunkExtra | DBCol::ColTrieChanges | DBCol::ColStateChangesForSplitStates
+            if key.len() == 40 =>
+        {
+            &key[32..]
+        }
+        _ => return None,
+    };
+    ShardUId::try_from(bytes).ok()
+}
+
+fn key_type(col: DBCol) -> &'static str {
+    use DBCol::*;
+    match col {
+        ColState => "trie nodes",
+        ColTrieChanges | ColStateChanges | ColStateChangesForSplitStates => "trie changes",
+        ColOutgoingReceipts
+        | ColIncomingReceipts
+        | ColReceipts
+        | ColReceiptIdToShardId
+        | ColFailedReceipts => "receipts",
+        ColTransactionResult | ColOutcomeIds => "outcomes",
+        ColTransactions => "transactions",
+        ColBlock
+        | ColBlockHeader
+        | ColBlockHeight
+        | ColBlockInfo
+        | ColBlockExtra
+        | ColBlockPerHeight
+        | ColNextBlockHashes
+        | ColBlockRefCount
+        | ColBlockMerkleTree
+        | ColBlockOrdinal
+        | ColHeaderHashesByHeight
+        | ColProcessedBlockHeights
+        | ColChallengedBlocks
+        | ColBlocksToCatchup
+        | ColBlockTransactionStats => "blocks",
+        ColChunks
+        | ColPartialChunks
+        | ColChunkExtra
+        | ColInvalidChunks
+        | ColChunkPerHeightShard
+        | ColChunkHashesByHeight => "chunks",
+        ColEpochInfo
+        | ColEpochStart
+        | ColEpochLightClientBlocks
+        | ColEpochValidatorInfo
+        | ColEpochChainStats
+        | ColEpochSigners => "epochs",
+        ColStateDlInfos | ColStateHeaders | ColStateParts => "state sync",
+        ColCachedContractCode | ColContractHistory => "contracts",
+        ColPeers
+        | ColAccountAnnouncements
+        | ColPeerComponent
+        | ColComponentEdges
+        | ColLastComponentNonce => "network",
+        _ => "other",
+    }
+}
+
+pub(crate) struct Comparison<'a> {
+    report: &'a KeyspaceReport,
+    previous: Option<&'a KeyspaceReport>,
+}
+
+impl Comparison<'_> {
+    fn section(
+        &self,
+        f: &mut fmt::Formatter,
+        title: &str,
+        select: fn(&KeyspaceReport) -> &BTreeMap<String, Usage>,
+    ) -> fmt::Result {
+        let current = select(self.report);
+        let previous = self.previous.map(select);
+        let mut names: BTreeSet<&String> = current.keys().collect();
+        names.extend(previous.iter().flat_map(|previous| previous.keys()));
+        let mut rows: Vec<_> = names
+            .into_iter()
+            .map(|name| {
+                let usage = current.get(name).copied().unwrap_or_default();
+                let before =
+                    previous.map(|previous| previous.get(name).copied().unwrap_or_default());
+                (name, usage, before)
+            })
+            .collect();
+        rows.sort_by_key(|(_, usage, _)| std::cmp::Reverse(usage.bytes()));
+
+        writeln!(f, "{}:", title)?;
+        for (name, usage, before) in rows {
+            write!(
+                f,
+                "  {:<32} {:>10} {:>12} keys",
+                name,
+                format_bytes(usage.bytes()),
+                usage.keys
+            )?;
+            if let Some(before) = before {
+                write!(
+                    f,
+                    "  {:>11} {:>+12} keys",
+                    format_delta(usage.bytes() as i128 - before.bytes() as i128),
+                    usage.keys as i128 - before.keys as i128
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Comparison<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.previous {
+            Some(previous) => writeln!(
+                f,
+                "Keyspace at {}, compared to {}",
+                self.report.generated_at, previous.generated_at
+            )?,
+            None => writeln!(f, "Keyspace at {}", self.report.generated_at)?,
+        }
+        self.section(f, "Columns", |report| &report.columns)?;
+        let disk_bytes: u64 = self.report.column_disk_bytes.values().sum();
+        if disk_bytes > 0 {
+            write!(f, "  {:<32} {:>10} on disk", "total", format_bytes(disk_bytes))?;
+            if let Some(previous) = self.previous {
+                let before: u64 = previous.column_disk_bytes.values().sum();
+                write!(f, "  {:>11}", format_delta(disk_bytes as i128 - before as i128))?;
+            }
+            writeln!(f)?;
+        }
+        self.section(f, "Shards", |report| &report.shards)?;
+        self.section(f, "Key types", |report| &report.key_types)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn format_delta(bytes: i128) -> String {
+    let sign = if bytes < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_bytes(bytes.unsigned_abs() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyspace_report() {
+        let shard_uid = ShardUId { version: 1, shard_id: 3 };
+        let block_hash = [7u8; 32];
+        let mut report = KeyspaceReport::default();
+        report.record(DBCol::ColState, &[&shard_uid.to_bytes()[..], &[1; 32]].concat(), &[0; 100]);
+        report.record(
+            DBCol::ColTrieChanges,
+            &[&block_hash[..], &shard_uid.to_bytes()].concat(),
+            &[0; 10],
+        );
+        report.record(DBCol::ColReceipts, &[2; 32], &[0; 50]);
+        assert_eq!(report.shards.len(), 1);
+        assert_eq!(report.shards["s3.v1"], Usage { keys: 2, key_bytes: 80, value_bytes: 110 });
+        assert_eq!(report.key_types["trie nodes"].value_bytes, 100);
+        assert_eq!(report.key_types["receipts"].bytes(), 82);
+        assert_eq!(report.columns["ColState"].keys, 1);
+
+        let mut current = report.clone();
+        current.record(
+            DBCol::ColState,
+            &[&shard_uid.to_bytes()[..], &[2; 32]].concat(),
+            &[0; 2008],
+        );
+        let output = current.compared_to(Some(&report)).to_string();
+        let line = output.lines().find(|line| line.contains("trie nodes")).unwrap();
+        assert!(line.contains("+2.0 KiB") && line.contains("+1 keys"), "{}", line);
+        assert!(output.contains("s3.v1"), "{}", output);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(1000), "1000 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 << 30), "3.0 GiB");
+        assert_eq!(format_delta(-2048), "-2.0 KiB");
+    }
+}
@@ -0,0 +1,355 @@
+//! Deterministic in-process localnet driven by a scenario script.
+//!
+//! All validators run in a single thread without networking: blocks, chunk parts and approvals
+//! are delivered in a fixed order, time is a fake clock advancing by `block_interval_ms` on
+//! every height, and all random choices of the clients and the scenario derive from the seed.
+//! Running the same scenario with the same seed and binary produces the same blocks, so a seed
+//! and a script are enough to reproduce a bug.
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::TimeZone;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use near_chain::{ChainGenesis, Provenance, RuntimeAdapter};
+use near_chain_configs::Genesis;
+use near_client::test_utils::TestEnv;
+use near_client::Error;
+use near_crypto::{InMemorySigner, KeyType};
+use near_network::types::{NetworkRequests, PeerManagerMessageRequest};
+use near_primitives::block_header::ApprovalType;
+use near_primitives::epoch_manager::RngSeed;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::network::PeerId;
+use near_primitives::sharding::PartialEncodedChunk;
+use near_primitives::time::{Clock, Utc};
+use near_primitives::transaction::SignedTransaction;
+use near_primitives::types::{AccountId, Balance, BlockHeight, BlockHeightDelta, Nonce};
+use near_primitives::utils::MaybeValidated;
+use near_store::test_utils::create_test_store;
+use nearcore::config::GenesisExt;
+use nearcore::NightshadeRuntime;
+
+/// Genesis time of every localnet, as the real time would make the blocks differ between runs.
+const GENESIS_TIMESTAMP: i64 = 1_600_000_000;
+
+fn default_validators() -> usize {
+    4
+}
+
+fn default_epoch_length() -> BlockHeightDelta {
+    20
+}
+
+fn default_block_interval_ms() -> u64 {
+    1000
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Scenario {
+    /// Number of validators, with accounts `test0`, `test1` and so on.
+    #[serde(default = "default_validators")]
+    pub validators: usize,
+    #[serde(default = "default_epoch_length")]
+    pub epoch_length: BlockHeightDelta,
+    /// Time between heights on the fake clock.
+    #[serde(default = "default_block_interval_ms")]
+    pub block_interval_ms: u64,
+    /// Last height to run.
+    pub heights: BlockHeight,
+    #[serde(default)]
+    pub events: Vec<ScenarioEvent>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScenarioEvent {
+    /// Height before which the event happens.
+    pub height: BlockHeight,
+    #[serde(flatten)]
+    pub action: ScenarioAction,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ScenarioAction {
+    /// Submits a transfer to all validators.
+    SendMoney {
+        signer: AccountId,
+        receiver: AccountId,
+        #[serde(with = "near_primitives::serialize::u128_dec_format")]
+        amount: Balance,
+    },
+    /// Submits transfers between random validators.
+    RandomTransfers { count: usize },
+    /// The block producer of the height is offline and doesn't produce its block.
+    SkipBlock,
+    /// Approvals sent by the validators while processing the height are lost.
+    DropApprovals { validators: Vec<AccountId> },
+}
+
+impl Scenario {
+    pub fn from_file(path: &Path) -> io::Result<Scenario> {
+        serde_yaml::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// Outcome of a height of the localnet.
+#[derive(Debug, PartialEq)]
+pub struct HeightReport {
+    pub height: BlockHeight,
+    /// Producer and hash of the block, if one was produced at this height.
+    pub block: Option<(AccountId, CryptoHash)>,
+    pub final_height: BlockHeight,
+}
+
+impl std::fmt::Display for HeightReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.block {
+            Some((producer, hash)) => write!(f, "#{} {} by {}", self.height, hash, producer)?,
+            None => write!(f, "#{} skipped", self.height)?,
+        }
+        write!(f, " final #{}", self.final_height)
+    }
+}
+
+struct Localnet {
+    env: TestEnv,
+    accounts: Vec<AccountId>,
+    peer_ids: Vec<PeerId>,
+    nonces: HashMap<AccountId, Nonce>,
+    rng: StdRng,
+}
+
+impl Localnet {
+    fn new(seed: u64, scenario: &Scenario) -> Self {
+        let accounts: Vec<AccountId> =
+            (0..scenario.validators).map(|i| format!("test{}", i).parse().unwrap()).collect();
+        let mut genesis = Genesis::test(accounts.clone(), accounts.len() as u64);
+        genesis.config.chain_id = "localnet".to_string();
+        genesis.config.epoch_length = scenario.epoch_length;
+        let runtimes = accounts
+            .iter()
+            .map(|_| {
+                Arc::new(NightshadeRuntime::test(Path::new("."), create_test_store(), &genesis))
+                    as Arc<dyn RuntimeAdapter>
+            })
+            .collect();
+        let seeds = accounts
+            .iter()
+            .map(|account_id| {
+                let rng_seed: RngSeed = hash(format!("{}/{}", seed, account_id).as_bytes()).0;
+                (account_id.clone(), rng_seed)
+            })
+            .collect();
+        let env = TestEnv::builder(ChainGenesis::from(&genesis))
+            .clients(accounts.clone())
+            .validators(accounts.clone())
+            .clients_random_seeds(seeds)
+            .runtime_adapters(runtimes)
+            .build();
+        let peer_ids =
+            accounts.iter().map(|account_id| PeerId::new(signer(account_id).public_key)).collect();
+        Self { env, accounts, peer_ids, nonces: HashMap::new(), rng: StdRng::seed_from_u64(seed) }
+    }
+
+    fn send_money(&mut self, signer_id: &AccountId, receiver_id: &AccountId, amount: Balance) {
+        let nonce = self.nonces.entry(signer_id.clone()).or_insert(0);
+        *nonce += 1;
+        let block_hash = self.env.clients[0].chain.head().unwrap().last_block_hash;
+        let tx = SignedTransaction::send_money(
+            *nonce,
+            signer_id.clone(),
+            receiver_id.clone(),
+            &signer(signer_id),
+            amount,
+            block_hash,
+        );
+        for client in self.env.clients.iter_mut() {
+            client.process_tx(tx.clone(), false, false);
+        }
+    }
+
+    fn apply(&mut self, action: &ScenarioAction) {
+        match action {
+            ScenarioAction::SendMoney { signer, receiver, amount } => {
+                self.send_money(signer, receiver, *amount)
+            }
+            ScenarioAction::RandomTransfers { count } => {
+                for _ in 0..*count {
+                    let signer = self.accounts[self.rng.gen_range(0, self.accounts.len())].clone();
+                    let receiver =
+                        self.accounts[self.rng.gen_range(0, self.accounts.len())].clone();
+                    let amount = self.rng.gen_range(1, 1_000_000);
+                    self.send_money(&signer, &receiver, amount);
+                }
+            }
+            ScenarioAction::SkipBlock | ScenarioAction::DropApprovals { .. } => {}
+        }
+    }
+
+    /// Delivers chunk parts and approvals sent by the clients, except approvals sent by
+    /// `dropped_approvals`, in the order of the clients.
+    fn deliver_messages(&mut self, dropped_approvals: &[AccountId]) -> Result<(), Error> {
+        for index in 0..self.env.clients.len() {
+            let sender = self.accounts[index].clone();
+            while let Some(request) = self.env.network_adapters[index].pop() {
+                match request {
+                    PeerManagerMessageRequest::NetworkRequests(
+                        NetworkRequests::PartialEncodedChunkMessage {
+                            account_id,
+                            partial_encoded_chunk,
+                        },
+                    ) => {
+                        let client = self.env.client(&account_id);
+                        let accepted_blocks = client.process_partial_encoded_chunk(
+                            MaybeValidated::from(PartialEncodedChunk::from(partial_encoded_chunk)),
+                        )?;
+                        for block in accepted_blocks {
+                            client.on_block_accepted_with_optional_chunk_produce(
+                                block.hash,
+                                block.status,
+                                block.provenance,
+                                false,
+                            );
+                        }
+                    }
+                    PeerManagerMessageRequest::NetworkRequests(NetworkRequests::Approval {
+                        approval_message,
+                    }) => {
+                        if !dropped_approvals.contains(&sender) {
+                            self.env.client(&approval_message.target).collect_block_approval(
+                                &approval_message.approval,
+                                ApprovalType::PeerApproval(self.peer_ids[index].clone()),
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Does what the doomslug timer of the clients does: sends the approvals which are due.
+    fn run_doomslug_timers(&mut self) -> Result<(), Error> {
+        for client in self.env.clients.iter_mut() {
+            client.check_and_update_doomslug_tip()?;
+            let approvals = client.doomslug.process_timer(Clock::instant());
+            let head = client.chain.head()?;
+            if client.is_validator(&head.epoch_id, &head.last_block_hash)
+                || client.is_validator(&head.next_epoch_id, &head.last_block_hash)
+            {
+                let (tip_hash, _) = client.doomslug.get_tip();
+                for approval in approvals {
+                    client.send_approval(&tip_hash, approval)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn produce_block(
+        &mut self,
+        height: BlockHeight,
+    ) -> Result<Option<(AccountId, CryptoHash)>, Error> {
+        let head = self.env.clients[0].chain.head()?;
+        let runtime_adapter = &self.env.clients[0].runtime_adapter;
+        let epoch_id = runtime_adapter.get_epoch_id_from_prev_block(&head.last_block_hash)?;
+        let producer = runtime_adapter.get_block_producer(&epoch_id, height)?;
+        let producer_index =
+            self.accounts.iter().position(|account_id| *account_id == producer).unwrap();
+        let block = match self.env.clients[producer_index].produce_block(height)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        let block_hash = *block.hash();
+        for index in 0..self.env.clients.len() {
+            let provenance =
+                if index == producer_index { Provenance::PRODUCED } else { Provenance::NONE };
+            self.env.process_block(index, block.clone(), provenance);
+        }
+        Ok(Some((producer, block_hash)))
+    }
+}
+
+fn signer(account_id: &AccountId) -> InMemorySigner {
+    InMemorySigner::from_seed(account_id.clone(), KeyType::ED25519, account_id.as_ref())
+}
+
+/// Runs the scenario and reports every height.  Must run on a thread of its own, as it makes the
+/// clock of the thread fake.
+pub fn run(seed: u64, scenario: &Scenario) -> Result<Vec<HeightReport>, Error> {
+    Clock::set_fake(Utc.timestamp(GENESIS_TIMESTAMP, 0));
+    let mut localnet = Localnet::new(seed, scenario);
+    let block_interval = Duration::from_millis(scenario.block_interval_ms);
+    let mut reports = vec![];
+    for height in 1..=scenario.heights {
+        Clock::advance(block_interval);
+        let events: Vec<&ScenarioEvent> =
+            scenario.events.iter().filter(|event| event.height == height).collect();
+        let mut skip_block = false;
+        let mut dropped_approvals = vec![];
+        for event in events {
+            match &event.action {
+                ScenarioAction::SkipBlock => skip_block = true,
+                ScenarioAction::DropApprovals { validators } => {
+                    dropped_approvals.extend(validators.iter().cloned())
+                }
+                action => localnet.apply(action),
+            }
+        }
+        let block = if skip_block { None } else { localnet.produce_block(height)? };
+        localnet.deliver_messages(&dropped_approvals)?;
+        localnet.run_doomslug_timers()?;
+        localnet.deliver_messages(&dropped_approvals)?;
+        let final_height = localnet.env.clients[0].chain.final_head()?.height;
+        reports.push(HeightReport { height, block, final_height });
+    }
+    Clock::reset();
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+validators: 2
+epoch_length: 5
+heights: 12
+events:
+  - height: 2
+    action: send_money
+    signer: test0
+    receiver: test1
+    amount: "1000"
+  - height: 4
+    action: random_transfers
+    count: 3
+  - height: 6
+    action: skip_block
+  - height: 8
+    action: drop_approvals
+    validators: [test1]
+"#;
+
+    #[test]
+    fn test_localnet_is_deterministic() {
+        let scenario: Scenario = serde_yaml::from_str(SCENARIO).unwrap();
+        let run_on_thread = |seed| {
+            let scenario: Scenario = serde_yaml::from_str(SCENARIO).unwrap();
+            std::thread::spawn(move || run(seed, &scenario).unwrap()).join().unwrap()
+        };
+        let first = run_on_thread(7);
+        assert_eq!(first.len() as BlockHeight, scenario.heights);
+        assert!(first[5].block.is_none());
+        assert!(first.iter().filter(|report| report.block.is_some()).count() >= 10);
+        assert_eq!(first, run_on_thread(7));
+    }
+}
@@ -1,4 +1,12 @@
+mod bench;
+mod block_bundle;
 mod cli;
+mod downtime;
+mod inspect_tx;
+mod keyspace;
+mod localnet;
+mod refcount_audit;
+mod report;
 
 use std::env;
 
@@ -6,6 +14,7 @@ use self::cli::NeardCmd;
 use clap::crate_version;
 use git_version::git_version;
 use near_performance_metrics;
+use near_primitives::features::BuildFeature;
 use near_primitives::version::{Version, DB_VERSION, PROTOCOL_VERSION};
 #[cfg(feature = "memory_stats")]
 use near_rust_allocator_proxy::allocator::MyAllocator;
@@ -31,6 +40,7 @@ static NEARD_VERSION_STRING: Lazy<String> = Lazy::new(|| {
         NEARD_VERSION.version, NEARD_VERSION.build, PROTOCOL_VERSION, DB_VERSION
     )
 });
+static PROTOCOL_VERSION_STRING: Lazy<String> = Lazy::new(|| PROTOCOL_VERSION.to_string());
 static DEFAULT_HOME: Lazy<PathBuf> = Lazy::new(|| get_default_home());
 
 #[cfg(feature = "memory_stats")]
@@ -42,11 +52,19 @@ static ALLOC: MyAllocator<tikv_jemallocator::Jemalloc> =
 #[global_allocator]
 static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
+const BUILD_FEATURES: &[BuildFeature] = &[
+    #[cfg(feature = "jemalloc")]
+    BuildFeature { name: "jemalloc", description: "Allocate memory with jemalloc" },
+    #[cfg(feature = "state_viewer")]
+    BuildFeature { name: "state_viewer", description: "The view_state subcommand" },
+];
+
 fn main() {
     // We use it to automatically search the for root certificates to perform HTTPS calls
     // (sending telemetry and downloading genesis)
     openssl_probe::init_ssl_cert_env_vars();
     near_performance_metrics::process::schedule_printing_performance_stats(Duration::from_secs(60));
+    near_primitives::features::register_build_features(BUILD_FEATURES);
 
     NeardCmd::parse_and_run()
 }
@@ -0,0 +1,120 @@
+//! Audit of the reference counts of the trie nodes of a stopped node.
+//!
+//! `database refcount-audit` counts the references to every node and value of the state of the
+//! selected shards at the head, adds the changes the garbage collection of the retained blocks
+//! will undo, and compares them to the counts stored in `ColState`. Leaked nodes, stored but
+//! unreferenced, only waste disk space; dangling nodes, referenced but missing, break reading the
+//! state. With `--repair` the stored counts are set to the expected ones, unless there are
+//! dangling nodes, which only syncing the state again fixes.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use near_primitives::block::Tip;
+use near_primitives::block_header::BlockHeader;
+use near_primitives::borsh::BorshDeserialize;
+use near_primitives::hash::CryptoHash;
+use near_primitives::shard_layout::{get_block_shard_uid, ShardUId};
+use near_primitives::types::chunk_extra::ChunkExtra;
+use near_primitives::types::ShardId;
+use near_primitives::utils::index_to_bytes;
+use near_store::{DBCol, RefcountAudit, RefcountReport, Store, TrieChanges, HEAD_KEY};
+
+/// Hashes listed per kind of mismatch, the others are only counted.
+const MAX_LISTED: usize = 10;
+
+/// The shards which state is stored at the head, restricted to `shard_ids` if not empty.
+pub(crate) fn head_shard_uids(
+    store: &Store,
+    shard_ids: &[ShardId],
+) -> Result<(Tip, Vec<ShardUId>), String> {
+    let head = store
+        .get_ser::<Tip>(DBCol::ColBlockMisc, HEAD_KEY)
+        .map_err(|err| err.to_string())?
+        .ok_or("No head found, the node never started")?;
+    let shard_uids: Vec<_> = store
+        .iter_prefix(DBCol::ColChunkExtra, head.last_block_hash.as_ref())
+        .filter_map(|(key, _)| ShardUId::try_from(&key[32..]).ok())
+        .filter(|shard_uid| shard_ids.is_empty() || shard_ids.contains(&shard_uid.shard_id))
+        .collect();
+    for shard_id in shard_ids {
+        if !shard_uids.iter().any(|shard_uid| shard_uid.shard_id == *shard_id) {
+            return Err(format!("Shard {} isn't tracked at the head", shard_id));
+        }
+    }
+    Ok((head, shard_uids))
+}
+
+/// Whether the block is on the canonical chain, as opposed to a fork.
+fn is_canonical(store: &Store, block_hash: &CryptoHash) -> Result<bool, String> {
+    let header = match store
+        .get_ser::<BlockHeader>(DBCol::ColBlockHeader, block_hash.as_ref())
+        .map_err(|err| err.to_string())?
+    {
+        Some(header) => header,
+        None => return Ok(false),
+    };
+    let canonical = store
+        .get_ser::<CryptoHash>(DBCol::ColBlockHeight, &index_to_bytes(header.height()))
+        .map_err(|err| err.to_string())?;
+    Ok(canonical.as_ref() == Some(block_hash))
+}
+
+pub(crate) fn audit(
+    store: &Store,
+    head: &Tip,
+    shard_uid: ShardUId,
+) -> Result<RefcountReport, String> {
+    let chunk_extra = store
+        .get_ser::<ChunkExtra>(
+            DBCol::ColChunkExtra,
+            &get_block_shard_uid(&head.last_block_hash, &shard_uid),
+        )
+        .map_err(|err| err.to_string())?
+        .ok_or_else(|| format!("No chunk extra of shard {:?} at the head", shard_uid))?;
+    let mut audit = RefcountAudit::new(store, shard_uid, chunk_extra.state_root())
+        .map_err(|err| err.to_string())?;
+    let shard_uid_bytes = shard_uid.to_bytes();
+    for (key, value) in store.iter_without_rc_logic(DBCol::ColTrieChanges) {
+        if key.len() != 40 || key[32..] != shard_uid_bytes {
+            continue;
+        }
+        let block_hash = CryptoHash::try_from(&key[..32]).unwrap();
+        let trie_changes = TrieChanges::try_from_slice(&value).map_err(|err| {
+            format!("Failed to decode the trie changes of {}: {}", block_hash, err)
+        })?;
+        audit.add_pending_gc(&trie_changes, is_canonical(store, &block_hash)?);
+    }
+    Ok(audit.check(store))
+}
+
+pub(crate) struct Summary<'a>(pub &'a RefcountReport);
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let report = self.0;
+        writeln!(
+            f,
+            "Shard s{}.v{}: {} nodes and values, {} leaked, {} drifted, {} dangling",
+            report.shard_uid.shard_id,
+            report.shard_uid.version,
+            report.stored,
+            report.leaked.len(),
+            report.drifted.len(),
+            report.dangling.len()
+        )?;
+        for (kind, mismatches) in [("leaked", &report.leaked), ("drifted", &report.drifted)] {
+            for mismatch in mismatches.iter().take(MAX_LISTED) {
+                writeln!(
+                    f,
+                    "  {:<8} {} stored {}, expected {}",
+                    kind, mismatch.hash, mismatch.stored, mismatch.expected
+                )?;
+            }
+        }
+        for hash in report.dangling.iter().take(MAX_LISTED) {
+            writeln!(f, "  {:<8} {}", "dangling", hash)?;
+        }
+        Ok(())
+    }
+}
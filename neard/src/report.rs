@@ -0,0 +1,241 @@
+//! Self-report bundle of a node, to attach to support requests.
+//!
+//! The bundle is a gzip compressed JSON document assembled from the running node through its
+//! JSON RPC: its version and sync status, the block production of its validator over the last
+//! epochs, its peers, the latency histograms of its metrics, and its config with the secrets
+//! and the public addresses redacted.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use near_jsonrpc_client::{new_client, JsonRpcClient};
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{AccountId, BlockHeight, BlockId, EpochHeight};
+use near_primitives::version::Version;
+use near_primitives::views::{CurrentEpochValidatorInfo, StatusResponse, ValidatorKickoutView};
+
+/// Config fields which values are replaced, wherever they appear.
+const REDACTED_FIELDS: &[&str] =
+    &["key", "admin_key", "secret", "password", "token", "url", "rpc_urls", "public_addrs"];
+const REDACTED: &str = "REDACTED";
+
+/// Maximum size of the responses of the node.
+const PAYLOAD_LIMIT: usize = 100 * 1024 * 1024;
+
+#[derive(Serialize)]
+pub(crate) struct Report {
+    generated_at: String,
+    /// Version of the binary which generated the report, which may differ from the one of the
+    /// running node given in its status.
+    neard_version: Version,
+    status: StatusResponse,
+    /// From the current epoch to the oldest one.
+    epochs: Vec<EpochReport>,
+    network_info: Value,
+    /// Histograms of the Prometheus metrics of the node, in the text exposition format.
+    latency_histograms: String,
+    config: Value,
+}
+
+#[derive(Serialize)]
+struct EpochReport {
+    epoch_height: EpochHeight,
+    epoch_start_height: BlockHeight,
+    /// Stake and production of the validator of the node, if it was a validator in the epoch.
+    validator: Option<CurrentEpochValidatorInfo>,
+    /// Why the validator of the node was kicked out at the end of the epoch before, if it was.
+    prev_epoch_kickout: Option<ValidatorKickoutView>,
+}
+
+fn rpc_error(err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("Failed to query the node: {}", err))
+}
+
+impl Report {
+    pub async fn collect(
+        rpc_addr: &str,
+        config: Value,
+        neard_version: Version,
+        last_epochs: u64,
+    ) -> io::Result<Self> {
+        let client = new_client(rpc_addr);
+        let status = client.status().await.map_err(rpc_error)?;
+        let epochs =
+            epoch_reports(&client, status.validator_account_id.as_ref(), last_epochs).await?;
+        let network_info =
+            serde_json::from_slice(&http_get(&client, "network_info").await?).map_err(rpc_error)?;
+        let metrics = http_get(&client, "metrics").await?;
+        Ok(Self {
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            neard_version,
+            status,
+            epochs,
+            network_info,
+            latency_histograms: histograms(&String::from_utf8_lossy(&metrics)),
+            config: redact(config),
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut encoder = GzEncoder::new(File::create(path)?, Compression::default());
+        serde_json::to_writer_pretty(&mut encoder, self).map_err(io::Error::from)?;
+        encoder.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+async fn http_get(client: &JsonRpcClient, path: &str) -> io::Result<Vec<u8>> {
+    let mut response = client
+        .client
+        .get(format!("{}/{}", client.server_addr, path))
+        .send()
+        .await
+        .map_err(rpc_error)?;
+    let body = response.body().limit(PAYLOAD_LIMIT).await.map_err(rpc_error)?;
+    Ok(body.to_vec())
+}
+
+/// Walks back the epochs from the current one. Stops early at genesis, or with a warning at
+/// the epochs the node no longer has the data of.
+async fn epoch_reports(
+    client: &JsonRpcClient,
+    account_id: Option<&AccountId>,
+    last_epochs: u64,
+) -> io::Result<Vec<EpochReport>> {
+    let mut reports = vec![];
+    let mut block_id = None;
+    while (reports.len() as u64) < last_epochs {
+        let info = match client.validators(block_id.clone()).await {
+            Ok(info) => info,
+            Err(err) if !reports.is_empty() => {
+                warn!(target: "neard", "Stopping at epoch {}: {}", reports.len(), err);
+                break;
+            }
+            Err(err) => return Err(rpc_error(err)),
+        };
+        let is_own = |other: &AccountId| Some(other) == account_id;
+        reports.push(EpochReport {
+            epoch_height: info.epoch_height,
+            epoch_start_height: info.epoch_start_height,
+            validator: info.current_validators.iter().find(|v| is_own(&v.account_id)).cloned(),
+            prev_epoch_kickout: info
+                .prev_epoch_kickout
+                .iter()
+                .find(|kickout| is_own(&kickout.account_id))
+                .cloned(),
+        });
+        // The last block of the epoch before is the parent of the first block of this one.
+        let first_block = client
+            .block_by_id(BlockId::Height(info.epoch_start_height))
+            .await
+            .map_err(rpc_error)?;
+        if first_block.header.prev_hash == CryptoHash::default() {
+            break;
+        }
+        block_id = Some(BlockId::Hash(first_block.header.prev_hash));
+    }
+    Ok(reports)
+}
+
+/// Keeps the histogram families of a Prometheus text exposition.
+fn histograms(metrics: &str) -> String {
+    let families: HashSet<&str> = metrics
+        .lines()
+        .filter_map(|line| line.strip_prefix("# TYPE ")?.strip_suffix(" histogram"))
+        .collect();
+    let mut histograms = String::new();
+    for line in metrics.lines() {
+        let name = line
+            .trim_start_matches("# HELP ")
+            .trim_start_matches("# TYPE ")
+            .split(|c: char| c == ' ' || c == '{')
+            .next()
+            .unwrap_or_default();
+        let family = ["_bucket", "_sum", "_count"]
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix))
+            .filter(|family| families.contains(family))
+            .unwrap_or(name);
+        if families.contains(family) {
+            histograms.push_str(line);
+            histograms.push('\n');
+        }
+    }
+    histograms
+}
+
+fn redact(mut config: Value) -> Value {
+    fn redact_in_place(value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (field, value) in fields.iter_mut() {
+                    if REDACTED_FIELDS.contains(&field.as_str()) && !value.is_null() {
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        redact_in_place(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(redact_in_place),
+            _ => {}
+        }
+    }
+    redact_in_place(&mut config);
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histograms() {
+        let metrics = "\
+# HELP near_block_processing_time Time taken to process blocks
+# TYPE near_block_processing_time histogram
+near_block_processing_time_bucket{le=\"0.005\"} 3
+near_block_processing_time_sum 0.01
+near_block_processing_time_count 3
+# HELP near_block_height_head Height of the current head
+# TYPE near_block_height_head gauge
+near_block_height_head 100
+";
+        assert_eq!(
+            histograms(metrics),
+            "\
+# HELP near_block_processing_time Time taken to process blocks
+# TYPE near_block_processing_time histogram
+near_block_processing_time_bucket{le=\"0.005\"} 3
+near_block_processing_time_sum 0.01
+near_block_processing_time_count 3
+"
+        );
+    }
+
+    #[test]
+    fn test_redact() {
+        let config = serde_json::json!({
+            "archive": false,
+            "network": {"addr": "0.0.0.0:24567", "public_addrs": ["ed25519:abc@1.2.3.4:24567"]},
+            "rpc": {"api_keys": {"keys": [{"name": "alice", "key": "secret"}], "admin_key": null}},
+            "webhooks": {"endpoints": [{"url": "https://example.com/?token=abc"}]},
+        });
+        assert_eq!(
+            redact(config),
+            serde_json::json!({
+                "archive": false,
+                "network": {"addr": "0.0.0.0:24567", "public_addrs": REDACTED},
+                "rpc": {"api_keys": {"keys": [{"name": "alice", "key": REDACTED}], "admin_key": null}},
+                "webhooks": {"endpoints": [{"url": REDACTED}]},
+            })
+        );
+    }
+}
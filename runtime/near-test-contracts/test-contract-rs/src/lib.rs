@@ -160,6 +160,18 @@ extern "C" {
     fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64);
     #[cfg(feature = "protocol_feature_alt_bn128")]
     fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    // ##############
+    // # Events API #
+    // ##############
+    #[cfg(feature = "protocol_feature_emit_event")]
+    fn emit_event(
+        standard_len: u64,
+        standard_ptr: u64,
+        version_len: u64,
+        version_ptr: u64,
+        data_len: u64,
+        data_ptr: u64,
+    );
 }
 
 macro_rules! ext_test {
@@ -264,6 +276,22 @@ pub unsafe fn ext_alt_bn128_pairing_check() {
     value_return(1, byte.as_ptr() as _);
 }
 
+#[cfg(feature = "protocol_feature_emit_event")]
+#[no_mangle]
+pub unsafe fn ext_emit_event() {
+    input(0);
+    let standard = b"nep171";
+    let version = b"1.0.0";
+    emit_event(
+        standard.len() as u64,
+        standard.as_ptr() as u64,
+        version.len() as u64,
+        version.as_ptr() as u64,
+        u64::MAX,
+        0,
+    );
+}
+
 #[no_mangle]
 pub unsafe fn ext_validator_stake() {
     input(0);
@@ -6,6 +6,7 @@ use crate::utils::split_method_names;
 use crate::ValuePtr;
 use byteorder::ByteOrder;
 use near_crypto::Secp256K1Signature;
+use near_primitives::transaction::ContractEvent;
 use near_primitives::version::is_implicit_account_creation_enabled;
 use near_primitives_core::config::ExtCosts::*;
 use near_primitives_core::config::{ActionCosts, ExtCosts, VMConfig, ViewConfig};
@@ -52,6 +53,8 @@ pub struct VMLogic<'a> {
     return_data: ReturnData,
     /// Logs written by the runtime.
     logs: Vec<String>,
+    /// Events emitted by the contract, limited together with the logs.
+    events: Vec<ContractEvent>,
     /// Registers can be used by the guest to store blobs of data without moving them across
     /// host-guest boundary.
     registers: HashMap<u64, Vec<u8>>,
@@ -137,6 +140,7 @@ impl<'a> VMLogic<'a> {
             gas_counter,
             return_data: ReturnData::None,
             logs: vec![],
+            events: vec![],
             registers: HashMap::new(),
             promises: vec![],
             receipt_to_account: HashMap::new(),
@@ -434,7 +438,8 @@ impl<'a> VMLogic<'a> {
 
     /// Checks that the current log number didn't reach the limit yet, so we can add a new message.
     fn check_can_add_a_log_message(&self) -> Result<()> {
-        if self.logs.len() as u64 >= self.config.limit_config.max_number_logs {
+        if (self.logs.len() + self.events.len()) as u64 >= self.config.limit_config.max_number_logs
+        {
             Err(HostError::NumberOfLogsExceeded { limit: self.config.limit_config.max_number_logs }
                 .into())
         } else {
@@ -461,8 +466,15 @@ impl<'a> VMLogic<'a> {
     }
 
     fn checked_push_log(&mut self, message: String) -> Result<()> {
+        self.add_log_length(message.len() as u64)?;
+        self.logs.push(message);
+        Ok(())
+    }
+
+    /// Events count towards the total log length, like the logs.
+    fn add_log_length(&mut self, len: u64) -> Result<()> {
         // The size of logged data can't be too large. No overflow.
-        self.total_log_length += message.len() as u64;
+        self.total_log_length += len;
         if self.total_log_length > self.config.limit_config.max_total_log_length {
             return Err(HostError::TotalLogLengthExceeded {
                 length: self.total_log_length,
@@ -470,7 +482,6 @@ impl<'a> VMLogic<'a> {
             }
             .into());
         }
-        self.logs.push(message);
         Ok(())
     }
 
@@ -2076,6 +2087,49 @@ impl<'a> VMLogic<'a> {
         self.checked_push_log(message)
     }
 
+    /// Emits an event of the `standard` at `version`, with the borsh serialized `data` defined by
+    /// the standard. The event is recorded apart from the logs and has costs of its own. Its data
+    /// isn't decoded, so only the standard and the version are charged for UTF-8 decoding. If
+    /// `data_len == u64::MAX` then the data is read from the register `data_ptr`.
+    ///
+    /// # Errors
+    ///
+    /// * If the standard, version or data extend outside the memory of the guest with
+    ///   `MemoryAccessViolation`;
+    /// * If the standard or version is not UTF-8 returns `BadUtf8`;
+    /// * If the data is read from a register that is not used returns `InvalidRegisterId`;
+    /// * If number of bytes of the event + `total_log_length` exceeds the `max_total_log_length`
+    ///   returns `TotalLogLengthExceeded`.
+    /// * If the total number of logs and events will exceed the `max_number_logs` returns
+    ///   `NumberOfLogsExceeded`.
+    ///
+    /// # Cost
+    ///
+    /// `base + emit_event_base + emit_event_byte * num_bytes + utf8 decoding cost of the standard
+    ///  and version + cost of reading the data`
+    #[cfg(feature = "protocol_feature_emit_event")]
+    pub fn emit_event(
+        &mut self,
+        standard_len: u64,
+        standard_ptr: u64,
+        version_len: u64,
+        version_ptr: u64,
+        data_len: u64,
+        data_ptr: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        self.check_can_add_a_log_message()?;
+        let standard = self.get_utf8_string(standard_len, standard_ptr)?;
+        let version = self.get_utf8_string(version_len, version_ptr)?;
+        let data = self.get_vec_from_memory_or_register(data_ptr, data_len)?;
+        let num_bytes = (standard.len() + version.len() + data.len()) as u64;
+        self.gas_counter.pay_base(emit_event_base)?;
+        self.gas_counter.pay_per(emit_event_byte, num_bytes)?;
+        self.add_log_length(num_bytes)?;
+        self.events.push(ContractEvent { standard, version, data });
+        Ok(())
+    }
+
     /// Special import kept for compatibility with AssemblyScript contracts. Not called by smart
     /// contracts directly, but instead called by the code generated by AssemblyScript.
     ///
@@ -2490,6 +2544,7 @@ impl<'a> VMLogic<'a> {
             burnt_gas: self.gas_counter.burnt_gas(),
             used_gas: self.gas_counter.used_gas(),
             logs: self.logs,
+            events: self.events,
             profile: self.gas_counter.profile_data(),
         }
     }
@@ -2497,6 +2552,7 @@ impl<'a> VMLogic<'a> {
     /// clones the outcome of execution.
     pub fn clone_outcome(&self) -> VMOutcome {
         let logs = self.logs.clone();
+        let events = self.events.clone();
         let return_data = self.return_data.clone();
         VMOutcome {
             balance: self.current_account_balance,
@@ -2505,6 +2561,7 @@ impl<'a> VMLogic<'a> {
             burnt_gas: self.gas_counter.burnt_gas(),
             used_gas: self.gas_counter.used_gas(),
             logs,
+            events,
             profile: self.gas_counter.profile_data(),
         }
     }
@@ -2536,6 +2593,7 @@ pub struct VMOutcome {
     pub burnt_gas: Gas,
     pub used_gas: Gas,
     pub logs: Vec<String>,
+    pub events: Vec<ContractEvent>,
     /// Data collected from making a contract call
     pub profile: ProfileData,
 }
@@ -153,6 +153,65 @@ fn test_log_number_limit() {
     assert_eq!(outcome.logs.len() as u64, max_number_logs);
 }
 
+#[cfg(feature = "protocol_feature_emit_event")]
+#[test]
+fn test_emit_event() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let (standard, version, data) = (b"nep171", b"1.0.0", vec![0u8, 1, 2, 255]);
+    logic
+        .emit_event(
+            standard.len() as _,
+            standard.as_ptr() as _,
+            version.len() as _,
+            version.as_ptr() as _,
+            data.len() as _,
+            data.as_ptr() as _,
+        )
+        .expect("Valid event");
+    let len = (standard.len() + version.len()) as u64;
+    // Unlike for logs, the data isn't decoded.
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::emit_event_base: 1,
+        ExtCosts::emit_event_byte: len + data.len() as u64,
+        ExtCosts::read_memory_base: 3,
+        ExtCosts::read_memory_byte: len + data.len() as u64,
+        ExtCosts::utf8_decoding_base: 2,
+        ExtCosts::utf8_decoding_byte: len,
+    });
+
+    let outcome = logic.outcome();
+    assert!(outcome.logs.is_empty());
+    assert_eq!(
+        outcome.events,
+        vec![near_primitives::transaction::ContractEvent {
+            standard: "nep171".to_string(),
+            version: "1.0.0".to_string(),
+            data,
+        }]
+    );
+}
+
+#[cfg(feature = "protocol_feature_emit_event")]
+#[test]
+fn test_emit_event_log_number_limit() {
+    let mut logic_builder = VMLogicBuilder::default();
+    logic_builder.config.limit_config.max_number_logs = 2;
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let string_bytes = b"nep171";
+    let emit_event = |logic: &mut crate::VMLogic| {
+        let len = string_bytes.len() as u64;
+        let ptr = string_bytes.as_ptr() as u64;
+        logic.emit_event(len, ptr, len, ptr, len, ptr)
+    };
+    logic.log_utf8(string_bytes.len() as _, string_bytes.as_ptr() as _).expect("Under the limit");
+    emit_event(&mut logic).expect("Under the limit");
+    assert_eq!(emit_event(&mut logic), Err(HostError::NumberOfLogsExceeded { limit: 2 }.into()));
+    let outcome = logic.outcome();
+    assert_eq!((outcome.logs.len(), outcome.events.len()), (1, 1));
+}
+
 #[test]
 fn test_log_utf16_number_limit() {
     let mut logic_builder = VMLogicBuilder::default();
@@ -269,6 +269,14 @@ wrapped_imports! {
     panic_utf8<[len: u64, ptr: u64] -> []>,
     log_utf8<[len: u64, ptr: u64] -> []>,
     log_utf16<[len: u64, ptr: u64] -> []>,
+    #["protocol_feature_emit_event", EmitEvent] emit_event<[
+        standard_len: u64,
+        standard_ptr: u64,
+        version_len: u64,
+        version_ptr: u64,
+        data_len: u64,
+        data_ptr: u64
+    ] -> []>,
     abort<[msg_ptr: u32, filename_ptr: u32, line: u32, col: u32] -> []>,
     // ################
     // # Promises API #
@@ -293,6 +293,41 @@ def_test_ext!(
     &base64::decode("AgAAAOzTRBYFMdAMNTUnUW2wNUYLmsNMgKQUC12+o1wVU7QSxF/il/WRT3I1bJqPaWKBbGqehkYu0QS7ct2nz52CRCn3EXSIf0p4ORYJ7mRmZLWtUyGrqlKl/4DNx2kHDEUrET+SS7pJZ4ql4b8tnwGv8W020cyHrmLCU15/Hp+LLCsD2H5fx6TkvPtG6iZSiHT1Ih1TDyGsHTrOzFWN3hx0FwAaB2tgYeH+WuEKReDHNFmxyi8v597Ji5NP4PU8bZXkGQ==").unwrap()
 );
 
+#[cfg(feature = "protocol_feature_emit_event")]
+#[test]
+pub fn test_emit_event() {
+    with_vm_variants(|vm_kind: VMKind| {
+        let code = ContractCode::new(near_test_contracts::nightly_rs_contract().to_vec(), None);
+        let mut fake_external = MockedExternal::new();
+        let context = create_context(vec![1, 2, 3]);
+        let config = VMConfig::test();
+        let fees = RuntimeFeesConfig::test();
+        let runtime = vm_kind.runtime().expect("runtime has not been compiled");
+        let (outcome, err) = runtime.run(
+            &code,
+            "ext_emit_event",
+            &mut fake_external,
+            context,
+            &config,
+            &fees,
+            &[],
+            LATEST_PROTOCOL_VERSION,
+            None,
+        );
+        assert!(err.is_none(), "Failed execution: {:?}", err);
+        let outcome = outcome.unwrap();
+        assert!(outcome.logs.is_empty());
+        assert_eq!(
+            outcome.events,
+            vec![near_primitives::transaction::ContractEvent {
+                standard: "nep171".to_string(),
+                version: "1.0.0".to_string(),
+                data: vec![1, 2, 3],
+            }]
+        );
+    });
+}
+
 #[test]
 pub fn test_out_of_memory() {
     with_vm_variants(|vm_kind: VMKind| {
@@ -158,6 +158,11 @@ fn ext_costs_config(cost_table: &CostTable) -> anyhow::Result<ExtCostsConfig> {
         alt_bn128_pairing_check_base: get(Cost::AltBn128PairingCheckBase)?,
         #[cfg(feature = "protocol_feature_alt_bn128")]
         alt_bn128_pairing_check_byte: get(Cost::AltBn128PairingCheckByte)?,
+        // TODO: estimate the events on their own, they are priced like the logs for now.
+        #[cfg(feature = "protocol_feature_emit_event")]
+        emit_event_base: get(Cost::LogBase)?,
+        #[cfg(feature = "protocol_feature_emit_event")]
+        emit_event_byte: get(Cost::LogByte)?,
     };
 
     Ok(res)
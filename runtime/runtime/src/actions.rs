@@ -239,6 +239,7 @@ pub(crate) fn action_function_call(
             account.set_amount(outcome.balance);
             account.set_storage_usage(outcome.storage_usage);
             result.result = Ok(outcome.return_data);
+            result.events.extend(outcome.events);
             result.new_receipts.extend(runtime_ext.into_receipts(account_id));
         }
     } else {
@@ -65,4 +65,14 @@ pub trait ViewRuntimeAdapter {
         account_id: &AccountId,
         prefix: &[u8],
     ) -> Result<ViewStateResult, crate::state_viewer::errors::ViewStateError>;
+
+    fn view_state_page(
+        &self,
+        shard_uid: &ShardUId,
+        state_root: MerkleHash,
+        account_id: &AccountId,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: u64,
+    ) -> Result<ViewStateResult, crate::state_viewer::errors::ViewStateError>;
 }
@@ -7,13 +7,16 @@ use num_traits::pow::Pow;
 use near_primitives::account::AccessKeyPermission;
 use near_primitives::errors::IntegerOverflowError;
 // Just re-exporting RuntimeConfig for backwards compatibility.
+use near_primitives::borsh::BorshSerialize;
 pub use near_primitives::num_rational::Rational;
 pub use near_primitives::runtime::config::RuntimeConfig;
-use near_primitives::runtime::fees::{transfer_exec_fee, transfer_send_fee, RuntimeFeesConfig};
+use near_primitives::runtime::fees::{
+    transfer_exec_fee, transfer_send_fee, RuntimeFeesConfig, StorageUsageConfig,
+};
 use near_primitives::transaction::{
     Action, AddKeyAction, DeployContractAction, FunctionCallAction, Transaction,
 };
-use near_primitives::types::{AccountId, Balance, Gas};
+use near_primitives::types::{AccountId, Balance, Gas, StorageUsage};
 use near_primitives::version::{is_implicit_account_creation_enabled, ProtocolVersion};
 
 /// Describes the cost of converting this transaction into a receipt.
@@ -262,6 +265,29 @@ pub fn total_prepaid_gas(actions: &[Action]) -> Result<Gas, IntegerOverflowError
     actions.iter().try_fold(0, |acc, action| safe_add_gas(acc, action.get_prepaid_gas()))
 }
 
+/// Storage the actions add to the receiver, with its account record if they create it, as counted
+/// by the runtime. The storage freed by the actions, e.g. of the replaced code, isn't subtracted.
+pub fn total_storage_usage_increase(
+    config: &StorageUsageConfig,
+    actions: &[Action],
+) -> Result<StorageUsage, IntegerOverflowError> {
+    let mut result: StorageUsage = 0;
+    for action in actions {
+        let delta = match action {
+            Action::CreateAccount(_) => config.num_bytes_account,
+            Action::DeployContract(DeployContractAction { code }) => code.len() as u64,
+            Action::AddKey(AddKeyAction { public_key, access_key }) => {
+                public_key.try_to_vec().map_or(0, |bytes| bytes.len() as u64)
+                    + access_key.try_to_vec().map_or(0, |bytes| bytes.len() as u64)
+                    + config.num_extra_bytes_record
+            }
+            _ => 0,
+        };
+        result = result.checked_add(delta).ok_or_else(|| IntegerOverflowError {})?;
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,4 +300,30 @@ mod tests {
         assert_eq!(safe_gas_price_inflated(10000, Rational::new(101, 100), 3).unwrap(), 10304);
         assert_eq!(safe_gas_price_inflated(10000, Rational::new(101, 100), 32).unwrap(), 13750);
     }
+
+    #[test]
+    fn test_total_storage_usage_increase() {
+        use near_crypto::{KeyType, PublicKey};
+        use near_primitives::account::AccessKey;
+        use near_primitives::transaction::{CreateAccountAction, TransferAction};
+
+        let config = StorageUsageConfig { num_bytes_account: 100, num_extra_bytes_record: 40 };
+        let public_key = PublicKey::empty(KeyType::ED25519);
+        let actions = vec![
+            Action::CreateAccount(CreateAccountAction {}),
+            Action::Transfer(TransferAction { deposit: 10 }),
+            Action::AddKey(AddKeyAction {
+                public_key: public_key.clone(),
+                access_key: AccessKey::full_access(),
+            }),
+            Action::DeployContract(DeployContractAction { code: vec![0; 1000] }),
+        ];
+        let key_bytes = (public_key.try_to_vec().unwrap().len()
+            + AccessKey::full_access().try_to_vec().unwrap().len()) as u64;
+        assert_eq!(
+            total_storage_usage_increase(&config, &actions).unwrap(),
+            100 + key_bytes + 40 + 1000
+        );
+        assert_eq!(total_storage_usage_increase(&config, &actions[1..2]).unwrap(), 0);
+    }
 }
@@ -30,7 +30,7 @@ use near_primitives::{
     },
     state_record::StateRecord,
     transaction::{
-        Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, LogEntry,
+        Action, ContractEvent, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, LogEntry,
         SignedTransaction,
     },
     trie_key::TrieKey,
@@ -71,6 +71,8 @@ pub mod config;
 pub mod ext;
 mod genesis;
 mod metrics;
+pub mod simulation;
+pub mod standalone;
 pub mod state_viewer;
 mod verifier;
 
@@ -132,6 +134,8 @@ pub struct ActionResult {
     pub gas_used: Gas,
     pub result: Result<ReturnData, ActionError>,
     pub logs: Vec<LogEntry>,
+    /// Events of the function calls, dropped with the receipts if an action fails.
+    pub events: Vec<ContractEvent>,
     pub new_receipts: Vec<Receipt>,
     pub validator_proposals: Vec<ValidatorStake>,
     pub profile: ProfileData,
@@ -160,9 +164,11 @@ impl ActionResult {
             *receipt_index += self.new_receipts.len() as u64;
         }
         if self.result.is_ok() {
+            self.events.append(&mut next_result.events);
             self.new_receipts.append(&mut next_result.new_receipts);
             self.validator_proposals.append(&mut next_result.validator_proposals);
         } else {
+            self.events.clear();
             self.new_receipts.clear();
             self.validator_proposals.clear();
         }
@@ -178,6 +184,7 @@ impl Default for ActionResult {
             gas_used: 0,
             result: Ok(ReturnData::None),
             logs: vec![],
+            events: vec![],
             new_receipts: vec![],
             validator_proposals: vec![],
             profile: Default::default(),
@@ -215,11 +222,13 @@ impl Runtime {
     /// `ExecutionOutcomeWithId` for the transaction.
     /// In case of an error, returns either `InvalidTxError` if the transaction verification failed
     /// or a `StorageError` wrapped into `RuntimeError`.
+    /// The signature is only left unchecked for the dry runs of transactions, see `simulation`.
     fn process_transaction(
         &self,
         state_update: &mut TrieUpdate,
         apply_state: &ApplyState,
         signed_transaction: &SignedTransaction,
+        verify_signature: bool,
         stats: &mut ApplyStats,
     ) -> Result<(Receipt, ExecutionOutcomeWithId), RuntimeError> {
         let _span =
@@ -231,7 +240,7 @@ impl Runtime {
             state_update,
             apply_state.gas_price,
             signed_transaction,
-            true,
+            verify_signature,
             Some(apply_state.block_index),
             apply_state.current_protocol_version,
         ) {
@@ -759,7 +768,7 @@ impl Runtime {
                 gas_burnt: result.gas_burnt,
                 tokens_burnt,
                 executor_id: account_id.clone(),
-                metadata: ExecutionMetadata::V2(result.profile),
+                metadata: ExecutionMetadata::new(result.profile, result.events),
             },
         })
     }
@@ -1254,6 +1263,7 @@ impl Runtime {
                 &mut state_update,
                 apply_state,
                 signed_transaction,
+                true,
                 &mut stats,
             )?;
             if receipt.receiver_id == signed_transaction.transaction.signer_id {
@@ -0,0 +1,101 @@
+//! Dry runs of transactions, to predict their outcomes and the gas they burn before they're
+//! signed and sent.
+//!
+//! The transaction and then the receipts it produces are applied on top of the states of the
+//! shards given, as if by the next blocks, but the changes are never finalized into the tries nor
+//! stored. Unlike applying a chunk, there's no gas limit, the delayed receipts of the shards are
+//! left in their queues, and the receipts to the shards without a state, e.g. not tracked by the
+//! node, aren't executed.
+
+use std::collections::{HashMap, VecDeque};
+
+use near_primitives::errors::{RuntimeError, StorageError};
+use near_primitives::receipt::Receipt;
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
+use near_primitives::types::{AccountId, EpochInfoProvider, RawStateChangesWithTrieKey, ShardId};
+use near_store::TrieUpdate;
+
+use crate::{ApplyState, ApplyStats, Runtime};
+
+/// Outcome of the dry run of a transaction.
+#[derive(Debug)]
+pub struct SimulationResult {
+    /// Outcomes of the transaction and then of the receipts, in execution order.
+    pub outcomes: Vec<ExecutionOutcomeWithId>,
+    /// Receipts produced by the transaction and the receipts, in execution order.
+    pub receipts: Vec<Receipt>,
+    /// Receipts left unexecuted, to shards without a state or past the limit of receipts.
+    pub unexecuted_receipts: Vec<Receipt>,
+    /// Changes of the states of all the shards.
+    pub state_changes: Vec<RawStateChangesWithTrieKey>,
+}
+
+impl Runtime {
+    /// Applies the transaction, then the receipts it produces until none are left or
+    /// `max_receipts` were executed, each on the state of the shard of its receiver. The state of
+    /// the shard of the signer must be given.
+    pub fn simulate_transaction(
+        &self,
+        mut state_updates: HashMap<ShardId, TrieUpdate>,
+        shard_id_of: &dyn Fn(&AccountId) -> ShardId,
+        apply_state: &ApplyState,
+        signed_transaction: &SignedTransaction,
+        verify_signature: bool,
+        epoch_info_provider: &dyn EpochInfoProvider,
+        max_receipts: usize,
+    ) -> Result<SimulationResult, RuntimeError> {
+        let mut stats = ApplyStats::default();
+        let signer_shard_id = shard_id_of(&signed_transaction.transaction.signer_id);
+        let state_update = state_updates.get_mut(&signer_shard_id).ok_or_else(|| {
+            StorageError::StorageInconsistentState(format!(
+                "No state of the shard {} of the signer",
+                signer_shard_id
+            ))
+        })?;
+        let (receipt, outcome) = self.process_transaction(
+            state_update,
+            apply_state,
+            signed_transaction,
+            verify_signature,
+            &mut stats,
+        )?;
+
+        let mut outcomes = vec![outcome];
+        let mut receipts = vec![receipt.clone()];
+        let mut unexecuted_receipts = vec![];
+        let mut pending_receipts = VecDeque::from(vec![receipt]);
+        let mut num_executed = 0;
+        while let Some(receipt) = pending_receipts.pop_front() {
+            let state_update = match state_updates.get_mut(&shard_id_of(&receipt.receiver_id)) {
+                Some(state_update) if num_executed < max_receipts => state_update,
+                _ => {
+                    unexecuted_receipts.push(receipt);
+                    continue;
+                }
+            };
+            num_executed += 1;
+            let mut outgoing_receipts = vec![];
+            if let Some(outcome) = self.process_receipt(
+                state_update,
+                apply_state,
+                &receipt,
+                &mut outgoing_receipts,
+                &mut vec![],
+                &mut stats,
+                epoch_info_provider,
+            )? {
+                outcomes.push(outcome);
+            }
+            receipts.extend(outgoing_receipts.iter().cloned());
+            pending_receipts.extend(outgoing_receipts);
+        }
+
+        let mut state_updates: Vec<_> = state_updates.into_iter().collect();
+        state_updates.sort_by_key(|(shard_id, _)| *shard_id);
+        let mut state_changes = vec![];
+        for (_, state_update) in state_updates {
+            state_changes.extend(state_update.finalize()?.1);
+        }
+        Ok(SimulationResult { outcomes, receipts, unexecuted_receipts, state_changes })
+    }
+}
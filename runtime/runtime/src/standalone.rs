@@ -0,0 +1,304 @@
+//! Library entry point for applying transactions with the full runtime semantics against an
+//! in-memory state, without constructing a `Chain` or a `Client`.
+//!
+//! Meant for embedders such as simulators, custom test frameworks and research tools. The state
+//! is kept in a single shard and stored in memory, every call to [`StandaloneRuntime::apply_block`]
+//! applies one chunk worth of transactions and receipts on top of the current state root.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use near_chain_configs::Genesis;
+use near_primitives::account::Account;
+use near_primitives::errors::{RuntimeError, StorageError};
+use near_primitives::receipt::Receipt;
+use near_primitives::runtime::config::RuntimeConfig;
+use near_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
+use near_primitives::shard_layout::ShardUId;
+use near_primitives::state_record::{state_record_to_account_id, StateRecord};
+use near_primitives::test_utils::MockEpochInfoProvider;
+use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
+use near_primitives::types::{AccountId, StateRoot};
+use near_primitives::views::ViewApplyState;
+use near_store::test_utils::create_tries;
+use near_store::{ShardTries, TrieUpdate};
+
+use crate::simulation::SimulationResult;
+use crate::state_viewer::{errors, TrieViewer};
+use crate::{ApplyResult, ApplyState, Runtime};
+
+/// Runtime applying blocks on top of an in-memory state built from a genesis.
+pub struct StandaloneRuntime {
+    runtime: Runtime,
+    tries: ShardTries,
+    root: StateRoot,
+    apply_state: ApplyState,
+    epoch_info_provider: MockEpochInfoProvider,
+    trie_viewer: TrieViewer,
+}
+
+impl StandaloneRuntime {
+    /// Creates the genesis state from the records of `genesis`. The genesis validators are the
+    /// only stakers known to the runtime.
+    pub fn new(genesis: &Genesis, runtime_config: RuntimeConfig) -> Self {
+        let runtime = Runtime::new();
+        let tries = create_tries();
+        let mut account_ids = HashSet::new();
+        genesis.for_each_record(|record: &StateRecord| {
+            account_ids.insert(state_record_to_account_id(record).clone());
+        });
+        let validators: Vec<_> = genesis
+            .config
+            .validators
+            .iter()
+            .map(|info| (info.account_id.clone(), info.public_key.clone(), info.amount))
+            .collect();
+        let root = runtime.apply_genesis_state(
+            tries.clone(),
+            0,
+            &validators,
+            genesis,
+            &runtime_config,
+            account_ids,
+        );
+        let apply_state = ApplyState {
+            block_index: genesis.config.genesis_height + 1,
+            prev_block_hash: Default::default(),
+            block_hash: Default::default(),
+            epoch_id: Default::default(),
+            epoch_height: 0,
+            gas_price: genesis.config.min_gas_price,
+            block_timestamp: genesis.config.genesis_time.timestamp_nanos() as u64,
+            gas_limit: None,
+            random_seed: Default::default(),
+            current_protocol_version: genesis.config.protocol_version,
+            config: Arc::new(runtime_config),
+            cache: None,
+            is_new_chunk: true,
+            migration_data: Arc::new(MigrationData::default()),
+            migration_flags: MigrationFlags::default(),
+        };
+        let epoch_info_provider = MockEpochInfoProvider::new(
+            genesis.config.validators.iter().map(|info| (info.account_id.clone(), info.amount)),
+        );
+        Self {
+            runtime,
+            tries,
+            root,
+            apply_state,
+            epoch_info_provider,
+            trie_viewer: TrieViewer::default(),
+        }
+    }
+
+    /// Current state root.
+    pub fn state_root(&self) -> StateRoot {
+        self.root
+    }
+
+    /// Parameters used to apply the next block. Embedders may adjust them, e.g. to change the
+    /// gas price, the timestamp or the protocol version.
+    pub fn apply_state_mut(&mut self) -> &mut ApplyState {
+        &mut self.apply_state
+    }
+
+    /// Applies a single block with the given incoming receipts and transactions, commits the
+    /// resulting state and moves to the next height.
+    pub fn apply_block(
+        &mut self,
+        receipts: &[Receipt],
+        transactions: &[SignedTransaction],
+    ) -> Result<ApplyResult, RuntimeError> {
+        let apply_result = self.runtime.apply(
+            self.tries.get_trie_for_shard(ShardUId::default()),
+            self.root,
+            &None,
+            &self.apply_state,
+            receipts,
+            transactions,
+            &self.epoch_info_provider,
+            None,
+        )?;
+        let (store_update, root) =
+            self.tries.apply_all(&apply_result.trie_changes, ShardUId::default())?;
+        store_update.commit().map_err(|err| {
+            RuntimeError::StorageError(StorageError::StorageInconsistentState(err.to_string()))
+        })?;
+        self.root = root;
+        self.apply_state.block_index += 1;
+        Ok(apply_result)
+    }
+
+    /// Applies the transactions, then keeps applying blocks with the produced receipts until
+    /// none are left or `max_blocks` blocks were applied. Returns the outcomes of the
+    /// transactions and of all the receipts, in execution order.
+    pub fn apply_until_idle(
+        &mut self,
+        transactions: &[SignedTransaction],
+        max_blocks: usize,
+    ) -> Result<Vec<ExecutionOutcomeWithId>, RuntimeError> {
+        let mut outcomes = vec![];
+        let mut receipts = vec![];
+        let mut transactions = transactions;
+        for _ in 0..max_blocks {
+            let apply_result = self.apply_block(&receipts, transactions)?;
+            outcomes.extend(apply_result.outcomes);
+            receipts = apply_result.outgoing_receipts;
+            transactions = &[];
+            if receipts.is_empty() {
+                break;
+            }
+        }
+        Ok(outcomes)
+    }
+
+    /// Dry run of the transaction and of at most `max_receipts` of the receipts it produces on
+    /// the current state, which is left unchanged.
+    pub fn simulate_transaction(
+        &self,
+        transaction: &SignedTransaction,
+        max_receipts: usize,
+    ) -> Result<SimulationResult, RuntimeError> {
+        let state_updates = std::iter::once((0, self.state_update())).collect();
+        self.runtime.simulate_transaction(
+            state_updates,
+            &|_| 0,
+            &self.apply_state,
+            transaction,
+            true,
+            &self.epoch_info_provider,
+            max_receipts,
+        )
+    }
+
+    /// Read-only view of the current state.
+    pub fn state_update(&self) -> TrieUpdate {
+        self.tries.new_trie_update(ShardUId::default(), self.root)
+    }
+
+    pub fn view_account(
+        &self,
+        account_id: &AccountId,
+    ) -> Result<Account, errors::ViewAccountError> {
+        self.trie_viewer.view_account(&self.state_update(), account_id)
+    }
+
+    /// Calls a view method of a contract on the current state. Returns the result and the logs.
+    pub fn call_function(
+        &self,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: &[u8],
+    ) -> Result<(Vec<u8>, Vec<String>), errors::CallFunctionError> {
+        let view_state = ViewApplyState {
+            block_height: self.apply_state.block_index,
+            prev_block_hash: self.apply_state.prev_block_hash,
+            block_hash: self.apply_state.block_hash,
+            epoch_id: self.apply_state.epoch_id.clone(),
+            epoch_height: self.apply_state.epoch_height,
+            block_timestamp: self.apply_state.block_timestamp,
+            current_protocol_version: self.apply_state.current_protocol_version,
+            cache: self.apply_state.cache.clone(),
+        };
+        let mut logs = vec![];
+        let result = self.trie_viewer.call_function(
+            self.state_update(),
+            view_state,
+            contract_id,
+            method_name,
+            args,
+            &mut logs,
+            &self.epoch_info_provider,
+        )?;
+        Ok((result, logs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_chain_configs::{get_initial_supply, GenesisConfig, GenesisRecords};
+    use near_crypto::{InMemorySigner, KeyType, Signer};
+    use near_primitives::account::AccessKey;
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::transaction::ExecutionStatus;
+    use near_primitives::types::AccountInfo;
+    use near_primitives::version::PROTOCOL_VERSION;
+    use testlib::runtime_utils::{alice_account, bob_account};
+
+    use super::*;
+
+    #[test]
+    fn test_transfer() {
+        let amount = 10u128.pow(30);
+        let stake = 10u128.pow(28);
+        let signer = InMemorySigner::from_seed(alice_account(), KeyType::ED25519, "alice");
+        let records = vec![
+            StateRecord::Account {
+                account_id: alice_account(),
+                account: Account::new(amount, stake, CryptoHash::default(), 0),
+            },
+            StateRecord::Account {
+                account_id: bob_account(),
+                account: Account::new(amount, 0, CryptoHash::default(), 0),
+            },
+            StateRecord::AccessKey {
+                account_id: alice_account(),
+                public_key: signer.public_key(),
+                access_key: AccessKey::full_access(),
+            },
+        ];
+        let validators = vec![AccountInfo {
+            account_id: alice_account(),
+            public_key: signer.public_key(),
+            amount: stake,
+        }];
+        let genesis = Genesis::new(
+            GenesisConfig {
+                protocol_version: PROTOCOL_VERSION,
+                validators,
+                total_supply: get_initial_supply(&records),
+                ..Default::default()
+            },
+            GenesisRecords(records),
+        );
+        let mut runtime = StandaloneRuntime::new(&genesis, RuntimeConfig::test());
+
+        let tx = SignedTransaction::send_money(
+            1,
+            alice_account(),
+            bob_account(),
+            &signer,
+            100,
+            CryptoHash::default(),
+        );
+        let simulation = runtime.simulate_transaction(&tx, 10).unwrap();
+        assert!(simulation.unexecuted_receipts.is_empty());
+        let outcomes = runtime.apply_until_idle(&[tx], 10).unwrap();
+        assert_eq!(simulation.outcomes, outcomes);
+        assert!(outcomes.len() >= 2);
+        for outcome in outcomes.iter() {
+            assert!(
+                !matches!(outcome.outcome.status, ExecutionStatus::Failure(_)),
+                "{:?}",
+                outcome
+            );
+        }
+        assert_eq!(runtime.view_account(&bob_account()).unwrap().amount(), amount + 100);
+        assert!(runtime.view_account(&"carol".parse().unwrap()).is_err());
+
+        // The state is left unchanged, and receipts past the limit aren't executed.
+        let tx = SignedTransaction::send_money(
+            2,
+            alice_account(),
+            bob_account(),
+            &signer,
+            100,
+            CryptoHash::default(),
+        );
+        let simulation = runtime.simulate_transaction(&tx, 0).unwrap();
+        assert_eq!(simulation.outcomes.len(), 1);
+        assert_eq!(simulation.unexecuted_receipts.len(), 1);
+        assert!(!simulation.state_changes.is_empty());
+        assert_eq!(runtime.view_account(&bob_account()).unwrap().amount(), amount + 100);
+    }
+}
@@ -6,6 +6,7 @@ use near_primitives::runtime::config_store::RuntimeConfigStore;
 use near_primitives::{
     account::{AccessKey, Account},
     borsh::BorshDeserialize,
+    cancellation::is_thread_cancelled,
     contract::ContractCode,
     hash::CryptoHash,
     receipt::ActionReceipt,
@@ -19,10 +20,13 @@ use near_primitives::{
     types::{AccountId, EpochInfoProvider, Gas},
     views::{StateItem, ViewApplyState, ViewStateResult},
 };
-use near_store::{get_access_key, get_account, get_code, TrieUpdate};
+use near_store::{get_access_key, get_account, get_code, read_state_page, TrieUpdate};
 use near_vm_logic::{ReturnData, ViewConfig};
 use std::{str, sync::Arc, time::Instant};
 
+/// Maximum number of items of a page of the contract state, larger limits are lowered to it.
+pub const MAX_VIEW_STATE_PAGE_LIMIT: u64 = 1000;
+
 pub mod errors;
 
 pub struct TrieViewer {
@@ -146,6 +150,11 @@ impl TrieViewer {
         let mut iter = state_update.trie.iter(&state_update.get_root())?;
         iter.seek(&query)?;
         for item in iter {
+            if is_thread_cancelled() {
+                return Err(errors::ViewStateError::InternalError {
+                    error_message: "Query cancelled".to_string(),
+                });
+            }
             let (key, value) = item?;
             if !key.starts_with(&query.as_ref()) {
                 break;
@@ -157,7 +166,51 @@ impl TrieViewer {
             });
         }
         // TODO(2076): Add proofs for the storage items.
-        Ok(ViewStateResult { values, proof: vec![] })
+        Ok(ViewStateResult { values, proof: vec![], continuation_token: None })
+    }
+
+    /// Reads a page of the contract state. If the trie of `state_update` records its reads, see
+    /// `Trie::recording_reads`, the nodes read for the page are returned as its proof.
+    pub fn view_state_page(
+        &self,
+        state_update: &TrieUpdate,
+        account_id: &AccountId,
+        prefix: &[u8],
+        start_after: Option<&[u8]>,
+        limit: u64,
+    ) -> Result<ViewStateResult, errors::ViewStateError> {
+        if get_account(state_update, account_id)?.is_none() {
+            return Err(errors::ViewStateError::AccountDoesNotExist {
+                requested_account_id: account_id.clone(),
+            });
+        }
+        // Only the nodes read for the page make its proof.
+        state_update.trie.recorded_storage();
+        let limit = std::cmp::min(limit, MAX_VIEW_STATE_PAGE_LIMIT) as usize;
+        let items = read_state_page(
+            &state_update.trie,
+            &state_update.get_root(),
+            account_id,
+            prefix,
+            start_after,
+            limit,
+        )?;
+        let continuation_token =
+            if items.len() == limit { items.last().map(|(key, _)| key.clone()) } else { None };
+        let values = items
+            .iter()
+            .map(|(key, value)| StateItem {
+                key: to_base64(key),
+                value: to_base64(value),
+                proof: vec![],
+            })
+            .collect();
+        let proof = state_update
+            .trie
+            .recorded_storage()
+            .map(|storage| storage.nodes.0.iter().map(to_base64).collect())
+            .unwrap_or_default();
+        Ok(ViewStateResult { values, proof, continuation_token })
     }
 
     pub fn call_function(
@@ -5,7 +5,7 @@ use clap::{AppSettings, Clap};
 use once_cell::sync::Lazy;
 
 use near_logger_utils::init_integration_logger;
-use near_primitives::types::{BlockHeight, ShardId};
+use near_primitives::types::{AccountId, BlockHeight, ShardId};
 use near_primitives::version::{DB_VERSION, PROTOCOL_VERSION};
 use near_store::{create_store, Store};
 use nearcore::{get_default_home, get_store_path, load_config, NearConfig};
@@ -80,6 +80,16 @@ pub enum StateViewerSubCommand {
     /// Dump contract data in storage of given account to binary file.
     #[clap(name = "dump_account_storage")]
     DumpAccountStorage(DumpAccountStorageCmd),
+    /// Report the load of the shards of a hypothetical shard layout, to inform resharding.
+    #[clap(name = "shard_load")]
+    ShardLoad(ShardLoadCmd),
+    /// Check that the accounts of a `batch-accounts` manifest were created.
+    #[clap(name = "check_accounts")]
+    CheckAccounts(CheckAccountsCmd),
+    /// Dump the delegators, the rewards to distribute and the accounting inconsistencies of a
+    /// staking pool contract.
+    #[clap(name = "staking_pool")]
+    StakingPool(StakingPoolCmd),
 }
 
 impl StateViewerSubCommand {
@@ -98,6 +108,9 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::CheckBlock => check_block_chunk_existence(store, near_config),
             StateViewerSubCommand::DumpCode(cmd) => cmd.run(home_dir, near_config, store),
             StateViewerSubCommand::DumpAccountStorage(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::ShardLoad(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::CheckAccounts(cmd) => cmd.run(home_dir, near_config, store),
+            StateViewerSubCommand::StakingPool(cmd) => cmd.run(home_dir, near_config, store),
         }
     }
 }
@@ -240,3 +253,52 @@ impl DumpAccountStorageCmd {
         );
     }
 }
+
+#[derive(Clap)]
+pub struct ShardLoadCmd {
+    /// Comma separated boundary accounts of the proposed shard layout. Shard `i` holds the
+    /// accounts between boundary accounts `i - 1` and `i`.
+    #[clap(long, use_delimiter = true)]
+    boundary_accounts: Vec<AccountId>,
+    /// Number of most recent blocks gas and receipts are measured over.
+    #[clap(long, default_value = "1000")]
+    num_blocks: u64,
+}
+
+impl ShardLoadCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Arc<Store>) {
+        shard_load(self.boundary_accounts, self.num_blocks, home_dir, near_config, store);
+    }
+}
+
+#[derive(Clap)]
+pub struct CheckAccountsCmd {
+    /// Manifest given to `batch-accounts`.
+    #[clap(long, parse(from_os_str))]
+    manifest: PathBuf,
+    /// Keys directory given to `batch-accounts`, to also check the generated keys.
+    #[clap(long, parse(from_os_str))]
+    keys_dir: Option<PathBuf>,
+}
+
+impl CheckAccountsCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Arc<Store>) {
+        check_accounts(&self.manifest, self.keys_dir.as_deref(), home_dir, near_config, store);
+    }
+}
+
+#[derive(Clap)]
+pub struct StakingPoolCmd {
+    /// Account of the staking pool contract.
+    #[clap(long)]
+    account_id: AccountId,
+    /// Height to read the state at, by default the latest one.
+    #[clap(long)]
+    height: Option<BlockHeight>,
+}
+
+impl StakingPoolCmd {
+    pub fn run(self, home_dir: &Path, near_config: NearConfig, store: Arc<Store>) {
+        staking_pool(self.account_id, self.height, home_dir, near_config, store);
+    }
+}
@@ -11,22 +11,26 @@ use near_chain::chain::collect_receipts_from_response;
 use near_chain::migrations::check_if_block_is_first_with_chunk_of_version;
 use near_chain::types::{ApplyTransactionResult, BlockHeaderInfo};
 use near_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate, RuntimeAdapter};
+use near_crypto::{KeyFile, PublicKey};
 use near_epoch_manager::EpochManager;
 use near_network::iter_peers_from_store;
+use near_primitives::account::AccessKeyPermission;
 use near_primitives::block::BlockHeader;
 use near_primitives::hash::CryptoHash;
 use near_primitives::serialize::to_base;
-use near_primitives::shard_layout::ShardUId;
+use near_primitives::shard_layout::{ShardLayout, ShardUId};
 use near_primitives::state_record::StateRecord;
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::chunk_extra::ChunkExtra;
-use near_primitives::types::{BlockHeight, ShardId, StateRoot};
+use near_primitives::types::{AccountId, Balance, BlockHeight, ShardId, StateRoot};
 use near_store::test_utils::create_test_store;
 use near_store::{Store, TrieIterator};
 use nearcore::{NearConfig, NightshadeRuntime};
 use node_runtime::adapter::ViewRuntimeAdapter;
 
 use crate::apply_chain_range::apply_chain_range;
+use crate::shard_load::ShardLoadReport;
+use crate::staking_pool::StakingPoolReport;
 use crate::state_dump::state_dump;
 
 pub(crate) fn peers(store: Arc<Store>) {
@@ -463,6 +467,206 @@ pub(crate) fn view_chain(
     }
 }
 
+/// Prints the load of the shards of the current shard layout and of the layout with the given
+/// boundary accounts, from the latest state and the last `num_blocks` blocks.
+pub(crate) fn shard_load(
+    boundary_accounts: Vec<AccountId>,
+    num_blocks: u64,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Arc<Store>,
+) {
+    let (runtime, state_roots, header) = load_trie(store.clone(), home_dir, &near_config);
+    let current_layout = runtime.get_shard_layout(header.epoch_id()).unwrap();
+    let mut reports = vec![
+        ("current", ShardLoadReport::new(current_layout)),
+        ("proposed", ShardLoadReport::new(ShardLayout::v1(vec![], boundary_accounts, None, 1))),
+    ];
+
+    for (shard_id, state_root) in state_roots.iter().enumerate() {
+        let trie = runtime.get_trie_for_shard(shard_id as u64, header.prev_hash()).unwrap();
+        for item in TrieIterator::new(&trie, state_root).unwrap() {
+            let (key, value) = item.unwrap();
+            for (_, report) in reports.iter_mut() {
+                report.record_state_item(&key, &value);
+            }
+        }
+    }
+
+    let mut chain_store = ChainStore::new(store, near_config.genesis.config.genesis_height);
+    let mut block_hash = *header.hash();
+    for _ in 0..num_blocks {
+        let block = match chain_store.get_block(&block_hash) {
+            Ok(block) => block.clone(),
+            Err(_) => break,
+        };
+        for (_, report) in reports.iter_mut() {
+            report.record_block();
+        }
+        for chunk_header in block.chunks().iter() {
+            if chunk_header.height_included() != block.header().height() {
+                continue;
+            }
+            let shard_id = chunk_header.shard_id();
+            let outcome_ids =
+                chain_store.get_outcomes_by_block_hash_and_shard_id(&block_hash, shard_id).unwrap();
+            for outcome_id in outcome_ids {
+                for outcome in chain_store.get_outcomes_by_id(&outcome_id).unwrap() {
+                    if outcome.block_hash != block_hash {
+                        continue;
+                    }
+                    let outcome = &outcome.outcome_with_id.outcome;
+                    for (_, report) in reports.iter_mut() {
+                        report.record_outcome(&outcome.executor_id, outcome.gas_burnt);
+                    }
+                }
+            }
+            if let Ok(receipts) = chain_store.get_outgoing_receipts(&block_hash, shard_id) {
+                for receipt in receipts.iter() {
+                    for (_, report) in reports.iter_mut() {
+                        report.record_receipt(receipt);
+                    }
+                }
+            }
+        }
+        if block.header().height() == near_config.genesis.config.genesis_height {
+            break;
+        }
+        block_hash = *block.header().prev_hash();
+    }
+
+    println!("Shard load at height {}", header.height());
+    for (name, report) in reports {
+        println!("\n{} shard layout:\n{}", name, report);
+    }
+}
+
+/// Account expected by a `batch-accounts` manifest.
+struct ManifestAccount {
+    account_id: AccountId,
+    balance: Balance,
+    public_key: Option<PublicKey>,
+}
+
+fn read_accounts_manifest(path: &Path, keys_dir: Option<&Path>) -> Vec<ManifestAccount> {
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&fs::read(path).unwrap()).expect("Failed to parse the manifest");
+    let accounts = manifest["accounts"].as_array().expect("The manifest lists no accounts");
+    accounts
+        .iter()
+        .map(|account| {
+            let account_id: AccountId = account["account_id"].as_str().unwrap().parse().unwrap();
+            let balance = account["balance"].as_str().unwrap().parse().unwrap();
+            let public_key = match account["public_key"].as_str() {
+                Some(public_key) => Some(public_key.parse().unwrap()),
+                None => keys_dir
+                    .map(|keys_dir| keys_dir.join(format!("{}.json", account_id)))
+                    .filter(|path| path.exists())
+                    .map(|path| KeyFile::from_file(&path).public_key),
+            };
+            ManifestAccount { account_id, balance, public_key }
+        })
+        .collect()
+}
+
+/// Checks that the accounts of a `batch-accounts` manifest exist in the latest state with their
+/// full access key and at least their initial balance. Exits with an error if some don't.
+pub(crate) fn check_accounts(
+    manifest: &Path,
+    keys_dir: Option<&Path>,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Arc<Store>,
+) {
+    let accounts = read_accounts_manifest(manifest, keys_dir);
+    let (runtime, state_roots, header) = load_trie(store, home_dir, &near_config);
+    let epoch_id = header.epoch_id();
+    let (mut missing, mut without_key, mut below_balance) = (0, 0, 0);
+    for account in &accounts {
+        let shard_id = runtime.account_id_to_shard_id(&account.account_id, epoch_id).unwrap();
+        let shard_uid = runtime.shard_id_to_uid(shard_id, epoch_id).unwrap();
+        let state_root = state_roots[shard_id as usize];
+        let state = match runtime.view_account(&shard_uid, state_root, &account.account_id) {
+            Ok(state) => state,
+            Err(_) => {
+                println!("{}: missing", account.account_id);
+                missing += 1;
+                continue;
+            }
+        };
+        if let Some(public_key) = &account.public_key {
+            match runtime.view_access_key(&shard_uid, state_root, &account.account_id, public_key) {
+                Ok(access_key) if access_key.permission == AccessKeyPermission::FullAccess => {}
+                _ => {
+                    println!("{}: no full access key {}", account.account_id, public_key);
+                    without_key += 1;
+                }
+            }
+        }
+        if state.amount() + state.locked() < account.balance {
+            println!(
+                "{}: balance {} below {}",
+                account.account_id,
+                state.amount() + state.locked(),
+                account.balance
+            );
+            below_balance += 1;
+        }
+    }
+    println!(
+        "Checked {} accounts at height {}: {} missing, {} without their key, {} below their balance",
+        accounts.len(),
+        header.height(),
+        missing,
+        without_key,
+        below_balance
+    );
+    if missing + without_key > 0 {
+        std::process::exit(1);
+    }
+}
+
+pub(crate) fn staking_pool(
+    account_id: AccountId,
+    height: Option<BlockHeight>,
+    home_dir: &Path,
+    near_config: NearConfig,
+    store: Arc<Store>,
+) {
+    let mode = height.map_or(LoadTrieMode::Latest, LoadTrieMode::Height);
+    let (runtime, state_roots, header) =
+        load_trie_stop_at_height(store, home_dir, &near_config, mode);
+    let epoch_id = header.epoch_id();
+    let shard_id = runtime.account_id_to_shard_id(&account_id, epoch_id).unwrap();
+    let shard_uid = runtime.shard_id_to_uid(shard_id, epoch_id).unwrap();
+    let state_root = state_roots[shard_id as usize];
+    let account = runtime
+        .view_account(&shard_uid, state_root, &account_id)
+        .unwrap_or_else(|err| panic!("Failed to read account {}: {}", account_id, err));
+    let trie = runtime.get_trie_for_shard(shard_id, header.prev_hash()).unwrap();
+    let get = |key: &[u8]| {
+        let key = TrieKey::ContractData { account_id: account_id.clone(), key: key.to_vec() };
+        trie.get(&state_root, &key.to_vec()).unwrap()
+    };
+    println!("Staking pool {} at height {}", account_id, header.height());
+    match StakingPoolReport::read(get, account.amount(), account.locked()) {
+        Ok(Some(report)) => {
+            print!("{}", report);
+            if !report.inconsistencies.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Ok(None) => {
+            println!("{} has no contract state", account_id);
+            std::process::exit(1);
+        }
+        Err(err) => {
+            println!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
 pub(crate) fn check_block_chunk_existence(store: Arc<Store>, near_config: NearConfig) {
     let genesis_height = near_config.genesis.config.genesis_height;
     let mut chain_store = ChainStore::new(store.clone(), genesis_height);
@@ -2,6 +2,8 @@
 
 mod apply_chain_range;
 mod commands;
+mod shard_load;
+mod staking_pool;
 mod state_dump;
 
 pub mod cli;
@@ -1,6 +1,8 @@
 mod apply_chain_range;
 mod cli;
 mod commands;
+mod shard_load;
+mod staking_pool;
 mod state_dump;
 
 fn main() {
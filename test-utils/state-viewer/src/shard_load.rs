@@ -0,0 +1,183 @@
+//! Forecast of the load of the shards of a hypothetical shard layout.
+//!
+//! Accounts and their state are attributed to shards from the latest state, gas and receipts
+//! from the outcomes and the outgoing receipts of recent blocks. Local receipts, executed in the
+//! chunk of the transaction that created them, are not stored and are not counted.
+
+use std::fmt;
+
+use near_primitives::receipt::Receipt;
+use near_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
+use near_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
+use near_primitives::trie_key::TrieKey;
+use near_primitives::types::{AccountId, Gas};
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub(crate) struct ShardLoad {
+    pub accounts: u64,
+    /// Size of the keys and values of the state of the accounts of the shard.
+    pub state_bytes: u64,
+    pub gas_burnt: Gas,
+    /// Receipts sent to other shards.
+    pub outgoing_cross_shard_receipts: u64,
+    /// Receipts received from other shards.
+    pub incoming_cross_shard_receipts: u64,
+    /// Receipts sent and received within the shard.
+    pub intra_shard_receipts: u64,
+}
+
+pub(crate) struct ShardLoadReport {
+    shard_layout: ShardLayout,
+    shards: Vec<ShardLoad>,
+    /// Size of the state not belonging to any account, such as delayed receipts.
+    unattributed_state_bytes: u64,
+    num_blocks: u64,
+}
+
+impl ShardLoadReport {
+    pub fn new(shard_layout: ShardLayout) -> Self {
+        let shards = vec![ShardLoad::default(); shard_layout.num_shards() as usize];
+        Self { shard_layout, shards, unattributed_state_bytes: 0, num_blocks: 0 }
+    }
+
+    fn shard(&mut self, account_id: &AccountId) -> &mut ShardLoad {
+        let shard_id = account_id_to_shard_id(account_id, &self.shard_layout);
+        &mut self.shards[shard_id as usize]
+    }
+
+    pub fn record_state_item(&mut self, key: &[u8], value: &[u8]) {
+        let size = (key.len() + value.len()) as u64;
+        match parse_account_id_from_raw_key(key) {
+            Ok(Some(account_id)) => {
+                let is_account =
+                    TrieKey::Account { account_id: account_id.clone() }.to_vec() == key;
+                let shard = self.shard(&account_id);
+                shard.state_bytes += size;
+                if is_account {
+                    shard.accounts += 1;
+                }
+            }
+            _ => self.unattributed_state_bytes += size,
+        }
+    }
+
+    pub fn record_block(&mut self) {
+        self.num_blocks += 1;
+    }
+
+    pub fn record_outcome(&mut self, executor_id: &AccountId, gas_burnt: Gas) {
+        self.shard(executor_id).gas_burnt += gas_burnt;
+    }
+
+    pub fn record_receipt(&mut self, receipt: &Receipt) {
+        let from = account_id_to_shard_id(&receipt.predecessor_id, &self.shard_layout);
+        let to = account_id_to_shard_id(&receipt.receiver_id, &self.shard_layout);
+        if from == to {
+            self.shards[from as usize].intra_shard_receipts += 1;
+        } else {
+            self.shards[from as usize].outgoing_cross_shard_receipts += 1;
+            self.shards[to as usize].incoming_cross_shard_receipts += 1;
+        }
+    }
+
+    #[cfg(test)]
+    fn shards(&self) -> &[ShardLoad] {
+        &self.shards
+    }
+}
+
+impl fmt::Display for ShardLoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let num_blocks = self.num_blocks.max(1);
+        writeln!(
+            f,
+            "{:>5} {:>12} {:>16} {:>18} {:>16} {:>16} {:>16}",
+            "shard",
+            "accounts",
+            "state bytes",
+            "Tgas per block",
+            "out x-shard/blk",
+            "in x-shard/blk",
+            "intra/blk"
+        )?;
+        for (shard_id, shard) in self.shards.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>5} {:>12} {:>16} {:>18.3} {:>16.2} {:>16.2} {:>16.2}",
+                shard_id,
+                shard.accounts,
+                shard.state_bytes,
+                shard.gas_burnt as f64 / 1e12 / num_blocks as f64,
+                shard.outgoing_cross_shard_receipts as f64 / num_blocks as f64,
+                shard.incoming_cross_shard_receipts as f64 / num_blocks as f64,
+                shard.intra_shard_receipts as f64 / num_blocks as f64,
+            )?;
+        }
+        write!(
+            f,
+            "{} state bytes not attributed to an account, gas and receipts over {} blocks",
+            self.unattributed_state_bytes, self.num_blocks
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives::receipt::{ActionReceipt, ReceiptEnum};
+    use near_primitives::types::AccountId;
+
+    fn account(account_id: &str) -> AccountId {
+        account_id.parse().unwrap()
+    }
+
+    fn receipt(predecessor_id: &str, receiver_id: &str) -> Receipt {
+        Receipt {
+            predecessor_id: account(predecessor_id),
+            receiver_id: account(receiver_id),
+            receipt_id: Default::default(),
+            receipt: ReceiptEnum::Action(ActionReceipt {
+                signer_id: account(predecessor_id),
+                signer_public_key: PublicKey::empty(KeyType::ED25519),
+                gas_price: 0,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions: vec![],
+            }),
+        }
+    }
+
+    #[test]
+    fn test_shard_load_report() {
+        let layout = ShardLayout::v1(vec![], vec![account("m")], None, 1);
+        let mut report = ShardLoadReport::new(layout);
+        let alice = TrieKey::Account { account_id: account("alice") }.to_vec();
+        let alice_key = TrieKey::AccessKey {
+            account_id: account("alice"),
+            public_key: PublicKey::empty(KeyType::ED25519),
+        }
+        .to_vec();
+        let zoe = TrieKey::Account { account_id: account("zoe") }.to_vec();
+        report.record_state_item(&alice, &[0; 10]);
+        report.record_state_item(&alice_key, &[0; 10]);
+        report.record_state_item(&zoe, &[0; 10]);
+        report.record_state_item(&TrieKey::DelayedReceiptIndices.to_vec(), &[0; 10]);
+        report.record_outcome(&account("zoe"), 1000);
+        report.record_receipt(&receipt("alice", "zoe"));
+        report.record_receipt(&receipt("zoe", "zoe"));
+
+        let shards = report.shards();
+        assert_eq!(shards[0].accounts, 1);
+        assert_eq!(shards[0].state_bytes, (alice.len() + alice_key.len() + 20) as u64);
+        assert_eq!(shards[0].outgoing_cross_shard_receipts, 1);
+        assert_eq!(shards[1].accounts, 1);
+        assert_eq!(shards[1].gas_burnt, 1000);
+        assert_eq!(shards[1].incoming_cross_shard_receipts, 1);
+        assert_eq!(shards[1].intra_shard_receipts, 1);
+        assert_eq!(
+            report.unattributed_state_bytes,
+            TrieKey::DelayedReceiptIndices.len() as u64 + 10
+        );
+    }
+}
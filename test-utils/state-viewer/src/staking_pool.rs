@@ -0,0 +1,316 @@
+//! Reads the state of a staking pool contract directly from the trie.
+//!
+//! The layout is the one of the reference staking pool contract of `near/core-contracts` built
+//! with `near-sdk` 2 and 3: the contract struct is borsh serialized under the `STATE` key, and
+//! its delegators are an `UnorderedMap` with the prefix `u`, that is a vector of the account ids
+//! under `uk` followed by the borsh serialized index, a vector of the accounts under `uv`, and
+//! the index of every account id under `ui` followed by the borsh serialized account id.
+//!
+//! The shares, balances and rewards are recomputed the way the contract does, so that the
+//! discrepancies between its accounting and the balance of the pool account can be found
+//! without running the contract.
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_primitives::types::{Balance, EpochHeight};
+use primitive_types::U256;
+
+const STATE_KEY: &[u8] = b"STATE";
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub(crate) struct RewardFeeFraction {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+/// `near_sdk::collections::Vector`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub(crate) struct Vector {
+    pub len: u64,
+    pub prefix: Vec<u8>,
+}
+
+impl Vector {
+    fn element_key(&self, index: u64) -> Vec<u8> {
+        [&self.prefix[..], &index.to_le_bytes()].concat()
+    }
+}
+
+/// `near_sdk::collections::UnorderedMap`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub(crate) struct UnorderedMap {
+    pub key_index_prefix: Vec<u8>,
+    pub keys: Vector,
+    pub values: Vector,
+}
+
+/// `StakingContract` of the staking pool.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub(crate) struct StakingPoolState {
+    pub owner_id: String,
+    /// `Base58PublicKey`, the curve type byte followed by the key.
+    pub stake_public_key: Vec<u8>,
+    pub last_epoch_height: EpochHeight,
+    pub last_total_balance: Balance,
+    pub total_staked_balance: Balance,
+    pub total_stake_shares: u128,
+    pub reward_fee_fraction: RewardFeeFraction,
+    pub accounts: UnorderedMap,
+    pub paused: bool,
+}
+
+impl StakingPoolState {
+    /// Balance staked by `stake_shares`, rounded down like the contract does.
+    pub fn staked_amount(&self, stake_shares: u128) -> Balance {
+        if self.total_stake_shares == 0 {
+            return 0;
+        }
+        (U256::from(self.total_staked_balance) * U256::from(stake_shares)
+            / U256::from(self.total_stake_shares))
+        .as_u128()
+    }
+}
+
+/// `Account` of a delegator.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub(crate) struct Delegator {
+    pub unstaked: Balance,
+    pub stake_shares: u128,
+    pub unstaked_available_epoch_height: EpochHeight,
+}
+
+pub(crate) struct StakingPoolReport {
+    pub state: StakingPoolState,
+    pub delegators: Vec<(String, Delegator)>,
+    /// Balance of the pool account, liquid and locked, at the block the state is read at.
+    pub account_balance: Balance,
+    pub account_locked: Balance,
+    pub inconsistencies: Vec<String>,
+}
+
+impl StakingPoolReport {
+    /// Reads the state of the pool from `get`, which returns the value of a key of the storage of
+    /// the contract. `None` if the contract has no `STATE`.
+    pub fn read(
+        get: impl Fn(&[u8]) -> Option<Vec<u8>>,
+        account_balance: Balance,
+        account_locked: Balance,
+    ) -> Result<Option<Self>, String> {
+        let state = match get(STATE_KEY) {
+            Some(state) => StakingPoolState::try_from_slice(&state).map_err(|err| {
+                format!("The state isn't the one of a staking pool contract: {}", err)
+            })?,
+            None => return Ok(None),
+        };
+        let accounts = &state.accounts;
+        let mut inconsistencies = vec![];
+        if accounts.keys.len != accounts.values.len {
+            inconsistencies.push(format!(
+                "{} delegator account ids but {} delegator accounts",
+                accounts.keys.len, accounts.values.len
+            ));
+        }
+        let mut delegators = vec![];
+        for index in 0..accounts.keys.len.min(accounts.values.len) {
+            let (account_id, delegator) = match (
+                get(&accounts.keys.element_key(index)),
+                get(&accounts.values.element_key(index)),
+            ) {
+                (Some(account_id), Some(delegator)) => (account_id, delegator),
+                _ => {
+                    inconsistencies.push(format!("Delegator {} is missing", index));
+                    continue;
+                }
+            };
+            let account_id = String::try_from_slice(&account_id)
+                .map_err(|err| format!("Invalid account id of delegator {}: {}", index, err))?;
+            let delegator = Delegator::try_from_slice(&delegator)
+                .map_err(|err| format!("Invalid account of delegator {}: {}", index, err))?;
+            let index_key =
+                [&accounts.key_index_prefix[..], &account_id.try_to_vec().unwrap()].concat();
+            match get(&index_key).map(|value| u64::try_from_slice(&value)) {
+                Some(Ok(stored_index)) if stored_index == index => {}
+                stored_index => inconsistencies.push(format!(
+                    "The index of {} is {:?}, expected {}",
+                    account_id, stored_index, index
+                )),
+            }
+            delegators.push((account_id, delegator));
+        }
+
+        let total_stake_shares: u128 =
+            delegators.iter().map(|(_, delegator)| delegator.stake_shares).sum();
+        if total_stake_shares != state.total_stake_shares {
+            inconsistencies.push(format!(
+                "The delegators have {} stake shares, the pool {}",
+                total_stake_shares, state.total_stake_shares
+            ));
+        }
+        let staked: Balance = delegators
+            .iter()
+            .map(|(_, delegator)| state.staked_amount(delegator.stake_shares))
+            .sum();
+        // Every delegator loses less than a yoctoNEAR to the rounding.
+        if staked > state.total_staked_balance
+            || state.total_staked_balance - staked > delegators.len() as Balance
+        {
+            inconsistencies.push(format!(
+                "The delegators have {} staked, the pool {}",
+                staked, state.total_staked_balance
+            ));
+        }
+        let unstaked: Balance = delegators.iter().map(|(_, delegator)| delegator.unstaked).sum();
+        if staked + unstaked > account_balance + account_locked {
+            inconsistencies.push(format!(
+                "The delegators have {} staked and unstaked, more than the {} of the pool account",
+                staked + unstaked,
+                account_balance + account_locked
+            ));
+        }
+        if !state.paused && account_locked < state.total_staked_balance {
+            inconsistencies.push(format!(
+                "The pool account has {} locked, less than the {} staked",
+                account_locked, state.total_staked_balance
+            ));
+        }
+        Ok(Some(Self { state, delegators, account_balance, account_locked, inconsistencies }))
+    }
+
+    /// Rewards the pool received since the last distribution, to be distributed at the next
+    /// `ping`, and the fee of the owner in it.
+    pub fn pending_rewards(&self) -> (Balance, Balance) {
+        let total_balance = self.account_balance + self.account_locked;
+        let rewards = total_balance.saturating_sub(self.state.last_total_balance);
+        let fee = &self.state.reward_fee_fraction;
+        let owner_fee = if fee.denominator == 0 {
+            0
+        } else {
+            (U256::from(fee.numerator) * U256::from(rewards) / U256::from(fee.denominator))
+                .as_u128()
+        };
+        (rewards, owner_fee)
+    }
+}
+
+impl fmt::Display for StakingPoolReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = &self.state;
+        writeln!(f, "owner: {}", state.owner_id)?;
+        writeln!(f, "paused: {}", state.paused)?;
+        writeln!(
+            f,
+            "reward fee: {}/{}",
+            state.reward_fee_fraction.numerator, state.reward_fee_fraction.denominator
+        )?;
+        writeln!(f, "last epoch height: {}", state.last_epoch_height)?;
+        writeln!(f, "total staked balance: {}", state.total_staked_balance)?;
+        writeln!(f, "total stake shares: {}", state.total_stake_shares)?;
+        writeln!(
+            f,
+            "account balance: {} liquid, {} locked",
+            self.account_balance, self.account_locked
+        )?;
+        let (rewards, owner_fee) = self.pending_rewards();
+        writeln!(
+            f,
+            "last total balance: {}, {} of rewards to distribute of which {} for the owner",
+            state.last_total_balance, rewards, owner_fee
+        )?;
+        writeln!(f, "{} delegators:", self.delegators.len())?;
+        for (account_id, delegator) in &self.delegators {
+            writeln!(
+                f,
+                "  {}: {} staked ({} shares), {} unstaked available at epoch {}",
+                account_id,
+                state.staked_amount(delegator.stake_shares),
+                delegator.stake_shares,
+                delegator.unstaked,
+                delegator.unstaked_available_epoch_height
+            )?;
+        }
+        if self.inconsistencies.is_empty() {
+            writeln!(f, "No inconsistencies")
+        } else {
+            writeln!(f, "{} inconsistencies:", self.inconsistencies.len())?;
+            for inconsistency in &self.inconsistencies {
+                writeln!(f, "  {}", inconsistency)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn storage(
+        delegators: &[(&str, Delegator)],
+        total_stake_shares: u128,
+    ) -> HashMap<Vec<u8>, Vec<u8>> {
+        let accounts = UnorderedMap {
+            key_index_prefix: b"ui".to_vec(),
+            keys: Vector { len: delegators.len() as u64, prefix: b"uk".to_vec() },
+            values: Vector { len: delegators.len() as u64, prefix: b"uv".to_vec() },
+        };
+        let mut storage = HashMap::new();
+        for (index, (account_id, delegator)) in delegators.iter().enumerate() {
+            let account_id = account_id.to_string().try_to_vec().unwrap();
+            storage.insert(accounts.keys.element_key(index as u64), account_id.clone());
+            storage
+                .insert(accounts.values.element_key(index as u64), delegator.try_to_vec().unwrap());
+            storage
+                .insert([&b"ui"[..], &account_id].concat(), (index as u64).try_to_vec().unwrap());
+        }
+        let state = StakingPoolState {
+            owner_id: "owner.near".to_string(),
+            stake_public_key: vec![0; 33],
+            last_epoch_height: 10,
+            last_total_balance: 3_000,
+            total_staked_balance: 2_000,
+            total_stake_shares,
+            reward_fee_fraction: RewardFeeFraction { numerator: 10, denominator: 100 },
+            accounts,
+            paused: false,
+        };
+        storage.insert(STATE_KEY.to_vec(), state.try_to_vec().unwrap());
+        storage
+    }
+
+    #[test]
+    fn test_read_staking_pool() {
+        let delegators = [
+            (
+                "alice.near",
+                Delegator {
+                    unstaked: 500,
+                    stake_shares: 1_000,
+                    unstaked_available_epoch_height: 12,
+                },
+            ),
+            (
+                "bob.near",
+                Delegator { unstaked: 0, stake_shares: 500, unstaked_available_epoch_height: 0 },
+            ),
+        ];
+        let storage = storage(&delegators, 1_500);
+        let get = |key: &[u8]| storage.get(key).cloned();
+        let report = StakingPoolReport::read(get, 1_100, 2_000).unwrap().unwrap();
+        assert_eq!(report.delegators.len(), 2);
+        assert_eq!(report.state.staked_amount(report.delegators[0].1.stake_shares), 1_333);
+        assert!(report.inconsistencies.is_empty(), "{:?}", report.inconsistencies);
+        assert_eq!(report.pending_rewards(), (100, 10));
+
+        // The shares of the delegators don't add up, and the pool account lost its stake.
+        let storage = self::storage(&delegators, 2_000);
+        let get = |key: &[u8]| storage.get(key).cloned();
+        let report = StakingPoolReport::read(get, 3_100, 0).unwrap().unwrap();
+        assert_eq!(report.inconsistencies.len(), 3, "{:?}", report.inconsistencies);
+
+        assert!(StakingPoolReport::read(|_| None, 0, 0).unwrap().is_none());
+        assert!(StakingPoolReport::read(|_| Some(vec![1, 2, 3]), 0, 0).is_err());
+    }
+}
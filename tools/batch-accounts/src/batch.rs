@@ -0,0 +1,243 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+
+use near_crypto::{PublicKey, Signer};
+use near_primitives::account::AccessKey;
+use near_primitives::hash::CryptoHash;
+use near_primitives::transaction::{
+    Action, AddKeyAction, CreateAccountAction, SignedTransaction, TransferAction,
+};
+use near_primitives::types::{AccountId, Nonce};
+
+// TODO(1905): Move out RPC interface for transacting into separate production crate.
+use integration_tests::user::User;
+
+use crate::manifest::{Manifest, ManifestAccount, Progress};
+
+/// How often the accounts of a batch are checked while waiting for them to be created.
+const POLL_PERIOD: Duration = Duration::from_secs(1);
+
+pub struct BatchConfig {
+    /// Number of transactions sent before waiting for their accounts to be created.
+    pub batch_size: usize,
+    /// How long to wait for the accounts of a batch before moving on to the next one.
+    pub batch_timeout: Duration,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    /// Accounts created by this run, or by an interrupted run which didn't record them.
+    pub created: usize,
+    /// Accounts recorded as created by a previous run.
+    pub already_created: usize,
+    /// Accounts which exist without the key of the manifest, so weren't created from it.
+    pub conflicting: Vec<AccountId>,
+    /// Accounts not created in time, to retry by running again.
+    pub pending: Vec<AccountId>,
+}
+
+/// Creates a sub-account of `signer`, funded and with a full access key.
+pub fn create_account_transaction(
+    signer: &dyn Signer,
+    signer_id: &AccountId,
+    nonce: Nonce,
+    block_hash: CryptoHash,
+    account: &ManifestAccount,
+    public_key: PublicKey,
+) -> SignedTransaction {
+    SignedTransaction::from_actions(
+        nonce,
+        signer_id.clone(),
+        account.account_id.clone(),
+        signer,
+        vec![
+            Action::CreateAccount(CreateAccountAction {}),
+            Action::Transfer(TransferAction { deposit: account.balance }),
+            Action::AddKey(AddKeyAction { public_key, access_key: AccessKey::full_access() }),
+        ],
+        block_hash,
+    )
+}
+
+/// Creates the accounts of the manifest not recorded in `progress` yet, in batches, signed by
+/// the signer of `user`. The progress is saved to `progress_path` after every batch.
+pub fn create_accounts(
+    user: &dyn User,
+    signer_id: &AccountId,
+    manifest: &Manifest,
+    keys_dir: &Path,
+    progress: &mut Progress,
+    progress_path: &Path,
+    config: &BatchConfig,
+) -> Result<Summary, String> {
+    manifest.check_parent(signer_id)?;
+    let mut summary = Summary::default();
+    let remaining: Vec<&ManifestAccount> = manifest
+        .accounts
+        .iter()
+        .filter(|account| !progress.created.contains(&account.account_id))
+        .collect();
+    summary.already_created = manifest.accounts.len() - remaining.len();
+    let signer = user.signer();
+
+    for batch in remaining.chunks(config.batch_size.max(1)) {
+        let mut to_create = vec![];
+        for &account in batch {
+            let public_key = account.public_key(keys_dir);
+            // An interrupted run may have created the account without recording it.
+            if user.get_access_key(&account.account_id, &public_key).is_ok() {
+                progress.created.insert(account.account_id.clone());
+                summary.created += 1;
+            } else if user.view_account(&account.account_id).is_ok() {
+                warn!(
+                    "Account {} already exists without the key of the manifest, skipping it",
+                    account.account_id
+                );
+                summary.conflicting.push(account.account_id.clone());
+            } else {
+                to_create.push((account, public_key));
+            }
+        }
+
+        let mut nonce = user.get_access_key_nonce_for_signer(signer_id)?;
+        let block_hash = user.get_best_block_hash().ok_or("Failed to get the latest block")?;
+        for (account, public_key) in &to_create {
+            nonce += 1;
+            let transaction = create_account_transaction(
+                &*signer,
+                signer_id,
+                nonce,
+                block_hash,
+                account,
+                public_key.clone(),
+            );
+            if let Err(err) = user.add_transaction(transaction) {
+                warn!("Failed to send the transaction creating {}: {}", account.account_id, err);
+            }
+        }
+
+        let started = Instant::now();
+        loop {
+            to_create.retain(|(account, public_key)| {
+                if user.get_access_key(&account.account_id, public_key).is_ok() {
+                    progress.created.insert(account.account_id.clone());
+                    summary.created += 1;
+                    false
+                } else {
+                    true
+                }
+            });
+            if to_create.is_empty() || started.elapsed() >= config.batch_timeout {
+                break;
+            }
+            std::thread::sleep(POLL_PERIOD);
+        }
+        summary
+            .pending
+            .extend(to_create.into_iter().map(|(account, _)| account.account_id.clone()));
+        progress.save(progress_path).map_err(|err| err.to_string())?;
+        info!(
+            "Created {} of {} accounts, {} pending",
+            progress.created.len(),
+            manifest.accounts.len(),
+            summary.pending.len()
+        );
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use integration_tests::node::{Node, RuntimeNode};
+    use near_primitives::types::Balance;
+
+    const NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+    fn manifest(account_ids: &[&str]) -> Manifest {
+        Manifest {
+            accounts: account_ids
+                .iter()
+                .map(|account_id| ManifestAccount {
+                    account_id: account_id.parse().unwrap(),
+                    balance: 10 * NEAR,
+                    public_key: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_create_accounts() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        let node = RuntimeNode::new(&alice);
+        let user = node.user();
+        let dir = tempfile::tempdir().unwrap();
+        let progress_path = dir.path().join("progress.json");
+        let config = BatchConfig { batch_size: 2, batch_timeout: Duration::from_secs(10) };
+        let manifest = manifest(&["a.alice.near", "b.alice.near", "c.alice.near"]);
+
+        let mut progress = Progress::default();
+        let summary = create_accounts(
+            &*user,
+            &alice,
+            &manifest,
+            dir.path(),
+            &mut progress,
+            &progress_path,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(summary.created, 3);
+        assert!(summary.pending.is_empty());
+        for account in &manifest.accounts {
+            assert_eq!(user.view_balance(&account.account_id).unwrap(), 10 * NEAR);
+            assert!(user
+                .get_access_key(&account.account_id, &account.public_key(dir.path()))
+                .is_ok());
+        }
+        assert_eq!(Progress::from_file(&progress_path).unwrap(), progress);
+
+        // Accounts created but not recorded by an interrupted run are not created again.
+        let nonce = user.get_access_key_nonce_for_signer(&alice).unwrap();
+        let mut progress = Progress::default();
+        let summary = create_accounts(
+            &*user,
+            &alice,
+            &manifest,
+            dir.path(),
+            &mut progress,
+            &progress_path,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(summary.created, 3);
+        assert_eq!(user.get_access_key_nonce_for_signer(&alice).unwrap(), nonce);
+
+        // Recorded accounts are skipped, accounts created otherwise are reported.
+        let manifest = self::manifest(&["a.alice.near", "d.alice.near"]);
+        let d: AccountId = "d.alice.near".parse().unwrap();
+        user.create_account(alice.clone(), d.clone(), user.signer().public_key(), NEAR).unwrap();
+        let summary = create_accounts(
+            &*user,
+            &alice,
+            &manifest,
+            dir.path(),
+            &mut progress,
+            &progress_path,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(summary.already_created, 1);
+        assert_eq!(summary.conflicting, vec![d]);
+    }
+
+    #[test]
+    fn test_check_parent() {
+        let alice: AccountId = "alice.near".parse().unwrap();
+        assert!(manifest(&["a.alice.near"]).check_parent(&alice).is_ok());
+        assert!(manifest(&["a.alice.near", "a.b.alice.near"]).check_parent(&alice).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+//! Creates sub-accounts of a parent account in batches, for load testing and onboarding.
+//!
+//! The manifest lists the accounts with the balance transferred to them, in yoctoNEAR, and
+//! optionally their public key:
+//!
+//! ```json
+//! {"accounts": [{"account_id": "user0.parent.near", "balance": "1000000000000000000000000"}]}
+//! ```
+//!
+//! Accounts without a public key get a generated one, written to the keys directory before the
+//! account is created. The accounts created are recorded after every batch, running the tool
+//! again resumes where it stopped. The result can be checked against the database of a node
+//! with the `check_accounts` command of `state-viewer`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::{App, Arg};
+use log::{error, info, warn, LevelFilter};
+
+use near_crypto::InMemorySigner;
+// TODO(1905): Move out RPC interface for transacting into separate production crate.
+use integration_tests::user::rpc_user::RpcUser;
+
+use crate::batch::{create_accounts, BatchConfig};
+use crate::manifest::{Manifest, Progress};
+
+mod batch;
+mod manifest;
+
+const DEFAULT_RPC_URL: &str = "http://localhost:3030";
+const DEFAULT_BATCH_SIZE: &str = "100";
+const DEFAULT_BATCH_TIMEOUT_SEC: &str = "60";
+
+fn main() {
+    env_logger::Builder::new().filter(None, LevelFilter::Info).init();
+    let matches = App::new("Batch accounts")
+        .about(
+            "Creates the sub-accounts listed in a manifest in batches, with generated keys. \
+             Running it again resumes where it stopped.",
+        )
+        .arg(
+            Arg::with_name("manifest")
+                .long("manifest")
+                .required(true)
+                .help("JSON file listing the accounts to create and their balance")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("key-file")
+                .long("key-file")
+                .required(true)
+                .help("Key file of the parent account, which signs and funds the accounts")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("keys-dir")
+                .long("keys-dir")
+                .required(true)
+                .help("Directory where the key files of the accounts are written")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("progress-file")
+                .long("progress-file")
+                .help("File recording the accounts created (default: next to the manifest)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("rpc-url")
+                .long("rpc-url")
+                .default_value(DEFAULT_RPC_URL)
+                .help("Url of RPC of the node to send the transactions to")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("batch-size")
+                .long("batch-size")
+                .default_value(DEFAULT_BATCH_SIZE)
+                .help("Number of transactions sent before waiting for the accounts")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("batch-timeout")
+                .long("batch-timeout")
+                .default_value(DEFAULT_BATCH_TIMEOUT_SEC)
+                .help("How long to wait for the accounts of a batch (in seconds)")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let manifest_path = matches.value_of("manifest").map(Path::new).unwrap();
+    let keys_dir = matches.value_of("keys-dir").map(Path::new).unwrap();
+    let progress_path = matches
+        .value_of("progress-file")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_path.with_extension("progress.json"));
+    let rpc_url = matches.value_of("rpc-url").unwrap();
+    let config = BatchConfig {
+        batch_size: matches
+            .value_of("batch-size")
+            .map(|s| s.parse().expect("Batch size must be a number"))
+            .unwrap(),
+        batch_timeout: matches
+            .value_of("batch-timeout")
+            .map(|s| Duration::from_secs(s.parse().expect("Batch timeout must be a number")))
+            .unwrap(),
+    };
+
+    let manifest = Manifest::from_file(manifest_path).expect("Failed to read the manifest");
+    let mut progress = Progress::from_file(&progress_path).expect("Failed to read the progress");
+    std::fs::create_dir_all(keys_dir).expect("Failed to create the keys directory");
+    let signer = InMemorySigner::from_file(Path::new(matches.value_of("key-file").unwrap()));
+    let signer_id = signer.account_id.clone();
+    let user = RpcUser::new(rpc_url, signer_id.clone(), Arc::new(signer));
+
+    let summary = match create_accounts(
+        &user,
+        &signer_id,
+        &manifest,
+        keys_dir,
+        &mut progress,
+        &progress_path,
+        &config,
+    ) {
+        Ok(summary) => summary,
+        Err(err) => {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    info!(
+        "Created {} accounts, {} created by previous runs",
+        summary.created, summary.already_created
+    );
+    if !summary.conflicting.is_empty() {
+        warn!("{} accounts already existed and were skipped", summary.conflicting.len());
+    }
+    if !summary.pending.is_empty() {
+        error!("{} accounts were not created in time, run again to retry", summary.pending.len());
+        std::process::exit(1);
+    }
+}
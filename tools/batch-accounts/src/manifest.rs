@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use near_crypto::{KeyFile, KeyType, PublicKey, SecretKey};
+use near_primitives::serialize::u128_dec_format;
+use near_primitives::types::{AccountId, Balance};
+
+/// Sub-accounts to create, all of the same parent account.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub accounts: Vec<ManifestAccount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestAccount {
+    pub account_id: AccountId,
+    /// Transferred from the parent account on creation, in yoctoNEAR.
+    #[serde(with = "u128_dec_format")]
+    pub balance: Balance,
+    /// Full access key of the account. Generated, and written to the keys directory, if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<PublicKey>,
+}
+
+impl Manifest {
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        let manifest: Manifest = serde_json::from_slice(&fs::read(path)?)?;
+        let mut account_ids = BTreeSet::new();
+        for account in &manifest.accounts {
+            if !account_ids.insert(&account.account_id) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Account {} is listed twice", account.account_id),
+                ));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Checks that the accounts can all be created by `parent_id`.
+    pub fn check_parent(&self, parent_id: &AccountId) -> Result<(), String> {
+        match self.accounts.iter().find(|account| !account.account_id.is_sub_account_of(parent_id))
+        {
+            Some(account) => Err(format!(
+                "Account {} is not a direct sub-account of {}",
+                account.account_id, parent_id
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ManifestAccount {
+    /// Full access key of the account. A generated key is written to `keys_dir` before the
+    /// account is created, and read back when creation is resumed.
+    pub fn public_key(&self, keys_dir: &Path) -> PublicKey {
+        if let Some(public_key) = &self.public_key {
+            return public_key.clone();
+        }
+        let path = key_file_path(keys_dir, &self.account_id);
+        if path.exists() {
+            return KeyFile::from_file(&path).public_key;
+        }
+        let secret_key = SecretKey::from_random(KeyType::ED25519);
+        let key_file = KeyFile {
+            account_id: self.account_id.clone(),
+            public_key: secret_key.public_key(),
+            secret_key,
+        };
+        key_file.write_to_file(&path);
+        key_file.public_key
+    }
+}
+
+pub fn key_file_path(keys_dir: &Path, account_id: &AccountId) -> PathBuf {
+    keys_dir.join(format!("{}.json", account_id))
+}
+
+/// Accounts of the manifest already created, saved after every batch so that an interrupted run
+/// resumes where it stopped.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Progress {
+    pub created: BTreeSet<AccountId>,
+}
+
+impl Progress {
+    /// Empty if the file doesn't exist yet.
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Written to a temporary file first, so that the progress is never lost half-written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(tmp_path, path)
+    }
+}
@@ -272,6 +272,7 @@ fn main() {
                 home_dir,
                 sync_mode: near_indexer::SyncModeEnum::FromInterruption,
                 await_for_node_synced: near_indexer::AwaitForNodeSyncedEnum::WaitForFullSync,
+                max_buffered_blocks: 100,
             };
             let system = actix::System::new();
             system.block_on(async move {